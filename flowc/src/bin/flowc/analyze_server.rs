@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams,
+                CompletionResponse, CompletionTextEdit, Diagnostic, DiagnosticSeverity,
+                DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+                GotoDefinitionParams, GotoDefinitionResponse, Location, OneOf, Position,
+                PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+                TextDocumentSyncKind, TextEdit};
+use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+                              Notification as _, PublishDiagnostics};
+use lsp_types::request::{Completion, GotoDefinition, Request as _};
+use simpath::Simpath;
+use url::Url;
+
+use flowcore::deserializers::deserializer::DEFINITION_FILE_EXTENSIONS;
+use flowcore::errors::Result as CoreResult;
+use flowcore::meta_provider::MetaProvider;
+use flowcore::model::lib_manifest::DEFAULT_LIB_JSON_MANIFEST_FILENAME;
+use flowcore::model::process::Process::{FlowProcess, FunctionProcess};
+use flowcore::provider::Provider;
+use flowrclib::checker;
+use flowrclib::compiler::parser;
+
+use crate::errors::{Error, Result};
+
+/// The state of the analysis server: where to find libraries and context functions, and the
+/// contents of the documents open in the editor
+struct AnalyzeServer {
+    lib_search_path: Simpath,
+    context_root: PathBuf,
+    documents: BTreeMap<Url, String>,
+}
+
+/// A `Provider` that provides the contents of documents open in the editor, which may not
+/// have been saved, and uses a `MetaProvider` for all other content
+struct DocumentProvider<'a> {
+    documents: &'a BTreeMap<Url, String>,
+    provider: MetaProvider,
+}
+
+impl Provider for DocumentProvider<'_> {
+    fn resolve_url(&self, url: &Url, default_name: &str, extensions: &[&str])
+        -> CoreResult<(Url, Option<Url>)> {
+        if self.documents.contains_key(url) {
+            return Ok((url.clone(), None));
+        }
+        self.provider.resolve_url(url, default_name, extensions)
+    }
+
+    fn get_contents(&self, url: &Url) -> CoreResult<Vec<u8>> {
+        match self.documents.get(url) {
+            Some(text) => Ok(text.as_bytes().to_vec()),
+            None => self.provider.get_contents(url),
+        }
+    }
+}
+
+/// Run a long-lived server that analyses flow and function definitions as they are edited,
+/// speaking the Language Server Protocol over stdin/stdout, for use by editors and IDEs.
+/// It provides:
+/// - diagnostics - errors loading a definition and the problems found by linting a flow
+///   (see [`checker::lint`]), published each time a document is opened or changed
+/// - go-to-definition - of the definition referenced by a process's `source`
+/// - completion - of `lib://` and `context://` references in a process's `source`, from the
+///   libraries found in the library search path and the context functions of the runner
+///
+/// Returns when the client has shutdown the server and sent the `exit` notification.
+pub fn analyze_server(lib_search_path: Simpath, context_root: PathBuf) -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["/".into(), "\"".into()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }).map_err(|e| format!("Could not serialize server capabilities: {e}"))?;
+    connection.initialize(capabilities).map_err(protocol_error)?;
+    info!("Flow analysis server initialized");
+
+    let mut server = AnalyzeServer {
+        lib_search_path,
+        context_root,
+        documents: BTreeMap::new(),
+    };
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request).map_err(protocol_error)? {
+                    break;
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(Message::Response(response)).map_err(protocol_error)?;
+            }
+            Message::Notification(notification) => {
+                if let Some(diagnostics) = server.handle_notification(notification) {
+                    let notification = Notification::new(PublishDiagnostics::METHOD.into(),
+                                                         diagnostics);
+                    connection.sender.send(Message::Notification(notification))
+                        .map_err(protocol_error)?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    // the io threads only end when the connection is dropped
+    drop(connection);
+    io_threads.join().map_err(protocol_error)?;
+    info!("Flow analysis server exited");
+    Ok(())
+}
+
+// Convert an error communicating with the client into an `Error`
+fn protocol_error<E: std::fmt::Display>(e: E) -> Error {
+    format!("Language Server Protocol error: {e}").into()
+}
+
+impl AnalyzeServer {
+    // Respond to a request from the client
+    fn handle_request(&self, request: Request) -> Response {
+        debug!("Request '{}'", request.method);
+        let result = match request.method.as_str() {
+            GotoDefinition::METHOD => serde_json::from_value::<GotoDefinitionParams>(request.params)
+                .map(|params| serde_json::to_value(self.definition(&params))),
+            Completion::METHOD => serde_json::from_value::<CompletionParams>(request.params)
+                .map(|params| serde_json::to_value(self.completion(&params))),
+            _ => return Response::new_err(request.id, ErrorCode::MethodNotFound as i32,
+                                          format!("Unsupported request '{}'", request.method)),
+        };
+
+        match result {
+            Ok(Ok(value)) => Response::new_ok(request.id, value),
+            Ok(Err(e)) | Err(e) => Response::new_err(request.id, ErrorCode::InvalidParams as i32,
+                                                     e.to_string()),
+        }
+    }
+
+    // Update the open documents from a notification, returning the diagnostics to publish
+    // for the document changed, if any
+    fn handle_notification(&mut self, notification: Notification) -> Option<PublishDiagnosticsParams> {
+        debug!("Notification '{}'", notification.method);
+        let url = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params).ok()?;
+                self.documents.insert(params.text_document.uri.clone(), params.text_document.text);
+                params.text_document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params).ok()?;
+                // with full document sync the last change is the whole document
+                let text = params.content_changes.into_iter().last()?.text;
+                self.documents.insert(params.text_document.uri.clone(), text);
+                params.text_document.uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params).ok()?;
+                self.documents.remove(&params.text_document.uri);
+                return Some(PublishDiagnosticsParams::new(params.text_document.uri, vec![], None));
+            }
+            _ => return None,
+        };
+
+        let diagnostics = self.diagnostics(&url)?;
+        Some(PublishDiagnosticsParams::new(url, diagnostics, None))
+    }
+
+    // A provider of the contents of open documents, and of the files they reference
+    fn provider(&self) -> DocumentProvider<'_> {
+        DocumentProvider {
+            documents: &self.documents,
+            provider: MetaProvider::new(self.lib_search_path.clone(), self.context_root.clone()),
+        }
+    }
+
+    // Load the flow or function definition in the document at `url`, returning diagnostics for
+    // any error loading it and any problems found by linting it. Documents that are not
+    // flow or function definitions are ignored.
+    fn diagnostics(&self, url: &Url) -> Option<Vec<Diagnostic>> {
+        let text = self.documents.get(url)?;
+        let definition = toml::from_str::<toml::Table>(text).ok()
+            .is_none_or(|table| table.contains_key("flow") || table.contains_key("function"));
+        if !definition {
+            return None;
+        }
+
+        let provider = self.provider();
+        let diagnostics = match parser::parse(url, &provider) {
+            Ok(FlowProcess(flow)) => match checker::lint(&flow) {
+                Ok(lints) => lints.iter()
+                    .map(|lint| diagnostic(text, &lint.to_string(), DiagnosticSeverity::WARNING))
+                    .collect(),
+                Err(e) => vec![diagnostic(text, &error_message(&e.into()), DiagnosticSeverity::ERROR)],
+            },
+            Ok(FunctionProcess(_)) => vec![],
+            Err(e) => vec![diagnostic(text, &error_message(&e.into()), DiagnosticSeverity::ERROR)],
+        };
+
+        Some(diagnostics)
+    }
+
+    // Find the definition referenced by the `source` of a process at the position
+    fn definition(&self, params: &GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = &params.text_document_position_params;
+        let text = self.documents.get(&position.text_document.uri)?;
+        let line = text.lines().nth(position.position.line as usize)?;
+        let (source, _) = source_value(line)?;
+
+        let reference = position.text_document.uri.join(source).ok()?;
+        let (resolved, _) = self.provider()
+            .resolve_url(&reference, "root", DEFINITION_FILE_EXTENSIONS).ok()?;
+
+        Some(GotoDefinitionResponse::Scalar(Location::new(resolved, Range::default())))
+    }
+
+    // Complete the `lib://` or `context://` reference being typed as the `source` of a process
+    fn completion(&self, params: &CompletionParams) -> Option<CompletionResponse> {
+        let position = &params.text_document_position;
+        let text = self.documents.get(&position.text_document.uri)?;
+        let line = text.lines().nth(position.position.line as usize)?;
+        let (_, start) = source_value(line)?;
+        let typed: String = line.chars().skip(start)
+            .take((position.position.character as usize).checked_sub(start)?)
+            .collect();
+
+        let range = Range::new(Position::new(position.position.line, u32::try_from(start).ok()?),
+                               position.position);
+        let items = self.references().into_iter()
+            .filter(|reference| reference.starts_with(&typed))
+            .map(|reference| CompletionItem {
+                label: reference.clone(),
+                kind: Some(CompletionItemKind::MODULE),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(range, reference))),
+                ..Default::default()
+            })
+            .collect();
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    // All the `lib://` references to the functions and flows of the (compiled) libraries in the
+    // library search path, and the `context://` references to the runner's context functions
+    fn references(&self) -> Vec<String> {
+        let mut references = vec![];
+
+        for dir in self.lib_search_path.directories() {
+            let Ok(entries) = fs::read_dir(dir) else { continue };
+            for lib_dir in entries.flatten().map(|entry| entry.path()) {
+                if lib_dir.join(DEFAULT_LIB_JSON_MANIFEST_FILENAME).with_extension("json").is_file() {
+                    if let Some(lib_name) = lib_dir.file_name() {
+                        let base = format!("lib://{}", lib_name.to_string_lossy());
+                        definition_references(&lib_dir, &lib_dir, &base, &mut references);
+                    }
+                }
+            }
+        }
+
+        definition_references(&self.context_root, &self.context_root, "context:/",
+                              &mut references);
+
+        references.sort();
+        references.dedup();
+        references
+    }
+}
+
+// Add references (e.g. "lib://flowstdlib/math/add") to the definitions found in `dir` within the
+// library or context root directory `root`. A function's definition is in a directory with
+// the same name (e.g. "math/add/add.toml")
+fn definition_references(root: &Path, dir: &Path, base: &str, references: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            definition_references(root, &path, base, references);
+        } else if path.extension().is_some_and(|extension| extension == "toml") {
+            let mut route = path.with_extension("");
+            if route.parent().and_then(Path::file_name) == route.file_name() {
+                route.pop();
+            }
+            if let Ok(relative) = route.strip_prefix(root) {
+                let relative: Vec<String> = relative.iter()
+                    .map(|segment| segment.to_string_lossy().to_string())
+                    .collect();
+                references.push(format!("{base}/{}", relative.join("/")));
+            }
+        }
+    }
+}
+
+// Get the value of a `source = "..."` on a line, and the column it starts at
+fn source_value(line: &str) -> Option<(&str, usize)> {
+    let (key, value) = line.split_once('=')?;
+    if key.trim() != "source" {
+        return None;
+    }
+    let value_start = line.len() - value.len();
+    let open = value_start + value.find('"')? + 1;
+    let value = line.get(open..)?;
+    let source = value.split('"').next()?;
+    Some((source, line.get(..open)?.chars().count()))
+}
+
+// Join the messages of an error and the errors that caused it
+fn error_message(e: &Error) -> String {
+    e.iter().map(ToString::to_string).collect::<Vec<String>>().join(": caused by: ")
+}
+
+// Create a diagnostic for a problem, placed at the first name or route in single quotes in
+// `message`, or the last segment of such a route, found in the document (e.g. the alias of a
+// process), or at the start of the document if none are found
+fn diagnostic(text: &str, message: &str, severity: DiagnosticSeverity) -> Diagnostic {
+    let range = message.split('\'').skip(1).step_by(2)
+        .filter(|quoted| !quoted.is_empty())
+        .find_map(|quoted| locate(text, quoted).or_else(||
+            quoted.rsplit('/').filter(|segment| !segment.is_empty())
+                .find_map(|segment| locate(text, segment))))
+        .unwrap_or_default();
+
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some("flowc".into()),
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
+
+// Find the range of the first occurrence of `needle` in quotes in `text`, or of `needle` alone
+fn locate(text: &str, needle: &str) -> Option<Range> {
+    let quoted = format!("\"{needle}\"");
+    let range = [quoted.as_str(), needle].into_iter().find_map(|pattern| {
+        text.lines().enumerate().find_map(|(number, line)| {
+            let start = line.find(pattern)?;
+            let start = line.get(..start)?.chars().count();
+            let end = start + pattern.chars().count();
+            Some(Range::new(Position::new(u32::try_from(number).ok()?, u32::try_from(start).ok()?),
+                            Position::new(u32::try_from(number).ok()?, u32::try_from(end).ok()?)))
+        })
+    });
+    range
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use lsp_types::{Position, Range};
+    use tempfile::tempdir;
+
+    use super::{definition_references, diagnostic, locate, source_value};
+
+    #[test]
+    fn source_values() {
+        assert_eq!(source_value("source = \"lib://flowstdlib/math/add\""),
+                   Some(("lib://flowstdlib/math/add", 10)));
+        assert_eq!(source_value("  source=\"lib://fl"), Some(("lib://fl", 10)));
+        assert_eq!(source_value("alias = \"add\""), None);
+        assert_eq!(source_value("[[process]]"), None);
+    }
+
+    #[test]
+    fn locate_quoted() {
+        let text = "flow = \"add\"\n\n[[process]]\nalias = \"add\"\n";
+        assert_eq!(locate(text, "add"), Some(Range::new(Position::new(0, 7), Position::new(0, 12))));
+        assert_eq!(locate(text, "process"), Some(Range::new(Position::new(2, 2), Position::new(2, 9))));
+        assert_eq!(locate(text, "sum"), None);
+    }
+
+    #[test]
+    fn diagnostic_at_route() {
+        let text = "flow = \"root\"\n\n[[process]]\nalias = \"sum\"\nsource = \"lib://flowstdlib/math/add\"\n";
+        let diagnostic = diagnostic(text, "Input at route '/root/sum/i1' is not connected to nor initialized",
+                                    lsp_types::DiagnosticSeverity::WARNING);
+        assert_eq!(diagnostic.range, Range::new(Position::new(3, 8), Position::new(3, 13)));
+    }
+
+    #[test]
+    fn diagnostic_at_start() {
+        let diagnostic = diagnostic("flow = \"root\"", "Could not parse",
+                                    lsp_types::DiagnosticSeverity::ERROR);
+        assert_eq!(diagnostic.range, Range::default());
+    }
+
+    #[test]
+    fn references_to_definitions() {
+        let lib_dir = tempdir().expect("Could not create temp dir").into_path();
+        fs::create_dir_all(lib_dir.join("math/add")).expect("Could not create dirs");
+        fs::write(lib_dir.join("math/add/add.toml"), "").expect("Could not write");
+        fs::write(lib_dir.join("math/add/add.md"), "").expect("Could not write");
+        fs::write(lib_dir.join("math/range.toml"), "").expect("Could not write");
+
+        let mut references = vec![];
+        definition_references(&lib_dir, &lib_dir, "lib://mylib", &mut references);
+        references.sort();
+        assert_eq!(references, vec!["lib://mylib/math/add", "lib://mylib/math/range"]);
+    }
+}
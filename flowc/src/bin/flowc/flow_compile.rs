@@ -9,8 +9,10 @@ use log::{debug, error, info};
 #[cfg(feature = "debugger")]
 use url::Url;
 
+use flowcore::meta_provider::MetaProvider;
+use flowcore::model::lock_file::LOCK_FILENAME;
+use flowcore::model::process::Process;
 use flowcore::model::process::Process::{FlowProcess, FunctionProcess};
-use flowcore::provider::Provider;
 use flowrclib::compiler::compile;
 use flowrclib::compiler::parser;
 use flowrclib::dumper::{flow_to_dot, functions_to_dot};
@@ -21,18 +23,27 @@ use crate::Options;
 
 /// Compile a flow, maybe run it
 pub fn compile_and_execute_flow(options: &Options,
-                                provider: &dyn Provider,
+                                provider: &MetaProvider,
                                 runner_name: &str,
                                 output_dir: &PathBuf) -> Result<()> {
     info!("==== Parsing flow hierarchy from '{}'", options.source_url);
-    #[cfg(feature = "debugger")]
-    let mut source_urls = BTreeMap::<String, Url>::new();
-
     let root = parser::parse(
         &options.source_url,
         provider,
     )?;
 
+    compile_and_execute(root, options, provider, runner_name, output_dir)
+}
+
+/// Compile an already parsed flow, maybe run it
+pub fn compile_and_execute(root: Process,
+                           options: &Options,
+                           provider: &MetaProvider,
+                           runner_name: &str,
+                           output_dir: &PathBuf) -> Result<()> {
+    #[cfg(feature = "debugger")]
+    let mut source_urls = BTreeMap::<String, Url>::new();
+
     match root {
         FlowProcess(flow) => {
             info!("Finished parsing flow hierarchy starting at root flow '{}'", flow.name);
@@ -62,9 +73,16 @@ pub fn compile_and_execute_flow(options: &Options,
                 output_dir,
                 &tables,
                 #[cfg(feature = "debugger")] source_urls,
+                options.manifest_format,
             )
             .chain_err(|| "Failed to write manifest")?;
 
+            // record the library versions resolved, so subsequent compiles use the same ones
+            let lock_file = provider.lock_file();
+            if !lock_file.is_empty() {
+                lock_file.write(&output_dir.join(LOCK_FILENAME))?;
+            }
+
             if options.compile_only {
                 info!("Flow execution skipped");
                 return Ok(());
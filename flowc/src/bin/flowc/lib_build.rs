@@ -10,6 +10,7 @@ use simpath::Simpath;
 use url::Url;
 use wax::Glob;
 
+use flowcore::content_hash;
 use flowcore::meta_provider::MetaProvider;
 use flowcore::model::lib_manifest::LibraryManifest;
 use flowcore::model::process::Process::{FlowProcess, FunctionProcess};
@@ -74,15 +75,22 @@ pub fn build_lib(options: &Options, provider: &dyn Provider, output_dir: &PathBu
 
     file_count += copy_docs(&lib_root_path.join("src"), output_dir)?;
 
-    let manifest_json_file = LibraryManifest::manifest_filename(output_dir);
+    let manifest_file = LibraryManifest::manifest_filename_with_format(output_dir,
+                                                                      options.manifest_format);
 
-    let (message, write_manifest) = check_manifest_status(&manifest_json_file, file_count,
+    let (message, write_manifest) = check_manifest_status(&manifest_file, file_count,
                                                           &lib_manifest)?;
 
     info!("{}", message);
 
     if write_manifest {
-        lib_manifest.write_json(&manifest_json_file)?;
+        // remove a manifest previously generated in a different format, so it is not loaded
+        if let Some(existing_manifest) = LibraryManifest::find_manifest_filename(output_dir) {
+            if existing_manifest != manifest_file {
+                fs::remove_file(existing_manifest)?;
+            }
+        }
+        lib_manifest.write(&manifest_file, options.manifest_format)?;
     }
 
     teardown_lib_workspace(&lib_root_path)?;
@@ -305,6 +313,12 @@ fn compile_functions(
                             file_count += 1;
                         }
 
+                        if wasm_destination.exists() {
+                            lib_manifest.add_implementation_hash(
+                                &wasm_relative_path.to_string_lossy(),
+                                content_hash::sha256(&fs::read(&wasm_destination)?));
+                        }
+
                         lib_manifest
                             .add_locator(
                                 &wasm_relative_path.to_string_lossy(),
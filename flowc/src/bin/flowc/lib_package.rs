@@ -0,0 +1,42 @@
+use std::env;
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use flowcore::content::archive_provider::package;
+
+use crate::errors::{bail, Result, ResultExt};
+use crate::Options;
+
+/// Package a compiled library (the output directory of a library build, containing its manifest,
+/// definitions, WASM implementations and docs) into a single library archive in the
+/// output directory (or the current directory if none was specified), so that it can be
+/// placed in a directory of the library search path.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The source is not a directory on the local file system
+/// - The directory does not contain a compiled library
+/// - The archive cannot be written to the output directory
+pub fn package_lib(options: &Options) -> Result<()> {
+    let lib_dir = options.source_url.to_file_path()
+        .map_err(|()| "Only a library on the local file system can be packaged")?;
+    if !lib_dir.is_dir() {
+        bail!("'{}' is not the directory of a compiled library", lib_dir.display());
+    }
+
+    let output_dir = match &options.output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => env::current_dir()
+            .chain_err(|| "Could not get the current working directory")?,
+    };
+
+    println!("   {} library ({}) with 'flowc'", "Packaging".green(), lib_dir.display());
+    let (archive_path, index) = package(&lib_dir, &output_dir)
+        .chain_err(|| format!("Could not package library in '{}'", lib_dir.display()))?;
+    println!("    {} {} v{} ({} files) into '{}'", "Finished".green(), index.metadata.name,
+             index.metadata.version, index.files.len(), archive_path.display());
+
+    Ok(())
+}
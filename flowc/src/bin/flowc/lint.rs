@@ -0,0 +1,33 @@
+use log::info;
+
+use flowcore::model::process::Process::{FlowProcess, FunctionProcess};
+use flowcore::provider::Provider;
+use flowrclib::checker;
+use flowrclib::compiler::parser;
+
+use crate::errors::{bail, Result, ResultExt};
+use crate::Options;
+
+/// Parse a flow and print the problems found in it by [`checker::lint`], without compiling it
+/// or generating a manifest. Return an error if any problems were found.
+pub fn lint_flow(options: &Options, provider: &dyn Provider) -> Result<()> {
+    info!("==== Parsing flow hierarchy from '{}'", options.source_url);
+    match parser::parse(&options.source_url, provider)? {
+        FlowProcess(flow) => {
+            let lints = checker::lint(&flow)
+                .chain_err(|| format!("Could not lint the flow '{}'", options.source_url))?;
+
+            for lint in &lints {
+                println!("{lint}");
+            }
+
+            if !lints.is_empty() {
+                bail!("{} problems found in the flow '{}'", lints.len(), options.source_url);
+            }
+
+            info!("No problems found in the flow '{}'", options.source_url);
+            Ok(())
+        }
+        FunctionProcess(_) => bail!("Process parsed was not of type 'Flow' and cannot be linted"),
+    }
+}
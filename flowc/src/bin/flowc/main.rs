@@ -9,31 +9,48 @@
 //! description of the command line options.
 
 use core::str::FromStr;
-use std::env;
+use std::{env, io};
+use std::ffi::OsStr;
 use std::path::PathBuf;
 use std::process::exit;
 
-use clap::{Arg, ArgMatches, Command};
+use clap::{Arg, ArgMatches, Command, ValueHint};
+use clap_complete::{CompleteEnv, generate, Shell};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate, PathCompleter, ValueCompleter};
 use env_logger::Builder;
 use log::{debug, error, info, LevelFilter};
 use serde_derive::Deserialize;
 use simpath::Simpath;
 use url::Url;
 
-use errors::{Result, ResultExt};
+use errors::{bail, Error, Result, ResultExt};
+use flowcore::config::Config;
+use flowcore::examples::find_examples;
 use flowcore::meta_provider::MetaProvider;
+use flowcore::model::lock_file::{LOCK_FILENAME, LockFile};
+use flowcore::model::manifest_format::ManifestFormat;
 use flowcore::url_helper::url_from_string;
 use flowrclib::info;
 use lib_build::build_lib;
 
+use crate::analyze_server::analyze_server;
 use crate::flow_compile::compile_and_execute_flow;
 use crate::lib_build::build_runner;
+use crate::lib_package::package_lib;
+use crate::lint::lint_flow;
+use crate::scaffold::{new_process, NewKind};
 use crate::source_arg::{CompileType, default_runner_dir, load_runner_spec};
+use crate::watch::watch_flow;
 
+mod analyze_server;
 mod errors;
 mod flow_compile;
 mod lib_build;
+mod lib_package;
+mod lint;
+mod scaffold;
 mod source_arg;
+mod watch;
 
 #[allow(clippy::struct_excessive_bools)]
 pub(crate) struct Options {
@@ -52,6 +69,12 @@ pub(crate) struct Options {
     runner_name: Option<String>,
     verbosity: Option<String>,
     optimize: bool,
+    manifest_format: ManifestFormat,
+    watch: bool,
+    lint: bool,
+    package: bool,
+    analyze_server: bool,
+    new: Option<(NewKind, String)>,
 }
 
 #[derive(Deserialize)]
@@ -60,24 +83,31 @@ pub(crate) struct RunnerSpec {
 }
 
 fn main() {
+    // When run by a shell to complete the command line, print the completions and exit
+    CompleteEnv::with_factory(get_command).complete();
+
     match run() {
         Err(ref e) => {
-            error!("{e}");
-            for e in e.iter().skip(1) {
-                error!("caused by: {e}");
-            }
-
-            // The backtrace is generated if env var `RUST_BACKTRACE` is set to `1` or `full`
-            if let Some(backtrace) = e.backtrace() {
-                error!("backtrace: {backtrace:?}");
-            }
-
+            print_error(e);
             exit(1);
         }
         Ok(()) => exit(0),
     }
 }
 
+// Log an error, with the chain of errors that caused it
+pub(crate) fn print_error(e: &Error) {
+    error!("{e}");
+    for e in e.iter().skip(1) {
+        error!("caused by: {e}");
+    }
+
+    // The backtrace is generated if env var `RUST_BACKTRACE` is set to `1` or `full`
+    if let Some(backtrace) = e.backtrace() {
+        error!("backtrace: {backtrace:?}");
+    }
+}
+
 // For the lib provider, libraries maybe installed in multiple places in the file system.
 // In order to find the content, a `FLOW_LIB_PATH` environment variable can be configured with a
 // list of directories in which to look for the library in question.
@@ -143,11 +173,46 @@ fn compile_type(url: &Url) -> Result<CompileType> {
     a message to display to the user if all went OK
 */
 fn run() -> Result<()> {
-    let options = parse_args(&get_matches())?;
+    let matches = get_command().get_matches();
+    if let Some(("completions", completions_matches)) = matches.subcommand() {
+        if let Some(shell) = completions_matches.get_one::<Shell>("shell") {
+            print_completions(*shell);
+        }
+        return Ok(());
+    }
+
+    let options = parse_args(&matches)?;
+    if let Some((kind, name)) = &options.new {
+        let dir = match &options.output_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => env::current_dir()
+                .chain_err(|| "Could not get the current working directory")?,
+        };
+        return new_process(*kind, name, &dir);
+    }
+
+    if options.package {
+        return package_lib(&options);
+    }
+
+    if options.analyze_server {
+        let runner_name = options.runner_name.as_ref().ok_or("Runner name was not specified")?;
+        return analyze_server(get_lib_search_path(&options.lib_dirs),
+                              default_runner_dir(runner_name));
+    }
+
     let mut lib_search_path = get_lib_search_path(&options.lib_dirs);
 
     let compile_type = compile_type(&options.source_url)?;
 
+    if options.watch && !matches!(compile_type, CompileType::Flow) {
+        bail!("'--watch' can only be used when compiling a flow");
+    }
+
+    if options.lint && !matches!(compile_type, CompileType::Flow) {
+        bail!("'--lint' can only be used with a flow");
+    }
+
     match compile_type {
         CompileType::Library => {
             let output_dir = source_arg::get_output_dir(&options.source_url,
@@ -180,15 +245,42 @@ fn run() -> Result<()> {
 
             let runner_name = options.runner_name.as_ref().ok_or("Runner name was not specified")?;
             let runner_dir = default_runner_dir(&runner_name.to_string());
-            let provider = &MetaProvider::new(lib_search_path, runner_dir);
+            if options.lint {
+                let provider = &MetaProvider::new(lib_search_path, runner_dir);
+                return lint_flow(&options, provider);
+            }
+            if options.watch {
+                return watch_flow(&options, &lib_search_path, &runner_dir, runner_name,
+                                  &output_dir);
+            }
+            let provider = &MetaProvider::new(lib_search_path, runner_dir)
+                .with_lock_file(LockFile::read(&output_dir.join(LOCK_FILENAME))?);
             compile_and_execute_flow(&options, provider, runner_name, &output_dir)
         }
     }
 }
 
-// Parse the command line arguments using clap
+/// Print a static completion script for `shell` to stdout, that completes `source_url` with paths.
+/// The script registered using `COMPLETE=<shell> flowc` runs `flowc` to complete it, and also
+/// completes it with the example flows installed (see [`complete_source_url`])
+fn print_completions(shell: Shell) {
+    generate(shell, &mut get_command(), env!("CARGO_BIN_NAME"), &mut io::stdout());
+}
+
+/// Complete `source_url` with the example flows installed that start with `current`, looked for
+/// each time it is completed, followed by any paths that do
+fn complete_source_url(current: &OsStr) -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<CompletionCandidate> = find_examples(&current.to_string_lossy())
+        .into_iter()
+        .map(|example| CompletionCandidate::new(example).help(Some("example flow".into())))
+        .collect();
+    candidates.extend(PathCompleter::any().complete(current));
+    candidates
+}
+
+// Define the command line arguments using clap
 #[allow(clippy::too_many_lines)]
-fn get_matches() -> ArgMatches {
+fn get_command() -> Command {
     let app = Command::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"));
 
@@ -201,7 +293,7 @@ fn get_matches() -> ArgMatches {
             .help("Generate symbols for debugging. If executing the flow, do so with the debugger"),
     );
 
-    let app = app
+    app
         .arg(
             Arg::new("compile")
                 .short('c')
@@ -264,6 +356,15 @@ fn get_matches() -> ArgMatches {
                 .action(clap::ArgAction::SetTrue)
                 .help("Optimize generated output (flows and wasm)"),
         )
+        .arg(
+            Arg::new("manifest_format")
+                .long("manifest-format")
+                .num_args(1)
+                .value_parser(["json", "cbor"])
+                .default_value("json")
+                .value_name("FORMAT")
+                .help("Format of the generated flow or library manifest: 'json' or compact binary 'cbor'"),
+        )
         .arg(
             Arg::new("provided")
                 .short('p')
@@ -299,9 +400,38 @@ fn get_matches() -> ArgMatches {
                 .value_name("STDIN_FILENAME")
                 .help("Read STDIN from the named file"),
         )
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .action(clap::ArgAction::SetTrue)
+                .help("Watch the flow's source files, recompiling (and re-running) it when they change"),
+        )
+        .arg(
+            Arg::new("lint")
+                .long("lint")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("watch")
+                .help("Analyse the flow and report problems found in it, without compiling it"),
+        )
+        .arg(
+            Arg::new("package")
+                .long("package")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["watch", "lint"])
+                .help("Package a compiled library directory into a library archive in OUTPUT_DIR"),
+        )
+        .arg(
+            Arg::new("analyze_server")
+                .long("analyze-server")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["watch", "lint", "package"])
+                .help("Run a Language Server Protocol server over stdio, analysing flow definitions as they are edited"),
+        )
         .arg(
             Arg::new("source_url")
                 .num_args(1)
+                .value_hint(ValueHint::AnyPath)
+                .add(ArgValueCompleter::new(complete_source_url))
                 .help("path or url for the flow or library to compile")
         )
         .arg(
@@ -309,15 +439,44 @@ fn get_matches() -> ArgMatches {
                 .num_args(0..)
                 .trailing_var_arg(true)
                 .help("List of arguments get passed to the flow when executed")
-        );
-
-    app.get_matches()
+        )
+        .subcommand(
+            Command::new("new")
+                .about("Create the source files for a new function or flow in a library, \
+                in OUTPUT_DIR or the current directory")
+                .arg(
+                    Arg::new("kind")
+                        .required(true)
+                        .value_parser(["function", "flow"])
+                        .help("The kind of process to create")
+                )
+                .arg(
+                    Arg::new("name")
+                        .required(true)
+                        .help("The name of the function or flow to create")
+                )
+        )
+        .subcommand(
+            Command::new("completions")
+                .about("Print a shell completion script for flowc")
+                .arg(
+                    Arg::new("shell")
+                        .required(true)
+                        .value_parser(clap::value_parser!(Shell))
+                        .help("The shell to print the completion script for")
+                )
+        )
 }
 
 // Parse the command line arguments
 fn parse_args(matches: &ArgMatches) -> Result<Options> {
+    let cwd = env::current_dir()
+        .chain_err(|| "Could not get the current working directory")?;
+    // defaults from config files, that command line options override
+    let config = Config::load(&cwd)?;
+
     let default = String::from("error");
-    let verbosity_option = matches.get_one::<String>("verbosity");
+    let verbosity_option = matches.get_one::<String>("verbosity").or(config.verbosity.as_ref());
     let verbosity = verbosity_option.unwrap_or(&default);
     let level = LevelFilter::from_str(verbosity).unwrap_or(LevelFilter::Error);
     let mut builder = Builder::from_default_env();
@@ -330,8 +489,6 @@ fn parse_args(matches: &ArgMatches) -> Result<Options> {
     );
     debug!("'flowrclib' version {}", info::version());
 
-    let cwd = env::current_dir()
-        .chain_err(|| "Could not get the current working directory")?;
     let cwd_url = Url::from_directory_path(cwd)
         .map_err(|()| "Could not form a Url for the current working directory")?;
 
@@ -340,7 +497,7 @@ fn parse_args(matches: &ArgMatches) -> Result<Options> {
                                   .map(String::as_str))
         .chain_err(|| "Could not create a url for flow from the 'FLOW' command line parameter")?;
 
-    let lib_dirs = if matches.contains_id("lib_dir") {
+    let mut lib_dirs: Vec<String> = if matches.contains_id("lib_dir") {
         matches
             .get_many::<String>("lib_dir")
             .chain_err(|| "Could not get the list of 'LIB_DIR' options specified")?
@@ -349,6 +506,20 @@ fn parse_args(matches: &ArgMatches) -> Result<Options> {
     } else {
         vec![]
     };
+    lib_dirs.extend(config.lib_dirs);
+
+    let new = match matches.subcommand() {
+        Some(("new", new_matches)) => {
+            let kind = match new_matches.get_one::<String>("kind").map(String::as_str) {
+                Some("flow") => NewKind::Flow,
+                _ => NewKind::Function,
+            };
+            let name = new_matches.get_one::<String>("name")
+                .ok_or("A name is required for the new function or flow")?;
+            Some((kind, name.clone()))
+        },
+        _ => None,
+    };
 
     let flow_args = match matches.get_many::<String>("flow_args") {
         Some(strings) => strings.map(std::string::ToString::to_string).collect(),
@@ -359,7 +530,7 @@ fn parse_args(matches: &ArgMatches) -> Result<Options> {
         source_url,
         flow_args,
         graphs: matches.get_flag("graphs"),
-        wasm_execution: matches.get_flag("wasm"),
+        wasm_execution: matches.get_flag("wasm") || config.native == Some(false),
         execution_metrics: matches.get_flag("metrics"),
         compile_only: matches.get_flag("compile"),
         debug_symbols: matches.get_flag("debug"),
@@ -368,8 +539,15 @@ fn parse_args(matches: &ArgMatches) -> Result<Options> {
         stdin_file: matches.get_one::<String>("stdin").map(std::string::ToString::to_string),
         lib_dirs,
         native_only: matches.get_flag("native"),
-        runner_name: matches.get_one::<String>("runner").map(std::string::ToString::to_string),
+        runner_name: matches.get_one::<String>("runner").cloned().or(config.runner),
         verbosity: verbosity_option.map(std::string::ToString::to_string),
-        optimize: matches.get_flag("optimize")
+        optimize: matches.get_flag("optimize"),
+        manifest_format: matches.get_one::<String>("manifest_format")
+            .map_or(Ok(ManifestFormat::default()), |format| format.parse())?,
+        watch: matches.get_flag("watch"),
+        lint: matches.get_flag("lint"),
+        package: matches.get_flag("package"),
+        analyze_server: matches.get_flag("analyze_server"),
+        new,
     })
 }
@@ -0,0 +1,358 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use simpath::Simpath;
+use url::Url;
+
+use flowcore::meta_provider::MetaProvider;
+use flowrclib::compiler::parser;
+
+use crate::errors::{bail, Result, ResultExt};
+
+/// The kind of process to create the source files for with `flowc new`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NewKind {
+    /// A function, with a definition, a rust implementation and tests
+    Function,
+    /// A flow, with a definition and a test that executes it
+    Flow,
+}
+
+/// Create the source files for a new function or flow called `name` in the library directory
+/// `dir`, using the layout of functions and flows in the library's source (e.g. as in `flowstdlib`):
+/// - A function is created in a new sub-directory called `name` containing `function.toml`,
+///   `{name}.toml`, `{name}.md` and `{name}.rs` (using `#[flow_function]`, with unit tests)
+/// - A flow is created in `dir` as `{name}.toml`, `{name}.md` and `{name}.rs` (with a test that
+///   executes the flow)
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - `name` is not a valid name for a function or flow
+/// - `dir` is not within the `src` directory of a library (with `lib.toml` in its root)
+/// - the library's metadata cannot be read
+/// - any of the files to create already exist, or cannot be written
+pub fn new_process(kind: NewKind, name: &str, dir: &Path) -> Result<()> {
+    if !valid_name(name) {
+        bail!("'{}' is not a valid name, use lowercase letters, digits and '_', starting with a letter",
+            name);
+    }
+
+    let dir = fs::canonicalize(dir)
+        .chain_err(|| format!("Could not find the directory '{}'", dir.display()))?;
+    let (lib_name, module) = lib_location(&dir)?;
+    let lib_route = format!("{lib_name}/{}", module.join("/"))
+        .trim_end_matches('/').to_string();
+
+    let files = match kind {
+        NewKind::Function => function_files(name, &lib_route, &dir.join(name)),
+        NewKind::Flow => flow_files(name, &lib_route, &dir),
+    };
+
+    for (path, _) in &files {
+        if path.exists() {
+            bail!("'{}' already exists", path.display());
+        }
+    }
+
+    if kind == NewKind::Function {
+        fs::create_dir(dir.join(name))
+            .chain_err(|| format!("Could not create directory for function '{name}'"))?;
+    }
+
+    for (path, contents) in &files {
+        fs::write(path, contents)
+            .chain_err(|| format!("Could not write '{}'", path.display()))?;
+        println!("     {} {}", "Created".green(), path.display());
+    }
+
+    let url = format!("lib://{lib_route}/{name}");
+    match kind {
+        NewKind::Function => println!("Add '{}' to the workspace members in 'lib.toml', \
+            declare it in the module and add '{url}' to the library manifest",
+            module.iter().map(String::as_str).chain([name]).collect::<Vec<&str>>().join("/")),
+        NewKind::Flow => println!("Declare it in the module to run its test, and use it as '{url}'"),
+    }
+
+    Ok(())
+}
+
+// A valid name for a function or flow, that can also be used as the name of a rust module
+fn valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+// Find the name of the library that `dir` is in, and the path of the module `dir` is within the
+// library's `src` directory
+fn lib_location(dir: &Path) -> Result<(String, Vec<String>)> {
+    let lib_root = dir.ancestors()
+        .find(|ancestor| ancestor.join("lib.toml").is_file())
+        .ok_or_else(|| format!("'{}' is not within a flow library (with a 'lib.toml' file)",
+                               dir.display()))?;
+
+    let module = dir.strip_prefix(lib_root.join("src"))
+        .map_err(|_| format!("'{}' is not within the 'src' directory of the library in '{}'",
+                             dir.display(), lib_root.display()))?
+        .iter()
+        .map(|segment| segment.to_string_lossy().to_string())
+        .collect();
+
+    let lib_root_url = Url::from_directory_path(lib_root)
+        .map_err(|()| "Could not create a Url for the library's root directory")?;
+    let provider = MetaProvider::new(Simpath::new("FLOW_LIB_PATH"), PathBuf::default());
+    let (metadata, _) = parser::parse_metadata(&lib_root_url, &provider)
+        .chain_err(|| format!("Could not read the metadata of the library in '{}'",
+                              lib_root.display()))?;
+
+    Ok((metadata.name, module))
+}
+
+// Convert a snake_case name to the CamelCase name of the struct `#[flow_function]` generates
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+// The files for a function, in the directory `dir` for it
+fn function_files(name: &str, lib_route: &str, dir: &Path) -> Vec<(PathBuf, String)> {
+    let title = camel_case(name);
+
+    let cargo = format!("[package]
+name = \"{name}\"
+version.workspace = true
+authors.workspace = true
+edition.workspace = true
+
+[lib]
+name = \"{name}\"
+crate-type = [\"cdylib\"]
+path = \"{name}.rs\"
+
+[dependencies]
+flowcore = {{ workspace = true }}
+flowmacro = {{ workspace = true }}
+serde_json = {{ workspace = true, default-features = false }}
+");
+
+    let definition = format!("function = \"{name}\"
+source = \"{name}.rs\"
+docs = \"{name}.md\"
+type = \"rust\"
+
+[[input]]
+name = \"input\" # - TODO describe the input
+
+[[output]] # - TODO describe the output
+");
+
+    let docs = format!("## {title} (//{lib_route}/{name})
+TODO describe what the function does
+
+### Include using
+```toml
+[[process]]
+source = \"lib://{lib_route}/{name}\"
+```
+");
+
+    let implementation = format!("use serde_json::Value;
+
+use flowcore::{{RUN_AGAIN, RunAgain}};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_{name}(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {{
+    let input = inputs.first().ok_or(\"Could not get input\")?;
+
+    // TODO calculate the output from the inputs
+    Ok((Some(input.clone()), RUN_AGAIN))
+}}
+
+#[cfg(test)]
+mod test {{
+    use serde_json::json;
+
+    use super::inner_{name};
+
+    #[test]
+    fn {name}_outputs_input() {{
+        let (result, _) = inner_{name}(&[json!(42)]).expect(\"_{name}() failed\");
+        assert_eq!(result, Some(json!(42)));
+    }}
+}}
+");
+
+    vec![
+        (dir.join("function.toml"), cargo),
+        (dir.join(format!("{name}.toml")), definition),
+        (dir.join(format!("{name}.md")), docs),
+        (dir.join(format!("{name}.rs")), implementation),
+    ]
+}
+
+// The files for a flow, in the directory `dir` of the module it is in
+fn flow_files(name: &str, lib_route: &str, dir: &Path) -> Vec<(PathBuf, String)> {
+    let title = camel_case(name);
+
+    let definition = format!("flow = \"{name}\"
+docs = \"{name}.md\"
+
+[[input]]
+name = \"input\"
+
+[[output]]
+name = \"output\"
+
+# TODO add the processes of the flow, and the connections between them
+[[connection]]
+from = \"input/input\"
+to = \"output/output\"
+");
+
+    let docs = format!("## {title} (//{lib_route}/{name})
+TODO describe what the flow does
+
+### Include using
+```toml
+[[process]]
+source = \"lib://{lib_route}/{name}\"
+```
+
+### Flow Graph
+
+<a href=\"{name}.dot.svg\" target=\"_blank\"><img src=\"{name}.dot.svg\"></a>
+
+Click image to navigate flow hierarchy.
+");
+
+    let test = format!("#[doc = include_str!(\"{name}.md\")]
+#[cfg(test)]
+mod test {{
+    use std::fs::File;
+    use std::io::Write;
+
+    use tempfile::tempdir;
+
+    use crate::test::execute_flow;
+
+    #[test]
+    fn test_{name}_flow() {{
+        let flow = \"\\
+flow = \\\"{name}_test\\\"
+
+[[process]]
+source = \\\"lib://{lib_route}/{name}\\\"
+input.input = {{ once = 42 }}
+
+[[process]]
+source = \\\"context://stdio/stdout\\\"
+
+[[connection]]
+from = \\\"{name}/output\\\"
+to = \\\"stdout\\\"
+\";
+
+        let temp_dir = tempdir().expect(\"Could not create temporary directory\")
+            .into_path();
+        let flow_filename = temp_dir.join(\"{name}_test.toml\");
+        let mut flow_file = File::create(&flow_filename)
+            .expect(\"Could not create flow file\");
+        flow_file.write_all(flow.as_bytes())
+            .expect(\"Could not write data bytes to created flow file\");
+
+        let stdout = execute_flow(&flow_filename);
+        assert_eq!(stdout, \"42\\n\".to_string());
+    }}
+}}
+");
+
+    vec![
+        (dir.join(format!("{name}.toml")), definition),
+        (dir.join(format!("{name}.md")), docs),
+        (dir.join(format!("{name}.rs")), test),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::Path;
+
+    use tempfile::tempdir;
+
+    use super::{camel_case, new_process, NewKind, valid_name};
+
+    fn create_lib(root: &Path) {
+        fs::write(root.join("lib.toml"), "[workspace]\n").expect("Could not write lib.toml");
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"mylib\"\nversion = \"0.1.0\"\n\
+            authors = [\"me\"]\ndescription = \"a library\"\n").expect("Could not write Cargo.toml");
+        fs::create_dir_all(root.join("src/math")).expect("Could not create module dir");
+    }
+
+    #[test]
+    fn names() {
+        assert!(valid_name("add"));
+        assert!(valid_name("base64_encode"));
+        assert!(!valid_name("Add"));
+        assert!(!valid_name("2d"));
+        assert!(!valid_name("a-b"));
+        assert!(!valid_name(""));
+        assert_eq!(camel_case("base64_encode"), "Base64Encode");
+    }
+
+    #[test]
+    fn new_function() {
+        let root = tempdir().expect("Could not create temp dir").into_path();
+        create_lib(&root);
+        let module = root.join("src/math");
+
+        new_process(NewKind::Function, "cube", &module).expect("Could not create function");
+
+        for file in ["function.toml", "cube.toml", "cube.md", "cube.rs"] {
+            assert!(module.join("cube").join(file).exists(), "'{file}' was not created");
+        }
+        let docs = fs::read_to_string(module.join("cube/cube.md")).expect("Could not read docs");
+        assert!(docs.contains("## Cube (//mylib/math/cube)"));
+        let implementation = fs::read_to_string(module.join("cube/cube.rs"))
+            .expect("Could not read implementation");
+        assert!(implementation.contains("fn inner_cube(inputs: &[Value])"));
+
+        assert!(new_process(NewKind::Function, "cube", &module).is_err(), "Overwrote function");
+    }
+
+    #[test]
+    fn new_flow() {
+        let root = tempdir().expect("Could not create temp dir").into_path();
+        create_lib(&root);
+        let module = root.join("src/math");
+
+        new_process(NewKind::Flow, "cube_all", &module).expect("Could not create flow");
+
+        for file in ["cube_all.toml", "cube_all.md", "cube_all.rs"] {
+            assert!(module.join(file).exists(), "'{file}' was not created");
+        }
+        let test = fs::read_to_string(module.join("cube_all.rs")).expect("Could not read test");
+        assert!(test.contains("source = \\\"lib://mylib/math/cube_all\\\""));
+    }
+
+    #[test]
+    fn not_in_a_lib() {
+        let dir = tempdir().expect("Could not create temp dir").into_path();
+        assert!(new_process(NewKind::Function, "cube", &dir).is_err());
+    }
+
+    #[test]
+    fn invalid_name() {
+        let root = tempdir().expect("Could not create temp dir").into_path();
+        create_lib(&root);
+        assert!(new_process(NewKind::Function, "Cube", &root.join("src/math")).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use log::{debug, info};
+use simpath::Simpath;
+
+use flowcore::meta_provider::MetaProvider;
+use flowcore::model::lock_file::{LOCK_FILENAME, LockFile};
+use flowcore::model::process::Process;
+use flowcore::model::process::Process::{FlowProcess, FunctionProcess};
+use flowrclib::compiler::parser;
+
+use crate::errors::Result;
+use crate::flow_compile::compile_and_execute;
+use crate::{print_error, Options};
+
+/// Interval between checks of the watched files for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The modification times of a set of files, `None` if a file does not exist
+type Snapshot = BTreeMap<PathBuf, Option<SystemTime>>;
+
+/// Compile (and unless only compiling, execute) the flow, then watch all the files it was
+/// built from - definitions and the source of provided implementations, including those of any
+/// local libraries it references - and repeat each time any of them change. Never returns
+/// unless there is an error setting up the watch.
+///
+/// A new `Provider` is created for each compile, so no content loaded by a previous compile
+/// can be used after a file has changed.
+pub fn watch_flow(options: &Options,
+                  lib_search_path: &Simpath,
+                  runner_dir: &Path,
+                  runner_name: &str,
+                  output_dir: &PathBuf) -> Result<()> {
+    // if the flow cannot be parsed the first time, the sources are not known, so watch the
+    // root source (file or directory) until it can be
+    let root_source = options.source_url.to_file_path()
+        .map_err(|()| "'--watch' can only be used with a flow on the local file system")?;
+    let mut sources = BTreeSet::from([root_source]);
+
+    loop {
+        let provider = MetaProvider::new(lib_search_path.clone(), runner_dir.to_path_buf())
+            .with_lock_file(LockFile::read(&output_dir.join(LOCK_FILENAME))?);
+        match parser::parse(&options.source_url, &provider) {
+            Ok(root) => {
+                sources = source_files(&root);
+                // take the snapshot before compiling, so changes made while compiling are seen
+                let snapshot = snapshot(&sources);
+                if let Err(e) = compile_and_execute(root, options, &provider, runner_name,
+                                                    output_dir) {
+                    print_error(&e);
+                }
+                wait_for_change(&snapshot);
+            }
+            Err(e) => {
+                let snapshot = snapshot(&sources);
+                print_error(&e.into());
+                wait_for_change(&snapshot);
+            }
+        }
+        println!("Change detected, recompiling '{}'", options.source_url);
+    }
+}
+
+/// Return the set of local files a parsed flow was built from - the definition files of all the
+/// processes in it and the source files of function implementations that exist locally
+pub(crate) fn source_files(process: &Process) -> BTreeSet<PathBuf> {
+    let mut files = BTreeSet::new();
+    add_source_files(process, &mut files);
+    files
+}
+
+fn add_source_files(process: &Process, files: &mut BTreeSet<PathBuf>) {
+    match process {
+        FlowProcess(flow) => {
+            if let Ok(path) = flow.source_url.to_file_path() {
+                files.insert(path);
+            }
+            for subprocess in flow.subprocesses.values() {
+                add_source_files(subprocess, files);
+            }
+        }
+        FunctionProcess(function) => {
+            if let Ok(path) = function.get_source_url().to_file_path() {
+                if !function.get_source().is_empty() {
+                    let implementation = path.with_file_name(function.get_source());
+                    if implementation.is_file() {
+                        files.insert(implementation);
+                    }
+                }
+                files.insert(path);
+            }
+        }
+    }
+}
+
+/// Take a snapshot of the modification times of `files`
+fn snapshot(files: &BTreeSet<PathBuf>) -> Snapshot {
+    files.iter()
+        .map(|file| (file.clone(), fs::metadata(file).and_then(|md| md.modified()).ok()))
+        .collect()
+}
+
+/// Return the files that have been modified, created or deleted since `before` was taken
+fn changed_files(before: &Snapshot) -> Vec<PathBuf> {
+    let keys = before.keys().cloned().collect();
+    let now = snapshot(&keys);
+    before.iter()
+        .filter(|(file, modified)| now.get(*file) != Some(*modified))
+        .map(|(file, _)| file.clone())
+        .collect()
+}
+
+/// Block until at least one of the files in `before` has changed. Wait one more interval after
+/// the first change is seen, so that a number of files being saved together cause one recompile
+fn wait_for_change(before: &Snapshot) {
+    info!("Watching {} files for changes", before.len());
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let changed = changed_files(before);
+        if !changed.is_empty() {
+            debug!("Changed files: {changed:?}");
+            thread::sleep(POLL_INTERVAL);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use simpath::Simpath;
+    use url::Url;
+
+    use flowcore::meta_provider::MetaProvider;
+    use flowrclib::compiler::parser;
+
+    use super::{changed_files, snapshot, source_files};
+
+    #[test]
+    fn detects_modified_created_and_deleted_files() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let modified = dir.path().join("modified.toml");
+        let created = dir.path().join("created.toml");
+        let deleted = dir.path().join("deleted.toml");
+        let unchanged = dir.path().join("unchanged.toml");
+        for file in [&modified, &deleted, &unchanged] {
+            fs::write(file, "flow = 'test'").expect("Could not write file");
+        }
+
+        let before = snapshot(&BTreeSet::from([modified.clone(), created.clone(),
+                                               deleted.clone(), unchanged.clone()]));
+        assert!(changed_files(&before).is_empty());
+
+        let file = fs::File::options().append(true).open(&modified).expect("Could not open file");
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10))
+            .expect("Could not set modification time");
+        fs::write(&created, "flow = 'test'").expect("Could not write file");
+        fs::remove_file(&deleted).expect("Could not delete file");
+
+        assert_eq!(changed_files(&before), vec![created, deleted, modified]);
+    }
+
+    #[test]
+    fn source_files_of_flow() {
+        let flow_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test-flows/names");
+        let url = Url::from_file_path(flow_dir.join("root.toml")).expect("Could not create Url");
+        let context_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../flowr/src/bin/flowrcli/context")
+            .canonicalize().expect("Could not get absolute path");
+        let provider = MetaProvider::new(Simpath::new("FLOW_LIB_PATH"), context_root);
+        let root = parser::parse(&url, &provider).expect("Could not parse flow");
+
+        let sources = source_files(&root);
+        assert!(sources.contains(&flow_dir.join("root.toml")));
+        assert!(sources.contains(&flow_dir.join("subflow.toml")));
+        assert!(sources.iter().any(|source| source.ends_with("stdio/stdin.toml")));
+    }
+}
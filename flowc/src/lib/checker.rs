@@ -0,0 +1,400 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use error_chain::bail;
+use log::info;
+
+use flowcore::model::flow_definition::FlowDefinition;
+use flowcore::model::function_definition::FunctionDefinition;
+use flowcore::model::input::InputInitializer::Always;
+use flowcore::model::io::IO;
+use flowcore::model::name::{HasName, Name};
+use flowcore::model::process::Process::FlowProcess;
+use flowcore::model::route::{HasRoute, Route};
+use flowcore::model::schema;
+
+use crate::compiler::compile::CompilerTables;
+use crate::compiler::gatherer;
+use crate::errors::{Result, ResultExt};
+
+/// A `Lint` is a problem found in a flow that does not prevent it being compiled, but that is
+/// probably a mistake in its definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// A pure function with no connections from its outputs, so running it has no effect
+    UnusedFunction(Route),
+    /// An input of a function that is not connected to, nor initialized, so it can never run
+    UnconnectedInput(Route),
+    /// An output of an impure function none of whose outputs are connected to any input, so
+    /// values sent are discarded
+    UnconnectedOutput(Route),
+    /// A process alias used more than once in the flow at the `Route`, so only the last process
+    /// referenced with that alias is used
+    ShadowedName(Route, Name),
+    /// A function that can never run, as one of its inputs is only connected to from functions
+    /// that can never run themselves
+    AlwaysBlocked(Route),
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Lint::UnusedFunction(route) =>
+                write!(f, "Function at route '{route}' is unused, as its outputs are not connected"),
+            Lint::UnconnectedInput(route) =>
+                write!(f, "Input at route '{route}' is not connected to nor initialized"),
+            Lint::UnconnectedOutput(route) =>
+                write!(f, "Output at route '{route}' is not connected to any input"),
+            Lint::ShadowedName(route, name) =>
+                write!(f, "Alias '{name}' is used for more than one process in flow '{route}'"),
+            Lint::AlwaysBlocked(route) =>
+                write!(f, "Function at route '{route}' can never run, as an input is only \
+                connected to from functions that can never run"),
+        }
+    }
+}
+
+/// Check that all Functions have connections to all their inputs or return an error
+/// All inputs must be connected and receive values at run-time or a function can never run
+/// This is different from Outputs can be used selectively, and so if one is not connected that
+/// is not a problem for compiling or running necessarily.
+///
+/// # Errors
+///
+/// Returns an error for the first input found that is not connected nor initialized, or that
+/// has a `Constant` initializer as well as a connection to it
+pub fn check_function_inputs(tables: &CompilerTables) -> Result<()> {
+    info!("\n=== Compiler: Checking all Function Inputs are connected");
+    for function in &tables.functions {
+        for input in function.get_inputs() {
+            if input.get_initializer().is_none() && input.get_flow_initializer().is_none()
+                && tables.connection_to(input.route()).is_none() {
+                bail!("Input at route '{}' is not connected to nor initialized", input.route());
+            }
+
+            // If has a Constant initializer and a connections then flag that as an error
+            if got_constant_initializer(input) && tables.connection_to(input.route()).is_some() {
+                bail!("Input at route '{}' has a 'Constant' initializer and a connection to it",
+                                       input.route());
+            }
+        }
+    }
+
+    info!("No problems found. All functions have connections to all their inputs");
+    Ok(())
+}
+
+/// Check that the JSON Schemas declared for function inputs are valid, and that the values of
+/// the initializers of those inputs conform to them
+///
+/// # Errors
+///
+/// Returns an error if a schema is not a valid JSON Schema, or if the value of an initializer
+/// does not conform to the schema of its input
+pub fn check_input_schemas(tables: &CompilerTables) -> Result<()> {
+    info!("\n=== Compiler: Checking Function Input initializers conform to their schemas");
+    for function in &tables.functions {
+        for input in function.get_inputs() {
+            if let Some(schema) = input.get_schema() {
+                schema::check_schema(schema).chain_err(||
+                    format!("Schema of input at route '{}' is not valid", input.route()))?;
+
+                let initializers = [input.get_initializer(), input.get_flow_initializer()];
+                for initializer in initializers.into_iter().flatten() {
+                    schema::validate(schema, initializer.get_value()).chain_err(||
+                        format!("Initializer of input at route '{}' does not conform to its schema",
+                                input.route()))?;
+                }
+            }
+        }
+    }
+
+    info!("No problems found. All initializers conform to their input's schema");
+    Ok(())
+}
+
+fn got_constant_initializer(input: &IO) -> bool {
+    matches!(input.get_initializer(), Some(Always(_)))
+}
+
+/// Check that some impure function producing a side effect is called or return an error
+///
+/// # Errors
+///
+/// Returns an error if no function in the flow produces a side effect
+pub fn check_side_effects(tables: &CompilerTables) -> Result<()> {
+    info!("\n=== Compiler: Checking flow has side-effects");
+    for function in &tables.functions {
+        // Until we separate impure inputs and side-effects we will assume that if a function
+        // is impure and has inputs then it has side-effects
+        if function.is_impure() && !function.inputs.is_empty() {
+            info!("Flow has side effects from 1 or more functions");
+            return Ok(());
+        }
+    }
+
+    bail!("Flow has no side-effects")
+}
+
+/// Analyse a parsed flow, without compiling it, for problems that are probably mistakes in its
+/// definition and return a list of the `Lint`s found.
+///
+/// # Errors
+///
+/// Returns an error if the functions and connections of the flow cannot be gathered and
+/// connected, for example if the types of the source and destination of a connection that
+/// crosses a flow boundary are incompatible
+pub fn lint(flow: &FlowDefinition) -> Result<Vec<Lint>> {
+    info!("\n=== Compiler: Linting flow '{}'", flow.route());
+    let mut tables = CompilerTables::new();
+    gatherer::gather_functions_and_connections(flow, &mut tables)?;
+    gatherer::collapse_connections(&mut tables)?;
+
+    let mut lints = shadowed_names(flow);
+    lints.append(&mut unconnected_ios(&tables));
+    lints.append(&mut always_blocked(&tables));
+
+    info!("{} problems found", lints.len());
+    Ok(lints)
+}
+
+// Find process aliases used more than once in a flow or any of its sub-flows
+fn shadowed_names(flow: &FlowDefinition) -> Vec<Lint> {
+    let mut lints = vec![];
+    let mut aliases = BTreeSet::new();
+    for process_ref in &flow.process_refs {
+        if !aliases.insert(process_ref.alias()) {
+            lints.push(Lint::ShadowedName(flow.route().clone(), process_ref.alias().clone()));
+        }
+    }
+
+    for subprocess in flow.subprocesses.values() {
+        if let FlowProcess(subflow) = subprocess {
+            lints.append(&mut shadowed_names(subflow));
+        }
+    }
+
+    lints
+}
+
+// Find functions with inputs that are not connected, and functions none of whose outputs are
+// connected, as it is normal to only use some of the outputs of a function
+fn unconnected_ios(tables: &CompilerTables) -> Vec<Lint> {
+    let mut lints = vec![];
+    for function in &tables.functions {
+        for input in function.get_inputs() {
+            if input.get_initializer().is_none() && input.get_flow_initializer().is_none()
+                && tables.connection_to(input.route()).is_none() {
+                lints.push(Lint::UnconnectedInput(input.route().clone()));
+            }
+        }
+
+        let unconnected_outputs: Vec<Route> = function.get_outputs().iter()
+            .filter(|output| !connected_from(tables, output.route()))
+            .map(|output| output.route().clone())
+            .collect();
+        if !unconnected_outputs.is_empty() &&
+            unconnected_outputs.len() == function.get_outputs().len() {
+            if function.is_impure() {
+                lints.extend(unconnected_outputs.into_iter().map(Lint::UnconnectedOutput));
+            } else {
+                lints.push(Lint::UnusedFunction(function.route().clone()));
+            }
+        }
+    }
+
+    lints
+}
+
+// Is there a connection from the IO at `route`, or from a sub-route of it
+fn connected_from(tables: &CompilerTables, route: &Route) -> bool {
+    tables.collapsed_connections.iter()
+        .any(|connection| connection.from_io().route().sub_route_of(route).is_some())
+}
+
+// Find the functions that can never run as one of their inputs can never receive a value.
+//
+// Starting with no functions that can run, repeatedly find functions all of whose inputs are
+// initialized or connected to from a function that can run, until no more are found.
+// Functions with unconnected inputs are not reported as they are found by `unconnected_ios()`
+fn always_blocked(tables: &CompilerTables) -> Vec<Lint> {
+    let mut can_run = BTreeSet::new();
+    loop {
+        let runnable: Vec<usize> = tables.functions.iter()
+            .filter(|function| !can_run.contains(&function.get_id()))
+            .filter(|function| function.get_inputs().iter()
+                .all(|input| input_can_receive(tables, input, &can_run)))
+            .map(FunctionDefinition::get_id)
+            .collect();
+        if runnable.is_empty() {
+            break;
+        }
+        can_run.extend(runnable);
+    }
+
+    tables.functions.iter()
+        .filter(|function| !can_run.contains(&function.get_id()))
+        .filter(|function| function.get_inputs().iter().all(|input|
+            input.get_initializer().is_some() || input.get_flow_initializer().is_some()
+                || tables.connection_to(input.route()).is_some()))
+        .map(|function| Lint::AlwaysBlocked(function.route().clone()))
+        .collect()
+}
+
+// Can the `input` receive a value: it is initialized, or connected to from a function that can
+// run (or a source that is not a function, such as an initialized flow input)
+fn input_can_receive(tables: &CompilerTables, input: &IO, can_run: &BTreeSet<usize>) -> bool {
+    if input.get_initializer().is_some() || input.get_flow_initializer().is_some() {
+        return true;
+    }
+
+    tables.collapsed_connections.iter()
+        .filter(|connection| connection.to_io().route() == input.route())
+        .any(|connection| {
+            match tables.functions.iter().find(|function|
+                connection.from_io().route().sub_route_of(function.route()).is_some()) {
+                Some(source) => can_run.contains(&source.get_id()),
+                None => true,
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+    use url::Url;
+
+    use flowcore::model::connection::Connection;
+    use flowcore::model::datatype::NUMBER_TYPE;
+    use flowcore::model::flow_definition::FlowDefinition;
+    use flowcore::model::function_definition::FunctionDefinition;
+    use flowcore::model::input::InputInitializer::Once;
+    use flowcore::model::io::{IO, IOType};
+    use flowcore::model::process_reference::ProcessReference;
+    use flowcore::model::route::Route;
+
+    use crate::compiler::compile::CompilerTables;
+
+    use super::Lint;
+
+    // A function with one input and one (default) output at routes derived from `name`
+    fn test_function(name: &str, impure: bool, id: usize) -> FunctionDefinition {
+        FunctionDefinition::new(
+            name.into(),
+            impure,
+            format!("file:///fake/{name}"),
+            name.into(),
+            vec![IO::new_named(vec!(NUMBER_TYPE.into()), format!("/root/{name}/in"), "in")],
+            vec![IO::new(vec!(NUMBER_TYPE.into()), format!("/root/{name}"))],
+            Url::parse(&format!("file:///fake/{name}")).expect("Could not parse Url"),
+            Route::from(format!("/root/{name}")),
+            None,
+            None,
+            vec!(),
+            id,
+            0,
+        )
+    }
+
+    fn test_connection(from: &str, to: &str) -> Connection {
+        let from_route = Route::from(format!("/root/{from}"));
+        let to_route = Route::from(format!("/root/{to}/in"));
+        let mut connection = Connection::new(from_route.clone(), to_route.clone());
+        connection.from_io_mut().set_route(&from_route, &IOType::FunctionOutput);
+        connection.to_io_mut().set_route(&to_route, &IOType::FunctionInput);
+        connection
+    }
+
+    // A pure function "a" whose output is connected to the input of impure function "b"
+    fn test_tables() -> CompilerTables {
+        let mut tables = CompilerTables::new();
+        tables.functions = vec![test_function("a", false, 0), test_function("b", true, 1)];
+        tables.collapsed_connections = vec![test_connection("a", "b")];
+        tables
+    }
+
+    #[test]
+    fn unconnected_input() {
+        let lints = super::unconnected_ios(&test_tables());
+        assert_eq!(lints, vec![Lint::UnconnectedInput("/root/a/in".into()),
+                               Lint::UnconnectedOutput("/root/b".into())]);
+    }
+
+    #[test]
+    fn unused_function() {
+        let mut tables = test_tables();
+        tables.collapsed_connections = vec![test_connection("b", "a")];
+        let lints = super::unconnected_ios(&tables);
+        assert!(lints.contains(&Lint::UnusedFunction("/root/a".into())));
+        assert!(!lints.contains(&Lint::UnconnectedOutput("/root/a".into())));
+    }
+
+    #[test]
+    fn no_functions_blocked() {
+        let mut tables = test_tables();
+        if let Some(function) = tables.functions.get_mut(0) {
+            if let Some(input) = function.inputs.get_mut(0) {
+                input.set_initializer(Some(Once(json!(1)))).expect("Could not set initializer");
+            }
+        }
+        assert!(super::always_blocked(&tables).is_empty());
+    }
+
+    #[test]
+    fn loop_without_initializer_always_blocked() {
+        let mut tables = test_tables();
+        tables.collapsed_connections.push(test_connection("b", "a"));
+        assert_eq!(super::always_blocked(&tables),
+                   vec![Lint::AlwaysBlocked("/root/a".into()), Lint::AlwaysBlocked("/root/b".into())]);
+    }
+
+    #[test]
+    fn shadowed_name() {
+        let process_ref = ProcessReference {
+            alias: "add".into(),
+            source: "lib://flowstdlib/math/add".into(),
+            initializations: BTreeMap::new(),
+            parameters: BTreeMap::new(),
+            inline: None,
+        };
+        let flow = FlowDefinition {
+            route: "/root".into(),
+            process_refs: vec![process_ref.clone(), process_ref],
+            ..FlowDefinition::default()
+        };
+        assert_eq!(super::shadowed_names(&flow),
+                   vec![Lint::ShadowedName("/root".into(), "add".into())]);
+    }
+
+    // Tables with function "a" whose input has `schema` and is initialized with `value`
+    fn tables_with_schema(schema: serde_json::Value, value: serde_json::Value) -> CompilerTables {
+        let mut tables = test_tables();
+        if let Some(function) = tables.functions.first_mut() {
+            for input in function.get_mut_inputs() {
+                input.set_schema(Some(schema.clone()));
+                input.set_initializer(Some(Once(value.clone()))).expect("Could not set initializer");
+            }
+        }
+        tables
+    }
+
+    #[test]
+    fn initializer_conforms_to_schema() {
+        let tables = tables_with_schema(json!({"minimum": 0}), json!(1));
+        assert!(super::check_input_schemas(&tables).is_ok());
+    }
+
+    #[test]
+    fn initializer_does_not_conform_to_schema() {
+        let tables = tables_with_schema(json!({"minimum": 0}), json!(-1));
+        assert!(super::check_input_schemas(&tables).is_err());
+    }
+
+    #[test]
+    fn invalid_schema() {
+        let tables = tables_with_schema(json!({"minimum": "zero"}), json!(1));
+        assert!(super::check_input_schemas(&tables).is_err());
+    }
+}
@@ -7,16 +7,20 @@ use url::Url;
 
 use flowcore::model::connection::Connection;
 use flowcore::model::flow_definition::FlowDefinition;
+use flowcore::model::flow_manifest::FlowManifest;
 use flowcore::model::function_definition::FunctionDefinition;
 use flowcore::model::name::HasName;
 use flowcore::model::output_connection::{OutputConnection, Source};
 use flowcore::model::output_connection::Source::{Input, Output};
+use flowcore::model::process::Process::{FlowProcess, FunctionProcess};
 use flowcore::model::route::{HasRoute, Route};
+use flowcore::provider::Provider;
 
-use crate::compiler::compile_wasm;
-use crate::errors::{Result, ResultExt};
+use crate::checker;
+use crate::compiler::{compile_wasm, parser};
+use crate::errors::{bail, Result, ResultExt};
+use crate::generator::generate;
 
-use super::checker;
 use super::gatherer;
 use super::optimizer;
 
@@ -127,6 +131,52 @@ pub fn compile(flow: &FlowDefinition,
                #[cfg(feature = "debugger")]
                source_urls: &mut BTreeMap<String, Url>,
 ) -> Result<CompilerTables> {
+    let mut tables = connect_functions(flow, optimize)?;
+    compile_supplied_implementations(
+        output_dir,
+        &mut tables,
+        skip_building,
+        optimize,
+        source_urls,
+    ).chain_err(|| "Could not compile to wasm the flow's supplied implementation(s)")?;
+
+    Ok(tables)
+}
+
+/// Compile the flow at `url` to a manifest in memory, getting its definition, and the definitions
+/// of the library and context functions it uses, from `provider`. No files are read or written
+/// except by `provider`, so a flow can be compiled where there is no file system, such as in a
+/// browser, using a [`MemoryProvider`][flowcore::memory_provider::MemoryProvider].
+///
+/// Implementations of functions supplied with a flow are compiled to WASM using the file system,
+/// so flows that supply any are not compiled this way.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - `url` is not the definition of a flow, or could not be parsed
+/// - the flow supplies implementations of any functions
+/// - the flow cannot be compiled, as with [`compile`]
+pub fn compile_in_memory(url: &Url, provider: &dyn Provider) -> Result<FlowManifest> {
+    let flow = match parser::parse(url, provider)? {
+        FlowProcess(flow) => flow,
+        FunctionProcess(_) => bail!("'{}' is the definition of a function, not a flow", url),
+    };
+
+    let tables = connect_functions(&flow, true)?;
+    if let Some(function) = tables.functions.iter().find(|function|
+        function.get_lib_reference().is_none() && function.get_context_reference().is_none()) {
+        bail!("Function '{}' supplied with flow '{}' needs a file system to be compiled to WASM",
+            function.name(), flow.name);
+    }
+
+    generate::create_manifest(&flow, false, url, &tables,
+                              #[cfg(feature = "debugger")] BTreeMap::new())
+}
+
+// Gather the functions of `flow` and the connections between them, collapsing connections between
+// flows into connections directly between functions and checking the result
+fn connect_functions(flow: &FlowDefinition, optimize: bool) -> Result<CompilerTables> {
     let mut tables = CompilerTables::new();
 
     gatherer::gather_functions_and_connections(flow, &mut tables)?;
@@ -135,15 +185,9 @@ pub fn compile(flow: &FlowDefinition,
         optimizer::optimize(&mut tables);
     }
     checker::check_function_inputs(&tables)?;
+    checker::check_input_schemas(&tables)?;
     checker::check_side_effects(&tables)?;
     configure_output_connections(&mut tables)?;
-    compile_supplied_implementations(
-        output_dir,
-        &mut tables,
-        skip_building,
-        optimize,
-        source_urls,
-    ).chain_err(|| "Could not compile to wasm the flow's supplied implementation(s)")?;
 
     Ok(tables)
 }
@@ -237,7 +281,8 @@ fn configure_output_connections(tables: &mut CompilerTables) -> Result<()> {
         debug!("  Source output route = '{}' --> function #{}:{}",
                source, destination_function_id, destination_input_index);
 
-        let output_conn = OutputConnection::new(
+        #[cfg_attr(not(feature = "debugger"), allow(unused_mut))]
+        let mut output_conn = OutputConnection::new(
             source,
             *destination_function_id,
             *destination_input_index,
@@ -246,6 +291,8 @@ fn configure_output_connections(tables: &mut CompilerTables) -> Result<()> {
             #[cfg(feature = "debugger")]
                 connection.name().to_string(),
         );
+        #[cfg(feature = "debugger")]
+        output_conn.set_source_location(connection.source_location().cloned());
         source_function.add_output_connection(output_conn);
     }
 
@@ -325,6 +372,74 @@ mod test {
 
     use crate::compiler::compile::{compile, get_paths};
 
+    mod compile_in_memory_tests {
+        use url::Url;
+
+        use flowcore::memory_provider::MemoryProvider;
+
+        use super::super::compile_in_memory;
+
+        const STDOUT_DEFINITION: &str =
+            include_str!("../../../../flowr/src/bin/flowrcli/context/stdio/stdout.toml");
+
+        fn url(url: &str) -> Url {
+            Url::parse(url).expect("Could not parse Url")
+        }
+
+        fn provider(root: &str) -> MemoryProvider {
+            MemoryProvider::default()
+                .with_content(url("memory://hello/root.toml"), root)
+                .with_content(url("context://stdio/stdout/stdout.toml"), STDOUT_DEFINITION)
+        }
+
+        #[test]
+        fn compiles_flow_without_files() {
+            let provider = provider("flow = \"hello\"
+
+[[process]]
+source = \"context://stdio/stdout\"
+input.default = { once = \"Hello World!\" }
+");
+            let manifest = compile_in_memory(&url("memory://hello"), &provider)
+                .expect("Could not compile flow in memory");
+            assert_eq!(manifest.functions().len(), 1);
+            assert!(manifest.get_context_references().contains(&url("context://stdio/stdout")));
+        }
+
+        #[test]
+        fn supplied_function_not_compiled() {
+            let provider = provider("flow = \"hello\"
+
+[[process]]
+source = \"double\"
+input.default = { once = 1 }
+
+[[process]]
+source = \"context://stdio/stdout\"
+
+[[connection]]
+from = \"double\"
+to = \"stdout\"
+").with_content(url("memory://hello/double.toml"), "function = \"double\"
+source = \"double.rs\"
+
+[[input]]
+
+[[output]]
+");
+            match compile_in_memory(&url("memory://hello"), &provider) {
+                Err(e) => assert!(e.to_string().contains("needs a file system"), "{e}"),
+                Ok(_) => panic!("A flow supplying a function should not compile in memory"),
+            }
+        }
+
+        #[test]
+        fn function_is_not_a_flow() {
+            let provider = provider("flow = \"hello\"");
+            assert!(compile_in_memory(&url("context://stdio/stdout"), &provider).is_err());
+        }
+    }
+
     mod get_source_tests {
         use std::collections::BTreeMap;
 
@@ -450,6 +565,8 @@ mod test {
             alias: function.alias().to_owned(),
             source: function.get_source_url().to_string(),
             initializations: BTreeMap::new(),
+            parameters: BTreeMap::new(),
+            inline: None,
         };
 
         let _test_flow = FlowDefinition::default();
@@ -67,6 +67,15 @@ pub fn compile_implementation(
                     cargo_build::run(implementation_source_path, cargo_target_dir,
                                      wasm_destination, optimize)?;
                 },
+                // a pre-built wasm implementation just needs copying to the output directory
+                "wasm" => {
+                    if let Some(parent) = wasm_destination.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::copy(implementation_source_path, wasm_destination)
+                        .chain_err(|| format!("Could not copy wasm implementation '{}' to '{}'",
+                            implementation_source_path.display(), wasm_destination.display()))?;
+                },
                 _ => bail!(
                     "Unknown build type '{}' for function at '{}'",
                     implementation_source_path.display(),
@@ -436,6 +445,45 @@ mod test {
         assert!(!built);
     }
 
+    #[test]
+    fn test_prebuilt_wasm_implementation_copied() {
+        let source_dir = tempdir()
+            .expect("Could not create temporary directory during testing")
+            .into_path();
+        write(source_dir.join("double.wasm"), b"wasm during testing")
+            .expect("Could not write to file during testing");
+
+        let mut function = test_function();
+        function.set_source("double.wasm");
+        function.build_type = "wasm".into();
+        function.source_url = Url::from_file_path(source_dir.join("root.toml"))
+            .expect("Could not create source Url");
+
+        let wasm_output_dir = tempdir()
+            .expect("Could not create temporary directory during testing")
+            .into_path();
+        let (implementation_source_path, wasm_destination) = compile::get_paths(&wasm_output_dir, &function)
+            .expect("Could not get paths for compiling");
+
+        let mut source_urls = BTreeMap::<String, Url>::new();
+
+        let built = super::compile_implementation(
+            wasm_output_dir.as_path(),
+            source_dir.join("target"),
+            &wasm_destination,
+            &implementation_source_path,
+            &mut function,
+            false,
+            false,
+            #[cfg(feature = "debugger")]
+            &mut source_urls
+        ).expect("compile_implementation() failed");
+
+        assert!(built);
+        assert!(wasm_output_dir.join("double.wasm").exists());
+        assert_eq!(function.get_implementation(), wasm_destination.to_string_lossy());
+    }
+
     #[test]
     fn test_compile_implementation_invalid_paths() {
         let mut function = test_function();
@@ -113,8 +113,12 @@ pub fn collapse_connections(tables: &mut CompilerTables) -> Result<()> {
                         *collapsed_connection.to_io_mut() = destination_io;
                         DataType::compatible_types(collapsed_connection.from_io().datatypes(),
                                                    collapsed_connection.to_io().datatypes(), &source_subroute)
-                            .chain_err(|| format!("Incompatible types in collapsed connection from '{}' to '{}'",
-                                                  collapsed_connection.from_io().route(), collapsed_connection.to_io().route()))?;
+                            .chain_err(|| format!("Incompatible types in collapsed connection from '{}' (defined in '{}') \
+                                to '{}' (defined in '{}')",
+                                collapsed_connection.from_io().route(),
+                                function_source_url(tables, collapsed_connection.from_io().route()),
+                                collapsed_connection.to_io().route(),
+                                function_source_url(tables, collapsed_connection.to_io().route())))?;
                         debug!("\tIndirect connection {}", collapsed_connection);
                         collapsed_connections.push(collapsed_connection);
                     }
@@ -164,6 +168,13 @@ pub fn collapse_connections(tables: &mut CompilerTables) -> Result<()> {
     Ok(())
 }
 
+// The url of the definition of the function that has the IO at `io_route`, for use in messages
+fn function_source_url(tables: &CompilerTables, io_route: &Route) -> String {
+    tables.functions.iter()
+        .find(|function| io_route.sub_route_of(function.route()).is_some())
+        .map_or_else(|| "unknown".into(), |function| function.get_source_url().to_string())
+}
+
 /*
     Given a route we have a connection to, attempt to find the final destinations, potentially
     traversing multiple intermediate connections (recursively) until we find any that do not
@@ -11,6 +11,5 @@ pub mod compile;
 pub mod compile_wasm;
 
 mod cargo_build;
-mod checker;
-mod gatherer;
+pub(crate) mod gatherer;
 mod optimizer;
@@ -1,12 +1,16 @@
 #[cfg(feature = "debugger")]
 use std::collections::BTreeMap;
 
+use error_chain::bail;
 use log::{debug, info, trace};
+use serde_json::Value;
 use url::Url;
 
-use flowcore::deserializers::deserializer::get;
+use flowcore::deserializers::deserializer::{get, DEFINITION_FILE_EXTENSIONS};
+use flowcore::deserializers::env_vars;
 use flowcore::model::flow_definition::FlowDefinition;
 use flowcore::model::flow_manifest::Cargo;
+use flowcore::model::function_definition::FunctionDefinition;
 use flowcore::model::input::InputInitializer;
 use flowcore::model::metadata::MetaData;
 use flowcore::model::name::HasName;
@@ -14,7 +18,9 @@ use flowcore::model::name::Name;
 use flowcore::model::process::Process;
 use flowcore::model::process::Process::FlowProcess;
 use flowcore::model::process::Process::FunctionProcess;
+use flowcore::model::process_reference::ProcessReference;
 use flowcore::model::route::Route;
+use flowcore::model::source_location::{line_of, SourceLocation};
 use flowcore::provider::Provider;
 
 use crate::errors::{Result, ResultExt};
@@ -83,6 +89,7 @@ pub fn parse(
         url,
         provider,
         &BTreeMap::new(),
+        &BTreeMap::new(),
         0,
     )
 }
@@ -96,10 +103,11 @@ fn parse_process(
     url: &Url,
     provider: &dyn Provider,
     initializations: &BTreeMap<String, InputInitializer>,
+    parameters: &BTreeMap<String, Value>,
     level: usize,
 ) -> Result<Process> {
     let (resolved_url, reference) = provider
-        .resolve_url(url, "root", &["toml"])
+        .resolve_url(url, "root", DEFINITION_FILE_EXTENSIONS)
         .chain_err(|| format!("Could not resolve the url: '{url}'"))?;
 
     let contents = provider
@@ -114,8 +122,16 @@ fn parse_process(
     let deserializer = get::<Process>(&resolved_url)?;
     debug!(
         "Loading process from url = '{resolved_url}' with deserializer: '{}'", deserializer.name());
-    let mut process = deserializer
-        .deserialize(&content, Some(&resolved_url))
+    // only local definitions are trusted with the values of environment variables, as a remote
+    // or library definition could reference them in urls, which would disclose them when fetched
+    let local = url.scheme() == "file" && resolved_url.scheme() == "file";
+    let deserialized = if local && content.contains("${") {
+        deserialize_with_env_vars(&content, &resolved_url)
+    } else {
+        deserializer.deserialize(&content, Some(&resolved_url))
+    };
+    let mut process = deserialized
+        .map_err(|e| definition_error(&content, &resolved_url).unwrap_or(e))
         .chain_err(|| format!("Could not parse a valid flow process from '{url}'"))?;
 
     match process {
@@ -128,9 +144,14 @@ fn parse_process(
                 initializations,
             )?;
             *flow_count += 1;
+            // locate references and connections in the definition before parameters are substituted
+            let reference_locations = process_ref_locations(flow, &content);
+            flow.set_connection_locations(&content);
+            flow.set_parameters(parameters)?;
             debug!("Deserialized the Flow, now parsing sub-processes");
             parse_process_refs(
                 flow,
+                reference_locations,
                 flow_count,
                 provider,
                 level,
@@ -138,6 +159,10 @@ fn parse_process(
             flow.build_connections(level)?;
         }
         FunctionProcess(ref mut function) => {
+            if !parameters.is_empty() {
+                bail!("Parameters were supplied to function '{}' at '{url}', but only flows \
+                    can declare parameters", function.name());
+            }
             function.config(
                 url,
                 &resolved_url,
@@ -153,6 +178,27 @@ fn parse_process(
     Ok(process)
 }
 
+// Deserialize a process definition that may reference environment variables in its string values,
+// substituting their values before deserializing it into a `Process`
+fn deserialize_with_env_vars(content: &str, url: &Url) -> flowcore::errors::Result<Process> {
+    let mut definition: Value = get(url)?.deserialize(content, Some(url))?;
+    env_vars::substitute(&mut definition)?;
+    Ok(serde_json::from_value(definition)?)
+}
+
+// Deserializing an untagged `Process` loses the details (e.g. line and column) of why the content
+// is not valid, so deserialize it again as the type of definition it declares itself to be
+fn definition_error(content: &str, url: &Url) -> Option<flowcore::errors::Error> {
+    let definition: serde_json::Value = get(url).ok()?.deserialize(content, Some(url)).ok()?;
+    if definition.get("flow").is_some() {
+        get::<FlowDefinition>(url).ok()?.deserialize(content, Some(url)).err()
+    } else if definition.get("function").is_some() {
+        get::<FunctionDefinition>(url).ok()?.deserialize(content, Some(url)).err()
+    } else {
+        None
+    }
+}
+
 /// Parses the meta-data struct of a flow definition
 ///
 /// load library metadata from the given url using the provider.
@@ -189,34 +235,66 @@ pub fn parse_metadata(url: &Url, provider: &dyn Provider) -> Result<(MetaData, L
 }
 
 /*
-    Parse sub-processes from the process_refs in a flow
+    Find where in a flow's definition, with contents `content`, each of its process_refs is
+*/
+fn process_ref_locations(flow: &FlowDefinition, content: &str) -> Vec<SourceLocation> {
+    let mut found: Vec<(&str, &str)> = vec![];
+    let mut locations = vec![];
+    for process_ref in &flow.process_refs {
+        // inline functions are located by their name, as they have no source
+        let (key, value) = match &process_ref.inline {
+            Some(function) => ("function", function.name.as_str()),
+            None => ("source", process_ref.source.as_str()),
+        };
+        let occurrence = found.iter().filter(|previous| **previous == (key, value)).count();
+        locations.push(SourceLocation::new(&flow.source_url,
+            line_of(content, key, value, occurrence)));
+        found.push((key, value));
+    }
+    locations
+}
+
+/*
+    Parse sub-processes from the process_refs in a flow, that are at `reference_locations`
 */
 fn parse_process_refs(
     flow: &mut FlowDefinition,
+    reference_locations: Vec<SourceLocation>,
     flow_count: &mut usize,
     provider: &dyn Provider,
     level: usize,
 ) -> Result<()> {
-    for process_ref in &mut flow.process_refs {
-        let subprocess_url = flow
-            .source_url
-            .join(&process_ref.source)
-            .map_err(|e| e.to_string())?;
-        let process = parse_process(
-            &flow.route,
-            process_ref.alias(),
-            flow.id,
-            flow_count,
-            &subprocess_url,
-            provider,
-            &process_ref.initializations,
-            level + 1,
-        )?;
+    for (process_ref, reference_location) in flow.process_refs.iter_mut().zip(reference_locations) {
+        let mut process = match &process_ref.inline {
+            Some(definition) => parse_inline_function(
+                process_ref,
+                definition,
+                &flow.source_url,
+                &flow.route,
+                flow.id,
+            )?,
+            None => {
+                let subprocess_url = process_ref.source_url(&flow.source_url)?;
+                parse_process(
+                    &flow.route,
+                    process_ref.alias(),
+                    flow.id,
+                    flow_count,
+                    &subprocess_url,
+                    provider,
+                    &process_ref.initializations,
+                    &process_ref.parameters,
+                    level + 1,
+                )?
+            }
+        };
         process_ref.set_alias(process.name());
 
         // runtime needs references to library functions to be able to load the implementations at load time
         // library flow definitions are "compiled down" to just library function references at compile time.
-        if let FunctionProcess(function) = &process {
+        if let FunctionProcess(ref mut function) = process {
+            function.set_reference_location(reference_location);
+
             if let Some(lib_ref) = function.get_lib_reference() {
                 flow.lib_references.insert(lib_ref.clone());
             }
@@ -233,6 +311,36 @@ fn parse_process_refs(
     Ok(())
 }
 
+/*
+    Create a function process from the definition of an anonymous function written inline in
+    `process_ref` in the flow at `flow_url`. Its implementation is relative to the flow definition.
+*/
+fn parse_inline_function(
+    process_ref: &ProcessReference,
+    definition: &FunctionDefinition,
+    flow_url: &Url,
+    flow_route: &Route,
+    flow_id: usize,
+) -> Result<Process> {
+    if !process_ref.parameters.is_empty() {
+        bail!("Parameters were supplied to inline function '{}' in '{flow_url}', but only flows \
+            can declare parameters", definition.name);
+    }
+
+    let mut function = definition.clone();
+    function.config(
+        flow_url,
+        flow_url,
+        flow_route,
+        process_ref.alias(),
+        flow_id,
+        None,
+        &process_ref.initializations,
+    ).chain_err(|| format!("Invalid inline function '{}' in '{flow_url}'", definition.name))?;
+
+    Ok(FunctionProcess(function))
+}
+
 #[cfg(test)]
 mod test {
     use url::Url;
@@ -1,5 +1,6 @@
 #[cfg(feature = "debugger")]
 use std::collections::BTreeMap;
+use std::fs;
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -7,10 +8,12 @@ use std::path::{Path, PathBuf};
 use log::info;
 use url::Url;
 
+use flowcore::content_hash;
 use flowcore::model::flow_definition::FlowDefinition;
 use flowcore::model::flow_manifest::{FlowManifest, DEFAULT_MANIFEST_FILENAME};
 use flowcore::model::function_definition::FunctionDefinition;
 use flowcore::model::input::Input;
+use flowcore::model::manifest_format::ManifestFormat;
 use flowcore::model::metadata::MetaData;
 #[cfg(feature = "debugger")]
 use flowcore::model::name::HasName;
@@ -59,7 +62,8 @@ pub fn create_manifest(
     Ok(manifest)
 }
 
-/// Generate a manifest for the flow in JSON that can be used to execute it
+/// Generate a manifest for the flow in `format` that can be used to execute it. Any manifest
+/// previously generated in a different format is removed, so it cannot be loaded instead.
 ///
 /// # Errors
 ///
@@ -67,7 +71,7 @@ pub fn create_manifest(
 /// - Cannot create the manifest file
 /// - Cannot create a `Url` from the `destination` `Path`
 /// - Cannot create a `FlowManifest` from the `FlowDefinition`
-/// - Cannot write the contents of the manifest to the file
+/// - Cannot serialize or write the contents of the manifest to the file
 ///
 pub fn write_flow_manifest(
     flow: &FlowDefinition,
@@ -75,12 +79,19 @@ pub fn write_flow_manifest(
     destination: &Path,
     tables: &CompilerTables,
     #[cfg(feature = "debugger")] source_urls: BTreeMap<String, Url>,
+    format: ManifestFormat,
 ) -> Result<PathBuf> {
     info!("\n==== Generating Manifest");
 
     let mut filename = destination.to_path_buf();
     filename.push(DEFAULT_MANIFEST_FILENAME);
-    filename.set_extension("json");
+    for stale_format in [ManifestFormat::Json, ManifestFormat::Cbor] {
+        filename.set_extension(stale_format.extension());
+        if stale_format != format && filename.is_file() {
+            fs::remove_file(&filename).chain_err(|| "Could not remove stale manifest file")?;
+        }
+    }
+    filename.set_extension(format.extension());
     let mut manifest_file =
         File::create(&filename).chain_err(|| "Could not create manifest file")?;
     let manifest_url =
@@ -96,11 +107,7 @@ pub fn write_flow_manifest(
         .chain_err(|| "Could not create manifest from parsed flow and compiler tables")?;
 
     manifest_file
-        .write_all(
-            serde_json::to_string_pretty(&manifest)
-                .chain_err(|| "Could not pretty format the manifest JSON contents")?
-                .as_bytes(),
-        )
+        .write_all(&format.serialize(&manifest)?)
         .chain_err(|| "Could not write manifest data bytes to created manifest file")?;
 
     Ok(filename)
@@ -138,7 +145,7 @@ fn function_to_runtimefunction(
         runtime_inputs.push(Input::try_from(input)?);
     }
 
-    Ok(RuntimeFunction::new(
+    let mut runtime_function = RuntimeFunction::new(
         #[cfg(feature = "debugger")]
             name,
         #[cfg(feature = "debugger")]
@@ -149,7 +156,36 @@ fn function_to_runtimefunction(
         function.get_flow_id(),
         function.get_output_connections(),
         debug_symbols,
-    ))
+    );
+    #[cfg(feature = "debugger")]
+    if debug_symbols {
+        runtime_function.set_source_location(function.get_reference_location().cloned());
+    }
+    runtime_function.set_retries(function.get_retries());
+    runtime_function.set_priority(function.get_priority());
+    runtime_function.set_pure(function.is_pure());
+    runtime_function.set_implementation_hash(provided_implementation_hash(function)?);
+
+    Ok(runtime_function)
+}
+
+/*
+    Get the SHA-256 hash of the WASM file of a provided implementation, so the runtime can verify
+    that it is the file that was compiled when it loads it
+*/
+fn provided_implementation_hash(function: &FunctionDefinition) -> Result<Option<String>> {
+    if function.get_lib_reference().is_some() || function.get_context_reference().is_some() {
+        return Ok(None);
+    }
+
+    let implementation_path = Path::new(function.get_implementation());
+    if !implementation_path.is_file() {
+        return Ok(None);
+    }
+
+    let contents = fs::read(implementation_path)
+        .chain_err(|| format!("Could not read implementation '{}'", implementation_path.display()))?;
+    Ok(Some(content_hash::sha256(&contents)))
 }
 
 /*
@@ -197,6 +233,9 @@ mod test {
     use flowcore::model::output_connection::Source::Output;
     use flowcore::model::output_connection::{OutputConnection, Source};
     use flowcore::model::route::Route;
+    use flowcore::model::runtime_function::Priority;
+    #[cfg(feature = "debugger")]
+    use flowcore::model::source_location::SourceLocation;
 
     use super::function_to_runtimefunction;
 
@@ -540,6 +579,85 @@ mod test {
         assert_eq!(serialized_process, expected.replace('\'', "\""));
     }
 
+    #[cfg(feature = "debugger")]
+    #[test]
+    fn function_source_location_generation() {
+        let mut function = test_function();
+        let location = SourceLocation::new(&Url::parse("file:///fake/root.toml")
+            .expect("Could not parse Url"), 7);
+        function.set_reference_location(location.clone());
+
+        let runtime_function = function_to_runtimefunction(
+            &Url::parse("file://test").expect("Couldn't parse test Url"),
+            &function,
+            true,
+        )
+            .expect("Could not convert compile time function to runtime function");
+        assert_eq!(runtime_function.source_location(), Some(&location));
+
+        let runtime_function = function_to_runtimefunction(
+            &Url::parse("file://test").expect("Couldn't parse test Url"),
+            &function,
+            false,
+        )
+            .expect("Could not convert compile time function to runtime function");
+        assert_eq!(runtime_function.source_location(), None);
+    }
+
+    #[test]
+    fn function_retries_generation() {
+        let mut function = test_function();
+        function.retries = Some(3);
+
+        let runtime_function = function_to_runtimefunction(
+            &Url::parse("file://test").expect("Couldn't parse test Url"),
+            &function,
+            false,
+        )
+            .expect("Could not convert compile time function to runtime function");
+
+        assert_eq!(runtime_function.retries(), Some(3));
+        let serialized_process = serde_json::to_string(&runtime_function)
+            .expect("Could not convert function content to json");
+        assert!(serialized_process.contains("\"retries\":3"));
+    }
+
+    #[test]
+    fn function_priority_generation() {
+        let mut function = test_function();
+        function.priority = Priority::High;
+
+        let runtime_function = function_to_runtimefunction(
+            &Url::parse("file://test").expect("Couldn't parse test Url"),
+            &function,
+            false,
+        )
+            .expect("Could not convert compile time function to runtime function");
+
+        assert_eq!(runtime_function.priority(), Priority::High);
+        let serialized_process = serde_json::to_string(&runtime_function)
+            .expect("Could not convert function content to json");
+        assert!(serialized_process.contains("\"priority\":\"high\""));
+    }
+
+    #[test]
+    fn function_pure_generation() {
+        let mut function = test_function();
+        function.pure = true;
+
+        let runtime_function = function_to_runtimefunction(
+            &Url::parse("file://test").expect("Couldn't parse test Url"),
+            &function,
+            false,
+        )
+            .expect("Could not convert compile time function to runtime function");
+
+        assert!(runtime_function.is_pure());
+        let serialized_process = serde_json::to_string(&runtime_function)
+            .expect("Could not convert function content to json");
+        assert!(serialized_process.contains("\"pure\":true"));
+    }
+
     #[test]
     fn function_with_array_element_output_generation() {
         let function = FunctionDefinition::new(
@@ -11,6 +11,10 @@
 /// that into a graph of [runtime functions][flowcore::model::runtime_function::RuntimeFunction]
 pub mod compiler;
 
+/// checks a compiled flow for errors that prevent it from being run, and "lints" it for problems
+/// that are probably mistakes, so they can be reported to the user
+pub mod checker;
+
 /// used to output a human readable version of the model and compiler tables to help debug
 /// compiler problems
 pub mod dumper;
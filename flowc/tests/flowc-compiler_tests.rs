@@ -416,4 +416,35 @@ fn json_indexing() {
     } else {
         panic!("Process loaded was not a flow");
     }
-}
\ No newline at end of file
+}
+#[test]
+fn incompatible_types_through_subflow() {
+    let meta_provider = MetaProvider::new(
+        helper::set_lib_search_path_to_project(),
+        helper::get_canonical_context_root(),
+    );
+    let path = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/incompatible-types-subflow/root.toml");
+    let process = parser::parse(&path, &meta_provider)
+        .expect("Could not parse flow");
+    if let FlowProcess(ref flow) = process {
+        let output_dir = tempdir().expect("A temp dir").into_path();
+
+        #[cfg(feature = "debugger")]
+            let mut source_urls = BTreeMap::<String, Url>::new();
+
+        match compile::compile(flow, &output_dir, false, false, &mut source_urls) {
+            Ok(_) => panic!("Flow with incompatible types should not compile"),
+            Err(e) => {
+                let message = e.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n");
+                assert!(message.contains("'/incompatible_types_subflow/get/string/2'"), "{message}");
+                assert!(message.contains("'/incompatible_types_subflow/write/file_write/bytes'"), "{message}");
+                assert!(message.contains("file_write.toml"), "{message}");
+                assert!(message.contains("'string' is not compatible with any of the destination types \
+                    ['bytes', 'array/number']"), "{message}");
+            }
+        }
+    } else {
+        panic!("Process loaded was not a flow");
+    }
+}
@@ -1,5 +1,8 @@
 #![allow(missing_docs)]
 
+use serde_json::json;
+use url::Url;
+
 use flowcore::meta_provider::MetaProvider;
 use flowcore::model::input::InputInitializer::{Always, Once};
 use flowcore::model::io::IO;
@@ -7,6 +10,7 @@ use flowcore::model::name::HasName;
 use flowcore::model::name::Name;
 use flowcore::model::process::Process::FlowProcess;
 use flowcore::model::process::Process::FunctionProcess;
+use flowcore::provider::Provider;
 use flowrclib::compiler::parser;
 
 #[path = "helper.rs"]
@@ -44,6 +48,29 @@ fn malformed_connection() {
     }
 }
 
+#[test]
+fn incompatible_types() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let path = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/incompatible-types/root.toml",
+    );
+    match parser::parse(&path, &meta_provider) {
+        Ok(_) => panic!("root.toml should not load successfully"),
+        Err(e) => {
+            let message = e.to_string();
+            assert!(message.contains("1 connection errors found in flow"), "{message}");
+            assert!(message.contains("incompatible-types/root.toml"), "{message}");
+            assert!(message.contains("Connection from 'get/string/2'"), "{message}");
+            assert!(message.contains("to 'file_write/bytes'"), "{message}");
+            assert!(message.contains("file_write.toml"), "{message}");
+            assert!(message.contains("'string' is not compatible with any of the destination types \
+                ['bytes', 'array/number']"), "{message}");
+        }
+    }
+}
+
 #[test]
 fn aliased_context_not_allowed() {
     let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
@@ -72,6 +99,37 @@ fn invalid_toml() {
     }
 }
 
+#[test]
+fn yaml_flow() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let url = helper::absolute_file_url_from_relative_path("flowc/tests/test-flows/yaml/root.yaml");
+    match parser::parse(&url, &meta_provider) {
+        Ok(FlowProcess(flow)) => {
+            assert_eq!(flow.name, Name::from("yaml"));
+            assert_eq!(flow.subprocesses.len(), 2);
+        }
+        Ok(_) => panic!("Did not load a flow"),
+        Err(e) => panic!("Flow could not be loaded: {e}"),
+    }
+}
+
+#[test]
+fn invalid_yaml_reports_location() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let path = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/invalid-yaml/root.yaml");
+    match parser::parse(&path, &meta_provider) {
+        Ok(_) => panic!("root.yaml should not load successfully"),
+        Err(e) => assert!(e.iter().any(|cause| cause.to_string().contains(" at line ")),
+                          "No location reported in error: {}",
+                          e.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")),
+    }
+}
+
 #[test]
 fn no_connections() {
     let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
@@ -118,6 +176,156 @@ fn function_input_initialized() {
     }
 }
 
+#[test]
+fn flow_parameters_substituted() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let url = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/parameters/root.toml",
+    );
+
+    match parser::parse(&url, &meta_provider) {
+        Ok(FlowProcess(flow)) => match flow.subprocesses.get(&Name::from("greeting")) {
+            Some(FlowProcess(greeting)) => {
+                match greeting.subprocesses.get(&Name::from("stdout")) {
+                    Some(FunctionProcess(stdout)) => {
+                        let input: &IO = stdout.get_inputs().first().expect("Could not get input 0");
+                        assert_eq!(input.get_initializer(), &Some(Once(json!("Hello World!"))));
+                    }
+                    _ => panic!("stdout sub-process was not a Function"),
+                }
+                match greeting.subprocesses.get(&Name::from("count")) {
+                    Some(FunctionProcess(count)) => {
+                        let input: &IO = count.get_inputs().first().expect("Could not get input 0");
+                        assert_eq!(input.get_initializer(), &Some(Once(json!(2))));
+                    }
+                    _ => panic!("count sub-process was not a Function"),
+                }
+                assert_eq!(greeting.connections.len(), 1);
+            }
+            _ => panic!("greeting sub-process was not a Flow"),
+        },
+        Ok(_) => panic!("Didn't load a flow"),
+        Err(e) => panic!("Error loading flow: {e}"),
+    }
+}
+
+#[test]
+fn flow_parameter_value_missing() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let url = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/parameters-missing/root.toml",
+    );
+
+    match parser::parse(&url, &meta_provider) {
+        Ok(_) => panic!("root.toml should not load successfully"),
+        Err(e) => assert!(e.iter().any(|cause| cause.to_string().contains("'greeting'")),
+                          "Missing parameter not reported in error: {}",
+                          e.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")),
+    }
+}
+
+#[test]
+fn env_var_default_substituted() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let url = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/env-vars/root.toml",
+    );
+
+    match parser::parse(&url, &meta_provider) {
+        Ok(FlowProcess(flow)) => match flow.subprocesses.get(&Name::from("stdout")) {
+            Some(FunctionProcess(stdout)) => {
+                let input: &IO = stdout.get_inputs().first().expect("Could not get input 0");
+                assert_eq!(input.get_initializer(), &Some(Once(json!("Hello World!"))));
+            }
+            _ => panic!("stdout sub-process was not a Function"),
+        },
+        Ok(_) => panic!("Didn't load a flow"),
+        Err(e) => panic!("Error loading flow: {e}"),
+    }
+}
+
+#[test]
+fn env_var_missing() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let url = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/env-vars-missing/root.toml",
+    );
+
+    match parser::parse(&url, &meta_provider) {
+        Ok(_) => panic!("root.toml should not load successfully"),
+        Err(e) => assert!(e.iter().any(|cause| cause.to_string()
+                              .contains("'FLOW_TEST_UNSET_GREETING'")),
+                          "Missing environment variable not reported in error: {}",
+                          e.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")),
+    }
+}
+
+// A provider of one definition, as if fetched from a remote server
+struct RemoteProvider(&'static str);
+
+impl Provider for RemoteProvider {
+    fn resolve_url(&self, url: &Url, _default_name: &str, _extensions: &[&str])
+        -> flowcore::errors::Result<(Url, Option<Url>)> {
+        Ok((url.clone(), None))
+    }
+
+    fn get_contents(&self, _url: &Url) -> flowcore::errors::Result<Vec<u8>> {
+        Ok(self.0.as_bytes().to_vec())
+    }
+}
+
+#[test]
+fn env_var_not_substituted_in_remote_definition() {
+    let provider = RemoteProvider("function = 'remote'
+source = 'https://example.com/${FLOW_TEST_UNSET_SECRET:-secret}.wasm'
+type = 'wasm'
+
+[[output]]
+");
+    let url = Url::parse("https://example.com/remote.toml").expect("Could not parse url");
+
+    match parser::parse(&url, &provider) {
+        Ok(FunctionProcess(function)) => assert_eq!(function.get_source(),
+            "https://example.com/${FLOW_TEST_UNSET_SECRET:-secret}.wasm"),
+        Ok(_) => panic!("Didn't load a function"),
+        Err(e) => panic!("Error loading function: {e}"),
+    }
+}
+
+#[test]
+fn inline_function() {
+    let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
+                                          helper::get_canonical_context_root(),
+    );
+    let url = helper::absolute_file_url_from_relative_path(
+        "flowc/tests/test-flows/inline-function/root.toml",
+    );
+
+    match parser::parse(&url, &meta_provider) {
+        Ok(FlowProcess(flow)) => match flow.subprocesses.get(&Name::from("double")) {
+            Some(FunctionProcess(double)) => {
+                assert_eq!(double.get_source(), "double.wasm");
+                assert_eq!(double.get_source_url(), &flow.source_url);
+                assert!(double.get_lib_reference().is_none());
+                assert_eq!(double.get_reference_location().map(|location| location.line), Some(5));
+                let input: &IO = double.get_inputs().first().expect("Could not get input 0");
+                assert_eq!(input.get_initializer(), &Some(Once(json!(21))));
+            }
+            _ => panic!("Inline sub-process was not a Function"),
+        },
+        Ok(_) => panic!("Didn't load a flow"),
+        Err(e) => panic!("Error loading flow: {e}"),
+    }
+}
+
 #[test]
 fn root_flow_takes_name_from_file() {
     let meta_provider = MetaProvider::new(helper::set_lib_search_path_to_project(),
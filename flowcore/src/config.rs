@@ -0,0 +1,155 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::info;
+use serde_derive::Deserialize;
+
+use crate::errors::{Result, ResultExt};
+
+/// The name of the global config file, in the `.flow` directory of the user's home directory
+pub const GLOBAL_CONFIG_FILENAME: &str = "config.toml";
+/// The name of the project config file, looked for in the current directory and its parents
+pub const PROJECT_CONFIG_FILENAME: &str = ".flow.toml";
+
+/// `Config` holds defaults for options of `flowc` and the flow runners, read from the global
+/// config file (`~/.flow/config.toml`) and a project config file (`.flow.toml`).
+/// Options specified on the command line override them.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Directories or base Urls to add to the library search path
+    #[serde(default)]
+    pub lib_dirs: Vec<String>,
+    /// The name of the runner that flows are compiled for and run with
+    pub runner: Option<String>,
+    /// The number of threads to use to execute jobs
+    pub threads: Option<usize>,
+    /// The verbosity level of logging output (trace, debug, info, warn, error, off)
+    pub verbosity: Option<String>,
+    /// Use native (`true`) or WASM (`false`) implementations of library functions
+    pub native: Option<bool>,
+}
+
+impl Config {
+    /// Load the `Config` from the global config file, overridden by the first project config file
+    /// found in `dir` or one of its parents. Missing config files are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file exists but cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Self> {
+        let global = match Self::global_config_path() {
+            Some(path) => Self::read(&path)?.unwrap_or_default(),
+            None => Config::default(),
+        };
+
+        match dir.ancestors()
+            .map(|ancestor| ancestor.join(PROJECT_CONFIG_FILENAME))
+            .find(|path| path.is_file()) {
+            Some(path) => Ok(global.merge(Self::read(&path)?.unwrap_or_default())),
+            None => Ok(global),
+        }
+    }
+
+    /// Read the `Config` in the file at `path`, if it exists
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("Could not read config file '{}'", path.display()))?;
+        let config = toml::from_str(&contents)
+            .chain_err(|| format!("Could not parse config file '{}'", path.display()))?;
+        info!("Read config file '{}'", path.display());
+        Ok(Some(config))
+    }
+
+    /// The path of the global config file, if the user's home directory is known
+    #[must_use]
+    pub fn global_config_path() -> Option<PathBuf> {
+        env::var("HOME").ok()
+            .map(|home_dir| Path::new(&home_dir).join(".flow").join(GLOBAL_CONFIG_FILENAME))
+    }
+
+    // Merge `other` into this config, with the values set in `other` taking precedence.
+    // Library directories of `other` are searched first
+    fn merge(self, other: Config) -> Config {
+        let mut lib_dirs = other.lib_dirs;
+        lib_dirs.extend(self.lib_dirs);
+
+        Config {
+            lib_dirs,
+            runner: other.runner.or(self.runner),
+            threads: other.threads.or(self.threads),
+            verbosity: other.verbosity.or(self.verbosity),
+            native: other.native.or(self.native),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{Config, PROJECT_CONFIG_FILENAME};
+
+    #[test]
+    fn missing_config_file() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        assert!(Config::read(&dir.path().join(PROJECT_CONFIG_FILENAME))
+            .expect("Could not read config").is_none());
+    }
+
+    #[test]
+    fn invalid_config_file() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let path = dir.path().join(PROJECT_CONFIG_FILENAME);
+        fs::write(&path, "colour = \"blue\"").expect("Could not write config");
+        assert!(Config::read(&path).is_err());
+    }
+
+    #[test]
+    fn project_config_found_in_parent() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        fs::write(dir.path().join(PROJECT_CONFIG_FILENAME),
+                  "lib_dirs = [\"/opt/flow/lib\"]\nthreads = 2\nnative = true")
+            .expect("Could not write config");
+        let sub_dir = dir.path().join("flows");
+        fs::create_dir(&sub_dir).expect("Could not create sub dir");
+
+        let config = Config::load(&sub_dir).expect("Could not load config");
+        assert!(config.lib_dirs.contains(&"/opt/flow/lib".to_string()));
+        assert_eq!(config.threads, Some(2));
+        assert_eq!(config.native, Some(true));
+    }
+
+    #[test]
+    fn project_overrides_global() {
+        let global = Config {
+            lib_dirs: vec!["/global".into()],
+            runner: Some("flowrcli".into()),
+            threads: Some(4),
+            verbosity: Some("info".into()),
+            native: None,
+        };
+        let project = Config {
+            lib_dirs: vec!["/project".into()],
+            threads: Some(1),
+            native: Some(false),
+            ..Config::default()
+        };
+
+        let config = global.merge(project);
+        assert_eq!(config.lib_dirs, vec!["/project".to_string(), "/global".to_string()]);
+        assert_eq!(config.runner.as_deref(), Some("flowrcli"));
+        assert_eq!(config.threads, Some(1));
+        assert_eq!(config.verbosity.as_deref(), Some("info"));
+        assert_eq!(config.native, Some(false));
+    }
+}
@@ -0,0 +1,486 @@
+use std::collections::BTreeSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use log::trace;
+use serde_derive::{Deserialize, Serialize};
+use url::Url;
+use zip::ZipArchive;
+
+use crate::errors::{bail, Result, ResultExt};
+use crate::model::lib_manifest::LibraryManifest;
+use crate::model::manifest_format::ManifestFormat;
+use crate::model::metadata::MetaData;
+use crate::provider::Provider;
+
+/// The file extension used for library archives
+pub const ARCHIVE_EXTENSION: &str = "flowlib";
+
+/// The Url scheme used to refer to content inside a library archive, such as
+/// `flowlib:///home/me/.flow/lib/flowstdlib.flowlib/math/add/add.wasm`
+pub const ARCHIVE_SCHEME: &str = "flowlib";
+
+/// The file name suffixes of the archive formats that library content can be read from
+pub const ARCHIVE_SUFFIXES: &[&str] = &[".flowlib", ".tar.gz", ".tgz", ".zip"];
+
+/// The name of the index file added to the root of a library archive
+pub const INDEX_FILENAME: &str = "index.json";
+
+/// `ArchiveIndex` describes the contents of a library archive, and is stored in it
+/// as `index.json`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ArchiveIndex {
+    /// The `metadata` (name, version etc) of the library in the archive
+    pub metadata: MetaData,
+    /// The paths (relative to the library root) of all the files in the archive
+    pub files: Vec<String>,
+}
+
+/// Package the compiled library in `lib_dir` into a library archive (a gzipped tar file) named
+/// after the library in `output_dir`, returning the path of the archive and its index.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - `lib_dir` does not contain a library manifest, or it cannot be read
+/// - the files of the library cannot be read
+/// - the archive cannot be written
+pub fn package(lib_dir: &Path, output_dir: &Path) -> Result<(PathBuf, ArchiveIndex)> {
+    let Some(manifest_path) = LibraryManifest::find_manifest_filename(lib_dir) else {
+        bail!("'{}' does not contain a library manifest, it may not have been compiled",
+            lib_dir.display());
+    };
+    let manifest: LibraryManifest = ManifestFormat::deserialize(&fs::read(&manifest_path)?)
+        .chain_err(|| format!("Could not read library manifest '{}'", manifest_path.display()))?;
+
+    let archive_path = output_dir.join(format!("{}.{ARCHIVE_EXTENSION}",
+                                               manifest.metadata.name));
+    let mut files = BTreeSet::new();
+    find_files(lib_dir, lib_dir, &archive_path, &mut files)?;
+    let index = ArchiveIndex {
+        metadata: manifest.metadata,
+        files: files.into_iter().collect(),
+    };
+
+    let archive_file = File::create(&archive_path)
+        .chain_err(|| format!("Could not create archive '{}'", archive_path.display()))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(archive_file, Compression::default()));
+
+    let index_contents = serde_json::to_vec_pretty(&index)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(index_contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, INDEX_FILENAME, index_contents.as_slice())?;
+
+    for file in &index.files {
+        builder.append_path_with_name(lib_dir.join(file), file)
+            .chain_err(|| format!("Could not add '{file}' to the archive"))?;
+    }
+    builder.into_inner()?.finish()?;
+
+    Ok((archive_path, index))
+}
+
+// Recursively find all the files under `dir`, adding their paths relative to `root` to `files`
+fn find_files(root: &Path, dir: &Path, skip: &Path, files: &mut BTreeSet<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_files(root, &path, skip, files)?;
+        } else if path != skip {
+            let relative = path.strip_prefix(root)
+                .map_err(|_| format!("Could not get path of '{}' in library", path.display()))?;
+            files.insert(relative.to_string_lossy().to_string());
+        }
+    }
+    Ok(())
+}
+
+/// The `ArchiveProvider` implements the `Provider` trait and takes care of fetching content
+/// located inside an archive on the local file system, without unpacking it. Library archives
+/// created by `package` as well as `.tar.gz`, `.tgz` and `.zip` archives can be read.
+pub struct ArchiveProvider;
+
+impl Provider for ArchiveProvider {
+    fn resolve_url(
+        &self,
+        url: &Url,
+        default_filename: &str,
+        extensions: &[&str],
+    ) -> Result<(Url, Option<Url>)> {
+        let (archive_path, archive_url_path, entry) = Self::split_url(url)?;
+        let entries = Self::entry_names(&archive_path)?;
+
+        if entries.contains(&entry) {
+            return Ok((url.clone(), None));
+        }
+
+        let dir_prefix = if entry.is_empty() { String::new() } else { format!("{entry}/") };
+        if entries.iter().any(|name| name.starts_with(&dir_prefix)) {
+            trace!("'{entry}' is a directory in '{}', so attempting to find default file \
+                    named '{default_filename}' in it", archive_path.display());
+            if let Some(found) = Self::entry_by_extensions(&entries,
+                                           &format!("{dir_prefix}{default_filename}"),
+                                           extensions) {
+                return Ok((Self::entry_url(&archive_url_path, &found)?, None));
+            }
+
+            let dir_name = entry.rsplit('/').next().unwrap_or_default();
+            if let Some(found) = Self::entry_by_extensions(&entries,
+                                           &format!("{dir_prefix}{dir_name}"),
+                                           extensions) {
+                return Ok((Self::entry_url(&archive_url_path, &found)?, None));
+            }
+
+            bail!("No file named '{}' or '{}' with extension '{}' found in '{}' in archive '{}'",
+                default_filename, dir_name, extensions.join(" or "), entry,
+                archive_path.display())
+        }
+
+        match Self::entry_by_extensions(&entries, &entry, extensions) {
+            Some(found) => Ok((Self::entry_url(&archive_url_path, &found)?, None)),
+            None => bail!("No file found at '{}' in archive '{}' with any of these extensions \
+                    '{extensions:?}'", entry, archive_path.display()),
+        }
+    }
+
+    fn get_contents(&self, url: &Url) -> Result<Vec<u8>> {
+        let (archive_path, _, name) = Self::split_url(url)?;
+        Self::read_entry(&archive_path, &name)
+            .chain_err(|| format!("Could not read '{name}' from archive '{}'",
+                                  archive_path.display()))
+    }
+}
+
+impl ArchiveProvider {
+    /// Create a Url to the file `path_in_lib` inside the library archive at `archive_path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a valid Url cannot be formed from `archive_path` and `path_in_lib`
+    pub fn archive_url(archive_path: &Path, path_in_lib: &str) -> Result<Url> {
+        let archive_url = Url::from_file_path(archive_path)
+            .map_err(|()| format!("Could not create Url for archive '{}'",
+                                  archive_path.display()))?;
+        Self::entry_url(archive_url.path(), path_in_lib)
+    }
+
+    /// If the `file:` Url `url` refers to a file inside an archive (such as
+    /// `file:///home/me/libs/flowstdlib.zip/math/add.toml`) then return the equivalent Url
+    /// to be used with the `ArchiveProvider`
+    #[must_use]
+    pub fn archive_url_for_file(url: &Url) -> Option<Url> {
+        if url.scheme() != "file" {
+            return None;
+        }
+        let (archive_path, archive_url_path, entry) = Self::split_url(url).ok()?;
+        if !archive_path.is_file() {
+            return None;
+        }
+        Self::entry_url(&archive_url_path, &entry).ok()
+    }
+
+    // Form the Url of `entry` in the archive whose (Url encoded) path is `archive_url_path`
+    fn entry_url(archive_url_path: &str, entry: &str) -> Result<Url> {
+        Ok(Url::parse(&format!("{ARCHIVE_SCHEME}://{archive_url_path}/{entry}"))?)
+    }
+
+    // Split a Url of the form `flowlib:///path/name.flowlib/dir/file` into the path of the
+    // archive on the file system, the path of the archive as used in the Url and the name
+    // of the entry ("dir/file") inside the archive
+    fn split_url(url: &Url) -> Result<(PathBuf, String, String)> {
+        let segments = url.path_segments()
+            .ok_or_else(|| format!("Could not get path of Url '{url}'"))?
+            .collect::<Vec<&str>>();
+        let archive_segment = segments.iter()
+            .position(|segment| ARCHIVE_SUFFIXES.iter()
+                .any(|suffix| segment.ends_with(suffix)))
+            .ok_or_else(|| format!("Url '{url}' does not refer to a library archive"))?;
+        let (archive_segments, entry_segments) = segments.split_at(archive_segment + 1);
+
+        let archive_url_path = format!("/{}", archive_segments.join("/"));
+        let archive_path = Url::parse(&format!("file://{archive_url_path}"))?
+            .to_file_path()
+            .map_err(|()| format!("Could not get path of archive in Url '{url}'"))?;
+        let entry = entry_segments.iter()
+            .filter(|segment| !segment.is_empty())
+            .copied()
+            .collect::<Vec<&str>>()
+            .join("/");
+
+        Ok((archive_path, archive_url_path, entry))
+    }
+
+    // Is the archive at `archive_path` a zip archive, as opposed to a gzipped tar archive
+    fn is_zip(archive_path: &Path) -> bool {
+        archive_path.extension() == Some(OsStr::new("zip"))
+    }
+
+    // Open the gzipped tar archive at `archive_path` for reading
+    fn open_tar(archive_path: &Path) -> Result<tar::Archive<GzDecoder<File>>> {
+        let file = File::open(archive_path)
+            .chain_err(|| format!("Could not open archive '{}'", archive_path.display()))?;
+        Ok(tar::Archive::new(GzDecoder::new(file)))
+    }
+
+    // Open the zip archive at `archive_path` for reading
+    fn open_zip(archive_path: &Path) -> Result<ZipArchive<File>> {
+        let file = File::open(archive_path)
+            .chain_err(|| format!("Could not open archive '{}'", archive_path.display()))?;
+        ZipArchive::new(file)
+            .map_err(|e| format!("Could not read zip archive '{}': {e}",
+                                 archive_path.display()).into())
+    }
+
+    // Get the names of all the files in the archive at `archive_path`
+    fn entry_names(archive_path: &Path) -> Result<BTreeSet<String>> {
+        if Self::is_zip(archive_path) {
+            return Ok(Self::open_zip(archive_path)?.file_names()
+                .filter(|name| !name.ends_with('/'))
+                .map(ToString::to_string)
+                .collect());
+        }
+
+        let mut archive = Self::open_tar(archive_path)?;
+        let mut names = BTreeSet::new();
+        for entry in archive.entries()
+            .chain_err(|| format!("Could not read archive '{}'", archive_path.display()))? {
+            let entry = entry?;
+            if entry.header().entry_type().is_file() {
+                names.insert(entry.path()?.to_string_lossy().to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    // Read the contents of the file `name` in the archive at `archive_path`
+    fn read_entry(archive_path: &Path, name: &str) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+
+        if Self::is_zip(archive_path) {
+            let mut archive = Self::open_zip(archive_path)?;
+            let mut entry = archive.by_name(name)
+                .map_err(|e| format!("Could not find '{name}': {e}"))?;
+            entry.read_to_end(&mut buffer)?;
+            return Ok(buffer);
+        }
+
+        let mut archive = Self::open_tar(archive_path)?;
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.path()?.to_string_lossy() == name {
+                entry.read_to_end(&mut buffer)?;
+                return Ok(buffer);
+            }
+        }
+        bail!("Could not find '{}'", name)
+    }
+
+    // Given the name of a file in the archive, find an entry with any of the allowed extensions
+    fn entry_by_extensions(entries: &BTreeSet<String>, name: &str, extensions: &[&str])
+        -> Option<String> {
+        extensions.iter()
+            .map(|extension| Path::new(name).with_extension(extension)
+                .to_string_lossy().to_string())
+            .find(|candidate| {
+                trace!("Looking for '{candidate}' in archive");
+                entries.contains(candidate)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    use tempfile::{tempdir, TempDir};
+    use url::Url;
+
+    use crate::model::lib_manifest::LibraryManifest;
+    use crate::model::metadata::MetaData;
+    use crate::provider::Provider;
+
+    use super::{ArchiveIndex, ArchiveProvider, INDEX_FILENAME, package};
+
+    fn write_lib(lib_dir: &Path) {
+        let metadata = MetaData {
+            name: "testlib".into(),
+            version: "1.2.3".into(),
+            description: "A test library".into(),
+            authors: vec![],
+        };
+        let lib_url = Url::parse("lib://testlib").expect("Could not parse lib Url");
+        LibraryManifest::new(lib_url, metadata)
+            .write_json(&LibraryManifest::manifest_filename(lib_dir))
+            .expect("Could not write manifest");
+        let add_dir = lib_dir.join("math/add");
+        fs::create_dir_all(&add_dir).expect("Could not create dir");
+        fs::write(add_dir.join("add.wasm"), b"wasm").expect("Could not write wasm");
+        fs::write(add_dir.join("add.toml"), b"function = \"add\"").expect("Could not write toml");
+    }
+
+    #[test]
+    fn package_lib() {
+        let lib_dir = tempdir().expect("Could not create temp dir");
+        let output_dir = tempdir().expect("Could not create temp dir");
+        write_lib(lib_dir.path());
+
+        let (archive_path, index) = package(lib_dir.path(), output_dir.path())
+            .expect("Could not package library");
+        assert_eq!(archive_path, output_dir.path().join("testlib.flowlib"));
+        assert_eq!(index.metadata.version, "1.2.3");
+        assert_eq!(index.files, vec!["manifest.json", "math/add/add.toml", "math/add/add.wasm"]);
+    }
+
+    #[test]
+    fn package_uncompiled_lib_fails() {
+        let lib_dir = tempdir().expect("Could not create temp dir");
+        assert!(package(lib_dir.path(), lib_dir.path()).is_err());
+    }
+
+    fn packaged_lib() -> (TempDir, PathBuf) {
+        let lib_dir = tempdir().expect("Could not create temp dir");
+        write_lib(lib_dir.path());
+        let (archive_path, _) = package(lib_dir.path(), lib_dir.path())
+            .expect("Could not package library");
+        (lib_dir, archive_path)
+    }
+
+    #[test]
+    fn resolve_manifest_in_root() {
+        let (_dir, archive_path) = packaged_lib();
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = ArchiveProvider::archive_url(&archive_path, "")
+            .expect("Could not create Url");
+        let (resolved_url, _) = provider.resolve_url(&url, "manifest", &["json"])
+            .expect("Could not resolve Url");
+        assert_eq!(resolved_url, ArchiveProvider::archive_url(&archive_path, "manifest.json")
+            .expect("Could not create Url"));
+        let manifest: LibraryManifest = serde_json::from_slice(&provider
+            .get_contents(&resolved_url).expect("Could not get contents"))
+            .expect("Could not parse manifest");
+        assert_eq!(manifest.metadata.name, "testlib");
+    }
+
+    #[test]
+    fn resolve_by_dir_name() {
+        let (_dir, archive_path) = packaged_lib();
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = ArchiveProvider::archive_url(&archive_path, "math/add")
+            .expect("Could not create Url");
+        let (resolved_url, _) = provider.resolve_url(&url, "function", &["wasm"])
+            .expect("Could not resolve Url");
+        assert_eq!(provider.get_contents(&resolved_url).expect("Could not get contents"),
+                   b"wasm");
+    }
+
+    #[test]
+    fn resolve_by_extension() {
+        let (_dir, archive_path) = packaged_lib();
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = ArchiveProvider::archive_url(&archive_path, "math/add/add")
+            .expect("Could not create Url");
+        let (resolved_url, _) = provider.resolve_url(&url, "", &["toml"])
+            .expect("Could not resolve Url");
+        assert!(resolved_url.path().ends_with("math/add/add.toml"));
+    }
+
+    #[test]
+    fn index_in_archive() {
+        let (_dir, archive_path) = packaged_lib();
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = ArchiveProvider::archive_url(&archive_path, INDEX_FILENAME)
+            .expect("Could not create Url");
+        let index: ArchiveIndex = serde_json::from_slice(&provider.get_contents(&url)
+            .expect("Could not get contents")).expect("Could not parse index");
+        assert_eq!(index.metadata.name, "testlib");
+    }
+
+    #[test]
+    fn resolve_missing_file_fails() {
+        let (_dir, archive_path) = packaged_lib();
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = ArchiveProvider::archive_url(&archive_path, "math/subtract")
+            .expect("Could not create Url");
+        assert!(provider.resolve_url(&url, "", &["toml"]).is_err());
+    }
+
+    fn zipped_lib() -> (TempDir, PathBuf) {
+        let dir = tempdir().expect("Could not create temp dir");
+        let archive_path = dir.path().join("testlib.zip");
+        let file = fs::File::create(&archive_path).expect("Could not create zip file");
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("math/", options).expect("Could not add directory");
+        zip.start_file("math/add.toml", options).expect("Could not start file");
+        zip.write_all(b"function = \"add\"").expect("Could not write file");
+        let _ = zip.finish().expect("Could not finish zip file");
+        (dir, archive_path)
+    }
+
+    #[test]
+    fn resolve_in_zip() {
+        let (_dir, archive_path) = zipped_lib();
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = ArchiveProvider::archive_url(&archive_path, "math/add")
+            .expect("Could not create Url");
+        let (resolved_url, _) = provider.resolve_url(&url, "", &["toml"])
+            .expect("Could not resolve Url");
+        assert!(resolved_url.path().ends_with("testlib.zip/math/add.toml"));
+        assert_eq!(provider.get_contents(&resolved_url).expect("Could not get contents"),
+                   b"function = \"add\"");
+    }
+
+    #[test]
+    fn resolve_in_tar_gz() {
+        let (dir, archive_path) = packaged_lib();
+        let tar_gz_path = dir.path().join("testlib.tar.gz");
+        fs::rename(archive_path, &tar_gz_path).expect("Could not rename archive");
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = ArchiveProvider::archive_url(&tar_gz_path, "math/add")
+            .expect("Could not create Url");
+        let (resolved_url, _) = provider.resolve_url(&url, "function", &["wasm"])
+            .expect("Could not resolve Url");
+        assert_eq!(provider.get_contents(&resolved_url).expect("Could not get contents"),
+                   b"wasm");
+    }
+
+    #[test]
+    fn file_url_in_archive() {
+        let (_dir, archive_path) = zipped_lib();
+        let file_url = Url::from_file_path(archive_path.join("math/add.toml"))
+            .expect("Could not create Url");
+        let archive_url = ArchiveProvider::archive_url_for_file(&file_url)
+            .expect("Could not get archive Url");
+        assert_eq!(archive_url, ArchiveProvider::archive_url(&archive_path, "math/add.toml")
+            .expect("Could not create Url"));
+    }
+
+    #[test]
+    fn file_url_not_in_archive() {
+        let (dir, _) = zipped_lib();
+        let file_url = Url::from_file_path(dir.path().join("testlib/math/add.toml"))
+            .expect("Could not create Url");
+        assert!(ArchiveProvider::archive_url_for_file(&file_url).is_none());
+        let missing_archive_url = Url::from_file_path(dir.path().join("other.zip/add.toml"))
+            .expect("Could not create Url");
+        assert!(ArchiveProvider::archive_url_for_file(&missing_archive_url).is_none());
+    }
+
+    #[test]
+    fn url_without_archive_fails() {
+        let provider: &dyn Provider = &ArchiveProvider;
+        let url = Url::parse("flowlib:///tmp/no-archive/manifest.json")
+            .expect("Could not create Url");
+        assert!(provider.get_contents(&url).is_err());
+    }
+}
@@ -4,4 +4,7 @@
 pub mod file_provider;
 /// The Content Provider for Http contents
 #[cfg(feature = "http_provider")]
-pub mod http_provider;
\ No newline at end of file
+pub mod http_provider;
+/// The Content Provider for contents of library archives
+#[cfg(feature = "archive_provider")]
+pub mod archive_provider;
@@ -0,0 +1,45 @@
+use sha2::{Digest, Sha256};
+
+use crate::errors::{bail, Result};
+
+/// Calculate the SHA-256 hash of `contents`, as a lower case hex string
+#[must_use]
+pub fn sha256(contents: &[u8]) -> String {
+    Sha256::digest(contents).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Verify that the SHA-256 hash of `contents` loaded from `source` is `expected`
+///
+/// # Errors
+///
+/// Returns an error if the hash of `contents` is not `expected`, which means that `contents`
+/// have been modified or corrupted since the hash was recorded
+pub fn verify(contents: &[u8], expected: &str, source: &str) -> Result<()> {
+    let actual = sha256(contents);
+    if actual != expected {
+        bail!("The SHA-256 hash '{}' of '{}' does not match the hash '{}' \
+            recorded when it was compiled. It may be stale (re-compile the flow or library) \
+            or corrupted", actual, source, expected);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{sha256, verify};
+
+    #[test]
+    fn hash_of_empty() {
+        assert_eq!(sha256(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    }
+
+    #[test]
+    fn verify_matching() {
+        assert!(verify(b"wasm", &sha256(b"wasm"), "test.wasm").is_ok());
+    }
+
+    #[test]
+    fn verify_mismatch() {
+        assert!(verify(b"stale wasm", &sha256(b"wasm"), "test.wasm").is_err());
+    }
+}
@@ -8,6 +8,10 @@ use super::json_deserializer::JsonDeserializer;
 use super::toml_deserializer::TomlDeserializer;
 use super::yaml_deserializer::YamlDeserializer;
 
+/// The file extensions of flow and function definition files that can be deserialized, in the
+/// order they are tried when looking for a definition file whose extension is not specified
+pub const DEFINITION_FILE_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json"];
+
 /// All deserializers have to implement this trait for content deserialization, plus a method
 /// to return their name to be able to inform the user of which deserializer was used
 pub trait Deserializer<'a, T: Deserialize<'a>> {
@@ -0,0 +1,152 @@
+use std::env;
+
+use serde_json::Value;
+
+use crate::errors::{bail, Result};
+
+/// Substitute the values of environment variables for references to them of the form `${NAME}`
+/// or `${NAME:-default}` in all the string values of a deserialized `definition`. The default
+/// is used when the variable is not set. References to the parameters a flow declares, which use
+/// the same syntax, are left for parameter substitution.
+///
+/// # Errors
+///
+/// Returns an error if a referenced variable is not set and the reference has no default
+pub fn substitute(definition: &mut Value) -> Result<()> {
+    let parameters = declared_parameters(definition);
+    substitute_with(definition, &parameters, &|name| env::var(name).ok())
+}
+
+// Return the names of the parameters declared in a flow `definition`
+fn declared_parameters(definition: &Value) -> Vec<String> {
+    definition.get("parameter")
+        .and_then(Value::as_array)
+        .map(|parameters| parameters.iter()
+            .filter_map(|parameter| parameter.get("name").and_then(Value::as_str))
+            .map(ToString::to_string)
+            .collect())
+        .unwrap_or_default()
+}
+
+// Substitute in all the string values of `value`, getting variable values using `lookup`
+fn substitute_with(value: &mut Value, skip: &[String], lookup: &dyn Fn(&str) -> Option<String>)
+    -> Result<()> {
+    match value {
+        Value::String(string) => *string = substitute_str(string, skip, lookup)?,
+        Value::Array(array) => {
+            for element in array {
+                substitute_with(element, skip, lookup)?;
+            }
+        }
+        Value::Object(map) => {
+            for element in map.values_mut() {
+                substitute_with(element, skip, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Substitute all references to environment variables in `text`
+fn substitute_str(text: &str, skip: &[String], lookup: &dyn Fn(&str) -> Option<String>)
+    -> Result<String> {
+    let mut substituted = String::new();
+    let mut rest = text;
+
+    while let Some((before, reference)) = rest.split_once("${") {
+        substituted.push_str(before);
+        let Some((inner, after)) = reference.split_once('}') else {
+            substituted.push_str("${");
+            rest = reference;
+            break;
+        };
+
+        let (name, default) = match inner.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (inner, None),
+        };
+
+        if !is_variable_name(name) || skip.iter().any(|parameter| parameter == name) {
+            substituted.push_str(&format!("${{{inner}}}"));
+        } else {
+            match (lookup(name), default) {
+                (Some(value), _) => substituted.push_str(&value),
+                (None, Some(default)) => substituted.push_str(default),
+                (None, None) => bail!("Environment variable '{}' is not set, and the \
+                    reference '${{{}}}' to it has no default (use '${{{}:-default}}')",
+                    name, name, name),
+            }
+        }
+        rest = after;
+    }
+
+    substituted.push_str(rest);
+    Ok(substituted)
+}
+
+// A variable name is a letter or underscore, followed by letters, digits or underscores
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{declared_parameters, substitute_str, substitute_with};
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("example.com".into()),
+            "PORT" => Some("8080".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn substitutes_set_variables() {
+        assert_eq!(substitute_str("http://${HOST}:${PORT}/flow", &[], &lookup)
+                       .expect("Could not substitute"), "http://example.com:8080/flow");
+    }
+
+    #[test]
+    fn uses_default_when_not_set() {
+        assert_eq!(substitute_str("${COUNT:-10}", &[], &lookup).expect("Could not substitute"),
+                   "10");
+        assert_eq!(substitute_str("${HOST:-localhost}", &[], &lookup)
+                       .expect("Could not substitute"), "example.com");
+        assert_eq!(substitute_str("${DIR:-}", &[], &lookup).expect("Could not substitute"), "");
+    }
+
+    #[test]
+    fn missing_variable_is_an_error() {
+        let error = substitute_str("${MISSING}", &[], &lookup).expect_err("Should be an error");
+        assert!(error.to_string().contains("'MISSING'"));
+    }
+
+    #[test]
+    fn non_references_unchanged() {
+        for text in ["no references", "$HOST", "${not a name}", "${HOST", "cost: $5"] {
+            assert_eq!(substitute_str(text, &[], &lookup).expect("Could not substitute"), text);
+        }
+    }
+
+    #[test]
+    fn parameters_not_substituted() {
+        let mut definition = json!({
+            "flow": "${HOST}",
+            "parameter": [{"name": "greeting", "type": "string"}],
+            "process": [{"source": "lib://flowstdlib/${greeting}", "input": {"port": {"once": "${PORT}"}}}]
+        });
+        let parameters = declared_parameters(&definition);
+        substitute_with(&mut definition, &parameters, &lookup).expect("Could not substitute");
+        assert_eq!(definition, json!({
+            "flow": "example.com",
+            "parameter": [{"name": "greeting", "type": "string"}],
+            "process": [{"source": "lib://flowstdlib/${greeting}", "input": {"port": {"once": "8080"}}}]
+        }));
+    }
+}
@@ -12,3 +12,6 @@ mod yaml_deserializer;
 
 /// Helper function used to get a deserializer for a given file (by file extension)
 pub mod deserializer;
+
+/// Substitution of environment variables referenced in the string values of definitions
+pub mod env_vars;
@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use serde::de::DeserializeOwned;
 use url::Url;
 
-use crate::errors::{Result, ResultExt};
+use crate::errors::{Error, Result};
 
 use super::deserializer::Deserializer;
 
@@ -31,11 +31,14 @@ where
     T: DeserializeOwned,
 {
     fn deserialize(&self, contents: &'a str, url: Option<&Url>) -> Result<T> {
-        serde_yaml::from_str(contents).chain_err(|| {
-            format!(
-                "Error deserializing Yaml from: '{}'",
+        serde_yaml::from_str(contents).map_err(|e| {
+            let location = e.location().map_or(String::new(), |location|
+                format!(" at line {} column {}", location.line(), location.column()));
+            let message = format!(
+                "Error deserializing Yaml from: '{}'{location}",
                 url.map_or("URL was None".to_owned(), std::string::ToString::to_string)
-            )
+            );
+            Error::with_chain(e, message)
         })
     }
 
@@ -70,6 +73,14 @@ mod test {
         );
     }
 
+    #[test]
+    fn error_reports_location() {
+        let deserializer = YamlDeserializer::<TestStruct>::new();
+        let error = deserializer.deserialize("name:\n  key: value", None)
+            .expect_err("Should not have parsed correctly as is invalid Yaml");
+        assert!(error.to_string().contains(" at line 2 column "), "{error}");
+    }
+
     #[test]
     fn flow() {
         let flow_with_name = "
@@ -0,0 +1,75 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directories that example flows are looked for in when the program is run: `flowr/examples`
+/// in the current directory, when run from a checkout of the flow source, and `.flow/examples` in
+/// the user's home directory, where they can be installed
+#[must_use]
+pub fn example_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Ok(current_dir) = env::current_dir() {
+        dirs.push(current_dir.join("flowr").join("examples"));
+    }
+    if let Ok(home_dir) = env::var("HOME") {
+        dirs.push(Path::new(&home_dir).join(".flow").join("examples"));
+    }
+    dirs
+}
+
+/// Find the example flows (directories with a `root.toml` flow definition) in [`example_dirs`]
+/// whose name or path starts with `prefix`, sorted by path. They are found each time this is
+/// called, so the examples installed when completing an argument are found.
+#[must_use]
+pub fn find_examples(prefix: &str) -> Vec<PathBuf> {
+    find_examples_in(&example_dirs(), prefix)
+}
+
+fn find_examples_in(dirs: &[PathBuf], prefix: &str) -> Vec<PathBuf> {
+    let mut examples: Vec<PathBuf> = dirs.iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.join("root.toml").is_file())
+        .filter(|path| path.to_string_lossy().starts_with(prefix)
+            || path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(prefix)))
+        .collect();
+    examples.sort();
+    examples
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::find_examples_in;
+
+    fn examples_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../flowr/examples")
+    }
+
+    #[test]
+    fn finds_examples_by_name() {
+        let examples = find_examples_in(&[examples_dir()], "fib");
+        assert_eq!(examples, vec![examples_dir().join("fibonacci")]);
+    }
+
+    #[test]
+    fn finds_examples_by_path() {
+        let prefix = examples_dir().join("seq").display().to_string();
+        let examples = find_examples_in(&[examples_dir()], &prefix);
+        assert_eq!(examples, vec![examples_dir().join("sequence"),
+                                  examples_dir().join("sequence-of-sequences")]);
+    }
+
+    #[test]
+    fn all_examples_found_without_prefix() {
+        let examples = find_examples_in(&[examples_dir()], "");
+        assert!(examples.contains(&examples_dir().join("hello-world")));
+        assert!(!examples.iter().any(|example| example.ends_with("README.md")));
+    }
+
+    #[test]
+    fn missing_dir_has_no_examples() {
+        assert!(find_examples_in(&[examples_dir().join("missing")], "").is_empty());
+    }
+}
@@ -4,9 +4,20 @@ use serde_json::Value;
 
 use crate::errors::Result;
 
+/// `config` reads defaults for the options of the compiler and runners from config files
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config;
+
+/// `content_hash` calculates and verifies SHA-256 hashes of content such as WASM implementations
+pub mod content_hash;
+
 /// serializers to read definition files from various text formats based on file extension
 pub mod deserializers;
 
+/// `examples` finds the example flows installed, e.g. to complete command line arguments with them
+#[cfg(not(target_arch = "wasm32"))]
+pub mod examples;
+
 /// contains [Error] that other modules in this crate will `use errors::*;`
 /// to get access to everything `error_chain` creates.
 pub mod errors;
@@ -15,6 +26,9 @@ pub mod errors;
 #[cfg(all(not(target_arch = "wasm32"), feature = "meta_provider"))]
 pub mod meta_provider;
 
+/// `memory_provider` serves content held in memory, without using the file system or network
+pub mod memory_provider;
+
 /// defines many of the core data structures used across libraries and binaries
 pub mod model;
 
@@ -34,6 +48,12 @@ pub const RUN_AGAIN: RunAgain = true;
 /// executed more times in the future.
 pub type RunAgain = bool;
 
+/// The minimum size (in bytes) of the buffer the runtime allocates in a WASM module's linear
+/// memory to pass the serialized inputs to an implementation. The implementation writes its
+/// serialized result back into the same buffer, so this is also the minimum capacity available
+/// for the result. The buffer is larger when the serialized inputs are larger than this.
+pub const WASM_MIN_BUFFER_SIZE: usize = 1024;
+
 /// contains the file and http content provider implementations
 #[cfg(not(target_arch = "wasm32"))]
 pub mod content;
@@ -91,4 +111,17 @@ pub trait Implementation: Sync + Send {
     /// executing the function required
     ///
     fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)>;
+
+    /// The `run_metered` method executes the function's implementation as `run` does, limiting
+    /// the number of instructions it may execute to `fuel` (if `Some`). Implementations that
+    /// cannot be metered, such as native ones, ignore `fuel` and run without a limit
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `run` does, or if the implementation runs out of fuel
+    ///
+    fn run_metered(&self, inputs: &[Value], fuel: Option<u64>) -> Result<(Option<Value>, RunAgain)> {
+        let _ = fuel;
+        self.run(inputs)
+    }
 }
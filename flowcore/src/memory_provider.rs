@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use log::trace;
+use url::Url;
+
+use crate::errors::{bail, Result};
+use crate::provider::Provider;
+
+/// The `MemoryProvider` implements the `Provider` trait and serves content held in memory,
+/// such as a set of flow definition files supplied by a user and library definitions bundled
+/// with an application. It does not use the file system or the network, so it can be used to
+/// compile flows where neither is available, such as in a browser when compiled to WASM.
+///
+/// Content is found using the same Urls that would be used to find it elsewhere, including
+/// "lib://" and "context://" Urls of library and context functions.
+/// ```
+/// use url::Url;
+/// use flowcore::memory_provider::MemoryProvider;
+/// use flowcore::provider::Provider;
+///
+/// let root = Url::parse("memory://hello-world/root.toml").unwrap();
+/// let provider = MemoryProvider::default()
+///     .with_content(root.clone(), "flow = \"hello-world\"");
+/// let dir = Url::parse("memory://hello-world").unwrap();
+/// let (resolved_url, _) = provider.resolve_url(&dir, "root", &["toml"]).unwrap();
+/// assert_eq!(resolved_url, root);
+/// assert_eq!(provider.get_contents(&resolved_url).unwrap(), b"flow = \"hello-world\"");
+/// ```
+#[derive(Default)]
+pub struct MemoryProvider {
+    contents: HashMap<Url, Vec<u8>>,
+}
+
+impl MemoryProvider {
+    /// Add the `contents` found at `url`, replacing any contents already added for it
+    pub fn add(&mut self, url: Url, contents: impl Into<Vec<u8>>) {
+        self.contents.insert(url, contents.into());
+    }
+
+    /// Add the `contents` found at `url`, returning the `MemoryProvider` with it added
+    #[must_use]
+    pub fn with_content(mut self, url: Url, contents: impl Into<Vec<u8>>) -> Self {
+        self.add(url, contents);
+        self
+    }
+
+    // Given a Url of a file without an extension, try to find contents with any of the
+    // allowed extensions
+    fn find_by_extensions(&self, url: &str, extensions: &[&str]) -> Option<Url> {
+        extensions.iter()
+            .filter_map(|extension| Url::parse(&format!("{url}.{extension}")).ok())
+            .inspect(|candidate| trace!("Looking for contents at '{candidate}'"))
+            .find(|candidate| self.contents.contains_key(candidate))
+    }
+}
+
+impl Provider for MemoryProvider {
+    /// Resolve `url` to the Url of contents held, trying, in order:
+    ///     - the Url as is
+    ///     - the Url with each of the `extensions`
+    ///     - a file called `default_name`, with each of the `extensions`, "in" the Url
+    ///     - a file with the same name as the last segment of the Url, with each of the
+    ///       `extensions`, "in" the Url
+    ///
+    /// Library and context references are returned for "lib://" and "context://" Urls
+    fn resolve_url(
+        &self,
+        url: &Url,
+        default_name: &str,
+        extensions: &[&str],
+    ) -> Result<(Url, Option<Url>)> {
+        let mut url = url.clone();
+        url.set_query(None);
+
+        let reference = match url.scheme() {
+            "lib" | "context" => Some(url.clone()),
+            _ => None,
+        };
+
+        if self.contents.contains_key(&url) {
+            return Ok((url, reference));
+        }
+
+        let base = url.as_str().trim_end_matches('/');
+        let dir_name = base.rsplit('/').next().unwrap_or_default();
+        let found = self.find_by_extensions(base, extensions)
+            .or_else(|| self.find_by_extensions(&format!("{base}/{default_name}"), extensions))
+            .or_else(|| self.find_by_extensions(&format!("{base}/{dir_name}"), extensions));
+
+        match found {
+            Some(found_url) => Ok((found_url, reference)),
+            None => bail!("No contents found for '{}' with any of these extensions '{:?}'",
+                url, extensions),
+        }
+    }
+
+    fn get_contents(&self, url: &Url) -> Result<Vec<u8>> {
+        match self.contents.get(url) {
+            Some(contents) => Ok(contents.clone()),
+            None => bail!("No contents found for '{}'", url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use url::Url;
+
+    use crate::provider::Provider;
+
+    use super::MemoryProvider;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).expect("Could not parse Url")
+    }
+
+    fn provider() -> MemoryProvider {
+        MemoryProvider::default()
+            .with_content(url("memory://flows/hello/root.toml"), "flow = \"hello\"")
+            .with_content(url("memory://flows/hello/greeting.toml"), "flow = \"greeting\"")
+            .with_content(url("lib://flowstdlib/math/add/add.toml"), "function = \"add\"")
+    }
+
+    #[test]
+    fn resolve_exact_url() {
+        let (resolved, reference) = provider()
+            .resolve_url(&url("memory://flows/hello/root.toml"), "root", &["toml"])
+            .expect("Could not resolve url");
+        assert_eq!(resolved, url("memory://flows/hello/root.toml"));
+        assert!(reference.is_none());
+    }
+
+    #[test]
+    fn resolve_with_extension() {
+        let (resolved, _) = provider()
+            .resolve_url(&url("memory://flows/hello/greeting"), "root", &["yaml", "toml"])
+            .expect("Could not resolve url");
+        assert_eq!(resolved, url("memory://flows/hello/greeting.toml"));
+    }
+
+    #[test]
+    fn resolve_default_name_in_directory() {
+        let (resolved, _) = provider()
+            .resolve_url(&url("memory://flows/hello/"), "root", &["toml"])
+            .expect("Could not resolve url");
+        assert_eq!(resolved, url("memory://flows/hello/root.toml"));
+    }
+
+    #[test]
+    fn resolve_lib_function() {
+        let provider = provider();
+        let lib_url = url("lib://flowstdlib/math/add");
+        let (resolved, reference) = provider.resolve_url(&lib_url, "", &["toml"])
+            .expect("Could not resolve url");
+        assert_eq!(resolved, url("lib://flowstdlib/math/add/add.toml"));
+        assert_eq!(reference, Some(lib_url));
+        assert_eq!(provider.get_contents(&resolved).expect("Could not get contents"),
+                   b"function = \"add\"");
+    }
+
+    #[test]
+    fn missing_contents() {
+        let provider = provider();
+        assert!(provider.resolve_url(&url("memory://flows/goodbye"), "root", &["toml"]).is_err());
+        assert!(provider.get_contents(&url("memory://flows/goodbye/root.toml")).is_err());
+    }
+}
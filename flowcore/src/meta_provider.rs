@@ -1,21 +1,39 @@
-#[cfg(feature = "context")]
+#[cfg(feature = "file_provider")]
+use std::fs;
+#[cfg(any(feature = "context", feature = "file_provider"))]
 use std::path::PathBuf;
+#[cfg(feature = "file_provider")]
+use std::sync::Mutex;
 
+#[cfg(feature = "file_provider")]
+use semver::{Version, VersionReq};
 #[cfg(feature = "file_provider")]
 use simpath::{FoundType, Simpath};
 use url::Url;
 
+#[cfg(feature = "archive_provider")]
+use crate::content::archive_provider::{ARCHIVE_SCHEME, ARCHIVE_SUFFIXES, ArchiveProvider};
 #[cfg(feature = "file_provider")]
 use crate::content::file_provider::FileProvider;
 #[cfg(feature = "http_provider")]
 use crate::content::http_provider::HttpProvider;
+#[cfg(feature = "file_provider")]
+use crate::content_hash;
 use crate::errors::{bail, Result, ResultExt};
+#[cfg(feature = "file_provider")]
+use crate::model::lib_manifest::LibraryManifest;
+#[cfg(feature = "file_provider")]
+use crate::model::lock_file::{LockedLibrary, LockFile, pin_version, VERSION_QUERY};
+#[cfg(feature = "file_provider")]
+use crate::model::manifest_format::ManifestFormat;
 use crate::provider::Provider;
 
 #[cfg(feature = "file_provider")]
 const FILE_PROVIDER: &dyn Provider = &FileProvider as &dyn Provider;
 #[cfg(feature = "http_provider")]
 const HTTP_PROVIDER: &dyn Provider = &HttpProvider as &dyn Provider;
+#[cfg(feature = "archive_provider")]
+const ARCHIVE_PROVIDER: &dyn Provider = &ArchiveProvider as &dyn Provider;
 
 /// The `MetaProvider` implements the `Provider` trait
 ///
@@ -26,6 +44,8 @@ pub struct MetaProvider {
     lib_search_path: Simpath,
     #[cfg(feature = "context")]
     context_root: PathBuf,
+    #[cfg(feature = "file_provider")]
+    lock_file: Mutex<LockFile>,
 }
 
 /// Instantiate `MetaProvider` and then use the Provider trait methods on it to resolve and fetch
@@ -70,9 +90,30 @@ impl MetaProvider {
             lib_search_path,
             #[cfg(feature = "context")]
             context_root,
+            #[cfg(feature = "file_provider")]
+            lock_file: Mutex::new(LockFile::default()),
         }
     }
 
+    /// Use the library versions locked in `lock_file` when resolving library references with
+    /// a version requirement, such as "lib://flowstdlib@^1.2/math/add"
+    #[cfg(feature = "file_provider")]
+    #[must_use]
+    pub fn with_lock_file(self, lock_file: LockFile) -> Self {
+        MetaProvider {
+            lock_file: Mutex::new(lock_file),
+            ..self
+        }
+    }
+
+    /// Return the `LockFile` with the library versions resolved so far, including those that
+    /// were already locked
+    #[cfg(feature = "file_provider")]
+    #[must_use]
+    pub fn lock_file(&self) -> LockFile {
+        self.lock_file.lock().map(|lock_file| lock_file.clone()).unwrap_or_default()
+    }
+
     /// Determine which specific provider should be used based on the scheme of the Url of the content
     fn get_provider(scheme: &str) -> Result<&dyn Provider> {
         match scheme {
@@ -80,6 +121,8 @@ impl MetaProvider {
             "file" => Ok(FILE_PROVIDER),
             #[cfg(all(not(target_arch = "wasm32"), feature = "http_provider"))]
             "http" | "https" => Ok(HTTP_PROVIDER),
+            #[cfg(all(not(target_arch = "wasm32"), feature = "archive_provider"))]
+            ARCHIVE_SCHEME => Ok(ARCHIVE_PROVIDER),
             _ => bail!("Cannot determine which provider to use for url with scheme: 'scheme'"),
         }
     }
@@ -115,7 +158,16 @@ impl MetaProvider {
     ///    file within the library.
     ///
     ///   Find library in question is found in the file system or via Http using the provider's
-    ///   search path (setup on provider creation).
+    ///   search path (setup on provider creation). If no directory for the library is found,
+    ///   then an archive of it ("flowstdlib.flowlib", "flowstdlib.tar.gz", "flowstdlib.tgz"
+    ///   or "flowstdlib.zip") in the search path is looked for.
+    ///
+    ///   If the Url has a "version" query parameter with a version requirement, such as
+    ///   "lib://flowstdlib/math/add?version=^1.2", then the library is resolved to the installed
+    ///   version of it that is locked for that requirement, or else the highest installed version
+    ///   that matches it, see `resolve_lib_version`. The reference returned is pinned to the version
+    ///   resolved (e.g. "lib://flowstdlib/math/add?version==1.3.1"), so that the flow compiled with
+    ///   it is run using the same version
     ///
     ///   Then return:
     ///    - a string representation of the Url (file: or http: or https:) where the file can be found
@@ -129,6 +181,17 @@ impl MetaProvider {
         let path_under_lib = url.path().trim_start_matches('/');
         let lib_reference = Some(Url::parse(&format!("lib://{lib_name}/{path_under_lib}"))?);
 
+        if let Some((_, requirement)) = url.query_pairs().find(|(key, _)| key == VERSION_QUERY) {
+            let (version, lib_root_path) = self.resolve_lib_version(lib_name, &requirement)?;
+            let mut pinned_reference = Url::parse(&format!("lib://{lib_name}/{path_under_lib}"))?;
+            pin_version(&mut pinned_reference, &version);
+            return Ok((
+                Url::from_directory_path(lib_root_path.join(path_under_lib))
+                    .map_err(|()| "Could not convert file: lib_path to Url")?,
+                Some(pinned_reference),
+            ));
+        }
+
         match self.lib_search_path.find(lib_name) {
             Ok(FoundType::File(lib_root_path)) => {
                 let lib_path = lib_root_path.join(path_under_lib);
@@ -142,12 +205,112 @@ impl MetaProvider {
                 lib_root_url.set_path(&format!("{}/{path_under_lib}", lib_root_url.path()));
                 Ok((lib_root_url, lib_reference))
             }
-            _ => bail!(
-                "Could not resolve library Url '{}' using {}",
-                url,
-                self.lib_search_path
-            ),
+            _ => {
+                #[cfg(feature = "archive_provider")]
+                for suffix in ARCHIVE_SUFFIXES {
+                    if let Ok(FoundType::File(archive_path)) =
+                        self.lib_search_path.find(&format!("{lib_name}{suffix}")) {
+                        return Ok((ArchiveProvider::archive_url(&archive_path, path_under_lib)?,
+                                   lib_reference));
+                    }
+                }
+
+                bail!(
+                    "Could not resolve library Url '{}' using {}",
+                    url,
+                    self.lib_search_path
+                )
+            }
+        }
+    }
+
+    /// Find the root directory of the installed version of library `lib_name` to use for the
+    /// version `requirement`. If a version is locked for it in the lock file then that version
+    /// must be installed and its manifest unchanged. Otherwise the highest installed version that
+    /// matches the requirement is used, and locked, unless the requirement is already pinned to
+    /// an exact version. Returns the version and the root directory of the library.
+    #[cfg(feature = "file_provider")]
+    fn resolve_lib_version(&self, lib_name: &str, requirement: &str) -> Result<(String, PathBuf)> {
+        let version_req = VersionReq::parse(requirement)
+            .chain_err(|| format!("Invalid version requirement '{requirement}' for library '{lib_name}'"))?;
+        let installed = self.installed_versions(lib_name);
+        let mut lock_file = self.lock_file.lock()
+            .map_err(|_| "Could not lock the lock file")?;
+
+        if let Some(locked) = lock_file.locked(lib_name, requirement) {
+            let Some((_, lib_root_path)) = installed.iter()
+                .find(|(version, _)| version.to_string() == locked.version) else {
+                bail!("Version '{}' of library '{lib_name}' locked in the lock file for requirement \
+                    '{requirement}' is not installed in {}", locked.version, self.lib_search_path);
+            };
+            if Self::manifest_hash(lib_root_path)? != locked.hash {
+                bail!("The manifest of version '{}' of library '{lib_name}' at '{}' has changed \
+                    since it was locked in the lock file", locked.version, lib_root_path.display());
+            }
+            return Ok((locked.version.clone(), lib_root_path.clone()));
+        }
+
+        let Some((version, lib_root_path)) = installed.into_iter()
+            .filter(|(version, _)| version_req.matches(version))
+            .max_by(|(a, _), (b, _)| a.cmp(b)) else {
+            bail!("No version of library '{lib_name}' matching '{requirement}' is installed in {}",
+                self.lib_search_path);
+        };
+
+        if requirement.strip_prefix('=') != Some(version.to_string().as_str()) {
+            lock_file.lock(LockedLibrary {
+                name: lib_name.to_string(),
+                requirement: requirement.to_string(),
+                version: version.to_string(),
+                hash: Self::manifest_hash(&lib_root_path)?,
+            });
         }
+
+        Ok((version.to_string(), lib_root_path))
+    }
+
+    /// Find the installed versions of library `lib_name` in the directories of the search path.
+    /// A version can be installed in a directory named with the version (e.g. "flowstdlib-1.2.3")
+    /// or in a directory named just with the library name, with the version taken from its manifest
+    #[cfg(feature = "file_provider")]
+    fn installed_versions(&self, lib_name: &str) -> Vec<(Version, PathBuf)> {
+        let mut installed = vec![];
+
+        for dir in self.lib_search_path.directories() {
+            let Ok(entries) = fs::read_dir(dir) else { continue };
+            for path in entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()) {
+                let Some(dir_name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+                let version = if dir_name == lib_name {
+                    Self::manifest_version(&path)
+                } else {
+                    dir_name.strip_prefix(&format!("{lib_name}-"))
+                        .and_then(|version| Version::parse(version).ok())
+                };
+                if let Some(version) = version {
+                    installed.push((version, path));
+                }
+            }
+        }
+
+        installed
+    }
+
+    // Read the version from the metadata in the manifest of the library at `lib_root_path`
+    #[cfg(feature = "file_provider")]
+    fn manifest_version(lib_root_path: &std::path::Path) -> Option<Version> {
+        let contents = fs::read(LibraryManifest::find_manifest_filename(lib_root_path)?).ok()?;
+        let manifest: LibraryManifest = ManifestFormat::deserialize(&contents).ok()?;
+        Version::parse(&manifest.metadata.version).ok()
+    }
+
+    // Calculate the SHA-256 hash, as a hex string, of the manifest of the library at `lib_root_path`
+    #[cfg(feature = "file_provider")]
+    fn manifest_hash(lib_root_path: &std::path::Path) -> Result<String> {
+        let manifest_path = LibraryManifest::find_manifest_filename(lib_root_path)
+            .chain_err(|| format!("No library manifest found in '{}'", lib_root_path.display()))?;
+        let contents = fs::read(&manifest_path)
+            .chain_err(|| format!("Could not read library manifest '{}'", manifest_path.display()))?;
+        Ok(content_hash::sha256(&contents))
     }
 }
 
@@ -159,6 +322,7 @@ impl Provider for MetaProvider {
     ///     -  a specific file or flow (that may or may not exist)
     ///     -  a directory - if exists then look for a provider specific default file
     ///     -  a file in a library, transform the reference into a Url where the content can be found
+    ///     -  a file inside an archive, which is then read using the `ArchiveProvider`
     fn resolve_url(
         &self,
         url: &Url,
@@ -171,6 +335,9 @@ impl Provider for MetaProvider {
             "lib" => self.resolve_lib_url(url)?,
             #[cfg(feature = "context")]
             "context" => self.resolve_context_url(url)?,
+            #[cfg(feature = "archive_provider")]
+            "file" => (ArchiveProvider::archive_url_for_file(url).unwrap_or_else(|| url.clone()),
+                       None),
             _ => (url.clone(), None),
         };
 
@@ -183,6 +350,11 @@ impl Provider for MetaProvider {
     /// Takes a Url with a scheme of "http", "https" or "file". Read and return the contents of the
     /// resource at that Url.
     fn get_contents(&self, url: &Url) -> Result<Vec<u8>> {
+        #[cfg(feature = "archive_provider")]
+        if let Some(archive_url) = ArchiveProvider::archive_url_for_file(url) {
+            return ARCHIVE_PROVIDER.get_contents(&archive_url);
+        }
+
         let scheme = url.scheme().to_string();
         let provider = Self::get_provider(&scheme)?;
         let content = provider.get_contents(url)?;
@@ -276,6 +448,168 @@ mod test {
         }
     }
 
+    #[cfg(feature = "file_provider")]
+    fn install_version(dir: &Path, version: &str) {
+        use crate::model::lib_manifest::LibraryManifest;
+        use crate::model::metadata::MetaData;
+
+        let lib_dir = dir.join(format!("versioned-{version}"));
+        std::fs::create_dir(&lib_dir).expect("Could not create dir");
+        let metadata = MetaData {
+            name: "versioned".into(),
+            version: version.into(),
+            ..MetaData::default()
+        };
+        LibraryManifest::new(Url::parse("lib://versioned").expect("Could not parse Url"), metadata)
+            .write_json(&LibraryManifest::manifest_filename(&lib_dir))
+            .expect("Could not write manifest");
+    }
+
+    #[cfg(feature = "file_provider")]
+    #[test]
+    fn resolve_lib_version() {
+        use crate::model::lock_file::{LockedLibrary, LockFile};
+
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        for version in ["1.2.0", "1.3.1", "2.0.0"] {
+            install_version(dir.path(), version);
+        }
+        let mut search_path = Simpath::new("versioned_path");
+        search_path.add_directory(&dir.path().to_string_lossy());
+        let lib_url = Url::parse("lib://versioned/math/add?version=%5E1.2")
+            .expect("Couldn't form Url");
+
+        // the highest matching version is used, and locked
+        let provider = MetaProvider::new(search_path.clone(),
+                                         #[cfg(feature = "context")] PathBuf::from("/"));
+        let (resolved_url, lib_ref) = provider.resolve_lib_url(&lib_url)
+            .expect("Could not resolve versioned library Url");
+        assert!(resolved_url.path().ends_with("versioned-1.3.1/math/add/"));
+        assert_eq!(lib_ref, Some(Url::parse("lib://versioned/math/add?version=%3D1.3.1")
+            .expect("Couldn't form Url")));
+        let locked = provider.lock_file().locked("versioned", "^1.2").cloned()
+            .expect("Version was not locked");
+        assert_eq!(locked.version, "1.3.1");
+
+        // a version locked in the lock file is used instead
+        let mut lock_file = LockFile::default();
+        lock_file.lock(LockedLibrary {
+            version: "1.2.0".into(),
+            hash: MetaProvider::manifest_hash(&dir.path().join("versioned-1.2.0"))
+                .expect("Could not hash manifest"),
+            ..locked.clone()
+        });
+        let provider = MetaProvider::new(search_path.clone(),
+                                         #[cfg(feature = "context")] PathBuf::from("/"))
+            .with_lock_file(lock_file);
+        let (resolved_url, _) = provider.resolve_lib_url(&lib_url)
+            .expect("Could not resolve versioned library Url");
+        assert!(resolved_url.path().ends_with("versioned-1.2.0/math/add/"));
+
+        // a locked version whose manifest has changed is an error
+        let mut lock_file = LockFile::default();
+        lock_file.lock(LockedLibrary {
+            hash: "changed".into(),
+            ..locked
+        });
+        let provider = MetaProvider::new(search_path.clone(),
+                                         #[cfg(feature = "context")] PathBuf::from("/"))
+            .with_lock_file(lock_file);
+        assert!(provider.resolve_lib_url(&lib_url).is_err());
+
+        // a reference pinned to the version resolved resolves to the same version, even if a
+        // higher one matching the original requirement is installed since
+        let provider = MetaProvider::new(search_path,
+                                         #[cfg(feature = "context")] PathBuf::from("/"));
+        let pinned_url = lib_ref.expect("No library reference");
+        install_version(dir.path(), "1.4.0");
+        let (resolved_url, _) = provider.resolve_lib_url(&pinned_url)
+            .expect("Could not resolve pinned library Url");
+        assert!(resolved_url.path().ends_with("versioned-1.3.1/math/add/"));
+        assert!(provider.lock_file().is_empty());
+
+        let unmatched_url = Url::parse("lib://versioned/math/add?version=%5E3")
+            .expect("Couldn't form Url");
+        assert!(provider.resolve_lib_url(&unmatched_url).is_err());
+    }
+
+    #[cfg(feature = "archive_provider")]
+    #[test]
+    fn resolve_path_in_archive() {
+        use crate::content::archive_provider::package;
+        use crate::model::lib_manifest::LibraryManifest;
+        use crate::model::metadata::MetaData;
+
+        let lib_dir = tempfile::tempdir().expect("Could not create temp dir");
+        let metadata = MetaData {
+            name: "archived".into(),
+            ..MetaData::default()
+        };
+        LibraryManifest::new(Url::parse("lib://archived").expect("Could not parse Url"), metadata)
+            .write_json(&LibraryManifest::manifest_filename(lib_dir.path()))
+            .expect("Could not write manifest");
+        std::fs::create_dir(lib_dir.path().join("math")).expect("Could not create dir");
+        std::fs::write(lib_dir.path().join("math/add.toml"), b"function = \"add\"")
+            .expect("Could not write definition");
+        let archive_dir = tempfile::tempdir().expect("Could not create temp dir");
+        let _ = package(lib_dir.path(), archive_dir.path()).expect("Could not package library");
+
+        let mut search_path = Simpath::new("archive_path");
+        search_path.add_directory(&archive_dir.path().to_string_lossy());
+        let provider = &MetaProvider::new(
+            search_path,
+            #[cfg(feature = "context")]
+                PathBuf::from("/"),
+        ) as &dyn Provider;
+
+        let lib_url = Url::parse("lib://archived/math/add").expect("Couldn't form Url");
+        let (resolved_url, lib_ref) = provider.resolve_url(&lib_url, "", &["toml"])
+            .expect("Could not resolve Url in archive");
+        assert_eq!(resolved_url.scheme(), "flowlib");
+        assert!(resolved_url.path().ends_with("archived.flowlib/math/add.toml"));
+        assert_eq!(lib_ref, Some(lib_url));
+        assert_eq!(provider.get_contents(&resolved_url).expect("Could not get contents"),
+                   b"function = \"add\"");
+
+        let manifest_url = Url::parse("lib://archived").expect("Couldn't form Url");
+        let (resolved_url, _) = provider.resolve_url(&manifest_url, "manifest", &["json"])
+            .expect("Could not resolve manifest Url in archive");
+        assert!(resolved_url.path().ends_with("archived.flowlib/manifest.json"));
+    }
+
+    #[cfg(feature = "archive_provider")]
+    #[test]
+    fn resolve_file_in_zip() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let archive_path = dir.path().join("flows.zip");
+        let file = std::fs::File::create(&archive_path).expect("Could not create zip file");
+        let mut zip = zip::ZipWriter::new(file);
+        zip.start_file("hello/root.toml", zip::write::SimpleFileOptions::default())
+            .expect("Could not start file");
+        zip.write_all(b"flow = \"hello\"").expect("Could not write file");
+        let _ = zip.finish().expect("Could not finish zip file");
+
+        let provider = &MetaProvider::new(
+            Simpath::new("empty"),
+            #[cfg(feature = "context")]
+                PathBuf::from("/"),
+        ) as &dyn Provider;
+
+        let url = Url::from_file_path(archive_path.join("hello")).expect("Couldn't form Url");
+        let (resolved_url, _) = provider.resolve_url(&url, "root", &["toml"])
+            .expect("Could not resolve Url in zip archive");
+        assert!(resolved_url.path().ends_with("flows.zip/hello/root.toml"));
+        assert_eq!(provider.get_contents(&resolved_url).expect("Could not get contents"),
+                   b"flow = \"hello\"");
+
+        let file_url = Url::from_file_path(archive_path.join("hello/root.toml"))
+            .expect("Couldn't form Url");
+        assert_eq!(provider.get_contents(&file_url).expect("Could not get contents"),
+                   b"flow = \"hello\"");
+    }
+
     #[cfg(all(feature = "http_provider", feature = "online_tests"))]
     #[test]
     fn resolve_web_path() {
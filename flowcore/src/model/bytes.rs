@@ -0,0 +1,312 @@
+use std::fmt;
+use std::ops::Deref;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::errors::{bail, Result};
+
+/// The prefix of the string that a value of the `bytes` `DataType` is carried in, followed by
+/// the base64 encoding of the bytes. It is that of a data URL, so the string is meaningful outside
+/// of flow too, e.g. it can be used as the `src` of an image in a web page
+pub const BYTES_PREFIX: &str = "data:application/octet-stream;base64,";
+
+/// `Bytes` is a sequence of raw bytes, such as the contents of a file or an image, that is sent
+/// between functions as a value of the `bytes` `DataType`. It is carried in a JSON `Value` as a
+/// base64 encoded string (see [`BYTES_PREFIX`]), which is much smaller than an array of numbers
+/// and is faster to serialize and deserialize.
+///
+/// That string is not inspected to find the type of a value: a value is only known to be `bytes`
+/// from the declared type of the input or output it is sent on
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bytes(Vec<u8>);
+
+impl Bytes {
+    /// Get the `Bytes` of a `value`, that can be of the `bytes` `DataType`, or an array of
+    /// numbers from 0 to 255
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is neither, or its base64 encoding is not valid
+    pub fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::String(string) => match string.strip_prefix(BYTES_PREFIX) {
+                Some(encoded) => Ok(Bytes(STANDARD.decode(encoded)
+                    .map_err(|e| format!("Could not decode bytes: {e}"))?)),
+                None => bail!("'{}' is not a bytes value", string),
+            },
+            Value::Array(array) => array.iter()
+                .map(|byte| byte.as_u64().and_then(|number| u8::try_from(number).ok())
+                    .ok_or_else(|| format!("'{byte}' is not a byte").into()))
+                .collect::<Result<Vec<u8>>>()
+                .map(Bytes),
+            _ => bail!("'{}' is not a bytes value or an array of bytes", value),
+        }
+    }
+
+    /// Return the `Value` of the `bytes` `DataType` carrying these `Bytes`
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        Value::String(format!("{BYTES_PREFIX}{}", STANDARD.encode(&self.0)))
+    }
+
+    /// Return the bytes, consuming the `Bytes`
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Bytes> for Value {
+    fn from(bytes: Bytes) -> Self {
+        bytes.to_value()
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes", self.0.len())
+    }
+}
+
+impl Serialize for Bytes {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    {
+        serializer.serialize_str(&format!("{BYTES_PREFIX}{}", STANDARD.encode(&self.0)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Bytes, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        Bytes::from_value(&value).map_err(|e| de::Error::custom(e.to_string()))
+    }
+}
+
+// The first byte of data passed across the WASM boundary when values of the `bytes` type in it
+// are carried as raw bytes after the JSON, which cannot be the first byte of JSON on its own
+const WASM_DATA_WITH_BYTES: u8 = 0;
+
+/// Serialize `data` to pass it across the boundary between the flow runtime and a WASM
+/// implementation of a function. The values of the `bytes` type in it are carried as raw bytes
+/// after the JSON, instead of in it as base64 encoded strings. In the JSON each is replaced by an
+/// empty `bytes` value, and they are appended in the order they are found (with the keys of
+/// objects in sorted order), each preceded by its length.
+///
+/// If there are no values of the `bytes` type in `data` it is serialized as JSON only.
+///
+/// # Errors
+///
+/// Returns an error if `data` could not be serialized
+pub fn to_wasm_data<T: Serialize>(data: &T) -> Result<Vec<u8>> {
+    let mut value = serde_json::to_value(data)
+        .map_err(|e| format!("Could not serialize data: {e}"))?;
+    let mut sections = vec![];
+    take_bytes(&mut value, &mut sections);
+    let json = serde_json::to_vec(&value)
+        .map_err(|e| format!("Could not serialize data: {e}"))?;
+    if sections.is_empty() {
+        return Ok(json);
+    }
+
+    let mut wasm_data = vec![WASM_DATA_WITH_BYTES];
+    for section in std::iter::once(&json).chain(sections.iter()) {
+        wasm_data.extend_from_slice(&u32::try_from(section.len())
+            .map_err(|_| "Data is too large to pass to WASM")?.to_le_bytes());
+        wasm_data.extend_from_slice(section);
+    }
+    Ok(wasm_data)
+}
+
+/// Deserialize data passed across the boundary between the flow runtime and a WASM implementation
+/// of a function, that was serialized using [`to_wasm_data`]
+///
+/// # Errors
+///
+/// Returns an error if the data is not valid
+pub fn from_wasm_data<T: DeserializeOwned>(wasm_data: &[u8]) -> Result<T> {
+    let Some((&WASM_DATA_WITH_BYTES, mut remaining)) = wasm_data.split_first() else {
+        return serde_json::from_slice(wasm_data)
+            .map_err(|e| format!("Could not deserialize data: {e}").into());
+    };
+
+    let mut sections = vec![];
+    while !remaining.is_empty() {
+        let (length, rest) = remaining.split_first_chunk::<4>()
+            .ok_or("Could not read length of data section")?;
+        let length = usize::try_from(u32::from_le_bytes(*length))
+            .map_err(|_| "Data section is too large")?;
+        if rest.len() < length {
+            bail!("Data section of {} bytes is shorter than its length of {}", rest.len(), length);
+        }
+        let (section, rest) = rest.split_at(length);
+        sections.push(section);
+        remaining = rest;
+    }
+
+    let mut sections = sections.into_iter();
+    let mut value: Value = serde_json::from_slice(sections.next().ok_or("No JSON data")?)
+        .map_err(|e| format!("Could not deserialize data: {e}"))?;
+    restore_bytes(&mut value, &mut sections)?;
+    if sections.next().is_some() {
+        bail!("More bytes data sections than bytes values");
+    }
+    serde_json::from_value(value).map_err(|e| format!("Could not deserialize data: {e}").into())
+}
+
+// The keys of an object in sorted order, so that values are visited in the same order whatever
+// order they are held in
+fn sorted_keys(map: &serde_json::Map<String, Value>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort_unstable();
+    keys
+}
+
+// Take the bytes of each value of the `bytes` type in `value` and add them to `sections`,
+// leaving an empty `bytes` value in its place
+fn take_bytes(value: &mut Value, sections: &mut Vec<Vec<u8>>) {
+    match value {
+        Value::String(string) => {
+            if let Some(bytes) = string.strip_prefix(BYTES_PREFIX)
+                .and_then(|encoded| STANDARD.decode(encoded).ok()) {
+                sections.push(bytes);
+                *string = BYTES_PREFIX.to_string();
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(|element| take_bytes(element, sections)),
+        Value::Object(map) => for key in sorted_keys(map) {
+            if let Some(element) = map.get_mut(&key) {
+                take_bytes(element, sections);
+            }
+        },
+        _ => {}
+    }
+}
+
+// Put the bytes of each section back into the empty `bytes` values in `value`, in the order they
+// were taken out of it
+fn restore_bytes<'a>(value: &mut Value, sections: &mut impl Iterator<Item=&'a [u8]>) -> Result<()> {
+    match value {
+        Value::String(string) if string == BYTES_PREFIX => {
+            let bytes = sections.next().ok_or("Fewer bytes data sections than bytes values")?;
+            string.push_str(&STANDARD.encode(bytes));
+        }
+        Value::Array(array) => for element in array {
+            restore_bytes(element, sections)?;
+        },
+        Value::Object(map) => for key in sorted_keys(map) {
+            if let Some(element) = map.get_mut(&key) {
+                restore_bytes(element, sections)?;
+            }
+        },
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::errors::Result;
+
+    use super::{Bytes, BYTES_PREFIX, from_wasm_data, to_wasm_data};
+
+    #[test]
+    fn bytes_to_value_and_back() {
+        let bytes = Bytes::from(vec![0, 128, 255]);
+        let value = bytes.to_value();
+        assert_eq!(value, json!(format!("{BYTES_PREFIX}AID/")));
+        assert_eq!(Bytes::from_value(&value).expect("Could not get bytes"), bytes);
+    }
+
+    #[test]
+    fn bytes_from_array_of_numbers() {
+        assert_eq!(Bytes::from_value(&json!([1, 2, 3])).expect("Could not get bytes").into_vec(),
+                   vec![1, 2, 3]);
+        assert!(Bytes::from_value(&json!([1, 256])).is_err());
+    }
+
+    #[test]
+    fn strings_are_not_bytes() {
+        assert!(Bytes::from_value(&json!("hello")).is_err());
+        assert!(Bytes::from_value(&json!(42)).is_err());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let bytes = Bytes::from(&b"hello"[..]);
+        let serialized = serde_json::to_string(&bytes).expect("Could not serialize");
+        assert_eq!(serde_json::from_str::<Bytes>(&serialized).expect("Could not deserialize"),
+                   bytes);
+    }
+
+    #[test]
+    fn wasm_data_without_bytes_is_json() {
+        let inputs = vec![json!("hello"), json!({"a": [1, 2]})];
+        let wasm_data = to_wasm_data(&inputs).expect("Could not serialize");
+        assert_eq!(wasm_data, serde_json::to_vec(&inputs).expect("Could not serialize"));
+        assert_eq!(from_wasm_data::<Vec<serde_json::Value>>(&wasm_data).expect("Could not deserialize"),
+                   inputs);
+    }
+
+    #[test]
+    fn wasm_data_carries_raw_bytes() {
+        let image = vec![0u8, 1, 2, 255];
+        let inputs = vec![json!({"z": Bytes::from(image.clone()).to_value(), "a": json!(1)}),
+                          Bytes::from(vec![]).to_value(), json!(BYTES_PREFIX),
+                          json!("text"), Bytes::from(&b"file"[..]).to_value()];
+        let wasm_data = to_wasm_data(&inputs).expect("Could not serialize");
+        assert!(wasm_data.windows(image.len()).any(|window| window == image.as_slice()));
+        assert!(!wasm_data.windows(4).any(|window| window == b"AAEC"));
+        assert_eq!(from_wasm_data::<Vec<serde_json::Value>>(&wasm_data).expect("Could not deserialize"),
+                   inputs);
+    }
+
+    #[test]
+    fn wasm_data_result_round_trip() {
+        let output = Some(json!({"bytes": Bytes::from(&b"contents"[..]).to_value()}));
+        let result: Result<(Option<serde_json::Value>, bool)> = Ok((output.clone(), true));
+        let wasm_data = to_wasm_data(&result).expect("Could not serialize");
+        let returned: Result<(Option<serde_json::Value>, bool)> = from_wasm_data(&wasm_data)
+            .expect("Could not deserialize");
+        assert_eq!(returned.expect("Result was an error").0, output);
+    }
+
+    #[test]
+    fn invalid_wasm_data() {
+        assert!(from_wasm_data::<Vec<serde_json::Value>>(&[0, 10, 0, 0, 0, b'[']).is_err());
+        let mut wasm_data = to_wasm_data(&vec![Bytes::from(vec![1]).to_value()])
+            .expect("Could not serialize");
+        wasm_data.extend_from_slice(&[1, 0, 0, 0, 7]);
+        assert!(from_wasm_data::<Vec<serde_json::Value>>(&wasm_data).is_err());
+    }
+}
@@ -1,14 +1,18 @@
+use std::collections::BTreeMap;
 use std::fmt;
 
 use log::debug;
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::errors::{Result, ResultExt};
 use crate::model::datatype::DataType;
 use crate::model::io::IO;
 use crate::model::name::Name;
+use crate::model::parameter;
 use crate::model::route::HasRoute;
 use crate::model::route::Route;
+use crate::model::source_location::SourceLocation;
 use crate::model::validation::Validate;
 
 /// `Connection` defines a connection between the output of one function or flow to the input
@@ -36,6 +40,9 @@ pub struct Connection {
     /// when collapsing connections to reduce work and avoid infinite recursion
     #[serde(skip)]
     level: usize,
+    /// `source_location` is where in a flow definition file this connection was defined, if known
+    #[serde(skip)]
+    source_location: Option<SourceLocation>,
 }
 
 /// `Direction` defines whether a `Connection` is coming from an IO or to an IO
@@ -95,7 +102,6 @@ impl Connection {
     }
 
     /// Return the name
-    #[cfg(feature = "debugger")]
     #[must_use]
     pub fn name(&self) -> &Name {
         &self.name
@@ -107,25 +113,18 @@ impl Connection {
     ///
     /// Will return `Err` if the source and destinations do not have any compatible types
     pub fn connect(&mut self, from_io: IO, to_io: IO, level: usize) -> Result<()> {
-        // are we selecting from a sub-route of an IO, such as an array index or element of output object?
-        // TODO this requires the accumulation of the subroute to be done during connection building #1192
-        let from_io_subroute = "";
-        DataType::compatible_types(
-            from_io.datatypes(),
-            to_io.datatypes(),
-            &Route::from(from_io_subroute),
-        )
-        .chain_err(|| {
-            format!(
-                "Incompatible source and destination types:\n\
-            Source:      '{}/{from_io_subroute}' of types {:?}\n\
-            Destination: '{}' of types {:?}",
-                from_io.route(),
-                from_io.datatypes(),
-                to_io.route(),
-                to_io.datatypes()
-            )
-        })?;
+        // any sub-route of the source IO (array element or member of an output's structure) has
+        // already been used to select the IO's types, in `find_by_subroute()`
+        DataType::compatible_types(from_io.datatypes(), to_io.datatypes(), &Route::default())
+            .chain_err(|| {
+                format!(
+                    "Incompatible types in connection from '{}' of types [{}] to '{}' of types [{}]",
+                    from_io.route(),
+                    DataType::type_list(from_io.datatypes()),
+                    to_io.route(),
+                    DataType::type_list(to_io.datatypes())
+                )
+            })?;
         debug!(
             "Connection built from '{}' to '{}'",
             from_io.route(),
@@ -138,6 +137,26 @@ impl Connection {
         Ok(())
     }
 
+    /// Substitute the flow parameter `values` for any references to them in the routes of
+    /// this connection
+    pub fn substitute_parameters(&mut self, values: &BTreeMap<String, Value>) {
+        self.from = Route::from(parameter::substitute_str(self.from.as_ref(), values));
+        for destination in &mut self.to {
+            *destination = Route::from(parameter::substitute_str(destination.as_ref(), values));
+        }
+    }
+
+    /// Return where in a flow definition file this connection was defined, if known
+    #[must_use]
+    pub fn source_location(&self) -> Option<&SourceLocation> {
+        self.source_location.as_ref()
+    }
+
+    /// Set where in a flow definition file this connection was defined
+    pub fn set_source_location(&mut self, source_location: SourceLocation) {
+        self.source_location = Some(source_location);
+    }
+
     /// Return the `from` Route specified in this connection
     #[must_use]
     pub fn from(&self) -> &Route {
@@ -32,8 +32,12 @@ pub const ARRAY_TYPE: &str = "array";
 /// Json "null" data type
 pub const NULL_TYPE: &str = "null";
 
+/// "bytes" data type, for raw binary data, carried as a base64 encoded string (see
+/// [`Bytes`](crate::model::bytes::Bytes))
+pub const BYTES_TYPE: &str = "bytes";
+
 const DATA_TYPES: &[&str] = &[OBJECT_TYPE, STRING_TYPE, NUMBER_TYPE, BOOLEAN_TYPE, ARRAY_TYPE,
-    NULL_TYPE, GENERIC_TYPE];
+    NULL_TYPE, BYTES_TYPE, GENERIC_TYPE];
 
 /// `DataType` is just a String defining what data type is being used
 #[derive(Hash, Debug, PartialEq, Ord, PartialOrd, Eq, Clone, Default, Serialize, Deserialize)]
@@ -241,26 +245,26 @@ impl DataType {
     /// types of the two `IO` are incompatible
     pub fn compatible_types(from: &[DataType], to: &[DataType], from_subroute: &Route) -> Result<()> {
         if from.is_empty() || to.is_empty() {
-            bail!("Either from or to IO does nopt specify any types")
+            bail!("Either from or to IO does not specify any types")
         }
 
         for from_type in from {
-            let mut compatible_destination_type = false;
-            for to_type in to {
-                let from_sub_type = Self::subtype_using_subroute(from_type, from_subroute)?;
-                if Self::two_compatible_types(&from_sub_type, to_type).is_ok() {
-                    compatible_destination_type = true;
-                }
-            }
-            if !compatible_destination_type {
-                bail!("Could not find a compatible destination type in '{:?}' for '{:?}'",
-                from, to)
+            let from_sub_type = Self::subtype_using_subroute(from_type, from_subroute)?;
+            if !to.iter().any(|to_type| Self::two_compatible_types(&from_sub_type, to_type).is_ok()) {
+                bail!("The source type '{}' is not compatible with any of the destination types [{}]",
+                    from_sub_type, Self::type_list(to))
             }
         }
 
         Ok(()) // found a compatible source and destination type pair
     }
 
+    /// Format a list of types for use in messages, e.g. "'string', 'array/number'"
+    #[must_use]
+    pub fn type_list(types: &[DataType]) -> String {
+        types.iter().map(|datatype| format!("'{datatype}'")).collect::<Vec<String>>().join(", ")
+    }
+
     fn subtype_using_subroute(full_type: &DataType, subroute: &Route) -> Result<DataType> {
         if subroute.depth() == 0 {
             return Ok(full_type.clone());
@@ -328,7 +332,9 @@ impl DataType {
 
 #[cfg(test)]
 mod test {
-    use crate::model::datatype::{ARRAY_TYPE, GENERIC_TYPE, NUMBER_TYPE, OBJECT_TYPE, STRING_TYPE};
+    use crate::model::bytes::Bytes;
+    use crate::model::datatype::{ARRAY_TYPE, BYTES_TYPE, GENERIC_TYPE, NUMBER_TYPE, OBJECT_TYPE,
+                                 STRING_TYPE};
     use crate::model::route::Route;
 
     use super::DataType;
@@ -385,6 +391,29 @@ mod test {
         json_type.valid().expect("'object' DataType should be valid");
     }
 
+    #[test]
+    fn valid_data_bytes_type() {
+        DataType::from(BYTES_TYPE).valid().expect("'bytes' DataType should be valid");
+        DataType::from(format!("{ARRAY_TYPE}/{BYTES_TYPE}")).valid()
+            .expect("'array/bytes' DataType should be valid");
+    }
+
+    #[test]
+    fn bytes_value_type_not_sniffed() {
+        assert_eq!(DataType::value_type(&Bytes::from(vec![1, 2]).to_value())
+                       .expect("Could not get type"), DataType::from(STRING_TYPE));
+    }
+
+    #[test]
+    fn bytes_not_compatible_with_string() {
+        assert!(DataType::compatible_types(&[DataType::from(BYTES_TYPE)],
+                                           &[DataType::from(STRING_TYPE)],
+                                           &Route::default()).is_err());
+        assert!(DataType::compatible_types(&[DataType::from(BYTES_TYPE)],
+                                           &[DataType::from(format!("{ARRAY_TYPE}/{BYTES_TYPE}"))],
+                                           &Route::default()).is_ok());
+    }
+
     #[test]
     fn invalid_data_type() {
         let string_type = DataType::from("foo");
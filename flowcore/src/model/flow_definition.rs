@@ -5,6 +5,7 @@ use std::mem::take;
 use error_chain::bail;
 use log::{debug, error, trace};
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 use url::Url;
 
 use crate::errors::{Result, ResultExt};
@@ -19,6 +20,8 @@ use crate::model::io::IOSet;
 use crate::model::metadata::MetaData;
 use crate::model::name::HasName;
 use crate::model::name::Name;
+use crate::model::parameter;
+use crate::model::parameter::Parameter;
 use crate::model::process::Process;
 use crate::model::process::Process::FlowProcess;
 use crate::model::process::Process::FunctionProcess;
@@ -27,6 +30,7 @@ use crate::model::route::{Route, RouteType};
 use crate::model::route::HasRoute;
 use crate::model::route::SetIORoutes;
 use crate::model::route::SetRoute;
+use crate::model::source_location::{line_of, SourceLocation};
 use crate::model::validation::Validate;
 
 /// `FlowDefinition` defines (at compile time) a parent or child flow in the nested flow hierarchy
@@ -42,8 +46,11 @@ pub struct FlowDefinition {
     /// `outputs` that this flow defines
     #[serde(default, rename = "output")]
     pub outputs: IOSet,
+    /// `parameters` that this flow declares, whose values are supplied by references to it
+    #[serde(default, rename = "parameter")]
+    pub parameters: Vec<Parameter>,
     /// Set of sub-processes referenced (used) in this flow
-    #[serde(default, rename = "process")]
+    #[serde(default, rename = "process", deserialize_with = "super::process_reference::process_references")]
     pub process_refs: Vec<ProcessReference>,
     /// `connections` within this flow, from flow input or to flow outputs
     #[serde(default, rename = "connection")]
@@ -90,6 +97,14 @@ impl Validate for FlowDefinition {
             output.validate()?;
         }
 
+        for parameter in &self.parameters {
+            parameter.validate()?;
+        }
+
+        for process_ref in &self.process_refs {
+            process_ref.validate()?;
+        }
+
         for connection in &self.connections {
             connection.validate()?;
         }
@@ -133,6 +148,7 @@ impl Default for FlowDefinition {
             name: String::default(),
             inputs: vec![],
             outputs: vec![],
+            parameters: vec![],
             process_refs: vec![],
             connections: vec![],
             metadata: MetaData::default(),
@@ -263,6 +279,160 @@ impl FlowDefinition {
         self.validate()
     }
 
+    /// Substitute the values of this flow's parameters, from those `supplied` by the process
+    /// reference to it or their defaults, for references to them in its process references and
+    /// connections
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the `supplied` values do not match the parameters declared
+    pub fn set_parameters(&mut self, supplied: &BTreeMap<String, Value>) -> Result<()> {
+        let values = parameter::resolve(&self.parameters, supplied)
+            .chain_err(|| format!("Invalid parameters for flow '{}'", self.name))?;
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        for process_ref in &mut self.process_refs {
+            process_ref.source = parameter::substitute_str(&process_ref.source, &values);
+            for initializer in process_ref.initializations.values_mut() {
+                *initializer = parameter::substitute_initializer(initializer, &values);
+            }
+            for value in process_ref.parameters.values_mut() {
+                *value = parameter::substitute_value(value, &values);
+            }
+        }
+
+        for connection in &mut self.connections {
+            connection.substitute_parameters(&values);
+        }
+
+        Ok(())
+    }
+
+    /// Record where in this flow's definition file, with contents `content`, each of its
+    /// connections was defined
+    pub fn set_connection_locations(&mut self, content: &str) {
+        let mut froms: Vec<&Route> = vec![];
+        let mut locations = vec![];
+        for connection in &self.connections {
+            let occurrence = froms.iter().filter(|from| **from == connection.from()).count();
+            locations.push(SourceLocation::new(&self.source_url,
+                line_of(content, "from", connection.from().as_ref(), occurrence)));
+            froms.push(connection.from());
+        }
+
+        for (connection, location) in self.connections.iter_mut().zip(locations) {
+            connection.set_source_location(location);
+        }
+    }
+
+    /// Serialize this flow, as it may have been edited by adding or removing process references
+    /// and connections, into flow definition TOML that can be parsed again. Only the fields read
+    /// from a definition are written, not those set when the flow is loaded or compiled
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a process reference has an inline function definition, which cannot
+    /// be written yet, or if the definition cannot be serialized
+    pub fn to_toml(&self) -> Result<String> {
+        let mut definition = serde_json::Map::new();
+        definition.insert("flow".into(), Value::String(self.name.to_string()));
+        if !self.docs.is_empty() {
+            definition.insert("docs".into(), Value::String(self.docs.clone()));
+        }
+        if self.metadata != MetaData::default() {
+            definition.insert("metadata".into(), serde_json::to_value(&self.metadata)
+                .chain_err(|| "Could not serialize flow metadata")?);
+        }
+        Self::insert_array(&mut definition, "input", self.inputs.iter().map(Self::io_value));
+        Self::insert_array(&mut definition, "output", self.outputs.iter().map(Self::io_value));
+        Self::insert_array(&mut definition, "parameter", self.parameters.iter()
+            .map(serde_json::to_value).collect::<serde_json::Result<Vec<Value>>>()
+            .chain_err(|| "Could not serialize flow parameters")?);
+        Self::insert_array(&mut definition, "process", self.process_refs.iter()
+            .map(Self::process_value).collect::<Result<Vec<Value>>>()?);
+        Self::insert_array(&mut definition, "connection",
+                           self.connections.iter().map(Self::connection_value));
+
+        toml::to_string(&Value::Object(definition))
+            .chain_err(|| format!("Could not serialize flow '{}' to TOML", self.name))
+    }
+
+    fn insert_array<I: IntoIterator<Item = Value>>(definition: &mut serde_json::Map<String, Value>,
+                                                   key: &str, values: I) {
+        let values: Vec<Value> = values.into_iter().collect();
+        if !values.is_empty() {
+            definition.insert(key.into(), Value::Array(values));
+        }
+    }
+
+    // A single value for one entry, or an array of them for more
+    fn one_or_array(values: Vec<Value>) -> Value {
+        if values.len() == 1 {
+            values.into_iter().next().unwrap_or_default()
+        } else {
+            Value::Array(values)
+        }
+    }
+
+    fn io_value(io: &IO) -> Value {
+        let mut value = serde_json::Map::new();
+        if !io.name().is_empty() {
+            value.insert("name".into(), Value::String(io.name().to_string()));
+        }
+        value.insert("type".into(), Self::one_or_array(io.datatypes().iter()
+            .map(|datatype| Value::String(datatype.to_string())).collect()));
+        if let Some(initializer) = io.get_initializer() {
+            value.insert("value".into(), Self::initializer_value(initializer));
+        }
+        if let Some(schema) = io.get_schema() {
+            value.insert("schema".into(), schema.clone());
+        }
+        Value::Object(value)
+    }
+
+    fn initializer_value(initializer: &InputInitializer) -> Value {
+        let (kind, value) = match initializer {
+            InputInitializer::Once(value) => ("once", value),
+            InputInitializer::Always(value) => ("always", value),
+        };
+        Value::Object(serde_json::Map::from_iter([(kind.to_string(), value.clone())]))
+    }
+
+    fn process_value(process_ref: &ProcessReference) -> Result<Value> {
+        if process_ref.inline.is_some() {
+            bail!("The process reference '{}' has an inline function definition, which cannot \
+                be serialized", process_ref.alias);
+        }
+        let mut value = serde_json::Map::new();
+        if !process_ref.alias.is_empty() {
+            value.insert("alias".into(), Value::String(process_ref.alias.to_string()));
+        }
+        value.insert("source".into(), Value::String(process_ref.source.clone()));
+        if !process_ref.initializations.is_empty() {
+            value.insert("input".into(), Value::Object(process_ref.initializations.iter()
+                .map(|(input, initializer)| (input.clone(), Self::initializer_value(initializer)))
+                .collect()));
+        }
+        if !process_ref.parameters.is_empty() {
+            value.insert("parameter".into(), Value::Object(process_ref.parameters.clone()
+                .into_iter().collect()));
+        }
+        Ok(Value::Object(value))
+    }
+
+    fn connection_value(connection: &Connection) -> Value {
+        let mut value = serde_json::Map::new();
+        if !connection.name().is_empty() {
+            value.insert("name".into(), Value::String(connection.name().to_string()));
+        }
+        value.insert("from".into(), Value::String(connection.from().to_string()));
+        value.insert("to".into(), Self::one_or_array(connection.to().iter()
+            .map(|route| Value::String(route.to_string())).collect()));
+        Value::Object(value)
+    }
+
     /// Check if the flow can be run (it could be a sub-flow not a context level runnable flow)
     #[must_use]
     pub fn is_runnable(&self) -> bool {
@@ -368,19 +538,20 @@ impl FlowDefinition {
     pub fn build_connections(&mut self, level: usize) -> Result<()> {
         debug!("Building connections for flow '{}'", self.name);
 
-        let mut error_count = 0;
+        let mut errors = vec![];
 
         // get connections out of self - so we can use immutable references to self inside loop
         let mut connections = take(&mut self.connections);
 
         for connection in&mut  connections {
             if let Err(e) = self.build_connection(connection, level) {
-                error_count += 1;
-                error!("{}", e);
+                let error = e.iter().map(ToString::to_string).collect::<Vec<String>>().join("\n\t");
+                error!("{}", error);
+                errors.push(error);
             }
         }
 
-        if error_count == 0 {
+        if errors.is_empty() {
             debug!(
                 "All connections inside flow '{}' successfully built",
                 self.source_url
@@ -388,13 +559,22 @@ impl FlowDefinition {
             Ok(())
         } else {
             bail!(
-                "{} connection errors found in flow '{}'",
-                error_count,
-                self.source_url
+                "{} connection errors found in flow '{}':\n{}",
+                errors.len(),
+                self.source_url,
+                errors.join("\n")
             )
         }
     }
 
+    // The url of the definition of the IO referred to by `route` in a connection in this flow.
+    // That of a sub-process for "process_name/io_name", or this flow's own for "input/.." or "output/.."
+    fn definition_url(&self, route: &Route) -> &Url {
+        route.as_ref().split('/').next()
+            .and_then(|process_name| self.subprocesses.get(process_name))
+            .map_or(&self.source_url, Process::source_url)
+    }
+
     // Connection to/from Formats:
     // "input/input_name"       - An Input of this Flow
     // "output/output_name"     - An Output of this Flow
@@ -414,7 +594,11 @@ impl FlowDefinition {
                 Ok(to_io) => {
                     trace!("Found connection destination:\n{:#?}", to_io);
                     let mut new_connection = connection.clone();
-                    new_connection.connect(from_io.clone(), to_io, level)?;
+                    new_connection.connect(from_io.clone(), to_io, level)
+                        .chain_err(|| format!("Connection from '{}' (defined in '{}') to '{}' \
+                            (defined in '{}') in flow '{}' has incompatible types",
+                            connection.from(), self.definition_url(connection.from()),
+                            to_route, self.definition_url(to_route), self.source_url))?;
                     self.connections.push(new_connection);
                 }
                 Err(error) => {
@@ -760,4 +944,90 @@ mod test {
             assert!(flow.build_connections(0).is_err());
         }
     }
+
+    mod to_toml {
+        use crate::model::connection::Connection;
+        use crate::model::flow_definition::FlowDefinition;
+        use crate::model::process_reference::ProcessReference;
+
+        const DEFINITION: &str = r#"
+flow = "edited"
+docs = "DESCRIPTION.md"
+
+[metadata]
+version = "1.0.0"
+authors = ["A N Other"]
+
+[[input]]
+name = "count"
+type = ["number", "array/number"]
+value = { once = 3 }
+
+[[output]]
+type = "string"
+
+[[process]]
+alias = "adder"
+source = "lib://flowstdlib/math/add"
+input.i1 = { once = 0 }
+input.i2 = { always = 1 }
+
+[[connection]]
+name = "sum"
+from = "adder"
+to = ["adder/i2", "output"]
+"#;
+
+        fn parse(definition: &str) -> FlowDefinition {
+            toml::from_str(definition).expect("Could not parse flow definition")
+        }
+
+        #[test]
+        fn round_trip() {
+            let flow = parse(DEFINITION);
+            let serialized = flow.to_toml().expect("Could not serialize flow");
+            let reparsed = parse(&serialized);
+            assert_eq!(reparsed.to_toml().expect("Could not serialize flow"), serialized);
+            assert_eq!(reparsed.name, flow.name);
+            assert_eq!(reparsed.metadata, flow.metadata);
+            let input = flow.inputs.first().expect("Could not get first input");
+            let reparsed_input = reparsed.inputs.first().expect("Could not get first input");
+            assert_eq!(reparsed_input.datatypes(), input.datatypes());
+            assert_eq!(reparsed_input.get_initializer(), input.get_initializer());
+            let process_ref = flow.process_refs.first().expect("Could not get first process");
+            let reparsed_process_ref = reparsed.process_refs.first()
+                .expect("Could not get first process");
+            assert_eq!(reparsed_process_ref.initializations, process_ref.initializations);
+            let connection = flow.connections.first().expect("Could not get first connection");
+            let reparsed_connection = reparsed.connections.first()
+                .expect("Could not get first connection");
+            assert_eq!(reparsed_connection.to(), connection.to());
+        }
+
+        #[test]
+        fn edited_flow_round_trip() {
+            let mut flow = parse(DEFINITION);
+            flow.process_refs.push(ProcessReference {
+                source: "context://stdio/stdout".into(),
+                ..Default::default()
+            });
+            flow.connections.push(Connection::new("adder", "stdout"));
+
+            let reparsed = parse(&flow.to_toml().expect("Could not serialize flow"));
+            assert_eq!(reparsed.process_refs.len(), 2);
+            assert_eq!(reparsed.process_refs.get(1).expect("Could not get process[1]").source,
+                       "context://stdio/stdout");
+            assert_eq!(reparsed.connections.len(), 2);
+            assert_eq!(reparsed.connections.get(1).expect("Could not get connection[1]").from()
+                           .to_string(), "adder");
+        }
+
+        #[test]
+        fn inline_function_not_serialized() {
+            let mut flow = parse(DEFINITION);
+            flow.process_refs.first_mut().expect("Could not get first process").inline =
+                Some(Default::default());
+            assert!(flow.to_toml().is_err());
+        }
+    }
 }
@@ -7,9 +7,9 @@ use std::fmt::Display;
 use serde_derive::{Deserialize, Serialize};
 use url::Url;
 
-use crate::deserializers::deserializer::get;
 use crate::errors::{Result, ResultExt};
 use crate::model::flow_definition::FlowDefinition;
+use crate::model::manifest_format::{MANIFEST_EXTENSIONS, ManifestFormat};
 use crate::model::metadata::MetaData;
 use crate::model::runtime_function::RuntimeFunction;
 use crate::provider::Provider;
@@ -144,28 +144,25 @@ impl FlowManifest {
     }
 
     /// Load, or Deserialize, a manifest from a `source` Url using `provider`
-    /// Sets all `location_url` fields to be URLs, a file URL for provided implementations
+    /// Sets all `location_url` fields to be URLs, a file URL for provided implementations.
+    /// The manifest can be in any `ManifestFormat`, which is detected from its contents.
     ///
     /// # Errors
     ///
     /// Returns `Err`if `manifest_url` cannot be resolved to a real url, the contents cannot be
-    /// read from the resolved url, if the contents are not valid Utf8, or if the implementation
-    /// url for the function definition is invalid
+    /// read from the resolved url, if the contents are not a valid manifest, or if the
+    /// implementation url for the function definition is invalid
     pub fn load(provider: &dyn Provider, manifest_url: &Url) -> Result<(FlowManifest, Url)> {
         let (resolved_url, _) = provider
-            .resolve_url(manifest_url, DEFAULT_MANIFEST_FILENAME, &["json"])
-            .chain_err(|| "Could not resolve url for manifest.json")?;
+            .resolve_url(manifest_url, DEFAULT_MANIFEST_FILENAME, MANIFEST_EXTENSIONS)
+            .chain_err(|| "Could not resolve url for manifest")?;
 
         let contents = provider
             .get_contents(&resolved_url)
             .chain_err(|| "Could not get contents while attempting to load manifest")?;
 
         let url = resolved_url.clone();
-        let content =
-            String::from_utf8(contents).chain_err(|| "Could not convert from utf8 to String")?;
-        let deserializer = get::<FlowManifest>(&resolved_url)?;
-        let mut manifest = deserializer
-            .deserialize(&content, Some(&resolved_url))
+        let mut manifest: FlowManifest = ManifestFormat::deserialize(&contents)
             .chain_err(|| format!("Could not create a FlowManifest from '{manifest_url}'"))?;
 
         // normalize the implementation_locations into URLs.
@@ -188,7 +185,7 @@ mod test {
     use crate::model::runtime_function::RuntimeFunction;
     use crate::provider::Provider;
 
-    use super::{FlowManifest, MetaData};
+    use super::{FlowManifest, ManifestFormat, MetaData};
 
     fn test_meta_data() -> MetaData {
         MetaData {
@@ -248,6 +245,19 @@ mod test {
         assert_eq!(manifest.functions.len(), 1);
     }
 
+    #[test]
+    fn cbor_round_trip() {
+        let mut manifest = FlowManifest::new(test_meta_data());
+        manifest.add_function(test_function());
+        manifest.add_context_reference(&Url::parse("context://stdio/stdout")
+            .expect("Could not parse URL"));
+
+        let contents = ManifestFormat::Cbor.serialize(&manifest).expect("Could not serialize");
+        let loaded: FlowManifest = ManifestFormat::deserialize(&contents)
+            .expect("Could not deserialize");
+        assert_eq!(loaded, manifest);
+    }
+
     #[test]
     fn load_manifest() {
         let test_content = "{
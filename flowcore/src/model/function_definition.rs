@@ -16,6 +16,8 @@ use crate::model::route::HasRoute;
 use crate::model::route::Route;
 use crate::model::route::SetIORoutes;
 use crate::model::route::SetRoute;
+use crate::model::runtime_function::Priority;
+use crate::model::source_location::SourceLocation;
 use crate::model::validation::Validate;
 
 /// `FunctionDefinition` defines a Function (compile time) that implements some processing in the flow hierarchy
@@ -28,6 +30,10 @@ pub struct FunctionDefinition {
     /// Is this an impure function that interacts with the environment
     #[serde(default)]
     pub impure: bool,
+    /// Is this a pure function, whose outputs depend only on its inputs, so that the result of a
+    /// job can be reused for later jobs with the same input values
+    #[serde(default)]
+    pub pure: bool,
     /// Name of the source file for the function implementation
     pub source: String,
     /// Name of any docs file associated with this Function
@@ -42,6 +48,13 @@ pub struct FunctionDefinition {
     /// The set of outputs this function generates when executed
     #[serde(default, rename = "output")]
     pub outputs: IOSet,
+    /// The number of times a job for this function is retried if its implementation returns an
+    /// error, overriding the default retry policy used when the flow is run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<usize>,
+    /// The `Priority` with which jobs for this function are dispatched when executors are busy
+    #[serde(default, skip_serializing_if = "Priority::is_normal")]
+    pub priority: Priority,
     /// As a function can be used multiple times in a single flow, the repeated instances must
     /// be referred to using an alias to disambiguate which instance is being referred to
     #[serde(skip_deserializing)]
@@ -70,6 +83,9 @@ pub struct FunctionDefinition {
     /// the `id` of the `FlowDefinition` that this `FunctionDefinition` lies within in the hierarchy
     #[serde(skip_deserializing)]
     pub(crate) flow_id: usize,
+    /// Where in a flow definition file this function was referenced from, if known
+    #[serde(skip)]
+    pub reference_location: Option<SourceLocation>,
 }
 
 impl Default for FunctionDefinition {
@@ -77,11 +93,14 @@ impl Default for FunctionDefinition {
         FunctionDefinition {
             name: String::default(),
             impure: false,
+            pure: false,
             source: String::new(),
             docs: String::new(),
             build_type: String::new(),
             inputs: vec![],
             outputs: vec![],
+            retries: None,
+            priority: Priority::Normal,
             alias: String::default(),
             source_url: FunctionDefinition::default_url(),
             route: Route::default(),
@@ -91,6 +110,7 @@ impl Default for FunctionDefinition {
             output_connections: vec![],
             function_id: 0,
             flow_id: 0,
+            reference_location: None,
         }
     }
 }
@@ -139,6 +159,7 @@ impl FunctionDefinition {
         FunctionDefinition {
             name,
             impure,
+            pure: false,
             source,
             docs: String::default(),
             alias,
@@ -153,6 +174,9 @@ impl FunctionDefinition {
             function_id: id,
             flow_id,
             build_type: String::default(),
+            retries: None,
+            priority: Priority::Normal,
+            reference_location: None,
         }
     }
 
@@ -227,12 +251,34 @@ impl FunctionDefinition {
         self.impure
     }
 
-    // A function can only be impure if it is provided by 'context'
+    /// Return true if this function is pure, and so the results of its jobs can be memoized
+    #[must_use]
+    pub fn is_pure(&self) -> bool {
+        self.pure
+    }
+
+    /// Get the number of times a failed job for this function should be retried, if specified
+    #[must_use]
+    pub fn get_retries(&self) -> Option<usize> {
+        self.retries
+    }
+
+    /// Get the `Priority` with which jobs for this function are dispatched
+    #[must_use]
+    pub fn get_priority(&self) -> Priority {
+        self.priority
+    }
+
+    // A function can only be impure if it is provided by 'context', and cannot also be pure
     fn check_impurity(&self, url: &Url) -> Result<()> {
         if self.impure && url.scheme() != "context" {
             bail!("Only functions provided by 'context' can be impure ('{url}')");
         }
 
+        if self.impure && self.pure {
+            bail!("A function cannot be both pure and impure ('{}')", url);
+        }
+
         Ok(())
     }
 
@@ -292,6 +338,17 @@ impl FunctionDefinition {
         &self.source_url
     }
 
+    /// Get where in a flow definition file this function was referenced from, if known
+    #[must_use]
+    pub fn get_reference_location(&self) -> Option<&SourceLocation> {
+        self.reference_location.as_ref()
+    }
+
+    /// Set where in a flow definition file this function was referenced from
+    pub fn set_reference_location(&mut self, reference_location: SourceLocation) {
+        self.reference_location = Some(reference_location);
+    }
+
     // Set the source url where this function is defined
     fn set_source_url(&mut self, source: &Url) {
         self.source_url = source.clone();
@@ -442,6 +499,7 @@ mod test {
     use crate::model::route::HasRoute;
     use crate::model::route::Route;
     use crate::model::route::SetRoute;
+    use crate::model::runtime_function::Priority;
     use crate::model::validation::Validate;
 
     use super::FunctionDefinition;
@@ -504,6 +562,77 @@ mod test {
         assert!(function.validate().is_err());
     }
 
+    #[test]
+    fn deserialize_retries() {
+        let function_str = "
+        function = 'test_function'
+        source = 'test.rs'
+        retries = 3
+        [[output]]
+        ";
+
+        let function: FunctionDefinition =
+            toml_from_str(function_str).expect("Couldn't read function from toml");
+        assert_eq!(function.get_retries(), Some(3));
+    }
+
+    #[test]
+    fn deserialize_priority() {
+        let function_str = "
+        function = 'test_function'
+        source = 'test.rs'
+        priority = 'high'
+        [[output]]
+        ";
+
+        let function: FunctionDefinition =
+            toml_from_str(function_str).expect("Couldn't read function from toml");
+        assert_eq!(function.get_priority(), Priority::High);
+    }
+
+    #[test]
+    fn deserialize_invalid_priority() {
+        let function_str = "
+        function = 'test_function'
+        source = 'test.rs'
+        priority = 'urgent'
+        [[output]]
+        ";
+
+        let function: Result<FunctionDefinition> = toml_from_str(function_str);
+        assert!(function.is_err());
+    }
+
+    #[test]
+    fn deserialize_pure() {
+        let function_str = "
+        function = 'test_function'
+        source = 'test.rs'
+        pure = true
+        [[output]]
+        ";
+
+        let function: FunctionDefinition =
+            toml_from_str(function_str).expect("Couldn't read function from toml");
+        assert!(function.is_pure());
+    }
+
+    #[test]
+    fn pure_and_impure_not_allowed() {
+        let function_str = "
+        function = 'pure_and_impure'
+        source = 'pure_and_impure.rs'
+        impure = true
+        pure = true
+        [[output]]
+        ";
+
+        let function: FunctionDefinition = toml_from_str(function_str)
+            .expect("Couldn't load function from toml");
+        let context_url = Url::parse("context://stdio/stdout").expect("Could not parse Url");
+        assert!(function.check_impurity(&context_url).is_err());
+    }
+
     #[test]
     fn deserialize_extra_field_fails() {
         let function_str = "
@@ -1,5 +1,6 @@
 #[cfg(feature = "debugger")]
 use std::fmt;
+use std::sync::Arc;
 
 use log::debug;
 use serde_derive::{Deserialize, Serialize};
@@ -60,10 +61,15 @@ pub struct Input {
     // An optional `InputInitializer` propagated from a flow input's initializer
     flow_initializer: Option<InputInitializer>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // An optional JSON Schema that values received should conform to
+    schema: Option<Value>,
+
     // The queue of values received so far as an ordered vector of entries,
-    // with first will be at the head and last at the tail
+    // with first will be at the head and last at the tail. Values are shared with other inputs
+    // the same value was sent to, instead of each having a copy
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    received: Vec<Value>,
+    received: Vec<Arc<Value>>,
 }
 
 #[allow(clippy::trivially_copy_pass_by_ref)] // As this is imposed on us by serde
@@ -82,14 +88,16 @@ impl TryFrom<&IO> for Input {
     fn try_from(io: &IO) -> Result<Self, Self::Error> {
         let data_type = io.datatypes().first().ok_or("Could not get datatype")?;
 
-        Ok(Input::new(
+        let mut input = Input::new(
             #[cfg(feature = "debugger")]
                 io.name(),
             data_type.type_array_order(),
             data_type.is_generic(),
             io.get_initializer().clone(),
             io.get_flow_initializer().clone(),
-        ))
+        );
+        input.schema.clone_from(io.get_schema());
+        Ok(input)
     }
 }
 
@@ -125,6 +133,7 @@ impl Input {
             generic,
             initializer,
             flow_initializer,
+            schema: None,
             received: Vec::new(),
         }
     }
@@ -143,6 +152,7 @@ impl Input {
             generic,
             initializer,
             flow_initializer,
+            schema: None,
             received: Vec::new(),
         }
     }
@@ -172,6 +182,12 @@ impl Input {
         &self.flow_initializer
     }
 
+    /// Return a reference to the JSON Schema that values received should conform to, if any
+    #[must_use]
+    pub fn schema(&self) -> &Option<Value> {
+        &self.schema
+    }
+
     /// Initialize an input with the `InputInitializer` if it has one, either on the function
     /// directly or via a connection from a flow input
     /// When called at start-up    it will initialize      if it's a `Once` or `Always` initializer
@@ -216,14 +232,17 @@ impl Input {
         self.array_order
     }
 
-    /// Send a Value or array of Values to this input
-    pub(crate) fn send(&mut self, value: Value) -> bool {
+    /// Send a Value or array of Values to this input. A value that is received as it is is shared
+    /// with anything else holding it, only the elements of arrays that are received one at a time,
+    /// or values wrapped in arrays, are copied
+    pub(crate) fn send(&mut self, value: impl Into<Arc<Value>>) -> bool {
+        let value = value.into();
         if self.generic {
             self.received.push(value);
         } else {
             match (
                 DataType::value_array_order(&value) - self.array_order(),
-                &value,
+                value.as_ref(),
             ) {
                 (0, _) => self.received.push(value),
                 (1, Value::Array(array)) => self.send_array_elements(array.clone()),
@@ -237,16 +256,16 @@ impl Input {
                 (-1, _) => {
                     debug!(
                         "\t\tSending value '{value}' wrapped in an Array: '{}'",
-                        json!([value])
+                        json!([*value])
                     );
-                    self.received.push(json!([value]));
+                    self.received.push(Arc::new(json!([*value])));
                 }
                 (-2, _) => {
                     debug!(
                         "\t\tSending value '{value}' wrapped in an Array of Array: '{}'",
-                        json!([[value]])
+                        json!([[*value]])
                     );
-                    self.received.push(json!([[value]]));
+                    self.received.push(Arc::new(json!([[*value]])));
                 }
                 _ => return false,
             }
@@ -254,18 +273,25 @@ impl Input {
         true // a value was sent!
     }
 
+    /// Return true if an array of values such as `element` sent to this `Input` would be received
+    /// as a series of its elements, so that the elements can be sent to it one at a time
+    #[must_use]
+    pub fn accepts_elements(&self, element: &Value) -> bool {
+        !self.generic && matches!(DataType::value_array_order(element) - self.array_order(), 0 | 1)
+    }
+
     // Send an array of values to this `Input`, by sending them one element at a time
     fn send_array_elements(&mut self, array: Vec<Value>) {
         debug!("\t\tSending Array as a series of Values");
         for value in array {
             debug!("\t\t\tSending array element as Value; '{value}'");
-            self.received.push(value);
+            self.received.push(Arc::new(value));
         }
     }
 
     /// Take the first element from the Input and return it. Could panic!
     #[must_use]
-    pub fn take(&mut self) -> Option<Value> {
+    pub fn take(&mut self) -> Option<Arc<Value>> {
         if self.received.is_empty() {
             return None;
         }
@@ -273,12 +299,24 @@ impl Input {
         Some(self.received.remove(0))
     }
 
+    /// Replace the values queued up in this input with `values`, as they are, for debugging
+    #[cfg(feature = "debugger")]
+    pub fn set_values(&mut self, values: Vec<Value>) {
+        self.received = values.into_iter().map(Arc::new).collect();
+    }
+
     /// Return the total number of values queued up in this input
     #[must_use]
     pub fn values_available(&self) -> usize {
         self.received.len()
     }
 
+    /// Return the total size, in bytes when serialized as JSON, of the values queued up in this input
+    #[must_use]
+    pub fn values_size(&self) -> usize {
+        self.received.iter().map(|value| value.to_string().len()).sum()
+    }
+
     /// Return true if there are no more values available from this input
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -288,6 +326,8 @@ impl Input {
 
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
+
     use serde_json::json;
     use serde_json::Value;
     use crate::model::input::InputInitializer::{Always, Once};
@@ -320,6 +360,16 @@ mod test {
         assert!(input.take().is_none());
     }
 
+    #[cfg(feature = "debugger")]
+    #[test]
+    fn set_values_replaces_queued_values() {
+        let mut input = Input::new("", 0, false, None, None);
+        input.send(json!(1));
+        input.set_values(vec![json!(2), json!(3)]);
+        assert_eq!(input.values_available(), 2);
+        assert_eq!(input.take(), Some(Arc::new(json!(2))));
+    }
+
     #[test]
     fn accepts_null() {
         let mut input = Input::new(
@@ -334,6 +384,22 @@ mod test {
         assert!(!input.is_empty());
     }
 
+    #[test]
+    fn values_size_is_serialized_size() {
+        let mut input = Input::new(
+            #[cfg(feature = "debugger")]
+                "",
+            0,
+            false,
+            None,
+            None,
+        );
+        assert_eq!(input.values_size(), 0);
+        input.send(json!("abc"));
+        input.send(json!(10));
+        assert_eq!(input.values_size(), 7);
+    }
+
     #[test]
     fn accepts_array() {
         let mut input = Input::new(
@@ -348,6 +414,46 @@ mod test {
         assert!(!input.is_empty());
     }
 
+    #[test]
+    fn accepts_elements() {
+        let input = Input::new(
+            #[cfg(feature = "debugger")]
+                "",
+            0,
+            false,
+            None,
+            None,
+        );
+        assert!(input.accepts_elements(&json!(5)));
+        assert!(input.accepts_elements(&json!([5, 10])));
+    }
+
+    #[test]
+    fn array_input_does_not_accept_elements() {
+        let input = Input::new(
+            #[cfg(feature = "debugger")]
+                "",
+            1,
+            false,
+            None,
+            None,
+        );
+        assert!(!input.accepts_elements(&json!(5)));
+    }
+
+    #[test]
+    fn generic_input_does_not_accept_elements() {
+        let input = Input::new(
+            #[cfg(feature = "debugger")]
+                "",
+            0,
+            true,
+            None,
+            None,
+        );
+        assert!(!input.accepts_elements(&json!(5)));
+    }
+
     #[test]
     fn take_empties() {
         let mut input = Input::new(
@@ -383,6 +489,22 @@ mod test {
         assert!(input.is_empty());
     }
 
+    #[test]
+    fn value_received_is_shared() {
+        let mut input = Input::new(
+            #[cfg(feature = "debugger")]
+                "",
+            1,
+            false,
+            None,
+            None,
+        );
+        let value = Arc::new(json!([1, 2, 3]));
+        input.send(value.clone());
+        let received = input.take().expect("Should have got a value from the input");
+        assert!(Arc::ptr_eq(&value, &received));
+    }
+
     #[test]
     fn init_first_time_once() {
         let mut input = Input::new(
@@ -396,7 +518,7 @@ mod test {
 
         input.init(true, false);
 
-        assert_eq!(input.take(), Some(json!(1)));
+        assert_eq!(input.take().as_deref(), Some(&json!(1)));
         assert!(input.is_empty());
     }
 
@@ -413,7 +535,7 @@ mod test {
 
         input.init(true, false);
 
-        assert_eq!(input.take(), Some(json!(1)));
+        assert_eq!(input.take().as_deref(), Some(&json!(1)));
         assert!(input.is_empty());
     }
 
@@ -430,7 +552,7 @@ mod test {
 
         input.init(true, false);
 
-        assert_eq!(input.take(), Some(json!(1)));
+        assert_eq!(input.take().as_deref(), Some(&json!(1)));
         assert!(input.is_empty());
 
         input.init(false, false);
@@ -451,12 +573,12 @@ mod test {
 
         input.init(true, false);
 
-        assert_eq!(input.take(), Some(json!(1)));
+        assert_eq!(input.take().as_deref(), Some(&json!(1)));
         assert!(input.is_empty());
 
         input.init(false, false);
 
-        assert_eq!(input.take(), Some(json!(1)));
+        assert_eq!(input.take().as_deref(), Some(&json!(1)));
         assert!(input.is_empty());
     }
 }
@@ -4,6 +4,7 @@ use std::fmt;
 use error_chain::bail;
 //use log::warn;
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::errors::{Result, ResultExt};
 use crate::model::datatype::{DataType, GENERIC_TYPE};
@@ -54,6 +55,10 @@ pub struct IO {
     #[serde(skip_deserializing)]
     flow_initializer: Option<InputInitializer>,
 
+    /// If a function's input, an optional JSON Schema that values sent to it should conform to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema: Option<Value>,
+
     /// [Route] where in the full flow hierarchy this IO is located, including it's [Name]
     /// as the last segment
     #[serde(skip_deserializing)]
@@ -143,6 +148,17 @@ impl IO {
         &self.initializer
     }
 
+    /// Get a reference to the JSON Schema that values of this IO should conform to, if any
+    #[must_use]
+    pub fn get_schema(&self) -> &Option<Value> {
+        &self.schema
+    }
+
+    /// Set the JSON Schema that values of this IO should conform to
+    pub fn set_schema(&mut self, schema: Option<Value>) {
+        self.schema = schema;
+    }
+
     /// Get a reference to the flow propagated input initializer of this IO
     #[must_use]
     pub fn get_flow_initializer(&self) -> &Option<InputInitializer> {
@@ -448,6 +464,21 @@ mod test {
         assert!(input.validate().is_ok(), "IO does not validate()");
     }
 
+    #[test]
+    fn deserialize_schema() {
+        let input_str = "
+        name = 'input'
+        type = 'number'
+        schema = { minimum = 0, maximum = 100 }
+        ";
+
+        let input: IO = match toml_from_str(input_str) {
+            Ok(x) => x,
+            Err(e) => panic!("TOML does not parse: {e}"),
+        };
+        assert_eq!(input.get_schema(), &Some(serde_json::json!({"minimum": 0, "maximum": 100})));
+    }
+
     #[test]
     fn deserialize_extra_field_fails() {
         let input_str = "
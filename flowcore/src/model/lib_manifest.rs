@@ -8,9 +8,9 @@ use log::{debug, info};
 use serde_derive::{Deserialize, Serialize};
 use url::Url;
 
-use crate::deserializers::deserializer::get;
 use crate::errors::{Result, ResultExt};
 use crate::Implementation;
+use crate::model::manifest_format::{MANIFEST_EXTENSIONS, ManifestFormat};
 use crate::model::metadata::MetaData;
 use crate::provider::Provider;
 
@@ -64,6 +64,11 @@ pub struct LibraryManifest {
     /// Value: Url where the source file it was derived from is located
     #[serde(default)]
     pub source_urls: BTreeMap<String, Url>,
+    /// `implementation_hashes` is a map of:
+    /// Key: path of a WASM implementation file, relative to the lib root, as used in locators
+    /// Value: SHA-256 hash (in hex) of the file when the library was compiled
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub implementation_hashes: BTreeMap<String, String>,
 }
 
 impl LibraryManifest {
@@ -75,6 +80,7 @@ impl LibraryManifest {
             metadata,
             locators: BTreeMap::<Url, ImplementationLocator>::new(),
             source_urls: BTreeMap::<String, Url>::new(),
+            implementation_hashes: BTreeMap::<String, String>::new(),
         }
     }
 
@@ -85,14 +91,13 @@ impl LibraryManifest {
     /// Returns an error if:
     /// - The provided `lib_manifest_url` `Url` cannot be resolved
     /// - The `provider` cannot fetch the contents from the resolved url
-    /// - The fetched contents cannot be converted to a valid Utf8 String
-    /// - The Fetched Utf8 String contents of the `Url` are not a valid `LibraryManifest`
+    /// - The fetched contents of the `Url` are not a valid `LibraryManifest` in any `ManifestFormat`
     pub fn load(provider: &Arc<dyn Provider>, lib_manifest_url: &Url) -> Result<(LibraryManifest, Url)> {
         let (resolved_url, _) = provider
             .resolve_url(
                 lib_manifest_url,
                 DEFAULT_LIB_JSON_MANIFEST_FILENAME,
-                &["json"],
+                MANIFEST_EXTENSIONS,
             )
             .chain_err(|| {
                 format!(
@@ -107,11 +112,7 @@ impl LibraryManifest {
         })?;
 
         let url = resolved_url.clone();
-        let content = String::from_utf8(manifest_content)
-            .chain_err(|| "Could not convert from utf8 to String")?;
-        let deserializer = get::<LibraryManifest>(&resolved_url)?;
-        let manifest = deserializer
-            .deserialize(&content, Some(&resolved_url))
+        let manifest = ManifestFormat::deserialize(&manifest_content)
             .chain_err(|| format!("Could not create a LibraryManifest from '{resolved_url}'"))?;
 
         Ok((manifest, url))
@@ -160,16 +161,39 @@ impl LibraryManifest {
         Ok(())
     }
 
+    /// Record the SHA-256 hash of the compiled WASM implementation file at
+    /// `implementation_path_relative` (relative to the lib root), so that it can be verified when
+    /// the implementation is loaded
+    pub fn add_implementation_hash(&mut self, implementation_path_relative: &str, hash: String) {
+        self.implementation_hashes.insert(implementation_path_relative.to_owned(), hash);
+    }
+
     /// Given an output directory, return a `PathBuf` to the json format manifest that should be
     /// generated inside it
     #[must_use]
     pub fn manifest_filename(base_dir: &Path) -> PathBuf {
+        Self::manifest_filename_with_format(base_dir, ManifestFormat::Json)
+    }
+
+    /// Given an output directory, return a `PathBuf` to the manifest in `format` that should be
+    /// generated inside it
+    #[must_use]
+    pub fn manifest_filename_with_format(base_dir: &Path, format: ManifestFormat) -> PathBuf {
         let mut filename = base_dir.to_path_buf();
         filename.push(DEFAULT_LIB_JSON_MANIFEST_FILENAME);
-        filename.set_extension("json");
+        filename.set_extension(format.extension());
         filename
     }
 
+    /// Return a `PathBuf` to the manifest that exists in `base_dir`, in whichever `ManifestFormat`
+    /// it was generated, or `None` if there is none
+    #[must_use]
+    pub fn find_manifest_filename(base_dir: &Path) -> Option<PathBuf> {
+        [ManifestFormat::Json, ManifestFormat::Cbor].into_iter()
+            .map(|format| Self::manifest_filename_with_format(base_dir, format))
+            .find(|filename| filename.is_file())
+    }
+
     /// Generate a manifest for the library in JSON format
     ///
     /// # Errors
@@ -179,15 +203,24 @@ impl LibraryManifest {
     /// - Contents of manifest cannot be written to file at `json_manifest_filename`
     ///
     pub fn write_json(&self, json_manifest_filename: &Path) -> Result<()> {
-        let mut manifest_file = File::create(json_manifest_filename)?;
+        self.write(json_manifest_filename, ManifestFormat::Json)
+    }
 
-        manifest_file.write_all(
-            serde_json::to_string_pretty(self)
-                .chain_err(|| "Could not pretty format the library manifest JSON contents")?
-                .as_bytes(),
-        )?;
+    /// Generate a manifest for the library in `format`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `File` cannot be created at `Path` `manifest_filename`
+    /// - Contents of manifest cannot be serialized or written to file at `manifest_filename`
+    ///
+    pub fn write(&self, manifest_filename: &Path, format: ManifestFormat) -> Result<()> {
+        let mut manifest_file = File::create(manifest_filename)?;
 
-        info!("Generated library JSON manifest at '{}'", json_manifest_filename.display());
+        manifest_file.write_all(&format.serialize(self)
+            .chain_err(|| "Could not serialize the library manifest contents")?)?;
+
+        info!("Generated library {format} manifest at '{}'", manifest_filename.display());
 
         Ok(())
     }
@@ -203,6 +236,10 @@ impl PartialEq for LibraryManifest {
             return false;
         }
 
+        if self.implementation_hashes != other.implementation_hashes {
+            return false;
+        }
+
         for locator in &self.locators {
             // try and find locator with the same key in the other HashMap
             if let Some(other_impl_locator) = other.locators.get(locator.0) {
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+
+use log::info;
+use serde_derive::{Deserialize, Serialize};
+use url::Url;
+
+use crate::errors::{Result, ResultExt};
+
+/// The name of the lock file written next to a flow's manifest
+pub const LOCK_FILENAME: &str = "flow.lock";
+/// The name of the query parameter used to pass a library version requirement in a "lib:" Url
+pub const VERSION_QUERY: &str = "version";
+
+/// Add the `version` of a library resolved for the library reference `url` to it, as the exact
+/// version requirement "=<version>", so that the same version is used wherever it is resolved,
+/// such as when the flow compiled with it is run
+pub fn pin_version(url: &mut Url, version: &str) {
+    url.query_pairs_mut().append_pair(VERSION_QUERY, &format!("={version}"));
+}
+
+/// Return the exact version of a library that the library reference `url` is pinned to, if any
+#[must_use]
+pub fn pinned_version(url: &Url) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == VERSION_QUERY)
+        .and_then(|(_, requirement)| requirement.strip_prefix('=').map(str::to_string))
+}
+
+/// A `LockedLibrary` records the installed version of a library that was resolved for a
+/// version requirement in a library reference, such as `lib://flowstdlib@^1.2/math/add`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LockedLibrary {
+    /// The name of the library
+    pub name: String,
+    /// The version requirement used in the library references, e.g. "^1.2"
+    pub requirement: String,
+    /// The version of the library that was resolved for the requirement
+    pub version: String,
+    /// The SHA-256 hash (in hex) of the resolved library's manifest
+    pub hash: String,
+}
+
+/// A `LockFile` records the library versions resolved when compiling a flow, so that subsequent
+/// compiles of it use the same versions, even if newer ones matching the requirements get installed
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LockFile {
+    /// The libraries resolved
+    #[serde(default, rename = "library")]
+    pub libraries: Vec<LockedLibrary>,
+}
+
+impl LockFile {
+    /// Read the `LockFile` at `path`, or return an empty one if none exists
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(LockFile::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("Could not read lock file '{}'", path.display()))?;
+        toml::from_str(&contents)
+            .chain_err(|| format!("Could not parse lock file '{}'", path.display()))
+    }
+
+    /// Write the `LockFile` to `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string(self)
+            .chain_err(|| "Could not serialize lock file")?;
+        fs::write(path, contents)
+            .chain_err(|| format!("Could not write lock file '{}'", path.display()))?;
+        info!("Generated lock file at '{}'", path.display());
+        Ok(())
+    }
+
+    /// Return true if no library versions have been locked
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.libraries.is_empty()
+    }
+
+    /// Find the version locked for library `name` with version `requirement`
+    #[must_use]
+    pub fn locked(&self, name: &str, requirement: &str) -> Option<&LockedLibrary> {
+        self.libraries.iter()
+            .find(|library| library.name == name && library.requirement == requirement)
+    }
+
+    /// Record the version resolved for a library, replacing any previous one for the same
+    /// library name and requirement
+    pub fn lock(&mut self, locked: LockedLibrary) {
+        self.libraries.retain(|library|
+            !(library.name == locked.name && library.requirement == locked.requirement));
+        self.libraries.push(locked);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use url::Url;
+
+    use super::{LockedLibrary, LockFile, pin_version, pinned_version};
+
+    fn locked(version: &str) -> LockedLibrary {
+        LockedLibrary {
+            name: "flowstdlib".into(),
+            requirement: "^1.2".into(),
+            version: version.into(),
+            hash: "00".into(),
+        }
+    }
+
+    #[test]
+    fn lock_replaces() {
+        let mut lock_file = LockFile::default();
+        lock_file.lock(locked("1.2.0"));
+        lock_file.lock(locked("1.3.0"));
+        assert_eq!(lock_file.libraries.len(), 1);
+        assert_eq!(lock_file.locked("flowstdlib", "^1.2").map(|l| l.version.as_str()),
+                   Some("1.3.0"));
+        assert!(lock_file.locked("flowstdlib", "^2").is_none());
+    }
+
+    #[test]
+    fn pin_and_read_version() {
+        let mut url = Url::parse("lib://flowstdlib/math/add").expect("Could not parse Url");
+        assert_eq!(pinned_version(&url), None);
+        pin_version(&mut url, "1.3.1");
+        assert_eq!(url.as_str(), "lib://flowstdlib/math/add?version=%3D1.3.1");
+        assert_eq!(pinned_version(&url), Some("1.3.1".into()));
+
+        let required = Url::parse("lib://flowstdlib/math/add?version=%5E1.2")
+            .expect("Could not parse Url");
+        assert_eq!(pinned_version(&required), None);
+    }
+
+    #[test]
+    fn write_and_read() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let path = dir.path().join(super::LOCK_FILENAME);
+        assert!(LockFile::read(&path).expect("Could not read lock file").is_empty());
+
+        let mut lock_file = LockFile::default();
+        lock_file.lock(locked("1.2.0"));
+        lock_file.write(&path).expect("Could not write lock file");
+        assert_eq!(LockFile::read(&path).expect("Could not read lock file"), lock_file);
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::errors::{bail, Error, Result, ResultExt};
+
+/// The file extensions of flow and library manifests, in the order they are looked for when
+/// the extension of a manifest is not specified
+pub const MANIFEST_EXTENSIONS: &[&str] = &["json", "cbor"];
+
+/// The formats that flow and library manifests can be serialized in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// Pretty printed JSON, readable by humans
+    #[default]
+    Json,
+    /// Compact binary CBOR, that is faster to parse on constrained targets
+    Cbor,
+}
+
+impl ManifestFormat {
+    /// The file extension used for a manifest in this format
+    #[must_use]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ManifestFormat::Json => "json",
+            ManifestFormat::Cbor => "cbor",
+        }
+    }
+
+    /// Detect the format of serialized manifest `contents`. A JSON manifest is an object, so
+    /// starts with '{' (possibly after whitespace), which is not a valid start of a CBOR map.
+    #[must_use]
+    pub fn detect(contents: &[u8]) -> Self {
+        match contents.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{') => ManifestFormat::Json,
+            _ => ManifestFormat::Cbor,
+        }
+    }
+
+    /// Serialize `manifest` in this format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `manifest` cannot be serialized
+    pub fn serialize<T: Serialize>(&self, manifest: &T) -> Result<Vec<u8>> {
+        match self {
+            ManifestFormat::Json => Ok(serde_json::to_string_pretty(manifest)
+                .chain_err(|| "Could not pretty format the manifest JSON contents")?
+                .into_bytes()),
+            ManifestFormat::Cbor => {
+                let mut contents = vec![];
+                ciborium::into_writer(manifest, &mut contents)
+                    .map_err(|e| format!("Could not serialize the manifest as CBOR: {e}"))?;
+                Ok(contents)
+            }
+        }
+    }
+
+    /// Deserialize a manifest from `contents`, detecting which format it is in
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `contents` are not a valid manifest in the format detected
+    pub fn deserialize<T: DeserializeOwned>(contents: &[u8]) -> Result<T> {
+        match Self::detect(contents) {
+            ManifestFormat::Json => Ok(serde_json::from_slice(contents)
+                .chain_err(|| "Could not deserialize the JSON manifest contents")?),
+            ManifestFormat::Cbor => Ok(ciborium::from_reader(contents)
+                .map_err(|e| format!("Could not deserialize the CBOR manifest contents: {e}"))?),
+        }
+    }
+}
+
+impl FromStr for ManifestFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "json" => Ok(ManifestFormat::Json),
+            "cbor" => Ok(ManifestFormat::Cbor),
+            _ => bail!("Unknown manifest format '{format}', expected one of: {}",
+                MANIFEST_EXTENSIONS.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for ManifestFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::ManifestFormat;
+
+    #[test]
+    fn parse_format() {
+        assert_eq!("json".parse::<ManifestFormat>().expect("Could not parse format"),
+                   ManifestFormat::Json);
+        assert_eq!("cbor".parse::<ManifestFormat>().expect("Could not parse format"),
+                   ManifestFormat::Cbor);
+        assert!("xml".parse::<ManifestFormat>().is_err());
+    }
+
+    #[test]
+    fn round_trip_both_formats() {
+        let manifest = json!({"metadata": {"name": "test"}, "functions": [1, 2.5, "three"]});
+        for format in [ManifestFormat::Json, ManifestFormat::Cbor] {
+            let contents = format.serialize(&manifest).expect("Could not serialize");
+            assert_eq!(ManifestFormat::detect(&contents), format);
+            let loaded: Value = ManifestFormat::deserialize(&contents).expect("Could not deserialize");
+            assert_eq!(loaded, manifest);
+        }
+    }
+
+    #[test]
+    fn cbor_is_smaller() {
+        let manifest = json!({"functions": [{"name": "add", "inputs": [{"initializer": null}]}]});
+        let json = ManifestFormat::Json.serialize(&manifest).expect("Could not serialize");
+        let cbor = ManifestFormat::Cbor.serialize(&manifest).expect("Could not serialize");
+        assert!(cbor.len() < json.len());
+    }
+}
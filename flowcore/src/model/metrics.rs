@@ -16,6 +16,56 @@ pub struct Metrics {
     start_time: Instant,
     elapsed_time_seconds: u64,
     max_simultaneous_jobs: usize,
+    #[serde(default)]
+    jobs_out_of_fuel: usize,
+    #[serde(default)]
+    executors_lost: usize,
+    #[serde(default)]
+    function_executions: Vec<usize>,
+    #[serde(default)]
+    executors: Vec<ExecutorMetrics>,
+}
+
+/// `ExecutorMetrics` are statistics on the jobs executed by an executor, reported by it
+/// periodically to the coordinator it gets jobs from, showing how work is distributed
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ExecutorMetrics {
+    /// An id that identifies the executor reporting them
+    pub executor_id: String,
+    /// The number of threads the executor uses to execute jobs
+    pub threads: usize,
+    /// The number of jobs executed since the executor connected to the coordinator
+    pub jobs_executed: usize,
+    /// The average time taken to execute a job, in milliseconds
+    pub average_job_ms: f64,
+    /// The number of jobs whose implementation was already loaded, so that it did not have to
+    /// be loaded (and compiled, if it is WASM) again
+    pub implementation_cache_hits: usize,
+    /// The proportion of the time since the last report that the threads were executing jobs,
+    /// from 0.0 (idle) to 1.0 (all busy)
+    pub load: f64,
+}
+
+impl fmt::Display for ExecutorMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({} threads): {} jobs, {:.1}ms average, {} cache hits, {:.0}% load",
+               self.executor_id, self.threads, self.jobs_executed, self.average_job_ms,
+               self.implementation_cache_hits, self.load * 100.0)
+    }
+}
+
+/// `MetricsSnapshot` is a snapshot of some of the `Metrics` of a flow, taken periodically
+/// while it is being executed
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct MetricsSnapshot {
+    /// Time in milliseconds since execution of the flow started
+    pub elapsed_ms: u64,
+    /// The number of jobs created so far
+    pub jobs_created: usize,
+    /// The number of jobs dispatched for execution that have not completed yet
+    pub jobs_outstanding: usize,
+    /// The number of jobs executed by each function so far, indexed by function id
+    pub function_executions: Vec<usize>,
 }
 
 impl Metrics {
@@ -29,6 +79,10 @@ impl Metrics {
             start_time: Instant::now(),
             elapsed_time_seconds: 0,
             max_simultaneous_jobs: 0,
+            jobs_out_of_fuel: 0,
+            executors_lost: 0,
+            function_executions: vec![0; num_functions],
+            executors: vec![],
         }
     }
 
@@ -39,6 +93,10 @@ impl Metrics {
         self.outputs_sent = 0;
         self.start_time = Instant::now();
         self.max_simultaneous_jobs = 0;
+        self.jobs_out_of_fuel = 0;
+        self.executors_lost = 0;
+        self.function_executions = vec![0; self.num_functions];
+        self.executors.clear();
     }
 
     /// Set the number of jobs created in `Metrics` to the `jobs` value
@@ -51,6 +109,28 @@ impl Metrics {
         self.outputs_sent += 1;
     }
 
+    /// Increment the tracker for the number of jobs that failed by running out of fuel
+    pub fn increment_jobs_out_of_fuel(&mut self) {
+        self.jobs_out_of_fuel += 1;
+    }
+
+    /// Increment the tracker for the number of executors lost, whose jobs were executed again
+    pub fn increment_executors_lost(&mut self) {
+        self.executors_lost += 1;
+    }
+
+    /// Increment the tracker for the number of jobs executed by the function `function_id`
+    pub fn increment_function_executions(&mut self, function_id: usize) {
+        if let Some(executions) = self.function_executions.get_mut(function_id) {
+            *executions += 1;
+        }
+    }
+
+    /// Set the latest `ExecutorMetrics` reported by the executors that executed the flow's jobs
+    pub fn set_executor_metrics(&mut self, executors: Vec<ExecutorMetrics>) {
+        self.executors = executors;
+    }
+
     /// Keep track of the maximum jobs that are executing in parallel during a flows
     /// execution, as a measure of the maximum level of parallelism achieved
     pub fn track_max_jobs(&mut self, jobs_running: usize) {
@@ -68,6 +148,18 @@ impl Metrics {
     pub fn stop_timer(&mut self) {
         self.elapsed_time_seconds = self.start_time.elapsed().as_secs();
     }
+
+    /// Take a [`MetricsSnapshot`] of the metrics so far, given the number of jobs created and the
+    /// number of jobs outstanding now
+    #[must_use]
+    pub fn snapshot(&self, jobs_created: usize, jobs_outstanding: usize) -> MetricsSnapshot {
+        MetricsSnapshot {
+            elapsed_ms: u64::try_from(self.start_time.elapsed().as_millis()).unwrap_or(u64::MAX),
+            jobs_created,
+            jobs_outstanding,
+            function_executions: self.function_executions.clone(),
+        }
+    }
 }
 
 impl fmt::Display for Metrics {
@@ -76,13 +168,19 @@ impl fmt::Display for Metrics {
         writeln!(f, "Number of Jobs Created: {}", self.jobs_created)?;
         writeln!(f, "Values sent: {}", self.outputs_sent)?;
         writeln!(f, "Elapsed time(s): {:.*}", 1, self.elapsed_time_seconds)?;
-        write!(f, "Max Jobs in Parallel: {}", self.max_simultaneous_jobs)
+        writeln!(f, "Max Jobs in Parallel: {}", self.max_simultaneous_jobs)?;
+        writeln!(f, "Jobs Out of Fuel: {}", self.jobs_out_of_fuel)?;
+        write!(f, "Executors Lost: {}", self.executors_lost)?;
+        for executor in &self.executors {
+            write!(f, "\nExecutor {executor}")?;
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Metrics;
+    use super::{ExecutorMetrics, Metrics};
 
     #[test]
     fn test_metrics_reset() {
@@ -90,11 +188,17 @@ mod test {
         metrics.jobs_created = 110;
         metrics.outputs_sent = 10;
         metrics.max_simultaneous_jobs = 4;
+        metrics.jobs_out_of_fuel = 2;
+        metrics.increment_executors_lost();
+        metrics.increment_function_executions(3);
         metrics.reset();
         assert_eq!(metrics.outputs_sent, 0);
         assert_eq!(metrics.jobs_created, 0);
         assert_eq!(metrics.num_functions, 10);
         assert_eq!(metrics.max_simultaneous_jobs, 0);
+        assert_eq!(metrics.jobs_out_of_fuel, 0);
+        assert_eq!(metrics.executors_lost, 0);
+        assert_eq!(metrics.function_executions, vec![0; 10]);
     }
 
     #[test]
@@ -109,9 +213,42 @@ mod test {
         assert_eq!(metrics.max_simultaneous_jobs, 4);
     }
 
+    #[test]
+    fn test_snapshot() {
+        let mut metrics = Metrics::new(3);
+        metrics.increment_function_executions(1);
+        metrics.increment_function_executions(1);
+        metrics.increment_function_executions(2);
+        // out of range function ids are ignored
+        metrics.increment_function_executions(3);
+
+        let snapshot = metrics.snapshot(5, 2);
+        assert_eq!(snapshot.jobs_created, 5);
+        assert_eq!(snapshot.jobs_outstanding, 2);
+        assert_eq!(snapshot.function_executions, vec![0, 2, 1]);
+    }
+
     #[test]
     fn test_metrics_display() {
         let metrics = Metrics::new(10);
         println!("{metrics}");
     }
+
+    #[test]
+    fn test_executor_metrics_display() {
+        let mut metrics = Metrics::new(10);
+        metrics.set_executor_metrics(vec![ExecutorMetrics {
+            executor_id: "1234-abcd".into(),
+            threads: 4,
+            jobs_executed: 20,
+            average_job_ms: 1.5,
+            implementation_cache_hits: 18,
+            load: 0.5,
+        }]);
+        assert!(metrics.to_string().ends_with(
+            "Executor 1234-abcd (4 threads): 20 jobs, 1.5ms average, 18 cache hits, 50% load"));
+
+        metrics.reset();
+        assert!(metrics.executors.is_empty());
+    }
 }
@@ -10,6 +10,8 @@ pub mod validation;
 pub mod io;
 /// `datatype` specifies the type of data permitted on a input, output or connection
 pub mod datatype;
+/// `bytes` defines the values of the `bytes` datatype, for raw binary data
+pub mod bytes;
 /// `connection` defines the connection between one process output to another process's input
 pub mod connection;
 /// `flow` is the definition of an entire flow, including children flows
@@ -18,6 +20,8 @@ pub mod flow_definition;
 pub mod function_definition;
 /// `name` is used to name various objects in the flow model
 pub mod name;
+/// `parameter` defines the parameters a flow can declare, whose values are supplied by references to it
+pub mod parameter;
 /// `process` is a generic definition of a `function` or a `flow` so a flow referring to it or using
 /// it does not need to know or define how it is implemented
 pub mod process;
@@ -30,13 +34,22 @@ pub mod route;
 pub mod input;
 /// `output_connection` defines a struct for a function's output connection
 pub mod output_connection;
+/// `source_location` defines where in a flow definition file a process or connection was defined
+pub mod source_location;
 /// `metadata` defined structs for flow meta data
 pub mod metadata;
 /// `submission`defines a struct for submitting flows for execution
 pub mod submission;
 /// `metrics` defines a struct for runtime execution metrics
 pub mod metrics;
+/// `manifest_format` defines the formats flow and library manifests can be serialized in
+pub mod manifest_format;
 /// `flow_manifest` is the struct that specifies the manifest of functions in a flow
 pub mod flow_manifest;
+/// `schema` validates values against the JSON Schemas that inputs may declare
+#[cfg(all(not(target_arch = "wasm32"), feature = "schema"))]
+pub mod schema;
+/// `lock_file` records the library versions resolved for version requirements in library references
+pub mod lock_file;
 /// `lib_manifest` defines the structs for specifying a Library's manifest and methods to load it
 pub mod lib_manifest;
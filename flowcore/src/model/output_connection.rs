@@ -2,6 +2,9 @@ use std::fmt;
 
 use serde_derive::{Deserialize, Serialize};
 
+#[cfg(feature = "debugger")]
+use crate::model::source_location::SourceLocation;
+
 /// The `Conversion` enum defines what type of run-time conversion of types is to be done
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
 pub enum Conversion {
@@ -39,6 +42,10 @@ pub struct OutputConnection {
     #[cfg(feature = "debugger")]
     #[serde(default, skip_serializing_if = "String::is_empty")]
     name: String,
+    /// Where in a flow definition file the connection was defined
+    #[cfg(feature = "debugger")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_location: Option<SourceLocation>,
 }
 
 /// If the Source is an Output and the String for the subroute is empty then we can just
@@ -73,8 +80,29 @@ impl OutputConnection {
             destination,
             #[cfg(feature = "debugger")]
             name,
+            #[cfg(feature = "debugger")]
+            source_location: None,
         }
     }
+
+    /// Return where in a flow definition file this connection was defined, if known
+    #[cfg(feature = "debugger")]
+    #[must_use]
+    pub fn source_location(&self) -> Option<&SourceLocation> {
+        self.source_location.as_ref()
+    }
+
+    /// Set where in a flow definition file this connection was defined
+    #[cfg(feature = "debugger")]
+    pub fn set_source_location(&mut self, source_location: Option<SourceLocation>) {
+        self.source_location = source_location;
+    }
+
+    /// Return true if this connection forwards the entire output value of a function
+    #[must_use]
+    pub fn forwards_entire_output(&self) -> bool {
+        is_default_source(&self.source)
+    }
 }
 
 impl fmt::Display for Source {
@@ -98,6 +126,11 @@ impl fmt::Display for OutputConnection {
             write!(f, " @ '{}'", self.destination)?;
         }
 
+        #[cfg(feature = "debugger")]
+        if let Some(source_location) = &self.source_location {
+            write!(f, " (defined at {source_location})")?;
+        }
+
         write!(f, "")
     }
 }
@@ -117,4 +150,32 @@ mod test {
         );
         println!("Connection: {connection}");
     }
+
+    #[test]
+    fn forwards_entire_output() {
+        let connection = super::OutputConnection::new(
+            super::Source::default(),
+            1,
+            0,
+            0,
+            String::new(),
+            #[cfg(feature = "debugger")]
+            String::new(),
+        );
+        assert!(connection.forwards_entire_output());
+    }
+
+    #[test]
+    fn sub_route_does_not_forward_entire_output() {
+        let connection = super::OutputConnection::new(
+            super::Source::Output("/sum".into()),
+            1,
+            0,
+            0,
+            String::new(),
+            #[cfg(feature = "debugger")]
+            String::new(),
+        );
+        assert!(!connection.forwards_entire_output());
+    }
 }
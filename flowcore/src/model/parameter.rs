@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use error_chain::bail;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::Result;
+use crate::model::input::InputInitializer;
+use crate::model::name::Name;
+use crate::model::validation::Validate;
+
+/// The type of value a flow `Parameter` can take
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterType {
+    /// A JSON number
+    Number,
+    /// A string
+    String,
+    /// A route within the flow, such as a sub-route of an output or an input to connect to
+    Route,
+}
+
+/// A `Parameter` declared by a flow definition, whose value is supplied by the process references
+/// that use the flow. References to it of the form `${name}` in the flow's process references
+/// (sources, input initializers and parameters supplied to sub-processes) and connection routes
+/// are substituted with the supplied value when the flow is parsed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Parameter {
+    /// The `name` of the parameter, as used in references to it
+    pub name: Name,
+    /// The type of value the parameter takes
+    #[serde(rename = "type")]
+    pub parameter_type: ParameterType,
+    /// An optional default value, used when a process reference does not supply one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+}
+
+impl Validate for Parameter {
+    fn validate(&self) -> Result<()> {
+        self.name.validate()?;
+        if let Some(default) = &self.default {
+            self.check_type(default)?;
+        }
+        Ok(())
+    }
+}
+
+impl Parameter {
+    // Check that `value` is of the type declared for this parameter
+    fn check_type(&self, value: &Value) -> Result<()> {
+        let valid = match self.parameter_type {
+            ParameterType::Number => value.is_number(),
+            ParameterType::String | ParameterType::Route => value.is_string(),
+        };
+
+        if !valid {
+            bail!("Value '{value}' of parameter '{}' is not of the declared type '{:?}'",
+                self.name, self.parameter_type);
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolve the values of the `parameters` declared by a flow from those `supplied` by the process
+/// reference to it, using declared defaults where a value is not supplied
+///
+/// # Errors
+///
+/// Returns `Err` if a supplied parameter is not declared, if a value is not supplied for a
+/// parameter with no default or if a value is not of the parameter's declared type
+pub fn resolve(parameters: &[Parameter], supplied: &BTreeMap<String, Value>)
+    -> Result<BTreeMap<String, Value>> {
+    for name in supplied.keys() {
+        if !parameters.iter().any(|parameter| &parameter.name == name) {
+            bail!("A value was supplied for parameter '{}' which is not declared", name);
+        }
+    }
+
+    let mut values = BTreeMap::new();
+    for parameter in parameters {
+        let value = match supplied.get(&parameter.name).or(parameter.default.as_ref()) {
+            Some(value) => value.clone(),
+            None => bail!("No value was supplied for parameter '{}' which has no default",
+                parameter.name),
+        };
+        parameter.check_type(&value)?;
+        values.insert(parameter.name.clone(), value);
+    }
+
+    Ok(values)
+}
+
+/// Substitute the parameter `values` for all references to them in `text`
+#[must_use]
+pub fn substitute_str(text: &str, values: &BTreeMap<String, Value>) -> String {
+    let mut substituted = text.to_string();
+    for (name, value) in values {
+        let replacement = match value {
+            Value::String(string) => string.clone(),
+            _ => value.to_string(),
+        };
+        substituted = substituted.replace(&format!("${{{name}}}"), &replacement);
+    }
+    substituted
+}
+
+/// Substitute the parameter `values` for all references to them in `value`. A string that is
+/// just a reference to a parameter takes the parameter's value, including its type.
+#[must_use]
+pub fn substitute_value(value: &Value, values: &BTreeMap<String, Value>) -> Value {
+    match value {
+        Value::String(string) => {
+            let whole_reference = string.strip_prefix("${")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .and_then(|name| values.get(name));
+            match whole_reference {
+                Some(parameter_value) => parameter_value.clone(),
+                None => Value::String(substitute_str(string, values)),
+            }
+        }
+        Value::Array(array) => Value::Array(array.iter()
+            .map(|element| substitute_value(element, values)).collect()),
+        Value::Object(map) => Value::Object(map.iter()
+            .map(|(key, element)| (key.clone(), substitute_value(element, values))).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// Substitute the parameter `values` for all references to them in the value of `initializer`
+pub fn substitute_initializer(initializer: &InputInitializer, values: &BTreeMap<String, Value>)
+    -> InputInitializer {
+    match initializer {
+        InputInitializer::Always(value) => InputInitializer::Always(substitute_value(value, values)),
+        InputInitializer::Once(value) => InputInitializer::Once(substitute_value(value, values)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use crate::model::input::InputInitializer;
+
+    use super::{Parameter, ParameterType, resolve, substitute_initializer, substitute_str,
+                substitute_value};
+
+    fn size_parameter(default: Option<serde_json::Value>) -> Parameter {
+        Parameter {
+            name: "size".into(),
+            parameter_type: ParameterType::Number,
+            default,
+        }
+    }
+
+    #[test]
+    fn supplied_value_used() {
+        let supplied = BTreeMap::from([("size".to_string(), json!(5))]);
+        let values = resolve(&[size_parameter(Some(json!(3)))], &supplied)
+            .expect("Could not resolve parameters");
+        assert_eq!(values.get("size"), Some(&json!(5)));
+    }
+
+    #[test]
+    fn default_value_used() {
+        let values = resolve(&[size_parameter(Some(json!(3)))], &BTreeMap::new())
+            .expect("Could not resolve parameters");
+        assert_eq!(values.get("size"), Some(&json!(3)));
+    }
+
+    #[test]
+    fn missing_value() {
+        assert!(resolve(&[size_parameter(None)], &BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn undeclared_value() {
+        let supplied = BTreeMap::from([("width".to_string(), json!(5))]);
+        assert!(resolve(&[size_parameter(Some(json!(3)))], &supplied).is_err());
+    }
+
+    #[test]
+    fn wrong_type() {
+        let supplied = BTreeMap::from([("size".to_string(), json!("five"))]);
+        assert!(resolve(&[size_parameter(None)], &supplied).is_err());
+    }
+
+    #[test]
+    fn substitute_in_route() {
+        let values = BTreeMap::from([("field".to_string(), json!("total"))]);
+        assert_eq!(substitute_str("sum/${field}", &values), "sum/total");
+    }
+
+    #[test]
+    fn substitute_typed_value() {
+        let values = BTreeMap::from([("size".to_string(), json!(5))]);
+        assert_eq!(substitute_value(&json!("${size}"), &values), json!(5));
+        assert_eq!(substitute_value(&json!(["${size}", "size ${size}"]), &values),
+                   json!([5, "size 5"]));
+    }
+
+    #[test]
+    fn substitute_in_initializer() {
+        let values = BTreeMap::from([("size".to_string(), json!(5))]);
+        assert_eq!(substitute_initializer(&InputInitializer::Once(json!("${size}")), &values),
+                   InputInitializer::Once(json!(5)));
+    }
+}
@@ -1,4 +1,5 @@
 use serde_derive::{Deserialize, Serialize};
+use url::Url;
 
 use crate::model::flow_definition::FlowDefinition;
 use crate::model::function_definition::FunctionDefinition;
@@ -16,6 +17,17 @@ pub enum Process {
     FunctionProcess(FunctionDefinition),
 }
 
+impl Process {
+    /// Return the url of the file/resource where the definition of this process was read from
+    #[must_use]
+    pub fn source_url(&self) -> &Url {
+        match self {
+            Process::FlowProcess(flow) => &flow.source_url,
+            Process::FunctionProcess(function) => function.get_source_url(),
+        }
+    }
+}
+
 impl HasName for Process {
     fn name(&self) -> &Name {
         match self {
@@ -1,17 +1,23 @@
 use std::collections::BTreeMap;
 use std::fmt;
 
+use error_chain::bail;
+use serde::{de, Deserializer};
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
 
 use crate::errors::Result;
+use crate::model::function_definition::FunctionDefinition;
 use crate::model::input::InputInitializer;
+use crate::model::lock_file::VERSION_QUERY;
 use crate::model::name::HasName;
 use crate::model::name::Name;
 use crate::model::validation::Validate;
 
 /// A `ProcessReference` is the struct used in a `Flow` to refer to a sub-process (Function or nested
 /// Flow) it contains
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ProcessReference {
     /// A reference may have an alias - this is used when multiple instances of the same Process
@@ -20,11 +26,38 @@ pub struct ProcessReference {
     #[serde(default = "Name::default")]
     pub alias: Name,
     /// Relative or absolute source of the referenced process
+    #[serde(default)]
     pub source: String,
+    /// The definition of an anonymous function, written inline in the flow instead of being
+    /// referenced by `source`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline: Option<FunctionDefinition>,
     /// When a process is references, each reference can set different initial values on the inputs
     /// of the referenced process.
     #[serde(default, rename = "input")]
     pub initializations: BTreeMap<String, InputInitializer>,
+    /// The values supplied for the parameters declared by the referenced flow
+    #[serde(default, rename = "parameter")]
+    pub parameters: BTreeMap<String, Value>,
+}
+
+/// Deserialize the `ProcessReference`s of a flow, failing if one of them refers to no process,
+/// having neither a `source` nor an `inline` function definition
+///
+/// # Errors
+///
+/// Returns `Err` if the references cannot be deserialized, or one of them refers to no process
+pub fn process_references<'de, D>(deserializer: D) -> std::result::Result<Vec<ProcessReference>, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    let references: Vec<ProcessReference> = serde::Deserialize::deserialize(deserializer)?;
+    if let Some(reference) = references.iter()
+        .find(|reference| reference.source.is_empty() && reference.inline.is_none()) {
+        return Err(de::Error::custom(format!("The process reference '{}' has no 'source' or \
+            'inline' function definition", reference.alias)));
+    }
+    Ok(references)
 }
 
 impl ProcessReference {
@@ -35,6 +68,27 @@ impl ProcessReference {
             alias.clone_into(&mut self.alias);
         }
     }
+
+    /// Return the Url of the referenced process, with its `source` relative to `flow_url`, the
+    /// Url of the flow that references it. A version requirement in a library reference
+    /// (e.g. "lib://flowstdlib@^1.2/math/add") is moved into a "version" query parameter
+    /// (e.g. "lib://flowstdlib/math/add?version=%5E1.2") that is used when resolving the library
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a valid Url cannot be formed from `source`
+    pub fn source_url(&self, flow_url: &Url) -> Result<Url> {
+        if let Some(lib_path) = self.source.strip_prefix("lib://") {
+            let (lib, path_under_lib) = lib_path.split_once('/').unwrap_or((lib_path, ""));
+            if let Some((lib_name, requirement)) = lib.split_once('@') {
+                let mut url = Url::parse(&format!("lib://{lib_name}/{path_under_lib}"))?;
+                url.query_pairs_mut().append_pair(VERSION_QUERY, requirement);
+                return Ok(url);
+            }
+        }
+
+        Ok(flow_url.join(&self.source)?)
+    }
 }
 
 impl HasName for ProcessReference {
@@ -48,6 +102,12 @@ impl HasName for ProcessReference {
 
 impl Validate for ProcessReference {
     fn validate(&self) -> Result<()> {
+        match (self.source.is_empty(), &self.inline) {
+            (true, None) => bail!("A process reference must have a 'source' or an 'inline' function definition"),
+            (false, Some(_)) => bail!("A process reference cannot have both a 'source' ('{}') and an 'inline' \
+                function definition", self.source),
+            _ => {}
+        }
         self.alias.validate()
     }
 }
@@ -70,6 +130,7 @@ mod test {
     use crate::deserializers::deserializer::get;
     use crate::errors::Result;
     use crate::model::input::InputInitializer::{Always, Once};
+    use crate::model::validation::Validate;
 
     use super::ProcessReference;
 
@@ -191,6 +252,40 @@ mod test {
         }
     }
 
+    #[test]
+    fn deserialize_inline_function() {
+        let input_str = "
+        alias = 'double'
+        inline = { function = 'double', source = 'double.wasm', type = 'wasm', input = [{type = 'number'}], output = [{type = 'number'}] }
+        ";
+
+        let reference: ProcessReference =
+            toml_from_str(input_str).expect("Could not deserialize ProcessReference from toml");
+        assert!(reference.validate().is_ok());
+        let function = reference.inline.expect("Could not get inline function definition");
+        assert_eq!(function.name, "double");
+        assert_eq!(function.get_source(), "double.wasm");
+    }
+
+    #[test]
+    fn source_and_inline_invalid() {
+        let input_str = "
+        source = 'other.toml'
+        inline = { function = 'double', source = 'double.wasm', type = 'wasm' }
+        ";
+
+        let reference: ProcessReference =
+            toml_from_str(input_str).expect("Could not deserialize ProcessReference from toml");
+        assert!(reference.validate().is_err());
+    }
+
+    #[test]
+    fn no_source_invalid() {
+        let reference: ProcessReference =
+            toml_from_str("alias = 'other'").expect("Could not deserialize ProcessReference from toml");
+        assert!(reference.validate().is_err());
+    }
+
     #[test]
     fn deserialize_extra_field_fails() {
         let input_str = "
@@ -202,4 +297,25 @@ mod test {
         let reference: Result<ProcessReference> = toml_from_str(input_str);
         assert!(reference.is_err());
     }
+
+    #[test]
+    fn source_url_relative() {
+        let reference = toml_from_str("source = 'other.toml'")
+            .expect("Could not deserialize ProcessReference from toml");
+        let flow_url = Url::parse("file:///flows/root.toml").expect("Could not parse URL");
+        assert_eq!(reference.source_url(&flow_url).expect("Could not get source Url").as_str(),
+                   "file:///flows/other.toml");
+    }
+
+    #[test]
+    fn source_url_with_version_requirement() {
+        let reference = toml_from_str("source = 'lib://flowstdlib@^1.2/math/add'")
+            .expect("Could not deserialize ProcessReference from toml");
+        let flow_url = Url::parse("file:///flows/root.toml").expect("Could not parse URL");
+        let url = reference.source_url(&flow_url).expect("Could not get source Url");
+        assert_eq!(url.host_str(), Some("flowstdlib"));
+        assert_eq!(url.path(), "/math/add");
+        assert_eq!(url.query_pairs().next().map(|(key, value)| (key.to_string(), value.to_string())),
+                   Some(("version".to_string(), "^1.2".to_string())));
+    }
 }
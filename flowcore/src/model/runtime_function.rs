@@ -1,5 +1,6 @@
 #[cfg(feature = "debugger")]
 use std::fmt;
+use std::sync::Arc;
 
 use log::debug;
 use serde_derive::{Deserialize, Serialize};
@@ -10,6 +11,30 @@ use crate::errors::{Result, ResultExt};
 use crate::model::input::Input;
 use crate::model::input::InputInitializer;
 use crate::model::output_connection::OutputConnection;
+#[cfg(feature = "debugger")]
+use crate::model::source_location::SourceLocation;
+
+/// The `Priority` of the jobs for a function. When executors are busy, jobs of latency-sensitive
+/// functions (such as those doing IO) with a higher priority are dispatched ahead of others
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Jobs are dispatched after those of other priorities, such as for bulk computation
+    Low,
+    /// The default priority of jobs
+    #[default]
+    Normal,
+    /// Jobs are dispatched before those of other priorities
+    High,
+}
+
+impl Priority {
+    /// Return true if this is the default `Normal` priority
+    #[must_use]
+    pub fn is_normal(&self) -> bool {
+        *self == Priority::Normal
+    }
+}
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
 /// `RuntimeFunction` contains all the information needed about a function and its implementation
@@ -23,6 +48,11 @@ pub struct RuntimeFunction {
     #[serde(default, skip_serializing_if = "String::is_empty")]
     route: String,
 
+    /// Where in a flow definition file this function was referenced from
+    #[cfg(feature = "debugger")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_location: Option<SourceLocation>,
+
     /// The unique `function_id` of this function at run-time
     function_id: usize,
 
@@ -42,11 +72,28 @@ pub struct RuntimeFunction {
     #[serde(skip_serializing_if = "is_default_url", default = "default_url")]
     implementation_url: Url,
 
+    /// The SHA-256 hash of a provided WASM implementation file, recorded when it was compiled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    implementation_hash: Option<String>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     inputs: Vec<Input>,
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     output_connections: Vec<OutputConnection>,
+
+    /// The number of times a job for this function is retried if its implementation returns an
+    /// error, overriding the default `RetryPolicy` of the `Submission`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retries: Option<usize>,
+
+    /// Is the function pure, so that the results of its jobs can be memoized
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pure: bool,
+
+    /// The `Priority` with which jobs for this function are dispatched
+    #[serde(default, skip_serializing_if = "Priority::is_normal")]
+    priority: Priority,
 }
 
 fn is_default_url(url: &Url) -> bool {
@@ -72,6 +119,10 @@ impl fmt::Display for RuntimeFunction {
 
         writeln!(f, "\t({})", self.implementation_location)?;
 
+        if let Some(source_location) = &self.source_location {
+            writeln!(f, "\tDefined at: {source_location}")?;
+        }
+
         for (number, input) in self.inputs.iter().enumerate() {
             writeln!(f, "\tInput:{number} {input}")?;
         }
@@ -107,10 +158,12 @@ impl RuntimeFunction {
     ) -> Self {
         let mut connections = output_connections.to_vec();
 
-        // Remove destination routes if not wanted
+        // Remove destination routes and source locations if not wanted
         if !include_destination_routes {
             for connection in &mut connections {
                 connection.destination = String::default();
+                #[cfg(feature = "debugger")]
+                connection.set_source_location(None);
             }
         }
 
@@ -119,12 +172,18 @@ impl RuntimeFunction {
             name: name.into(),
             #[cfg(feature = "debugger")]
             route: route.into(),
+            #[cfg(feature = "debugger")]
+            source_location: None,
             function_id: id,
             flow_id,
             implementation_location: implementation_location.into(),
             implementation_url: default_url(),
+            implementation_hash: None,
             output_connections: connections,
             inputs,
+            retries: None,
+            pure: false,
+            priority: Priority::Normal,
         }
     }
 
@@ -151,6 +210,19 @@ impl RuntimeFunction {
         &self.route
     }
 
+    /// Accessor for where in a flow definition file this `RuntimeFunction` was referenced from
+    #[cfg(feature = "debugger")]
+    #[must_use]
+    pub fn source_location(&self) -> Option<&SourceLocation> {
+        self.source_location.as_ref()
+    }
+
+    /// Set where in a flow definition file this `RuntimeFunction` was referenced from
+    #[cfg(feature = "debugger")]
+    pub fn set_source_location(&mut self, source_location: Option<SourceLocation>) {
+        self.source_location = source_location;
+    }
+
     /// Accessor for a `RuntimeFunction` `id`
     #[must_use]
     pub fn id(&self) -> usize {
@@ -186,11 +258,12 @@ impl RuntimeFunction {
         &self.implementation_location
     }
 
-    /// Send a value to the specified input of this function.
+    /// Send a value to the specified input of this function. A value already shared (such as one
+    /// being sent to several inputs) is not copied
     /// # Errors
     ///
     /// Will return `Err` if the IO numbered `io_number` does not exist
-    pub fn send(&mut self, io_number: usize, value: Value) -> Result<()> {
+    pub fn send(&mut self, io_number: usize, value: impl Into<Arc<Value>>) -> Result<()> {
         let _ = self
             .inputs
             .get_mut(io_number)
@@ -227,6 +300,51 @@ impl RuntimeFunction {
         &self.implementation_url
     }
 
+    /// Get the number of times a failed job for this function should be retried, if specified
+    #[must_use]
+    pub fn retries(&self) -> Option<usize> {
+        self.retries
+    }
+
+    /// Set the number of times a failed job for this function should be retried, overriding the
+    /// default `RetryPolicy` of the `Submission`
+    pub fn set_retries(&mut self, retries: Option<usize>) {
+        self.retries = retries;
+    }
+
+    /// Get the `Priority` with which jobs for this function are dispatched
+    #[must_use]
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Set the `Priority` with which jobs for this function are dispatched
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    /// Return true if the function is pure, and so the results of its jobs can be memoized
+    #[must_use]
+    pub fn is_pure(&self) -> bool {
+        self.pure
+    }
+
+    /// Set whether the function is pure, and so the results of its jobs can be memoized
+    pub fn set_pure(&mut self, pure: bool) {
+        self.pure = pure;
+    }
+
+    /// Get the SHA-256 hash of the function's provided WASM implementation, if one was recorded
+    #[must_use]
+    pub fn implementation_hash(&self) -> Option<&String> {
+        self.implementation_hash.as_ref()
+    }
+
+    /// Set the SHA-256 hash of the function's provided WASM implementation
+    pub fn set_implementation_hash(&mut self, implementation_hash: Option<String>) {
+        self.implementation_hash = implementation_hash;
+    }
+
     fn location_to_url(manifest_url: &Url, location: &str) -> Result<Url> {
         Url::parse(location)
             .or_else(|_| manifest_url.clone().join(location))
@@ -245,6 +363,25 @@ impl RuntimeFunction {
             .is_empty())
     }
 
+    /// Returns true if [Input] number `input_number` would receive an array of values such as
+    /// `element` as a series of its elements, so they can be sent to it one at a time
+    #[must_use]
+    pub fn accepts_elements(&self, input_number: usize, element: &Value) -> bool {
+        self.inputs
+            .get(input_number)
+            .is_some_and(|input| input.accepts_elements(element))
+    }
+
+    /// Return the JSON Schema (if any) that values of each of the function's inputs should
+    /// conform to, or an empty `Vec` if none of its inputs declare one
+    #[must_use]
+    pub fn input_schemas(&self) -> Vec<Option<Value>> {
+        if self.inputs.iter().all(|input| input.schema().is_none()) {
+            return vec![];
+        }
+        self.inputs.iter().map(|input| input.schema().clone()).collect()
+    }
+
     /// Returns how many jobs can be created for this function with the available inputs
     /// NOTE: For Impure functions without inputs (that can always run and produce a value)
     /// this will return 1 always
@@ -272,7 +409,6 @@ impl RuntimeFunction {
     }
 
     /// Inspect the values of the `inputs` of a `RuntimeFunction`
-    #[cfg(any(feature = "debugger", debug_assertions))]
     #[must_use]
     pub fn inputs(&self) -> &Vec<Input> {
         &self.inputs
@@ -285,13 +421,24 @@ impl RuntimeFunction {
         self.inputs.get(id)
     }
 
+    /// Replace the values queued up on the `input` number `id` of a `RuntimeFunction`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if input `id` does not exist
+    #[cfg(feature = "debugger")]
+    pub fn set_input_values(&mut self, id: usize, values: Vec<Value>) -> Result<()> {
+        self.inputs.get_mut(id).ok_or("Could not get that input")?.set_values(values);
+        Ok(())
+    }
+
     /// Read the values from the inputs and return them for use in executing the `RuntimeFunction`
-    pub fn take_input_set(&mut self) -> Option<Vec<Value>> {
+    pub fn take_input_set(&mut self) -> Option<Vec<Arc<Value>>> {
         if !self.can_run() {
             return None;
         }
 
-        let mut input_set: Vec<Value> = Vec::with_capacity(self.inputs.len());
+        let mut input_set: Vec<Arc<Value>> = Vec::with_capacity(self.inputs.len());
         for input in &mut self.inputs {
             input_set.push(input.take()?);
         }
@@ -329,7 +476,7 @@ mod test {
     use crate::model::output_connection::OutputConnection;
     use crate::model::output_connection::Source::Output;
 
-    use super::RuntimeFunction;
+    use super::{Priority, RuntimeFunction};
 
     /*************** Below are tests for basic json.pointer functionality *************************/
 
@@ -376,7 +523,7 @@ mod test {
         function.send(0, json!(1)).expect("Could not send");
         assert_eq!(
             json!(1),
-            function
+            *function
                 .take_input_set()
                 .expect("Couldn't get input set")
                 .remove(0),
@@ -391,7 +538,7 @@ mod test {
         function.send(0, json!([1, 2])).expect("Could not send");
         assert_eq!(
             json!([1, 2]),
-            function
+            *function
                 .take_input_set()
                 .expect("Couldn't get input set")
                 .remove(0),
@@ -405,7 +552,7 @@ mod test {
         function.init();
         function.send(0, json!([1, 2])).expect("Could not send");
         assert_eq!(
-            function
+            *function
                 .take_input_set()
                 .expect("Couldn't get input set")
                 .remove(0),
@@ -445,6 +592,46 @@ mod test {
         )
     }
 
+    #[test]
+    fn retries_serialized_only_if_set() {
+        let mut function = test_function(0);
+        let json = serde_json::to_string(&function).expect("Could not serialize");
+        assert!(!json.contains("retries"));
+
+        function.set_retries(Some(3));
+        let json = serde_json::to_string(&function).expect("Could not serialize");
+        let deserialized: RuntimeFunction = serde_json::from_str(&json)
+            .expect("Could not deserialize");
+        assert_eq!(deserialized.retries(), Some(3));
+    }
+
+    #[test]
+    fn priority_serialized_only_if_not_normal() {
+        let mut function = test_function(0);
+        let json = serde_json::to_string(&function).expect("Could not serialize");
+        assert!(!json.contains("priority"));
+
+        function.set_priority(Priority::High);
+        let json = serde_json::to_string(&function).expect("Could not serialize");
+        assert!(json.contains("\"priority\":\"high\""));
+        let deserialized: RuntimeFunction = serde_json::from_str(&json)
+            .expect("Could not deserialize");
+        assert_eq!(deserialized.priority(), Priority::High);
+    }
+
+    #[test]
+    fn pure_serialized_only_if_set() {
+        let mut function = test_function(0);
+        let json = serde_json::to_string(&function).expect("Could not serialize");
+        assert!(!json.contains("pure"));
+
+        function.set_pure(true);
+        let json = serde_json::to_string(&function).expect("Could not serialize");
+        let deserialized: RuntimeFunction = serde_json::from_str(&json)
+            .expect("Could not deserialize");
+        assert!(deserialized.is_pure());
+    }
+
     #[cfg(feature = "debugger")]
     #[test]
     fn debugger_can_inspect_non_full_input() {
@@ -650,7 +837,7 @@ mod test {
                 // Check
                 assert_eq!(
                     test_case.value_expected,
-                    function
+                    *function
                         .take_input_set()
                         .expect("Couldn't get input set")
                         .remove(0)
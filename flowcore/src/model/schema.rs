@@ -0,0 +1,68 @@
+use serde_json::Value;
+
+use crate::errors::{bail, Result};
+
+/// Check that `schema` is a valid JSON Schema
+///
+/// # Errors
+///
+/// Returns an error describing the problem if `schema` is not a valid JSON Schema
+pub fn check_schema(schema: &Value) -> Result<()> {
+    if let Err(e) = jsonschema::validator_for(schema) {
+        bail!("Invalid JSON Schema: {}", e);
+    }
+    Ok(())
+}
+
+/// Validate `value` against the JSON Schema `schema`
+///
+/// # Errors
+///
+/// Returns an error if `schema` is not a valid JSON Schema, or if `value` does not conform
+/// to it, describing all the ways in which it does not
+pub fn validate(schema: &Value, value: &Value) -> Result<()> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| format!("Invalid JSON Schema: {e}"))?;
+    let errors = validator.iter_errors(value)
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>();
+    if !errors.is_empty() {
+        bail!("Value '{}' does not match schema: {}", value, errors.join(", "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{check_schema, validate};
+
+    #[test]
+    fn valid_schema() {
+        assert!(check_schema(&json!({"type": "number", "minimum": 0})).is_ok());
+    }
+
+    #[test]
+    fn invalid_schema() {
+        assert!(check_schema(&json!({"type": "no-such-type"})).is_err());
+    }
+
+    #[test]
+    fn value_matches() {
+        assert!(validate(&json!({"type": "number", "minimum": 0}), &json!(42)).is_ok());
+    }
+
+    #[test]
+    fn value_does_not_match() {
+        let error = validate(&json!({"type": "number", "minimum": 0}), &json!(-1))
+            .expect_err("Value should not match the schema");
+        assert!(error.to_string().contains("minimum"));
+    }
+
+    #[test]
+    fn object_missing_property() {
+        let schema = json!({"type": "object", "required": ["name"]});
+        assert!(validate(&schema, &json!({"age": 3})).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+use url::Url;
+
+/// A `SourceLocation` is where in a flow definition file a process was referenced or a connection
+/// was defined. They are recorded in the manifest when compiled with debug symbols, so that the
+/// debugger can show where in the flow's definition a function or connection came from.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct SourceLocation {
+    /// The Url of the definition file
+    pub file: Url,
+    /// The line number (starting at 1) in the definition file, or 0 if it is not known
+    #[serde(default, skip_serializing_if = "is_unknown_line")]
+    pub line: usize,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_unknown_line(line: &usize) -> bool {
+    *line == 0
+}
+
+impl SourceLocation {
+    /// Create a new `SourceLocation` for line number `line` in the file at `file`
+    #[must_use]
+    pub fn new(file: &Url, line: usize) -> Self {
+        SourceLocation {
+            file: file.clone(),
+            line,
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.file)
+        } else {
+            write!(f, "{}:{}", self.file, self.line)
+        }
+    }
+}
+
+/// Find the line number (starting at 1) of the `occurrence`th (starting at 0) line in `content`
+/// that assigns `value` to the field `key`, in any of the supported definition formats, or 0 if
+/// it is not found. e.g. `source = "value"` (toml), `source: value` (yaml) or
+/// `"source": "value"` (json)
+#[must_use]
+pub fn line_of(content: &str, key: &str, value: &str, occurrence: usize) -> usize {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| assigns(line, key, value))
+        .nth(occurrence)
+        .map_or(0, |(number, _)| number + 1)
+}
+
+// Return true if `line` assigns `value` to the field `key`, possibly within an inline table
+fn assigns(line: &str, key: &str, value: &str) -> bool {
+    let Some(after_key) = line.find(key).and_then(|start| line.get(start + key.len()..)) else {
+        return false;
+    };
+
+    match after_key.trim_start_matches(['"', '\'']).trim_start().strip_prefix(['=', ':']) {
+        Some(assigned) => {
+            let assigned = assigned.trim();
+            assigned.starts_with(&format!("\"{value}\"")) || assigned.starts_with(&format!("'{value}'"))
+                || assigned.trim_end_matches(',').trim() == value
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use url::Url;
+
+    use super::{line_of, SourceLocation};
+
+    #[test]
+    fn display() {
+        let url = Url::parse("file:///root.toml").expect("Could not parse Url");
+        assert_eq!(SourceLocation::new(&url, 3).to_string(), "file:///root.toml:3");
+        assert_eq!(SourceLocation::new(&url, 0).to_string(), "file:///root.toml");
+    }
+
+    #[test]
+    fn toml_line() {
+        let content = "flow = \"test\"\n\n[[process]]\nsource = \"lib://flowstdlib/math/add\"\n\n\
+        [[process]]\nalias = \"other\"\nsource = \"lib://flowstdlib/math/add\"\n";
+        assert_eq!(line_of(content, "source", "lib://flowstdlib/math/add", 0), 4);
+        assert_eq!(line_of(content, "source", "lib://flowstdlib/math/add", 1), 8);
+    }
+
+    #[test]
+    fn yaml_line() {
+        let content = "flow: test\nconnection:\n  - from: add\n    to: print\n";
+        assert_eq!(line_of(content, "from", "add", 0), 3);
+    }
+
+    #[test]
+    fn json_line() {
+        let content = "{\n  \"flow\": \"test\",\n  \"connection\": [\n    {\n      \"from\": \"add\",\n\
+        \"to\": \"print\"\n    }\n  ]\n}";
+        assert_eq!(line_of(content, "from", "add", 0), 5);
+    }
+
+    #[test]
+    fn inline_table_line() {
+        let content = "flow = \"test\"\n[[process]]\ninline = { function = \"double\", source = \"double.wasm\" }\n";
+        assert_eq!(line_of(content, "function", "double", 0), 3);
+    }
+
+    #[test]
+    fn not_found() {
+        assert_eq!(line_of("flow = \"test\"", "from", "add", 0), 0);
+        assert_eq!(line_of("from = \"add\"", "from", "add", 1), 0);
+    }
+}
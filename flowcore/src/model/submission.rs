@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use log::info;
@@ -6,6 +7,90 @@ use serde_derive::{Deserialize, Serialize};
 
 use crate::model::flow_manifest::FlowManifest;
 
+/// The time waited before the first retry of a failed job, if a function specifies that its jobs
+/// should be retried but the `Submission` does not have a `RetryPolicy`
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A `RetryPolicy` defines what happens when the implementation of a function returns an error
+/// for a job: the job is retried up to `retries` times, waiting `backoff` before the first retry
+/// and doubling the wait before each subsequent one. If the job still fails after that, execution
+/// of the flow is aborted.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// The number of times a failed job is retried before the flow is aborted
+    pub retries: usize,
+    /// The time to wait before the first retry of a job
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Get the `RetryPolicy` that applies to jobs for a function, using the number of `retries`
+    /// specified by the function if any, otherwise the `default` policy of the `Submission`.
+    ///
+    /// `None` means jobs for the function are not retried, and errors do not abort the flow.
+    #[must_use]
+    pub fn for_function(retries: Option<usize>, default: Option<RetryPolicy>) -> Option<Self> {
+        match retries {
+            Some(retries) => Some(RetryPolicy {
+                retries,
+                backoff: default.map_or(DEFAULT_RETRY_BACKOFF, |policy| policy.backoff),
+            }),
+            None => default,
+        }
+    }
+}
+
+/// A `JobTimeoutPolicy` defines what happens to a job whose result is not received within the
+/// `job_timeout` of the `Submission`, for example because the executor running it hung or was lost
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum JobTimeoutPolicy {
+    /// The job fails with a timeout error, and is retried if its `RetryPolicy` allows
+    #[default]
+    Fail,
+    /// The job is dispatched for execution again, up to this many times, before it fails
+    Redispatch(usize),
+}
+
+/// `ResourceLimits` on the execution of a flow protect a coordinator, that may be shared by many
+/// clients, from flows that use far more resources than expected, such as runaway recursive flows.
+/// Execution of a flow that exceeds one of them is aborted.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct ResourceLimits {
+    /// The maximum number of jobs created, `None` if not limited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_jobs: Option<usize>,
+    /// The maximum estimated memory, in bytes, used by the values held on the inputs of functions
+    /// and of jobs (when serialized as JSON), `None` if not limited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory: Option<usize>,
+    /// The maximum time the flow may execute for, `None` if not limited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_time: Option<Duration>,
+}
+
+impl ResourceLimits {
+    /// Return true if none of the resources are limited
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        *self == ResourceLimits::default()
+    }
+}
+
+/// The interval between checkpoints of the state of execution, if not specified
+pub const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A `CheckpointPolicy` defines where and how often the state of a flow's execution is saved to
+/// disk (a "checkpoint") so that execution can be resumed from it later, for example after a crash.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct CheckpointPolicy {
+    /// The path of the file the checkpoint is written to
+    pub path: PathBuf,
+    /// The interval between checkpoints being written during execution
+    pub interval: Duration,
+    /// Resume execution from the checkpoint at `path`, instead of from the flow's initial state
+    pub resume: bool,
+}
+
 /// A `Submission` is the struct used to send a flow to the Coordinator for execution. It contains
 /// all the information necessary to execute it:
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
@@ -14,8 +99,44 @@ pub struct Submission {
     pub manifest: FlowManifest,
     /// An optional maximum number of jobs you want dispatched/executing in parallel
     pub max_parallel_jobs: Option<usize>,
-    /// The Duration to wait before timing out when waiting for jobs to complete
+    /// The Duration to wait for the result of a job after it was dispatched, before applying the
+    /// `job_timeout_policy` to it. `None` to wait for results forever
     pub job_timeout: Option<Duration>,
+    /// What happens to a job whose result is not received within `job_timeout`
+    #[serde(default)]
+    pub job_timeout_policy: JobTimeoutPolicy,
+    /// The default `RetryPolicy` for jobs that fail, `None` if they should not be retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_policy: Option<RetryPolicy>,
+    /// The `CheckpointPolicy` to use to save (and possibly resume) the state of execution,
+    /// `None` if no checkpoints should be written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_policy: Option<CheckpointPolicy>,
+    /// Execute jobs one at a time, in the order they became ready to run, so that executing the
+    /// flow with the same inputs always produces the same output in the same order
+    #[serde(default)]
+    pub deterministic: bool,
+    /// The maximum number of elements of an array output by a job that are returned in one
+    /// message. Larger arrays are streamed back in chunks and delivered incrementally to their
+    /// destinations. `None` if array outputs should not be streamed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<usize>,
+    /// Validate the values of inputs of jobs against the JSON Schemas declared for them before
+    /// the jobs are executed, failing jobs with values that do not conform to them
+    #[serde(default)]
+    pub validate: bool,
+    /// The maximum amount of fuel (roughly, the number of instructions) that a single job with a
+    /// WASM implementation may consume, failing the job if it runs out. `None` if not limited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// The maximum number of small library jobs sent to an executor together in one message, with
+    /// their results returned together, to reduce the messaging overhead of flows with many
+    /// small jobs. `None` if jobs should be sent one at a time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+    /// The `ResourceLimits` on the execution of the flow, that abort it if exceeded
+    #[serde(default, skip_serializing_if = "ResourceLimits::is_unlimited")]
+    pub limits: ResourceLimits,
     /// Whether debugging is enabled or not for the flow
     #[cfg(feature = "debugger")]
     pub debug_enabled: bool,
@@ -24,12 +145,15 @@ pub struct Submission {
 impl Submission {
     /// Create a new `Submission` of a flow for execution with the specified `FlowManifest`
     /// optionally setting a limit for the number of jobs running in parallel
-    /// via `max_parallel_jobs`
+    /// via `max_parallel_jobs`, the default `retry_policy` for jobs that fail and the
+    /// `checkpoint_policy` used to save and resume the state of execution
     #[must_use]
     pub fn new(
         manifest: FlowManifest,
         max_parallel_jobs: Option<usize>,
         job_timeout: Option<Duration>,
+        retry_policy: Option<RetryPolicy>,
+        checkpoint_policy: Option<CheckpointPolicy>,
         #[cfg(feature = "debugger")] debug: bool,
     ) -> Submission {
         if let Some(limit) = max_parallel_jobs {
@@ -40,6 +164,15 @@ impl Submission {
             manifest,
             max_parallel_jobs,
             job_timeout,
+            job_timeout_policy: JobTimeoutPolicy::Fail,
+            retry_policy,
+            checkpoint_policy,
+            deterministic: false,
+            chunk_size: None,
+            validate: false,
+            fuel: None,
+            batch_size: None,
+            limits: ResourceLimits::default(),
             #[cfg(feature = "debugger")]
             debug_enabled: debug,
         }
@@ -51,8 +184,77 @@ impl fmt::Display for Submission {
             writeln!(f, "Maximum Parallel Jobs: {limit}")?;
         }
         writeln!(f,   "          Job Timeout: {:?}", self.job_timeout)?;
+        if let JobTimeoutPolicy::Redispatch(times) = self.job_timeout_policy {
+            writeln!(f, "   Job Timeout Policy: redispatch up to {times} times")?;
+        }
+        if let Some(policy) = self.retry_policy {
+            writeln!(f, "         Retry Policy: {} retries, {:?} backoff", policy.retries,
+                     policy.backoff)?;
+        }
+        if let Some(policy) = &self.checkpoint_policy {
+            writeln!(f, "           Checkpoint: {}, interval {:?}, resume {}",
+                     policy.path.display(), policy.interval, policy.resume)?;
+        }
+        if self.deterministic {
+            writeln!(f, "        Deterministic: true")?;
+        }
+        if let Some(chunk_size) = self.chunk_size {
+            writeln!(f, "           Chunk Size: {chunk_size}")?;
+        }
+        if self.validate {
+            writeln!(f, "      Validate Inputs: true")?;
+        }
+        if let Some(fuel) = self.fuel {
+            writeln!(f, "         Fuel per Job: {fuel}")?;
+        }
+        if let Some(batch_size) = self.batch_size {
+            writeln!(f, "           Batch Size: {batch_size}")?;
+        }
+        if let Some(max_jobs) = self.limits.max_jobs {
+            writeln!(f, "             Max Jobs: {max_jobs}")?;
+        }
+        if let Some(max_memory) = self.limits.max_memory {
+            writeln!(f, "     Max Memory Bytes: {max_memory}")?;
+        }
+        if let Some(max_time) = self.limits.max_time {
+            writeln!(f, "             Max Time: {max_time:?}")?;
+        }
         #[cfg(feature = "debugger")]
         writeln!(f,   "                Debug: {}", self.debug_enabled)?;
         write!(f,     "             Manifest: \n{}", self.manifest)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{DEFAULT_RETRY_BACKOFF, RetryPolicy};
+
+    const DEFAULT: RetryPolicy = RetryPolicy {
+        retries: 3,
+        backoff: Duration::from_millis(10),
+    };
+
+    #[test]
+    fn no_retry_policy() {
+        assert_eq!(RetryPolicy::for_function(None, None), None);
+    }
+
+    #[test]
+    fn submission_default_policy() {
+        assert_eq!(RetryPolicy::for_function(None, Some(DEFAULT)), Some(DEFAULT));
+    }
+
+    #[test]
+    fn function_retries_override_default() {
+        assert_eq!(RetryPolicy::for_function(Some(1), Some(DEFAULT)),
+                   Some(RetryPolicy { retries: 1, backoff: Duration::from_millis(10) }));
+    }
+
+    #[test]
+    fn function_retries_without_default() {
+        assert_eq!(RetryPolicy::for_function(Some(2), None),
+                   Some(RetryPolicy { retries: 2, backoff: DEFAULT_RETRY_BACKOFF }));
+    }
+}
@@ -1,4 +1,3 @@
-#![feature(proc_macro_span)]
 //! `flow_function` is a `proc_macro_attribute` macro that wraps a `fn` with a struct and a method
 //! to implement the [Implementation][flowcore::Implementation] trait, so it can be used as the
 //! implementation of a flow function.
@@ -9,10 +8,11 @@ use proc_macro::{Span, TokenStream};
 use std::fs;
 use std::path::Path;
 
-use proc_macro2::Ident;
 use quote::{format_ident, quote, ToTokens};
-use syn::{ItemFn, parse_macro_input, ReturnType};
+use syn::{FnArg, GenericArgument, ItemFn, parse_macro_input, PathArguments, ReturnType, Type};
 
+use flowcore::model::datatype::{ARRAY_TYPE, BOOLEAN_TYPE, BYTES_TYPE, GENERIC_TYPE, NULL_TYPE,
+                                  NUMBER_TYPE, OBJECT_TYPE, STRING_TYPE};
 use flowcore::model::function_definition::FunctionDefinition;
 
 /// The `flow_function` macro definition
@@ -24,7 +24,8 @@ use flowcore::model::function_definition::FunctionDefinition;
 pub fn flow_function(_attr: TokenStream, implementation: TokenStream) -> TokenStream {
     // Get the full path to the file where the macro was used, and join the relative filename from
     // the macro's attributes, to find the path to the function's definition file
-    let mut definition_file_path = Span::call_site().source_file().path().canonicalize()
+    let mut definition_file_path = Span::call_site().local_file()
+        .and_then(|path| path.canonicalize().ok())
         .expect("the 'flow' macro could not get the full file path of the file where it was invoked");
     definition_file_path.set_extension("toml");
 
@@ -45,11 +46,14 @@ fn load_function_definition(path: &Path) -> FunctionDefinition {
 }
 
 // If the function accepts inputs as &[serde_json::Value] then there is no need to extract
-// and convert the inputs, otherwise form the expected list of inputs for the implementation
-// function from the vector of Values passed in.
+// and convert the inputs, otherwise generate code to extract each input Value and convert it to
+// the type of the corresponding implementation function parameter, after checking that type is
+// compatible with the input's type in the function definition.
+// Returns the conversion code and the list of arguments to call the implementation function with.
 // Full of hacks as TokenStream2 from into_token_stream() doesn't implement PartialEq to be
 // able to compare it with a quote!() version of what I'm expecting
-fn input_conversion(definition: &FunctionDefinition, implementation_ast: &ItemFn) -> Ident {
+fn input_conversion(definition: &FunctionDefinition, implementation_ast: &ItemFn)
+    -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     let implementation_name = &implementation_ast.sig.ident;
     let implemented_inputs = &implementation_ast.sig.inputs;
 
@@ -59,7 +63,7 @@ fn input_conversion(definition: &FunctionDefinition, implementation_ast: &ItemFn
             .expect("the 'flow' macro could not get the function's first argument type");
 
         if input.into_token_stream().to_string() == quote! { inputs : &[Value] }.to_string() {
-            return format_ident!("inputs");
+            return (quote! {}, quote! { inputs });
         }
     }
 
@@ -70,25 +74,84 @@ fn input_conversion(definition: &FunctionDefinition, implementation_ast: &ItemFn
                definition.name, definition.inputs.len(),
                implementation_name, implemented_inputs.len());
 
-    // TODO If function accepts types directly (not `&[Value]`), check they match function definition
-    // for input_pair in implemented_inputs.pairs() {
-    //    match input_pair {
-    //       Punctuated(t, p) => {
-    //           println!("FnArg: {:?}", t);
-    //           match t {
-    //               Receiver(r) => println!("error, self not allowed"),
-    //               Typed(pt) => {
-    //                  println!("PatType: {:?}", pt));
-    //                  println!("Input name: {}", pt.pat);
-    //                  println!("Input type: {:?}", pt.ty);
-    //               }
-    //           }
-    //       End(_) = {}
-    //       }
-    //    }
-    // }
-
-    unimplemented!()
+    let mut conversions = vec![];
+    let mut arguments = vec![];
+    for (index, (implemented_input, defined_input)) in implemented_inputs.iter()
+        .zip(definition.inputs.iter()).enumerate() {
+        let implemented_type = match implemented_input {
+            FnArg::Typed(typed_input) => &typed_input.ty,
+            FnArg::Receiver(_) => panic!("a 'flow_function' macro check failed:\n\
+                '{implementation_name}()' cannot take 'self' as an input"),
+        };
+
+        for datatype in defined_input.datatypes() {
+            assert!(type_matches(&datatype.to_string(), implemented_type),
+                    "a 'flow_function' macro check failed:\n\
+                    input #{index} of '{}' is defined of type '{datatype}'\n\
+                    but '{implementation_name}()' implements it as type '{}'",
+                    definition.name, implemented_type.into_token_stream());
+        }
+
+        let argument = format_ident!("input_{}", index);
+        let error = format!("Could not convert input #{index} to '{}'",
+                            implemented_type.into_token_stream());
+        conversions.push(quote! {
+            let #argument: #implemented_type = serde_json::from_value(
+                inputs.get(#index).ok_or("Could not get input")?.clone())
+                .map_err(|e| format!("{}: {e}", #error))?;
+        });
+        arguments.push(argument);
+    }
+
+    (quote! { #(#conversions)* }, quote! { #(#arguments),* })
+}
+
+// Determine if a `DataType` (as a String) defined for an input in a function definition can be
+// converted to the `implemented_type` of the corresponding parameter of the implementation
+// function. A `Value` can hold any `DataType`, and an `Option` of a compatible type can also
+// hold a `null`
+fn type_matches(datatype: &str, implemented_type: &Type) -> bool {
+    let segment = match implemented_type {
+        Type::Path(type_path) => match type_path.path.segments.last() {
+            Some(segment) => segment,
+            None => return false,
+        },
+        _ => return false,
+    };
+    let type_arguments: Vec<&Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments.args.iter()
+            .filter_map(|argument| match argument {
+                GenericArgument::Type(argument_type) => Some(argument_type),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    match (segment.ident.to_string().as_str(), type_arguments.as_slice()) {
+        ("Value", []) => true,
+        ("Option", [inner]) => datatype == NULL_TYPE || type_matches(datatype, inner),
+        ("String", []) => datatype == STRING_TYPE,
+        ("Bytes", []) => datatype == BYTES_TYPE,
+        ("bool", []) => datatype == BOOLEAN_TYPE,
+        ("i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" |
+         "usize" | "f32" | "f64", []) => datatype == NUMBER_TYPE,
+        ("Vec", [inner]) => contained_type_matches(datatype, ARRAY_TYPE, inner),
+        ("Map" | "HashMap" | "BTreeMap", [_key, inner]) =>
+            contained_type_matches(datatype, OBJECT_TYPE, inner),
+        _ => false,
+    }
+}
+
+// Determine if `datatype` is of the `container` type (array or object) and the type of values it
+// contains (generic if not specified) matches the `implemented_type` of the contained values
+fn contained_type_matches(datatype: &str, container: &str, implemented_type: &Type) -> bool {
+    match datatype.strip_prefix(container) {
+        Some("") => type_matches(GENERIC_TYPE, implemented_type),
+        Some(contained) => contained.strip_prefix('/')
+            .is_some_and(|contained_type| type_matches(contained_type, implemented_type)),
+        None => false,
+    }
 }
 
 // check that the return type of the implementation function is what we need. i.e. that it
@@ -112,7 +175,7 @@ fn generate_code(function_implementation: TokenStream,
 
     check_return_type(&implementation_ast.sig.output);
 
-    let input_list = input_conversion(definition, &implementation_ast);
+    let (input_conversion, input_list) = input_conversion(definition, &implementation_ast);
 
     let number_of_defined_inputs = definition.inputs.len();
 
@@ -155,25 +218,62 @@ fn generate_code(function_implementation: TokenStream,
             return ptr as *mut std::os::raw::c_void;
         }
 
-        // Wrapper function for running a wasm implementation
+        // Wrapper function for running a wasm implementation. Inputs and result are passed as JSON
+        // with values of the `bytes` type carried as raw bytes after it (see `to_wasm_data`)
+        // Errors deserializing the inputs, or a result too large to fit in the buffer allocated by
+        // the runtime, are returned as a serialized `Err` result, so the runtime can report them as
+        // a job error instead of the wasm module trapping
         #[cfg(target_arch = "wasm32")]
         #[no_mangle]
         pub extern "C" fn run_wasm(input_data_ptr: *mut std::os::raw::c_void, input_data_length: i32) -> i32 {
-            use std::ptr::copy;
-            let input_data: &[u8] = unsafe {
-                std::slice::from_raw_parts(input_data_ptr as *mut u8,
-                                      input_data_length as usize)
+            use std::ptr::copy_nonoverlapping;
+
+            // Encode an error as a serialized `Err` result
+            fn encode_error(message: String) -> Vec<u8> {
+                let error_result: flowcore::errors::Result<(Option<Value>, flowcore::RunAgain)> =
+                    Err(message.into());
+                serde_json::to_vec(&error_result)
+                    .unwrap_or_else(|_| br#"{"Err":"Could not serialize error"}"#.to_vec())
+            }
+
+            let input_data_length = usize::try_from(input_data_length).unwrap_or(0);
+
+            // Copy the inputs out of linear memory, so that writing the result back into the same
+            // buffer does not alias the input data
+            let input_data: Vec<u8> = if input_data_ptr.is_null() {
+                Vec::new()
+            } else {
+                unsafe {
+                    std::slice::from_raw_parts(input_data_ptr as *const u8, input_data_length)
+                }.to_vec()
+            };
+
+            let result = match flowcore::model::bytes::from_wasm_data::<Vec<Value>>(&input_data) {
+                Ok(inputs) => {
+                    let object = #struct_name {};
+                    object.run(&inputs)
+                },
+                Err(e) => Err(format!("Could not deserialize inputs: {e}").into()),
             };
 
-            let inputs: Vec<Value> = serde_json::from_slice(&input_data).unwrap();
-            let object = #struct_name {};
-            let result = object.run(&inputs);
+            let mut return_data = flowcore::model::bytes::to_wasm_data(&result)
+                .unwrap_or_else(|e| encode_error(format!("Could not serialize result: {e}")));
+
+            // The runtime allocated a buffer of at least WASM_MIN_BUFFER_SIZE bytes, or larger
+            // if the input data was larger
+            let capacity = std::cmp::max(input_data.len(), flowcore::WASM_MIN_BUFFER_SIZE);
+            if return_data.len() > capacity {
+                return_data = encode_error(format!("Result of {} bytes exceeds the buffer capacity of {capacity} bytes",
+                                                   return_data.len()));
+            }
 
-            let return_data = serde_json::to_vec(&result).unwrap();
+            if input_data_ptr.is_null() {
+                return 0;
+            }
 
-            unsafe { copy(return_data.as_ptr(), input_data_ptr as *mut u8, return_data.len()); }
+            unsafe { copy_nonoverlapping(return_data.as_ptr(), input_data_ptr as *mut u8, return_data.len()); }
 
-            return_data.len() as i32
+            i32::try_from(return_data.len()).unwrap_or(0)
         }
     };
 
@@ -189,9 +289,10 @@ fn generate_code(function_implementation: TokenStream,
         use flowcore::Implementation;
         impl Implementation for #struct_name {
             fn run(&self, inputs: &[Value]) -> flowcore::errors::Result<(Option<Value>, flowcore::RunAgain)> {
-//                #input_conversion
                 #input_number_check
 
+                #input_conversion
+
                 #implementation_name(#input_list)
             }
         }
@@ -226,4 +327,68 @@ fn find_definition_filename(attributes: TokenStream) -> String {
 
     panic!("the 'flow' macro must include the ´definition' attribute")
 }
- */
\ No newline at end of file
+ */
+
+#[cfg(test)]
+mod test {
+    use syn::Type;
+
+    use super::type_matches;
+
+    fn matches(datatype: &str, implemented_type: &str) -> bool {
+        let implemented_type: Type = syn::parse_str(implemented_type)
+            .expect("Could not parse type");
+        type_matches(datatype, &implemented_type)
+    }
+
+    #[test]
+    fn value_matches_any_type() {
+        for datatype in ["", "string", "number", "boolean", "array/number", "object", "null"] {
+            assert!(matches(datatype, "Value"));
+            assert!(matches(datatype, "serde_json::Value"));
+        }
+    }
+
+    #[test]
+    fn primitive_types() {
+        assert!(matches("string", "String"));
+        assert!(matches("boolean", "bool"));
+        assert!(matches("number", "i64"));
+        assert!(matches("number", "f64"));
+        assert!(!matches("number", "String"));
+        assert!(!matches("string", "i32"));
+        assert!(!matches("", "String"));
+    }
+
+    #[test]
+    fn bytes_type() {
+        assert!(matches("bytes", "Bytes"));
+        assert!(matches("array/bytes", "Vec<Bytes>"));
+        assert!(!matches("string", "Bytes"));
+        assert!(!matches("bytes", "String"));
+    }
+
+    #[test]
+    fn array_types() {
+        assert!(matches("array/number", "Vec<u32>"));
+        assert!(matches("array/array/string", "Vec<Vec<String>>"));
+        assert!(matches("array", "Vec<Value>"));
+        assert!(!matches("array/number", "Vec<String>"));
+        assert!(!matches("number", "Vec<i64>"));
+    }
+
+    #[test]
+    fn object_types() {
+        assert!(matches("object", "Map<String, Value>"));
+        assert!(matches("object/number", "HashMap<String, i64>"));
+        assert!(!matches("object/number", "HashMap<String, bool>"));
+        assert!(!matches("object", "HashMap<String, String>"));
+    }
+
+    #[test]
+    fn option_types() {
+        assert!(matches("null", "Option<String>"));
+        assert!(matches("string", "Option<String>"));
+        assert!(!matches("number", "Option<String>"));
+    }
+}
@@ -0,0 +1,46 @@
+#![allow(missing_docs)]
+
+//! Test the `flow_function` macro wrapping an implementation function whose parameters are
+//! concrete types, with inputs converted from the `Value`s passed to `run()`
+
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_typed_inputs(name: String, times: usize, suffixes: Vec<String>, shout: Option<bool>)
+    -> Result<(Option<Value>, RunAgain)> {
+    let mut output = format!("{}{}", name.repeat(times), suffixes.concat());
+    if shout.unwrap_or(false) {
+        output = output.to_uppercase();
+    }
+    Ok((Some(json!(output)), RUN_AGAIN))
+}
+
+#[test]
+fn inputs_are_converted() {
+    let (result, _) = TypedInputs.run(&[json!("ab"), json!(2), json!(["c", "d"]), json!(null)])
+        .expect("run() failed");
+    assert_eq!(result, Some(json!("ababcd")));
+}
+
+#[test]
+fn optional_input_is_converted() {
+    let (result, _) = TypedInputs.run(&[json!("ab"), json!(1), json!([]), json!(true)])
+        .expect("run() failed");
+    assert_eq!(result, Some(json!("AB")));
+}
+
+#[test]
+fn input_of_wrong_type_is_an_error() {
+    let error = TypedInputs.run(&[json!("ab"), json!(-1), json!([]), json!(null)])
+        .expect_err("run() should fail for a negative 'usize'");
+    assert!(error.to_string().starts_with("Could not convert input #1 to 'usize'"));
+}
+
+#[test]
+fn wrong_number_of_inputs_is_an_error() {
+    assert!(TypedInputs.run(&[json!("ab"), json!(1)]).is_err());
+}
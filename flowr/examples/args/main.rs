@@ -6,15 +6,8 @@ fn main() {
 
 #[cfg(test)]
 mod test {
-    use std::env;
-    use std::path::PathBuf;
-
     #[test]
     fn test_args_example() {
-        let _ = env::set_current_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .parent().expect("Could not cd into flow directory"));
-
-        super::main();
-        utilities::check_test_output(file!());
+        utilities::test_example(file!(), "flowrcli", false, true);
     }
 }
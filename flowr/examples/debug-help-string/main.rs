@@ -15,7 +15,7 @@ mod test {
         let _ = env::set_current_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .parent().expect("Could not cd into flow directory"));
 
-        super::main();
-        utilities::check_test_output(file!());
+        let output_dir = utilities::run_example(file!(), "flowrcli", false, true);
+        utilities::check_test_output(file!(), &output_dir);
     }
 }
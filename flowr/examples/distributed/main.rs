@@ -0,0 +1,19 @@
+//! A runner for the example, distributing its jobs across two `flowrex` workers
+fn main() {
+    let run = utilities::distributed::run_example_distributed(file!(), 2);
+    println!("{run}");
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_distributed_example() {
+        utilities::distributed::test_example_distributed(file!(), 2);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_distributed_mandlebrot_example() {
+        utilities::distributed::test_example_distributed("flowr/examples/mandlebrot/main.rs", 3);
+    }
+}
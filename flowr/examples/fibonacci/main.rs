@@ -1,5 +1,4 @@
 //! A runner for the example using flowrcli
-extern crate serial_test;
 
 fn main() {
     utilities::run_example(file!(), "flowrcli",
@@ -8,17 +7,8 @@ fn main() {
 
 #[cfg(test)]
 mod test {
-    use std::env;
-    use std::path::PathBuf;
-    use serial_test::serial;
-
     #[test]
-    #[serial]
     fn test_fibonacci_example() {
-        let _ = env::set_current_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .parent().expect("Could not cd into flow directory"));
-
-        super::main();
-        utilities::check_test_output(file!());
+        utilities::test_example(file!(), "flowrcli", false, true);
     }
 }
@@ -0,0 +1,124 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use flowcore::errors::{bail, Result, ResultExt};
+
+/// The environment variable with the `identity=token` entries (separated by ',') of the clients
+/// allowed to submit flows, used by a coordinator when no tokens file is given
+pub const CLIENT_TOKENS_ENV_VAR: &str = "FLOW_CLIENT_TOKENS";
+
+/// The environment variable with the token a client presents, used when no token file is given
+pub const CLIENT_TOKEN_ENV_VAR: &str = "FLOW_CLIENT_TOKEN";
+
+/// The tokens that clients must present to a coordinator to submit flows to it, each with the
+/// identity of the client it belongs to
+pub struct ClientTokens {
+    // (identity, token) of each client
+    clients: Vec<(String, String)>,
+}
+
+impl ClientTokens {
+    /// Load the tokens from the file at `path`, that has one `identity=token` entry per line.
+    /// Blank lines and lines starting with '#' are ignored
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("Could not read client tokens file '{}'", path.display()))?;
+        Self::parse(contents.lines())
+    }
+
+    /// Get the tokens from the `FLOW_CLIENT_TOKENS` environment variable, if it is set
+    pub fn from_env() -> Result<Option<Self>> {
+        match env::var(CLIENT_TOKENS_ENV_VAR) {
+            Ok(entries) => Self::parse(entries.split(',')).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Parse the `identity=token` entries, ignoring blank ones and comments
+    pub(crate) fn parse<'a>(entries: impl Iterator<Item = &'a str>) -> Result<Self> {
+        let mut clients = vec![];
+        for entry in entries.map(str::trim).filter(|entry| !entry.is_empty() && !entry.starts_with('#')) {
+            match entry.split_once('=') {
+                Some((identity, token)) if !identity.trim().is_empty() && !token.trim().is_empty() =>
+                    clients.push((identity.trim().to_string(), token.trim().to_string())),
+                _ => bail!("Invalid client token entry '{}', expected 'identity=token'", entry),
+            }
+        }
+
+        if clients.is_empty() {
+            bail!("No client tokens were found, so no client could submit a flow");
+        }
+
+        Ok(ClientTokens { clients })
+    }
+
+    /// Get the identity of the client that `token` belongs to, or None if the client did not
+    /// present a token or the token is not known
+    pub fn identify(&self, token: Option<&str>) -> Option<&str> {
+        let token = token?;
+        self.clients.iter()
+            .find(|(_, known)| constant_time_eq(known.as_bytes(), token.as_bytes()))
+            .map(|(identity, _)| identity.as_str())
+    }
+}
+
+// Compare two byte strings taking the same time wherever they differ, so that the time taken to
+// reject a token does not reveal how much of it was correct
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Read the token a client presents to the coordinator from the file at `path` if given, or else
+/// from the `FLOW_CLIENT_TOKEN` environment variable, if it is set
+pub fn client_token(path: Option<&Path>) -> Result<Option<String>> {
+    match path {
+        Some(path) => fs::read_to_string(path)
+            .map(|token| Some(token.trim().to_string()))
+            .chain_err(|| format!("Could not read client token file '{}'", path.display())),
+        None => Ok(env::var(CLIENT_TOKEN_ENV_VAR).ok()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{client_token, ClientTokens};
+
+    #[test]
+    fn identify_clients() {
+        let tokens = ClientTokens::parse("alice=secret1\n\n# ops team\nbob = secret2\n".lines())
+            .expect("Could not parse tokens");
+
+        assert_eq!(tokens.identify(Some("secret1")), Some("alice"));
+        assert_eq!(tokens.identify(Some("secret2")), Some("bob"));
+        assert_eq!(tokens.identify(Some("secret")), None);
+        assert_eq!(tokens.identify(None), None);
+    }
+
+    #[test]
+    fn invalid_entries_rejected() {
+        assert!(ClientTokens::parse("alice".lines()).is_err());
+        assert!(ClientTokens::parse("=secret".lines()).is_err());
+        assert!(ClientTokens::parse("# no tokens".lines()).is_err());
+    }
+
+    #[test]
+    fn load_tokens_and_token() {
+        let temp = tempdir().expect("Couldn't get temporary directory");
+        let tokens_path = temp.path().join("tokens");
+        fs::write(&tokens_path, "alice=secret1\n").expect("Could not write tokens file");
+        let token_path = temp.path().join("token");
+        fs::write(&token_path, "secret1\n").expect("Could not write token file");
+
+        let tokens = ClientTokens::load(&tokens_path).expect("Could not load tokens");
+        let token = client_token(Some(&token_path)).expect("Could not read token");
+        assert_eq!(tokens.identify(token.as_deref()), Some("alice"));
+    }
+}
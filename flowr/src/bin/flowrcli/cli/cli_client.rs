@@ -1,8 +1,11 @@
 use std::collections::HashMap;
-use std::fs::File;
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
 use std::io;
+use std::io::IsTerminal;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use image::{ImageBuffer, ImageFormat, Rgb, RgbImage};
@@ -11,19 +14,29 @@ use log::debug;
 use log::error;
 #[cfg(feature = "debugger")]
 use log::info;
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
 
 use flowcore::errors::Result;
+use flowrlib::kv_store::KvStore;
+use flowrlib::process;
+use flowrlib::tcp;
 
 use crate::cli::connections::ClientConnection;
-use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+use crate::cli::coordinator_message::{ClientMessage, Color, CoordinatorMessage, FileMetaData, StyledText};
 
 const DEFAULT_NAME : &str = "unknown";
 
-#[derive(Debug, Clone)]
+// The file, in the `.flow` directory of the user's home directory, where lines read are saved
+const HISTORY_FILENAME: &str = "history";
+
 pub struct CliRuntimeClient {
     args: Vec<String>,
     override_args: Arc<Mutex<Vec<String>>>,
+    allowed_env_vars: Vec<String>,
+    allow_exec: bool,
     image_buffers: HashMap<String, ImageBuffer<Rgb<u8>, Vec<u8>>>,
+    editor: Option<DefaultEditor>,
     #[cfg(feature = "metrics")] display_metrics: bool,
 }
 
@@ -31,11 +44,16 @@ impl CliRuntimeClient {
     /// Create a new runtime client
     pub fn new(args: Vec<String>,
                override_args: Arc<Mutex<Vec<String>>>,
+               allowed_env_vars: Vec<String>,
+               allow_exec: bool,
                #[cfg(feature = "metrics")] display_metrics: bool) -> Self {
         CliRuntimeClient {
             args,
             override_args,
+            allowed_env_vars,
+            allow_exec,
             image_buffers: HashMap::<String, ImageBuffer<Rgb<u8>, Vec<u8>>>::new(),
+            editor: None,
             #[cfg(feature = "metrics")] display_metrics,
         }
     }
@@ -65,6 +83,39 @@ impl CliRuntimeClient {
         }
     }
 
+    // Read a line from the terminal with line editing, and history of previous lines that is
+    // saved in the user's `~/.flow` directory, so it is available next time a flow is run
+    fn edit_line(&mut self, prompt: &str) -> ClientMessage {
+        let mut editor = match self.editor.take() {
+            Some(editor) => editor,
+            None => match DefaultEditor::new() {
+                Ok(mut editor) => {
+                    if let Some(path) = history_path() {
+                        // Ignore error on first use due to no previous history existing
+                        let _ = editor.load_history(&path);
+                    }
+                    editor
+                }
+                Err(e) => return ClientMessage::Error(format!("Could not create line editor: '{e}'")),
+            },
+        };
+
+        let response = match editor.readline(prompt) {
+            Ok(line) => {
+                let line = line.trim().to_string();
+                if !line.is_empty() && editor.add_history_entry(&line).unwrap_or(false) {
+                    save_history(&mut editor);
+                }
+                ClientMessage::Line(line)
+            }
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => ClientMessage::GetLineEof,
+            Err(e) => ClientMessage::Error(format!("Could not read line: '{e}'")),
+        };
+
+        self.editor = Some(editor);
+        response
+    }
+
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::many_single_char_names)]
     fn process_coordinator_message(&mut self, message: CoordinatorMessage) -> ClientMessage {
@@ -87,6 +138,25 @@ impl CliRuntimeClient {
                 self.flush_image_buffers();
                 ClientMessage::ClientExiting(Ok(()))
             }
+
+            #[cfg(feature = "metrics")]
+            CoordinatorMessage::FlowCancelled(metrics) => {
+                eprintln!("Flow execution was cancelled");
+                if self.display_metrics {
+                    println!("\nMetrics: \n{metrics}");
+                    let _ = io::stdout().flush();
+                }
+
+                self.flush_image_buffers();
+                ClientMessage::ClientExiting(Ok(()))
+            }
+
+            #[cfg(not(feature = "metrics"))]
+            CoordinatorMessage::FlowCancelled => {
+                eprintln!("Flow execution was cancelled");
+                self.flush_image_buffers();
+                ClientMessage::ClientExiting(Ok(()))
+            }
             CoordinatorMessage::FlowStart => {
                 debug!("===========================    Starting flow execution =============================");
                 ClientMessage::Ack
@@ -95,6 +165,10 @@ impl CliRuntimeClient {
                 debug!("Coordinator is exiting");
                 ClientMessage::ClientExiting(result)
             }
+            CoordinatorMessage::SubmissionRejected(reason) => {
+                debug!("Coordinator rejected the submission");
+                ClientMessage::ClientExiting(Err(format!("Submission rejected: {reason}").into()))
+            }
             CoordinatorMessage::StdoutEof => ClientMessage::Ack,
             CoordinatorMessage::Stdout(contents) => {
                 let stdout = io::stdout();
@@ -103,6 +177,18 @@ impl CliRuntimeClient {
                 let _ = io::stdout().flush();
                 ClientMessage::Ack
             }
+            CoordinatorMessage::StdoutStyled(spans) => {
+                let contents = if io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none() {
+                    ansi_styled(&spans)
+                } else {
+                    spans.into_iter().map(|span| span.text).collect()
+                };
+                let stdout = io::stdout();
+                let mut handle = stdout.lock();
+                let _ = handle.write_all(format!("{contents}\n").as_bytes());
+                let _ = io::stdout().flush();
+                ClientMessage::Ack
+            }
             CoordinatorMessage::StderrEof => ClientMessage::Ack,
             CoordinatorMessage::Stderr(contents) => {
                 let stderr = io::stderr();
@@ -123,23 +209,17 @@ impl CliRuntimeClient {
                 ClientMessage::Error("Could not read Stdin".into())
             }
             CoordinatorMessage::GetLine(prompt) => {
-                let mut input = String::new();
-                if !prompt.is_empty() {
-                    print!("{prompt}");
-                    let _ = io::stdout().flush();
-                }
-                let line = io::stdin().lock().read_line(&mut input);
-                match line {
-                    Ok(n) if n > 0 => ClientMessage::Line(input.trim().to_string()),
-                    Ok(0) => ClientMessage::GetLineEof,
-                    _ => ClientMessage::Error("Could not read Readline".into()),
+                if io::stdin().is_terminal() {
+                    self.edit_line(&prompt)
+                } else {
+                    read_line(&prompt)
                 }
             }
             CoordinatorMessage::Read(file_path) => match File::open(&file_path) {
                 Ok(mut f) => {
                     let mut buffer = Vec::new();
                     match f.read_to_end(&mut buffer) {
-                        Ok(_) => ClientMessage::FileContents(file_path, buffer),
+                        Ok(_) => ClientMessage::FileContents(file_path, buffer.into()),
                         Err(_) => ClientMessage::Error(format!(
                             "Could not read content from '{file_path:?}'"
                         )),
@@ -148,7 +228,7 @@ impl CliRuntimeClient {
                 Err(_) => ClientMessage::Error(format!("Could not open file '{file_path:?}'")),
             },
             CoordinatorMessage::Write(filename, bytes) => match File::create(&filename) {
-                Ok(mut file) => match file.write_all(bytes.as_slice()) {
+                Ok(mut file) => match file.write_all(&bytes) {
                     Ok(()) => ClientMessage::Ack,
                     Err(e) => {
                         let msg = format!("Error writing to file: '{filename}': '{e}'");
@@ -162,6 +242,51 @@ impl CliRuntimeClient {
                     ClientMessage::Error(msg)
                 }
             },
+            CoordinatorMessage::Append(filename, bytes) => match OpenOptions::new()
+                .append(true).open(&filename) {
+                Ok(mut file) => match file.write_all(&bytes) {
+                    Ok(()) => ClientMessage::Ack,
+                    Err(e) => {
+                        let msg = format!("Error appending to file: '{filename}': '{e}'");
+                        error!("{msg}");
+                        ClientMessage::Error(msg)
+                    }
+                },
+                Err(e) => {
+                    let msg = format!("Error opening file: '{filename}': '{e}'");
+                    error!("{msg}");
+                    ClientMessage::Error(msg)
+                }
+            },
+            CoordinatorMessage::Remove(filename) => match fs::remove_file(&filename) {
+                Ok(()) => ClientMessage::Ack,
+                Err(e) => {
+                    let msg = format!("Error removing file: '{filename}': '{e}'");
+                    error!("{msg}");
+                    ClientMessage::Error(msg)
+                }
+            },
+            CoordinatorMessage::List(path) => match fs::read_dir(&path) {
+                Ok(entries) => {
+                    let mut paths: Vec<String> = entries.flatten()
+                        .map(|entry| entry.path().display().to_string())
+                        .collect();
+                    paths.sort();
+                    ClientMessage::DirectoryEntries(paths)
+                }
+                Err(e) => ClientMessage::Error(format!("Could not list directory '{path}': '{e}'")),
+            },
+            CoordinatorMessage::Stat(path) => match fs::metadata(&path) {
+                Ok(metadata) => ClientMessage::Metadata(FileMetaData::from(&metadata)),
+                Err(e) => ClientMessage::Error(format!("Could not get metadata of '{path}': '{e}'")),
+            },
+            CoordinatorMessage::ImageRead(filename) => match image::open(&filename) {
+                Ok(image) => {
+                    let image = image.to_rgb8();
+                    ClientMessage::Image(image.width(), image.height(), image.into_raw())
+                }
+                Err(e) => ClientMessage::Error(format!("Could not read image '{filename}': '{e}'")),
+            },
             #[allow(clippy::many_single_char_names)]
             CoordinatorMessage::PixelWrite((x, y), (r, g, b), (width, height), name)
             => {
@@ -188,32 +313,159 @@ impl CliRuntimeClient {
                     ClientMessage::Args(self.args.clone())
                 }
             },
+            CoordinatorMessage::GetEnvVar(name) => {
+                if self.allowed_env_vars.contains(&name) {
+                    ClientMessage::EnvVar(env::var(&name).ok())
+                } else {
+                    ClientMessage::Error(format!("The flow is not allowed to read environment \
+                        variable '{name}', allow it using '--allow-env {name}'"))
+                }
+            },
+            CoordinatorMessage::GetEnvVars => ClientMessage::EnvVars(env::vars()
+                .filter(|(name, _)| self.allowed_env_vars.contains(name))
+                .collect()),
+            CoordinatorMessage::KvGet(key) => match KvStore::open()
+                .and_then(|store| store.get(&key)) {
+                Ok(value) => ClientMessage::KvValue(value),
+                Err(e) => ClientMessage::Error(e.to_string()),
+            },
+            CoordinatorMessage::KvPut(key, value) => match KvStore::open()
+                .and_then(|store| store.put(key, value)) {
+                Ok(()) => ClientMessage::Ack,
+                Err(e) => ClientMessage::Error(e.to_string()),
+            },
+            CoordinatorMessage::KvDelete(key) => match KvStore::open()
+                .and_then(|store| store.delete(&key)) {
+                Ok(value) => ClientMessage::KvValue(value),
+                Err(e) => ClientMessage::Error(e.to_string()),
+            },
+            CoordinatorMessage::Exec(command, args, stdin) => {
+                if self.allow_exec {
+                    match process::exec(&command, &args, stdin) {
+                        Ok((code, stdout, stderr)) => ClientMessage::ProcessOutput(code, stdout, stderr),
+                        Err(e) => ClientMessage::Error(e.to_string()),
+                    }
+                } else {
+                    ClientMessage::Error(format!("The flow is not allowed to run command \
+                        '{command}', allow it using '--allow-exec'"))
+                }
+            },
+            CoordinatorMessage::TcpSend(address, bytes, await_response) =>
+                match tcp::send(&address, &bytes, await_response) {
+                    Ok(Some(response)) => ClientMessage::TcpResponse(response),
+                    Ok(None) => ClientMessage::Ack,
+                    Err(e) => ClientMessage::Error(e.to_string()),
+                },
             CoordinatorMessage::Invalid => ClientMessage::Ack,
         }
     }
 }
 
+// Render spans of styled text as a String with the ANSI escape sequences to display them in a terminal
+fn ansi_styled(spans: &[StyledText]) -> String {
+    let mut styled = String::new();
+    for span in spans {
+        let mut codes = vec![];
+        if span.bold {
+            codes.push(1);
+        }
+        if span.italic {
+            codes.push(3);
+        }
+        if span.underline {
+            codes.push(4);
+        }
+        if let Some(color) = span.color {
+            codes.push(30 + ansi_color(color));
+        }
+        if let Some(background) = span.background {
+            codes.push(40 + ansi_color(background));
+        }
+
+        if codes.is_empty() {
+            styled.push_str(&span.text);
+        } else {
+            let codes: Vec<String> = codes.iter().map(ToString::to_string).collect();
+            styled.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.text));
+        }
+    }
+    styled
+}
+
+// The offset of a color in the ANSI foreground (30-37) and background (40-47) color codes
+fn ansi_color(color: Color) -> u8 {
+    match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::White => 7,
+    }
+}
+
+// Read a line from stdin when it is not a terminal, e.g. when input is piped into the runner
+fn read_line(prompt: &str) -> ClientMessage {
+    let mut input = String::new();
+    if !prompt.is_empty() {
+        print!("{prompt}");
+        let _ = io::stdout().flush();
+    }
+    let line = io::stdin().lock().read_line(&mut input);
+    match line {
+        Ok(n) if n > 0 => ClientMessage::Line(input.trim().to_string()),
+        Ok(0) => ClientMessage::GetLineEof,
+        _ => ClientMessage::Error("Could not read Readline".into()),
+    }
+}
+
+// Save the history of lines read, creating the `~/.flow` directory if it doesn't exist yet
+fn save_history(editor: &mut DefaultEditor) {
+    if let Some(path) = history_path() {
+        if let Some(history_dir) = path.parent() {
+            let _ = fs::create_dir_all(history_dir);
+        }
+        if let Err(e) = editor.save_history(&path) {
+            debug!("Could not save history to '{}': '{e}'", path.display());
+        }
+    }
+}
+
+// The path of the file where the history of lines read is saved, if the user's home directory
+// is known
+fn history_path() -> Option<PathBuf> {
+    env::var("HOME").ok()
+        .map(|home_dir| Path::new(&home_dir).join(".flow").join(HISTORY_FILENAME))
+}
+
 #[cfg(test)]
 mod test {
     use std::fs;
     use std::fs::File;
+    use std::io::BufReader;
     use std::io::prelude::*;
+    use std::net::TcpListener;
     use std::sync::{Arc, Mutex};
+    use std::thread;
 
     use tempfile::tempdir;
 
     #[cfg(feature = "metrics")]
     use flowcore::model::metrics::Metrics;
 
-    use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+    use crate::cli::coordinator_message::{ClientMessage, Color, CoordinatorMessage, StyledText};
 
-    use super::CliRuntimeClient;
+    use super::{ansi_styled, CliRuntimeClient};
 
     #[test]
     fn test_arg_passing() {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string(), "1".to_string()],
             Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")]
             false,
         );
@@ -233,6 +485,8 @@ mod test {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string(), "1".to_string()],
             override_args.clone(),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")]
                 false,
         );
@@ -252,6 +506,156 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_env_var_allowed() {
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!("PATH".to_string()),
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        match client.process_coordinator_message(CoordinatorMessage::GetEnvVar("PATH".into())) {
+            ClientMessage::EnvVar(value) => assert_eq!(value, std::env::var("PATH").ok()),
+            _ => panic!("Didn't get EnvVar response as expected"),
+        }
+
+        match client.process_coordinator_message(CoordinatorMessage::GetEnvVars) {
+            ClientMessage::EnvVars(vars) => assert!(vars.keys().all(|name| name == "PATH")),
+            _ => panic!("Didn't get EnvVars response as expected"),
+        }
+    }
+
+    #[test]
+    fn test_directory_listing_and_stat() {
+        let temp = tempdir().expect("Couldn't get temporary directory");
+        let file_path = temp.path().join("test_stat");
+        fs::write(&file_path, b"12345").expect("Could not write test file");
+        fs::create_dir(temp.path().join("a_dir")).expect("Could not create test dir");
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        match client.process_coordinator_message(
+            CoordinatorMessage::List(temp.path().display().to_string())) {
+            ClientMessage::DirectoryEntries(paths) => assert_eq!(paths, vec!(
+                temp.path().join("a_dir").display().to_string(),
+                file_path.display().to_string())),
+            _ => panic!("Didn't get DirectoryEntries response as expected"),
+        }
+
+        match client.process_coordinator_message(
+            CoordinatorMessage::Stat(file_path.display().to_string())) {
+            ClientMessage::Metadata(metadata) => {
+                assert!(metadata.is_file);
+                assert!(!metadata.is_dir);
+                assert_eq!(metadata.size, 5);
+                assert!(metadata.modified.is_some());
+            }
+            _ => panic!("Didn't get Metadata response as expected"),
+        }
+
+        match client.process_coordinator_message(
+            CoordinatorMessage::Stat(temp.path().join("missing").display().to_string())) {
+            ClientMessage::Error(_) => {},
+            _ => panic!("Getting the metadata of a missing file should be an error"),
+        }
+    }
+
+    #[test]
+    fn test_tcp_send() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let address = listener.local_addr().expect("Could not get address").to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("Could not accept connection");
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream);
+            reader.read_line(&mut request).expect("Could not read request");
+            reader.get_mut().write_all(b"PONG\n").expect("Could not write response");
+            request
+        });
+
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        match client.process_coordinator_message(
+            CoordinatorMessage::TcpSend(address, b"PING\n".to_vec(), true)) {
+            ClientMessage::TcpResponse(response) => assert_eq!(response, b"PONG\n".to_vec()),
+            _ => panic!("Didn't get TcpResponse response as expected"),
+        }
+        assert_eq!(server.join().expect("Could not join server thread"), "PING\n");
+    }
+
+    #[test]
+    fn test_exec_allowed() {
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            true,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        match client.process_coordinator_message(CoordinatorMessage::Exec("cat".into(),
+                                                                         vec!(), "hello".into())) {
+            ClientMessage::ProcessOutput(code, stdout, stderr) => {
+                assert_eq!(code, Some(0));
+                assert_eq!(stdout, "hello");
+                assert!(stderr.is_empty());
+            },
+            _ => panic!("Didn't get ProcessOutput response as expected"),
+        }
+    }
+
+    #[test]
+    fn test_exec_not_allowed() {
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        match client.process_coordinator_message(CoordinatorMessage::Exec("cat".into(),
+                                                                         vec!(), String::new())) {
+            ClientMessage::Error(_) => {},
+            _ => panic!("Running a command when not allowed should be an error"),
+        }
+    }
+
+    #[test]
+    fn test_env_var_not_allowed() {
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        match client.process_coordinator_message(CoordinatorMessage::GetEnvVar("PATH".into())) {
+            ClientMessage::Error(_) => {},
+            _ => panic!("Reading an environment variable not allowed should be an error"),
+        }
+    }
+
     #[test]
     fn test_file_reading() {
         let test_contents = b"The quick brown fox jumped over the lazy dog";
@@ -268,6 +672,8 @@ mod test {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string()],
             Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")]
             false,
         );
@@ -275,7 +681,7 @@ mod test {
         match client.process_coordinator_message(CoordinatorMessage::Read(file_path.clone())) {
             ClientMessage::FileContents(path_read, contents) => {
                 assert_eq!(path_read, file_path);
-                assert_eq!(contents, test_contents);
+                assert_eq!(&*contents, test_contents);
             }
             _ => panic!("Didn't get Read response as expected"),
         }
@@ -291,16 +697,93 @@ mod test {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string()],
             Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")]
             false,
         );
 
         match client.process_coordinator_message(CoordinatorMessage::Write(
             file.to_str().expect("Couldn't get filename").to_string(),
-            b"Hello".to_vec())) {
+            b"Hello".to_vec().into())) {
+            ClientMessage::Ack => {},
+            _ => panic!("Didn't get Write response as expected"),
+        }
+    }
+
+    #[test]
+    fn test_file_bytes_round_trip() {
+        let contents = vec![0, 128, 255, b'\n'];
+        let temp = tempdir()
+            .expect("Couldn't get temporary directory")
+            .into_path();
+        let filename = temp.join("test").to_str().expect("Couldn't get filename").to_string();
+
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        // send the messages as they are sent between coordinator and client
+        let write: String = CoordinatorMessage::Write(filename.clone(), contents.clone().into()).into();
+        match client.process_coordinator_message(write.into()) {
+            ClientMessage::Ack => {},
+            _ => panic!("Didn't get Write response as expected"),
+        }
+
+        let read: String = CoordinatorMessage::Read(filename).into();
+        let response: String = client.process_coordinator_message(read.into()).into();
+        match ClientMessage::from(response) {
+            ClientMessage::FileContents(_, bytes) => assert_eq!(bytes.into_vec(), contents),
+            _ => panic!("Didn't get Read response as expected"),
+        }
+    }
+
+    #[test]
+    fn test_file_appending_and_removal() {
+        let temp = tempdir()
+            .expect("Couldn't get temporary directory")
+            .into_path();
+        let file = temp.join("test");
+        let filename = file.to_str().expect("Couldn't get filename").to_string();
+
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+
+        match client.process_coordinator_message(CoordinatorMessage::Append(
+            filename.clone(), b"Hello".to_vec().into())) {
+            ClientMessage::Error(_) => {},
+            _ => panic!("Should not be able to append to a file that doesn't exist"),
+        }
+
+        match client.process_coordinator_message(CoordinatorMessage::Write(
+            filename.clone(), b"Hello".to_vec().into())) {
             ClientMessage::Ack => {},
             _ => panic!("Didn't get Write response as expected"),
         }
+
+        match client.process_coordinator_message(CoordinatorMessage::Append(
+            filename.clone(), b" World".to_vec().into())) {
+            ClientMessage::Ack => {},
+            _ => panic!("Didn't get Append response as expected"),
+        }
+        assert_eq!(fs::read_to_string(&file).expect("Could not read file"), "Hello World");
+
+        match client.process_coordinator_message(CoordinatorMessage::Remove(filename)) {
+            ClientMessage::Ack => {},
+            _ => panic!("Didn't get Remove response as expected"),
+        }
+        assert!(!file.exists());
     }
 
     #[test]
@@ -308,6 +791,8 @@ mod test {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string()],
             Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")]
             false,
         );
@@ -317,11 +802,22 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_ansi_styled() {
+        let spans = vec![
+            StyledText { text: "Error: ".into(), color: Some(Color::Red), bold: true, ..Default::default() },
+            StyledText { text: "file not found".into(), ..Default::default() },
+        ];
+        assert_eq!(ansi_styled(&spans), "\x1b[1;31mError: \x1b[0mfile not found");
+    }
+
     #[test]
     fn test_stderr() {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string()],
             Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")]
             false,
         );
@@ -336,6 +832,8 @@ mod test {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string()],
             Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")]
             false,
         );
@@ -362,6 +860,36 @@ mod test {
         client.process_coordinator_message(CoordinatorMessage::FlowEnd(Metrics::new(1)));
 
         assert!(path.exists(), "Image file was not created");
+
+        match client.process_coordinator_message(
+            CoordinatorMessage::ImageRead(path.display().to_string())) {
+            ClientMessage::Image(width, height, pixels) => {
+                assert_eq!((width, height), (10, 10));
+                assert_eq!(pixels.len(), 10 * 10 * 3);
+                assert_eq!(pixels.get(0..3), Some([255, 200, 20].as_slice()));
+            }
+            _ => panic!("Didn't get Image response as expected"),
+        }
+    }
+
+    #[test]
+    fn flow_cancelled() {
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")] false,
+        );
+
+        #[cfg(not(feature = "metrics"))]
+        let message = CoordinatorMessage::FlowCancelled;
+        #[cfg(feature = "metrics")]
+        let message = CoordinatorMessage::FlowCancelled(Metrics::new(1));
+        match client.process_coordinator_message(message) {
+            ClientMessage::ClientExiting(Ok(())) => {},
+            _ => panic!("Didn't get ClientExiting response as expected"),
+        }
     }
 
     #[test]
@@ -369,6 +897,8 @@ mod test {
         let mut client = CliRuntimeClient::new(
             vec!["file:///test_flow.toml".to_string()],
             Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
             #[cfg(feature = "metrics")] false,
         );
 
@@ -377,4 +907,21 @@ mod test {
             _ => panic!("Didn't get ClientExiting response as expected"),
         }
     }
+
+    #[test]
+    fn submission_rejected() {
+        let mut client = CliRuntimeClient::new(
+            vec!["file:///test_flow.toml".to_string()],
+            Arc::new(Mutex::new(vec!())),
+            vec!(),
+            false,
+            #[cfg(feature = "metrics")] false,
+        );
+
+        let message = CoordinatorMessage::SubmissionRejected("Unknown client token".into());
+        match client.process_coordinator_message(message) {
+            ClientMessage::ClientExiting(Err(_)) => {},
+            _ => panic!("Didn't get ClientExiting with an error as expected"),
+        }
+    }
 }
@@ -4,6 +4,7 @@ use log::error;
 use rustyline::error::ReadlineError;
 use rustyline::history::DefaultHistory;
 use rustyline::{DefaultEditor, Editor};
+use serde_json::Value;
 
 use flowcore::errors::Result;
 use flowcore::model::runtime_function::RuntimeFunction;
@@ -11,8 +12,8 @@ use flowrlib::debug_command::BreakpointSpec;
 use flowrlib::debug_command::DebugCommand;
 use flowrlib::debug_command::DebugCommand::{
     Ack, Breakpoint, Continue, DebugClientStarting, Delete, ExitDebugger, FunctionList, Inspect,
-    InspectBlock, InspectFunction, InspectInput, InspectOutput, List, Modify, RunReset, Step,
-    Validate,
+    InspectBlock, InspectFunction, InspectInput, InspectOutput, List, Modify, RunReset, Set,
+    Step, StepBack, Unwatch, Validate, Watch,
 };
 use flowrlib::run_state::{RunState, State};
 
@@ -22,7 +23,7 @@ use DebugServerMessage::{
     BlockBreakpoint, BlockState, DataBreakpoint, Deadlock, EnteringDebugger, ExecutionEnded,
     ExecutionStarted, ExitingDebugger, FlowUnblockBreakpoint, FunctionStates, Functions,
     InputState, JobCompleted, JobError, Message, OutputState, OverallState, Panic,
-    PriorToSendingJob, Resetting, SendingValue, WaitingForCommand,
+    PriorToSendingJob, Resetting, SendingValue, WaitingForCommand, WatchedValue,
 };
 
 const FLOWR_HISTORY_FILENAME: &str = ".flowr_history";
@@ -44,7 +45,16 @@ const HELP_STRING: &str = "Debugger commands:
 'q' | 'quit'                  - Stop flow execution and exit debugger
 'r' | 'reset' or 'run' {args} - If running already then reset the state, or run the flow with {args}
 's' | 'step' [n]              - Step over the next 'n' jobs (default = 1) then break
+'set' [spec] {value}          - Set the value about to be sent at a breakpoint prior to sending it, or
+                                the values queued at an input to those of a JSON array, using spec:
+                                 - destination_id:input_number
+'sb' | 'back' [n]             - Step back to the state prior to the previous 'n' jobs (default = 1)
+'u' | 'unwatch' {spec} or '*' - Delete the watch matching {spec} or all with '*'
 'v' | 'validate'              - Validate the state of the flow by running a series of checks
+'w' | 'watch' [spec]          - Print the value output on each job completion, without breaking,
+                                or list all watches if no spec:
+                                 - on a function's output by function_id (integer)
+                                 - on an output by source_id/output_route ('source_id/' for default output)
 ";
 
 /*
@@ -199,6 +209,28 @@ impl CliDebugClient {
         }
     }
 
+    // Parse the optional input spec and the JSON value of a 'set' command. A value that is not
+    // valid JSON is taken to be a string
+    fn parse_set_spec(params: Option<Vec<String>>) -> Option<DebugCommand> {
+        let mut params = params.unwrap_or_default();
+        let input = match Self::parse_breakpoint_spec(Some(params.iter().take(1).cloned().collect())) {
+            Some(BreakpointSpec::Input(input)) => {
+                params.remove(0);
+                Some(input)
+            }
+            _ => None,
+        };
+
+        let text = params.join(" ");
+        if text.is_empty() {
+            println!("No value given for 'set' command. Use 'h' or 'help' command for help");
+            return None;
+        }
+
+        let value = serde_json::from_str(&text).unwrap_or(Value::String(text));
+        Some(Set(input, value))
+    }
+
     /*
        Wait for the user to input a valid debugger command then return the corresponding response
        that should be sent to the debug server
@@ -264,7 +296,11 @@ impl CliDebugClient {
                 Some(RunReset)
             }
             "s" | "step" => Some(Step(Self::parse_optional_int(params))),
+            "sb" | "back" => Some(StepBack(Self::parse_optional_int(params))),
+            "set" => Self::parse_set_spec(params),
+            "u" | "unwatch" => Some(Unwatch(Self::parse_breakpoint_spec(params))),
             "v" | "validate" => Some(Validate),
+            "w" | "watch" => Some(Watch(Self::parse_breakpoint_spec(params))),
             _ => {
                 println!("Unknown debugger command '{command}'\n");
                 None
@@ -299,16 +335,33 @@ impl CliDebugClient {
                 destination_name,
                 io_name,
                 input_number,
-            ) => println!(
-                "Data breakpoint: Function #{source_function_id} '{source_function_name}{output_route}' \
-                --{value}-> Function #{destination_id}:{input_number} '{destination_name}'/'{io_name}'",
+                source_location,
+            ) => {
+                println!(
+                    "Data breakpoint: Function #{source_function_id} '{source_function_name}{output_route}' \
+                    --{value}-> Function #{destination_id}:{input_number} '{destination_name}'/'{io_name}'",
+                );
+                if let Some(location) = source_location {
+                    println!("\tConnection defined at: {location}");
+                }
+            }
+            WatchedValue(job_id, function_id, function_name, output_route, value) => println!(
+                "Watch: Job #{job_id} Function #{function_id} '{function_name}{output_route}' = {value}",
             ),
             Panic(message, jobs_created) => {
                 println!("Function panicked after {jobs_created} jobs created: {message}");
                 return self.get_user_command(jobs_created);
             }
-            JobError(job) => {
-                println!("Error occurred executing a Job: \n'{job}'");
+            JobError(job, source_location) => {
+                if job.out_of_fuel() {
+                    println!("Job #{} ran out of fuel executing Function #{}: \n'{job}'",
+                             job.payload.job_id, job.function_id);
+                } else {
+                    println!("Error occurred executing a Job: \n'{job}'");
+                }
+                if let Some(location) = source_location {
+                    println!("\tFunction #{} referenced at: {location}", job.function_id);
+                }
                 return self.get_user_command(job.payload.job_id);
             }
             Deadlock(message) => println!("Deadlock detected {message}"),
@@ -361,12 +414,16 @@ impl CliDebugClient {
     fn function_list(functions: Vec<RuntimeFunction>) {
         println!("Functions List");
         for function in functions {
-            println!(
+            print!(
                 "\t#{} '{}' @ '{}'",
                 function.id(),
                 function.name(),
                 function.route()
             );
+            match function.source_location() {
+                Some(location) => println!(" ({location})"),
+                None => println!(),
+            }
         }
         println!("Use 'i n' or 'inspect n' to inspect the function number 'n'");
     }
@@ -412,6 +469,7 @@ mod test {
     use serde_json::json;
 
     use crate::cli::cli_debug_client::CliDebugClient;
+    use flowrlib::debug_command::DebugCommand::Set;
     use flowcore::model::flow_manifest::FlowManifest;
     use flowcore::model::input::Input;
     use flowcore::model::input::InputInitializer::Once;
@@ -482,6 +540,8 @@ mod test {
             test_manifest(functions),
             None,
             None,
+            None,
+            None,
             #[cfg(feature = "debugger")]
                 true,
         )
@@ -495,4 +555,17 @@ mod test {
 
         CliDebugClient::display_state(&state);
     }
+
+    #[test]
+    fn parse_set_spec() {
+        let params = |text: &str| Some(text.split(' ').map(ToString::to_string).collect());
+
+        assert_eq!(CliDebugClient::parse_set_spec(params("42")), Some(Set(None, json!(42))));
+        assert_eq!(CliDebugClient::parse_set_spec(params("hello")),
+                   Some(Set(None, json!("hello"))));
+        assert_eq!(CliDebugClient::parse_set_spec(params("1:0 [1, 2]")),
+                   Some(Set(Some((1, 0)), json!([1, 2]))));
+        assert_eq!(CliDebugClient::parse_set_spec(params("1:0")), None);
+        assert_eq!(CliDebugClient::parse_set_spec(None), None);
+    }
 }
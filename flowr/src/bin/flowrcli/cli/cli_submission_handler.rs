@@ -1,7 +1,7 @@
 use std::sync::{Arc, Mutex};
 
 use error_chain::bail;
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 
 use flowcore::errors::Result;
 #[cfg(feature = "metrics")]
@@ -10,7 +10,7 @@ use flowcore::model::submission::Submission;
 use flowrlib::run_state::RunState;
 use flowrlib::submission_handler::SubmissionHandler;
 
-#[cfg(feature = "debugger")]
+use crate::cli::auth::ClientTokens;
 use crate::cli::connections::{DONT_WAIT, WAIT};
 use crate::cli::coordinator_message::ClientMessage;
 use crate::cli::coordinator_message::CoordinatorMessage;
@@ -19,19 +19,52 @@ use crate::CoordinatorConnection;
 /// A [`SubmissionHandler`] to allow submitting flows for execution from the CLI
 pub(crate) struct CLISubmissionHandler {
     coordinator_connection: Arc<Mutex<CoordinatorConnection>>,
+    // A request from the client that was received while checking for a different one
+    pending_request: Option<ClientMessage>,
+    // The tokens clients must present to submit flows, if clients are authenticated
+    tokens: Option<ClientTokens>,
+    // The identity of the authenticated client whose flow is being executed
+    client: Option<String>,
 }
 
 impl CLISubmissionHandler {
-    /// Create a new Submission handler using the connection provided
-    pub fn new(connection: Arc<Mutex<CoordinatorConnection>>) -> Self {
+    /// Create a new Submission handler using the connection provided. If `tokens` are provided
+    /// then only submissions from clients presenting one of them are accepted
+    pub fn new(connection: Arc<Mutex<CoordinatorConnection>>, tokens: Option<ClientTokens>) -> Self {
         CLISubmissionHandler {
             coordinator_connection: connection,
+            pending_request: None,
+            tokens,
+            client: None,
         }
     }
+
+    // A tag identifying the authenticated client, to add to log output
+    fn client_tag(&self) -> String {
+        self.client.as_ref()
+            .map(|client| format!(" for client '{client}'"))
+            .unwrap_or_default()
+    }
+
+    // Get a request sent by the client, without waiting for one. A request received earlier
+    // while checking for a different one is returned first.
+    fn check_for_request(&mut self) -> Result<Option<ClientMessage>> {
+        if let Some(request) = self.pending_request.take() {
+            return Ok(Some(request));
+        }
+
+        Ok(self
+            .coordinator_connection
+            .lock()
+            .map_err(|_| "Could not lock coordinator connection")?
+            .receive(DONT_WAIT)
+            .ok())
+    }
 }
 
 impl SubmissionHandler for CLISubmissionHandler {
     fn flow_execution_starting(&mut self) -> Result<()> {
+        info!("Flow execution starting{}", self.client_tag());
         let _ = self.coordinator_connection
             .lock()
             .map_err(|_| "Could not lock coordinator connection")?
@@ -46,40 +79,72 @@ impl SubmissionHandler for CLISubmissionHandler {
     // A different message or Absence of a message returns Ok(false)
     #[cfg(feature = "debugger")]
     fn should_enter_debugger(&mut self) -> Result<bool> {
-        let msg = self
-            .coordinator_connection
-            .lock()
-            .map_err(|_| "Could not lock coordinator connection")?
-            .receive(DONT_WAIT);
-        match msg {
-            Ok(ClientMessage::EnterDebugger) => {
+        match self.check_for_request()? {
+            Some(ClientMessage::EnterDebugger) => {
                 debug!("Got EnterDebugger message");
                 Ok(true)
             }
-            Ok(m) => {
+            Some(ClientMessage::CancelFlow) => {
+                self.pending_request = Some(ClientMessage::CancelFlow);
+                Ok(false)
+            }
+            Some(m) => {
+                debug!("Got {:?} message", m);
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // See if the runtime client has sent a message to request that execution of the flow be
+    // cancelled, if so, return Ok(true).
+    // A different message or Absence of a message returns Ok(false)
+    fn should_cancel(&mut self) -> Result<bool> {
+        match self.check_for_request()? {
+            Some(ClientMessage::CancelFlow) => {
+                info!("Flow execution cancel requested{}", self.client_tag());
+                Ok(true)
+            }
+            Some(ClientMessage::EnterDebugger) => {
+                self.pending_request = Some(ClientMessage::EnterDebugger);
+                Ok(false)
+            }
+            Some(m) => {
                 debug!("Got {:?} message", m);
                 Ok(false)
             }
-            _ => Ok(false),
+            None => Ok(false),
         }
     }
 
     #[cfg(feature = "metrics")]
     fn flow_execution_ended(&mut self, state: &RunState, metrics: Metrics) -> Result<()> {
+        info!("Flow execution ended{}, metrics:\n{}", self.client_tag(), metrics);
+        let message = if state.cancelled() {
+            CoordinatorMessage::FlowCancelled(metrics)
+        } else {
+            CoordinatorMessage::FlowEnd(metrics)
+        };
         self.coordinator_connection
             .lock()
             .map_err(|_| "Could not lock coordinator connection")?
-            .send(CoordinatorMessage::FlowEnd(metrics))?;
+            .send(message)?;
         debug!("{}", state);
         Ok(())
     }
 
     #[cfg(not(feature = "metrics"))]
     fn flow_execution_ended(&mut self, state: &RunState) -> Result<()> {
+        info!("Flow execution ended{}", self.client_tag());
+        let message = if state.cancelled() {
+            CoordinatorMessage::FlowCancelled
+        } else {
+            CoordinatorMessage::FlowEnd
+        };
         self.coordinator_connection
             .lock()
             .map_err(|_| "Could not lock coordinator connection")?
-            .send(CoordinatorMessage::FlowEnd)?;
+            .send(message)?;
         debug!("{}", state);
         Ok(())
     }
@@ -87,17 +152,30 @@ impl SubmissionHandler for CLISubmissionHandler {
     // Loop waiting for one of the following two messages from the client thread:
     //  - `ClientSubmission` with a submission, then return Ok(Some(submission))
     //  - `ClientExiting` then return Ok(None)
+    // If clients are authenticated, a submission from a client that does not present a known
+    // token is rejected, and the loop continues waiting for another one
     fn wait_for_submission(&mut self) -> Result<Option<Submission>> {
         loop {
             info!("Coordinator is waiting to receive a 'Submission'");
             let guard = self.coordinator_connection.lock();
             #[allow(clippy::single_match_else)]
             match guard {
-                Ok(locked) =>  {
+                Ok(mut locked) =>  {
                     let received = locked.receive(WAIT);
                     match received {
-                        Ok(ClientMessage::ClientSubmission(submission)) => {
-                            info!("Coordinator received a submission for execution");
+                        Ok(ClientMessage::ClientSubmission(token, submission)) => {
+                            if let Some(tokens) = &self.tokens {
+                                match tokens.identify(token.as_deref()) {
+                                    Some(identity) => self.client = Some(identity.to_string()),
+                                    None => {
+                                        warn!("Coordinator rejected a submission from an unauthenticated client");
+                                        locked.send(CoordinatorMessage::SubmissionRejected(
+                                            "Client token missing or not known".into()))?;
+                                        continue;
+                                    }
+                                }
+                            }
+                            info!("Coordinator received a submission for execution{}", self.client_tag());
                             trace!("\n{}", submission);
                             return Ok(Some(submission));
                         }
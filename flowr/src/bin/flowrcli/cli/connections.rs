@@ -1,13 +1,26 @@
+use std::collections::VecDeque;
 use std::fmt::Display;
+use std::io::ErrorKind;
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
 use std::time::Duration;
 
 /// This is the message-queue implementation of the Client<-->[Coordinator][flowrlib::coordinator::Coordinator]
 /// communications
-use log::{debug, info, trace};
+use log::{debug, error, info, trace, warn};
 use simpdiscoverylib::{BeaconListener, BeaconSender};
-use zmq::Socket;
+use tungstenite::{Message, WebSocket};
+use tungstenite::error::ProtocolError;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::HeaderValue;
 
 use flowcore::errors::{Result, ResultExt, bail};
+use flowrlib::discovery::{announce_service, ServiceBrowser};
+use flowrlib::security::CurveKeys;
+use flowrlib::services::bind_socket_address;
+use flowrlib::session::{ZmqRequester, ZmqResponder};
 
 /// WAIT for a message to arrive when performing a `receive()`
 pub const WAIT: i32 = 0;
@@ -22,16 +35,79 @@ pub const COORDINATOR_SERVICE_NAME: &str = "runtime._flowr._tcp.local";
 #[cfg(feature = "debugger")]
 pub const DEBUG_SERVICE_NAME: &str = "debug._flowr._tcp.local";
 
-/// Try to discover a particular service by name
+/// The number of the most recent messages sent in a session over a `WebSocket` that are kept,
+/// to be sent again if they were not received when the session is resumed after a disconnection
+pub const RESUME_BUFFER_LENGTH: usize = 1000;
+
+/// The number of attempts a client makes to reconnect to the coordinator and resume its session
+/// after the `WebSocket` connecting them is interrupted, before giving up
+pub const RECONNECT_ATTEMPTS: usize = 10;
+
+/// The time a client waits before each attempt to reconnect to the coordinator
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+// The time to wait for a beacon announcing a service, before checking if it has been
+// discovered using mDNS instead, or between checks if beacons cannot be listened for
+const DISCOVERY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// The header of the coordinator's response to a client reconnecting to resume a session, with the
+// number of messages it has received in the session
+const RECEIVED_HEADER: &str = "flow-received";
+
+// A `WebSocket` accepted from a client, with the session token and count of messages received
+// that it sent if it is reconnecting to resume a session
+type AcceptedSocket = (WebSocket<TcpStream>, Option<(String, usize)>);
+
+/// The transport used to carry messages between a client and the
+/// [Coordinator][flowrlib::coordinator::Coordinator]. Both ends must use the same one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// `ZeroMQ` request/reply sockets
+    Zmq,
+    /// A `WebSocket`, that can be used from browsers and through firewalls that only allow HTTP
+    WebSocket,
+}
+
+/// Try to discover a particular service by name, using the beacons sent for it or its mDNS
+/// announcement, whichever is found first. The beacons are only listened for on IPv4, so if
+/// that is not possible (e.g. on an IPv6-only host) the service is discovered using only mDNS
 pub fn discover_service(discovery_port: u16, name: &str) -> Result<String> {
-    let listener = BeaconListener::new(name.as_bytes(), discovery_port)?;
-    let beacon = listener.wait(None)?;
-    let address = format!("{}:{}", beacon.service_ip, beacon.service_port);
-    Ok(address)
+    let browser = ServiceBrowser::new(discovery_port, name)
+        .map_err(|e| warn!("Could not discover services using mDNS: {e}"))
+        .ok();
+    let listener = match BeaconListener::new(name.as_bytes(), discovery_port) {
+        Ok(listener) => Some(listener),
+        Err(e) if browser.is_some() => {
+            warn!("Could not listen for discovery beacons on port {discovery_port}, \
+                discovering '{name}' using only mDNS: {e}");
+            None
+        }
+        Err(e) => return Err(e.into()),
+    };
+    loop {
+        match &listener {
+            Some(listener) => match listener.wait(Some(DISCOVERY_POLL_INTERVAL)) {
+                Ok(beacon) => return Ok(format!("{}:{}", beacon.service_ip, beacon.service_port)),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {},
+                Err(e) => return Err(e.into()),
+            },
+            None => thread::sleep(DISCOVERY_POLL_INTERVAL),
+        }
+        if let Some(address) = browser.as_ref().and_then(ServiceBrowser::discovered) {
+            return Ok(address);
+        }
+    }
 }
 
-/// Start a background thread that sends out beacons for service discovery by a client every second
-pub fn enable_service_discovery(discovery_port: u16, name: &str, service_port: u16) -> Result<()> {
+/// Start a background thread that sends out beacons for service discovery by a client every
+/// second, and announce the service using mDNS so it can also be discovered by standard tools.
+/// If the service is bound to a specific `address`, only that address is announced using mDNS
+pub fn enable_service_discovery(discovery_port: u16, name: &str, service_port: u16,
+                                address: Option<IpAddr>) -> Result<()> {
+    if let Err(e) = announce_service(discovery_port, name, service_port, address) {
+        warn!("Could not announce service '{name}' using mDNS: {e}");
+    }
+
     match BeaconSender::new(service_port, name.as_bytes(), discovery_port) {
         Ok(beacon) => {
             info!(
@@ -48,27 +124,202 @@ pub fn enable_service_discovery(discovery_port: u16, name: &str, service_port: u
     Ok(())
 }
 
+// Encryption is only supported by the `Zmq` transport
+fn check_unencrypted(keys: Option<&CurveKeys>) -> Result<()> {
+    if keys.is_some() {
+        bail!("Encryption with a key file is not supported with the WebSocket transport");
+    }
+    Ok(())
+}
+
+// Read the next text message from a `WebSocket`, skipping control messages.
+// Returns Ok(None) if the other end has closed the connection
+fn read_websocket(socket: &mut WebSocket<TcpStream>) -> Result<Option<String>> {
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => return Ok(Some(text)),
+            Ok(Message::Binary(bytes)) => return Ok(Some(String::from_utf8(bytes)
+                .chain_err(|| "WebSocket message was not valid UTF-8")?)),
+            Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed |
+                                        tungstenite::Error::AlreadyClosed |
+                                        tungstenite::Error::Protocol(
+                                            ProtocolError::ResetWithoutClosingHandshake)) =>
+                return Ok(None),
+            Err(tungstenite::Error::Io(e)) if matches!(e.kind(),
+                ErrorKind::ConnectionReset | ErrorKind::BrokenPipe | ErrorKind::UnexpectedEof) =>
+                return Ok(None),
+            Ok(_) => {},
+            Err(e) => bail!("WebSocket error: '{}'", e),
+        }
+    }
+}
+
+// A session between a client and the coordinator over a `WebSocket`, identified by a token
+// chosen by the client. Each end counts the messages it sent and received, and keeps the most
+// recent ones sent, so that when a client reconnects after an interruption (using the same token)
+// both ends can send again the messages the other did not receive, and continue where they left off
+struct Session {
+    token: String,
+    sent: usize,
+    received: usize,
+    recently_sent: VecDeque<String>,
+}
+
+impl Session {
+    fn new(token: String) -> Self {
+        Session {
+            token,
+            sent: 0,
+            received: 0,
+            recently_sent: VecDeque::new(),
+        }
+    }
+
+    fn record_sent(&mut self, message: &str) {
+        self.sent += 1;
+        self.recently_sent.push_back(message.to_string());
+        if self.recently_sent.len() > RESUME_BUFFER_LENGTH {
+            self.recently_sent.pop_front();
+        }
+    }
+
+    // The messages sent that were not received by the other end, which has received `received`
+    fn undelivered(&self, received: usize) -> Result<Vec<String>> {
+        let missing = self.sent.checked_sub(received)
+            .ok_or("Other end received more messages than were sent in the session")?;
+        let skip = self.recently_sent.len().checked_sub(missing)
+            .ok_or("Messages not received are no longer kept to be sent again")?;
+        Ok(self.recently_sent.iter().skip(skip).cloned().collect())
+    }
+}
+
+// Parse the session token and the number of messages received from the query of the url a
+// client connected to, of the form `?session=<token>&received=<count>`
+fn parse_resume(query: Option<&str>) -> Option<(String, usize)> {
+    let mut token = None;
+    let mut received = None;
+    for (name, value) in query?.split('&').filter_map(|pair| pair.split_once('=')) {
+        match name {
+            "session" => token = Some(value.to_string()),
+            "received" => received = value.parse().ok(),
+            _ => {}
+        }
+    }
+    Some((token?, received?))
+}
+
+// Connect to a coordinator using a `WebSocket` for `session`. If the coordinator resumes the
+// session, the number of messages it received in it is also returned
+fn connect_websocket(coordinator_address: &str, session: &Session)
+    -> Result<(WebSocket<TcpStream>, Option<usize>)> {
+    let stream = TcpStream::connect(coordinator_address)
+        .chain_err(|| format!("Client Connection - Could not connect to: {coordinator_address}"))?;
+    let url = format!("ws://{coordinator_address}/?session={}&received={}",
+                      session.token, session.received);
+    let (socket, response) = tungstenite::client(url, stream)
+        .map_err(|e| format!("Client Connection - WebSocket handshake failed: {e}"))?;
+    let received = response.headers().get(RECEIVED_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    Ok((socket, received))
+}
+
+// The client side of a `WebSocket` transport, that reconnects to the coordinator and resumes its
+// session if the connection is interrupted
+struct ClientWebSocket {
+    coordinator_address: String,
+    socket: WebSocket<TcpStream>,
+    session: Session,
+}
+
+impl ClientWebSocket {
+    fn connect(coordinator_address: &str) -> Result<Self> {
+        let session = Session::new(format!("{:016x}", rand::random::<u64>()));
+        let (socket, _) = connect_websocket(coordinator_address, &session)?;
+        Ok(ClientWebSocket {
+            coordinator_address: coordinator_address.to_string(),
+            socket,
+            session,
+        })
+    }
+
+    fn receive(&mut self) -> Result<String> {
+        loop {
+            match read_websocket(&mut self.socket)? {
+                Some(message) => {
+                    self.session.received += 1;
+                    return Ok(message);
+                }
+                None => self.reconnect()?,
+            }
+        }
+    }
+
+    fn send(&mut self, message: String) -> Result<()> {
+        self.session.record_sent(&message);
+        if let Err(e) = self.socket.send(Message::Text(message)) {
+            debug!("Error sending to coordinator: '{e}'");
+            // the message will be sent again when the session is resumed
+            self.reconnect()?;
+        }
+        Ok(())
+    }
+
+    // Reconnect to the coordinator and resume the session, sending again the messages that the
+    // coordinator did not receive
+    fn reconnect(&mut self) -> Result<()> {
+        warn!("Connection to coordinator at '{}' was interrupted, reconnecting",
+            self.coordinator_address);
+        for attempt in 1..=RECONNECT_ATTEMPTS {
+            thread::sleep(RECONNECT_DELAY);
+            match connect_websocket(&self.coordinator_address, &self.session) {
+                Ok((socket, Some(received))) => {
+                    self.socket = socket;
+                    for message in self.session.undelivered(received)? {
+                        self.socket.send(Message::Text(message))
+                            .chain_err(|| "Error sending to coordinator")?;
+                    }
+                    info!("Client resumed session with coordinator after {attempt} attempt(s)");
+                    return Ok(());
+                }
+                Ok((_, None)) => bail!("Coordinator at '{}' could not resume the session",
+                    self.coordinator_address),
+                Err(e) => debug!("Attempt {attempt} to reconnect failed: {e}"),
+            }
+        }
+        bail!("Could not reconnect to coordinator at '{}'", self.coordinator_address)
+    }
+}
+
+enum ClientSocket {
+    Zmq(Box<Mutex<ZmqRequester>>),
+    WebSocket(Box<Mutex<ClientWebSocket>>),
+    Channel(Sender<String>, Receiver<String>),
+}
+
 /// `ClientConnection` stores information related to the connection from a client
 /// to the [Coordinator][flowrlib::coordinator::Coordinator] and is used each time a message is to
-/// be sent or received.
+/// be sent or received. If the connection is interrupted the client reconnects and resumes its
+/// session, so no messages are lost in either direction.
 pub struct ClientConnection {
-    requester: Socket,
+    requester: ClientSocket,
 }
 
 impl ClientConnection {
-    /// Create a new connection between client and [Coordinator][flowrlib::coordinator::Coordinator]
-    pub fn new(coordinator_address: &str) -> Result<Self> {
-        info!("Client will attempt to connect to coordinator at: '{coordinator_address}'");
-
-        let context = zmq::Context::new();
-
-        let requester = context
-            .socket(zmq::REQ)
-            .chain_err(|| "Client could not connect to coordinator service")?;
-
-        requester
-            .connect(&format!("tcp://{coordinator_address}"))
-            .chain_err(|| format!("Client Connection - Could not connect to socket at: {coordinator_address}"))?;
+    /// Create a new connection between client and [Coordinator][flowrlib::coordinator::Coordinator].
+    /// If `keys` are provided the connection is encrypted, which requires the `Zmq` transport
+    pub fn new(coordinator_address: &str, transport: Transport, keys: Option<&CurveKeys>) -> Result<Self> {
+        info!("Client will attempt to connect to coordinator at: '{coordinator_address}' using {transport:?}");
+
+        let requester = match transport {
+            Transport::Zmq => ClientSocket::Zmq(Box::new(Mutex::new(
+                ZmqRequester::connect(coordinator_address, keys)?))),
+            Transport::WebSocket => {
+                check_unencrypted(keys)?;
+                ClientSocket::WebSocket(Box::new(Mutex::new(
+                    ClientWebSocket::connect(coordinator_address)?)))
+            }
+        };
 
         info!("Client connected to coordinator at '{coordinator_address}'");
 
@@ -83,13 +334,18 @@ impl ClientConnection {
     {
         trace!("Client waiting for message from coordinator");
 
-        let msg = self
-            .requester
-            .recv_msg(0)
-            .map_err(|e| format!("Error receiving from coordinator: {e}"))?;
+        let message_string = match &self.requester {
+            ClientSocket::Zmq(requester) => requester.lock()
+                .map_err(|_| "Could not lock connection")?
+                .receive()?,
+            ClientSocket::WebSocket(socket) => socket.lock()
+                .map_err(|_| "Could not lock WebSocket")?
+                .receive()
+                .chain_err(|| "Error receiving from coordinator")?,
+            ClientSocket::Channel(_, receiver) => receiver.recv()
+                .map_err(|_| "Error receiving from coordinator: connection closed")?,
+        };
 
-        let message_string = msg.as_str().ok_or("Could not get message as str")?
-            .to_string();
         let message: CM = message_string.into();
         trace!("Client Received <--- {}", message);
         Ok(message)
@@ -102,36 +358,234 @@ impl ClientConnection {
         CM: Into<String> + Display,
     {
         trace!("Client Sent     ---> {}", message);
-        self.requester
-            .send(&message.into(), 0)
-            .chain_err(|| "Error sending to coordinator")
+        match &self.requester {
+            ClientSocket::Zmq(requester) => requester.lock()
+                .map_err(|_| "Could not lock connection")?
+                .send(message.into()),
+            ClientSocket::WebSocket(socket) => socket.lock()
+                .map_err(|_| "Could not lock WebSocket")?
+                .send(message.into())
+                .chain_err(|| "Error sending to coordinator"),
+            ClientSocket::Channel(sender, _) => sender.send(message.into())
+                .map_err(|_| "Error sending to coordinator: connection closed".into()),
+        }
+    }
+}
+
+// Close a `WebSocket` cleanly so the coordinator can accept a connection from another client
+impl Drop for ClientConnection {
+    fn drop(&mut self) {
+        if let ClientSocket::WebSocket(socket) = &self.requester {
+            if let Ok(mut socket) = socket.lock() {
+                let _ = socket.socket.close(None);
+                let _ = socket.socket.flush();
+            }
+        }
+    }
+}
+
+// The coordinator side of a `WebSocket` transport. A background thread accepts connections from
+// clients and passes them over a channel, a new one is used when the current client disconnects.
+// Messages sent while the client of a session is disconnected are kept, and sent when it
+// reconnects to resume the session. A client connecting with a different session starts a new one.
+struct WebSocketResponder {
+    connections: Receiver<AcceptedSocket>,
+    socket: Mutex<Option<WebSocket<TcpStream>>>,
+    session: Arc<Mutex<Option<Session>>>,
+}
+
+impl WebSocketResponder {
+    fn bind(port: u16, bind_address: Option<IpAddr>) -> Result<Self> {
+        let listener = TcpListener::bind(bind_socket_address(bind_address, port))
+            .chain_err(|| format!("Coordinator Connection - could not bind WebSocket on port: {port}"))?;
+        let (sender, connections) = channel();
+        let session = Arc::new(Mutex::new(None::<Session>));
+        let current_session = session.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut resume = None;
+                // tell a client reconnecting to the current session how many messages were received
+                let callback = |request: &Request, mut response: Response|
+                    -> std::result::Result<Response, ErrorResponse> {
+                    resume = parse_resume(request.uri().query());
+                    if let (Some((token, _)), Ok(session)) = (&resume, current_session.lock()) {
+                        if let Some(session) = session.as_ref().filter(|s| &s.token == token) {
+                            response.headers_mut().insert(RECEIVED_HEADER,
+                                                          HeaderValue::from(session.received));
+                        }
+                    }
+                    Ok(response)
+                };
+                match stream.map(|stream| tungstenite::accept_hdr(stream, callback)) {
+                    Ok(Ok(socket)) => {
+                        if sender.send((socket, resume)).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(Err(e)) => error!("WebSocket handshake with client failed: {e}"),
+                    Err(e) => error!("Could not accept WebSocket connection: {e}"),
+                }
+            }
+        });
+
+        Ok(WebSocketResponder {
+            connections,
+            socket: Mutex::new(None),
+            session,
+        })
+    }
+
+    // Start using a new connection from a client. If it resumes the current session, send the
+    // messages the client did not receive, otherwise start a new session
+    fn connected(&self, mut socket: WebSocket<TcpStream>, resume: Option<(String, usize)>)
+        -> Result<WebSocket<TcpStream>> {
+        let mut session = self.session.lock().map_err(|_| "Could not lock session")?;
+        let undelivered = match (&resume, session.as_ref()) {
+            (Some((token, received)), Some(current)) if &current.token == token =>
+                Some(current.undelivered(*received)?),
+            _ => None,
+        };
+
+        match undelivered {
+            Some(messages) => {
+                for message in messages {
+                    socket.send(Message::Text(message))
+                        .map_err(|e| format!("Coordinator error sending to client: '{e}'"))?;
+                }
+                info!("Client resumed its session");
+            }
+            None => {
+                if let Some(previous) = session.as_ref() {
+                    debug!("Client session '{}' has ended", previous.token);
+                }
+                *session = resume.map(|(token, _)| Session::new(token));
+            }
+        }
+        Ok(socket)
+    }
+
+    fn receive(&self, flags: i32) -> Result<String> {
+        let mut guard = self.socket.lock()
+            .map_err(|_| "Could not lock WebSocket")?;
+
+        loop {
+            if guard.is_none() {
+                let (socket, resume) = if flags == DONT_WAIT {
+                    self.connections.try_recv()
+                        .map_err(|_| "Coordinator error getting message: 'no client connected'")?
+                } else {
+                    self.connections.recv()
+                        .map_err(|_| "Coordinator error getting message: 'no more clients'")?
+                };
+                *guard = Some(self.connected(socket, resume)?);
+            }
+
+            let socket = guard.as_mut().ok_or("No WebSocket connected")?;
+            socket.get_mut().set_nonblocking(flags == DONT_WAIT)
+                .chain_err(|| "Could not set WebSocket blocking mode")?;
+            let result = read_websocket(socket);
+            socket.get_mut().set_nonblocking(false)
+                .chain_err(|| "Could not set WebSocket blocking mode")?;
+
+            match result {
+                Ok(Some(message)) => {
+                    if let Some(session) = self.session.lock()
+                        .map_err(|_| "Could not lock session")?.as_mut() {
+                        session.received += 1;
+                    }
+                    return Ok(message);
+                }
+                Ok(None) => {
+                    debug!("WebSocket client disconnected");
+                    *guard = None;
+                    if flags == DONT_WAIT {
+                        bail!("Coordinator error getting message: 'client disconnected'");
+                    }
+                }
+                Err(e) => bail!("Coordinator error getting message: '{}'", e),
+            }
+        }
+    }
+
+    // Send a message to the client. If it is disconnected and can resume its session, the message
+    // is kept and sent when it does
+    fn send(&self, message: String) -> Result<()> {
+        let mut guard = self.socket.lock()
+            .map_err(|_| "Could not lock WebSocket")?;
+        let resumable = match self.session.lock()
+            .map_err(|_| "Could not lock session")?.as_mut() {
+            Some(session) => {
+                session.record_sent(&message);
+                true
+            }
+            None => false,
+        };
+
+        let result = match guard.as_mut() {
+            Some(socket) => socket.send(Message::Text(message))
+                .map_err(|e| format!("Coordinator error sending to client: '{e}'")),
+            None => Err("Coordinator error sending to client: 'no client connected'".into()),
+        };
+
+        match result {
+            Err(e) if resumable => {
+                debug!("{e}, message will be sent when the client resumes its session");
+                *guard = None;
+                Ok(())
+            }
+            result => result.map_err(Into::into),
+        }
     }
 }
 
+enum CoordinatorSocket {
+    Zmq(Box<ZmqResponder>),
+    WebSocket(Box<WebSocketResponder>),
+    Channel(Sender<String>, Receiver<String>),
+}
+
+/// Create a [`ClientConnection`] and a [`CoordinatorConnection`] connected to each other by
+/// in-process channels instead of sockets, for a client and a
+/// [Coordinator][flowrlib::coordinator::Coordinator] running in the same process
+pub fn connection_pair() -> (ClientConnection, CoordinatorConnection) {
+    let (client_sender, coordinator_receiver) = channel();
+    let (coordinator_sender, client_receiver) = channel();
+
+    (ClientConnection { requester: ClientSocket::Channel(client_sender, client_receiver) },
+     CoordinatorConnection {
+         responder: CoordinatorSocket::Channel(coordinator_sender, coordinator_receiver)
+     })
+}
+
 /// [`CoordinatorConnection`] store information about the [Coordinator][flowrlib::coordinator::Coordinator]
 /// side of the client/coordinator communications between a client and a [Coordinator][flowrlib::coordinator::Coordinator]
 /// and is used each time a message needs to be sent or received.
 pub struct CoordinatorConnection {
-    responder: Socket,
+    responder: CoordinatorSocket,
 }
 
 /// Implement a [`CoordinatorConnection`] for sending and receiving messages between client and
 /// a [Coordinator][flowrlib::coordinator::Coordinator]
 impl CoordinatorConnection {
     /// Create a new [Coordinator][flowrlib::coordinator::Coordinator]
-    /// side of the client/coordinator Connection
-    pub fn new(service_name: &'static str, port: u16) -> Result<Self> {
-        let context = zmq::Context::new();
-        let responder = context
-            .socket(zmq::REP)
-            .chain_err(|| "Coordinator Connection - could not create Socket")?;
-
-        debug!("Coordinator Connection attempting to bind to: tcp://*:{port}");
-        responder.bind(&format!("tcp://*:{port}"))
-            .chain_err(||
-                format!("Coordinator Connection - could not bind on TCP Socket on: tcp://{port}"))?;
-
-        info!("Service '{}' listening on *:{}", service_name, port);
+    /// side of the client/coordinator Connection, bound to `port` on the interface with
+    /// `bind_address`, or on all interfaces if none is given. If `keys` are provided the connection
+    /// is encrypted and only clients using the same keys can connect, which requires the `Zmq` transport
+    pub fn new(service_name: &'static str, port: u16, bind_address: Option<IpAddr>,
+               transport: Transport, keys: Option<&CurveKeys>) -> Result<Self> {
+        let responder = match transport {
+            Transport::Zmq => CoordinatorSocket::Zmq(Box::new(
+                ZmqResponder::bind(port, bind_address, keys)?)),
+            Transport::WebSocket => {
+                check_unencrypted(keys)?;
+                debug!("Coordinator Connection attempting to bind to: ws://{}",
+                    bind_socket_address(bind_address, port));
+                CoordinatorSocket::WebSocket(Box::new(WebSocketResponder::bind(port, bind_address)?))
+            }
+        };
+
+        info!("Service '{}' listening on *:{} using {:?}", service_name, port, transport);
 
         Ok(CoordinatorConnection {
             responder
@@ -145,13 +599,16 @@ impl CoordinatorConnection {
     {
         trace!("Coordinator waiting for message from client");
 
-        let msg = self
-            .responder
-            .recv_msg(flags)
-            .map_err(|e| format!("Coordinator error getting message: '{e}'"))?;
+        let message_string = match &self.responder {
+            CoordinatorSocket::Zmq(responder) => responder.receive(flags)?,
+            CoordinatorSocket::WebSocket(responder) => responder.receive(flags)?,
+            CoordinatorSocket::Channel(_, receiver) => if flags == DONT_WAIT {
+                receiver.try_recv().map_err(|e| format!("Coordinator error getting message: '{e}'"))?
+            } else {
+                receiver.recv().map_err(|e| format!("Coordinator error getting message: '{e}'"))?
+            },
+        };
 
-        let message_string = msg.as_str().ok_or("Could not get message as str")?
-            .to_string();
         let message = message_string.into();
         trace!("                ---> Coordinator Received {}", message);
         Ok(message)
@@ -176,9 +633,12 @@ impl CoordinatorConnection {
     {
         trace!("                <--- Coordinator Sent {}", message);
 
-        self.responder
-            .send(&message.into(), 0)
-            .map_err(|e| format!("Coordinator error sending to client: '{e}'"))?;
+        match &self.responder {
+            CoordinatorSocket::Zmq(responder) => responder.send(message.into())?,
+            CoordinatorSocket::WebSocket(responder) => responder.send(message.into())?,
+            CoordinatorSocket::Channel(sender, _) => sender.send(message.into())
+                .map_err(|_| "Coordinator error sending to client: 'client disconnected'")?,
+        }
 
         Ok(())
     }
@@ -187,13 +647,14 @@ impl CoordinatorConnection {
 #[cfg(test)]
 mod test {
     use std::fmt;
+    use std::net::Shutdown;
     use std::time::Duration;
 
     use portpicker::pick_unused_port;
     use serde_derive::{Deserialize, Serialize};
     use serial_test::serial;
 
-    use crate::cli::connections::{ClientConnection, CoordinatorConnection, discover_service, DONT_WAIT, enable_service_discovery, WAIT};
+    use crate::cli::connections::{ClientConnection, ClientSocket, connection_pair, CoordinatorConnection, discover_service, DONT_WAIT, enable_service_discovery, parse_resume, RESUME_BUFFER_LENGTH, Session, Transport, WAIT};
 
     #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
     enum CoordinatorMessage {
@@ -252,16 +713,16 @@ mod test {
     #[serial]
     fn coordinator_receive_wait_get_reply() {
         let test_port = pick_unused_port().expect("No ports free");
-        let mut coordinator_connection = CoordinatorConnection::new("test", test_port)
+        let mut coordinator_connection = CoordinatorConnection::new("test", test_port, None, Transport::Zmq, None)
             .expect("Could not create CoordinatorConnection");
 
         let discovery_port = pick_unused_port().expect("No ports free");
-        enable_service_discovery(discovery_port, "test", test_port)
+        enable_service_discovery(discovery_port, "test", test_port, None)
             .expect("Could not enable service discovery");
 
         let coordinator_address = discover_service(discovery_port, "test")
             .expect("Could not discover service");
-        let client = ClientConnection::new(&coordinator_address)
+        let client = ClientConnection::new(&coordinator_address, Transport::Zmq, None)
             .expect("Could not create ClientConnection");
 
         // Open the connection by sending the first message from the client
@@ -292,15 +753,15 @@ mod test {
     #[serial]
     fn coordinator_receive_nowait_get_reply() {
         let test_port = pick_unused_port().expect("No ports free");
-        let mut coordinator_connection = CoordinatorConnection::new("test", test_port)
+        let mut coordinator_connection = CoordinatorConnection::new("test", test_port, None, Transport::Zmq, None)
             .expect("Could not create CoordinatorConnection");
         let discovery_port = pick_unused_port().expect("No ports free");
-        enable_service_discovery(discovery_port, "test", test_port)
+        enable_service_discovery(discovery_port, "test", test_port, None)
             .expect("Could not enable service discovery");
 
         let coordinator_address = discover_service(discovery_port, "test")
             .expect("Could discovery service");
-        let client = ClientConnection::new(&coordinator_address)
+        let client = ClientConnection::new(&coordinator_address, Transport::Zmq, None)
             .expect("Could not create ClientConnection");
 
         // Open the connection by sending the first message from the client
@@ -330,4 +791,177 @@ mod test {
             CoordinatorMessage::World
         );
     }
+
+    // Requires network access
+    #[test]
+    #[serial]
+    fn websocket_coordinator_receive_wait_get_reply() {
+        let test_port = pick_unused_port().expect("No ports free");
+        let mut coordinator_connection = CoordinatorConnection::new("test", test_port, None,
+                                                                    Transport::WebSocket, None)
+            .expect("Could not create CoordinatorConnection");
+
+        let client = ClientConnection::new(&format!("127.0.0.1:{test_port}"), Transport::WebSocket, None)
+            .expect("Could not create ClientConnection");
+
+        client
+            .send(ClientMessage::Hello)
+            .expect("Could not send initial 'Hello' message");
+
+        let client_message = coordinator_connection
+            .receive::<ClientMessage>(WAIT)
+            .expect("Could not receive message at Coordinator");
+        assert_eq!(client_message, ClientMessage::Hello);
+
+        coordinator_connection
+            .send(CoordinatorMessage::World)
+            .expect("Could not send Coordinator message");
+
+        let coordinator_message = client
+            .receive::<CoordinatorMessage>()
+            .expect("Could not receive message at client");
+        assert_eq!(coordinator_message, CoordinatorMessage::World);
+    }
+
+    // Requires network access
+    #[test]
+    #[serial]
+    fn websocket_coordinator_receive_nowait() {
+        let test_port = pick_unused_port().expect("No ports free");
+        let mut coordinator_connection = CoordinatorConnection::new("test", test_port, None,
+                                                                    Transport::WebSocket, None)
+            .expect("Could not create CoordinatorConnection");
+
+        assert!(coordinator_connection.receive::<ClientMessage>(DONT_WAIT).is_err());
+
+        let client = ClientConnection::new(&format!("127.0.0.1:{test_port}"), Transport::WebSocket, None)
+            .expect("Could not create ClientConnection");
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(coordinator_connection.receive::<ClientMessage>(DONT_WAIT).is_err());
+
+        client
+            .send(ClientMessage::Hello)
+            .expect("Could not send initial 'Hello' message");
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(
+            coordinator_connection
+                .receive::<ClientMessage>(DONT_WAIT)
+                .expect("Could not receive message at Coordinator"),
+            ClientMessage::Hello
+        );
+
+        coordinator_connection
+            .send(CoordinatorMessage::World)
+            .expect("Could not send Coordinator message");
+
+        assert_eq!(
+            client
+                .receive::<CoordinatorMessage>()
+                .expect("Could not receive message at client"),
+            CoordinatorMessage::World
+        );
+    }
+
+    // Requires network access
+    #[test]
+    #[serial]
+    fn websocket_client_resumes_session() {
+        let test_port = pick_unused_port().expect("No ports free");
+        let mut coordinator_connection = CoordinatorConnection::new("test", test_port, None,
+                                                                    Transport::WebSocket, None)
+            .expect("Could not create CoordinatorConnection");
+
+        let client = ClientConnection::new(&format!("127.0.0.1:{test_port}"), Transport::WebSocket, None)
+            .expect("Could not create ClientConnection");
+
+        client
+            .send(ClientMessage::Hello)
+            .expect("Could not send initial 'Hello' message");
+        assert_eq!(
+            coordinator_connection
+                .receive::<ClientMessage>(WAIT)
+                .expect("Could not receive message at Coordinator"),
+            ClientMessage::Hello
+        );
+
+        // interrupt the connection, before the coordinator's reply is sent
+        if let ClientSocket::WebSocket(socket) = &client.requester {
+            socket.lock().expect("Could not lock WebSocket")
+                .socket.get_mut().shutdown(Shutdown::Both)
+                .expect("Could not shutdown connection");
+        }
+        coordinator_connection
+            .send(CoordinatorMessage::World)
+            .expect("Could not send Coordinator message");
+
+        // the client reconnects, receives the reply it missed and sends another message
+        let client_thread = std::thread::spawn(move || {
+            let message = client.receive::<CoordinatorMessage>()
+                .expect("Could not receive message at client");
+            client.send(ClientMessage::Hello).expect("Could not send 'Hello' message");
+            message
+        });
+
+        assert_eq!(
+            coordinator_connection
+                .receive::<ClientMessage>(WAIT)
+                .expect("Could not receive message at Coordinator"),
+            ClientMessage::Hello
+        );
+        assert_eq!(client_thread.join().expect("Client thread panicked"), CoordinatorMessage::World);
+    }
+
+    #[test]
+    fn session_undelivered_messages() {
+        let mut session = Session::new("token".into());
+        session.record_sent("one");
+        session.record_sent("two");
+        assert_eq!(session.undelivered(2).expect("Could not get undelivered"), Vec::<String>::new());
+        assert_eq!(session.undelivered(1).expect("Could not get undelivered"), vec!["two".to_string()]);
+        assert!(session.undelivered(3).is_err());
+
+        for _ in 0..RESUME_BUFFER_LENGTH {
+            session.record_sent("more");
+        }
+        assert!(session.undelivered(0).is_err());
+    }
+
+    #[test]
+    fn parse_resume_query() {
+        assert_eq!(parse_resume(Some("session=abc&received=3")), Some(("abc".into(), 3)));
+        assert_eq!(parse_resume(Some("session=abc")), None);
+        assert_eq!(parse_resume(None), None);
+    }
+
+    #[test]
+    fn channel_coordinator_receive_get_reply() {
+        let (client, mut coordinator_connection) = connection_pair();
+
+        assert!(coordinator_connection.receive::<ClientMessage>(DONT_WAIT).is_err());
+
+        client
+            .send(ClientMessage::Hello)
+            .expect("Could not send initial 'Hello' message");
+
+        assert_eq!(
+            coordinator_connection
+                .receive::<ClientMessage>(DONT_WAIT)
+                .expect("Could not receive message at Coordinator"),
+            ClientMessage::Hello
+        );
+
+        coordinator_connection
+            .send(CoordinatorMessage::World)
+            .expect("Could not send Coordinator message");
+
+        assert_eq!(
+            client
+                .receive::<CoordinatorMessage>()
+                .expect("Could not receive message at client"),
+            CoordinatorMessage::World
+        );
+    }
 }
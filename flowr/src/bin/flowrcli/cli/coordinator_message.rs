@@ -1,8 +1,13 @@
+use std::collections::BTreeMap;
 use std::fmt;
+use std::fs;
+use std::time::UNIX_EPOCH;
 
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 
 use flowcore::errors::Result;
+use flowcore::model::bytes::Bytes;
 #[cfg(feature = "metrics")]
 use flowcore::model::metrics::Metrics;
 use flowcore::model::submission::Submission;
@@ -20,14 +25,25 @@ pub enum CoordinatorMessage {
     /// A flow has stopped executing
     #[cfg(not(feature = "metrics"))]
     FlowEnd,
+    /// Execution of a flow was cancelled at the request of the client, with the metrics of the
+    /// execution up to that point
+    #[cfg(feature = "metrics")]
+    FlowCancelled(Metrics),
+    /// Execution of a flow was cancelled at the request of the client
+    #[cfg(not(feature = "metrics"))]
+    FlowCancelled,
     /// Coordinator is exiting, with a result (OK, or Err)
     CoordinatorExiting(Result<()>),
+    /// The submission was rejected as the client could not be authenticated, with the reason
+    SubmissionRejected(String),
 
     /// ** These messages are used to implement the context functions between the `cli_runtime_server`
     /// that runs as part of the `Coordinator` and the `cli_runtime_client` that interacts with
     /// STDIO
     /// A String of contents was sent to stdout
     Stdout(String),
+    /// A line of styled text, made up of spans of text with different styles, was sent to stdout
+    StdoutStyled(Vec<StyledText>),
     /// A String of contents was sent to stderr
     Stderr(String),
     /// A Request to read from Stdin
@@ -36,12 +52,36 @@ pub enum CoordinatorMessage {
     GetLine(String),
     /// A Request to get the arguments for the flow
     GetArgs,
+    /// A Request to get the value of the named environment variable
+    GetEnvVar(String),
+    /// A Request to get all the environment variables the flow is allowed to read
+    GetEnvVars,
+    /// A Request to get the value stored for a key in the key-value store
+    KvGet(String),
+    /// A Request to store a value for a key in the key-value store
+    KvPut(String, Value),
+    /// A Request to delete the value stored for a key in the key-value store
+    KvDelete(String),
+    /// A Request to run a command, with arguments, writing a String to its stdin
+    Exec(String, Vec<String>, String),
+    /// A Request to send bytes to a TCP server at an address, optionally waiting for a response
+    TcpSend(String, Vec<u8>, bool),
     /// A Request to read bytes from a file
     Read(String),
     /// A Request to write a series of bytes to a file
-    Write(String, Vec<u8>),
+    Write(String, Bytes),
+    /// A Request to append a series of bytes to the end of a file
+    Append(String, Bytes),
+    /// A Request to remove a file
+    Remove(String),
+    /// A Request to list the entries of a directory
+    List(String),
+    /// A Request to get the metadata of a file or directory
+    Stat(String),
     /// A Request to write a pixel to an `ImageBuffer`
     PixelWrite((u32, u32), (u8, u8, u8), (u32, u32), String),
+    /// A Request to read and decode an image file
+    ImageRead(String),
     /// A Request to snd EOF to Stdout
     StdoutEof,
     /// A Request to snd EOF to Stderr
@@ -60,17 +100,35 @@ impl fmt::Display for CoordinatorMessage {
                 CoordinatorMessage::FlowEnd(_) => "FlowEnd".into(),
                 #[cfg(not(feature = "metrics"))]
                 CoordinatorMessage::FlowEnd => "FlowEnd".into(),
+                #[cfg(feature = "metrics")]
+                CoordinatorMessage::FlowCancelled(_) => "FlowCancelled".into(),
+                #[cfg(not(feature = "metrics"))]
+                CoordinatorMessage::FlowCancelled => "FlowCancelled".into(),
                 CoordinatorMessage::FlowStart => "FlowStart".into(),
                 CoordinatorMessage::CoordinatorExiting(result) =>
                     format!("CoordinatorExiting with result: {result:?}"),
+                CoordinatorMessage::SubmissionRejected(_) => "SubmissionRejected".into(),
                 CoordinatorMessage::Stdout(_) => "Stdout".into(),
+                CoordinatorMessage::StdoutStyled(_) => "StdoutStyled".into(),
                 CoordinatorMessage::Stderr(_) => "Stderr".into(),
                 CoordinatorMessage::GetStdin => "GetStdIn".into(),
                 CoordinatorMessage::GetLine(_) => "GetLine".into(),
                 CoordinatorMessage::GetArgs => "GetArgs".into(),
+                CoordinatorMessage::GetEnvVar(_) => "GetEnvVar".into(),
+                CoordinatorMessage::GetEnvVars => "GetEnvVars".into(),
+                CoordinatorMessage::KvGet(_) => "KvGet".into(),
+                CoordinatorMessage::KvPut(_, _) => "KvPut".into(),
+                CoordinatorMessage::KvDelete(_) => "KvDelete".into(),
+                CoordinatorMessage::Exec(_, _, _) => "Exec".into(),
+                CoordinatorMessage::TcpSend(_, _, _) => "TcpSend".into(),
                 CoordinatorMessage::Read(_) => "Read".into(),
                 CoordinatorMessage::Write(_, _) => "Write".into(),
+                CoordinatorMessage::Append(_, _) => "Append".into(),
+                CoordinatorMessage::Remove(_) => "Remove".into(),
+                CoordinatorMessage::List(_) => "List".into(),
+                CoordinatorMessage::Stat(_) => "Stat".into(),
                 CoordinatorMessage::PixelWrite(_, _, _, _) => "PixelWrite".into(),
+                CoordinatorMessage::ImageRead(_) => "ImageRead".into(),
                 CoordinatorMessage::StdoutEof => "StdOutEof".into(),
                 CoordinatorMessage::StderrEof => "StdErrEof".into(),
                 CoordinatorMessage::Invalid => "Invalid".into(),
@@ -87,17 +145,38 @@ pub struct FileMetaData {
     pub is_file: bool,
     /// Was the Path inspected a directory or not
     pub is_dir: bool,
+    /// The size in bytes of the file
+    pub size: u64,
+    /// When the file was last modified, in seconds since the UNIX epoch, if available
+    pub modified: Option<u64>,
+}
+
+impl From<&fs::Metadata> for FileMetaData {
+    fn from(metadata: &fs::Metadata) -> Self {
+        FileMetaData {
+            is_file: metadata.is_file(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            modified: metadata.modified().ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| since_epoch.as_secs()),
+        }
+    }
 }
 
 /// A Message from the a client to the Coordinator
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ClientMessage {
     /// ** These messages are used to implement the `SubmissionProtocol` between the Coordinator
     /// and the client
-    /// A submission from the client for execution
-    ClientSubmission(Submission),
+    /// A submission from the client for execution, with the token the client presents to
+    /// authenticate itself, if any
+    ClientSubmission(Option<String>, Submission),
     /// Client requests that server enters the ddebugger at the next opportunity
     EnterDebugger,
+    /// Client requests that execution of the flow it submitted be cancelled
+    CancelFlow,
 
     /// ** These messages are used to implement the context functions between the `cli_runtime_client`
     /// and the `cli_runtime_server` that runs as part of the `Coordinator`
@@ -109,6 +188,16 @@ pub enum ClientMessage {
     Line(String),
     /// A Vector of Strings that are the flow's arguments from Client, sent to the Server
     Args(Vec<String>),
+    /// The value of an environment variable, or None if it is not set, sent to the Server
+    EnvVar(Option<String>),
+    /// The value stored for a key in the key-value store, or None if there is none
+    KvValue(Option<Value>),
+    /// The names and values of the environment variables the flow is allowed to read
+    EnvVars(BTreeMap<String, String>),
+    /// The exit code (if it exited normally), stdout and stderr of a command that was run
+    ProcessOutput(Option<i32>, String, String),
+    /// The response received from a TCP server
+    TcpResponse(Vec<u8>),
     /// An Error occurred in the `runtime_client`
     Error(String),
     /// EOF was detected on input reading using Stdin
@@ -118,7 +207,13 @@ pub enum ClientMessage {
     /// Invalid - used when deserialization goes wrong
     Invalid,
     /// Contents read from a file
-    FileContents(String, Vec<u8>),
+    FileContents(String, Bytes),
+    /// The width, height and (r, g, b) bytes of the pixels, row by row, of an image read
+    Image(u32, u32, Vec<u8>),
+    /// The paths of the entries of a directory, sorted
+    DirectoryEntries(Vec<String>),
+    /// The metadata of a file or directory
+    Metadata(FileMetaData),
 
     /// ** This message is just internal to the client and not sent to the Coordinator
     /// Client is exiting Event loop
@@ -135,20 +230,69 @@ impl fmt::Display for ClientMessage {
                 ClientMessage::Stdin(_) => "Stdin".into(),
                 ClientMessage::Line(_) => "Line".into(),
                 ClientMessage::Args(_) => "Args".into(),
+                ClientMessage::EnvVar(_) => "EnvVar".into(),
+                ClientMessage::KvValue(_) => "KvValue".into(),
+                ClientMessage::EnvVars(_) => "EnvVars".into(),
+                ClientMessage::ProcessOutput(_, _, _) => "ProcessOutput".into(),
+                ClientMessage::TcpResponse(_) => "TcpResponse".into(),
                 ClientMessage::Error(_) => "Error".into(),
                 ClientMessage::GetStdinEof => "GetStdinEof".into(),
                 ClientMessage::GetLineEof => "GetLineEof".into(),
                 ClientMessage::ClientExiting(result) =>
                     format!("ClientExiting with server result: {result:?}"),
-                ClientMessage::ClientSubmission(_) => "ClientSubmission".into(),
+                ClientMessage::ClientSubmission(_, _) => "ClientSubmission".into(),
                 ClientMessage::EnterDebugger => "EnterDebugger".into(),
+                ClientMessage::CancelFlow => "CancelFlow".into(),
                 ClientMessage::Invalid => "Invalid".into(),
                 ClientMessage::FileContents(_, _) => "FileContents".into(),
+                ClientMessage::Image(_, _, _) => "Image".into(),
+                ClientMessage::DirectoryEntries(_) => "DirectoryEntries".into(),
+                ClientMessage::Metadata(_) => "Metadata".into(),
             }
         )
     }
 }
 
+/// A color that styled text can be displayed in, or with as a background
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    /// Black
+    Black,
+    /// Red
+    Red,
+    /// Green
+    Green,
+    /// Yellow
+    Yellow,
+    /// Blue
+    Blue,
+    /// Magenta
+    Magenta,
+    /// Cyan
+    Cyan,
+    /// White
+    White,
+}
+
+/// A span of text and the style it should be displayed with, fields not specified are not styled
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+#[serde(default)]
+pub struct StyledText {
+    /// The text to display
+    pub text: String,
+    /// The color to display the text in
+    pub color: Option<Color>,
+    /// The color to display behind the text
+    pub background: Option<Color>,
+    /// Display the text in bold
+    pub bold: bool,
+    /// Display the text in italics
+    pub italic: bool,
+    /// Display the text underlined
+    pub underline: bool,
+}
+
 impl From<CoordinatorMessage> for String {
     fn from(msg: CoordinatorMessage) -> Self {
         serde_json::to_string(&msg).unwrap_or_default()
@@ -6,6 +6,7 @@ use serde_json::Value;
 use flowcore::model::input::Input;
 use flowcore::model::output_connection::OutputConnection;
 use flowcore::model::runtime_function::RuntimeFunction;
+use flowcore::model::source_location::SourceLocation;
 use flowrlib::block::Block;
 use flowrlib::job::Job;
 use flowrlib::run_state::{RunState, State};
@@ -29,12 +30,15 @@ pub enum DebugServerMessage {
     FlowUnblockBreakpoint(usize),
     /// A breakpoint on a `Value` being sent between two functions was encountered
     /// includes: `source_process_id`, `output_route`, `value`, `destination_id`, `function_name`,
-    /// `io_name`, `input_number`));
-    DataBreakpoint(String, usize, String, Value, usize, String, String, usize),
+    /// `io_name`, `input_number` and where the connection was defined (if known)
+    DataBreakpoint(String, usize, String, Value, usize, String, String, usize, Option<SourceLocation>),
+    /// A value was output on a watched output route by a `Job` that completed
+    /// includes: `job_id`, `function_id`, `function_name`, `output_route`, `value`
+    WatchedValue(usize, usize, String, String, Value),
     /// A panic occurred executing a `Flows` `Job` -  includes the output of the job that panicked
     Panic(String, usize),
-    /// There was an error executing the Job
-    JobError(Job),
+    /// There was an error executing the Job - includes where the function was referenced from (if known)
+    JobError(Job, Option<SourceLocation>),
     /// A check has detected that there is a deadlock between functions impeding more execution
     Deadlock(String),
     /// Execution of the flow has started
@@ -79,14 +83,15 @@ impl fmt::Display for DebugServerMessage {
                 DebugServerMessage::ExitingDebugger => "ExitingDebugger",
                 DebugServerMessage::PriorToSendingJob(_) => "PriorToSendingJob",
                 DebugServerMessage::BlockBreakpoint(_) => "BlockBreakpoint",
-                DebugServerMessage::DataBreakpoint(_, _, _, _, _, _, _, _) => "DataBreakpoint",
+                DebugServerMessage::DataBreakpoint(_, _, _, _, _, _, _, _, _) => "DataBreakpoint",
+                DebugServerMessage::WatchedValue(_, _, _, _, _) => "WatchedValue",
                 DebugServerMessage::Deadlock(_) => "Deadlock",
                 DebugServerMessage::Error(_) => "Error",
                 DebugServerMessage::ExecutionStarted => "ExecutionStarted",
                 DebugServerMessage::ExecutionEnded => "ExecutionEnded",
                 DebugServerMessage::Functions(_) => "Functions",
                 DebugServerMessage::FunctionStates(_) => "FunctionState",
-                DebugServerMessage::JobError(_) => "JobError",
+                DebugServerMessage::JobError(_, _) => "JobError",
                 DebugServerMessage::SendingValue(_, _, _, _) => "SendingValue",
                 DebugServerMessage::OverallState(_) => "OverallState",
                 DebugServerMessage::Panic(_, _) => "Panic",
@@ -0,0 +1,717 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, error, info, warn};
+use serde_derive::Serialize;
+use serde_json::{json, Value};
+use url::Url;
+
+use flowcore::errors::Result;
+use flowcore::model::flow_manifest::FlowManifest;
+#[cfg(feature = "metrics")]
+use flowcore::model::metrics::{Metrics, MetricsSnapshot};
+use flowcore::model::submission::Submission;
+use flowcore::provider::Provider;
+use flowcore::url_helper::url_from_string;
+use flowrlib::run_state::RunState;
+use flowrlib::services::bind_socket_address;
+use flowrlib::submission_handler::{SubmissionHandler, SubmissionListener};
+
+use crate::cli::auth::ClientTokens;
+
+// The largest request body accepted, a submission only needs to carry a manifest url
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+
+// The largest request line and headers accepted, read before the client is authenticated
+const MAX_HEADER_BYTES: u64 = 8 * 1024;
+
+// The most headers accepted in a request
+const MAX_HEADERS: usize = 32;
+
+// The time allowed to read a whole request from, or write a response to, a client before the
+// connection is closed, so that a client that stops sending or trickles bytes does not hold it open
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The most connections handled concurrently, further connections are closed until one ends
+const MAX_CONNECTIONS: usize = 32;
+
+// The most runs that have ended that are kept to be queried, the earliest are forgotten first
+const MAX_ENDED_RUNS: usize = 1000;
+
+/// The status of a run of a flow submitted using the REST API
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    /// The submission is waiting to be accepted by the coordinator
+    Queued,
+    /// The flow is being executed
+    Running,
+    /// Execution of the flow has ended
+    Completed,
+    /// Execution of the flow was cancelled before it ended
+    Cancelled,
+    /// The flow could not be executed
+    Failed,
+}
+
+// What is known about a run, as reported by the API
+#[derive(Serialize, Debug, Clone)]
+struct Run {
+    id: usize,
+    manifest: String,
+    status: RunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip)]
+    cancel_requested: bool,
+    // the identity of the authenticated client that submitted it, if clients are authenticated
+    #[serde(skip)]
+    client: Option<String>,
+    // the latest snapshot of the metrics while running, or the final metrics once ended
+    #[serde(skip)]
+    metrics: Option<Value>,
+}
+
+impl Run {
+    fn ended(&self) -> bool {
+        matches!(self.status, RunStatus::Completed | RunStatus::Cancelled | RunStatus::Failed)
+    }
+}
+
+/// The runs of flows submitted using the REST API, indexed by their id
+#[derive(Default)]
+pub struct Runs {
+    next_id: usize,
+    runs: HashMap<usize, Run>,
+}
+
+impl Runs {
+    fn add(&mut self, manifest: &Url, client: Option<&str>) -> usize {
+        self.evict_ended();
+        self.next_id += 1;
+        self.runs.insert(self.next_id, Run {
+            id: self.next_id,
+            manifest: manifest.to_string(),
+            status: RunStatus::Queued,
+            error: None,
+            cancel_requested: false,
+            client: client.map(str::to_string),
+            metrics: None,
+        });
+        self.next_id
+    }
+
+    // Forget the earliest runs that have ended beyond the most that are kept, so that a
+    // long-running coordinator does not keep every run ever submitted
+    fn evict_ended(&mut self) {
+        let mut ended: Vec<usize> = self.runs.values()
+            .filter(|run| run.ended())
+            .map(|run| run.id)
+            .collect();
+        let excess = ended.len().saturating_sub(MAX_ENDED_RUNS);
+        ended.sort_unstable();
+        for id in ended.iter().take(excess) {
+            self.runs.remove(id);
+        }
+    }
+
+    fn update(runs: &Arc<Mutex<Runs>>, id: usize, update: impl FnOnce(&mut Run)) -> Result<()> {
+        let mut runs = runs.lock().map_err(|_| "Could not lock runs")?;
+        if let Some(run) = runs.runs.get_mut(&id) {
+            update(run);
+        }
+        Ok(())
+    }
+
+    // A tag identifying the authenticated client that submitted a run, to add to log output
+    fn client_tag(runs: &Arc<Mutex<Runs>>, id: usize) -> Result<String> {
+        let runs = runs.lock().map_err(|_| "Could not lock runs")?;
+        Ok(runs.runs.get(&id)
+            .and_then(|run| run.client.as_ref())
+            .map(|client| format!(" for client '{client}'"))
+            .unwrap_or_default())
+    }
+}
+
+// A response to a request: the HTTP status code and the JSON body
+type Response = (u16, Value);
+
+// The request line and the headers of a request that are used
+#[derive(Debug, Default, PartialEq)]
+struct RequestHead {
+    method: String,
+    path: String,
+    content_length: usize,
+    token: Option<String>,
+}
+
+// A reader of a request from a client that fails once the time allowed to read the whole request
+// has passed, as a timeout on each read would not end a connection a client trickles bytes on
+struct DeadlineReader {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::Error::new(ErrorKind::TimedOut,
+                                           "The request was not received in time"));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// The REST API used to submit flows for execution, query the status and metrics of their runs,
+/// and cancel them
+pub struct RunsApi {
+    runs: Arc<Mutex<Runs>>,
+    submissions: Sender<(usize, Submission)>,
+    provider: Arc<dyn Provider>,
+    base_url: Url,
+    tokens: Option<ClientTokens>,
+}
+
+impl RunsApi {
+    /// Route a request with `method`, `path` and `body` to the endpoint that handles it.
+    /// If clients are authenticated, requests that do not present a known bearer `token` are
+    /// rejected with a `401` status, and a client can only query or cancel the runs it submitted
+    ///
+    /// - `POST /runs` with body `{"manifest": "<url>"}` submits the flow manifest at the url
+    /// - `GET /runs/{id}` returns the status of a run
+    /// - `GET /runs/{id}/metrics` returns the metrics of a run
+    /// - `DELETE /runs/{id}` cancels a run
+    pub fn route(&self, method: &str, path: &str, token: Option<&str>, body: &str) -> Response {
+        let client = match &self.tokens {
+            Some(tokens) => match tokens.identify(token) {
+                Some(identity) => Some(identity),
+                None => {
+                    warn!("Rejected a '{method} {path}' request from an unauthenticated client");
+                    return (401, json!({"error": "Client token missing or not known"}));
+                }
+            },
+            None => None,
+        };
+
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let result = match (method, segments.as_slice()) {
+            ("POST", ["runs"]) => self.submit(body, client),
+            ("GET", ["runs", id]) => self.status(id, client),
+            ("GET", ["runs", id, "metrics"]) => self.metrics(id, client),
+            ("DELETE", ["runs", id]) => self.cancel(id, client),
+            (_, ["runs"] | ["runs", _] | ["runs", _, "metrics"]) =>
+                Ok((405, json!({"error": format!("Method '{method}' not allowed on '{path}'")}))),
+            _ => Ok((404, json!({"error": format!("No endpoint at '{path}'")}))),
+        };
+
+        result.unwrap_or_else(|e| (500, json!({"error": e.to_string()})))
+    }
+
+    fn submit(&self, body: &str, client: Option<&str>) -> Result<Response> {
+        let request: Value = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(e) => return Ok((400, json!({"error": format!("Invalid JSON body: {e}")}))),
+        };
+        let Some(manifest) = request.get("manifest").and_then(Value::as_str) else {
+            return Ok((400, json!({"error": "The body must have a 'manifest' url"})));
+        };
+
+        let manifest_url = url_from_string(&self.base_url, Some(manifest))?;
+        let flow_manifest = match FlowManifest::load(self.provider.as_ref(), &manifest_url) {
+            Ok((flow_manifest, _)) => flow_manifest,
+            Err(e) => return Ok((400, json!({
+                "error": format!("Could not load manifest '{manifest_url}': {e}")}))),
+        };
+
+        let submission = Submission::new(flow_manifest, None, None, None, None,
+                                         #[cfg(feature = "debugger")] false);
+        let id = self.runs.lock().map_err(|_| "Could not lock runs")?.add(&manifest_url, client);
+        self.submissions.send((id, submission))
+            .map_err(|_| "The coordinator is no longer accepting submissions")?;
+        info!("Run #{id} submitted for '{manifest_url}'{}", Runs::client_tag(&self.runs, id)?);
+
+        Ok((201, json!({"id": id})))
+    }
+
+    fn status(&self, id: &str, client: Option<&str>) -> Result<Response> {
+        let runs = self.runs.lock().map_err(|_| "Could not lock runs")?;
+        Ok(match Self::find(&runs, id, client) {
+            Ok(run) => (200, serde_json::to_value(run)?),
+            Err(response) => response,
+        })
+    }
+
+    fn metrics(&self, id: &str, client: Option<&str>) -> Result<Response> {
+        let runs = self.runs.lock().map_err(|_| "Could not lock runs")?;
+        Ok(match Self::find(&runs, id, client) {
+            Ok(run) => (200, run.metrics.clone().unwrap_or(Value::Null)),
+            Err(response) => response,
+        })
+    }
+
+    fn cancel(&self, id: &str, client: Option<&str>) -> Result<Response> {
+        let mut runs = self.runs.lock().map_err(|_| "Could not lock runs")?;
+        let Some(run) = id.parse::<usize>().ok().and_then(|id| runs.runs.get_mut(&id))
+            .filter(|run| run.client.as_deref() == client) else {
+            return Ok(not_found(id));
+        };
+        match run.status {
+            // not accepted by the coordinator yet, so the listener will skip it
+            RunStatus::Queued => run.status = RunStatus::Cancelled,
+            RunStatus::Running => run.cancel_requested = true,
+            _ => return Ok((409, json!({"error": format!("Run #{} has already ended", run.id)}))),
+        }
+        info!("Cancellation of run #{} requested", run.id);
+        Ok((202, serde_json::to_value(&*run)?))
+    }
+
+    // Find the run with `id` submitted by `client`. The runs of other clients are not found, so
+    // that their ids are not revealed to it
+    fn find<'a>(runs: &'a Runs, id: &str, client: Option<&str>)
+        -> std::result::Result<&'a Run, Response> {
+        id.parse::<usize>().ok()
+            .and_then(|id| runs.runs.get(&id))
+            .filter(|run| run.client.as_deref() == client)
+            .ok_or_else(|| not_found(id))
+    }
+
+    // Read a request from `stream`, route it and write the response
+    fn handle_connection(&self, stream: TcpStream) -> Result<()> {
+        stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+        let mut reader = BufReader::new(DeadlineReader {
+            stream: stream.try_clone()?,
+            deadline: Instant::now() + CONNECTION_TIMEOUT,
+        });
+
+        let (status, body) = match read_head(&mut reader)? {
+            None => (431, json!({"error": "Request headers too large"})),
+            Some(head) if head.content_length > MAX_BODY_LENGTH =>
+                (413, json!({"error": "Request body too large"})),
+            Some(head) => {
+                let mut body = vec![0; head.content_length];
+                reader.read_exact(&mut body)?;
+                debug!("HTTP request: {} {}", head.method, head.path);
+                self.route(&head.method, &head.path, head.token.as_deref(),
+                           &String::from_utf8_lossy(&body))
+            }
+        };
+
+        write_response(stream, status, &body)
+    }
+}
+
+// Read the request line and headers from `reader`, or `None` if they are larger than
+// [`MAX_HEADER_BYTES`] or there are more than [`MAX_HEADERS`] headers
+fn read_head(reader: &mut impl BufRead) -> Result<Option<RequestHead>> {
+    let mut reader = reader.take(MAX_HEADER_BYTES);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let mut head = RequestHead {
+        method: parts.next().unwrap_or_default().to_string(),
+        path: parts.next().unwrap_or_default().to_string(),
+        ..RequestHead::default()
+    };
+
+    let mut headers = 0;
+    loop {
+        // a line cut short by the limit is not ended by a newline
+        if reader.limit() == 0 {
+            return Ok(None);
+        }
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Ok(None);
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                head.content_length = value.trim().parse().unwrap_or(0);
+            } else if name.trim().eq_ignore_ascii_case("authorization") {
+                head.token = bearer_token(value);
+            }
+        }
+    }
+
+    Ok(Some(head))
+}
+
+// Get the token from the value of an `Authorization: Bearer <token>` header
+fn bearer_token(value: &str) -> Option<String> {
+    let (scheme, token) = value.trim().split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim().to_string())
+}
+
+fn not_found(id: &str) -> Response {
+    (404, json!({"error": format!("No run with id '{id}'")}))
+}
+
+// Write an HTTP response with `status` and a JSON `body` to `stream`, then close it
+fn write_response(mut stream: TcpStream, status: u16, body: &Value) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    let authenticate = if status == 401 { "WWW-Authenticate: Bearer\r\n" } else { "" };
+    let body = body.to_string();
+    write!(stream, "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\n\
+        {authenticate}Content-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len())?;
+    Ok(stream.flush()?)
+}
+
+/// A [`SubmissionListener`] that accepts the flows submitted using the REST API served on a port
+pub struct HttpSubmissionListener {
+    runs: Arc<Mutex<Runs>>,
+    submissions: Receiver<(usize, Submission)>,
+}
+
+impl HttpSubmissionListener {
+    /// Serve the REST API on `port` of the interface with `bind_address` from a background thread,
+    /// handling each connection on a thread of its own, up to [`MAX_CONNECTIONS`] at once, loading the manifests of flows submitted
+    /// using `provider`, and relative manifest urls from the current working directory.
+    /// If `tokens` are given, only requests from clients presenting one of them are accepted.
+    /// If no `bind_address` is given it is served on all IPv4 interfaces when `tokens` are given,
+    /// and otherwise only on the local host, as any client could then execute flows on this host
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `port` cannot be bound to
+    pub fn bind(port: u16, bind_address: Option<IpAddr>, provider: Arc<dyn Provider>, base_url: Url,
+                tokens: Option<ClientTokens>) -> Result<Self> {
+        let bind_address = bind_address
+            .or_else(|| tokens.is_none().then_some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        let listener = TcpListener::bind(bind_socket_address(bind_address, port))
+            .map_err(|e| format!("Could not bind to port {port} for the REST API: {e}"))?;
+        let runs = Arc::new(Mutex::new(Runs::default()));
+        let (sender, receiver) = channel();
+        let api = Arc::new(RunsApi {
+            runs: runs.clone(),
+            submissions: sender,
+            provider,
+            base_url,
+            tokens,
+        });
+
+        info!("Serving the REST API on port {port}");
+        thread::spawn(move || {
+            let connections = Arc::new(AtomicUsize::new(0));
+            for stream in listener.incoming().flatten() {
+                if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    warn!("Closed a REST API connection, as {MAX_CONNECTIONS} are being handled");
+                    continue;
+                }
+                let api = api.clone();
+                let connections = connections.clone();
+                thread::spawn(move || {
+                    if let Err(e) = api.handle_connection(stream) {
+                        error!("Could not handle HTTP request: {e}");
+                    }
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        Ok(HttpSubmissionListener {
+            runs,
+            submissions: receiver,
+        })
+    }
+}
+
+impl SubmissionListener for HttpSubmissionListener {
+    fn accept(&mut self, wait: bool) -> Result<Option<(Submission, Box<dyn SubmissionHandler>)>> {
+        loop {
+            let (id, submission) = if wait {
+                match self.submissions.recv() {
+                    Ok(submitted) => submitted,
+                    Err(_) => return Ok(None),
+                }
+            } else {
+                match self.submissions.try_recv() {
+                    Ok(submitted) => submitted,
+                    Err(TryRecvError::Empty | TryRecvError::Disconnected) => return Ok(None),
+                }
+            };
+
+            // skip runs cancelled before they were accepted, and since forgotten
+            let cancelled = self.runs.lock().map_err(|_| "Could not lock runs")?
+                .runs.get(&id).is_none_or(|run| run.status == RunStatus::Cancelled);
+            if !cancelled {
+                return Ok(Some((submission, Box::new(HttpSubmissionHandler {
+                    id,
+                    runs: self.runs.clone(),
+                }))));
+            }
+        }
+    }
+}
+
+// A [`SubmissionHandler`] that records the progress of a run, for the REST API to report
+struct HttpSubmissionHandler {
+    id: usize,
+    runs: Arc<Mutex<Runs>>,
+}
+
+impl SubmissionHandler for HttpSubmissionHandler {
+    fn flow_execution_starting(&mut self) -> Result<()> {
+        info!("Run #{} starting{}", self.id, Runs::client_tag(&self.runs, self.id)?);
+        Runs::update(&self.runs, self.id, |run| run.status = RunStatus::Running)
+    }
+
+    #[cfg(feature = "debugger")]
+    fn should_enter_debugger(&mut self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn should_cancel(&mut self) -> Result<bool> {
+        let runs = self.runs.lock().map_err(|_| "Could not lock runs")?;
+        Ok(runs.runs.get(&self.id).is_some_and(|run| run.cancel_requested))
+    }
+
+    fn flow_execution_ended(&mut self, state: &RunState,
+                            #[cfg(feature = "metrics")] metrics: Metrics) -> Result<()> {
+        info!("Run #{} ended{}", self.id, Runs::client_tag(&self.runs, self.id)?);
+        #[cfg(feature = "metrics")]
+        let metrics = serde_json::to_value(metrics)?;
+        Runs::update(&self.runs, self.id, |run| {
+            run.status = if state.cancelled() { RunStatus::Cancelled } else { RunStatus::Completed };
+            #[cfg(feature = "metrics")]
+            { run.metrics = Some(metrics); }
+        })
+    }
+
+    #[cfg(feature = "metrics")]
+    fn metrics_snapshot(&mut self, snapshot: MetricsSnapshot) -> Result<()> {
+        let snapshot = serde_json::to_value(snapshot)?;
+        Runs::update(&self.runs, self.id, |run| run.metrics = Some(snapshot))
+    }
+
+    fn wait_for_submission(&mut self) -> Result<Option<Submission>> {
+        Ok(None)
+    }
+
+    fn coordinator_is_exiting(&mut self, result: Result<()>) -> Result<()> {
+        if let Err(e) = result {
+            Runs::update(&self.runs, self.id, |run| {
+                run.status = RunStatus::Failed;
+                run.error = Some(e.to_string());
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, Read, Write};
+    use std::net::TcpStream;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    use portpicker::pick_unused_port;
+    use serde_json::json;
+    use simpath::Simpath;
+    use url::Url;
+
+    use flowcore::meta_provider::MetaProvider;
+
+    use crate::cli::auth::ClientTokens;
+
+    use super::{bearer_token, HttpSubmissionListener, MAX_ENDED_RUNS, MAX_HEADERS, read_head,
+                RequestHead, Runs, RunsApi, RunStatus};
+
+    fn api() -> RunsApi {
+        let (sender, _) = channel();
+        RunsApi {
+            runs: Arc::new(Mutex::new(Runs::default())),
+            submissions: sender,
+            provider: Arc::new(MetaProvider::new(Simpath::new("FLOW_LIB_PATH"),
+                                                 PathBuf::from("/"))),
+            base_url: Url::parse("file:///").expect("Could not parse url"),
+            tokens: None,
+        }
+    }
+
+    fn authenticated_api() -> RunsApi {
+        RunsApi {
+            tokens: Some(ClientTokens::parse("alice=secret1".lines())
+                .expect("Could not parse tokens")),
+            ..api()
+        }
+    }
+
+    #[test]
+    fn unknown_endpoint() {
+        assert_eq!(api().route("GET", "/flows", None, "").0, 404);
+    }
+
+    #[test]
+    fn method_not_allowed() {
+        assert_eq!(api().route("PUT", "/runs/1", None, "").0, 405);
+    }
+
+    #[test]
+    fn unknown_run() {
+        let api = api();
+        assert_eq!(api.route("GET", "/runs/1", None, "").0, 404);
+        assert_eq!(api.route("GET", "/runs/one/metrics", None, "").0, 404);
+        assert_eq!(api.route("DELETE", "/runs/1", None, "").0, 404);
+    }
+
+    #[test]
+    fn submission_without_manifest() {
+        let api = api();
+        assert_eq!(api.route("POST", "/runs", None, "not json").0, 400);
+        assert_eq!(api.route("POST", "/runs", None, "{\"args\": []}").0, 400);
+    }
+
+    #[test]
+    fn submission_of_missing_manifest() {
+        assert_eq!(api().route("POST", "/runs", None,
+                               "{\"manifest\": \"file:///no/such/manifest.json\"}").0, 400);
+    }
+
+    #[test]
+    fn status_and_cancel_of_queued_run() {
+        let api = api();
+        let id = api.runs.lock().expect("Could not lock runs")
+            .add(&Url::parse("file:///manifest.json").expect("Could not parse url"), None);
+
+        let (status, body) = api.route("GET", &format!("/runs/{id}"), None, "");
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({"id": id, "manifest": "file:///manifest.json", "status": "queued"}));
+
+        assert_eq!(api.route("DELETE", &format!("/runs/{id}"), None, "").0, 202);
+        assert_eq!(api.runs.lock().expect("Could not lock runs").runs.get(&id)
+                       .map(|run| run.status), Some(RunStatus::Cancelled));
+        assert_eq!(api.route("DELETE", &format!("/runs/{id}"), None, "").0, 409);
+    }
+
+    #[test]
+    fn unauthenticated_submission_rejected() {
+        let api = authenticated_api();
+        let body = "{\"manifest\": \"file:///no/such/manifest.json\"}";
+        assert_eq!(api.route("POST", "/runs", None, body).0, 401);
+        assert_eq!(api.route("POST", "/runs", Some("secret"), body).0, 401);
+        assert!(api.runs.lock().expect("Could not lock runs").runs.is_empty());
+    }
+
+    #[test]
+    fn authenticated_submission_accepted() {
+        // the manifest does not exist, so it is not submitted, but the request gets past authentication
+        assert_eq!(authenticated_api().route("POST", "/runs", Some("secret1"),
+                                             "{\"manifest\": \"file:///no/such/manifest.json\"}").0, 400);
+    }
+
+    #[test]
+    fn runs_of_other_clients_not_found() {
+        let api = RunsApi {
+            tokens: Some(ClientTokens::parse("alice=secret1\nbob=secret2".lines())
+                .expect("Could not parse tokens")),
+            ..api()
+        };
+        let id = api.runs.lock().expect("Could not lock runs")
+            .add(&Url::parse("file:///manifest.json").expect("Could not parse url"), Some("alice"));
+
+        assert_eq!(api.route("GET", &format!("/runs/{id}"), Some("secret1"), "").0, 200);
+        assert_eq!(api.route("GET", &format!("/runs/{id}"), Some("secret2"), "").0, 404);
+        assert_eq!(api.route("GET", &format!("/runs/{id}/metrics"), Some("secret2"), "").0, 404);
+        assert_eq!(api.route("DELETE", &format!("/runs/{id}"), Some("secret2"), "").0, 404);
+        assert_eq!(api.runs.lock().expect("Could not lock runs").runs.get(&id)
+                       .map(|run| run.status), Some(RunStatus::Queued));
+    }
+
+    #[test]
+    fn parse_bearer_token() {
+        assert_eq!(bearer_token(" Bearer secret1 "), Some("secret1".into()));
+        assert_eq!(bearer_token("bearer secret1"), Some("secret1".into()));
+        assert_eq!(bearer_token("Basic YWxpY2U6c2VjcmV0MQ=="), None);
+        assert_eq!(bearer_token("secret1"), None);
+    }
+
+    #[test]
+    fn read_request_head() {
+        let request = "POST /runs HTTP/1.1\r\nContent-Length: 2\r\nAuthorization: Bearer secret1\r\n\r\n{}";
+        assert_eq!(read_head(&mut Cursor::new(request)).expect("Could not read head"),
+                   Some(RequestHead {
+                       method: "POST".into(),
+                       path: "/runs".into(),
+                       content_length: 2,
+                       token: Some("secret1".into()),
+                   }));
+    }
+
+    #[test]
+    fn header_too_long() {
+        let request = format!("GET /runs/1 HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(10_000));
+        assert_eq!(read_head(&mut Cursor::new(request)).expect("Could not read head"), None);
+    }
+
+    #[test]
+    fn too_many_headers() {
+        let request = format!("GET /runs/1 HTTP/1.1\r\n{}\r\n", "X-Header: a\r\n".repeat(MAX_HEADERS + 1));
+        assert_eq!(read_head(&mut Cursor::new(request)).expect("Could not read head"), None);
+    }
+
+    #[test]
+    fn earliest_ended_runs_evicted() {
+        let mut runs = Runs::default();
+        let manifest = Url::parse("file:///manifest.json").expect("Could not parse url");
+        let running = runs.add(&manifest, None);
+        if let Some(run) = runs.runs.get_mut(&running) {
+            run.status = RunStatus::Running;
+        }
+        for _ in 0..=MAX_ENDED_RUNS {
+            let id = runs.add(&manifest, None);
+            if let Some(run) = runs.runs.get_mut(&id) {
+                run.status = RunStatus::Completed;
+            }
+        }
+
+        let latest = runs.add(&manifest, None);
+        assert!(runs.runs.contains_key(&running));
+        assert!(!runs.runs.contains_key(&(running + 1)));
+        assert!(runs.runs.contains_key(&(running + 2)));
+        assert!(runs.runs.contains_key(&latest));
+        assert_eq!(runs.runs.len(), MAX_ENDED_RUNS + 2);
+    }
+
+    #[test]
+    fn idle_connection_does_not_block_others() {
+        let port = pick_unused_port().expect("No ports free");
+        let _listener = HttpSubmissionListener::bind(port, None, api().provider,
+                                                     Url::parse("file:///").expect("Could not parse url"),
+                                                     None).expect("Could not bind REST API");
+
+        let _idle = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect");
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect");
+        stream.set_read_timeout(Some(Duration::from_secs(5))).expect("Could not set timeout");
+        write!(stream, "GET /runs/1 HTTP/1.1\r\n\r\n").expect("Could not send request");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).expect("Could not read response");
+        assert!(response.starts_with("HTTP/1.1 404"));
+    }
+}
@@ -1,5 +1,7 @@
 #[allow(clippy::module_name_repetitions)]
 pub mod cli_client;
+/// authentication of the clients submitting flows to a coordinator, using tokens
+pub mod auth;
 pub(crate) mod test_helper;
 /// 'debug' defines structs passed between the Server and the Client regarding debug events
 /// and client responses to them
@@ -14,4 +16,6 @@ pub mod cli_debug_handler;
 #[allow(clippy::module_name_repetitions)]
 pub mod cli_submission_handler;
 pub mod connections;
+/// a REST API over HTTP for submitting flows to a coordinator, and querying and cancelling their runs
+pub mod http_server;
 pub mod coordinator_message;
@@ -5,7 +5,7 @@ pub mod test {
     use portpicker::pick_unused_port;
 
     use crate::cli::connections::{ClientConnection, CoordinatorConnection, discover_service,
-                                  enable_service_discovery, WAIT};
+                                  enable_service_discovery, Transport, WAIT};
     use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
 
     pub fn wait_for_then_send(
@@ -14,19 +14,19 @@ pub mod test {
     ) -> Arc<Mutex<CoordinatorConnection>> {
         let test_port = pick_unused_port().expect("No ports free");
         let server_connection = Arc::new(Mutex::new(
-            CoordinatorConnection::new("foo", test_port)
+            CoordinatorConnection::new("foo", test_port, None, Transport::Zmq, None)
                 .expect("Could not create server connection"),
         ));
         let discovery_port = pick_unused_port().expect("No ports free");
         enable_service_discovery(discovery_port, "foo",
-                                 test_port).expect("Could not enable service discovery");
+                                 test_port, None).expect("Could not enable service discovery");
 
         let connection = server_connection.lock()
             .expect("Could not get access to server connection");
 
         let server_address = discover_service(discovery_port, "foo")
             .expect("Could discovery service");
-        let client_connection = ClientConnection::new(&server_address)
+        let client_connection = ClientConnection::new(&server_address, Transport::Zmq, None)
             .expect("Could not create ClientConnection");
 
         // First message must come from the client to open the connection
@@ -57,7 +57,7 @@ mod test {
 
     use flowcore::{DONT_RUN_AGAIN, Implementation};
 
-    use crate::cli::connections::CoordinatorConnection;
+    use crate::cli::connections::{CoordinatorConnection, Transport};
     use crate::cli::coordinator_message::ClientMessage::Args;
     use crate::cli::coordinator_message::CoordinatorMessage::GetArgs;
     use crate::cli::test_helper::test::wait_for_then_send;
@@ -70,7 +70,7 @@ mod test {
         let test_port = pick_unused_port().expect("No ports free");
         let getter = &Get {
             server_connection: Arc::new(Mutex::new(
-                CoordinatorConnection::new("foo", test_port)
+                CoordinatorConnection::new("foo", test_port, None, Transport::Zmq, None)
                     .expect("Could not create server connection"),
             )),
         } as &dyn Implementation;
@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+
+use crate::cli::connections::CoordinatorConnection;
+use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+
+/// `Implementation` struct for the `get` function
+pub struct Get {
+    /// It holds a reference to the runtime client in order to get the environment variable
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for Get {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let name = inputs.first().and_then(Value::as_str)
+            .ok_or("Could not get the name of the environment variable")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::GetEnvVar(name.into())) {
+            Ok(ClientMessage::EnvVar(value)) => Ok((value.map(|value| json!(value)), RUN_AGAIN)),
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use serial_test::serial;
+
+    use flowcore::{Implementation, RUN_AGAIN};
+
+    use crate::cli::coordinator_message::ClientMessage::{EnvVar, Error};
+    use crate::cli::coordinator_message::CoordinatorMessage::GetEnvVar;
+    use crate::cli::test_helper::test::wait_for_then_send;
+
+    use super::Get;
+
+    #[test]
+    #[serial]
+    fn gets_env_var() {
+        let server_connection = wait_for_then_send(GetEnvVar("HOST".into()),
+                                                   EnvVar(Some("example.com".into())));
+        let getter = &Get { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = getter.run(&[json!("HOST")]).expect("_get() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!("example.com")));
+    }
+
+    #[test]
+    #[serial]
+    fn env_var_not_set() {
+        let server_connection = wait_for_then_send(GetEnvVar("HOST".into()), EnvVar(None));
+        let getter = &Get { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = getter.run(&[json!("HOST")]).expect("_get() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn env_var_not_allowed() {
+        let server_connection = wait_for_then_send(GetEnvVar("HOST".into()),
+                                                   Error("not allowed".into()));
+        let getter = &Get { server_connection } as &dyn Implementation;
+
+        assert!(getter.run(&[json!("HOST")]).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use flowcore::{DONT_RUN_AGAIN, Implementation, RunAgain};
+use flowcore::errors::Result;
+
+use crate::cli::connections::CoordinatorConnection;
+use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+
+/// `Implementation` struct for the `vars` function
+pub struct Vars {
+    /// It holds a reference to the runtime client in order to get the environment variables
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for Vars {
+    fn run(&self, _inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::GetEnvVars) {
+            Ok(ClientMessage::EnvVars(vars)) => Ok((Some(json!(vars)), DONT_RUN_AGAIN)),
+            _ => Ok((None, DONT_RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+    use serial_test::serial;
+
+    use flowcore::{DONT_RUN_AGAIN, Implementation};
+
+    use crate::cli::coordinator_message::ClientMessage::EnvVars;
+    use crate::cli::coordinator_message::CoordinatorMessage::GetEnvVars;
+    use crate::cli::test_helper::test::wait_for_then_send;
+
+    use super::Vars;
+
+    #[test]
+    #[serial]
+    fn gets_env_vars() {
+        let vars = BTreeMap::from([("HOST".to_string(), "example.com".to_string())]);
+        let server_connection = wait_for_then_send(GetEnvVars, EnvVars(vars));
+        let getter = &Vars { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = getter.run(&[]).expect("_vars() failed");
+
+        assert_eq!(run_again, DONT_RUN_AGAIN);
+        assert_eq!(value, Some(json!({"HOST": "example.com"})));
+    }
+}
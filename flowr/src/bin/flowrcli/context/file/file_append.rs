@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowcore::model::bytes::Bytes;
+use serde_json::Value;
+
+use crate::cli::connections::CoordinatorConnection;
+use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+
+/// `Implementation` struct for the `file_append` function
+pub struct FileAppend {
+    /// It holds a reference to the runtime client in order to append to files
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for FileAppend {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let filename = inputs.first().and_then(Value::as_str).ok_or("Could not get filename")?;
+        let bytes = Bytes::from_value(inputs.get(1).ok_or("Could not get bytes")?)?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::Append(filename.into(), bytes)) {
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use flowcore::model::bytes::Bytes;
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+    use crate::cli::test_helper::test::wait_for_then_send;
+
+    use super::FileAppend;
+
+    #[test]
+    #[serial]
+    fn append_file() {
+        let file_path = "/fake/append_test";
+        let file_contents = "test text".as_bytes().to_vec();
+        let inputs = [json!(file_path), json!(file_contents)];
+        let file_append_message = CoordinatorMessage::Append(file_path.to_string(), file_contents.into());
+
+        let server_connection = wait_for_then_send(file_append_message, ClientMessage::Ack);
+
+        let appender = &FileAppend { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = appender.run(&inputs).expect("_file_append() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn append_missing_file() {
+        let file_path = "/fake/missing";
+        let file_contents = "test text".as_bytes().to_vec();
+        let inputs = [json!(file_path), json!(file_contents)];
+        let file_append_message = CoordinatorMessage::Append(file_path.to_string(), file_contents.into());
+
+        let server_connection = wait_for_then_send(file_append_message,
+                                                   ClientMessage::Error("No such file".into()));
+
+        let appender = &FileAppend { server_connection } as &dyn Implementation;
+
+        assert!(appender.run(&inputs).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn append_bytes() {
+        let file_path = "/fake/append_test";
+        let file_contents = vec![0, 128, 255];
+        let inputs = [json!(file_path), Bytes::from(file_contents.clone()).to_value()];
+        let file_append_message = CoordinatorMessage::Append(file_path.to_string(), file_contents.into());
+
+        let server_connection = wait_for_then_send(file_append_message, ClientMessage::Ack);
+
+        let appender = &FileAppend { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = appender.run(&inputs).expect("_file_append() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn append_invalid_bytes() {
+        let inputs = [json!("/fake/append_test"), json!([1, 256])];
+        let server_connection = wait_for_then_send(CoordinatorMessage::Append(String::new(), vec![].into()),
+                                                   ClientMessage::Ack);
+        let appender = &FileAppend { server_connection } as &dyn Implementation;
+        assert!(appender.run(&inputs).is_err());
+    }
+}
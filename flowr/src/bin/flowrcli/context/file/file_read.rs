@@ -26,8 +26,8 @@ impl Implementation for FileRead {
         match response {
             Ok(ClientMessage::FileContents(_path, bytes)) => {
                 let mut output_map = serde_json::Map::new();
-                output_map.insert("bytes".into(), json!(bytes));
-                let string = String::from_utf8(bytes)
+                output_map.insert("bytes".into(), bytes.to_value());
+                let string = String::from_utf8(bytes.into_vec())
                     .map_err(|_| "Could not create Utf8 String")?;
                 output_map.insert("string".into(), json!(string));
                 Ok((Some(Value::Object(output_map)), RUN_AGAIN))
@@ -40,6 +40,7 @@ impl Implementation for FileRead {
 #[cfg(test)]
 mod test {
     use flowcore::{Implementation, RUN_AGAIN};
+    use flowcore::model::bytes::Bytes;
     use serde_json::{json, Value};
     use serial_test::serial;
 
@@ -61,7 +62,7 @@ mod test {
         let file_read_message = CoordinatorMessage::Read(file_path.to_string());
 
         let server_connection = wait_for_then_send(file_read_message,
-        FileContents(file_path.to_string(), file_contents));
+        FileContents(file_path.to_string(), file_contents.clone().into()));
 
         let reader = &FileRead { server_connection } as &dyn Implementation;
 
@@ -72,6 +73,9 @@ mod test {
             Some(Value::Object(map)) => {
                 assert_eq!(map.get("string").expect("Could not get file contents as string"),
                            &json!(file_string));
+                let bytes = map.get("bytes").expect("Could not get file contents as bytes");
+                assert_eq!(Bytes::from_value(bytes).expect("Could not get bytes").into_vec(),
+                           file_contents);
             },
             _ => panic!("Did not get back FileContents")
         }
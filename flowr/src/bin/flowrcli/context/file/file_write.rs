@@ -2,6 +2,7 @@ use std::sync::{Arc, Mutex};
 
 use flowcore::{Implementation, RUN_AGAIN, RunAgain};
 use flowcore::errors::Result;
+use flowcore::model::bytes::Bytes;
 use serde_json::Value;
 
 use crate::cli::connections::CoordinatorConnection;
@@ -16,18 +17,11 @@ pub struct FileWrite {
 impl Implementation for FileWrite {
     fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
         let filename = inputs.first().ok_or("Could not get filename")?;
-        let bytes = inputs.get(1).ok_or("Could not get bytes")?;
+        let bytes = Bytes::from_value(inputs.get(1).ok_or("Could not get bytes")?)?;
 
         let mut server = self.server_connection.lock()
             .map_err(|_| "Could not lock server")?;
 
-        let byte_array = bytes.as_array().ok_or("Could not get bytes")?;
-
-        #[allow(clippy::cast_possible_truncation)]
-        let bytes = byte_array
-            .iter()
-            .map(|byte_value| byte_value.as_u64().unwrap_or(0) as u8)
-            .collect();
         let _ = server.send_and_receive_response::<CoordinatorMessage, ClientMessage>(CoordinatorMessage::Write(
             filename.as_str().unwrap_or("").to_string(),
             bytes,
@@ -40,6 +34,7 @@ impl Implementation for FileWrite {
 #[cfg(test)]
 mod test {
     use flowcore::{Implementation, RUN_AGAIN};
+    use flowcore::model::bytes::Bytes;
     use serde_json::json;
     use serial_test::serial;
 
@@ -54,7 +49,25 @@ mod test {
         let file_path = "/fake/write_test";
         let file_contents = "test text".as_bytes().to_vec();
         let inputs = [json!(file_path), json!(file_contents)];
-        let file_write_message = CoordinatorMessage::Write(file_path.to_string(), file_contents);
+        let file_write_message = CoordinatorMessage::Write(file_path.to_string(), file_contents.into());
+
+        let server_connection = wait_for_then_send(file_write_message, ClientMessage::Ack);
+
+        let writer = &FileWrite { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = writer.run(&inputs).expect("_file_write() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn write_bytes() {
+        let file_path = "/fake/write_test";
+        let file_contents = vec![0, 128, 255];
+        let inputs = [json!(file_path), Bytes::from(file_contents.clone()).to_value()];
+        let file_write_message = CoordinatorMessage::Write(file_path.to_string(), file_contents.into());
 
         let server_connection = wait_for_then_send(file_write_message, ClientMessage::Ack);
 
@@ -65,4 +78,14 @@ mod test {
         assert_eq!(run_again, RUN_AGAIN);
         assert_eq!(value, None);
     }
+
+    #[test]
+    #[serial]
+    fn write_invalid_bytes() {
+        let inputs = [json!("/fake/write_test"), json!([1, 256])];
+        let server_connection = wait_for_then_send(CoordinatorMessage::Write(String::new(), vec![].into()),
+                                                   ClientMessage::Ack);
+        let writer = &FileWrite { server_connection } as &dyn Implementation;
+        assert!(writer.run(&inputs).is_err());
+    }
 }
@@ -0,0 +1,75 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use serde_json::{json, Value};
+
+use crate::cli::connections::CoordinatorConnection;
+use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+
+/// `Implementation` struct for the `list` function
+pub struct List {
+    /// It holds a reference to the runtime client in order to list directories
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for List {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let path = inputs.first().and_then(Value::as_str).ok_or("Could not get path")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::List(path.into())) {
+            Ok(ClientMessage::DirectoryEntries(paths)) => {
+                let names: Vec<String> = paths.iter()
+                    .map(|path| Path::new(path).file_name()
+                        .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned()))
+                    .collect();
+                let mut output_map = serde_json::Map::new();
+                output_map.insert("paths".into(), json!(paths));
+                output_map.insert("names".into(), json!(names));
+                Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+            }
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::cli::coordinator_message::ClientMessage::{DirectoryEntries, Error};
+    use crate::cli::coordinator_message::CoordinatorMessage;
+    use crate::cli::test_helper::test::wait_for_then_send;
+
+    use super::List;
+
+    #[test]
+    #[serial]
+    fn list_directory() {
+        let server_connection = wait_for_then_send(CoordinatorMessage::List("/tmp".into()),
+            DirectoryEntries(vec!["/tmp/a.txt".into(), "/tmp/b".into()]));
+        let lister = &List { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = lister.run(&[json!("/tmp")]).expect("list() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!({"paths": ["/tmp/a.txt", "/tmp/b"], "names": ["a.txt", "b"]})));
+    }
+
+    #[test]
+    #[serial]
+    fn list_missing_directory() {
+        let server_connection = wait_for_then_send(CoordinatorMessage::List("/missing".into()),
+            Error("Could not list directory".into()));
+        let lister = &List { server_connection } as &dyn Implementation;
+
+        assert!(lister.run(&[json!("/missing")]).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use serde_json::{json, Value};
+
+use crate::cli::connections::CoordinatorConnection;
+use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+
+/// `Implementation` struct for the `stat` function
+pub struct Stat {
+    /// It holds a reference to the runtime client in order to get file metadata
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for Stat {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let path = inputs.first().and_then(Value::as_str).ok_or("Could not get path")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::Stat(path.into())) {
+            Ok(ClientMessage::Metadata(metadata)) => {
+                let mut output_map = serde_json::Map::new();
+                output_map.insert("is_file".into(), json!(metadata.is_file));
+                output_map.insert("is_dir".into(), json!(metadata.is_dir));
+                output_map.insert("size".into(), json!(metadata.size));
+                if let Some(modified) = metadata.modified {
+                    output_map.insert("modified".into(), json!(modified));
+                }
+                Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+            }
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::cli::coordinator_message::{CoordinatorMessage, FileMetaData};
+    use crate::cli::coordinator_message::ClientMessage::Metadata;
+    use crate::cli::test_helper::test::wait_for_then_send;
+
+    use super::Stat;
+
+    #[test]
+    #[serial]
+    fn stat_file() {
+        let metadata = FileMetaData {
+            is_file: true,
+            is_dir: false,
+            size: 42,
+            modified: Some(1_700_000_000),
+        };
+        let server_connection = wait_for_then_send(CoordinatorMessage::Stat("file".into()),
+                                                   Metadata(metadata));
+        let stat = &Stat { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = stat.run(&[json!("file")]).expect("stat() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!({"is_file": true, "is_dir": false, "size": 42,
+            "modified": 1_700_000_000})));
+    }
+}
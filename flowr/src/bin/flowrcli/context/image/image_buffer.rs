@@ -2,6 +2,7 @@ use std::sync::{Arc, Mutex};
 
 use flowcore::{Implementation, RUN_AGAIN, RunAgain};
 use flowcore::errors::Result;
+use flowcore::model::bytes::Bytes;
 use serde_json::Value;
 
 use crate::cli::connections::CoordinatorConnection;
@@ -17,7 +18,7 @@ impl Implementation for ImageBuffer {
     #[allow(clippy::many_single_char_names)]
     fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
         let pixel = inputs.first().ok_or("Could not get pixels")?.as_array().ok_or("Could not get pixels")?;
-        let value = inputs.get(1).ok_or("Could not get value")?.as_array().ok_or("Could not get value")?;
+        let value = Bytes::from_value(inputs.get(1).ok_or("Could not get value")?)?;
         let size = inputs.get(2).ok_or("Could not get size")?.as_array().ok_or("Could not get size")?;
         let filename = inputs.get(3).ok_or("Could not get filename")?.as_str().ok_or("Could not get filename")?;
 
@@ -26,9 +27,9 @@ impl Implementation for ImageBuffer {
 
         let x = pixel.first().ok_or("Could not get x")?.as_u64().ok_or("Could not get x")?;
         let y = pixel.get(1).ok_or("Could not get y")?.as_u64().ok_or("Could not get y")?;
-        let r = value.first().ok_or("Could not get r")?.as_u64().ok_or("Could not get r")?;
-        let g = value.get(1).ok_or("Could not get g")?.as_u64().ok_or("Could not get g")?;
-        let b = value.get(2).ok_or("Could not get b")?.as_u64().ok_or("Could not get b")?;
+        let r = *value.first().ok_or("Could not get r")?;
+        let g = *value.get(1).ok_or("Could not get g")?;
+        let b = *value.get(2).ok_or("Could not get b")?;
         let w = size.first().ok_or("Could not get w")?.as_u64().ok_or("Could not get w")?;
         let h = size.get(1).ok_or("Could not get h")?.as_u64().ok_or("Could not get h")?;
 
@@ -36,9 +37,7 @@ impl Implementation for ImageBuffer {
                 (
                     u32::try_from(x).map_err(|_| "Integer overflow in 'x'")?,
                     u32::try_from(y).map_err(|_| "Integer overflow in 'y'")?),
-                (u8::try_from(r).map_err(|_| "Integer overflow in 'r'")?,
-                 u8::try_from(g).map_err(|_| "Integer overflow in 'g'")?,
-                 u8::try_from(b).map_err(|_| "Integer overflow in 'b'")?),
+                (r, g, b),
                 (u32::try_from(w).map_err(|_| "Integer overflow in 'w'")?,
                  u32::try_from(h).map_err(|_| "Integer overflow in 'h'")?),
                 filename.to_string(),
@@ -51,6 +50,7 @@ impl Implementation for ImageBuffer {
 #[cfg(test)]
 mod test {
     use flowcore::{Implementation, RUN_AGAIN};
+    use flowcore::model::bytes::Bytes;
     use serde_json::json;
     use serial_test::serial;
 
@@ -96,4 +96,37 @@ mod test {
         assert_eq!(run_again, RUN_AGAIN);
         assert_eq!(value, None);
     }
+
+    #[test]
+    #[serial]
+    fn valid_bytes() {
+        let pixel = (0, 0);
+        let color = (1, 2, 3);
+        let size = (1, 3);
+        let buffer_name = "image_buffer.png".into();
+        let inputs = [json!(pixel), Bytes::from(vec![1, 2, 3]).to_value(), json!(size),
+            json!(buffer_name)];
+        let pixel = CoordinatorMessage::PixelWrite(pixel, color, size, buffer_name);
+
+        let server_connection = wait_for_then_send(pixel, ClientMessage::Ack);
+        let buffer = &ImageBuffer { server_connection } as &dyn Implementation;
+        let (value, run_again) = buffer.run(&inputs).expect("run() failed");
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_color() {
+        let pixel = (0, 0);
+        let color = (1, 2, 3);
+        let size = (1, 3);
+        let buffer_name = "image_buffer.png".into();
+        let inputs = [json!(pixel), json!([1, 2, 256]), json!(size), json!(buffer_name)];
+        let pixel = CoordinatorMessage::PixelWrite(pixel, color, size, buffer_name);
+
+        let server_connection = wait_for_then_send(pixel, ClientMessage::Ack);
+        let buffer = &ImageBuffer { server_connection } as &dyn Implementation;
+        assert!(buffer.run(&inputs).is_err());
+    }
 }
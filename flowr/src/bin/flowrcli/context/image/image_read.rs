@@ -0,0 +1,71 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use serde_json::{json, Value};
+
+use crate::cli::connections::CoordinatorConnection;
+use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+
+/// `Implementation` struct for the `image_read` function
+pub struct ImageRead {
+    /// It holds a reference to the runtime client in order to read images
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for ImageRead {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let filename = inputs.first().and_then(Value::as_str).ok_or("Could not get filename")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::ImageRead(filename.into())) {
+            Ok(ClientMessage::Image(width, height, bytes)) => {
+                let pixels: Vec<Value> = bytes.chunks_exact(3).map(|rgb| json!(rgb)).collect();
+                let mut output_map = serde_json::Map::new();
+                output_map.insert("width".into(), json!(width));
+                output_map.insert("height".into(), json!(height));
+                output_map.insert("pixels".into(), Value::Array(pixels));
+                Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+            }
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+    use crate::cli::test_helper::test::wait_for_then_send;
+
+    use super::ImageRead;
+
+    #[test]
+    #[serial]
+    fn read_image() {
+        let server_connection = wait_for_then_send(CoordinatorMessage::ImageRead("image.png".into()),
+            ClientMessage::Image(2, 1, vec![255, 0, 0, 0, 0, 255]));
+        let reader = &ImageRead { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = reader.run(&[json!("image.png")]).expect("_image_read() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!({"width": 2, "height": 1, "pixels": [[255, 0, 0], [0, 0, 255]]})));
+    }
+
+    #[test]
+    #[serial]
+    fn read_invalid_image() {
+        let server_connection = wait_for_then_send(CoordinatorMessage::ImageRead("image.png".into()),
+            ClientMessage::Error("Could not read image".into()));
+        let reader = &ImageRead { server_connection } as &dyn Implementation;
+
+        assert!(reader.run(&[json!("image.png")]).is_err());
+    }
+}
@@ -1,3 +1,5 @@
 /// A module with functions for writing images
 #[allow(clippy::module_name_repetitions)]
-pub mod image_buffer;
\ No newline at end of file
+pub mod image_buffer;
+/// the `image_read` module to read images for a flow
+pub mod image_read;
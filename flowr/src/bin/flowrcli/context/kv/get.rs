@@ -0,0 +1,78 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+
+use crate::cli::connections::CoordinatorConnection;
+use crate::cli::coordinator_message::{ClientMessage, CoordinatorMessage};
+
+/// `Implementation` struct for the `get` function
+pub struct Get {
+    /// It holds a reference to the runtime client in order to get the value from its store
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for Get {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let key = inputs.first().and_then(Value::as_str).ok_or("Could not get key")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::KvGet(key.into())) {
+            Ok(ClientMessage::KvValue(value)) => Ok((value, RUN_AGAIN)),
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use serial_test::serial;
+
+    use flowcore::{Implementation, RUN_AGAIN};
+
+    use crate::cli::coordinator_message::ClientMessage::{Error, KvValue};
+    use crate::cli::coordinator_message::CoordinatorMessage::KvGet;
+    use crate::cli::test_helper::test::wait_for_then_send;
+
+    use super::Get;
+
+    #[test]
+    #[serial]
+    fn get_value() {
+        let server_connection = wait_for_then_send(KvGet("count".into()), KvValue(Some(json!(42))));
+        let getter = &Get { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = getter.run(&[json!("count")]).expect("_get() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!(42)));
+    }
+
+    #[test]
+    #[serial]
+    fn get_missing_value() {
+        let server_connection = wait_for_then_send(KvGet("count".into()), KvValue(None));
+        let getter = &Get { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = getter.run(&[json!("count")]).expect("_get() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn get_error() {
+        let server_connection = wait_for_then_send(KvGet("count".into()),
+                                                   Error("Could not read store".into()));
+        let getter = &Get { server_connection } as &dyn Implementation;
+
+        assert!(getter.run(&[json!("count")]).is_err());
+    }
+}
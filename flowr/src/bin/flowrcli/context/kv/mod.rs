@@ -0,0 +1,6 @@
+/// The `get` module to get a value from the key-value store
+pub mod get;
+/// The `put` module to store a value in the key-value store
+pub mod put;
+/// The `delete` module to delete a value from the key-value store
+pub mod delete;
@@ -0,0 +1,2 @@
+/// The `tcp_send` module to send bytes to a TCP server
+pub mod tcp_send;
@@ -1,5 +1,7 @@
 /// the `stdout` module to allow a flow to send to the stdout function
 pub mod stdout;
+/// the `stdout_styled` module to allow a flow to send styled text to stdout
+pub mod stdout_styled;
 /// the `stderr` module to allow a flow to send to the stderr function
 pub mod stderr;
 /// the `stdin` module to allow a flow to get from the stdin function
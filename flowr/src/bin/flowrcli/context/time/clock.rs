@@ -0,0 +1,43 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+
+/// `Implementation` struct for the `clock` function
+pub struct Clock;
+
+impl Implementation for Clock {
+    fn run(&self, _inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Could not read the clock: {e}"))?;
+        let millis = u64::try_from(since_epoch.as_millis())?;
+
+        Ok((Some(json!(millis)), RUN_AGAIN))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use serde_json::json;
+
+    use flowcore::{Implementation, RUN_AGAIN};
+
+    use super::Clock;
+
+    #[test]
+    fn reads_clock() {
+        let before = SystemTime::now().duration_since(UNIX_EPOCH)
+            .expect("Could not read the clock").as_millis();
+        let (value, run_again) = Clock.run(&[json!(true)]).expect("_clock() failed");
+        let after = SystemTime::now().duration_since(UNIX_EPOCH)
+            .expect("Could not read the clock").as_millis();
+
+        assert_eq!(run_again, RUN_AGAIN);
+        let millis = u128::from(value.and_then(|v| v.as_u64()).expect("Could not get millis"));
+        assert!(before <= millis && millis <= after);
+    }
+}
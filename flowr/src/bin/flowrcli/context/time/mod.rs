@@ -0,0 +1,2 @@
+/// the `clock` module to allow a flow to read the time from the clock of the runner
+pub mod clock;
@@ -12,7 +12,8 @@
 //! Depending on the command line options supplied `flowr` executes the
 //! [`Coordinator`][flowrlib::coordinator::Coordinator] of flow execution in a background thread,
 //! or the [`cli::cli_client`] in the main thread (where the interaction with STDIO and
-//! File System happens) or both. They communicate via network messages using the
+//! File System happens) or both. They communicate via network messages (or in-process channels
+//! with `--single-process`) using the
 //! [`SubmissionHandler`][flowrlib::submission_handler::SubmissionHandler] to submit flows for execution,
 //! and interchanging [`ClientMessages`][crate::cli::coordinator_message::ClientMessage]
 //! and [`CoordinatorMessages`][crate::cli::coordinator_message::CoordinatorMessage] for execution of context
@@ -20,18 +21,25 @@
 //! [`Executors`][flowrlib::executor::Executor]
 
 use core::str::FromStr;
+use std::ffi::OsStr;
+use std::io;
+use std::net::IpAddr;
 use std::path::PathBuf;
 use std::process::exit;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{env, thread};
 
-use clap::{Arg, ArgMatches, Command};
+use clap::{Arg, ArgMatches, Command, ValueHint};
+use clap_complete::{CompleteEnv, generate, Shell};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate, PathCompleter, ValueCompleter};
 use env_logger::Builder;
 use log::{error, info, trace, LevelFilter};
 use portpicker::pick_unused_port;
 use simpath::Simpath;
 use url::Url;
 
+use cli::auth::{client_token, ClientTokens};
 use cli::cli_client::CliRuntimeClient;
 #[cfg(feature = "debugger")]
 use cli::cli_debug_client::CliDebugClient;
@@ -42,6 +50,7 @@ use cli::cli_submission_handler::CLISubmissionHandler;
 use cli::connections::ClientConnection;
 use cli::connections::CoordinatorConnection;
 use cli::coordinator_message::ClientMessage;
+use cli::http_server::HttpSubmissionListener;
 #[cfg(feature = "debugger")]
 use cli::debug_message::DebugServerMessage;
 #[cfg(feature = "debugger")]
@@ -49,24 +58,38 @@ use cli::debug_message::DebugServerMessage::{
     BlockBreakpoint, DataBreakpoint, ExecutionEnded, ExecutionStarted, ExitingDebugger,
     JobCompleted, JobError, Panic, PriorToSendingJob, Resetting, WaitingForCommand,
 };
+use flowcore::config::Config;
+use flowcore::examples::find_examples;
 use flowcore::errors::{Result, ResultExt};
 use flowcore::meta_provider::MetaProvider;
 use flowcore::model::flow_manifest::FlowManifest;
-use flowcore::model::submission::Submission;
+use flowcore::model::submission::{
+    CheckpointPolicy, DEFAULT_CHECKPOINT_INTERVAL, DEFAULT_RETRY_BACKOFF, JobTimeoutPolicy,
+    ResourceLimits, RetryPolicy, Submission,
+};
 use flowcore::provider::Provider;
 use flowcore::url_helper::url_from_string;
 use flowrlib::coordinator::Coordinator;
 use flowrlib::dispatcher::Dispatcher;
+use flowrlib::event_sink::JsonEventLog;
 use flowrlib::executor::Executor;
 use flowrlib::info as flowrlib_info;
+use flowrlib::security::CurveKeys;
 use flowrlib::services::{
-    CONTROL_SERVICE_NAME, JOB_QUEUES_DISCOVERY_PORT, JOB_SERVICE_NAME, RESULTS_JOB_SERVICE_NAME,
+    bind_endpoint, local_endpoint, CONTROL_SERVICE_NAME, JOB_QUEUES_DISCOVERY_PORT,
+    JOB_SERVICE_NAME, NATIVE_JOB_SERVICE_NAME, RESULTS_JOB_SERVICE_NAME,
 };
+use flowrlib::transport::{ChannelJobTransport, ExecutorConnector, JobQueue, ZmqConnector};
 
 use crate::cli::connections::{
-    discover_service, enable_service_discovery, COORDINATOR_SERVICE_NAME, DEBUG_SERVICE_NAME,
+    connection_pair, discover_service, enable_service_discovery, Transport,
+    COORDINATOR_SERVICE_NAME, DEBUG_SERVICE_NAME,
 };
 
+// The `Dispatcher` of jobs for the coordinator, with the `ExecutorConnector`s used to connect the
+// executors of library and flow jobs, and of context jobs, to it
+type JobQueues = (Dispatcher, Arc<dyn ExecutorConnector>, Arc<dyn ExecutorConnector>);
+
 /// Include the module that implements the context functions
 mod context;
 
@@ -82,6 +105,9 @@ mod errors;
 
 /// Main for flowr binary - call `run()` and print any error that results or exit silently if OK
 fn main() {
+    // When run by a shell to complete the command line, print the completions and exit
+    CompleteEnv::with_factory(get_command).complete();
+
     match run() {
         Err(ref e) => {
             error!("{e}");
@@ -122,10 +148,23 @@ fn get_lib_search_path(search_path_additions: &[String]) -> Simpath {
 /// Run `flowr`. After setting up logging and parsing the command line arguments invoke `flowrlib`
 /// and return any errors found.
 fn run() -> Result<()> {
-    let matches = get_matches();
+    let matches = get_command().get_matches();
+
+    if let Some(("completions", completions_matches)) = matches.subcommand() {
+        if let Some(shell) = completions_matches.get_one::<Shell>("shell") {
+            print_completions(*shell);
+        }
+        return Ok(());
+    }
+
+    // defaults from config files, that command line options override
+    let config = Config::load(&env::current_dir()
+        .chain_err(|| "Could not get the current working directory")?)?;
 
     let default = String::from("error");
-    let verbosity = matches.get_one::<String>("verbosity").unwrap_or(&default);
+    let verbosity = matches.get_one::<String>("verbosity")
+        .or(config.verbosity.as_ref())
+        .unwrap_or(&default);
     let level = LevelFilter::from_str(verbosity).unwrap_or(LevelFilter::Error);
     let mut builder = Builder::from_default_env();
     builder.filter_level(level).init();
@@ -137,10 +176,14 @@ fn run() -> Result<()> {
     );
     info!("'flowrlib' version {}", flowrlib_info::version());
 
+    // kept until the end of execution, so all traces are flushed to the collector
+    #[cfg(feature = "otlp")]
+    let _telemetry = flowrlib::telemetry::init(env!("CARGO_PKG_NAME"))?;
+
     #[cfg(feature = "debugger")]
     let debug_this_flow = matches.get_flag("debugger");
-    let native_flowstdlib = matches.get_flag("native");
-    let lib_dirs = if matches.contains_id("lib_dir") {
+    let native_flowstdlib = matches.get_flag("native") || config.native.unwrap_or(false);
+    let mut lib_dirs: Vec<String> = if matches.contains_id("lib_dir") {
         matches
             .get_many::<String>("lib_dir")
             .chain_err(|| "Could not get the list of 'LIB_DIR' options specified")?
@@ -149,8 +192,15 @@ fn run() -> Result<()> {
     } else {
         vec![]
     };
+    lib_dirs.extend(config.lib_dirs);
     let lib_search_path = get_lib_search_path(&lib_dirs);
-    let num_threads = num_threads(&matches);
+    let num_threads = num_threads(&matches, config.threads);
+    let transport = get_transport(&matches);
+    let keys = get_keys(&matches)?;
+    let bind_address = matches.get_one::<IpAddr>("bind-address").copied();
+    let event_log = matches.get_one::<PathBuf>("event-log")
+        .map(|path| JsonEventLog::create(path))
+        .transpose()?;
 
     if let Some(discovery_port) = matches.get_one::<u16>("client") {
         client_only(
@@ -159,15 +209,33 @@ fn run() -> Result<()> {
             #[cfg(feature = "debugger")]
             debug_this_flow,
             *discovery_port,
+            transport,
+            keys.as_ref(),
         )?;
     } else if matches.get_flag("server") {
-        coordinator_only(num_threads, lib_search_path, native_flowstdlib)?;
+        coordinator_only(num_threads, lib_search_path, native_flowstdlib, transport,
+                         keys.as_ref(), bind_address, event_log,
+                         matches.get_one::<u16>("http").copied(), get_client_tokens(&matches)?)?;
+    } else if matches.get_flag("single-process") {
+        single_process(
+            num_threads,
+            lib_search_path,
+            native_flowstdlib,
+            &matches,
+            event_log,
+            #[cfg(feature = "debugger")]
+            debug_this_flow,
+        )?;
     } else {
         client_and_coordinator(
             num_threads,
             lib_search_path,
             native_flowstdlib,
             &matches,
+            transport,
+            keys.as_ref(),
+            bind_address,
+            event_log,
             #[cfg(feature = "debugger")]
             debug_this_flow,
         )?;
@@ -177,23 +245,42 @@ fn run() -> Result<()> {
 }
 
 /// Start just a [Coordinator][flowrlib::coordinator::Coordinator] in the calling thread.
+/// If `http_port` is given, flows are submitted using the REST API served on it, instead of by a
+/// client connecting to the coordinator.
+/// If `client_tokens` are given, only clients presenting one of them can submit flows.
+/// If a `bind_address` is given, services are only offered on the interface with that address
+#[allow(clippy::too_many_arguments)]
 fn coordinator_only(
     num_threads: usize,
     lib_search_path: Simpath,
     native_flowstdlib: bool,
+    transport: Transport,
+    keys: Option<&CurveKeys>,
+    bind_address: Option<IpAddr>,
+    event_log: Option<JsonEventLog>,
+    http_port: Option<u16>,
+    client_tokens: Option<ClientTokens>,
 ) -> Result<()> {
+    if let Some(port) = http_port {
+        return http_coordinator(num_threads, lib_search_path, native_flowstdlib, keys,
+                                bind_address, event_log, port, client_tokens);
+    }
+
     let coordinator_port = pick_unused_port().chain_err(|| "No ports free")?;
     let coordinator_connection =
-        CoordinatorConnection::new(COORDINATOR_SERVICE_NAME, coordinator_port)?;
+        CoordinatorConnection::new(COORDINATOR_SERVICE_NAME, coordinator_port, bind_address,
+                                   transport, keys)?;
     let discovery_port = pick_unused_port().chain_err(|| "No ports free")?;
-    enable_service_discovery(discovery_port, COORDINATOR_SERVICE_NAME, coordinator_port)?;
+    enable_service_discovery(discovery_port, COORDINATOR_SERVICE_NAME, coordinator_port,
+                             bind_address)?;
 
     #[cfg(feature = "debugger")]
     let debug_port = pick_unused_port().chain_err(|| "No ports free")?;
     #[cfg(feature = "debugger")]
-    let debug_server_connection = CoordinatorConnection::new(DEBUG_SERVICE_NAME, debug_port)?;
+    let debug_server_connection =
+        CoordinatorConnection::new(DEBUG_SERVICE_NAME, debug_port, bind_address, transport, keys)?;
     #[cfg(feature = "debugger")]
-    enable_service_discovery(discovery_port, DEBUG_SERVICE_NAME, debug_port)?;
+    enable_service_discovery(discovery_port, DEBUG_SERVICE_NAME, debug_port, bind_address)?;
 
     println!("{discovery_port}");
 
@@ -205,6 +292,77 @@ fn coordinator_only(
         coordinator_connection,
         #[cfg(feature = "debugger")]
         debug_server_connection,
+        keys.cloned(),
+        bind_address,
+        event_log,
+        client_tokens,
+        false,
+        None,
+        true,
+    )?;
+
+    info!("'flowr' coordinator has exited");
+
+    Ok(())
+}
+
+/// Start a [Coordinator][flowrlib::coordinator::Coordinator] in the calling thread that executes
+/// the flows submitted using the REST API served on `http_port`, concurrently.
+/// If `client_tokens` are given, only requests presenting one of them are accepted.
+/// There is no client to submit them, so the context functions of those flows are executed by a
+/// client in a background thread of this process, connected to the coordinator by channels
+#[allow(clippy::too_many_arguments)]
+fn http_coordinator(
+    num_threads: usize,
+    lib_search_path: Simpath,
+    native_flowstdlib: bool,
+    keys: Option<&CurveKeys>,
+    bind_address: Option<IpAddr>,
+    event_log: Option<JsonEventLog>,
+    http_port: u16,
+    client_tokens: Option<ClientTokens>,
+) -> Result<()> {
+    let cwd_url = Url::from_directory_path(env::current_dir()?)
+        .map_err(|()| "Could not form a Url for the current working directory")?;
+    let provider =
+        Arc::new(MetaProvider::new(lib_search_path.clone(), PathBuf::from("/"))) as Arc<dyn Provider>;
+    let listener = HttpSubmissionListener::bind(http_port, bind_address, provider, cwd_url,
+                                                client_tokens)?;
+
+    let (context_client_connection, coordinator_connection) = connection_pair();
+    // the debugger is not used by flows submitted using the REST API
+    #[cfg(feature = "debugger")]
+    let (_debug_client_connection, debug_connection) = connection_pair();
+
+    info!("Starting client for context functions in background thread");
+    thread::spawn(move || {
+        let context_client = CliRuntimeClient::new(
+            vec![],
+            Arc::new(Mutex::new(vec![])),
+            vec![],
+            false,
+            #[cfg(feature = "metrics")]
+            false,
+        );
+        if let Err(e) = context_client.event_loop(&context_client_connection) {
+            error!("Client for context functions exited with error: {e}");
+        }
+    });
+
+    info!("Starting coordinator in main thread");
+    coordinator(
+        num_threads,
+        lib_search_path,
+        native_flowstdlib,
+        coordinator_connection,
+        #[cfg(feature = "debugger")]
+        debug_connection,
+        keys.cloned(),
+        bind_address,
+        event_log,
+        None,
+        false,
+        Some(listener),
         true,
     )?;
 
@@ -215,27 +373,36 @@ fn coordinator_only(
 
 /// Start a [Coordinator][flowrlib::coordinator::Coordinator] in a background thread,
 /// then start a client in the calling thread
+#[allow(clippy::too_many_arguments)]
 fn client_and_coordinator(
     num_threads: usize,
     lib_search_path: Simpath,
     native_flowstdlib: bool,
     matches: &ArgMatches,
+    transport: Transport,
+    keys: Option<&CurveKeys>,
+    bind_address: Option<IpAddr>,
+    event_log: Option<JsonEventLog>,
     #[cfg(feature = "debugger")] debug_this_flow: bool,
 ) -> Result<()> {
     let runtime_port = pick_unused_port().chain_err(|| "No ports free")?;
     let coordinator_connection =
-        CoordinatorConnection::new(COORDINATOR_SERVICE_NAME, runtime_port)?;
+        CoordinatorConnection::new(COORDINATOR_SERVICE_NAME, runtime_port, bind_address,
+                                   transport, keys)?;
 
     let discovery_port = pick_unused_port().chain_err(|| "No ports free")?;
-    enable_service_discovery(discovery_port, COORDINATOR_SERVICE_NAME, runtime_port)?;
+    enable_service_discovery(discovery_port, COORDINATOR_SERVICE_NAME, runtime_port,
+                             bind_address)?;
 
     #[cfg(feature = "debugger")]
     let debug_port = pick_unused_port().chain_err(|| "No ports free")?;
     #[cfg(feature = "debugger")]
-    let debug_connection = CoordinatorConnection::new(DEBUG_SERVICE_NAME, debug_port)?;
-    enable_service_discovery(discovery_port, DEBUG_SERVICE_NAME, debug_port)?;
+    let debug_connection =
+        CoordinatorConnection::new(DEBUG_SERVICE_NAME, debug_port, bind_address, transport, keys)?;
+    enable_service_discovery(discovery_port, DEBUG_SERVICE_NAME, debug_port, bind_address)?;
 
     let coordinator_lib_search_path = lib_search_path.clone();
+    let coordinator_keys = keys.cloned();
 
     info!("Starting coordinator in background thread");
     thread::spawn(move || {
@@ -246,13 +413,19 @@ fn client_and_coordinator(
             coordinator_connection,
             #[cfg(feature = "debugger")]
             debug_connection,
+            coordinator_keys,
+            bind_address,
+            event_log,
+            None,
+            false,
+            None,
             false,
         );
     });
 
     let coordinator_address = discover_service(discovery_port, COORDINATOR_SERVICE_NAME)?;
 
-    let runtime_client_connection = ClientConnection::new(&coordinator_address)?;
+    let runtime_client_connection = ClientConnection::new(&coordinator_address, transport, keys)?;
 
     client(
         matches,
@@ -261,19 +434,76 @@ fn client_and_coordinator(
         #[cfg(feature = "debugger")]
         debug_this_flow,
         #[cfg(feature = "debugger")]
-        discovery_port,
+        debug_client_connection(debug_this_flow, discovery_port, transport, keys)?,
+    )
+}
+
+/// Start a [Coordinator][flowrlib::coordinator::Coordinator] and its executors in a background
+/// thread, then start a client in the calling thread. They are all connected by in-process
+/// channels, so no ports are opened and no service discovery is used
+fn single_process(
+    num_threads: usize,
+    lib_search_path: Simpath,
+    native_flowstdlib: bool,
+    matches: &ArgMatches,
+    event_log: Option<JsonEventLog>,
+    #[cfg(feature = "debugger")] debug_this_flow: bool,
+) -> Result<()> {
+    let (client_connection, coordinator_connection) = connection_pair();
+    #[cfg(feature = "debugger")]
+    let (debug_client_connection, debug_connection) = connection_pair();
+
+    let coordinator_lib_search_path = lib_search_path.clone();
+
+    info!("Starting coordinator in background thread, connected by channels");
+    thread::spawn(move || {
+        let _ = coordinator(
+            num_threads,
+            coordinator_lib_search_path,
+            native_flowstdlib,
+            coordinator_connection,
+            #[cfg(feature = "debugger")]
+            debug_connection,
+            None,
+            None,
+            event_log,
+            None,
+            true,
+            None,
+            false,
+        );
+    });
+
+    client(
+        matches,
+        lib_search_path,
+        &client_connection,
+        #[cfg(feature = "debugger")]
+        debug_this_flow,
+        #[cfg(feature = "debugger")]
+        debug_this_flow.then_some(debug_client_connection),
     )
 }
 
 /// Create a new `Coordinator`, pre-load any libraries in native format that we want to have before
 /// loading a flow and it's library references, then enter the `submission_loop()` accepting and
-/// executing flows submitted for execution, executing each one using the `Coordinator`
+/// executing flows submitted for execution, executing each one using the `Coordinator`.
+/// If `client_tokens` are given, only submissions from clients presenting one of them are accepted.
+/// If a `bind_address` is given, the job queues are only offered on the interface with that address.
+/// If an `http_listener` is given, `serve()` the flows it accepts concurrently instead
+#[allow(clippy::too_many_arguments)]
 fn coordinator(
     num_threads: usize,
     lib_search_path: Simpath,
     native_flowstdlib: bool,
     coordinator_connection: CoordinatorConnection,
     #[cfg(feature = "debugger")] debug_connection: CoordinatorConnection,
+    keys: Option<CurveKeys>,
+    bind_address: Option<IpAddr>,
+    event_log: Option<JsonEventLog>,
+    client_tokens: Option<ClientTokens>,
+    in_process: bool,
+    http_listener: Option<HttpSubmissionListener>,
     loop_forever: bool,
 ) -> Result<()> {
     let connection = Arc::new(Mutex::new(coordinator_connection));
@@ -286,16 +516,11 @@ fn coordinator(
     let provider =
         Arc::new(MetaProvider::new(lib_search_path, PathBuf::from("/"))) as Arc<dyn Provider>;
 
-    let ports = get_four_ports()?;
-    trace!("Announcing three job queues and a control socket on ports: {ports:?}");
-    let job_queues = get_bind_addresses(ports);
-    let dispatcher = Dispatcher::new(&job_queues)?;
-    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, JOB_SERVICE_NAME, ports.0)?;
-    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, RESULTS_JOB_SERVICE_NAME, ports.2)?;
-    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, CONTROL_SERVICE_NAME, ports.3)?;
-
-    let (job_source_name, context_job_source_name, results_sink, control_socket) =
-        get_connect_addresses(ports);
+    let (dispatcher, job_connector, context_job_connector) = if in_process {
+        in_process_job_queues()?
+    } else {
+        zmq_job_queues(keys.as_ref(), bind_address)?
+    };
 
     let mut executor = Executor::new();
     // if the command line options request loading native implementation of available native libs
@@ -308,28 +533,16 @@ fn coordinator(
             Url::parse("memory://")?, // Statically linked library has no resolved Url
         )?;
     }
-    executor.start(
-        &provider,
-        num_threads,
-        &job_source_name,
-        &results_sink,
-        &control_socket,
-    );
+    executor.start_with_connector(&provider, num_threads, job_connector);
 
     let mut context_executor = Executor::new();
     context_executor.add_lib(
         context::get_manifest(connection.clone())?,
         Url::parse("memory://")?, // Statically linked library has no resolved Url
     )?;
-    context_executor.start(
-        &provider,
-        1,
-        &context_job_source_name,
-        &results_sink,
-        &control_socket,
-    );
+    context_executor.start_with_connector(&provider, 1, context_job_connector);
 
-    let mut submitter = CLISubmissionHandler::new(connection);
+    let mut submitter = CLISubmissionHandler::new(connection, client_tokens);
 
     let mut coordinator = Coordinator::new(
         dispatcher,
@@ -337,21 +550,68 @@ fn coordinator(
         #[cfg(feature = "debugger")]
         &mut debug_server,
     );
+    if let Some(log) = event_log {
+        coordinator.set_event_sink(Box::new(log));
+    }
 
-    coordinator.submission_loop(loop_forever)?;
+    match http_listener {
+        Some(mut listener) => coordinator.serve(&mut listener)?,
+        None => coordinator.submission_loop(loop_forever)?,
+    }
 
     Ok(())
 }
 
+/// Create a `Dispatcher` of jobs using `zmq` sockets bound to free ports, that are announced using
+/// service discovery so that `flowrex` executors can also get jobs from it, and the
+/// `ExecutorConnector`s for executors in this process to connect to them. If a `bind_address` is
+/// given the sockets are bound to the interface with that address, otherwise to all interfaces
+fn zmq_job_queues(keys: Option<&CurveKeys>, bind_address: Option<IpAddr>) -> Result<JobQueues> {
+    let ports = get_five_ports()?;
+    trace!("Announcing four job queues and a control socket on ports: {ports:?}");
+    let job_queues = get_bind_addresses(ports, bind_address);
+    let dispatcher = Dispatcher::new(&job_queues, keys)?;
+    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, JOB_SERVICE_NAME, ports.0, bind_address)?;
+    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, RESULTS_JOB_SERVICE_NAME, ports.2,
+                             bind_address)?;
+    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, CONTROL_SERVICE_NAME, ports.3,
+                             bind_address)?;
+    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, NATIVE_JOB_SERVICE_NAME, ports.4,
+                             bind_address)?;
+
+    let (job_source_name, context_job_source_name, results_sink, control_socket,
+        native_job_source_name) = get_connect_addresses(ports, bind_address);
+    let job_connector = ZmqConnector::new(&job_source_name, &native_job_source_name,
+                                          &results_sink, &control_socket, keys.cloned());
+    let context_job_connector = ZmqConnector::new(&context_job_source_name,
+                                                  &native_job_source_name, &results_sink,
+                                                  &control_socket, keys.cloned());
+
+    Ok((dispatcher, Arc::new(job_connector), Arc::new(context_job_connector)))
+}
+
+/// Create a `Dispatcher` of jobs using in-process channels, and the `ExecutorConnector`s for
+/// executors in this process to connect to them
+fn in_process_job_queues() -> Result<JobQueues> {
+    let transport = ChannelJobTransport::new();
+    let job_connector = transport.connector(JobQueue::Lib)?;
+    let context_job_connector = transport.connector(JobQueue::General)?;
+
+    Ok((Dispatcher::with_transport(Box::new(transport)), Arc::new(job_connector),
+        Arc::new(context_job_connector)))
+}
+
 /// Start only a client in the calling thread. Discover the remote Coordinator using service discovery
 fn client_only(
     matches: &ArgMatches,
     lib_search_path: Simpath,
     #[cfg(feature = "debugger")] debug_this_flow: bool,
     discovery_port: u16,
+    transport: Transport,
+    keys: Option<&CurveKeys>,
 ) -> Result<()> {
     let coordinator_address = discover_service(discovery_port, COORDINATOR_SERVICE_NAME)?;
-    let client_connection = ClientConnection::new(&coordinator_address)?;
+    let client_connection = ClientConnection::new(&coordinator_address, transport, keys)?;
 
     client(
         matches,
@@ -360,10 +620,27 @@ fn client_only(
         #[cfg(feature = "debugger")]
         debug_this_flow,
         #[cfg(feature = "debugger")]
-        discovery_port,
+        debug_client_connection(debug_this_flow, discovery_port, transport, keys)?,
     )
 }
 
+/// Connect to the debug server of the coordinator found using service discovery, if the flow is
+/// to be debugged
+#[cfg(feature = "debugger")]
+fn debug_client_connection(
+    debug_this_flow: bool,
+    discovery_port: u16,
+    transport: Transport,
+    keys: Option<&CurveKeys>,
+) -> Result<Option<ClientConnection>> {
+    if !debug_this_flow {
+        return Ok(None);
+    }
+
+    let debug_server_address = discover_service(discovery_port, DEBUG_SERVICE_NAME)?;
+    ClientConnection::new(&debug_server_address, transport, keys).map(Some)
+}
+
 /// Start the clients that talks to the coordinator
 #[cfg(feature = "debugger")]
 fn client(
@@ -371,7 +648,7 @@ fn client(
     lib_search_path: Simpath,
     client_connection: &ClientConnection,
     #[cfg(feature = "debugger")] debug_this_flow: bool,
-    #[cfg(feature = "debugger")] discovery_port: u16,
+    #[cfg(feature = "debugger")] debug_client_connection: Option<ClientConnection>,
 ) -> Result<()> {
     // keep an Arc Mutex protected set of override args that debug client can override
     let override_args = Arc::new(Mutex::new(Vec::<String>::new()));
@@ -384,45 +661,67 @@ fn client(
     let parallel_jobs_limit = matches
         .get_one::<usize>("jobs")
         .map(std::borrow::ToOwned::to_owned);
-    let submission = Submission::new(
+    let mut submission = Submission::new(
         flow_manifest,
         parallel_jobs_limit,
-        None, // No timeout waiting for job results
+        matches.get_one::<u64>("job-timeout").map(|secs| Duration::from_secs(*secs)),
+        get_retry_policy(matches),
+        get_checkpoint_policy(matches),
         #[cfg(feature = "debugger")]
         debug_this_flow,
     );
+    submission.deterministic = matches.get_flag("deterministic");
+    submission.chunk_size = matches.get_one::<usize>("chunk-size").copied();
+    submission.batch_size = matches.get_one::<usize>("batch").copied();
+    submission.validate = matches.get_flag("validate");
+    submission.fuel = matches.get_one::<u64>("fuel").copied();
+    if let Some(times) = matches.get_one::<usize>("redispatch") {
+        submission.job_timeout_policy = JobTimeoutPolicy::Redispatch(*times);
+    }
+    submission.limits = get_resource_limits(matches);
 
     trace!("Creating CliRuntimeClient");
+    let allowed_env_vars = matches.get_many::<String>("allow-env")
+        .map(|names| names.cloned().collect())
+        .unwrap_or_default();
     let client = CliRuntimeClient::new(
         flow_args,
         override_args.clone(),
+        allowed_env_vars,
+        matches.get_flag("allow-exec"),
         #[cfg(feature = "metrics")]
         matches.get_flag("metrics"),
     );
 
     #[cfg(feature = "debugger")]
-    if debug_this_flow {
-        let debug_server_address = discover_service(discovery_port, DEBUG_SERVICE_NAME)?;
-        let debug_client_connection = ClientConnection::new(&debug_server_address)?;
+    if let Some(debug_client_connection) = debug_client_connection {
         let debug_client = CliDebugClient::new(debug_client_connection, override_args);
         let _ = thread::spawn(move || {
             debug_client.debug_client_loop();
         });
     }
 
+    let token = client_token(matches.get_one::<PathBuf>("token-file").map(PathBuf::as_path))?;
+
     info!("Client sending submission to coordinator");
-    client_connection.send(ClientMessage::ClientSubmission(submission))?;
+    client_connection.send(ClientMessage::ClientSubmission(token, submission))?;
 
     trace!("Entering client event loop");
     client.event_loop(client_connection)
 }
 
 /// Determine the number of threads to use to execute flows
-/// - default (if value is not provided on the command line)of the number of cores
+/// - one if deterministic execution was requested
+/// - the number set in the config files (if value is not provided on the command line)
+/// - default (if value is not provided in either) of the number of cores
 #[allow(clippy::redundant_closure_for_method_calls)]
-fn num_threads(matches: &ArgMatches) -> usize {
-    match matches.get_one::<usize>("threads") {
-        Some(num_threads) => *num_threads,
+fn num_threads(matches: &ArgMatches, config_threads: Option<usize>) -> usize {
+    if matches.get_flag("deterministic") {
+        return 1;
+    }
+
+    match matches.get_one::<usize>("threads").copied().or(config_threads) {
+        Some(num_threads) => num_threads,
         None =>
         {
             #[allow(clippy::redundant_closure)]
@@ -433,8 +732,26 @@ fn num_threads(matches: &ArgMatches) -> usize {
     }
 }
 
-/// Parse the command line arguments using clap
-fn get_matches() -> ArgMatches {
+/// Print a static completion script for `shell` to stdout, that completes the flow manifest with
+/// paths. To also complete it with the example flows installed, register the script printed by
+/// `COMPLETE=<shell> flowrcli` instead, which runs `flowrcli` to complete the argument
+fn print_completions(shell: Shell) {
+    generate(shell, &mut get_command(), env!("CARGO_BIN_NAME"), &mut io::stdout());
+}
+
+/// Complete the flow manifest argument with the example flows, found when it is completed, whose
+/// name or path starts with `current`, and then with paths
+fn complete_flow_manifest(current: &OsStr) -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<CompletionCandidate> = find_examples(&current.to_string_lossy())
+        .into_iter()
+        .map(|example| CompletionCandidate::new(example).help(Some("example flow".into())))
+        .collect();
+    candidates.extend(PathCompleter::any().complete(current));
+    candidates
+}
+
+/// Define the command line arguments using clap
+fn get_command() -> Command {
     let app = Command::new(env!("CARGO_PKG_NAME")).version(env!("CARGO_PKG_VERSION"));
 
     #[cfg(feature = "debugger")]
@@ -465,7 +782,7 @@ fn get_matches() -> ArgMatches {
             .help("Link with native (not WASM) version of flowstdlib"),
     );
 
-    let app = app
+    app
         .arg(Arg::new("server")
              .short('s')
              .long("server")
@@ -481,6 +798,68 @@ fn get_matches() -> ArgMatches {
              .conflicts_with("server")
              .help("Launch only a client (no coordinator) to connect to a remote coordinator"),
         )
+        .arg(Arg::new("single-process")
+             .long("single-process")
+             .action(clap::ArgAction::SetTrue)
+             .conflicts_with_all(["server", "client", "websocket", "key-file"])
+             .help("Run client, coordinator and executors in one process, connected by channels instead of sockets"),
+        )
+        .arg(Arg::new("http")
+             .long("http")
+             .number_of_values(1)
+             .value_parser(clap::value_parser!(u16))
+             .requires("server")
+             .help("Serve a REST API on this port to submit flows and query or cancel their runs (with --server)"),
+        )
+        .arg(Arg::new("bind-address")
+             .long("bind-address")
+             .number_of_values(1)
+             .value_parser(clap::value_parser!(IpAddr))
+             .value_name("ADDRESS")
+             .conflicts_with_all(["client", "single-process"])
+             .help("Offer the coordinator's services only on the interface with this IPv4 or IPv6 ADDRESS ('::' for all IPv6 interfaces)"),
+        )
+        .arg(Arg::new("websocket")
+             .short('w')
+             .long("websocket")
+             .action(clap::ArgAction::SetTrue)
+             .help("Use a WebSocket (instead of ZeroMQ) between client and coordinator"),
+        )
+        .arg(Arg::new("key-file")
+             .short('K')
+             .long("key-file")
+             .number_of_values(1)
+             .value_parser(clap::value_parser!(PathBuf))
+             .value_name("FILE")
+             .conflicts_with("websocket")
+             .help("Encrypt connections using the keys in FILE (generated if it does not exist)"),
+        )
+        .arg(Arg::new("tokens")
+             .long("tokens")
+             .number_of_values(1)
+             .value_parser(clap::value_parser!(PathBuf))
+             .value_name("FILE")
+             .requires("server")
+             .help("Only accept submissions from clients presenting a token from the 'identity=token' lines in FILE (with --server)"),
+        )
+        .arg(Arg::new("token-file")
+             .long("token-file")
+             .number_of_values(1)
+             .value_parser(clap::value_parser!(PathBuf))
+             .value_name("FILE")
+             .requires("client")
+             .help("Present the token in FILE to the coordinator to be allowed to submit flows (with --client)"),
+        )
+        .arg(Arg::new("allow-env")
+            .long("allow-env")
+            .num_args(1)
+            .action(clap::ArgAction::Append)
+            .value_name("NAME")
+            .help("Allow the flow to read environment variable NAME using 'context://env' functions"))
+        .arg(Arg::new("allow-exec")
+            .long("allow-exec")
+            .action(clap::ArgAction::SetTrue)
+            .help("Allow the flow to run commands using the 'context://process/exec' function"))
         .arg(Arg::new("jobs")
             .short('j')
             .long("jobs")
@@ -488,6 +867,104 @@ fn get_matches() -> ArgMatches {
             .value_parser(clap::value_parser!(usize))
             .value_name("MAX_JOBS")
             .help("Set maximum number of jobs that can be running in parallel)"))
+        .arg(Arg::new("deterministic")
+            .long("deterministic")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["jobs", "threads"])
+            .help("Execute jobs one at a time in a fixed order, so output is the same on every run"))
+        .arg(Arg::new("chunk-size")
+            .long("chunk-size")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("ELEMENTS")
+            .help("Stream array outputs of jobs larger than ELEMENTS back in chunks of that size"))
+        .arg(Arg::new("batch")
+            .long("batch")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("JOBS")
+            .help("Send up to JOBS small library jobs to an executor in one message, with their results returned together"))
+        .arg(Arg::new("validate")
+            .long("validate")
+            .action(clap::ArgAction::SetTrue)
+            .help("Validate values of function inputs against their JSON Schemas before running jobs"))
+        .arg(Arg::new("fuel")
+            .long("fuel")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(u64))
+            .value_name("FUEL")
+            .help("Fail WASM jobs that execute more than (approximately) FUEL instructions"))
+        .arg(Arg::new("job-timeout")
+            .long("job-timeout")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(u64))
+            .value_name("SECS")
+            .help("Fail a job if its result is not received within SECS seconds of it being dispatched"))
+        .arg(Arg::new("redispatch")
+            .long("redispatch")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("TIMES")
+            .requires("job-timeout")
+            .help("Dispatch a job that times out again, up to TIMES times, before failing it"))
+        .arg(Arg::new("job-limit")
+            .long("job-limit")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("JOBS")
+            .help("Abort the flow if it creates more than JOBS jobs"))
+        .arg(Arg::new("memory-limit")
+            .long("memory-limit")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("MB")
+            .help("Abort the flow if the values it holds use more than an estimated MB megabytes"))
+        .arg(Arg::new("time-limit")
+            .long("time-limit")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(u64))
+            .value_name("SECS")
+            .help("Abort the flow if it executes for more than SECS seconds"))
+        .arg(Arg::new("retries")
+            .short('r')
+            .long("retries")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("RETRIES")
+            .help("Retry a job that fails up to RETRIES times, then abort the flow"))
+        .arg(Arg::new("backoff")
+            .long("backoff")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(u64))
+            .value_name("MILLIS")
+            .requires("retries")
+            .help("Wait before the first retry of a failed job, doubled for each retry (default: 100)"))
+        .arg(Arg::new("checkpoint")
+            .long("checkpoint")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(PathBuf))
+            .value_name("FILE")
+            .conflicts_with("resume")
+            .help("Periodically write a checkpoint of the state of execution to FILE"))
+        .arg(Arg::new("checkpoint-interval")
+            .long("checkpoint-interval")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(u64))
+            .value_name("SECS")
+            .help("Set the interval between checkpoints of the state of execution (default: 60)"))
+        .arg(Arg::new("resume")
+            .long("resume")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(PathBuf))
+            .value_name("FILE")
+            .help("Resume execution of the flow from the checkpoint in FILE, and continue writing checkpoints to it"))
+        .arg(Arg::new("event-log")
+            .long("event-log")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(PathBuf))
+            .value_name("FILE")
+            .conflicts_with("client")
+            .help("Write events during execution to FILE as newline delimited JSON"))
         .arg(Arg::new("lib_dir")
             .short('L')
             .long("libdir")
@@ -510,13 +987,80 @@ fn get_matches() -> ArgMatches {
             .help("Set verbosity level for output (trace, debug, info, warn, error(default), off)"))
         .arg(Arg::new("flow-manifest")
             .num_args(1)
+            .value_hint(ValueHint::AnyPath)
+            .add(ArgValueCompleter::new(complete_flow_manifest))
             .help("the file path of the 'flow' manifest file"))
         .arg(Arg::new("flow_args")
             .num_args(0..)
             .trailing_var_arg(true)
-            .help("A list of arguments to pass to the flow."));
+            .help("A list of arguments to pass to the flow."))
+        .subcommand(Command::new("completions")
+            .about("Print a shell completion script for flowrcli")
+            .arg(Arg::new("shell")
+                .required(true)
+                .value_parser(clap::value_parser!(Shell))
+                .help("The shell to print the completion script for")))
+}
+
+/// Get the `Transport` to use between client and coordinator from the command line options
+fn get_transport(matches: &ArgMatches) -> Transport {
+    if matches.get_flag("websocket") {
+        Transport::WebSocket
+    } else {
+        Transport::Zmq
+    }
+}
+
+/// Get the `CurveKeys` to encrypt connections with from the key file in the command line
+/// options, if any
+fn get_keys(matches: &ArgMatches) -> Result<Option<CurveKeys>> {
+    matches.get_one::<PathBuf>("key-file")
+        .map(|path| CurveKeys::load_or_generate(path))
+        .transpose()
+}
+
+/// Get the `ClientTokens` that clients must present to submit flows, from the tokens file in the
+/// command line options, or else from the `FLOW_CLIENT_TOKENS` environment variable, if set
+fn get_client_tokens(matches: &ArgMatches) -> Result<Option<ClientTokens>> {
+    match matches.get_one::<PathBuf>("tokens") {
+        Some(path) => ClientTokens::load(path).map(Some),
+        None => ClientTokens::from_env(),
+    }
+}
+
+/// Get the default `RetryPolicy` for failed jobs from the command line options, if any
+fn get_retry_policy(matches: &ArgMatches) -> Option<RetryPolicy> {
+    matches.get_one::<usize>("retries").map(|retries| RetryPolicy {
+        retries: *retries,
+        backoff: matches.get_one::<u64>("backoff")
+            .map_or(DEFAULT_RETRY_BACKOFF, |millis| Duration::from_millis(*millis)),
+    })
+}
+
+/// Get the `ResourceLimits` on the execution of the flow from the command line options
+fn get_resource_limits(matches: &ArgMatches) -> ResourceLimits {
+    ResourceLimits {
+        max_jobs: matches.get_one::<usize>("job-limit").copied(),
+        max_memory: matches.get_one::<usize>("memory-limit")
+            .map(|megabytes| megabytes.saturating_mul(1024 * 1024)),
+        max_time: matches.get_one::<u64>("time-limit").map(|secs| Duration::from_secs(*secs)),
+    }
+}
 
-    app.get_matches()
+/// Get the `CheckpointPolicy` for saving, and possibly resuming, the state of execution from
+/// the command line options, if any
+fn get_checkpoint_policy(matches: &ArgMatches) -> Option<CheckpointPolicy> {
+    let (path, resume) = match matches.get_one::<PathBuf>("resume") {
+        Some(path) => (path, true),
+        None => (matches.get_one::<PathBuf>("checkpoint")?, false),
+    };
+
+    Some(CheckpointPolicy {
+        path: path.clone(),
+        interval: matches.get_one::<u64>("checkpoint-interval")
+            .map_or(DEFAULT_CHECKPOINT_INTERVAL, |secs| Duration::from_secs(*secs)),
+        resume,
+    })
 }
 
 /// Parse the command line arguments passed onto the flow itself
@@ -548,40 +1092,48 @@ fn get_flow_args(matches: &ArgMatches, flow_manifest_url: &Url) -> Vec<String> {
     flow_args
 }
 
-// Return addresses and ports to be used for each of the three queues
+// Return addresses and ports for executors in this process to connect to each of the queues,
+// bound using `bind_address`
 // - (general) job source
 // - context job source
 // - results sink
 // - control messages
-fn get_connect_addresses(ports: (u16, u16, u16, u16)) -> (String, String, String, String) {
+// - native job source
+fn get_connect_addresses(ports: (u16, u16, u16, u16, u16), bind_address: Option<IpAddr>)
+    -> (String, String, String, String, String) {
     (
-        format!("tcp://127.0.0.1:{}", ports.0),
-        format!("tcp://127.0.0.1:{}", ports.1),
-        format!("tcp://127.0.0.1:{}", ports.2),
-        format!("tcp://127.0.0.1:{}", ports.3),
+        local_endpoint(bind_address, ports.0),
+        local_endpoint(bind_address, ports.1),
+        local_endpoint(bind_address, ports.2),
+        local_endpoint(bind_address, ports.3),
+        local_endpoint(bind_address, ports.4),
     )
 }
 
-// Return addresses to bind to for
+// Return addresses to bind to, on the interface with `bind_address` or all interfaces, for
 // - (general) job source
 // - context job source
 // - results sink
 // - control messages
-fn get_bind_addresses(ports: (u16, u16, u16, u16)) -> (String, String, String, String) {
+// - native job source
+fn get_bind_addresses(ports: (u16, u16, u16, u16, u16), bind_address: Option<IpAddr>)
+    -> (String, String, String, String, String) {
     (
-        format!("tcp://*:{}", ports.0),
-        format!("tcp://*:{}", ports.1),
-        format!("tcp://*:{}", ports.2),
-        format!("tcp://*:{}", ports.3),
+        bind_endpoint(bind_address, ports.0),
+        bind_endpoint(bind_address, ports.1),
+        bind_endpoint(bind_address, ports.2),
+        bind_endpoint(bind_address, ports.3),
+        bind_endpoint(bind_address, ports.4),
     )
 }
 
-// Return four free ports to use for client-coordinator message queues
-fn get_four_ports() -> Result<(u16, u16, u16, u16)> {
+// Return five free ports to use for client-coordinator message queues
+fn get_five_ports() -> Result<(u16, u16, u16, u16, u16)> {
     Ok((
         pick_unused_port().chain_err(|| "No ports free")?,
         pick_unused_port().chain_err(|| "No ports free")?,
         pick_unused_port().chain_err(|| "No ports free")?,
         pick_unused_port().chain_err(|| "No ports free")?,
+        pick_unused_port().chain_err(|| "No ports free")?,
     ))
 }
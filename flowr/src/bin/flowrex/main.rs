@@ -7,37 +7,149 @@ use core::str::FromStr;
 /// Any implementations are either preloaded static linked binary functions or loaded from WASM
 /// from peers.
 
-use std::{env, thread};
-use std::path::PathBuf;
+use std::{env, fs, process, thread};
+use std::io;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use clap::{Arg, ArgMatches, Command};
+use clap_complete::{generate, Shell};
 use env_logger::Builder;
-use log::{error, info, LevelFilter, trace};
+use log::{error, info, LevelFilter, trace, warn};
 use simpath::Simpath;
 use simpdiscoverylib::BeaconListener;
 #[cfg(feature = "flowstdlib")]
 use url::Url;
 
-use flowcore::errors::{Result, ResultExt};
+use flowcore::config::Config;
+use flowcore::errors::{bail, Result, ResultExt};
 use flowcore::meta_provider::MetaProvider;
 use flowcore::provider::Provider;
+use flowrlib::discovery::ServiceBrowser;
 use flowrlib::executor::Executor;
 use flowrlib::info as flowrlib_info;
-use flowrlib::services::{CONTROL_SERVICE_NAME, JOB_QUEUES_DISCOVERY_PORT,
-                         JOB_SERVICE_NAME, RESULTS_JOB_SERVICE_NAME};
+use flowrlib::security::CurveKeys;
+use flowrlib::services::{CONTROL_SERVICE_NAME, JOB_QUEUES_DISCOVERY_PORT, JOBS_EXECUTED_MESSAGE,
+                         JOB_SERVICE_NAME, NATIVE_JOB_SERVICE_NAME, RESULTS_JOB_SERVICE_NAME};
 
 /// We'll put our errors in an `errors` module, and other modules in this crate will
 /// `use crate::errors::*;` to get access to everything `error_chain` creates.
 pub mod errors;
 
-/// Try to discover a server offering a particular service by name
-fn discover_service(discovery_port: u16, name: &str) -> Result<String> {
-    let listener = BeaconListener::new(name.as_bytes(), discovery_port)?;
-    let beacon = listener.wait(None)?;
-    let server_address = format!("{}:{}", beacon.service_ip, beacon.service_port);
-    Ok(server_address)
+/// Time to wait before first retrying to listen on a discovery port that is in use, which is
+/// doubled on each subsequent retry up to `MAX_DISCOVERY_RETRY_INTERVAL`
+const DISCOVERY_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest time to wait between retries to listen on a discovery port that is in use
+const MAX_DISCOVERY_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Number of times to retry listening on a discovery port that is in use before giving up
+const MAX_DISCOVERY_RETRIES: u32 = 10;
+
+/// The names of the job, native job, results and control services that executors connect to
+const SERVICE_NAMES: [&str; 4] = [JOB_SERVICE_NAME, NATIVE_JOB_SERVICE_NAME,
+    RESULTS_JOB_SERVICE_NAME, CONTROL_SERVICE_NAME];
+
+/// Time to wait for a service to be discovered before checking if a shutdown has been requested
+const DISCOVERY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A file containing the process id of `flowrex`, that is removed when it exits
+struct PidFile(PathBuf);
+
+impl PidFile {
+    /// Write the id of this process to the file at `path`
+    fn create(path: &Path) -> Result<Self> {
+        fs::write(path, format!("{}\n", process::id()))
+            .chain_err(|| format!("Could not write pid file '{}'", path.display()))?;
+        Ok(PidFile(path.to_path_buf()))
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.0) {
+            error!("Could not remove pid file '{}': {e}", self.0.display());
+        }
+    }
+}
+
+/// Try to discover a server offering a particular service by name, using the beacons sent for it
+/// or its mDNS announcement, returning `None` if a shutdown is requested before it is discovered.
+///
+/// Only one listener at a time can be bound to the discovery port, so if other `flowrex`
+/// processes on this machine are discovering services, retry (backing off between retries)
+/// until they release it, while checking for the service's mDNS announcement. Beacons are only
+/// listened for on IPv4, so if the port cannot be bound after `MAX_DISCOVERY_RETRIES` retries, or
+/// for another reason (e.g. on an IPv6-only host), the service is discovered using only mDNS,
+/// failing if that is not possible either
+fn discover_service(discovery_port: u16, name: &str, shutdown: &AtomicBool)
+    -> Result<Option<String>> {
+    let browser = ServiceBrowser::new(discovery_port, name)
+        .map_err(|e| warn!("Could not discover services using mDNS: {e}"))
+        .ok();
+    let mut listener = None;
+    let mut retries = 0;
+    let mut retry_interval = DISCOVERY_RETRY_INTERVAL;
+    while !shutdown.load(Ordering::SeqCst) {
+        if listener.is_none() && retries <= MAX_DISCOVERY_RETRIES {
+            match BeaconListener::new(name.as_bytes(), discovery_port) {
+                Ok(beacon_listener) => listener = Some(beacon_listener),
+                Err(e) if e.kind() == ErrorKind::AddrInUse && retries < MAX_DISCOVERY_RETRIES => {
+                    trace!("Discovery port {discovery_port} in use, retrying in {}ms",
+                        retry_interval.as_millis());
+                    retries += 1;
+                }
+                Err(e) => {
+                    if browser.is_none() {
+                        if e.kind() == ErrorKind::AddrInUse {
+                            bail!("Discovery port {} still in use after {} retries",
+                                discovery_port, retries);
+                        }
+                        return Err(e.into());
+                    }
+                    warn!("Could not listen for discovery beacons on port {discovery_port}, \
+                        discovering '{name}' using only mDNS: {e}");
+                    retries = MAX_DISCOVERY_RETRIES + 1;
+                }
+            }
+        }
+
+        match &listener {
+            Some(listener) => match listener.wait(Some(DISCOVERY_TIMEOUT)) {
+                Ok(beacon) => return Ok(Some(format!("{}:{}", beacon.service_ip,
+                                                     beacon.service_port))),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {},
+                Err(e) => return Err(e.into()),
+            },
+            None => {
+                thread::sleep(retry_interval);
+                retry_interval = (retry_interval * 2).min(MAX_DISCOVERY_RETRY_INTERVAL);
+            }
+        }
+
+        if let Some(address) = browser.as_ref().and_then(ServiceBrowser::discovered) {
+            return Ok(Some(address));
+        }
+    }
+    Ok(None)
+}
+
+/// Detach from the terminal and continue running in the background, with no standard input
+/// or output
+#[cfg(unix)]
+fn daemonize() -> Result<()> {
+    daemonize::Daemonize::new()
+        .start()
+        .map_err(|e| format!("Could not run as a daemon: {e}").into())
+}
+
+#[cfg(not(unix))]
+fn daemonize() -> Result<()> {
+    bail!("Running as a daemon is not supported on '{}'", env::consts::OS)
 }
 
 /// Main for flowrex binary - call `run()` and print any error that results or exit silently if OK
@@ -61,10 +173,23 @@ fn main() {
 }
 
 fn run() -> Result<()> {
-    let matches = get_matches();
+    let matches = get_command().get_matches();
+
+    if let Some(("completions", completions_matches)) = matches.subcommand() {
+        if let Some(shell) = completions_matches.get_one::<Shell>("shell") {
+            generate(*shell, &mut get_command(), env!("CARGO_BIN_NAME"), &mut io::stdout());
+        }
+        return Ok(());
+    }
+
+    // defaults from config files, that command line options override
+    let config = Config::load(&env::current_dir()
+        .chain_err(|| "Could not get the current working directory")?)?;
 
     let default = String::from("error");
-    let verbosity = matches.get_one::<String>("verbosity").unwrap_or(&default);
+    let verbosity = matches.get_one::<String>("verbosity")
+        .or(config.verbosity.as_ref())
+        .unwrap_or(&default);
     let level = LevelFilter::from_str(verbosity).unwrap_or(LevelFilter::Error);
     let mut builder = Builder::from_default_env();
     builder.filter_level(level).init();
@@ -76,18 +201,52 @@ fn run() -> Result<()> {
     );
     info!("'flowrlib' version {}", flowrlib_info::version());
 
-    start_executors(num_threads(&matches))?;
+    let keys = matches.get_one::<PathBuf>("key-file")
+        .map(|path| CurveKeys::load(path))
+        .transpose()?;
+
+    // resolved before daemonizing, as that changes the working directory to the root directory
+    let pid_file_path = matches.get_one::<PathBuf>("pidfile")
+        .map(|path| env::current_dir().map(|dir| dir.join(path)))
+        .transpose()
+        .chain_err(|| "Could not get the current working directory")?;
+
+    // must be done before any threads are started, as they do not survive it
+    if matches.get_flag("daemon") {
+        daemonize()?;
+    }
+
+    let _pid_file = pid_file_path.as_deref().map(PidFile::create).transpose()?;
+
+    // kept until the end of execution, so all traces are flushed to the collector
+    #[cfg(feature = "otlp")]
+    let _telemetry = flowrlib::telemetry::init(env!("CARGO_PKG_NAME"))?;
+
+    // set on SIGINT or SIGTERM, or when the coordinator asks executors to shut down
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let handler_shutdown = shutdown.clone();
+    ctrlc::set_handler(move || {
+        info!("Shutting down once the jobs received have been executed");
+        handler_shutdown.store(true, Ordering::SeqCst);
+    }).chain_err(|| "Could not set handler for termination signals")?;
+
+    start_executors(num_threads(&matches, config.threads), keys.as_ref(), &shutdown)?;
 
     info!("'{}' has exited", env!("CARGO_PKG_NAME"));
 
     Ok(())
 }
 
-fn start_executors(num_threads: usize) -> Result<()> {
-    // loop, re-discovering flowr announced services that change network address on each run
-    loop {
-        #[allow(unused_mut)]
+fn start_executors(num_threads: usize, keys: Option<&CurveKeys>, shutdown: &Arc<AtomicBool>)
+    -> Result<()> {
+    // loop, re-discovering flowr announced services that change network address on each run,
+    // until a shutdown is requested
+    while !shutdown.load(Ordering::SeqCst) {
         let mut executor = Executor::new();
+        executor.set_shutdown_flag(shutdown.clone());
+        if let Some(curve_keys) = keys {
+            executor.set_keys(curve_keys.clone());
+        }
 
         #[cfg(feature = "flowstdlib")]
         executor.add_lib(
@@ -100,43 +259,49 @@ fn start_executors(num_threads: usize) -> Result<()> {
         let provider = Arc::new(MetaProvider::new(Simpath::new(""),
             PathBuf::from("/"))) as Arc<dyn Provider>;
 
-        let job_service = format!("tcp://{}",
-                                  discover_service(JOB_QUEUES_DISCOVERY_PORT,
-                                                   JOB_SERVICE_NAME)?);
-        let results_service = format!("tcp://{}",
-                                      discover_service(JOB_QUEUES_DISCOVERY_PORT,
-                                                       RESULTS_JOB_SERVICE_NAME)?);
-
-        let control_service = format!("tcp://{}",
-                                      discover_service(JOB_QUEUES_DISCOVERY_PORT,
-                                                       CONTROL_SERVICE_NAME)?);
+        let Some(services) = discover_services(shutdown)? else { break };
+        let [job_service, native_job_service, results_service, control_service] = services;
 
         trace!("Starting '{}' executors", env!("CARGO_PKG_NAME"));
-        executor.start(&provider, num_threads, &job_service, &results_service,
-                       &control_service);
+        executor.start(&provider, num_threads, &job_service, &native_job_service,
+                       &results_service, &control_service);
 
         trace!("Waiting for all executors to complete");
-        executor.wait();
-        trace!("All executors completed, exiting");
+        let jobs_executed = executor.wait();
+        info!("All executors completed. {JOBS_EXECUTED_MESSAGE}{jobs_executed}");
+    }
+
+    Ok(())
+}
+
+// Discover the addresses of the job, native job, results and control services, returning `None`
+// if a shutdown is requested before all of them are discovered
+fn discover_services(shutdown: &AtomicBool) -> Result<Option<[String; 4]>> {
+    let mut addresses: [String; 4] = Default::default();
+    for (address, name) in addresses.iter_mut().zip(SERVICE_NAMES) {
+        match discover_service(JOB_QUEUES_DISCOVERY_PORT, name, shutdown)? {
+            Some(server_address) => *address = format!("tcp://{server_address}"),
+            None => return Ok(None),
+        }
     }
+    Ok(Some(addresses))
 }
 
 // Determine the number of threads to use to execute flows
-// - default (if value is not provided on the command line) to the "available_parallelism()"
+// - the number set in the config files (if value is not provided on the command line)
+// - default (if value is not provided in either) to the "available_parallelism()"
 #[allow(clippy::redundant_closure_for_method_calls)]
-fn num_threads(matches: &ArgMatches) -> usize {
-    match matches.get_one::<usize>("threads") {
-        Some(num_threads) => *num_threads,
+fn num_threads(matches: &ArgMatches, config_threads: Option<usize>) -> usize {
+    match matches.get_one::<usize>("threads").copied().or(config_threads) {
+        Some(num_threads) => num_threads,
         None => thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
     }
 }
 
 // Parse the command line arguments using clap
-fn get_matches() -> ArgMatches {
-    let app = Command::new(env!("CARGO_PKG_NAME"))
-        .version(env!("CARGO_PKG_VERSION"));
-
-    let app = app
+fn get_command() -> Command {
+    Command::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
         .arg(Arg::new("threads")
             .short('t')
             .long("threads")
@@ -144,12 +309,34 @@ fn get_matches() -> ArgMatches {
             .value_parser(clap::value_parser!(usize))
             .value_name("THREADS")
             .help("Set number of threads to use to execute jobs (default: cores available)"))
+        .arg(Arg::new("key-file")
+            .short('K')
+            .long("key-file")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(PathBuf))
+            .value_name("FILE")
+            .help("Encrypt connections to the job queues using the keys in FILE, as used by the coordinator"))
+        .arg(Arg::new("daemon")
+            .short('d')
+            .long("daemon")
+            .action(clap::ArgAction::SetTrue)
+            .help("Run in the background, detached from the terminal"))
+        .arg(Arg::new("pidfile")
+            .long("pidfile")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(PathBuf))
+            .value_name("FILE")
+            .help("Write the process id to FILE, removing it on exit"))
         .arg(Arg::new("verbosity")
             .short('v')
             .long("verbosity")
             .number_of_values(1)
             .value_name("VERBOSITY_LEVEL")
-            .help("Set verbosity level for output (trace, debug, info, warn, error (default), off)"));
-
-    app.get_matches()
+            .help("Set verbosity level for output (trace, debug, info, warn, error (default), off)"))
+        .subcommand(Command::new("completions")
+            .about("Print a shell completion script for flowrex")
+            .arg(Arg::new("shell")
+                .required(true)
+                .value_parser(clap::value_parser!(Shell))
+                .help("The shell to print the completion script for")))
 }
\ No newline at end of file
@@ -0,0 +1,104 @@
+use iced::alignment::Horizontal;
+use iced::widget::{text_input, Button, Column, Row, Text};
+use iced::{Element, Length};
+use iced_aw::Card;
+
+use crate::{CoordinatorSettings, Message};
+
+/// A dialog where the user enters the host and discovery port of a remote coordinator to
+/// connect to
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectDialog {
+    /// IP address of the host the coordinator is running on, or empty for any host
+    pub host: String,
+    /// Port the coordinator announces itself on, for discovery
+    pub port: String,
+    /// An error in the values entered, shown to the user
+    pub error: Option<String>,
+}
+
+impl ConnectDialog {
+    /// Create a dialog, showing the host and port of the `settings` in use if they are for a
+    /// remote coordinator
+    pub(crate) fn new(settings: &CoordinatorSettings) -> Self {
+        match settings {
+            CoordinatorSettings::ClientOnly(host, port) => ConnectDialog {
+                host: host.clone().unwrap_or_default(),
+                port: port.to_string(),
+                error: None,
+            },
+            CoordinatorSettings::Server(_) => ConnectDialog::default(),
+        }
+    }
+
+    /// Return the settings to connect to the coordinator entered, or an error if not valid
+    pub(crate) fn settings(&self) -> Result<CoordinatorSettings, String> {
+        let port = self.port.trim().parse::<u16>()
+            .map_err(|_| format!("'{}' is not a valid discovery port", self.port.trim()))?;
+        let host = match self.host.trim() {
+            "" => None,
+            host => Some(host.to_string()),
+        };
+        Ok(CoordinatorSettings::ClientOnly(host, port))
+    }
+
+    pub(crate) fn view(&self) -> Element<'_, Message> {
+        let mut body = Column::new()
+            .spacing(10)
+            .push(text_input("Host IP address (optional, default any)", &self.host)
+                .on_input(Message::ConnectHostChanged)
+                .on_paste(Message::ConnectHostChanged))
+            .push(text_input("Discovery port", &self.port)
+                .on_input(Message::ConnectPortChanged)
+                .on_paste(Message::ConnectPortChanged)
+                .on_submit(Message::ConnectToCoordinator));
+        if let Some(error) = &self.error {
+            body = body.push(Text::new(error));
+        }
+
+        let button = |label, message| Button::new(Text::new(label)
+            .horizontal_alignment(Horizontal::Center))
+            .width(Length::Fill)
+            .on_press(message);
+
+        Card::new(Text::new("Connect to a Coordinator"), body)
+            .foot(Row::new().spacing(10).padding(5).width(Length::Fill)
+                .push(button("Connect", Message::ConnectToCoordinator))
+                .push(button("Cancel", Message::CloseModal)))
+            .max_width(400.0)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::CoordinatorSettings;
+
+    use super::ConnectDialog;
+
+    fn dialog(host: &str, port: &str) -> ConnectDialog {
+        ConnectDialog {
+            host: host.into(),
+            port: port.into(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn any_host() {
+        assert!(matches!(dialog(" ", "9002").settings(),
+            Ok(CoordinatorSettings::ClientOnly(None, 9002))));
+    }
+
+    #[test]
+    fn host() {
+        assert!(matches!(dialog("192.168.1.10 ", "9002").settings(),
+            Ok(CoordinatorSettings::ClientOnly(Some(host), 9002)) if host == "192.168.1.10"));
+    }
+
+    #[test]
+    fn invalid_port() {
+        assert!(dialog("", "foo").settings().is_err());
+        assert!(dialog("", "70000").settings().is_err());
+    }
+}
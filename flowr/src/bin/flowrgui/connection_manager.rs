@@ -1,8 +1,11 @@
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 use iced::{Subscription, subscription};
+use iced::futures::channel::mpsc::Sender;
+use iced::futures::future;
 use log::{error, info, trace};
 use portpicker::pick_unused_port;
 use tokio::sync::mpsc::Receiver;
@@ -13,11 +16,12 @@ use flowcore::provider::Provider;
 use flowrlib::coordinator::Coordinator;
 use flowrlib::dispatcher::Dispatcher;
 use flowrlib::executor::Executor;
-use flowrlib::services::{CONTROL_SERVICE_NAME, JOB_QUEUES_DISCOVERY_PORT, JOB_SERVICE_NAME, RESULTS_JOB_SERVICE_NAME};
+use flowrlib::services::{bind_endpoint, local_endpoint, CONTROL_SERVICE_NAME,
+    JOB_QUEUES_DISCOVERY_PORT, JOB_SERVICE_NAME, NATIVE_JOB_SERVICE_NAME, RESULTS_JOB_SERVICE_NAME};
 
 use crate::{context, CoordinatorSettings, ServerSettings};
 use crate::errors::{Result, ResultExt};
-use crate::gui::client_connection::{ClientConnection, discover_service};
+use crate::gui::client_connection::{ClientConnection, discover_service_on_host};
 use crate::gui::client_message::ClientMessage;
 use crate::gui::coordinator_connection::{COORDINATOR_SERVICE_NAME, DEBUG_SERVICE_NAME,
                                          enable_service_discovery};
@@ -29,89 +33,117 @@ use crate::gui::submission_handler::CLISubmissionHandler;
 /// States in which the Connection to the Coordinator can find itself
 pub enum CoordinatorState {
     Init(ServerSettings),
-    Discovery(u16),
-    Discovered(String),
+    Discovery(Option<String>, u16),
+    Discovered(String, u16),
     Connected(Receiver<ClientMessage>, Arc<Mutex<ClientConnection>>),
+    Disconnected,
 }
 
+// How long to wait to discover a coordinator, before reporting that none was found
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
 // Creates an asynchronous worker that sends messages back and forth between the App and
-// the Coordinator
-#[allow(clippy::unwrap_used)]
-pub fn subscribe(coordinator_settings: CoordinatorSettings) -> Subscription<CoordinatorMessage> {
+// the Coordinator. A new worker is created for each connection `attempt`, so the App can
+// reconnect, to the same or a different Coordinator, after a connection is lost or fails.
+pub fn subscribe(coordinator_settings: CoordinatorSettings, attempt: usize)
+    -> Subscription<CoordinatorMessage> {
     struct Connect;
     subscription::channel(
-        std::any::TypeId::of::<Connect>(),
+        (std::any::TypeId::of::<Connect>(), attempt),
         100,
         move |mut app_sender| {
             let settings = coordinator_settings.clone();
             async move {
                 let mut state = match settings {
                     CoordinatorSettings::Server(sett) => CoordinatorState::Init(sett.clone()),
-                    CoordinatorSettings::ClientOnly(port) => CoordinatorState::Discovery(port),
+                    CoordinatorSettings::ClientOnly(host, port) =>
+                        CoordinatorState::Discovery(host, port),
                 };
 
                 let mut running = false;
                 loop {
                     match state {
                         CoordinatorState::Init(settings) => {
-                            let discovery_port = start_server(settings)
-                                .unwrap(); // TODO
-                            state = CoordinatorState::Discovery(discovery_port);
+                            state = match start_server(settings) {
+                                Ok(discovery_port) => CoordinatorState::Discovery(None, discovery_port),
+                                Err(e) => disconnected(&mut app_sender,
+                                                       format!("Could not start coordinator: {e}")),
+                            };
                         },
 
-                        CoordinatorState::Discovery(discovery_port) => {
-                            let address = discover_service(discovery_port, COORDINATOR_SERVICE_NAME)
-                                .unwrap(); // TODO
-                            state = CoordinatorState::Discovered(address);
+                        CoordinatorState::Discovery(host, discovery_port) => {
+                            state = match discover_service_on_host(discovery_port,
+                                                                   COORDINATOR_SERVICE_NAME,
+                                                                   host.as_deref(),
+                                                                   DISCOVERY_TIMEOUT) {
+                                Ok(address) => CoordinatorState::Discovered(address, discovery_port),
+                                Err(e) => disconnected(&mut app_sender, e.to_string()),
+                            };
                         }
 
-                        CoordinatorState::Discovered(address) => {
-                            let connection = ClientConnection::new(&address)
-                                .unwrap(); // TODO
-
-                            // Create channel to get messages from the app
-                            let (app_side_sender, app_receiver) = tokio::sync::mpsc::channel(100);
+                        CoordinatorState::Discovered(address, discovery_port) => {
+                            state = match ClientConnection::new(&address) {
+                                Ok(connection) => {
+                                    // Create channel to get messages from the app
+                                    let (app_side_sender, app_receiver) = tokio::sync::mpsc::channel(100);
 
-                            // Send the Sender to the App in a Message, for App to use to send us messages
-                            let _ = app_sender.try_send(CoordinatorMessage::Connected(app_side_sender));
+                                    // Send the Sender to the App in a Message, for App to use to send us messages
+                                    let _ = app_sender.try_send(CoordinatorMessage::Connected(app_side_sender,
+                                                                                              discovery_port));
 
-                            state = CoordinatorState::Connected(app_receiver,
-                                                                Arc::new(Mutex::new(connection)));
+                                    CoordinatorState::Connected(app_receiver,
+                                                                Arc::new(Mutex::new(connection)))
+                                }
+                                Err(e) => disconnected(&mut app_sender,
+                                                       format!("Could not connect to '{address}': {e}")),
+                            };
                         },
 
                         CoordinatorState::Connected(ref mut app_receiver,
                                                     ref connection) => {
                             if running {
                                 // read the message back from the Coordinator
-                                let coordinator_message: CoordinatorMessage = connection
-                                    .lock().unwrap().receive().unwrap(); // TODO
+                                let received = connection.lock()
+                                    .map_err(|_| "Could not lock connection".into())
+                                    .and_then(|connection| connection.receive());
+                                let coordinator_message: CoordinatorMessage = match received {
+                                    Ok(message) => message,
+                                    Err(e) => {
+                                        running = false;
+                                        state = disconnected(&mut app_sender,
+                                                             format!("Lost connection to coordinator: {e}"));
+                                        continue;
+                                    }
+                                };
 
                                 // Forward the message to the app
-                                app_sender.try_send(coordinator_message.clone()).unwrap(); // TODO
+                                let _ = app_sender.try_send(coordinator_message.clone());
 
                                 // If that was end of flow, there will be no response from app
-                                if matches!(&coordinator_message, &CoordinatorMessage::FlowEnd(_)) {
+                                if matches!(&coordinator_message, &CoordinatorMessage::FlowEnd(_)
+                                    | &CoordinatorMessage::FlowCancelled(_)) {
                                     running = false;
                                 } else {
                                     // read the message back from the app and send it to the Coordinator
                                     #[allow(clippy::single_match_else)]
                                     match app_receiver.recv().await {
                                         Some(client_message) => {
-                                            connection.lock().unwrap().send(client_message).unwrap();
+                                            send(connection, client_message);
                                         }
                                         None => error!("Error receiving from app"), // TODO
                                     }
                                 }
-
-                                // TODO handle coordinator exit, disconnection or error
                             } else {
                                 // read the Submit message from the app and send it to the coordinator
                                 if let Some(client_message) = app_receiver.recv().await {
-                                    connection.lock().unwrap().send(client_message).unwrap();
+                                    send(connection, client_message);
                                     running = true;
                                 }
                             }
                         }
+
+                        // wait, until this worker is replaced by one for a new connection attempt
+                        CoordinatorState::Disconnected => future::pending::<()>().await,
                     }
                 }
             }
@@ -119,6 +151,25 @@ pub fn subscribe(coordinator_settings: CoordinatorSettings) -> Subscription<Coor
     )
 }
 
+// Report to the app that the connection to the Coordinator could not be made or was lost
+fn disconnected(app_sender: &mut Sender<CoordinatorMessage>, reason: String) -> CoordinatorState {
+    error!("{reason}");
+    let _ = app_sender.try_send(CoordinatorMessage::Disconnected(reason));
+    CoordinatorState::Disconnected
+}
+
+// Send a message from the app to the Coordinator
+fn send(connection: &Arc<Mutex<ClientConnection>>, message: ClientMessage) {
+    match connection.lock() {
+        Ok(connection) => {
+            if let Err(e) = connection.send(message) {
+                error!("Could not send message to coordinator: {e}");
+            }
+        }
+        Err(_) => error!("Could not lock connection to coordinator"),
+    }
+}
+
 // Start a coordinator server in a background thread, then discover it and return the address
 fn start_server(coordinator_settings: ServerSettings) -> Result<u16> {
     let runtime_port = pick_unused_port().chain_err(|| "No ports free")?;
@@ -156,19 +207,20 @@ fn coordinator(
 
     let mut debug_server = CliDebugHandler { debug_server_connection: debug_connection };
 
-    let provider = Arc::new(MetaProvider::new(coordinator_settings.lib_search_path,
+    let provider = Arc::new(MetaProvider::new(coordinator_settings.lib_search_path(),
                                               PathBuf::from("/"))) as Arc<dyn Provider>;
 
-    let ports = get_four_ports()?;
-    trace!("Announcing three job queues and a control socket on ports: {ports:?}");
+    let ports = get_five_ports()?;
+    trace!("Announcing four job queues and a control socket on ports: {ports:?}");
     let job_queues = get_bind_addresses(ports);
-    let dispatcher = Dispatcher::new(&job_queues)?;
+    let dispatcher = Dispatcher::new(&job_queues, None)?;
     enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, JOB_SERVICE_NAME, ports.0)?;
     enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, RESULTS_JOB_SERVICE_NAME, ports.2)?;
     enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, CONTROL_SERVICE_NAME, ports.3)?;
+    enable_service_discovery(JOB_QUEUES_DISCOVERY_PORT, NATIVE_JOB_SERVICE_NAME, ports.4)?;
 
-    let (job_source_name, context_job_source_name, results_sink, control_socket) =
-        get_connect_addresses(ports);
+    let (job_source_name, context_job_source_name, results_sink, control_socket,
+        native_job_source_name) = get_connect_addresses(ports);
 
     let mut executor = Executor::new();
     // if the command line options request loading native implementation of available native libs
@@ -183,6 +235,7 @@ fn coordinator(
     }
     executor.start(&provider, coordinator_settings.num_threads,
                    &job_source_name,
+                   &native_job_source_name,
                    &results_sink,
                    &control_socket,
     );
@@ -194,6 +247,7 @@ fn coordinator(
     )?;
     context_executor.start(&provider, 1,
                            &context_job_source_name,
+                           &native_job_source_name,
                            &results_sink,
                            &control_socket,
     );
@@ -209,17 +263,19 @@ fn coordinator(
     Ok(coordinator.submission_loop(loop_forever)?)
 }
 
-// Return addresses and ports to be used for each of the three queues
+// Return addresses and ports to be used for each of the queues
 // - (general) job source
 // - context job source
 // - results sink
 // - control messages
-fn get_connect_addresses(ports: (u16, u16, u16, u16)) -> (String, String, String, String) {
+// - native job source
+fn get_connect_addresses(ports: (u16, u16, u16, u16, u16)) -> (String, String, String, String, String) {
     (
-        format!("tcp://127.0.0.1:{}", ports.0),
-        format!("tcp://127.0.0.1:{}", ports.1),
-        format!("tcp://127.0.0.1:{}", ports.2),
-        format!("tcp://127.0.0.1:{}", ports.3),
+        local_endpoint(None, ports.0),
+        local_endpoint(None, ports.1),
+        local_endpoint(None, ports.2),
+        local_endpoint(None, ports.3),
+        local_endpoint(None, ports.4),
     )
 }
 
@@ -228,20 +284,23 @@ fn get_connect_addresses(ports: (u16, u16, u16, u16)) -> (String, String, String
 // - context job source
 // - results sink
 // - control messages
-fn get_bind_addresses(ports: (u16, u16, u16, u16)) -> (String, String, String, String) {
+// - native job source
+fn get_bind_addresses(ports: (u16, u16, u16, u16, u16)) -> (String, String, String, String, String) {
     (
-        format!("tcp://*:{}", ports.0),
-        format!("tcp://*:{}", ports.1),
-        format!("tcp://*:{}", ports.2),
-        format!("tcp://*:{}", ports.3),
+        bind_endpoint(None, ports.0),
+        bind_endpoint(None, ports.1),
+        bind_endpoint(None, ports.2),
+        bind_endpoint(None, ports.3),
+        bind_endpoint(None, ports.4),
     )
 }
 
-// Return four free ports to use for client-coordinator message queues
-fn get_four_ports() -> Result<(u16, u16, u16, u16)> {
+// Return five free ports to use for client-coordinator message queues
+fn get_five_ports() -> Result<(u16, u16, u16, u16, u16)> {
     Ok((pick_unused_port().chain_err(|| "No ports free")?,
         pick_unused_port().chain_err(|| "No ports free")?,
         pick_unused_port().chain_err(|| "No ports free")?,
         pick_unused_port().chain_err(|| "No ports free")?,
+        pick_unused_port().chain_err(|| "No ports free")?,
     ))
 }
\ No newline at end of file
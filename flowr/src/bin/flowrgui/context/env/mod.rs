@@ -0,0 +1,4 @@
+/// The `get` module to get the value of an environment variable
+pub mod get;
+/// The `vars` module to get all the environment variables the flow is allowed to read
+pub mod vars;
@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use serde_json::Value;
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+/// `Implementation` struct for the `file_remove` function
+pub struct FileRemove {
+    /// It holds a reference to the runtime client in order to remove files
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for FileRemove {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let filename = inputs.first().and_then(Value::as_str).ok_or("Could not get filename")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::Remove(filename.into())) {
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::gui::client_message::ClientMessage;
+    use crate::gui::coordinator_message::CoordinatorMessage;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::FileRemove;
+
+    #[test]
+    #[serial]
+    fn remove_file() {
+        let file_path = "/fake/remove_test";
+        let server_connection = wait_for_then_send(
+            CoordinatorMessage::Remove(file_path.to_string()), ClientMessage::Ack);
+
+        let remover = &FileRemove { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = remover.run(&[json!(file_path)]).expect("_file_remove() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+}
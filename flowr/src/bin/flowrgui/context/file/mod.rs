@@ -3,4 +3,14 @@
 pub mod file_write;
 /// the `file_read` module to take care of reading files for a flow
 #[allow(clippy::module_name_repetitions)]
-pub mod file_read;
\ No newline at end of file
+pub mod file_read;
+/// the `file_append` module to take care of appending to files for a flow
+#[allow(clippy::module_name_repetitions)]
+pub mod file_append;
+/// the `file_remove` module to take care of removing files for a flow
+#[allow(clippy::module_name_repetitions)]
+pub mod file_remove;
+/// the `list` module to list the entries of a directory for a flow
+pub mod list;
+/// the `stat` module to get the metadata of a file or directory for a flow
+pub mod stat;
@@ -0,0 +1,67 @@
+use flowcore::errors::Result;
+use serde_json::Value;
+
+/// Get a pair of numbers, such as an (x, y) point or a (width, height) size, from the input
+/// at `index` of `inputs`
+pub(crate) fn pair(inputs: &[Value], index: usize, name: &str) -> Result<(u32, u32)> {
+    let array = inputs.get(index).and_then(Value::as_array)
+        .ok_or_else(|| format!("Could not get {name}"))?;
+    let first = array.first().and_then(Value::as_u64)
+        .ok_or_else(|| format!("Could not get {name}"))?;
+    let second = array.get(1).and_then(Value::as_u64)
+        .ok_or_else(|| format!("Could not get {name}"))?;
+    Ok((u32::try_from(first).map_err(|_| format!("Integer overflow in '{name}'"))?,
+        u32::try_from(second).map_err(|_| format!("Integer overflow in '{name}'"))?))
+}
+
+/// Get an (r, g, b) color from the input at `index` of `inputs`
+pub(crate) fn color(inputs: &[Value], index: usize) -> Result<(u8, u8, u8)> {
+    let array = inputs.get(index).and_then(Value::as_array).ok_or("Could not get color")?;
+    let mut values = array.iter().map(|value| value.as_u64()
+        .and_then(|value| u8::try_from(value).ok())
+        .ok_or("Invalid value in 'color'"));
+    match (values.next(), values.next(), values.next()) {
+        (Some(r), Some(g), Some(b)) => Ok((r?, g?, b?)),
+        _ => Err("Could not get color".into()),
+    }
+}
+
+/// Get the name of the drawing from the input at `index` of `inputs`
+pub(crate) fn name(inputs: &[Value], index: usize) -> Result<String> {
+    Ok(inputs.get(index).and_then(Value::as_str).ok_or("Could not get name")?.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{color, name, pair};
+
+    #[test]
+    fn get_pair() {
+        assert_eq!(pair(&[json!([10, 20])], 0, "point").expect("Could not get pair"), (10, 20));
+    }
+
+    #[test]
+    fn invalid_pair() {
+        assert!(pair(&[json!([1.2, 3.4])], 0, "point").is_err());
+        assert!(pair(&[json!([1])], 0, "point").is_err());
+        assert!(pair(&[], 0, "point").is_err());
+    }
+
+    #[test]
+    fn get_color() {
+        assert_eq!(color(&[json!([255, 128, 0])], 0).expect("Could not get color"), (255, 128, 0));
+    }
+
+    #[test]
+    fn invalid_color() {
+        assert!(color(&[json!([256, 0, 0])], 0).is_err());
+        assert!(color(&[json!([0, 0])], 0).is_err());
+    }
+
+    #[test]
+    fn get_name() {
+        assert_eq!(name(&[json!("canvas")], 0).expect("Could not get name"), "canvas");
+    }
+}
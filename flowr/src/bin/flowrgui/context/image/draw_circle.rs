@@ -0,0 +1,78 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use serde_json::Value;
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+use super::draw_args::{color, name, pair};
+
+/// `Implementation` struct for the `draw_circle` function
+pub struct DrawCircle {
+    /// It holds a reference to the runtime client in order to send commands
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for DrawCircle {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let center = pair(inputs, 0, "center")?;
+        let radius = inputs.get(1).and_then(Value::as_u64).ok_or("Could not get radius")?;
+        let radius = u32::try_from(radius).map_err(|_| "Integer overflow in 'radius'")?;
+        let color = color(inputs, 2)?;
+        let filled = inputs.get(3).and_then(Value::as_bool).unwrap_or(false);
+        let size = pair(inputs, 4, "size")?;
+        let name = name(inputs, 5)?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        let _: Result<ClientMessage> = server.send_and_receive_response(
+            CoordinatorMessage::DrawCircle(center, radius, color, filled, size, name));
+
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::gui::client_message::ClientMessage;
+    use crate::gui::coordinator_message::CoordinatorMessage;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::DrawCircle;
+
+    #[test]
+    #[serial]
+    fn valid() {
+        let inputs = [json!([50, 50]), json!(25), json!([0, 0, 255]),
+                      json!(false), json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawCircle(
+            (50, 50), 25, (0, 0, 255), false, (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawCircle { server_connection } as &dyn Implementation;
+        let (value, run_again) = drawer.run(&inputs).expect("run() failed");
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_color() {
+        let inputs = [json!([50, 50]), json!(25), json!("blue"),
+                      json!(false), json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawCircle(
+            (50, 50), 25, (0, 0, 255), false, (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawCircle { server_connection } as &dyn Implementation;
+        assert!(drawer.run(&inputs).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use serde_json::Value;
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+use super::draw_args::{color, name, pair};
+
+/// `Implementation` struct for the `draw_line` function
+pub struct DrawLine {
+    /// It holds a reference to the runtime client in order to send commands
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for DrawLine {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let from = pair(inputs, 0, "from")?;
+        let to = pair(inputs, 1, "to")?;
+        let color = color(inputs, 2)?;
+        let size = pair(inputs, 3, "size")?;
+        let name = name(inputs, 4)?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        let _: Result<ClientMessage> = server.send_and_receive_response(
+            CoordinatorMessage::DrawLine(from, to, color, size, name));
+
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::gui::client_message::ClientMessage;
+    use crate::gui::coordinator_message::CoordinatorMessage;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::DrawLine;
+
+    #[test]
+    #[serial]
+    fn valid() {
+        let inputs = [json!([0, 0]), json!([10, 20]), json!([255, 0, 0]),
+                      json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawLine(
+            (0, 0), (10, 20), (255, 0, 0), (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawLine { server_connection } as &dyn Implementation;
+        let (value, run_again) = drawer.run(&inputs).expect("run() failed");
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_color() {
+        let inputs = [json!([0, 0]), json!([10, 20]), json!([300, 0, 0]),
+                      json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawLine(
+            (0, 0), (10, 20), (255, 0, 0), (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawLine { server_connection } as &dyn Implementation;
+        assert!(drawer.run(&inputs).is_err());
+    }
+}
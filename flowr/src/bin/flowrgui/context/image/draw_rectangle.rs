@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use serde_json::Value;
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+use super::draw_args::{color, name, pair};
+
+/// `Implementation` struct for the `draw_rectangle` function
+pub struct DrawRectangle {
+    /// It holds a reference to the runtime client in order to send commands
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for DrawRectangle {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let origin = pair(inputs, 0, "origin")?;
+        let dimensions = pair(inputs, 1, "dimensions")?;
+        let color = color(inputs, 2)?;
+        let filled = inputs.get(3).and_then(Value::as_bool).unwrap_or(false);
+        let size = pair(inputs, 4, "size")?;
+        let name = name(inputs, 5)?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        let _: Result<ClientMessage> = server.send_and_receive_response(
+            CoordinatorMessage::DrawRectangle(origin, dimensions, color, filled, size, name));
+
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::gui::client_message::ClientMessage;
+    use crate::gui::coordinator_message::CoordinatorMessage;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::DrawRectangle;
+
+    #[test]
+    #[serial]
+    fn valid() {
+        let inputs = [json!([5, 5]), json!([20, 10]), json!([0, 255, 0]),
+                      json!(true), json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawRectangle(
+            (5, 5), (20, 10), (0, 255, 0), true, (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawRectangle { server_connection } as &dyn Implementation;
+        let (value, run_again) = drawer.run(&inputs).expect("run() failed");
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_color() {
+        let inputs = [json!([5, 5]), json!([20, 10]), json!([0, 255]),
+                      json!(true), json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawRectangle(
+            (5, 5), (20, 10), (0, 255, 0), true, (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawRectangle { server_connection } as &dyn Implementation;
+        assert!(drawer.run(&inputs).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use serde_json::Value;
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+use super::draw_args::{color, name, pair};
+
+/// `Implementation` struct for the `draw_text` function
+pub struct DrawText {
+    /// It holds a reference to the runtime client in order to send commands
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for DrawText {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let position = pair(inputs, 0, "position")?;
+        let text = inputs.get(1).and_then(Value::as_str).ok_or("Could not get text")?;
+        let color = color(inputs, 2)?;
+        let size = pair(inputs, 3, "size")?;
+        let name = name(inputs, 4)?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        let _: Result<ClientMessage> = server.send_and_receive_response(
+            CoordinatorMessage::DrawText(position, text.into(), color, size, name));
+
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::gui::client_message::ClientMessage;
+    use crate::gui::coordinator_message::CoordinatorMessage;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::DrawText;
+
+    #[test]
+    #[serial]
+    fn valid() {
+        let inputs = [json!([10, 10]), json!("Hello"), json!([255, 255, 255]),
+                      json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawText(
+            (10, 10), "Hello".into(), (255, 255, 255), (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawText { server_connection } as &dyn Implementation;
+        let (value, run_again) = drawer.run(&inputs).expect("run() failed");
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_color() {
+        let inputs = [json!([10, 10]), json!("Hello"), json!([255, 255, -1]),
+                      json!([100, 100]), json!("canvas")];
+        let message = CoordinatorMessage::DrawText(
+            (10, 10), "Hello".into(), (255, 255, 255), (100, 100), "canvas".into());
+
+        let server_connection = wait_for_then_send(message, ClientMessage::Ack);
+        let drawer = &DrawText { server_connection } as &dyn Implementation;
+        assert!(drawer.run(&inputs).is_err());
+    }
+}
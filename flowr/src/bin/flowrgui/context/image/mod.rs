@@ -1,3 +1,15 @@
 /// A module with functions for writing images
 #[allow(clippy::module_name_repetitions)]
-pub mod image_buffer;
\ No newline at end of file
+pub mod image_buffer;
+/// the `image_read` module to read images for a flow
+pub mod image_read;
+/// Functions to get the arguments of drawing functions from their inputs
+mod draw_args;
+/// the `draw_line` module to draw lines on a drawing for a flow
+pub mod draw_line;
+/// the `draw_rectangle` module to draw rectangles on a drawing for a flow
+pub mod draw_rectangle;
+/// the `draw_circle` module to draw circles on a drawing for a flow
+pub mod draw_circle;
+/// the `draw_text` module to draw text on a drawing for a flow
+pub mod draw_text;
@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+/// `Implementation` struct for the `delete` function
+pub struct Delete {
+    /// It holds a reference to the runtime client in order to delete the value from its store
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for Delete {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let key = inputs.first().and_then(Value::as_str).ok_or("Could not get key")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::KvDelete(key.into())) {
+            Ok(ClientMessage::KvValue(value)) => Ok((value, RUN_AGAIN)),
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use serial_test::serial;
+
+    use flowcore::{Implementation, RUN_AGAIN};
+
+    use crate::gui::client_message::ClientMessage::KvValue;
+    use crate::gui::coordinator_message::CoordinatorMessage::KvDelete;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::Delete;
+
+    #[test]
+    #[serial]
+    fn delete_value() {
+        let server_connection = wait_for_then_send(KvDelete("count".into()), KvValue(Some(json!(42))));
+        let deleter = &Delete { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = deleter.run(&[json!("count")]).expect("_delete() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!(42)));
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+/// `Implementation` struct for the `put` function
+pub struct Put {
+    /// It holds a reference to the runtime client in order to store the value
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for Put {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let key = inputs.first().and_then(Value::as_str).ok_or("Could not get key")?;
+        let value = inputs.get(1).ok_or("Could not get value")?;
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(CoordinatorMessage::KvPut(key.into(), value.clone())) {
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use serial_test::serial;
+
+    use flowcore::{Implementation, RUN_AGAIN};
+
+    use crate::gui::client_message::ClientMessage::{Ack, Error};
+    use crate::gui::coordinator_message::CoordinatorMessage::KvPut;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::Put;
+
+    #[test]
+    #[serial]
+    fn put_value() {
+        let server_connection = wait_for_then_send(KvPut("count".into(), json!(42)), Ack);
+        let putter = &Put { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = putter.run(&[json!("count"), json!(42)]).expect("_put() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn put_error() {
+        let server_connection = wait_for_then_send(KvPut("count".into(), json!(42)),
+                                                   Error("Could not write store".into()));
+        let putter = &Put { server_connection } as &dyn Implementation;
+
+        assert!(putter.run(&[json!("count"), json!(42)]).is_err());
+    }
+}
@@ -11,9 +11,14 @@ use url::Url;
 use crate::CoordinatorConnection;
 
 mod args;
+mod env;
 mod file;
 mod image;
+mod kv;
+mod net;
+mod process;
 mod stdio;
+mod time;
 
 /// Return a `LibraryManifest` for the context functions
 pub fn get_manifest(
@@ -34,6 +39,18 @@ pub fn get_manifest(
             server_connection: server_connection.clone(),
         })),
     );
+    manifest.locators.insert(
+        Url::parse("context://env/get").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(env::get::Get {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://env/vars").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(env::vars::Vars {
+            server_connection: server_connection.clone(),
+        })),
+    );
     manifest.locators.insert(
         Url::parse("context://file/file_write")
             .chain_err(|| "Could not parse url")?,
@@ -48,6 +65,32 @@ pub fn get_manifest(
             server_connection: server_connection.clone(),
         })),
     );
+    manifest.locators.insert(
+        Url::parse("context://file/file_append")
+            .chain_err(|| "Could not parse url")?,
+        Native(Arc::new(file::file_append::FileAppend {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://file/file_remove")
+            .chain_err(|| "Could not parse url")?,
+        Native(Arc::new(file::file_remove::FileRemove {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://file/list").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(file::list::List {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://file/stat").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(file::stat::Stat {
+            server_connection: server_connection.clone(),
+        })),
+    );
     manifest.locators.insert(
         Url::parse("context://image/image_buffer")
             .chain_err(|| "Could not parse url")?,
@@ -55,6 +98,66 @@ pub fn get_manifest(
             server_connection: server_connection.clone(),
         })),
     );
+    manifest.locators.insert(
+        Url::parse("context://image/image_read").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(image::image_read::ImageRead {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://image/draw_line").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(image::draw_line::DrawLine {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://image/draw_rectangle").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(image::draw_rectangle::DrawRectangle {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://image/draw_circle").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(image::draw_circle::DrawCircle {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://image/draw_text").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(image::draw_text::DrawText {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://kv/get").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(kv::get::Get {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://kv/put").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(kv::put::Put {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://kv/delete").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(kv::delete::Delete {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://net/tcp_send").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(net::tcp_send::TcpSend {
+            server_connection: server_connection.clone(),
+        })),
+    );
+    manifest.locators.insert(
+        Url::parse("context://process/exec").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(process::exec::Exec {
+            server_connection: server_connection.clone(),
+        })),
+    );
     manifest.locators.insert(
         Url::parse("context://stdio/readline")
             .chain_err(|| "Could not parse url")?,
@@ -74,10 +177,20 @@ pub fn get_manifest(
             server_connection: server_connection.clone(),
         })),
     );
+    manifest.locators.insert(
+        Url::parse("context://stdio/stdout_styled").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(stdio::stdout_styled::StdoutStyled {
+            server_connection: server_connection.clone(),
+        })),
+    );
     manifest.locators.insert(
         Url::parse("context://stdio/stderr").chain_err(|| "Could not parse url")?,
         Native(Arc::new(stdio::stderr::Stderr { server_connection })),
     );
+    manifest.locators.insert(
+        Url::parse("context://time/clock").chain_err(|| "Could not parse url")?,
+        Native(Arc::new(time::clock::Clock)),
+    );
 
     Ok(manifest)
 }
@@ -0,0 +1,99 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+/// `Implementation` struct for the `tcp_send` function
+pub struct TcpSend {
+    /// It holds a reference to the runtime client in order to have it connect to the server
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for TcpSend {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let address = inputs.first().and_then(Value::as_str).ok_or("Could not get address")?;
+        let byte_array = inputs.get(1).and_then(Value::as_array).ok_or("Could not get bytes")?;
+        let await_response = inputs.get(2).and_then(Value::as_bool).unwrap_or(false);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let bytes = byte_array
+            .iter()
+            .map(|byte_value| byte_value.as_u64().unwrap_or(0) as u8)
+            .collect();
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(
+            CoordinatorMessage::TcpSend(address.into(), bytes, await_response)) {
+            Ok(ClientMessage::TcpResponse(response)) => {
+                let mut output_map = serde_json::Map::new();
+                output_map.insert("string".into(), json!(String::from_utf8_lossy(&response)));
+                output_map.insert("bytes".into(), json!(response));
+                Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+            }
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use serial_test::serial;
+
+    use flowcore::{Implementation, RUN_AGAIN};
+
+    use crate::gui::client_message::ClientMessage::{Ack, Error, TcpResponse};
+    use crate::gui::coordinator_message::CoordinatorMessage::TcpSend as TcpSendMessage;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::TcpSend;
+
+    #[test]
+    #[serial]
+    fn send_and_await_response() {
+        let server_connection = wait_for_then_send(
+            TcpSendMessage("localhost:7000".into(), b"PING\n".to_vec(), true),
+            TcpResponse(b"PONG\n".to_vec()));
+        let sender = &TcpSend { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = sender.run(&[json!("localhost:7000"), json!(b"PING\n".to_vec()),
+            json!(true)]).expect("_tcp_send() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!({"bytes": b"PONG\n".to_vec(), "string": "PONG\n"})));
+    }
+
+    #[test]
+    #[serial]
+    fn send_without_response() {
+        let server_connection = wait_for_then_send(
+            TcpSendMessage("localhost:7000".into(), b"LOG\n".to_vec(), false), Ack);
+        let sender = &TcpSend { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = sender.run(&[json!("localhost:7000"), json!(b"LOG\n".to_vec()),
+            json!(false)]).expect("_tcp_send() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn send_connection_refused() {
+        let server_connection = wait_for_then_send(
+            TcpSendMessage("localhost:7000".into(), vec![], false),
+            Error("Could not connect".into()));
+        let sender = &TcpSend { server_connection } as &dyn Implementation;
+
+        assert!(sender.run(&[json!("localhost:7000"), json!([]), json!(false)]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::CoordinatorMessage;
+
+/// `Implementation` struct for the `exec` function
+pub struct Exec {
+    /// It holds a reference to the runtime client in order to have it run the command
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for Exec {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let command = inputs.first().and_then(Value::as_str)
+            .ok_or("Could not get the command to run")?;
+        let args = match inputs.get(1) {
+            Some(Value::Array(args)) => args.iter()
+                .map(|arg| arg.as_str().map_or_else(|| arg.to_string(), ToString::to_string))
+                .collect(),
+            Some(Value::String(arg)) => vec![arg.clone()],
+            _ => vec![],
+        };
+        let stdin = inputs.get(2).and_then(Value::as_str).unwrap_or_default();
+
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        match server.send_and_receive_response(
+            CoordinatorMessage::Exec(command.into(), args, stdin.into())) {
+            Ok(ClientMessage::ProcessOutput(code, stdout, stderr)) => {
+                let mut output_map = serde_json::Map::new();
+                if let Some(code) = code {
+                    output_map.insert("code".into(), json!(code));
+                }
+                output_map.insert("stdout".into(), json!(stdout));
+                output_map.insert("stderr".into(), json!(stderr));
+                Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+            }
+            Ok(ClientMessage::Error(message)) => bail!(message),
+            _ => Ok((None, RUN_AGAIN)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use serial_test::serial;
+
+    use flowcore::{Implementation, RUN_AGAIN};
+
+    use crate::gui::client_message::ClientMessage::{Error, ProcessOutput};
+    use crate::gui::coordinator_message::CoordinatorMessage::Exec as ExecMessage;
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::Exec;
+
+    #[test]
+    #[serial]
+    fn exec_command() {
+        let server_connection = wait_for_then_send(
+            ExecMessage("echo".into(), vec!["hello".into()], String::new()),
+            ProcessOutput(Some(0), "hello\n".into(), String::new()));
+        let exec = &Exec { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = exec.run(&[json!("echo"), json!(["hello"]), json!("")])
+            .expect("_exec() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, Some(json!({"code": 0, "stdout": "hello\n", "stderr": ""})));
+    }
+
+    #[test]
+    #[serial]
+    fn exec_not_allowed() {
+        let server_connection = wait_for_then_send(
+            ExecMessage("echo".into(), vec![], String::new()),
+            Error("not allowed".into()));
+        let exec = &Exec { server_connection } as &dyn Implementation;
+
+        assert!(exec.run(&[json!("echo"), json!([]), json!("")]).is_err());
+    }
+}
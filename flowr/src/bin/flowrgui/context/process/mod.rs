@@ -0,0 +1,2 @@
+/// The `exec` module to run a command
+pub mod exec;
@@ -0,0 +1,98 @@
+use std::sync::{Arc, Mutex};
+
+use flowcore::{Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{Result, ResultExt};
+use serde_json::Value;
+
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_connection::CoordinatorConnection;
+use crate::gui::coordinator_message::{CoordinatorMessage, StyledText};
+
+/// `Implementation` struct for the `stdout_styled` function
+pub struct StdoutStyled {
+    /// It holds a reference to the runtime client in order to write output
+    pub server_connection: Arc<Mutex<CoordinatorConnection>>,
+}
+
+impl Implementation for StdoutStyled {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let input = inputs.first().ok_or("Could not get input")?;
+
+        let spans: Vec<StyledText> = match input {
+            Value::String(text) => vec![StyledText { text: text.clone(), ..Default::default() }],
+            Value::Array(_) => serde_json::from_value(input.clone())
+                .chain_err(|| format!("Could not parse styled text from '{input}'"))?,
+            _ => vec![serde_json::from_value(input.clone())
+                .chain_err(|| format!("Could not parse styled text from '{input}'"))?],
+        };
+
+        // Gain sole access to send to the client to avoid mixing output from other functions
+        let mut server = self.server_connection.lock()
+            .map_err(|_| "Could not lock server")?;
+
+        let _: Result<ClientMessage> = server
+            .send_and_receive_response(CoordinatorMessage::StdoutStyled(spans));
+
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::{Implementation, RUN_AGAIN};
+    use serde_json::json;
+    use serial_test::serial;
+
+    use crate::gui::client_message::ClientMessage;
+    use crate::gui::coordinator_message::{Color, CoordinatorMessage, StyledText};
+    use crate::gui::test_helper::test::wait_for_then_send;
+
+    use super::StdoutStyled;
+
+    #[test]
+    #[serial]
+    fn send_styled_spans() {
+        let spans = vec![
+            StyledText { text: "Passed".into(), color: Some(Color::Green), bold: true, ..Default::default() },
+            StyledText { text: " 3 tests".into(), ..Default::default() },
+        ];
+        let server_connection = wait_for_then_send(CoordinatorMessage::StdoutStyled(spans),
+                                                   ClientMessage::Ack);
+        let stdout = &StdoutStyled { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = stdout.run(&[json!([
+            {"text": "Passed", "color": "green", "bold": true},
+            {"text": " 3 tests"}
+        ])]).expect("_stdout_styled() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn send_styled_object() {
+        let spans = vec![
+            StyledText { text: "Warning".into(), color: Some(Color::Yellow), ..Default::default() },
+        ];
+        let server_connection = wait_for_then_send(CoordinatorMessage::StdoutStyled(spans),
+                                                   ClientMessage::Ack);
+        let stdout = &StdoutStyled { server_connection } as &dyn Implementation;
+
+        let (value, run_again) = stdout.run(&[json!({"text": "Warning", "color": "yellow"})])
+            .expect("_stdout_styled() failed");
+
+        assert_eq!(run_again, RUN_AGAIN);
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_color() {
+        let server_connection = wait_for_then_send(CoordinatorMessage::StdoutStyled(vec![]),
+                                                   ClientMessage::Ack);
+        let stdout = &StdoutStyled { server_connection } as &dyn Implementation;
+
+        assert!(stdout.run(&[json!({"text": "Oops", "color": "plaid"})]).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::{Arc, Mutex};
+
+use iced::{Subscription, subscription};
+use iced::futures::future;
+use log::{error, info};
+use tokio::sync::mpsc::Receiver;
+
+use flowrlib::debug_command::DebugCommand;
+use flowrlib::debug_command::DebugCommand::{Ack, DebugClientStarting, ExitDebugger};
+
+use crate::gui::client_connection::{ClientConnection, discover_service};
+use crate::gui::coordinator_connection::DEBUG_SERVICE_NAME;
+use crate::gui::debug_client;
+use crate::gui::debug_message::DebugServerMessage;
+
+/// Events sent to the App about the debugger of the Coordinator
+#[derive(Debug, Clone)]
+pub enum DebugEvent {
+    /// A connection to the debug service has been made, commands for it are sent on this `Sender`
+    Connected(tokio::sync::mpsc::Sender<DebugCommand>),
+    /// Lines of text describing a message received from the debug server
+    Output(Vec<String>),
+    /// The debug server is waiting for a command, at the job number given
+    WaitingForCommand(usize),
+    /// Connection with the debug service was lost, or could not be made
+    Disconnected(String),
+}
+
+/// States in which the Connection to the debug service can find itself
+enum DebuggerState {
+    Discovery(u16),
+    Connected(Receiver<DebugCommand>, Arc<Mutex<ClientConnection>>),
+    Disconnected,
+}
+
+// Creates an asynchronous worker that discovers the debug service of the Coordinator using
+// the `discovery_port` and then relays messages between it and the Debugger tab of the App
+pub fn subscribe(discovery_port: u16) -> Subscription<DebugEvent> {
+    struct Debug;
+    subscription::channel(
+        std::any::TypeId::of::<Debug>(),
+        100,
+        move |mut app_sender| {
+            async move {
+                let mut state = DebuggerState::Discovery(discovery_port);
+
+                loop {
+                    match state {
+                        DebuggerState::Discovery(port) => {
+                            match discover_service(port, DEBUG_SERVICE_NAME)
+                                .and_then(|address| ClientConnection::new(&address)) {
+                                Ok(connection) => {
+                                    info!("Connected to the debug service");
+                                    // Create channel to get commands from the app
+                                    let (app_side_sender, app_receiver) =
+                                        tokio::sync::mpsc::channel(100);
+                                    let _ = app_sender.try_send(DebugEvent::Connected(app_side_sender));
+
+                                    // Send a first message to initialize the connection
+                                    let connection = Arc::new(Mutex::new(connection));
+                                    send(&connection, DebugClientStarting);
+                                    state = DebuggerState::Connected(app_receiver, connection);
+                                }
+                                Err(e) => {
+                                    let _ = app_sender.try_send(DebugEvent::Disconnected(e.to_string()));
+                                    state = DebuggerState::Disconnected;
+                                }
+                            }
+                        }
+
+                        DebuggerState::Connected(ref mut app_receiver, ref connection) => {
+                            let received = connection.lock()
+                                .map_err(|_| "Could not lock connection".into())
+                                .and_then(|connection| connection.receive::<DebugServerMessage>());
+                            match received {
+                                Ok(message) => {
+                                    let _ = app_sender.try_send(DebugEvent::Output(
+                                        debug_client::describe(&message)));
+
+                                    // respond with the user's command if the server is waiting for one
+                                    let response = match debug_client::waiting_for_command(&message) {
+                                        Some(job_number) => {
+                                            let _ = app_sender.try_send(
+                                                DebugEvent::WaitingForCommand(job_number));
+                                            app_receiver.recv().await.unwrap_or(ExitDebugger)
+                                        }
+                                        None => Ack,
+                                    };
+
+                                    send(connection, response);
+                                }
+                                Err(e) => {
+                                    error!("Error receiving message from debugger: {e}");
+                                    let _ = app_sender.try_send(DebugEvent::Disconnected(e.to_string()));
+                                    state = DebuggerState::Disconnected;
+                                }
+                            }
+                        }
+
+                        DebuggerState::Disconnected => future::pending::<()>().await,
+                    }
+                }
+            }
+        }
+    )
+}
+
+// Send a command from the app to the debug service
+fn send(connection: &Arc<Mutex<ClientConnection>>, command: DebugCommand) {
+    match connection.lock() {
+        Ok(connection) => {
+            if let Err(e) = connection.send(command) {
+                error!("Could not send command to debugger: {e}");
+            }
+        }
+        Err(_) => error!("Could not lock connection to debugger"),
+    }
+}
@@ -0,0 +1,96 @@
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+use iced::mouse::Cursor;
+use iced::widget::canvas::{Frame, Geometry, Path, Program, Stroke, Text};
+
+use crate::Message;
+
+/// A shape drawn by a flow on a [Drawing]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Shape {
+    /// A line between two points
+    Line { from: Point, to: Point, color: Color },
+    /// A rectangle with its top left corner at `origin`, filled or just its outline
+    Rectangle { origin: Point, size: Size, color: Color, filled: bool },
+    /// A circle, filled or just its outline
+    Circle { center: Point, radius: f32, color: Color, filled: bool },
+    /// Text with its top left corner at `position`
+    Text { position: Point, content: String, color: Color },
+}
+
+/// A canvas of a fixed size that a flow draws shapes on, shown in the Images tab
+#[derive(Debug, Clone)]
+pub(crate) struct Drawing {
+    pub width: u32,
+    pub height: u32,
+    pub shapes: Vec<Shape>,
+}
+
+impl Drawing {
+    /// Create a new, empty, drawing of `width` by `height` pixels
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        Drawing {
+            width,
+            height,
+            shapes: vec![],
+        }
+    }
+
+    /// Convert an (r, g, b) triplet sent by a flow into a `Color`
+    pub(crate) fn color((red, green, blue): (u8, u8, u8)) -> Color {
+        Color::from_rgb8(red, green, blue)
+    }
+
+    /// Convert an (x, y) coordinate sent by a flow into a `Point`
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn point((x, y): (u32, u32)) -> Point {
+        Point::new(x as f32, y as f32)
+    }
+}
+
+impl Program<Message> for Drawing {
+    type State = ();
+
+    fn draw(&self, _state: &(), renderer: &Renderer, _theme: &Theme, bounds: Rectangle,
+            _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        // scale the drawing to fit in the space it is given, at the zoom selected in the Images tab
+        #[allow(clippy::cast_precision_loss)]
+        let (width, height) = (self.width as f32, self.height as f32);
+        frame.scale((bounds.width / width).min(bounds.height / height));
+
+        frame.fill_rectangle(Point::ORIGIN, Size::new(width, height), Color::BLACK);
+
+        for shape in &self.shapes {
+            match shape {
+                Shape::Line { from, to, color } => {
+                    frame.stroke(&Path::line(*from, *to), Stroke::default().with_color(*color));
+                }
+                Shape::Rectangle { origin, size, color, filled: true } => {
+                    frame.fill_rectangle(*origin, *size, *color);
+                }
+                Shape::Rectangle { origin, size, color, filled: false } => {
+                    frame.stroke(&Path::rectangle(*origin, *size),
+                                 Stroke::default().with_color(*color));
+                }
+                Shape::Circle { center, radius, color, filled: true } => {
+                    frame.fill(&Path::circle(*center, *radius), *color);
+                }
+                Shape::Circle { center, radius, color, filled: false } => {
+                    frame.stroke(&Path::circle(*center, *radius),
+                                 Stroke::default().with_color(*color));
+                }
+                Shape::Text { position, content, color } => {
+                    frame.fill_text(Text {
+                        content: content.clone(),
+                        position: *position,
+                        color: *color,
+                        ..Text::default()
+                    });
+                }
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
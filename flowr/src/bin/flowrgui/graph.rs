@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme, Vector};
+use iced::alignment::{Horizontal, Vertical};
+use iced::mouse::Cursor;
+use iced::widget::canvas::{Frame, Geometry, Path, Program, Stroke, Text};
+
+use flowcore::model::flow_manifest::FlowManifest;
+
+use crate::Message;
+
+// Dimensions used to lay out the graph, before it is scaled to fit the space available
+const NODE_WIDTH: f32 = 160.0;
+const NODE_HEIGHT: f32 = 40.0;
+const COLUMN_SPACING: f32 = 80.0;
+const ROW_SPACING: f32 = 30.0;
+const MARGIN: f32 = 20.0;
+
+/// The execution state of a function in the graph, based on the jobs executed for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum NodeState {
+    /// No job has been dispatched for the function yet
+    #[default]
+    Idle,
+    /// One or more jobs for the function are executing
+    Running,
+    /// The last job for the function completed successfully
+    Completed,
+    /// The last job for the function completed with an error
+    Failed,
+}
+
+impl NodeState {
+    fn color(self) -> Color {
+        match self {
+            NodeState::Idle => Color::from_rgb8(0xb0, 0xb0, 0xb0),
+            NodeState::Running => Color::from_rgb8(0xff, 0xa5, 0x00),
+            NodeState::Completed => Color::from_rgb8(0x3c, 0xb3, 0x71),
+            NodeState::Failed => Color::from_rgb8(0xdc, 0x14, 0x3c),
+        }
+    }
+}
+
+/// A function in the graph, and its position in the layout
+#[derive(Debug, Clone)]
+pub(crate) struct Node {
+    name: String,
+    column: usize,
+    row: usize,
+    jobs_running: usize,
+    state: NodeState,
+}
+
+/// A graph of the functions of a flow (nodes) and the connections between them (edges), with the
+/// execution state of each function
+#[derive(Debug, Clone, Default)]
+pub struct FlowGraph {
+    nodes: BTreeMap<usize, Node>,
+    edges: BTreeSet<(usize, usize)>,
+    jobs_dispatched: usize,
+    jobs_completed: usize,
+    jobs_failed: usize,
+}
+
+impl From<&FlowManifest> for FlowGraph {
+    fn from(manifest: &FlowManifest) -> Self {
+        let mut nodes = BTreeMap::new();
+        let mut edges = BTreeSet::new();
+
+        for function in manifest.functions() {
+            nodes.insert(function.id(), Node {
+                name: function.name().to_string(),
+                column: 0,
+                row: 0,
+                jobs_running: 0,
+                state: NodeState::Idle,
+            });
+            for connection in function.get_output_connections() {
+                edges.insert((function.id(), connection.destination_id));
+            }
+        }
+
+        let mut graph = FlowGraph { nodes, edges, ..FlowGraph::default() };
+        graph.layout();
+        graph
+    }
+}
+
+impl FlowGraph {
+    // Assign each node a column, by the length of the shortest path to it from a function with
+    // no connections into it, and a row within that column, ordered by function id
+    fn layout(&mut self) {
+        let mut columns: BTreeMap<usize, usize> = BTreeMap::new();
+        let has_input: BTreeSet<usize> = self.edges.iter()
+            .filter(|(source, destination)| source != destination)
+            .map(|(_, destination)| *destination)
+            .collect();
+
+        // Start from the functions with no inputs, then from any not reached (in a cycle) by id
+        let sources: Vec<usize> = self.nodes.keys().copied()
+            .filter(|id| !has_input.contains(id))
+            .chain(self.nodes.keys().copied())
+            .collect();
+
+        for source in sources {
+            if columns.contains_key(&source) {
+                continue;
+            }
+            columns.insert(source, 0);
+            let mut queue = VecDeque::from([source]);
+            while let Some(id) = queue.pop_front() {
+                let column = columns.get(&id).copied().unwrap_or_default();
+                for (_, destination) in self.edges.range((id, 0)..=(id, usize::MAX)) {
+                    if !columns.contains_key(destination) {
+                        columns.insert(*destination, column + 1);
+                        queue.push_back(*destination);
+                    }
+                }
+            }
+        }
+
+        let mut rows: BTreeMap<usize, usize> = BTreeMap::new();
+        for (id, node) in &mut self.nodes {
+            node.column = columns.get(id).copied().unwrap_or_default();
+            let row = rows.entry(node.column).or_default();
+            node.row = *row;
+            *row += 1;
+        }
+    }
+
+    /// A job for the function with `function_id` has been dispatched for execution
+    pub(crate) fn job_dispatched(&mut self, function_id: usize) {
+        self.jobs_dispatched += 1;
+        if let Some(node) = self.nodes.get_mut(&function_id) {
+            node.jobs_running += 1;
+            node.state = NodeState::Running;
+        }
+    }
+
+    /// A job for the function with `function_id` has completed, successfully or not
+    pub(crate) fn job_completed(&mut self, function_id: usize, success: bool) {
+        self.jobs_completed += 1;
+        if !success {
+            self.jobs_failed += 1;
+        }
+        if let Some(node) = self.nodes.get_mut(&function_id) {
+            node.jobs_running = node.jobs_running.saturating_sub(1);
+            node.state = match (node.jobs_running, success) {
+                (0, true) => NodeState::Completed,
+                (0, false) => NodeState::Failed,
+                _ => NodeState::Running,
+            };
+        }
+    }
+
+    /// Return the number of functions in the graph
+    pub(crate) fn function_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return a summary of the jobs executed so far
+    pub(crate) fn summary(&self) -> String {
+        format!("{} functions, {} jobs dispatched, {} completed, {} failed",
+                self.nodes.len(), self.jobs_dispatched, self.jobs_completed, self.jobs_failed)
+    }
+
+    // The top left corner of the box for `node` in the layout
+    fn position(node: &Node) -> Point {
+        #[allow(clippy::cast_precision_loss)]
+        Point::new(MARGIN + node.column as f32 * (NODE_WIDTH + COLUMN_SPACING),
+                   MARGIN + node.row as f32 * (NODE_HEIGHT + ROW_SPACING))
+    }
+
+    // The size of the whole layout
+    fn size(&self) -> Size {
+        let (width, height) = self.nodes.values()
+            .map(Self::position)
+            .fold((0.0f32, 0.0f32), |(width, height), position|
+                (width.max(position.x + NODE_WIDTH), height.max(position.y + NODE_HEIGHT)));
+        Size::new(width + MARGIN, height + MARGIN)
+    }
+}
+
+impl Program<Message> for FlowGraph {
+    type State = ();
+
+    fn draw(&self, _state: &(), renderer: &Renderer, theme: &Theme, bounds: Rectangle,
+            _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let text_color = theme.palette().text;
+
+        // shrink the graph to fit in the space available, but never enlarge it
+        let size = self.size();
+        frame.scale((bounds.width / size.width).min(bounds.height / size.height).min(1.0));
+
+        let edge_stroke = Stroke::default().with_width(1.5).with_color(text_color);
+        for (source, destination) in &self.edges {
+            if let (Some(from), Some(to)) = (self.nodes.get(source), self.nodes.get(destination)) {
+                let start = Self::position(from) + Vector::new(NODE_WIDTH, NODE_HEIGHT / 2.0);
+                let end = Self::position(to) + Vector::new(0.0, NODE_HEIGHT / 2.0);
+                frame.stroke(&Path::line(start, end), edge_stroke.clone());
+                // mark the destination end of the connection
+                frame.fill(&Path::circle(end, 3.0), text_color);
+            }
+        }
+
+        for node in self.nodes.values() {
+            let position = Self::position(node);
+            let node_box = Path::rectangle(position, Size::new(NODE_WIDTH, NODE_HEIGHT));
+            frame.fill(&node_box, node.state.color());
+            frame.stroke(&node_box, Stroke::default().with_width(1.0).with_color(text_color));
+            frame.fill_text(Text {
+                content: node.name.clone(),
+                position: position + Vector::new(NODE_WIDTH / 2.0, NODE_HEIGHT / 2.0),
+                color: Color::BLACK,
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Center,
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::model::flow_manifest::FlowManifest;
+    use flowcore::model::metadata::MetaData;
+    use flowcore::model::output_connection::{OutputConnection, Source};
+    use flowcore::model::runtime_function::RuntimeFunction;
+
+    use super::{FlowGraph, NodeState};
+
+    fn function(id: usize, destinations: &[usize]) -> RuntimeFunction {
+        let connections: Vec<OutputConnection> = destinations.iter()
+            .map(|destination| OutputConnection::new(Source::default(), *destination, 0, 0,
+                                                     String::new(), String::new()))
+            .collect();
+        RuntimeFunction::new(format!("f{id}"), format!("/f{id}"), "file://fake/test",
+                             vec![], id, 0, &connections, false)
+    }
+
+    // f0 -> f1 -> f2 -> f1 (a loop), f0 -> f3, f4 connected to itself only
+    fn graph() -> FlowGraph {
+        let mut manifest = FlowManifest::new(MetaData::default());
+        manifest.add_function(function(0, &[1, 3]));
+        manifest.add_function(function(1, &[2]));
+        manifest.add_function(function(2, &[1]));
+        manifest.add_function(function(3, &[]));
+        manifest.add_function(function(4, &[4]));
+        FlowGraph::from(&manifest)
+    }
+
+    fn position(graph: &FlowGraph, id: usize) -> (usize, usize) {
+        let node = graph.nodes.get(&id).expect("No such node");
+        (node.column, node.row)
+    }
+
+    #[test]
+    fn layout() {
+        let graph = graph();
+        assert_eq!(graph.function_count(), 5);
+        assert_eq!(position(&graph, 0), (0, 0));
+        assert_eq!(position(&graph, 1), (1, 0));
+        assert_eq!(position(&graph, 2), (2, 0));
+        assert_eq!(position(&graph, 3), (1, 1));
+        assert_eq!(position(&graph, 4), (0, 1));
+    }
+
+    #[test]
+    fn node_states() {
+        let mut graph = graph();
+        assert_eq!(graph.nodes.get(&1).map(|node| node.state), Some(NodeState::Idle));
+
+        graph.job_dispatched(1);
+        graph.job_dispatched(1);
+        graph.job_completed(1, false);
+        assert_eq!(graph.nodes.get(&1).map(|node| node.state), Some(NodeState::Running));
+        graph.job_completed(1, true);
+        assert_eq!(graph.nodes.get(&1).map(|node| node.state), Some(NodeState::Completed));
+
+        graph.job_dispatched(2);
+        graph.job_completed(2, false);
+        assert_eq!(graph.nodes.get(&2).map(|node| node.state), Some(NodeState::Failed));
+
+        assert_eq!(graph.summary(), "5 functions, 3 jobs dispatched, 3 completed, 2 failed");
+    }
+}
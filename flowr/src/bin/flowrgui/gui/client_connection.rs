@@ -1,27 +1,94 @@
 use std::fmt::Display;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use flowcore::errors::{Result, ResultExt};
+use flowcore::errors::{bail, Result, ResultExt};
 /// This is the message-queue implementation of the Client<-->[Coordinator][flowrlib::coordinator::Coordinator]
 /// communications
-use log::{info, trace};
+use log::{info, trace, warn};
 use simpdiscoverylib::BeaconListener;
-use zmq::Socket;
 
-use crate::gui::coordinator_connection::WAIT;
+use flowrlib::discovery::ServiceBrowser;
+use flowrlib::session::ZmqRequester;
 
-/// Try to discover a particular service by name
+// The time to wait for a beacon announcing a service, before checking if it has been
+// discovered using mDNS instead, or between checks if beacons cannot be listened for
+const DISCOVERY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Try to discover a particular service by name, using the beacons sent for it or its mDNS
+/// announcement, whichever is found first
 pub fn discover_service(discovery_port: u16, name: &str) -> Result<String> {
-    let listener = BeaconListener::new(name.as_bytes(), discovery_port)?;
-    let beacon = listener.wait(None)?;
-    let address = format!("{}:{}", beacon.service_ip, beacon.service_port);
-    Ok(address)
+    discover(discovery_port, name, None, None)
+}
+
+/// Try to discover a particular service by name, announced from `host` if one is given, waiting
+/// at most `timeout` for it to be found
+pub fn discover_service_on_host(discovery_port: u16, name: &str, host: Option<&str>,
+                                timeout: Duration) -> Result<String> {
+    discover(discovery_port, name, host, Some(Instant::now() + timeout))
+}
+
+// Discover the service called `name`, announced from `host` if one is given, using the beacons
+// sent for it or its mDNS announcement, giving up at `deadline` if one is given. The beacons are
+// only listened for on IPv4, so if that is not possible (e.g. on an IPv6-only host) the service
+// is discovered using only mDNS
+fn discover(discovery_port: u16, name: &str, host: Option<&str>, deadline: Option<Instant>)
+    -> Result<String> {
+    let browser = ServiceBrowser::new(discovery_port, name)
+        .map_err(|e| warn!("Could not discover services using mDNS: {e}"))
+        .ok();
+    let listener = match BeaconListener::new(name.as_bytes(), discovery_port) {
+        Ok(listener) => Some(listener),
+        Err(e) if browser.is_some() => {
+            warn!("Could not listen for discovery beacons on port {discovery_port}, \
+                discovering '{name}' using only mDNS: {e}");
+            None
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let from_host = |service_ip: String| host.is_none() || host == Some(service_ip.as_str());
+
+    loop {
+        let wait = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    bail!("No '{name}' service was discovered from '{}' on port {discovery_port}",
+                        host.unwrap_or_default());
+                }
+                remaining.min(DISCOVERY_POLL_INTERVAL)
+            }
+            None => DISCOVERY_POLL_INTERVAL,
+        };
+
+        match &listener {
+            Some(listener) => match listener.wait(Some(wait)) {
+                Ok(beacon) if from_host(beacon.service_ip.to_string()) =>
+                    return Ok(format!("{}:{}", beacon.service_ip, beacon.service_port)),
+                Ok(_) => {},
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {},
+                Err(e) => return Err(e.into()),
+            },
+            None => thread::sleep(wait),
+        }
+
+        while let Some(address) = browser.as_ref().and_then(ServiceBrowser::discovered) {
+            if address.parse::<SocketAddr>().is_ok_and(|socket| from_host(socket.ip().to_string())) {
+                return Ok(address);
+            }
+        }
+    }
 }
 
 /// `ClientConnection` stores information related to the connection from a client
 /// to the [Coordinator][flowrlib::coordinator::Coordinator] and is used each time a message is to
-/// be sent or received.
+/// be sent or received. If the connection is interrupted the client reconnects and resumes its
+/// session, so no messages are lost in either direction.
 pub struct ClientConnection {
-    requester: Socket,
+    requester: Mutex<ZmqRequester>,
 }
 
 impl ClientConnection {
@@ -29,19 +96,11 @@ impl ClientConnection {
     pub fn new(coordinator_address: &str) -> Result<Self> {
         info!("Client will attempt to connect to coordinator at: '{coordinator_address}'");
 
-        let context = zmq::Context::new();
-
-        let requester = context
-            .socket(zmq::REQ)
-            .chain_err(|| "Client could not connect to coordinator service")?;
-
-        requester
-            .connect(&format!("tcp://{coordinator_address}"))
-            .chain_err(|| format!("Client Connection - Could not connect to socket at: {coordinator_address}"))?;
+        let requester = ZmqRequester::connect(coordinator_address, None)?;
 
         info!("Client connected to coordinator at '{coordinator_address}'");
 
-        Ok(ClientConnection { requester })
+        Ok(ClientConnection { requester: Mutex::new(requester) })
     }
 
     /// Receive a [`CoordinatorMessage`][crate::gui::coordinator_message::CoordinatorMessage] from the
@@ -51,13 +110,11 @@ impl ClientConnection {
         CM: From<String> + Display {
         trace!("Client waiting for message from coordinator");
 
-        let msg = self
-            .requester
-            .recv_msg(WAIT)
-            .map_err(|e| format!("Error receiving from coordinator: {e}"))?;
+        let message_string = self.requester.lock()
+            .map_err(|_| "Could not lock connection")?
+            .receive()
+            .chain_err(|| "Error receiving from coordinator")?;
 
-        let message_string = msg.as_str()
-            .ok_or("Could not get Message as String")?.to_owned();
         trace!("Client Received <--- {}", message_string);
         Ok(message_string.into())
     }
@@ -68,8 +125,9 @@ impl ClientConnection {
     where
         CM: Into<String> + Display {
         trace!("Client Sending     ---> {}", message);
-        self.requester
-            .send(&message.into(), WAIT)
+        self.requester.lock()
+            .map_err(|_| "Could not lock connection")?
+            .send(message.into())
             .chain_err(|| "Error sending to coordinator")
     }
 }
@@ -1,10 +1,16 @@
+use std::collections::BTreeMap;
 use std::fmt;
 
 use flowcore::errors::Result;
+use flowcore::model::bytes::Bytes;
 use flowcore::model::submission::Submission;
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::gui::coordinator_message::FileMetaData;
 
 /// A Message from the a client to the Coordinator
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ClientMessage {
     /// ** These messages are used to implement the `SubmissionProtocol` between the Coordinator
@@ -13,6 +19,9 @@ pub enum ClientMessage {
     ClientSubmission(Submission),
     /// Client requests that server enters the ddebugger at the next opportunity
     EnterDebugger,
+    /// Client requests that execution of the flow it submitted be cancelled. It may be sent in
+    /// response to any message from the Coordinator while a flow is executing
+    CancelFlow,
 
     /// ** These messages are used to implement the context functions between the `cli_runtime_client`
     /// and the `cli_runtime_server` that runs as part of the `Coordinator`
@@ -24,6 +33,16 @@ pub enum ClientMessage {
     Line(String),
     /// A Vector of Strings that are the flow's arguments from Client, sent to the Server
     Args(Vec<String>),
+    /// The value of an environment variable, or None if it is not set, sent to the Server
+    EnvVar(Option<String>),
+    /// The value stored for a key in the key-value store, or None if there is none
+    KvValue(Option<Value>),
+    /// The names and values of the environment variables the flow is allowed to read
+    EnvVars(BTreeMap<String, String>),
+    /// The exit code (if it exited normally), stdout and stderr of a command that was run
+    ProcessOutput(Option<i32>, String, String),
+    /// The response received from a TCP server
+    TcpResponse(Vec<u8>),
     /// An Error occurred in the `runtime_client`
     Error(String),
     /// EOF was detected on input reading using Stdin
@@ -33,7 +52,13 @@ pub enum ClientMessage {
     /// Invalid - used when deserialization goes wrong
     Invalid,
     /// Contents read from a file
-    FileContents(String, Vec<u8>),
+    FileContents(String, Bytes),
+    /// The width, height and (r, g, b) bytes of the pixels, row by row, of an image read
+    Image(u32, u32, Vec<u8>),
+    /// The paths of the entries of a directory, sorted
+    DirectoryEntries(Vec<String>),
+    /// The metadata of a file or directory
+    Metadata(FileMetaData),
 
     /// ** This message is just internal to the client and not sent to the Coordinator
     /// Client is exiting Event loop
@@ -50,14 +75,23 @@ impl fmt::Display for ClientMessage {
                 ClientMessage::Stdin(_) => "Stdin",
                 ClientMessage::Line(_) => "Line",
                 ClientMessage::Args(_) => "Args",
+                ClientMessage::EnvVar(_) => "EnvVar",
+                ClientMessage::KvValue(_) => "KvValue",
+                ClientMessage::EnvVars(_) => "EnvVars",
+                ClientMessage::ProcessOutput(_, _, _) => "ProcessOutput",
+                ClientMessage::TcpResponse(_) => "TcpResponse",
                 ClientMessage::Error(_) => "Error",
                 ClientMessage::GetStdinEof => "GetStdinEof",
                 ClientMessage::GetLineEof => "GetLineEof",
                 ClientMessage::ClientExiting(_) => "ClientExiting",
                 ClientMessage::ClientSubmission(_) => "ClientSubmission",
                 ClientMessage::EnterDebugger => "EnterDebugger",
+                ClientMessage::CancelFlow => "CancelFlow",
                 ClientMessage::Invalid => "Invalid",
                 ClientMessage::FileContents(_, _) => "FileContents",
+                ClientMessage::Image(_, _, _) => "Image",
+                ClientMessage::DirectoryEntries(_) => "DirectoryEntries",
+                ClientMessage::Metadata(_) => "Metadata",
             }
         )
     }
@@ -1,13 +1,15 @@
 use std::fmt::Display;
 use std::time::Duration;
 
-use flowcore::errors::{Result, ResultExt, bail};
+use flowcore::errors::{Result, bail};
 
 /// This is the message-queue implementation of the Client<-->[Coordinator][flowrlib::coordinator::Coordinator]
 /// communications
-use log::{debug, info, trace};
+use log::{info, trace, warn};
 use simpdiscoverylib::BeaconSender;
-use zmq::Socket;
+
+use flowrlib::discovery::announce_service;
+use flowrlib::session::ZmqResponder;
 
 /// WAIT for a message to arrive when performing a `receive()`
 pub const WAIT:i32 = 0;
@@ -21,8 +23,13 @@ pub const COORDINATOR_SERVICE_NAME: &str = "runtime._flowr._tcp.local";
 /// Use this to discover the debug service by name
 pub const DEBUG_SERVICE_NAME: &str = "debug._flowr._tcp.local";
 
-/// Start a background thread that sends out beacons for service discovery by a client every second
+/// Start a background thread that sends out beacons for service discovery by a client every
+/// second, and announce the service using mDNS so it can also be discovered by standard tools
 pub fn enable_service_discovery(discovery_port: u16, name: &str, service_port: u16) -> Result<()> {
+    if let Err(e) = announce_service(discovery_port, name, service_port, None) {
+        warn!("Could not announce service '{name}' using mDNS: {e}");
+    }
+
     match BeaconSender::new(service_port, name.as_bytes(), discovery_port) {
         Ok(beacon) => {
             info!(
@@ -43,7 +50,7 @@ pub fn enable_service_discovery(discovery_port: u16, name: &str, service_port: u
 /// side of the client/coordinator communications between a client and a [Coordinator][flowrlib::coordinator::Coordinator]
 /// and is used each time a message needs to be sent or received.
 pub struct CoordinatorConnection {
-    responder: Socket,
+    responder: ZmqResponder,
 }
 
 /// Implement a [`CoordinatorConnection`] for sending and receiving messages between client and
@@ -52,15 +59,7 @@ impl CoordinatorConnection {
     /// Create a new [Coordinator][flowrlib::coordinator::Coordinator]
     /// side of the client/coordinator Connection
     pub fn new(service_name: &'static str, port: u16) -> Result<Self> {
-        let context = zmq::Context::new();
-        let responder = context
-            .socket(zmq::REP)
-            .chain_err(|| "Coordinator Connection - could not create Socket")?;
-
-        debug!("Coordinator Connection attempting to bind to: tcp://*:{port}");
-        responder.bind(&format!("tcp://*:{port}"))
-            .chain_err(||
-                format!("Coordinator Connection - could not bind on TCP Socket on: tcp://{port}"))?;
+        let responder = ZmqResponder::bind(port, None, None)?;
 
         info!("Service '{}' listening on *:{}", service_name, port);
 
@@ -75,13 +74,7 @@ impl CoordinatorConnection {
         CM: From<String> + Display {
         trace!("Coordinator waiting for message from client");
 
-        let msg = self
-            .responder
-            .recv_msg(flags)
-            .map_err(|e| format!("Coordinator error getting message: '{e}'"))?;
-
-        let message_string = msg.as_str().ok_or("Could not get message as str")?
-            .to_string();
+        let message_string = self.responder.receive(flags)?;
         let message = message_string.into();
         trace!("                ---> Coordinator Received {}", message);
         Ok(message)
@@ -103,8 +96,6 @@ impl CoordinatorConnection {
     where
         SM: Into<String> + Display {
         trace!("                <--- Coordinator Sending {}", message);
-        self.responder
-            .send(&message.into(), 0)
-            .chain_err(|| "Coordinator error sending to client".to_string())
+        self.responder.send(message.into())
     }
 }
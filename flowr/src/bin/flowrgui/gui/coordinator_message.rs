@@ -1,9 +1,13 @@
 use std::fmt;
+use std::fs;
+use std::time::UNIX_EPOCH;
 
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 
 use flowcore::errors::Result;
-use flowcore::model::metrics::Metrics;
+use flowcore::model::bytes::Bytes;
+use flowcore::model::metrics::{Metrics, MetricsSnapshot};
 
 use crate::gui::client_message::ClientMessage;
 
@@ -12,8 +16,8 @@ use crate::gui::client_message::ClientMessage;
 pub enum CoordinatorMessage {
     #[serde(skip_deserializing, skip_serializing)]
     /// ** These messages are used to communicate to the app the connection status to the Coordinator
-    /// A connection has been made
-    Connected(tokio::sync::mpsc::Sender<ClientMessage>),
+    /// A connection has been made, to a Coordinator discovered on the port given
+    Connected(tokio::sync::mpsc::Sender<ClientMessage>, u16),
     /// Connection with the Coordinator has been lost
     Disconnected(String),
     /// ** These messages are used to implement the `SubmissionProtocol` between the coordinator
@@ -22,14 +26,25 @@ pub enum CoordinatorMessage {
     FlowStart,
     /// A flow has stopped executing
     FlowEnd(Metrics),
+    /// Execution of a flow was cancelled at the request of the client, with the metrics of the
+    /// execution up to that point
+    FlowCancelled(Metrics),
     /// Coordinator is exiting, with a result (OK, or Err)
     CoordinatorExiting(Result<()>),
+    /// A job for the function with this id has been dispatched for execution
+    JobDispatched(usize),
+    /// A job for the function with this id has completed, successfully (true) or not (false)
+    JobCompleted(usize, bool),
+    /// A periodic snapshot of the metrics of the flow being executed
+    MetricsSnapshot(MetricsSnapshot),
 
     /// ** These messages are used to implement the context functions between the `cli_runtime_server`
     /// that runs as part of the `Coordinator` and the `cli_runtime_client` that interacts with
     /// STDIO
     /// A String of contents was sent to stdout
     Stdout(String),
+    /// A line of styled text, made up of spans of text with different styles, was sent to stdout
+    StdoutStyled(Vec<StyledText>),
     /// A String of contents was sent to stderr
     Stderr(String),
     /// A Request to read from Stdin
@@ -38,12 +53,44 @@ pub enum CoordinatorMessage {
     GetLine(String),
     /// A Request to get the arguments for the flow
     GetArgs,
+    /// A Request to get the value of the named environment variable
+    GetEnvVar(String),
+    /// A Request to get all the environment variables the flow is allowed to read
+    GetEnvVars,
+    /// A Request to get the value stored for a key in the key-value store
+    KvGet(String),
+    /// A Request to store a value for a key in the key-value store
+    KvPut(String, Value),
+    /// A Request to delete the value stored for a key in the key-value store
+    KvDelete(String),
+    /// A Request to run a command, with arguments, writing a String to its stdin
+    Exec(String, Vec<String>, String),
+    /// A Request to send bytes to a TCP server at an address, optionally waiting for a response
+    TcpSend(String, Vec<u8>, bool),
     /// A Request to read bytes from a file
     Read(String),
     /// A Request to write a series of bytes to a file
-    Write(String, Vec<u8>),
+    Write(String, Bytes),
+    /// A Request to append a series of bytes to the end of a file
+    Append(String, Bytes),
+    /// A Request to remove a file
+    Remove(String),
+    /// A Request to list the entries of a directory
+    List(String),
+    /// A Request to get the metadata of a file or directory
+    Stat(String),
     /// A Request to write a pixel to an `ImageBuffer`
     PixelWrite((u32, u32), (u8, u8, u8), (u32, u32), String),
+    /// A Request to draw a line from one point to another in a color, on a drawing of a size
+    DrawLine((u32, u32), (u32, u32), (u8, u8, u8), (u32, u32), String),
+    /// A Request to draw a rectangle at a point of a size in a color, filled or not, on a drawing of a size
+    DrawRectangle((u32, u32), (u32, u32), (u8, u8, u8), bool, (u32, u32), String),
+    /// A Request to draw a circle at a center point of a radius in a color, filled or not, on a drawing of a size
+    DrawCircle((u32, u32), u32, (u8, u8, u8), bool, (u32, u32), String),
+    /// A Request to draw text at a point in a color, on a drawing of a size
+    DrawText((u32, u32), String, (u8, u8, u8), (u32, u32), String),
+    /// A Request to read and decode an image file
+    ImageRead(String),
     /// A Request to snd EOF to Stdout
     StdoutEof,
     /// A Request to snd EOF to Stderr
@@ -58,19 +105,40 @@ impl fmt::Display for CoordinatorMessage {
             f,
             "{}",
             match self {
-                CoordinatorMessage::Connected(_) => "Connected",
+                CoordinatorMessage::Connected(_, _) => "Connected",
                 CoordinatorMessage::Disconnected(_) => "Disconnected",
                 CoordinatorMessage::FlowEnd(_) => "FlowEnd",
+                CoordinatorMessage::FlowCancelled(_) => "FlowCancelled",
                 CoordinatorMessage::FlowStart => "FlowStart",
                 CoordinatorMessage::CoordinatorExiting(_) => "CoordinatorExiting",
+                CoordinatorMessage::JobDispatched(_) => "JobDispatched",
+                CoordinatorMessage::JobCompleted(_, _) => "JobCompleted",
+                CoordinatorMessage::MetricsSnapshot(_) => "MetricsSnapshot",
                 CoordinatorMessage::Stdout(_) => "Stdout",
+                CoordinatorMessage::StdoutStyled(_) => "StdoutStyled",
                 CoordinatorMessage::Stderr(_) => "Stderr",
                 CoordinatorMessage::GetStdin => "GetStdIn",
                 CoordinatorMessage::GetLine(_) => "GetLine",
                 CoordinatorMessage::GetArgs => "GetArgs",
+                CoordinatorMessage::GetEnvVar(_) => "GetEnvVar",
+                CoordinatorMessage::GetEnvVars => "GetEnvVars",
+                CoordinatorMessage::KvGet(_) => "KvGet",
+                CoordinatorMessage::KvPut(_, _) => "KvPut",
+                CoordinatorMessage::KvDelete(_) => "KvDelete",
+                CoordinatorMessage::Exec(_, _, _) => "Exec",
+                CoordinatorMessage::TcpSend(_, _, _) => "TcpSend",
                 CoordinatorMessage::Read(_) => "Read",
                 CoordinatorMessage::Write(_, _) => "Write",
+                CoordinatorMessage::Append(_, _) => "Append",
+                CoordinatorMessage::Remove(_) => "Remove",
+                CoordinatorMessage::List(_) => "List",
+                CoordinatorMessage::Stat(_) => "Stat",
                 CoordinatorMessage::PixelWrite(_, _, _, _) => "PixelWrite",
+                CoordinatorMessage::DrawLine(_, _, _, _, _) => "DrawLine",
+                CoordinatorMessage::DrawRectangle(_, _, _, _, _, _) => "DrawRectangle",
+                CoordinatorMessage::DrawCircle(_, _, _, _, _, _) => "DrawCircle",
+                CoordinatorMessage::DrawText(_, _, _, _, _) => "DrawText",
+                CoordinatorMessage::ImageRead(_) => "ImageRead",
                 CoordinatorMessage::StdoutEof => "StdOutEof",
                 CoordinatorMessage::StderrEof => "StdErrEof",
                 CoordinatorMessage::Invalid => "Invalid",
@@ -87,6 +155,63 @@ pub struct FileMetaData {
     pub is_file: bool,
     /// Was the Path inspected a directory or not
     pub is_dir: bool,
+    /// The size in bytes of the file
+    pub size: u64,
+    /// When the file was last modified, in seconds since the UNIX epoch, if available
+    pub modified: Option<u64>,
+}
+
+impl From<&fs::Metadata> for FileMetaData {
+    fn from(metadata: &fs::Metadata) -> Self {
+        FileMetaData {
+            is_file: metadata.is_file(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            modified: metadata.modified().ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| since_epoch.as_secs()),
+        }
+    }
+}
+
+/// A color that styled text can be displayed in, or with as a background
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    /// Black
+    Black,
+    /// Red
+    Red,
+    /// Green
+    Green,
+    /// Yellow
+    Yellow,
+    /// Blue
+    Blue,
+    /// Magenta
+    Magenta,
+    /// Cyan
+    Cyan,
+    /// White
+    White,
+}
+
+/// A span of text and the style it should be displayed with, fields not specified are not styled
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+#[serde(default)]
+pub struct StyledText {
+    /// The text to display
+    pub text: String,
+    /// The color to display the text in
+    pub color: Option<Color>,
+    /// The color to display behind the text
+    pub background: Option<Color>,
+    /// Display the text in bold
+    pub bold: bool,
+    /// Display the text in italics
+    pub italic: bool,
+    /// Display the text underlined
+    pub underline: bool,
 }
 
 impl From<CoordinatorMessage> for String {
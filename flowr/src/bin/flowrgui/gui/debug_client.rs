@@ -1,407 +1,268 @@
-use std::sync::{Arc, Mutex};
+use std::fmt::Write;
 
-use flowcore::errors::Result;
 use flowcore::model::runtime_function::RuntimeFunction;
 use flowrlib::debug_command::BreakpointSpec;
 use flowrlib::debug_command::DebugCommand;
 use flowrlib::debug_command::DebugCommand::{
-    Ack, Breakpoint, Continue, DebugClientStarting, Delete, ExitDebugger, FunctionList, Inspect,
-    InspectBlock, InspectFunction, InspectInput, InspectOutput, List, Modify, RunReset, Step,
-    Validate,
+    Inspect, InspectBlock, InspectFunction, InspectInput, InspectOutput,
 };
 use flowrlib::run_state::{RunState, State};
-use log::error;
-use rustyline::error::ReadlineError;
-use rustyline::history::DefaultHistory;
-use rustyline::{DefaultEditor, Editor};
 
-use crate::gui::client_connection::ClientConnection;
 use crate::gui::debug_message::DebugServerMessage;
 use DebugServerMessage::{
     BlockBreakpoint, BlockState, DataBreakpoint, Deadlock, EnteringDebugger, ExecutionEnded,
     ExecutionStarted, ExitingDebugger, FlowUnblockBreakpoint, FunctionStates, Functions,
     InputState, JobCompleted, JobError, Message, OutputState, OverallState, Panic,
-    PriorToSendingJob, Resetting, SendingValue, WaitingForCommand,
+    PriorToSendingJob, Resetting, SendingValue, WaitingForCommand, WatchedValue,
 };
 
-const FLOWR_HISTORY_FILENAME: &str = ".flowr_history";
-
-const HELP_STRING: &str = "Debugger commands:
-'b' | 'breakpoint' {spec}     - Set a breakpoint using spec:
-                                 - on a function by function_id (integer)
-                                 - on an output by source_id/output_route ('source_id/' for default output)
-                                 - on an input by destination_id:input_number
-                                 - on block creation by blocked_process_id->blocking_process_id
-'c' | 'continue'              - Continue execution after a breakpoint
-'d' | 'delete' {spec} or '*'  - Delete the breakpoint matching {spec} or all with '*'
-'e' | 'exit'                  - Stop flow execution and exit debugger
-'f' | 'functions'             - Show the list of functions
-'h' | 'help' | '?'            - Display this help message
-'i' | 'inspect' [n]           - Inspect the overall state, or the function number 'n'
-'l' | 'list'                  - List all breakpoints
-'m' | 'modify' [name]=[value] - Modify a debugger or runtime variable named 'name' to value 'value'
-'q' | 'quit'                  - Stop flow execution and exit debugger
-'r' | 'reset' or 'run' {args} - If running already then reset the state, or run the flow with {args}
-'s' | 'step' [n]              - Step over the next 'n' jobs (default = 1) then break
-'v' | 'validate'              - Validate the state of the flow by running a series of checks
-";
-
 /*
-    A debug client that implements the DebugClient trait defined in the flowrlib library.
+    Functions used by the Debugger tab of flowrgui to interpret messages received from the
+    debug server, and to create the commands to send back to it.
 */
-pub struct DebugClient {
-    connection: ClientConnection,
-    override_args: Arc<Mutex<Vec<String>>>,
-    editor: Editor<(), DefaultHistory>,
-    last_command: String,
-}
-
-impl DebugClient {
-    /// Create a new debug client accepting the debug connection
-    pub fn new(connection: ClientConnection, override_args: Arc<Mutex<Vec<String>>>) -> Self {
-        DebugClient {
-            connection,
-            override_args,
-            editor: DefaultEditor::new().expect("Could not create Editor"),
-            last_command: String::new(),
-        }
-    }
-
-    /// Main debug client loop where events are received, processed and responses sent
-    pub fn debug_client_loop(mut self) {
-        // Ignore error on first start-up due to no previous command history existing
-        let _ = self.editor.load_history(FLOWR_HISTORY_FILENAME);
-
-        // Send an first message to initialize the connection
-        let _ = self.connection.send(DebugClientStarting);
 
-        // loop while? and avoid break?
-        loop {
-            match self.connection.receive() {
-                Ok(debug_server_message) => {
-                    if let Ok(response) = self.process_server_message(debug_server_message) {
-                        let _ = self.connection.send(response);
-                    }
-                }
-                Err(err) => {
-                    error!("Error receiving event from debugger: {}", err);
-                    break;
-                }
-            }
-        }
+/// Parse a breakpoint `spec` entered by the user:
+///  - on a function by `function_id` (integer)
+///  - on an output by `source_id/output_route` (`source_id/` for default output)
+///  - on an input by `destination_id:input_number`
+///  - on block creation by `blocked_process_id->blocking_process_id`
+///  - all, using `*`
+pub fn parse_breakpoint_spec(spec: &str) -> Option<BreakpointSpec> {
+    let spec = spec.trim();
 
-        let _ = self.editor.save_history(FLOWR_HISTORY_FILENAME);
+    if spec == "*" {
+        return Some(BreakpointSpec::All);
     }
 
-    fn help() {
-        println!("{HELP_STRING}");
+    if let Ok(integer) = spec.parse::<usize>() {
+        return Some(BreakpointSpec::Numeric(integer));
     }
 
-    fn parse_command(&self, mut input: String) -> Result<(String, String, Option<Vec<String>>)> {
-        input = input.trim().to_string();
-        if input.is_empty() && !self.last_command.is_empty() {
-            input.clone_from(&self.last_command);
-            println!("Repeating last valid command: '{input}'");
+    if spec.contains('/') {
+        // is an output specified
+        let (source, route) = spec.split_once('/')?;
+        if let Ok(source_process_id) = source.parse::<usize>() {
+            return Some(BreakpointSpec::Output((source_process_id, format!("/{route}"))));
         }
-
-        let parts: Vec<String> = input.split(' ').map(ToString::to_string).collect();
-        let command = parts.first().ok_or("Could not get first part")?.to_string();
-
-        if !parts.is_empty() {
-            return Ok((
-                input,
-                command,
-                Some(parts.get(1..).ok_or("Could not get parts")?.to_vec()),
-            ));
-        };
-
-        Ok((input, command, None))
-    }
-
-    fn parse_optional_int(params: Option<Vec<String>>) -> Option<usize> {
-        if let Some(param) = params {
-            if !param.is_empty() {
-                if let Ok(integer) = param.get(1)?.parse::<usize>() {
-                    return Some(integer);
-                }
-            }
+    } else if spec.contains(':') {
+        // is an input specifier
+        let (destination, input) = spec.split_once(':')?;
+        if let (Ok(destination_function_id), Ok(destination_input_number)) =
+            (destination.parse::<usize>(), input.parse::<usize>()) {
+            return Some(BreakpointSpec::Input((destination_function_id, destination_input_number)));
         }
-
-        None
+    } else if spec.contains("->") {
+        // is a block specifier
+        let (source, destination) = spec.split_once("->")?;
+        return Some(BreakpointSpec::Block((source.parse::<usize>().ok(),
+                                           destination.parse::<usize>().ok())));
     }
 
-    fn parse_breakpoint_spec(specs: Option<Vec<String>>) -> Option<BreakpointSpec> {
-        if let Some(spec) = specs {
-            if !spec.is_empty() {
-                if spec.first()? == "*" {
-                    return Some(BreakpointSpec::All);
-                }
-
-                if let Ok(integer) = spec.first()?.parse::<usize>() {
-                    return Some(BreakpointSpec::Numeric(integer));
-                }
-
-                if spec.first()?.contains('/') {
-                    // is an output specified
-                    let sub_parts: Vec<&str> = spec.first()?.split('/').collect();
-                    if let Ok(source_process_id) = sub_parts.first()?.parse::<usize>() {
-                        return Some(BreakpointSpec::Output((
-                            source_process_id,
-                            format!("/{}", sub_parts.get(1)?),
-                        )));
-                    }
-                } else if spec.first()?.contains(':') {
-                    // is an input specifier
-                    let sub_parts: Vec<&str> = spec.first()?.split(':').collect();
-                    if let (Ok(destination_function_id), Ok(destination_input_number)) = (
-                        sub_parts.first()?.parse::<usize>(),
-                        sub_parts.get(1)?.parse::<usize>(),
-                    ) {
-                        return Some(BreakpointSpec::Input((
-                            destination_function_id,
-                            destination_input_number,
-                        )));
-                    }
-                } else if spec.first()?.contains("->") {
-                    // is a block specifier
-                    let sub_parts: Vec<&str> = spec.first()?.split("->").collect();
-                    let source = sub_parts.first()?.parse::<usize>().ok();
-                    let destination = sub_parts.get(1)?.parse::<usize>().ok();
-                    return Some(BreakpointSpec::Block((source, destination)));
-                }
-            }
-        }
+    None
+}
 
-        None
+/// Create the command to inspect what is described by `spec`, or the overall state if it
+/// is empty. Returns `None` if `spec` cannot be inspected
+pub fn parse_inspect_spec(spec: &str) -> Option<DebugCommand> {
+    if spec.trim().is_empty() {
+        return Some(Inspect);
     }
 
-    fn parse_inspect_spec(spec: Option<Vec<String>>) -> Option<DebugCommand> {
-        match Self::parse_breakpoint_spec(spec) {
-            None => Some(Inspect),
-            Some(BreakpointSpec::Numeric(function_id)) => Some(InspectFunction(function_id)),
-            Some(BreakpointSpec::Input((function_id, input_number))) => {
-                Some(InspectInput(function_id, input_number))
-            }
-            Some(BreakpointSpec::Output((function_id, sub_route))) => {
-                Some(InspectOutput(function_id, sub_route))
-            }
-            Some(BreakpointSpec::Block((source_function_id, destination_function_id))) => {
-                Some(InspectBlock(source_function_id, destination_function_id))
-            }
-            _ => {
-                println!(
-                    "Unsupported format for 'inspect' command. Use 'h' or 'help' command for help"
-                );
-                None
-            }
+    match parse_breakpoint_spec(spec)? {
+        BreakpointSpec::Numeric(function_id) => Some(InspectFunction(function_id)),
+        BreakpointSpec::Input((function_id, input_number)) => {
+            Some(InspectInput(function_id, input_number))
         }
-    }
-
-    /*
-       Wait for the user to input a valid debugger command then return the corresponding response
-       that should be sent to the debug server
-    */
-    fn get_user_command(&mut self, job_number: usize) -> Result<DebugCommand> {
-        loop {
-            match self.editor.readline(&format!("Job #{job_number}> ")) {
-                Ok(line) => match self.parse_command(line) {
-                    Ok((line, command, params)) => {
-                        if let Some(debugger_command) = self.get_server_command(&command, params) {
-                            self.editor
-                                .add_history_entry(&line)
-                                .map_err(|_| "Could not add history line")?;
-                            self.last_command = line;
-                            return Ok(debugger_command);
-                        }
-                        self.last_command = String::new();
-                    }
-                    Err(e) => eprintln!("{e}"),
-                },
-                Err(ReadlineError::Interrupted) => {
-                    println!("Use 'q' or 'quit' to exit the debugger");
-                }
-                Err(_) => return Ok(ExitDebugger), // Includes CONTROL-D exits
-            }
+        BreakpointSpec::Output((function_id, sub_route)) => {
+            Some(InspectOutput(function_id, sub_route))
         }
+        BreakpointSpec::Block((source_function_id, destination_function_id)) => {
+            Some(InspectBlock(source_function_id, destination_function_id))
+        }
+        BreakpointSpec::All => None,
     }
+}
 
-    /*
-       Determine what response should be sent to the server for the input command, or none.
-       Some commands act locally at the client (such as "help") and don't generate any response
-       to send to the server.
-       Likewise command parsing errors shouldn't generate a response to send to the server.
-    */
-    fn get_server_command(
-        &mut self,
-        command: &str,
-        params: Option<Vec<String>>,
-    ) -> Option<DebugCommand> {
-        match command {
-            "b" | "breakpoint" => Some(Breakpoint(Self::parse_breakpoint_spec(params))),
-            "c" | "continue" => Some(Continue),
-            "d" | "delete" => Some(Delete(Self::parse_breakpoint_spec(params))),
-            "e" | "exit" | "q" | "quit" => Some(ExitDebugger),
-            "f" | "functions" => Some(FunctionList),
-            "h" | "?" | "help" => {
-                // only command that doesn't send a message to debugger
-                Self::help();
-                self.editor
-                    .add_history_entry(command)
-                    .expect("Could not add history line");
-                None
-            }
-            "i" | "inspect" => Self::parse_inspect_spec(params),
-            "l" | "list" => Some(List),
-            "m" | "modify" => Some(Modify(params)),
-            "r" | "run" | "reset" => {
-                if let Some(mut overrides) = params {
-                    if let Ok(mut args) = self.override_args.lock() {
-                        args.clear();
-                        args.append(&mut overrides);
-                    }
-                }
-                Some(RunReset)
-            }
-            "s" | "step" => Some(Step(Self::parse_optional_int(params))),
-            "v" | "validate" => Some(Validate),
-            _ => {
-                println!("Unknown debugger command '{command}'\n");
-                None
-            }
-        }
+/// If the debug server is waiting for the user to enter a command in response to `message`
+/// return the number of the job it is waiting at
+pub fn waiting_for_command(message: &DebugServerMessage) -> Option<usize> {
+    match message {
+        WaitingForCommand(job_id) => Some(*job_id),
+        Panic(_, jobs_created) => Some(*jobs_created),
+        JobError(job, _) => Some(job.payload.job_id),
+        _ => None,
     }
+}
 
-    /*
-        This processes a message received from the debug server.
-        A message may be generated by the debug server without any a request from the debug client
-        Some messages expect the client to respond with a command for the debug server.
-    */
-    fn process_server_message(&mut self, message: DebugServerMessage) -> Result<DebugCommand> {
-        match message {
-            JobCompleted(job) => {
-                println!("Job #{} completed by Function #{}", job.payload.job_id, job.function_id);
-                if let Ok((Some(output), _)) = job.result {
-                    println!("\tOutput value: '{}'", &output);
-                }
+/// Describe a message received from the debug server as lines of text to show to the user
+pub fn describe(message: &DebugServerMessage) -> Vec<String> {
+    match message {
+        JobCompleted(job) => {
+            let mut lines = vec![format!("Job #{} completed by Function #{}",
+                                         job.payload.job_id, job.function_id)];
+            if let Ok((Some(output), _)) = &job.result {
+                lines.push(format!("\tOutput value: '{output}'"));
             }
-            PriorToSendingJob(job) => {
-                println!("About to send Job #{} to Function #{}", job.payload.job_id, job.function_id);
-                println!("\tInputs: {:?}", job.payload.input_set);
-            }
-            BlockBreakpoint(block) => println!("Block breakpoint: {block:?}"),
-            DataBreakpoint(
-                source_function_name,
-                source_function_id,
-                output_route,
-                value,
-                destination_id,
-                destination_name,
-                io_name,
-                input_number,
-            ) => println!(
+            lines
+        }
+        PriorToSendingJob(job) => vec![
+            format!("About to send Job #{} to Function #{}", job.payload.job_id, job.function_id),
+            format!("\tInputs: {:?}", job.payload.input_set),
+        ],
+        BlockBreakpoint(block) => vec![format!("Block breakpoint: {block:?}")],
+        DataBreakpoint(
+            source_function_name,
+            source_function_id,
+            output_route,
+            value,
+            destination_id,
+            destination_name,
+            io_name,
+            input_number,
+            source_location,
+        ) => {
+            let mut lines = vec![format!(
                 "Data breakpoint: Function #{source_function_id} '{source_function_name}{output_route}' \
                 --{value}-> Function #{destination_id}:{input_number} '{destination_name}'/'{io_name}'",
-            ),
-            Panic(message, jobs_created) => {
-                println!("Function panicked after {jobs_created} jobs created: {message}");
-                return self.get_user_command(jobs_created);
-            }
-            JobError(job) => {
-                println!("Error occurred executing a Job: \n'{job}'");
-                return self.get_user_command(job.payload.job_id);
+            )];
+            if let Some(location) = source_location {
+                lines.push(format!("\tConnection defined at: {location}"));
             }
-            Deadlock(message) => println!("Deadlock detected {message}"),
-            EnteringDebugger => println!(
-                "Server is Entering Debugger. Use 'h' or 'help' for help on commands at the prompt"
-            ),
-            ExitingDebugger => println!("Debugger is exiting"),
-            ExecutionStarted => println!("Running flow"),
-            ExecutionEnded => println!("Flow has completed"),
-            Functions(functions) => Self::function_list(functions),
-            SendingValue(source_process_id, value, destination_id, input_number) => println!(
-                "Function #{source_process_id} sending '{value}' to {destination_id}:{input_number}",
-            ),
-            DebugServerMessage::Error(error_message) => println!("{error_message}"),
-            Message(message) => println!("{message}"),
-            Resetting => println!("Resetting state"),
-            WaitingForCommand(job_id) => return self.get_user_command(job_id),
-            DebugServerMessage::Invalid => println!("Invalid message received from debug server"),
-            FunctionStates((function, state)) => {
-                print!("{function}");
-                println!("\tState: {state:?}");
-            }
-            OverallState(run_state) => Self::display_state(&run_state),
-            InputState(input) => println!("{input}"),
-            OutputState(output_connections) => {
-                if output_connections.is_empty() {
-                    println!("No output connections from that sub-route");
-                } else {
-                    for connection in output_connections {
-                        println!("{connection}");
-                    }
-                }
+            lines
+        }
+        WatchedValue(job_id, function_id, function_name, output_route, value) => vec![format!(
+            "Watch: Job #{job_id} Function #{function_id} '{function_name}{output_route}' = {value}",
+        )],
+        Panic(message, jobs_created) => {
+            vec![format!("Function panicked after {jobs_created} jobs created: {message}")]
+        }
+        JobError(job, source_location) => {
+            let mut lines = if job.out_of_fuel() {
+                vec![format!("Job #{} ran out of fuel executing Function #{}: '{job}'",
+                             job.payload.job_id, job.function_id)]
+            } else {
+                vec![format!("Error occurred executing a Job: '{job}'")]
+            };
+            if let Some(location) = source_location {
+                lines.push(format!("\tFunction #{} referenced at: {location}", job.function_id));
             }
-            BlockState(blocks) => {
-                if blocks.is_empty() {
-                    println!("No blocks between functions matching the specification were found");
-                }
-                for block in blocks {
-                    println!("{block}");
-                }
+            lines
+        }
+        Deadlock(message) => vec![format!("Deadlock detected {message}")],
+        EnteringDebugger => vec!["Server is Entering Debugger".into()],
+        ExitingDebugger => vec!["Debugger is exiting".into()],
+        ExecutionStarted => vec!["Running flow".into()],
+        ExecutionEnded => vec!["Flow has completed".into()],
+        Functions(functions) => function_list(functions),
+        SendingValue(source_process_id, value, destination_id, input_number) => vec![format!(
+            "Function #{source_process_id} sending '{value}' to {destination_id}:{input_number}",
+        )],
+        DebugServerMessage::Error(error_message) => vec![error_message.clone()],
+        Message(message) => message.lines().map(ToString::to_string).collect(),
+        Resetting => vec!["Resetting state".into()],
+        WaitingForCommand(job_id) => vec![format!("Waiting for a command at Job #{job_id}")],
+        DebugServerMessage::Invalid => vec!["Invalid message received from debug server".into()],
+        FunctionStates((function, state)) => {
+            let mut lines: Vec<String> = function.to_string().lines()
+                .map(ToString::to_string).collect();
+            lines.push(format!("\tState: {state:?}"));
+            lines
+        }
+        OverallState(run_state) => describe_state(run_state),
+        InputState(input) => vec![input.to_string()],
+        OutputState(output_connections) => {
+            if output_connections.is_empty() {
+                vec!["No output connections from that sub-route".into()]
+            } else {
+                output_connections.iter().map(ToString::to_string).collect()
             }
-            FlowUnblockBreakpoint(flow_id) => {
-                println!("Flow #{flow_id} was busy and has now gone idle, unblocking senders to functions");
+        }
+        BlockState(blocks) => {
+            if blocks.is_empty() {
+                vec!["No blocks between functions matching the specification were found".into()]
+            } else {
+                blocks.iter().map(ToString::to_string).collect()
             }
         }
-
-        Ok(Ack)
+        FlowUnblockBreakpoint(flow_id) => vec![format!(
+            "Flow #{flow_id} was busy and has now gone idle, unblocking senders to functions")],
     }
+}
 
-    fn function_list(functions: Vec<RuntimeFunction>) {
-        println!("Functions List");
-        for function in functions {
-            println!(
-                "\t#{} '{}' @ '{}'",
-                function.id(),
-                function.name(),
-                function.route()
-            );
+fn function_list(functions: &[RuntimeFunction]) -> Vec<String> {
+    let mut lines = vec!["Functions List".to_string()];
+    for function in functions {
+        let mut line = format!("\t#{} '{}' @ '{}'", function.id(), function.name(),
+                               function.route());
+        if let Some(location) = function.source_location() {
+            let _ = write!(line, " ({location})");
         }
-        println!("Use 'i n' or 'inspect n' to inspect the function number 'n'");
+        lines.push(line);
     }
+    lines.push("Use 'Inspect' with a function number to inspect that function".into());
+    lines
+}
 
-    /*
-       Display information to the user about the current RunState
-    */
-    fn display_state(run_state: &RunState) {
-        println!("{run_state}\n");
+/*
+   Describe the current RunState, including the states of all functions and any blocks
+*/
+fn describe_state(run_state: &RunState) -> Vec<String> {
+    let mut lines: Vec<String> = run_state.to_string().lines().map(ToString::to_string).collect();
 
-        for id in 0..run_state.num_functions() {
-            if let Some(function) = run_state.get_function(id) {
-                print!("{function}", );
-                let function_states = run_state.get_function_states(id);
-                println!("\tStates: {function_states:?}");
+    for id in 0..run_state.num_functions() {
+        if let Some(function) = run_state.get_function(id) {
+            lines.extend(function.to_string().lines().map(ToString::to_string));
+            let function_states = run_state.get_function_states(id);
+            lines.push(format!("\tStates: {function_states:?}"));
 
-                if function_states.contains(&State::Blocked) {
-                    for block in run_state.get_blocks() {
-                        if block.blocked_function_id == id {
-                            println!("\t\t{block:?}");
-                        }
+            if function_states.contains(&State::Blocked) {
+                for block in run_state.get_blocks() {
+                    if block.blocked_function_id == id {
+                        lines.push(format!("\t\t{block:?}"));
                     }
                 }
+            }
 
-                // print any blocked or blocking function information
-                for block in run_state.get_blocks() {
-                    if block.blocking_function_id == id {
-                        println!(
-                            "\tBlocking #{}:{} <- Blocked #{}",
-                            block.blocking_function_id,
-                            block.blocking_io_number,
-                            block.blocked_function_id
-                        );
-                    }
+            // add any blocked or blocking function information
+            for block in run_state.get_blocks() {
+                if block.blocking_function_id == id {
+                    lines.push(format!(
+                        "\tBlocking #{}:{} <- Blocked #{}",
+                        block.blocking_function_id,
+                        block.blocking_io_number,
+                        block.blocked_function_id
+                    ));
                 }
             }
         }
     }
+
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use flowrlib::debug_command::BreakpointSpec;
+    use flowrlib::debug_command::DebugCommand::{Inspect, InspectFunction, InspectInput};
+
+    use super::{parse_breakpoint_spec, parse_inspect_spec};
+
+    #[test]
+    fn parse_specs() {
+        assert_eq!(parse_breakpoint_spec("*"), Some(BreakpointSpec::All));
+        assert_eq!(parse_breakpoint_spec("3"), Some(BreakpointSpec::Numeric(3)));
+        assert_eq!(parse_breakpoint_spec("1/sum"),
+                   Some(BreakpointSpec::Output((1, "/sum".into()))));
+        assert_eq!(parse_breakpoint_spec("2:0"), Some(BreakpointSpec::Input((2, 0))));
+        assert_eq!(parse_breakpoint_spec("1->2"), Some(BreakpointSpec::Block((Some(1), Some(2)))));
+        assert_eq!(parse_breakpoint_spec("foo"), None);
+    }
+
+    #[test]
+    fn parse_inspect() {
+        assert_eq!(parse_inspect_spec(""), Some(Inspect));
+        assert_eq!(parse_inspect_spec("4"), Some(InspectFunction(4)));
+        assert_eq!(parse_inspect_spec("4:1"), Some(InspectInput(4, 1)));
+        assert_eq!(parse_inspect_spec("*"), None);
+    }
 }
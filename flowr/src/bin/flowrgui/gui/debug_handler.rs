@@ -1,6 +1,7 @@
 use flowcore::model::input::Input;
 use flowcore::model::output_connection::OutputConnection;
 use flowcore::model::runtime_function::RuntimeFunction;
+use flowcore::model::source_location::SourceLocation;
 use flowrlib::block::Block;
 use flowrlib::debug_command::DebugCommand;
 use flowrlib::debugger_handler::DebuggerHandler;
@@ -11,7 +12,7 @@ use serde_json::Value;
 use crate::{BlockBreakpoint, CoordinatorConnection, DataBreakpoint, ExecutionEnded, ExecutionStarted,
             ExitingDebugger, JobCompleted, JobError, Panic, PriorToSendingJob, Resetting,
             WaitingForCommand};
-use crate::DebugServerMessage::{BlockState, Error, FlowUnblockBreakpoint, Functions, FunctionStates, InputState, Message, OutputState, OverallState};
+use crate::DebugServerMessage::{BlockState, Error, FlowUnblockBreakpoint, Functions, FunctionStates, InputState, Message, OutputState, OverallState, WatchedValue};
 use crate::gui::coordinator_connection::WAIT;
 
 /// A debug handler for interacting between the CLI client and the Debugger in the Coordinator
@@ -55,7 +56,8 @@ impl DebuggerHandler for CliDebugHandler {
     // A breakpoint on sending a value from a specific function or to a specific function was hit
     fn send_breakpoint(&mut self, source_function_name: &str, source_function_id: usize,
                        output_route: &str, value: &Value, destination_id: usize,
-                       destination_name: &str, io_name: &str, input_number: usize) {
+                       destination_name: &str, io_name: &str, input_number: usize,
+                       source_location: Option<&SourceLocation>) {
         let _: flowcore::errors::Result<DebugCommand> = self
             .debug_server_connection
             .send_and_receive_response(DataBreakpoint(
@@ -67,14 +69,15 @@ impl DebuggerHandler for CliDebugHandler {
                 destination_name.to_string(),
                 io_name.to_string(),
                 input_number,
+                source_location.cloned(),
             ));
     }
 
     // A job error occurred during execution of the flow
-    fn job_error(&mut self, job: &Job) {
+    fn job_error(&mut self, job: &Job, source_location: Option<&SourceLocation>) {
         let _: flowcore::errors::Result<DebugCommand> = self
             .debug_server_connection
-            .send_and_receive_response(JobError(job.clone()));
+            .send_and_receive_response(JobError(job.clone(), source_location.cloned()));
     }
 
     // A specific job completed
@@ -84,6 +87,19 @@ impl DebuggerHandler for CliDebugHandler {
                 .send_and_receive_response(JobCompleted(job.clone()));
     }
 
+    // A job completed that output a value on a watched output route
+    fn watch_value(&mut self, job: &Job, function_name: &str, output_route: &str, value: &Value) {
+        let _: flowcore::errors::Result<DebugCommand> = self
+            .debug_server_connection
+            .send_and_receive_response(WatchedValue(
+                job.payload.job_id,
+                job.function_id,
+                function_name.to_string(),
+                output_route.to_string(),
+                value.clone(),
+            ));
+    }
+
     // returns a set of blocks
     fn blocks(&mut self, blocks: Vec<Block>) {
         let _: flowcore::errors::Result<DebugCommand> =
@@ -1,6 +1,5 @@
 pub(crate) mod test_helper;
 pub mod debug_message;
-#[allow(dead_code)]
 pub mod debug_client;
 pub mod debug_handler;
 pub mod submission_handler;
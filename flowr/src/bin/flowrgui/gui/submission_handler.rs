@@ -2,8 +2,9 @@ use std::sync::{Arc, Mutex};
 
 use error_chain::bail;
 use flowcore::errors::Result;
-use flowcore::model::metrics::Metrics;
+use flowcore::model::metrics::{Metrics, MetricsSnapshot};
 use flowcore::model::submission::Submission;
+use flowrlib::job::Job;
 use flowrlib::run_state::RunState;
 use flowrlib::submission_handler::SubmissionHandler;
 use log::{debug, error, info, trace};
@@ -16,6 +17,8 @@ use crate::gui::coordinator_message::CoordinatorMessage;
 /// A [`SubmissionHandler`] to allow submitting flows for execution from the CLI
 pub(crate) struct CLISubmissionHandler {
     coordinator_connection: Arc<Mutex<CoordinatorConnection>>,
+    // Has the client responded to a message with a request to cancel execution of the flow
+    cancel_requested: bool,
 }
 
 impl CLISubmissionHandler {
@@ -23,8 +26,23 @@ impl CLISubmissionHandler {
     pub fn new(connection: Arc<Mutex<CoordinatorConnection>>) -> Self {
         CLISubmissionHandler {
             coordinator_connection: connection,
+            cancel_requested: false,
         }
     }
+
+    // Send `message` to the client and wait for its response, noting if the client responded
+    // with a request to cancel execution of the flow
+    fn send_and_check_response(&mut self, message: CoordinatorMessage) -> Result<()> {
+        let response = self.coordinator_connection
+            .lock()
+            .map_err(|_| "Could not lock coordinator connection")?
+            .send_and_receive_response::<CoordinatorMessage, ClientMessage>(message)?;
+        if matches!(response, ClientMessage::CancelFlow) {
+            debug!("Got CancelFlow message");
+            self.cancel_requested = true;
+        }
+        Ok(())
+    }
 }
 
 impl SubmissionHandler for CLISubmissionHandler {
@@ -58,15 +76,39 @@ impl SubmissionHandler for CLISubmissionHandler {
         }
     }
 
+    // The client sends a request to cancel execution in response to the messages sent to it when
+    // jobs are dispatched and completed, so they are checked for in `send_and_check_response()`
+    fn should_cancel(&mut self) -> Result<bool> {
+        Ok(std::mem::take(&mut self.cancel_requested))
+    }
+
     fn flow_execution_ended(&mut self, state: &RunState, metrics: Metrics) -> Result<()> {
+        let message = if state.cancelled() {
+            CoordinatorMessage::FlowCancelled(metrics)
+        } else {
+            CoordinatorMessage::FlowEnd(metrics)
+        };
         self.coordinator_connection
             .lock()
             .map_err(|_| "Could not lock coordinator connection")?
-            .send(CoordinatorMessage::FlowEnd(metrics))?;
+            .send(message)?;
         debug!("{}", state);
         Ok(())
     }
 
+    fn job_dispatched(&mut self, job: &Job) -> Result<()> {
+        self.send_and_check_response(CoordinatorMessage::JobDispatched(job.function_id))
+    }
+
+    fn job_completed(&mut self, job: &Job) -> Result<()> {
+        self.send_and_check_response(
+            CoordinatorMessage::JobCompleted(job.function_id, job.result.is_ok()))
+    }
+
+    fn metrics_snapshot(&mut self, snapshot: MetricsSnapshot) -> Result<()> {
+        self.send_and_check_response(CoordinatorMessage::MetricsSnapshot(snapshot))
+    }
+
     // Loop waiting for one of the following two messages from the client thread:
     //  - `ClientSubmission` with a submission, then return Ok(Some(submission))
     //  - `ClientExiting` then return Ok(None)
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::sync::Mutex;
+
+use iced::{Subscription, subscription};
+use iced::futures::{future, SinkExt};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+// The most detailed level of log records captured for the Log tab, whatever the verbosity of
+// the output to the terminal
+const CAPTURE_LEVEL: Level = Level::Debug;
+
+/// The log levels that can be selected to filter the records shown in the Log tab
+pub const LEVELS: [LevelFilter; 4] = [LevelFilter::Error, LevelFilter::Warn, LevelFilter::Info,
+    LevelFilter::Debug];
+
+// Log records are only captured from the crates of flow, not the GUI toolkit and its dependencies
+const CAPTURED_TARGETS: [&str; 4] = ["flowrgui", "flowrlib", "flowcore", "flowstdlib"];
+
+// The receiver of captured log records, taken by the subscription that sends them to the App
+static RECEIVER: Lazy<Mutex<Option<UnboundedReceiver<LogRecord>>>> = Lazy::new(|| Mutex::new(None));
+
+/// A log record captured from the coordinator or client, shown in the Log tab
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// The level the record was logged at
+    pub level: Level,
+    /// The module that logged the record
+    pub target: String,
+    /// The message logged
+    pub message: String,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+// A logger that writes log records to the terminal, as filtered by the verbosity level, and
+// captures the records of flow's crates, up to [CAPTURE_LEVEL], for the Log tab
+struct CaptureLogger {
+    terminal: env_logger::Logger,
+    sender: UnboundedSender<LogRecord>,
+}
+
+impl CaptureLogger {
+    fn captures(metadata: &Metadata) -> bool {
+        metadata.level() <= CAPTURE_LEVEL
+            && CAPTURED_TARGETS.iter().any(|target| metadata.target().starts_with(target))
+    }
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        Self::captures(metadata) || self.terminal.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.terminal.matches(record) {
+            self.terminal.log(record);
+        }
+
+        if Self::captures(record.metadata()) {
+            let _ = self.sender.send(LogRecord {
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.terminal.flush();
+    }
+}
+
+/// Install a logger that writes to the terminal using `terminal` and also captures log
+/// records for the Log tab
+pub fn init(terminal: env_logger::Logger) {
+    let max_level = terminal.filter().max(CAPTURE_LEVEL.to_level_filter());
+    let (sender, receiver) = unbounded_channel();
+
+    if let Ok(mut guard) = RECEIVER.lock() {
+        *guard = Some(receiver);
+    }
+
+    match log::set_boxed_logger(Box::new(CaptureLogger { terminal, sender })) {
+        Ok(()) => log::set_max_level(max_level),
+        Err(e) => eprintln!("Could not set logger: {e}"),
+    }
+}
+
+// Creates an asynchronous worker that sends the log records captured to the App
+pub fn subscribe() -> Subscription<LogRecord> {
+    struct Capture;
+    subscription::channel(
+        std::any::TypeId::of::<Capture>(),
+        100,
+        |mut app_sender| async move {
+            let receiver = RECEIVER.lock().ok().and_then(|mut guard| guard.take());
+            if let Some(mut receiver) = receiver {
+                while let Some(record) = receiver.recv().await {
+                    let _ = app_sender.send(record).await;
+                }
+            }
+
+            // nothing more will be captured
+            future::pending().await
+        }
+    )
+}
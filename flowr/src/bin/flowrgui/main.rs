@@ -20,31 +20,41 @@
 //! [`Executors`][flowrlib::executor::Executor]
 
 use core::str::FromStr;
-use std::fs::File;
+use std::ffi::OsStr;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
 use std::io::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{env, process, thread};
 
 use clap::Command as ClapCommand;
-use clap::{Arg, ArgMatches};
+use clap::{Arg, ArgMatches, ValueHint};
+use clap_complete::{CompleteEnv, generate, Shell};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate, PathCompleter, ValueCompleter};
 use env_logger::Builder;
 use iced::alignment::Horizontal;
-use iced::executor;
+use iced::{event, executor, window, Event};
 use iced::widget::scrollable::Id;
-use iced::widget::{scrollable, text_input, Button, Column, Row, Text};
-use iced::{Alignment, Application, Command, Element, Length, Settings, Subscription, Theme};
+use iced::widget::{pick_list, scrollable, text_input, Button, Column, Row, Text};
+use iced::{Alignment, Application, Command, Element, Length, Settings, Size, Subscription, Theme};
 use iced_aw::{modal, Card};
-use image::{ImageBuffer, Rgba, RgbaImage};
-use log::{info, LevelFilter};
+use native_dialog::FileDialog;
+use image::{ImageBuffer, ImageFormat, Rgba, RgbaImage};
+use log::{error, info, LevelFilter};
 use simpath::Simpath;
 use url::Url;
 
+use flowcore::config::Config;
+use flowcore::examples::find_examples;
 use flowcore::meta_provider::MetaProvider;
 use flowcore::model::flow_manifest::FlowManifest;
 use flowcore::model::submission::Submission;
 use flowcore::provider::Provider;
 use flowcore::url_helper::url_from_string;
 use flowrlib::info as flowrlib_info;
+use flowrlib::kv_store::KvStore;
+use flowrlib::tcp;
 use gui::coordinator_connection::CoordinatorConnection;
 use gui::debug_message::DebugServerMessage;
 use gui::debug_message::DebugServerMessage::{
@@ -53,8 +63,15 @@ use gui::debug_message::DebugServerMessage::{
 };
 
 use crate::gui::client_message::ClientMessage;
-use crate::gui::coordinator_message::CoordinatorMessage;
-use crate::tabs::TabSet;
+use crate::gui::coordinator_message::{CoordinatorMessage, FileMetaData};
+use crate::drawing::{Drawing, Shape};
+use crate::graph::FlowGraph;
+use crate::connect_dialog::ConnectDialog;
+use crate::saved_settings::SavedSettings;
+use crate::settings_dialog::SettingsDialog;
+use crate::debug_manager::DebugEvent;
+use crate::log_capture::LogRecord;
+use crate::tabs::{DebugAction, ImageZoom, Line, StdinRequest, TabSet};
 
 /// Include the module that implements the context functions
 mod context;
@@ -68,27 +85,62 @@ mod gui;
 /// module that runs a coordinator in background
 mod connection_manager;
 
+/// module with the dialog to connect to a remote coordinator
+mod connect_dialog;
+
+/// module with the dialog to edit the settings used to start a coordinator
+mod settings_dialog;
+
+/// module with the settings saved when flowrgui exits and restored when it next starts
+mod saved_settings;
+
+/// module that connects the Debugger tab to the debugger of the coordinator
+mod debug_manager;
+
 /// module with the different UI tabs
 mod tabs;
 
+/// module with the graph of a flow's functions, showing their execution state
+mod graph;
+
+/// module with the drawings flows make using lines, rectangles, circles and text
+mod drawing;
+
+/// module that captures log records to show in the Log tab
+mod log_capture;
+
+/// module with the charts of the metrics of a flow, drawn while it is running
+mod metrics_chart;
+
 /// provides [Error][errors::Error] that other modules in this crate will `use crate::errors::*;`
 /// to get access to everything `error_chain` creates.
 mod errors;
 
 /// [Message] enum captures all the types of messages that are sent to and processed by the
 /// `flowrgui` Iced Application
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum Message {
     /// We lost contact with the coordinator
     CoordinatorDisconnected(String),
     /// The Coordinator sent to the client/App a Coordinator Message
     CoordinatorSent(CoordinatorMessage),
+    /// The reply to a message from the Coordinator that was prepared in the background is ready
+    /// to be sent to it
+    Reply(ClientMessage),
     /// The UI has requested to submit the flow to the Coordinator for execution
     SubmitFlow, // TODO put SubmissionSettings into this variant?
-    /// ddd
-    Submitted,
+    /// The flow has been submitted to the Coordinator, with the graph of its functions if its
+    /// manifest could be loaded
+    Submitted(Option<FlowGraph>),
+    /// The UI has requested to stop the flow being executed by the Coordinator
+    StopFlow,
     /// The Url of the flow to run has been edited by the UI
     UrlChanged(String),
+    /// The UI has requested to pick the flow manifest to run using a file dialog
+    BrowseForFlow,
+    /// A flow manifest was picked in the file dialog, or none if it was cancelled
+    FlowPicked(Option<String>),
     /// The arguments to send to the flow when executed have been edited by the UI
     FlowArgsChanged(String),
     /// A different tab of stdio has been selected
@@ -97,33 +149,132 @@ pub enum Message {
     NewStdin(String),
     /// A new line entered for STDIN
     LineOfStdin(String),
+    /// The UI has requested to pick a file to load into STDIN
+    LoadStdinFile,
+    /// A file was picked to load into STDIN, or none if the file dialog was cancelled
+    StdinFilePicked(Option<PathBuf>),
+    /// The UI has signalled the end of STDIN
+    StdinEof,
+    /// A change to the scale images are shown at was selected in the Images tab
+    ImageZoomSelected(ImageZoom),
+    /// The UI has requested to save the image with this name as a PNG file
+    SaveImage(String),
+    /// A log record was captured, to show in the Log tab
+    LogRecorded(LogRecord),
+    /// The most detailed level of log records to show was selected in the Log tab
+    LogLevelSelected(LevelFilter),
+    /// The text to search for in log records has been edited in the Log tab
+    LogSearchChanged(String),
+    /// The path to save the image with this name to was picked, or none if the file dialog was
+    /// cancelled
+    ImageSavePathPicked(String, Option<PathBuf>),
     /// toggle to auto-scroll to bottom of STDIO has changed
     StdioAutoScrollTogglerChanged(Id, bool),
     /// closing of the Modal was requested
     CloseModal,
+    /// The debugger of the Coordinator sent an event to the Debugger tab
+    DebuggerSent(DebugEvent),
+    /// The breakpoint or inspect spec in the Debugger tab has been edited
+    DebugSpecChanged(String),
+    /// An action on the debugger was selected in the Debugger tab
+    DebugActionSelected(DebugAction),
+    /// The UI has requested to show the dialog to connect to a remote Coordinator
+    ShowConnectDialog,
+    /// The host of the remote Coordinator has been edited in the connect dialog
+    ConnectHostChanged(String),
+    /// The discovery port of the remote Coordinator has been edited in the connect dialog
+    ConnectPortChanged(String),
+    /// The UI has requested to connect to the remote Coordinator entered in the connect dialog
+    ConnectToCoordinator,
+    /// The UI has requested to connect again to the Coordinator, after the connection was lost
+    Reconnect,
+    /// The UI has requested to show the dialog to edit the settings of the Coordinator
+    ShowSettingsDialog,
+    /// The number of threads has been edited in the settings dialog
+    SettingsThreadsChanged(String),
+    /// The use of native flowstdlib has been toggled in the settings dialog
+    SettingsNativeToggled(bool),
+    /// The library search path has been edited in the settings dialog
+    SettingsLibDirsChanged(String),
+    /// The UI has requested to restart the Coordinator with the settings entered in the dialog
+    ApplySettings,
+    /// The window has been resized to the new width and height
+    WindowResized(u32, u32),
+    /// Closing of the window was requested
+    CloseRequested,
 }
 
 #[allow(clippy::ignored_unit_patterns)]
 enum CoordinatorState {
+    Connecting,
     Disconnected(String),
     Connected(tokio::sync::mpsc::Sender<ClientMessage>),
 }
 
 /// Main for flowrgui binary - call `run()` and print any error that results or exit silently if OK
 fn main() -> iced::Result {
+    // Print the completions if run by a shell to complete the command line, without starting the GUI
+    CompleteEnv::with_factory(FlowrGui::cli_command).complete();
+
+    // Print a completion script if requested, without starting the GUI
+    if let Some(("completions", completions_matches)) = FlowrGui::cli_command().get_matches().subcommand() {
+        if let Some(shell) = completions_matches.get_one::<Shell>("shell") {
+            print_completions(*shell);
+        }
+        return Ok(());
+    }
+
+    // settings saved when last run, that command line options override
+    let saved_settings = SavedSettings::path()
+        .map(|path| SavedSettings::read(&path))
+        .transpose()
+        .unwrap_or_else(|e| {
+            eprintln!("Could not load saved settings: {e}");
+            None
+        })
+        .flatten()
+        .unwrap_or_default();
+
+    let default_size = window::Settings::default().size;
+    let size = Size::new(saved_settings.window_width.unwrap_or(default_size.width),
+                         saved_settings.window_height.unwrap_or(default_size.height));
+
     FlowrGui::run(Settings {
         antialiasing: true,
-        ..Settings::default()
+        window: window::Settings {
+            size,
+            // settings are saved before the window is closed
+            exit_on_close_request: false,
+            ..window::Settings::default()
+        },
+        ..Settings::with_flags(saved_settings)
     })
 }
 
+/// Print a completion script for `shell` to stdout, where the flow manifest is completed with paths
+/// by the shell. `COMPLETE=<shell> flowrgui` prints one that completes it with the example flows
+/// installed too, by running `flowrgui` to find them without starting the GUI
+fn print_completions(shell: Shell) {
+    generate(shell, &mut FlowrGui::cli_command(), env!("CARGO_BIN_NAME"), &mut io::stdout());
+}
+
+/// Complete the flow manifest to run with the example flows that start with `current`, found each
+/// time it is completed, and with paths
+fn complete_flow_manifest(current: &OsStr) -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<CompletionCandidate> = find_examples(&current.to_string_lossy())
+        .into_iter()
+        .map(|example| CompletionCandidate::new(example).help(Some("example flow".into())))
+        .collect();
+    candidates.extend(PathCompleter::any().complete(current));
+    candidates
+}
+
 #[derive(Clone)]
 struct SubmissionSettings {
-    // TODO make native a UI setting
-    // TODO num threads make a UI setting
-    // TODO make lib search path a UI setting
     flow_manifest_url: String,
     flow_args: String,
+    allowed_env_vars: Vec<String>,
+    allow_exec: bool,
     debug_this_flow: bool,
     display_metrics: bool,
     parallel_jobs_limit: Option<usize>, // TODO read from settings or UI
@@ -136,8 +287,30 @@ pub struct ServerSettings {
     native_flowstdlib: bool,
     /// How many executor threads should be used
     num_threads: usize,
-    /// The path to search for libs when a lib reference is found
-    lib_search_path: Simpath,
+    /// Directories or base Urls to add to the path to search for libs when a lib reference
+    /// is found
+    lib_dirs: Vec<String>,
+}
+
+impl ServerSettings {
+    // For the lib provider, libraries maybe installed in multiple places in the file system.
+    // In order to find the content, a FLOW_LIB_PATH environment variable can be configured with a
+    // list of directories in which to look for the library in question.
+    fn lib_search_path(&self) -> Simpath {
+        let mut lib_search_path = Simpath::new_with_separator("FLOW_LIB_PATH", ',');
+
+        for additions in &self.lib_dirs {
+            lib_search_path.add(additions);
+            info!("'{}' added to the Library Search Path", additions);
+        }
+
+        if lib_search_path.is_empty() {
+            let home_dir = env::var("HOME").unwrap_or_else(|_| "Could not get $HOME".to_string());
+            lib_search_path.add(&format!("{home_dir}/.flow/lib"));
+        }
+
+        lib_search_path
+    }
 }
 
 /// [`CoordinatorSettings`] captures the parameters to be used when creating a new Coordinator
@@ -145,8 +318,9 @@ pub struct ServerSettings {
 pub enum CoordinatorSettings {
     /// Start a server coordinator using the settings supplied
     Server(ServerSettings),
-    /// Don't start a coordinator server, just discover existing one on this port
-    ClientOnly(u16),
+    /// Don't start a coordinator server, just discover an existing one on this port, on the
+    /// host with this IP address if one is given
+    ClientOnly(Option<String>, u16),
 }
 
 struct UiSettings {
@@ -164,11 +338,17 @@ struct FlowrGui {
     coordinator_settings: CoordinatorSettings,
     ui_settings: UiSettings,
     coordinator_state: CoordinatorState,
+    connection_attempt: usize,
+    discovery_port: Option<u16>,
     tab_set: TabSet,
     running: bool,
     submitted: bool,
+    stop_requested: bool,
     show_modal: bool,
     modal_content: (String, String),
+    connect_dialog: Option<ConnectDialog>,
+    settings_dialog: Option<SettingsDialog>,
+    saved_settings: SavedSettings,
 }
 
 // Implement the iced Application trait for FlowIde
@@ -176,22 +356,29 @@ impl Application for FlowrGui {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = SavedSettings;
 
-    /// Create the flowrgui app and populate fields with options passed on the command line
-    fn new(_flags: ()) -> (Self, Command<Message>) {
-        let settings = FlowrGui::initial_settings();
+    /// Create the flowrgui app and populate fields with options passed on the command line,
+    /// or the settings saved when it was last run
+    fn new(saved_settings: SavedSettings) -> (Self, Command<Message>) {
+        let settings = FlowrGui::initial_settings(&saved_settings);
 
         let flowrgui = FlowrGui {
             submission_settings: settings.0,
             coordinator_settings: settings.1,
             ui_settings: settings.2,
-            coordinator_state: CoordinatorState::Disconnected("Starting".into()),
+            coordinator_state: CoordinatorState::Connecting,
+            connection_attempt: 0,
+            discovery_port: None,
             tab_set: TabSet::new(),
             submitted: false,
             running: false,
+            stop_requested: false,
             show_modal: false,
             modal_content: (String::new(), String::new()),
+            connect_dialog: None,
+            settings_dialog: None,
+            saved_settings,
         };
 
         (flowrgui, Command::none())
@@ -201,40 +388,159 @@ impl Application for FlowrGui {
         String::from("flowrgui")
     }
 
+    #[allow(clippy::too_many_lines)]
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
-            Message::CoordinatorSent(CoordinatorMessage::Connected(sender)) => {
+            Message::CoordinatorSent(CoordinatorMessage::Connected(sender, discovery_port)) => {
                 self.coordinator_state = CoordinatorState::Connected(sender);
+                self.discovery_port = Some(discovery_port);
                 if self.ui_settings.auto {
                     return Command::perform(Self::auto_submit(), |()| Message::SubmitFlow);
                 }
             }
             Message::SubmitFlow => {
                 if let CoordinatorState::Connected(sender) = &self.coordinator_state {
+                    self.saved_settings.add_recent_flow(&self.submission_settings.flow_manifest_url);
                     return Command::perform(
                         Self::submit(sender.clone(), self.submission_settings.clone()),
-                        |()| Message::Submitted,
+                        Message::Submitted,
                     );
                 }
             }
-            Message::Submitted => {
+            Message::Submitted(graph) => {
                 self.tab_set.clear();
+                if let Some(graph) = graph {
+                    self.tab_set.graph_tab.graph = graph;
+                }
                 self.submitted = true;
             }
+            Message::StopFlow => self.stop_requested = true,
             Message::FlowArgsChanged(value) => self.submission_settings.flow_args = value,
             Message::UrlChanged(value) => self.submission_settings.flow_manifest_url = value,
-            Message::TabSelected(_) | Message::StdioAutoScrollTogglerChanged(_, _) => {
+            Message::BrowseForFlow => return Command::perform(Self::pick_flow(),
+                                                              Message::FlowPicked),
+            Message::FlowPicked(Some(path)) => self.submission_settings.flow_manifest_url = path,
+            Message::FlowPicked(None) => {}
+            Message::TabSelected(_) | Message::StdioAutoScrollTogglerChanged(_, _)
+            | Message::DebuggerSent(_) | Message::DebugSpecChanged(_)
+            | Message::DebugActionSelected(_) | Message::ImageZoomSelected(_)
+            | Message::LogRecorded(_) | Message::LogLevelSelected(_) | Message::LogSearchChanged(_) => {
                 return self.tab_set.update(message);
             }
+            Message::SaveImage(name) => {
+                return Command::perform(Self::pick_image_path(name.clone()),
+                                        move |path| Message::ImageSavePathPicked(name, path));
+            }
+            Message::ImageSavePathPicked(name, Some(path)) => {
+                if let Err(e) = self.save_image(&name, &path) {
+                    self.show_modal = true;
+                    self.modal_content = ("Could not save image".into(), e);
+                }
+            }
+            Message::ImageSavePathPicked(_, None) => {}
+            Message::CloseModal => {
+                self.show_modal = false;
+                self.connect_dialog = None;
+                self.settings_dialog = None;
+            }
+            Message::CoordinatorDisconnected(reason)
+            | Message::CoordinatorSent(CoordinatorMessage::Disconnected(reason)) => {
+                self.connection_changed(CoordinatorState::Disconnected(reason));
+            }
+            Message::ShowConnectDialog => {
+                self.connect_dialog = Some(ConnectDialog::new(&self.coordinator_settings));
+            }
+            Message::ConnectHostChanged(host) => {
+                if let Some(dialog) = &mut self.connect_dialog {
+                    dialog.host = host;
+                }
+            }
+            Message::ConnectPortChanged(port) => {
+                if let Some(dialog) = &mut self.connect_dialog {
+                    dialog.port = port;
+                }
+            }
+            Message::ConnectToCoordinator => {
+                if let Some(dialog) = &mut self.connect_dialog {
+                    match dialog.settings() {
+                        Ok(settings) => {
+                            self.coordinator_settings = settings;
+                            self.connect_dialog = None;
+                            self.reconnect();
+                        }
+                        Err(e) => dialog.error = Some(e),
+                    }
+                }
+            }
+            Message::Reconnect => self.reconnect(),
+            Message::ShowSettingsDialog => {
+                if let CoordinatorSettings::Server(settings) = &self.coordinator_settings {
+                    self.settings_dialog = Some(SettingsDialog::new(settings));
+                }
+            }
+            Message::SettingsThreadsChanged(threads) => {
+                if let Some(dialog) = &mut self.settings_dialog {
+                    dialog.threads = threads;
+                }
+            }
+            Message::SettingsNativeToggled(native) => {
+                if let Some(dialog) = &mut self.settings_dialog {
+                    dialog.native = native;
+                }
+            }
+            Message::SettingsLibDirsChanged(lib_dirs) => {
+                if let Some(dialog) = &mut self.settings_dialog {
+                    dialog.lib_dirs = lib_dirs;
+                }
+            }
+            Message::ApplySettings => {
+                if let Some(dialog) = &mut self.settings_dialog {
+                    match dialog.settings() {
+                        Ok(settings) => {
+                            self.coordinator_settings = CoordinatorSettings::Server(settings);
+                            self.settings_dialog = None;
+                            self.reconnect();
+                        }
+                        Err(e) => dialog.error = Some(e),
+                    }
+                }
+            }
+            #[allow(clippy::cast_precision_loss)]
+            Message::WindowResized(width, height) => {
+                self.saved_settings.window_width = Some(width as f32);
+                self.saved_settings.window_height = Some(height as f32);
+            }
+            Message::CloseRequested => {
+                self.save_settings();
+                return window::close(window::Id::MAIN);
+            }
+            Message::Reply(msg) => self.send(msg),
             Message::CoordinatorSent(coord_msg) => {
                 return self.process_coordinator_message(coord_msg);
             }
-            Message::CloseModal => self.show_modal = false,
-            Message::CoordinatorDisconnected(reason) => {
-                self.coordinator_state = CoordinatorState::Disconnected(reason);
-            }
             Message::NewStdin(text) => self.tab_set.stdin_tab.text_entered(text),
-            Message::LineOfStdin(line) => self.tab_set.stdin_tab.new_line(line),
+            Message::LineOfStdin(line) => {
+                self.tab_set.stdin_tab.new_line(line);
+                self.respond_to_stdin_request();
+            }
+            Message::LoadStdinFile => return Command::perform(Self::pick_stdin_file(),
+                                                              Message::StdinFilePicked),
+            Message::StdinFilePicked(Some(path)) => match fs::read_to_string(&path) {
+                Ok(contents) => {
+                    self.tab_set.stdin_tab.load(&contents);
+                    self.respond_to_stdin_request();
+                }
+                Err(e) => {
+                    self.show_modal = true;
+                    self.modal_content = ("Could not load file".into(),
+                                          format!("Could not read '{}': {e}", path.display()));
+                }
+            },
+            Message::StdinFilePicked(None) => {}
+            Message::StdinEof => {
+                self.tab_set.stdin_tab.eof = true;
+                self.respond_to_stdin_request();
+            }
         }
 
         Command::none()
@@ -248,7 +554,11 @@ impl Application for FlowrGui {
             .push(self.status_row())
             .padding(10);
 
-        let overlay = if self.show_modal {
+        let overlay = if let Some(dialog) = &self.connect_dialog {
+            Some(dialog.view())
+        } else if let Some(dialog) = &self.settings_dialog {
+            Some(dialog.view())
+        } else if self.show_modal {
             Some(
                 Card::new(
                     Text::new(self.modal_content.clone().0),
@@ -261,7 +571,8 @@ impl Application for FlowrGui {
                                 .on_press(Message::CloseModal),
                         ),
                     )
-                    .max_width(300.0),
+                    .max_width(300.0)
+                    .into(),
             )
         } else {
             None
@@ -274,8 +585,24 @@ impl Application for FlowrGui {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        connection_manager::subscribe(self.coordinator_settings.clone())
-            .map(Message::CoordinatorSent)
+        let coordinator = connection_manager::subscribe(self.coordinator_settings.clone(),
+                                                        self.connection_attempt)
+            .map(Message::CoordinatorSent);
+        let window_events = event::listen_with(Self::window_event);
+        let log_records = log_capture::subscribe().map(Message::LogRecorded);
+
+        // once the coordinator is found, connect to its debugger if debugging was requested
+        match self.discovery_port {
+            Some(discovery_port) if self.submission_settings.debug_this_flow => {
+                Subscription::batch([
+                    coordinator,
+                    window_events,
+                    log_records,
+                    debug_manager::subscribe(discovery_port).map(Message::DebuggerSent),
+                ])
+            }
+            _ => Subscription::batch([coordinator, window_events, log_records]),
+        }
     }
 }
 
@@ -285,11 +612,11 @@ impl FlowrGui {
         info!("Auto submitting flow");
     }
 
-    // Submit the flow to the coordinator for execution
+    // Submit the flow to the coordinator for execution, returning the graph of its functions
     async fn submit(
         sender: tokio::sync::mpsc::Sender<ClientMessage>,
         settings: SubmissionSettings,
-    ) {
+    ) -> Option<FlowGraph> {
         match Self::flow_url(&settings.flow_manifest_url) {
             Ok(url) => {
                 let provider =
@@ -297,10 +624,13 @@ impl FlowrGui {
 
                 match FlowManifest::load(provider, &url) {
                     Ok((flow_manifest, _)) => {
+                        let graph = FlowGraph::from(&flow_manifest);
                         let submission = Submission::new(
                             flow_manifest,
                             settings.parallel_jobs_limit,
                             None, // No timeout waiting for job results
+                            None, // Failed jobs are not retried
+                            None, // No checkpoints of execution state are written
                             settings.debug_this_flow,
                         );
 
@@ -313,23 +643,111 @@ impl FlowrGui {
                         {
                             Ok(()) => {
                                 // TODO report info that submitted
+                                Some(graph)
                             }
                             Err(_) => {
                                 // TODO report submit error
+                                None
                             }
                         }
                     }
                     Err(_e) => {
                         // TODO report manifest loading error
+                        None
                     }
                 }
             }
             Err(_e) => {
                 // TODO report Invalid Url error
+                None
             }
         }
     }
 
+    // Show a native file dialog on its own thread, as it blocks until it is closed, so that
+    // the UI keeps being updated, returning the path picked or None if it was cancelled
+    async fn show_file_dialog<F>(dialog: F) -> Option<PathBuf>
+        where F: FnOnce(FileDialog) -> native_dialog::Result<Option<PathBuf>> + Send + 'static {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        thread::spawn(move || {
+            let current_dir = env::current_dir().unwrap_or_default();
+            let _ = sender.send(dialog(FileDialog::new().set_location(&current_dir)));
+        });
+        receiver.await.ok()?.unwrap_or_else(|e| {
+            error!("Could not show file dialog: {e}");
+            None
+        })
+    }
+
+    // Run `command` in a background thread, so the UI is not blocked until it exits, returning
+    // the reply to send to the coordinator
+    async fn exec(command: String, args: Vec<String>, stdin: String) -> ClientMessage {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        thread::spawn(move || {
+            let _ = sender.send(flowrlib::process::exec(&command, &args, stdin));
+        });
+        match receiver.await {
+            Ok(Ok((code, stdout, stderr))) => ClientMessage::ProcessOutput(code, stdout, stderr),
+            Ok(Err(e)) => ClientMessage::Error(e.to_string()),
+            Err(_) => ClientMessage::Error("Could not get the result of the command".into()),
+        }
+    }
+
+    // Send `bytes` to the TCP server at `address` in a background thread, so the UI is not blocked
+    // while connecting or waiting for a response, returning the reply to send to the coordinator
+    async fn tcp_send(address: String, bytes: Vec<u8>, await_response: bool) -> ClientMessage {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        thread::spawn(move || {
+            let _ = sender.send(match tcp::send(&address, &bytes, await_response) {
+                Ok(Some(response)) => ClientMessage::TcpResponse(response),
+                Ok(None) => ClientMessage::Ack,
+                Err(e) => ClientMessage::Error(e.to_string()),
+            });
+        });
+        receiver.await.unwrap_or_else(|_|
+            ClientMessage::Error("Could not get the response of the TCP server".into()))
+    }
+
+    // Show a native file dialog for the user to pick a flow manifest, returning its path
+    async fn pick_flow() -> Option<String> {
+        Self::show_file_dialog(|dialog| dialog
+            .set_title("Pick a flow manifest to run")
+            .add_filter("Flow manifest", &["json"])
+            .show_open_single_file())
+            .await
+            .map(|path| path.display().to_string())
+    }
+
+    // Show a native file dialog for the user to pick a file to load into STDIN
+    async fn pick_stdin_file() -> Option<PathBuf> {
+        Self::show_file_dialog(|dialog| dialog
+            .set_title("Pick a file to load into standard input")
+            .show_open_single_file())
+            .await
+    }
+
+    // Show a native file dialog for the user to pick the path to save the image called `name` to
+    async fn pick_image_path(name: String) -> Option<PathBuf> {
+        let file_name = Path::new(&name).with_extension("png");
+        let file_name = file_name.file_name().unwrap_or_default().to_string_lossy().to_string();
+        Self::show_file_dialog(move |dialog| dialog
+            .set_title("Save image as PNG")
+            .add_filter("PNG image", &["png"])
+            .set_filename(&file_name)
+            .show_save_single_file())
+            .await
+    }
+
+    // Save the image called `name` shown in the Images tab to a PNG file at `path`
+    fn save_image(&mut self, name: &str, path: &Path) -> Result<(), String> {
+        let image = self.tab_set.images_tab.images.get(name)
+            .ok_or_else(|| format!("There is no image called '{name}'"))?;
+        image.data.save_with_format(path, ImageFormat::Png)
+            .map_err(|e| format!("Could not save image to '{}': {e}", path.display()))?;
+        self.tab_set.fileio_tab.content.push(format!("SAVE --> {}", path.display()).into());
+        Ok(())
+    }
+
     // report a new error
     #[allow(clippy::unused_self)]
     // TODO implement some display of this info on the UI
@@ -345,7 +763,14 @@ impl FlowrGui {
             "Flow location (relative, or absolute)",
             &self.submission_settings.flow_manifest_url,
         )
-            .on_input(Message::UrlChanged);
+            .on_input(Message::UrlChanged)
+            .on_paste(Message::UrlChanged);
+
+        let browse = Button::new("...").on_press(Message::BrowseForFlow);
+
+        let recent = pick_list(self.saved_settings.recent_flows.as_slice(), None::<String>,
+                               Message::UrlChanged)
+            .placeholder("Recent flows");
 
         let args = text_input(
             "Space separated flow arguments",
@@ -363,16 +788,39 @@ impl FlowrGui {
             play = play.on_press(Message::SubmitFlow);
         }
 
+        let mut stop = Button::new("Stop");
+        if self.running && !self.stop_requested {
+            stop = stop.on_press(Message::StopFlow);
+        }
+
+        let mut connect = Button::new("Connect...");
+        if !self.running && !self.submitted {
+            connect = connect.on_press(Message::ShowConnectDialog);
+        }
+
+        // the settings are only used when the coordinator is started in the background
+        let mut settings = Button::new("Settings...");
+        if matches!(self.coordinator_settings, CoordinatorSettings::Server(_))
+            && !self.running && !self.submitted {
+            settings = settings.on_press(Message::ShowSettingsDialog);
+        }
+
         Row::new()
             .spacing(10)
             .align_items(Alignment::End)
             .push(url)
+            .push(browse)
+            .push(recent)
             .push(args)
             .push(play)
+            .push(stop)
+            .push(connect)
+            .push(settings)
     }
 
     fn status_row(&self) -> Row<Message> {
         let status = match &self.coordinator_state {
+            CoordinatorState::Connecting => "Connecting".into(),
             CoordinatorState::Disconnected(reason) => format!("Disconnected({reason})"),
             CoordinatorState::Connected(_) => {
                 let msg = match (self.submitted, self.running) {
@@ -384,19 +832,87 @@ impl FlowrGui {
             }
         };
 
-        Row::new().push(Text::new(format!("Coordinator: {status}")))
+        let row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(Text::new(format!("Coordinator: {status}")));
+
+        if matches!(self.coordinator_state, CoordinatorState::Disconnected(_)) {
+            row.push(Button::new("Reconnect").on_press(Message::Reconnect))
+        } else {
+            row
+        }
+    }
+
+    // Convert the window events the App is interested in to Messages
+    fn window_event(event: Event, _status: event::Status) -> Option<Message> {
+        match event {
+            Event::Window(_, window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+            Event::Window(_, window::Event::CloseRequested) => Some(Message::CloseRequested),
+            _ => None,
+        }
+    }
+
+    // Save the settings in use, to be restored the next time flowrgui is started
+    fn save_settings(&mut self) {
+        self.saved_settings.flow_manifest_url =
+            Some(self.submission_settings.flow_manifest_url.clone());
+        self.saved_settings.flow_args = Some(self.submission_settings.flow_args.clone());
+        if let CoordinatorSettings::Server(settings) = &self.coordinator_settings {
+            self.saved_settings.threads = Some(settings.num_threads);
+            self.saved_settings.native = Some(settings.native_flowstdlib);
+            self.saved_settings.lib_dirs.clone_from(&settings.lib_dirs);
+        }
+
+        if let Some(path) = SavedSettings::path() {
+            match self.saved_settings.write(&path) {
+                Ok(()) => info!("Settings saved to '{}'", path.display()),
+                Err(e) => error!("Could not save settings: {e}"),
+            }
+        }
+    }
+
+    // Start a new attempt to connect to the Coordinator, using the current settings
+    fn reconnect(&mut self) {
+        self.connection_attempt += 1;
+        self.connection_changed(CoordinatorState::Connecting);
+    }
+
+    // The connection to the Coordinator has been lost, or is being replaced by a new one, so any
+    // flow it was running will not be heard from again
+    fn connection_changed(&mut self, state: CoordinatorState) {
+        self.coordinator_state = state;
+        self.discovery_port = None;
+        self.running = false;
+        self.submitted = false;
+        self.stop_requested = false;
+        self.tab_set.stdin_tab.waiting = None;
     }
 
-    // Create initial Settings structs for Submission and Coordinator from the CLI options
-    fn initial_settings() -> (SubmissionSettings, CoordinatorSettings, UiSettings) {
+    // Create initial Settings structs for Submission and Coordinator from the CLI options,
+    // falling back to the settings saved when last run
+    fn initial_settings(saved: &SavedSettings) -> (SubmissionSettings, CoordinatorSettings, UiSettings) {
         let matches = Self::parse_cli_args();
 
+        // defaults from config files, that command line options override
+        let config = env::current_dir()
+            .map_err(flowcore::errors::Error::from)
+            .and_then(|cwd| Config::load(&cwd))
+            .unwrap_or_else(|e| {
+                eprintln!("Could not load config: {e}");
+                Config::default()
+            });
+
         // init logging
         let default = String::from("error");
-        let verbosity = matches.get_one::<String>("verbosity").unwrap_or(&default);
+        let verbosity = matches.get_one::<String>("verbosity")
+            .or(config.verbosity.as_ref())
+            .unwrap_or(&default);
         let level = LevelFilter::from_str(verbosity).unwrap_or(LevelFilter::Error);
         let mut builder = Builder::from_default_env();
-        builder.filter_level(level).init();
+        log_capture::init(builder.filter_level(level).build());
 
         info!(
             "'{}' version {}",
@@ -405,19 +921,19 @@ impl FlowrGui {
         );
         info!("'flowrlib' version {}", flowrlib_info::version());
 
-        let flow_manifest_url = matches
-            .get_one::<String>("flow-manifest")
-            .unwrap_or(&String::new())
-            .to_string();
-        let flow_args = match matches.get_many::<String>("flow-args") {
-            Some(values) => {
-                println!("values {values:?}");
-                values
-                    .map(std::string::ToString::to_string)
-                    .collect::<Vec<String>>()
-                    .join(" ")
-            }
-            None => String::new(),
+        // the saved flow arguments only apply to the saved flow manifest
+        let (flow_manifest_url, flow_args) = match matches.get_one::<String>("flow-manifest") {
+            Some(flow_manifest_url) => {
+                let flow_args = matches.get_many::<String>("flow-args")
+                    .map(|values| values
+                        .map(std::string::ToString::to_string)
+                        .collect::<Vec<String>>()
+                        .join(" "))
+                    .unwrap_or_default();
+                (flow_manifest_url.to_string(), flow_args)
+            }
+            None => (saved.flow_manifest_url.clone().unwrap_or_default(),
+                     saved.flow_args.clone().unwrap_or_default()),
         };
 
         // TODO read from settings or UI
@@ -429,9 +945,9 @@ impl FlowrGui {
         let debug_this_flow = matches.get_flag("debugger");
 
         let coordinator_settings = if let Some(port) = matches.get_one::<u16>("client") {
-            CoordinatorSettings::ClientOnly(*port)
+            CoordinatorSettings::ClientOnly(None, *port)
         } else {
-            let lib_dirs = if matches.contains_id("lib_dir") {
+            let mut lib_dirs: Vec<String> = if matches.contains_id("lib_dir") {
                 if let Some(dirs) = matches.get_many::<String>("lib_dir") {
                     dirs.map(std::string::ToString::to_string).collect()
                 } else {
@@ -440,17 +956,27 @@ impl FlowrGui {
             } else {
                 vec![]
             };
+            // the library search path saved when last run replaces the one in config files
+            let other_lib_dirs = if saved.lib_dirs.is_empty() {
+                config.lib_dirs
+            } else {
+                saved.lib_dirs.clone()
+            };
+            for lib_dir in other_lib_dirs {
+                if !lib_dirs.contains(&lib_dir) {
+                    lib_dirs.push(lib_dir);
+                }
+            }
 
-            let lib_search_path = FlowrGui::lib_search_path(&lib_dirs);
-
-            let native_flowstdlib = matches.get_flag("native");
+            let native_flowstdlib = matches.get_flag("native")
+                || saved.native.or(config.native).unwrap_or(false);
 
-            let num_threads = FlowrGui::num_threads(&matches);
+            let num_threads = FlowrGui::num_threads(&matches, saved.threads.or(config.threads));
 
             let server_settings = ServerSettings {
                 native_flowstdlib,
                 num_threads,
-                lib_search_path,
+                lib_dirs,
             };
 
             CoordinatorSettings::Server(server_settings)
@@ -460,6 +986,10 @@ impl FlowrGui {
             SubmissionSettings {
                 flow_manifest_url,
                 flow_args,
+                allowed_env_vars: matches.get_many::<String>("allow-env")
+                    .map(|names| names.cloned().collect())
+                    .unwrap_or_default(),
+                allow_exec: matches.get_flag("allow-exec"),
                 debug_this_flow,
                 display_metrics: matches.get_flag("metrics"),
                 parallel_jobs_limit,
@@ -473,6 +1003,11 @@ impl FlowrGui {
 
     // Parse the command line arguments using clap
     fn parse_cli_args() -> ArgMatches {
+        Self::cli_command().get_matches()
+    }
+
+    // Define the command line arguments using clap
+    fn cli_command() -> ClapCommand {
         let app = ClapCommand::new(env!("CARGO_PKG_NAME")).version(env!("CARGO_PKG_VERSION"));
 
         let app = app.arg(
@@ -517,7 +1052,17 @@ impl FlowrGui {
                 .help("Run any flow specified automatically on start-up. Exit automatically."),
         );
 
-        let app = app
+        app
+            .arg(Arg::new("allow-env")
+                .long("allow-env")
+                .num_args(1)
+                .action(clap::ArgAction::Append)
+                .value_name("NAME")
+                .help("Allow the flow to read environment variable NAME using 'context://env' functions"))
+            .arg(Arg::new("allow-exec")
+                .long("allow-exec")
+                .action(clap::ArgAction::SetTrue)
+                .help("Allow the flow to run commands using the 'context://process/exec' function"))
             .arg(Arg::new("jobs")
                 .short('j')
                 .long("jobs")
@@ -547,13 +1092,19 @@ impl FlowrGui {
                 .help("Set verbosity level for output (trace, debug, info, warn, default: error)"))
             .arg(Arg::new("flow-manifest")
                 .num_args(1)
+                .value_hint(ValueHint::AnyPath)
+                .add(ArgValueCompleter::new(complete_flow_manifest))
                 .help("the file path of the 'flow' manifest file"))
             .arg(Arg::new("flow-args")
                 .num_args(0..)
                 .trailing_var_arg(true)
-                .help("A list of arguments to pass to the flow."));
-
-        app.get_matches()
+                .help("A list of arguments to pass to the flow."))
+            .subcommand(ClapCommand::new("completions")
+                .about("Print a shell completion script for flowrgui")
+                .arg(Arg::new("shell")
+                    .required(true)
+                    .value_parser(clap::value_parser!(Shell))
+                    .help("The shell to print the completion script for")))
     }
 
     // Create absolute file:// Url for flow location - using the contents of UI field
@@ -577,31 +1128,13 @@ impl FlowrGui {
         flow_args
     }
 
-    // For the lib provider, libraries maybe installed in multiple places in the file system.
-    // In order to find the content, a FLOW_LIB_PATH environment variable can be configured with a
-    // list of directories in which to look for the library in question.
-    fn lib_search_path(search_path_additions: &[String]) -> Simpath {
-        let mut lib_search_path = Simpath::new_with_separator("FLOW_LIB_PATH", ',');
-
-        for additions in search_path_additions {
-            lib_search_path.add(additions);
-            info!("'{}' added to the Library Search Path", additions);
-        }
-
-        if lib_search_path.is_empty() {
-            let home_dir = env::var("HOME").unwrap_or_else(|_| "Could not get $HOME".to_string());
-            lib_search_path.add(&format!("{home_dir}/.flow/lib"));
-        }
-
-        lib_search_path
-    }
-
     // Determine the number of threads to use to execute flows
-    // - default (if value is not provided on the command line) of the number of cores
+    // - the number saved or set in the config files (if value is not provided on the command line)
+    // - default (if value is not provided in either) of the number of cores
     #[allow(clippy::redundant_closure_for_method_calls)]
-    fn num_threads(matches: &ArgMatches) -> usize {
-        match matches.get_one::<usize>("threads") {
-            Some(num_threads) => *num_threads,
+    fn num_threads(matches: &ArgMatches, config_threads: Option<usize>) -> usize {
+        match matches.get_one::<usize>("threads").copied().or(config_threads) {
+            Some(num_threads) => num_threads,
             // Could be simplified to `std::num::NonZero::get`but generic NonZero is unstable
             None => thread::available_parallelism()
                 .map(|n| n.get())
@@ -609,16 +1142,34 @@ impl FlowrGui {
         }
     }
 
+    // Respond to a request from the flow to read STDIN it is waiting on, if it can be answered
+    // now that more input has been entered or the end of it signalled
+    fn respond_to_stdin_request(&mut self) {
+        if let Some(msg) = self.tab_set.stdin_tab.respond() {
+            self.send(msg);
+        }
+    }
+
     fn send(&mut self, msg: ClientMessage) {
         if let CoordinatorState::Connected(ref sender) = self.coordinator_state {
             let _ = sender.try_send(msg);
         }
     }
 
+    // Acknowledge a message about a job from the coordinator, or respond with a request to cancel
+    // execution of the flow if the UI has requested it be stopped
+    fn acknowledge_job(&mut self) {
+        if self.stop_requested {
+            self.send(ClientMessage::CancelFlow);
+        } else {
+            self.send(ClientMessage::Ack);
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     fn process_coordinator_message(&mut self, message: CoordinatorMessage) -> Command<Message> {
         match message {
-            CoordinatorMessage::Connected(_) => {
+            CoordinatorMessage::Connected(_, _) => {
                 self.error("Coordinator is already connected");
             }
             CoordinatorMessage::FlowStart => {
@@ -626,8 +1177,18 @@ impl FlowrGui {
                 self.submitted = false;
                 self.send(ClientMessage::Ack);
             }
+            CoordinatorMessage::FlowCancelled(metrics) => {
+                self.running = false;
+                self.stop_requested = false;
+                if self.submission_settings.display_metrics {
+                    self.show_modal = true;
+                    self.modal_content = ("Flow Cancelled - Metrics".into(), format!("{metrics}"));
+                }
+                // NO response - so we can use next request sent to submit another flow
+            }
             CoordinatorMessage::FlowEnd(metrics) => {
                 self.running = false;
+                self.stop_requested = false;
                 if self.submission_settings.display_metrics {
                     self.show_modal = true;
                     self.modal_content = ("Flow Ended - Metrics".into(), format!("{metrics}"));
@@ -642,8 +1203,30 @@ impl FlowrGui {
                 self.coordinator_state = CoordinatorState::Disconnected("Exited".into());
                 self.send(ClientMessage::Ack);
             }
+            CoordinatorMessage::JobDispatched(function_id) => {
+                self.tab_set.graph_tab.graph.job_dispatched(function_id);
+                self.acknowledge_job();
+            }
+            CoordinatorMessage::JobCompleted(function_id, success) => {
+                self.tab_set.graph_tab.graph.job_completed(function_id, success);
+                self.acknowledge_job();
+            }
+            CoordinatorMessage::MetricsSnapshot(snapshot) => {
+                self.tab_set.metrics_tab.history.push(snapshot);
+                self.acknowledge_job();
+            }
             CoordinatorMessage::Stdout(string) => {
-                self.tab_set.stdout_tab.content.push(string);
+                self.tab_set.stdout_tab.content.push(string.into());
+                self.send(ClientMessage::Ack);
+                if self.tab_set.stdout_tab.auto_scroll {
+                    return scrollable::snap_to(
+                        self.tab_set.stdout_tab.id.clone(),
+                        scrollable::RelativeOffset::END,
+                    );
+                }
+            }
+            CoordinatorMessage::StdoutStyled(spans) => {
+                self.tab_set.stdout_tab.content.push(Line::Styled(spans));
                 self.send(ClientMessage::Ack);
                 if self.tab_set.stdout_tab.auto_scroll {
                     return scrollable::snap_to(
@@ -653,7 +1236,7 @@ impl FlowrGui {
                 }
             }
             CoordinatorMessage::Stderr(string) => {
-                self.tab_set.stderr_tab.content.push(string);
+                self.tab_set.stderr_tab.content.push(string.into());
                 self.send(ClientMessage::Ack);
                 if self.tab_set.stderr_tab.auto_scroll {
                     return scrollable::snap_to(
@@ -662,19 +1245,17 @@ impl FlowrGui {
                     );
                 }
             }
+            // if there is no input yet, the response is sent when the user enters some or
+            // signals the end of it
             CoordinatorMessage::GetStdin => {
-                let msg = match self.tab_set.stdin_tab.get_all() {
-                    Some(buf) => ClientMessage::Stdin(buf),
-                    None => ClientMessage::GetLineEof,
-                };
-                self.send(msg);
+                if let Some(msg) = self.tab_set.stdin_tab.request(StdinRequest::All) {
+                    self.send(msg);
+                }
             }
             CoordinatorMessage::GetLine(prompt) => {
-                let msg = match self.tab_set.stdin_tab.get_line(&prompt) {
-                    Some(line) => ClientMessage::Line(line),
-                    None => ClientMessage::GetLineEof,
-                };
-                self.send(msg);
+                if let Some(msg) = self.tab_set.stdin_tab.request(StdinRequest::Line(prompt)) {
+                    self.send(msg);
+                }
             }
             CoordinatorMessage::GetArgs => {
                 let args = self.flow_arg_vec();
@@ -697,6 +1278,73 @@ impl FlowrGui {
                 }
                 */
             }
+            CoordinatorMessage::GetEnvVar(name) => {
+                let msg = if self.submission_settings.allowed_env_vars.contains(&name) {
+                    ClientMessage::EnvVar(env::var(&name).ok())
+                } else {
+                    ClientMessage::Error(format!("The flow is not allowed to read environment \
+                        variable '{name}', allow it using '--allow-env {name}'"))
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::GetEnvVars => {
+                let vars = env::vars()
+                    .filter(|(name, _)| self.submission_settings.allowed_env_vars.contains(name))
+                    .collect();
+                self.send(ClientMessage::EnvVars(vars));
+            }
+            CoordinatorMessage::KvGet(key) => {
+                let msg = match KvStore::open().and_then(|store| store.get(&key)) {
+                    Ok(value) => ClientMessage::KvValue(value),
+                    Err(e) => ClientMessage::Error(e.to_string()),
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::KvPut(key, value) => {
+                let msg = match KvStore::open().and_then(|store| store.put(key, value)) {
+                    Ok(()) => ClientMessage::Ack,
+                    Err(e) => ClientMessage::Error(e.to_string()),
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::KvDelete(key) => {
+                let msg = match KvStore::open().and_then(|store| store.delete(&key)) {
+                    Ok(value) => ClientMessage::KvValue(value),
+                    Err(e) => ClientMessage::Error(e.to_string()),
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::List(path) => {
+                let msg = match fs::read_dir(&path) {
+                    Ok(entries) => {
+                        let mut paths: Vec<String> = entries.flatten()
+                            .map(|entry| entry.path().display().to_string())
+                            .collect();
+                        paths.sort();
+                        ClientMessage::DirectoryEntries(paths)
+                    }
+                    Err(e) => ClientMessage::Error(format!("Could not list directory '{path}': '{e}'")),
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::Stat(path) => {
+                let msg = match fs::metadata(&path) {
+                    Ok(metadata) => ClientMessage::Metadata(FileMetaData::from(&metadata)),
+                    Err(e) => ClientMessage::Error(format!("Could not get metadata of '{path}': '{e}'")),
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::TcpSend(address, bytes, await_response) => {
+                return Command::perform(Self::tcp_send(address, bytes, await_response),
+                                        Message::Reply);
+            }
+            CoordinatorMessage::Exec(command, args, stdin) => {
+                if self.submission_settings.allow_exec {
+                    return Command::perform(Self::exec(command, args, stdin), Message::Reply);
+                }
+                self.send(ClientMessage::Error(format!("The flow is not allowed to run command \
+                    '{command}', allow it using '--allow-exec'")));
+            }
             CoordinatorMessage::Read(file_path) => {
                 // TODO list file reads and write in the UI somewhere
                 let msg = match File::open(&file_path) {
@@ -707,14 +1355,14 @@ impl FlowrGui {
                                 self.tab_set
                                     .fileio_tab
                                     .content
-                                    .push(format!("READ <-- {file_path}"));
+                                    .push(format!("READ <-- {file_path}").into());
                                 /*
                                                                if self.tab_set.stdout_tab.auto_scroll {
                                                                    return scrollable::snap_to(
                                                                        self.tab_set.stdout_tab.id.clone(), scrollable::RelativeOffset::END);
                                                                }
                                 */
-                                ClientMessage::FileContents(file_path, buffer)
+                                ClientMessage::FileContents(file_path, buffer.into())
                             }
                             Err(_) => ClientMessage::Error(format!(
                                 "Could not read content from '{file_path:?}'"
@@ -727,12 +1375,12 @@ impl FlowrGui {
             }
             CoordinatorMessage::Write(filename, bytes) => {
                 let msg = match File::create(&filename) {
-                    Ok(mut file) => match file.write_all(bytes.as_slice()) {
+                    Ok(mut file) => match file.write_all(&bytes) {
                         Ok(()) => {
                             self.tab_set
                                 .fileio_tab
                                 .content
-                                .push(format!("WRITE --> {filename}"));
+                                .push(format!("WRITE --> {filename}").into());
                             /*
                                                            if self.tab_set.stdout_tab.auto_scroll {
                                                                return scrollable::snap_to(
@@ -756,6 +1404,61 @@ impl FlowrGui {
                 };
                 self.send(msg);
             }
+            CoordinatorMessage::Append(filename, bytes) => {
+                let msg = match OpenOptions::new().append(true).open(&filename) {
+                    Ok(mut file) => match file.write_all(&bytes) {
+                        Ok(()) => {
+                            self.tab_set
+                                .fileio_tab
+                                .content
+                                .push(format!("APPEND --> {filename}").into());
+                            ClientMessage::Ack
+                        }
+                        Err(e) => {
+                            let msg = format!("Error appending to file: '{filename}': '{e}'");
+                            self.error(&msg);
+                            ClientMessage::Error(msg)
+                        }
+                    },
+                    Err(e) => {
+                        let msg = format!("Error opening file: '{filename}': '{e}'");
+                        self.error(&msg);
+                        ClientMessage::Error(msg)
+                    }
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::Remove(filename) => {
+                let msg = match fs::remove_file(&filename) {
+                    Ok(()) => {
+                        self.tab_set
+                            .fileio_tab
+                            .content
+                            .push(format!("REMOVE --> {filename}").into());
+                        ClientMessage::Ack
+                    }
+                    Err(e) => {
+                        let msg = format!("Error removing file: '{filename}': '{e}'");
+                        self.error(&msg);
+                        ClientMessage::Error(msg)
+                    }
+                };
+                self.send(msg);
+            }
+            CoordinatorMessage::ImageRead(filename) => {
+                let msg = match image::open(&filename) {
+                    Ok(image) => {
+                        self.tab_set
+                            .fileio_tab
+                            .content
+                            .push(format!("READ <-- {filename}").into());
+                        let image = image.to_rgb8();
+                        ClientMessage::Image(image.width(), image.height(), image.into_raw())
+                    }
+                    Err(e) => ClientMessage::Error(format!("Could not read image '{filename}': '{e}'")),
+                };
+                self.send(msg);
+            }
             CoordinatorMessage::PixelWrite(
                 (x_coord, y_coord),
                 (red, green, blue),
@@ -784,6 +1487,45 @@ impl FlowrGui {
                 }
                 self.send(ClientMessage::Ack);
             }
+            CoordinatorMessage::DrawLine(from, to, color, (width, height), name) => {
+                self.tab_set.images_tab.drawing(name, width, height).shapes.push(Shape::Line {
+                    from: Drawing::point(from),
+                    to: Drawing::point(to),
+                    color: Drawing::color(color),
+                });
+                self.send(ClientMessage::Ack);
+            }
+            CoordinatorMessage::DrawRectangle(origin, (rect_width, rect_height), color, filled,
+                                              (width, height), name) => {
+                #[allow(clippy::cast_precision_loss)]
+                let size = Size::new(rect_width as f32, rect_height as f32);
+                self.tab_set.images_tab.drawing(name, width, height).shapes.push(Shape::Rectangle {
+                    origin: Drawing::point(origin),
+                    size,
+                    color: Drawing::color(color),
+                    filled,
+                });
+                self.send(ClientMessage::Ack);
+            }
+            CoordinatorMessage::DrawCircle(center, radius, color, filled, (width, height), name) => {
+                #[allow(clippy::cast_precision_loss)]
+                let radius = radius as f32;
+                self.tab_set.images_tab.drawing(name, width, height).shapes.push(Shape::Circle {
+                    center: Drawing::point(center),
+                    radius,
+                    color: Drawing::color(color),
+                    filled,
+                });
+                self.send(ClientMessage::Ack);
+            }
+            CoordinatorMessage::DrawText(position, content, color, (width, height), name) => {
+                self.tab_set.images_tab.drawing(name, width, height).shapes.push(Shape::Text {
+                    position: Drawing::point(position),
+                    content,
+                    color: Drawing::color(color),
+                });
+                self.send(ClientMessage::Ack);
+            }
             _ => {}
         };
         Command::none()
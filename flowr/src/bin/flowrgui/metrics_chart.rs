@@ -0,0 +1,249 @@
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+use iced::alignment::Horizontal;
+use iced::mouse::Cursor;
+use iced::widget::canvas::{Frame, Geometry, Path, Program, Stroke, Text};
+
+use flowcore::model::metrics::MetricsSnapshot;
+
+use crate::Message;
+
+// Space left around the plot of a chart for its title and axis labels
+const MARGIN: f32 = 30.0;
+const PLOT_COLOR: Color = Color::from_rgb(0.14, 0.45, 0.78);
+
+/// The charts of the metrics of a flow that can be drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChartKind {
+    /// The number of jobs completed per second, over time
+    JobsPerSecond,
+    /// The number of jobs dispatched that have not completed yet, over time
+    OutstandingJobs,
+    /// The number of jobs executed by each function so far
+    FunctionExecutions,
+}
+
+impl ChartKind {
+    fn title(self) -> &'static str {
+        match self {
+            ChartKind::JobsPerSecond => "Jobs completed per second",
+            ChartKind::OutstandingJobs => "Outstanding jobs",
+            ChartKind::FunctionExecutions => "Jobs executed per function",
+        }
+    }
+}
+
+/// The snapshots of the metrics received during the execution of a flow
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MetricsHistory {
+    snapshots: Vec<MetricsSnapshot>,
+}
+
+impl MetricsHistory {
+    /// Add a new snapshot of the metrics of the flow
+    pub(crate) fn push(&mut self, snapshot: MetricsSnapshot) {
+        self.snapshots.push(snapshot);
+    }
+
+    /// Remove all the snapshots, before a new flow is executed
+    pub(crate) fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Return true if no snapshots have been received
+    pub(crate) fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Return a chart of the `kind` given, that can be drawn on a `Canvas`
+    pub(crate) fn chart(&self, kind: ChartKind) -> Chart<'_> {
+        Chart { history: self, kind }
+    }
+
+    // (seconds since execution started, jobs completed per second since the previous snapshot)
+    #[allow(clippy::cast_precision_loss)]
+    fn jobs_per_second(&self) -> Vec<(f32, f32)> {
+        self.snapshots.windows(2).filter_map(|pair| {
+            if let [previous, current] = pair {
+                let interval_ms = current.elapsed_ms.saturating_sub(previous.elapsed_ms);
+                if interval_ms > 0 {
+                    let completed = Self::jobs_completed(current)
+                        .saturating_sub(Self::jobs_completed(previous));
+                    return Some((current.elapsed_ms as f32 / 1000.0,
+                                 completed as f32 * 1000.0 / interval_ms as f32));
+                }
+            }
+            None
+        }).collect()
+    }
+
+    // (seconds since execution started, jobs outstanding)
+    #[allow(clippy::cast_precision_loss)]
+    fn outstanding_jobs(&self) -> Vec<(f32, f32)> {
+        self.snapshots.iter()
+            .map(|snapshot| (snapshot.elapsed_ms as f32 / 1000.0, snapshot.jobs_outstanding as f32))
+            .collect()
+    }
+
+    // The number of jobs executed by each function, in the latest snapshot
+    fn function_executions(&self) -> &[usize] {
+        self.snapshots.last()
+            .map(|snapshot| snapshot.function_executions.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn jobs_completed(snapshot: &MetricsSnapshot) -> usize {
+        snapshot.function_executions.iter().sum()
+    }
+}
+
+/// A chart of one kind of the metrics in a [`MetricsHistory`]
+pub(crate) struct Chart<'a> {
+    history: &'a MetricsHistory,
+    kind: ChartKind,
+}
+
+impl Chart<'_> {
+    // Plot `points` as a line, scaled so all of them fit in the plot area starting at `origin`
+    fn draw_line(frame: &mut Frame, points: &[(f32, f32)], origin: Point, plot: Size,
+                 color: Color) {
+        let max_x = points.iter().map(|(x, _)| *x).fold(0.0f32, f32::max).max(1.0);
+        let max_y = points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max).max(1.0);
+
+        let path = Path::new(|builder| {
+            for (index, (x, y)) in points.iter().enumerate() {
+                let point = Point::new(origin.x + x / max_x * plot.width,
+                                       origin.y - y / max_y * plot.height);
+                if index == 0 {
+                    builder.move_to(point);
+                } else {
+                    builder.line_to(point);
+                }
+            }
+        });
+        frame.stroke(&path, Stroke::default().with_width(2.0).with_color(PLOT_COLOR));
+
+        Self::axis_labels(frame, &format!("{max_y:.1}"), &format!("{max_x:.0}s"), origin, plot,
+                          color);
+    }
+
+    // Draw a bar for each of `values`, scaled so the largest fits in the plot area
+    #[allow(clippy::cast_precision_loss)]
+    fn draw_bars(frame: &mut Frame, values: &[usize], origin: Point, plot: Size, color: Color) {
+        let max = values.iter().copied().max().unwrap_or(0).max(1);
+        let slot = plot.width / values.len().max(1) as f32;
+
+        for (function_id, value) in values.iter().enumerate() {
+            let height = *value as f32 / max as f32 * plot.height;
+            let left = origin.x + function_id as f32 * slot;
+            frame.fill_rectangle(Point::new(left + slot * 0.1, origin.y - height),
+                                 Size::new(slot * 0.8, height), PLOT_COLOR);
+            // label the bars with the function id, if there is room
+            if slot >= 16.0 {
+                frame.fill_text(Text {
+                    content: format!("#{function_id}"),
+                    position: Point::new(left + slot / 2.0, origin.y + 4.0),
+                    color,
+                    horizontal_alignment: Horizontal::Center,
+                    ..Text::default()
+                });
+            }
+        }
+
+        Self::axis_labels(frame, &max.to_string(), "", origin, plot, color);
+    }
+
+    // Label the maximum values on the y and x axes
+    fn axis_labels(frame: &mut Frame, max_y: &str, max_x: &str, origin: Point, plot: Size,
+                   color: Color) {
+        frame.fill_text(Text {
+            content: max_y.to_string(),
+            position: Point::new(origin.x - 4.0, origin.y - plot.height),
+            color,
+            horizontal_alignment: Horizontal::Right,
+            ..Text::default()
+        });
+        frame.fill_text(Text {
+            content: max_x.to_string(),
+            position: Point::new(origin.x + plot.width, origin.y + 4.0),
+            color,
+            horizontal_alignment: Horizontal::Right,
+            ..Text::default()
+        });
+    }
+}
+
+impl Program<Message> for Chart<'_> {
+    type State = ();
+
+    fn draw(&self, _state: &(), renderer: &Renderer, theme: &Theme, bounds: Rectangle,
+            _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let text_color = theme.palette().text;
+
+        frame.fill_text(Text {
+            content: self.kind.title().to_string(),
+            position: Point::new(MARGIN, 4.0),
+            color: text_color,
+            ..Text::default()
+        });
+
+        let origin = Point::new(MARGIN, bounds.height - MARGIN);
+        let plot = Size::new((bounds.width - 2.0 * MARGIN).max(1.0),
+                             (bounds.height - 2.0 * MARGIN).max(1.0));
+        let axis_stroke = Stroke::default().with_width(1.0).with_color(text_color);
+        frame.stroke(&Path::line(origin, Point::new(origin.x, origin.y - plot.height)),
+                     axis_stroke.clone());
+        frame.stroke(&Path::line(origin, Point::new(origin.x + plot.width, origin.y)),
+                     axis_stroke);
+
+        match self.kind {
+            ChartKind::JobsPerSecond => Self::draw_line(&mut frame,
+                                                        &self.history.jobs_per_second(),
+                                                        origin, plot, text_color),
+            ChartKind::OutstandingJobs => Self::draw_line(&mut frame,
+                                                          &self.history.outstanding_jobs(),
+                                                          origin, plot, text_color),
+            ChartKind::FunctionExecutions => Self::draw_bars(&mut frame,
+                                                             self.history.function_executions(),
+                                                             origin, plot, text_color),
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowcore::model::metrics::MetricsSnapshot;
+
+    use super::MetricsHistory;
+
+    fn snapshot(elapsed_ms: u64, jobs_outstanding: usize,
+                function_executions: Vec<usize>) -> MetricsSnapshot {
+        MetricsSnapshot {
+            elapsed_ms,
+            jobs_created: 0,
+            jobs_outstanding,
+            function_executions,
+        }
+    }
+
+    #[test]
+    fn series() {
+        let mut history = MetricsHistory::default();
+        assert!(history.is_empty());
+        assert!(history.jobs_per_second().is_empty());
+        assert!(history.function_executions().is_empty());
+
+        history.push(snapshot(1000, 3, vec![1, 1]));
+        history.push(snapshot(2000, 1, vec![5, 7]));
+        history.push(snapshot(2500, 0, vec![6, 7]));
+
+        assert_eq!(history.jobs_per_second(), vec![(2.0, 10.0), (2.5, 2.0)]);
+        assert_eq!(history.outstanding_jobs(), vec![(1.0, 3.0), (2.0, 1.0), (2.5, 0.0)]);
+        assert_eq!(history.function_executions(), &[6, 7]);
+
+        history.clear();
+        assert!(history.is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::errors::{Result, ResultExt};
+
+// The file, in the `.flow` directory of the user's home directory, where settings are saved
+const SAVED_SETTINGS_FILENAME: &str = "flowrgui.toml";
+// The maximum number of recently run flows remembered
+const MAX_RECENT_FLOWS: usize = 10;
+
+/// The settings of `flowrgui` that are saved when it exits and restored when it next starts.
+/// Options specified on the command line override them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub(crate) struct SavedSettings {
+    /// The location of the last flow manifest run
+    pub flow_manifest_url: Option<String>,
+    /// The arguments last passed to the flow
+    pub flow_args: Option<String>,
+    /// The locations of the flow manifests run most recently, most recent first
+    pub recent_flows: Vec<String>,
+    /// The number of threads to use to execute jobs
+    pub threads: Option<usize>,
+    /// Directories or base Urls to add to the library search path
+    pub lib_dirs: Vec<String>,
+    /// Use native (`true`) or WASM (`false`) implementations of library functions
+    pub native: Option<bool>,
+    /// The width of the window
+    pub window_width: Option<f32>,
+    /// The height of the window
+    pub window_height: Option<f32>,
+}
+
+impl SavedSettings {
+    /// The path of the saved settings file, if the user's home directory is known
+    pub(crate) fn path() -> Option<PathBuf> {
+        env::var("HOME").ok()
+            .map(|home_dir| Path::new(&home_dir).join(".flow").join(SAVED_SETTINGS_FILENAME))
+    }
+
+    /// Add the location of a flow manifest that has been run to the recent flows, or move it
+    /// to the front if it is already one of them
+    pub(crate) fn add_recent_flow(&mut self, flow_manifest_url: &str) {
+        if flow_manifest_url.trim().is_empty() {
+            return;
+        }
+        self.recent_flows.retain(|url| url != flow_manifest_url);
+        self.recent_flows.insert(0, flow_manifest_url.to_string());
+        self.recent_flows.truncate(MAX_RECENT_FLOWS);
+    }
+
+    /// Read the `SavedSettings` in the file at `path`, if it exists
+    pub(crate) fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("Could not read settings file '{}'", path.display()))?;
+        let settings = toml::from_str(&contents)
+            .chain_err(|| format!("Could not parse settings file '{}'", path.display()))?;
+        Ok(Some(settings))
+    }
+
+    /// Write the `SavedSettings` to the file at `path`, creating the directory for it if needed
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .chain_err(|| format!("Could not create directory '{}'", dir.display()))?;
+        }
+        let contents = toml::to_string(self).chain_err(|| "Could not serialize settings")?;
+        fs::write(path, contents)
+            .chain_err(|| format!("Could not write settings file '{}'", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{MAX_RECENT_FLOWS, SavedSettings};
+
+    #[test]
+    fn missing_settings_file() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        assert!(SavedSettings::read(&dir.path().join("flowrgui.toml"))
+            .expect("Could not read settings").is_none());
+    }
+
+    #[test]
+    fn partial_settings_file() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let path = dir.path().join("flowrgui.toml");
+        fs::write(&path, "threads = 2").expect("Could not write settings");
+
+        let settings = SavedSettings::read(&path).expect("Could not read settings")
+            .expect("No settings read");
+        assert_eq!(settings.threads, Some(2));
+        assert!(settings.lib_dirs.is_empty());
+        assert!(settings.flow_manifest_url.is_none());
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let path = dir.path().join(".flow").join("flowrgui.toml");
+        let settings = SavedSettings {
+            flow_manifest_url: Some("flowr/examples/fibonacci".into()),
+            flow_args: Some("10 20".into()),
+            recent_flows: vec!["flowr/examples/fibonacci".into()],
+            threads: Some(4),
+            lib_dirs: vec!["/opt/flow/lib".into()],
+            native: Some(true),
+            window_width: Some(1024.0),
+            window_height: Some(768.0),
+        };
+
+        settings.write(&path).expect("Could not write settings");
+        assert_eq!(SavedSettings::read(&path).expect("Could not read settings"), Some(settings));
+    }
+
+    #[test]
+    fn recent_flows() {
+        let mut settings = SavedSettings::default();
+        settings.add_recent_flow("first");
+        settings.add_recent_flow("second");
+        settings.add_recent_flow("first");
+        settings.add_recent_flow(" ");
+        assert_eq!(settings.recent_flows, vec!["first".to_string(), "second".to_string()]);
+
+        for flow in 0..MAX_RECENT_FLOWS {
+            settings.add_recent_flow(&flow.to_string());
+        }
+        assert_eq!(settings.recent_flows.len(), MAX_RECENT_FLOWS);
+        assert_eq!(settings.recent_flows.first().map(String::as_str), Some("9"));
+    }
+}
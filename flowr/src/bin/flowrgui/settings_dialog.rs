@@ -0,0 +1,110 @@
+use iced::alignment::Horizontal;
+use iced::widget::{checkbox, text_input, Button, Column, Row, Text};
+use iced::{Element, Length};
+use iced_aw::Card;
+
+use crate::{Message, ServerSettings};
+
+/// A dialog where the user edits the settings used to start the coordinator in the background
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SettingsDialog {
+    /// The number of threads to use to execute jobs
+    pub threads: String,
+    /// Use the natively linked flowstdlib library, or the WASM version
+    pub native: bool,
+    /// Comma separated directories or base Urls to add to the library search path
+    pub lib_dirs: String,
+    /// An error in the values entered, shown to the user
+    pub error: Option<String>,
+}
+
+impl SettingsDialog {
+    /// Create a dialog, showing the `settings` in use
+    pub(crate) fn new(settings: &ServerSettings) -> Self {
+        SettingsDialog {
+            threads: settings.num_threads.to_string(),
+            native: settings.native_flowstdlib,
+            lib_dirs: settings.lib_dirs.join(","),
+            error: None,
+        }
+    }
+
+    /// Return the settings entered, or an error if not valid
+    pub(crate) fn settings(&self) -> Result<ServerSettings, String> {
+        let num_threads = match self.threads.trim().parse::<usize>() {
+            Ok(threads) if threads > 0 => threads,
+            _ => return Err(format!("'{}' is not a valid number of threads", self.threads.trim())),
+        };
+        let lib_dirs = self.lib_dirs.split(',')
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(ToString::to_string)
+            .collect();
+        Ok(ServerSettings {
+            native_flowstdlib: self.native,
+            num_threads,
+            lib_dirs,
+        })
+    }
+
+    pub(crate) fn view(&self) -> Element<'_, Message> {
+        let mut body = Column::new()
+            .spacing(10)
+            .push(Text::new("Threads"))
+            .push(text_input("Number of threads to execute jobs", &self.threads)
+                .on_input(Message::SettingsThreadsChanged)
+                .on_paste(Message::SettingsThreadsChanged))
+            .push(Text::new("Library search path"))
+            .push(text_input("Comma separated directories or base Urls", &self.lib_dirs)
+                .on_input(Message::SettingsLibDirsChanged)
+                .on_paste(Message::SettingsLibDirsChanged)
+                .on_submit(Message::ApplySettings))
+            .push(checkbox("Use native flowstdlib", self.native)
+                .on_toggle(Message::SettingsNativeToggled));
+        if let Some(error) = &self.error {
+            body = body.push(Text::new(error));
+        }
+
+        let button = |label, message| Button::new(Text::new(label)
+            .horizontal_alignment(Horizontal::Center))
+            .width(Length::Fill)
+            .on_press(message);
+
+        Card::new(Text::new("Coordinator Settings"), body)
+            .foot(Row::new().spacing(10).padding(5).width(Length::Fill)
+                .push(button("Apply", Message::ApplySettings))
+                .push(button("Cancel", Message::CloseModal)))
+            .max_width(400.0)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SettingsDialog;
+
+    fn dialog(threads: &str, lib_dirs: &str) -> SettingsDialog {
+        SettingsDialog {
+            threads: threads.into(),
+            native: true,
+            lib_dirs: lib_dirs.into(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn valid_settings() {
+        let settings = dialog(" 4", "/opt/flow/lib, ,https://example.com/lib")
+            .settings().expect("Settings should be valid");
+        assert_eq!(settings.num_threads, 4);
+        assert!(settings.native_flowstdlib);
+        assert_eq!(settings.lib_dirs, vec!["/opt/flow/lib".to_string(),
+                                           "https://example.com/lib".to_string()]);
+    }
+
+    #[test]
+    fn invalid_threads() {
+        assert!(dialog("0", "").settings().is_err());
+        assert!(dialog("many", "").settings().is_err());
+    }
+}
@@ -1,14 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
-use iced::{Command, Element, Length};
-use iced::widget::{Column, scrollable, text, toggler};
-use iced::widget::image::{Handle, Viewer};
+use iced::{Alignment, Command, ContentFit, Element, Font, Length};
+use iced::font::{Style, Weight};
+use iced::widget::{Button, Canvas, Column, pick_list, Row, scrollable, text, toggler};
+use iced::widget::image::{Handle, Image};
 use iced::widget::scrollable::{Id, Scrollable};
 use iced::widget::TextInput;
 use iced_aw::{TabBarStyles, TabLabel, Tabs};
 use once_cell::sync::Lazy;
+use tokio::sync::mpsc::Sender;
+
+use flowrlib::debug_command::DebugCommand;
+use log::LevelFilter;
 
 use crate::{ImageReference, Message};
+use crate::debug_manager::DebugEvent;
+use crate::drawing::Drawing;
+use crate::log_capture::{LEVELS, LogRecord};
+use crate::graph::FlowGraph;
+use crate::metrics_chart::{ChartKind, MetricsHistory};
+use crate::gui::debug_client::{parse_breakpoint_spec, parse_inspect_spec};
+use crate::gui::client_message::ClientMessage;
+use crate::gui::coordinator_message::{Color, StyledText};
 
 #[allow(clippy::struct_field_names)]
 pub(crate) struct TabSet {
@@ -18,6 +31,10 @@ pub(crate) struct TabSet {
     pub stdin_tab: StdInTab,
     pub images_tab: ImageTab,
     pub fileio_tab: StdOutTab,
+    pub graph_tab: GraphTab,
+    pub metrics_tab: MetricsTab,
+    pub debugger_tab: DebuggerTab,
+    pub log_tab: LogTab,
 }
 
 impl TabSet {
@@ -44,6 +61,10 @@ impl TabSet {
                 content: vec!(),
                 auto_scroll: true
             },
+            graph_tab: GraphTab::new("Graph"),
+            metrics_tab: MetricsTab::new("Metrics"),
+            debugger_tab: DebuggerTab::new("Debugger"),
+            log_tab: LogTab::new("Log"),
         }
     }
 
@@ -62,6 +83,14 @@ impl TabSet {
                     return scrollable::snap_to(id,scrollable::RelativeOffset::END);
                 }
             },
+            Message::DebuggerSent(_) | Message::DebugSpecChanged(_)
+            | Message::DebugActionSelected(_) => {
+                return self.debugger_tab.update(message);
+            }
+            Message::ImageZoomSelected(zoom) => self.images_tab.zoom(zoom),
+            Message::LogRecorded(record) => self.log_tab.push(record),
+            Message::LogLevelSelected(level) => self.log_tab.level = level,
+            Message::LogSearchChanged(search) => self.log_tab.search = search,
             _ => {},
         }
 
@@ -75,6 +104,10 @@ impl TabSet {
             .push(2, self.stdin_tab.tab_label(), self.stdin_tab.view())
             .push(3, self.images_tab.tab_label(), self.images_tab.view())
             .push(4, self.fileio_tab.tab_label(), self.fileio_tab.view())
+            .push(5, self.graph_tab.tab_label(), self.graph_tab.view())
+            .push(6, self.metrics_tab.tab_label(), self.metrics_tab.view())
+            .push(7, self.debugger_tab.tab_label(), self.debugger_tab.view())
+            .push(8, self.log_tab.tab_label(), self.log_tab.view())
             .set_active_tab(&self.active_tab)
             .tab_bar_style(TabBarStyles::Blue)
             .into()
@@ -86,6 +119,10 @@ impl TabSet {
         self.stdin_tab.clear();
         self.images_tab.clear();
         self.fileio_tab.clear();
+        self.graph_tab.clear();
+        self.metrics_tab.clear();
+        self.debugger_tab.clear();
+        self.log_tab.clear();
     }
 }
 
@@ -99,10 +136,58 @@ pub trait Tab {
     fn clear(&mut self);
 }
 
+/// A line of output shown in a tab, either plain text or spans of styled text
+pub(crate) enum Line {
+    Plain(String),
+    Styled(Vec<StyledText>),
+}
+
+impl From<String> for Line {
+    fn from(string: String) -> Self {
+        Line::Plain(string)
+    }
+}
+
+impl Line {
+    fn view(&self) -> Element<'_, Message> {
+        match self {
+            Line::Plain(string) => text(string).into(),
+            Line::Styled(spans) => Row::with_children(spans.iter().map(|span| {
+                let mut font = Font::DEFAULT;
+                if span.bold {
+                    font.weight = Weight::Bold;
+                }
+                if span.italic {
+                    font.style = Style::Italic;
+                }
+                let mut styled = text(&span.text).font(font);
+                if let Some(color) = span.color {
+                    styled = styled.style(iced_color(color));
+                }
+                Element::from(styled)
+            })).into(),
+        }
+    }
+}
+
+// The color to display text of the given `Color` in
+fn iced_color(color: Color) -> iced::Color {
+    match color {
+        Color::Black => iced::Color::BLACK,
+        Color::Red => iced::Color::from_rgb8(205, 49, 49),
+        Color::Green => iced::Color::from_rgb8(13, 188, 121),
+        Color::Yellow => iced::Color::from_rgb8(229, 229, 16),
+        Color::Blue => iced::Color::from_rgb8(36, 114, 200),
+        Color::Magenta => iced::Color::from_rgb8(188, 63, 188),
+        Color::Cyan => iced::Color::from_rgb8(17, 168, 205),
+        Color::White => iced::Color::from_rgb8(229, 229, 229),
+    }
+}
+
 pub(crate) struct StdOutTab {
     pub name: String,
     pub id: Id,
-    pub content: Vec<String>,
+    pub content: Vec<Line>,
     pub auto_scroll: bool,
 }
 
@@ -117,9 +202,7 @@ impl Tab for StdOutTab {
         let text_column = Column::with_children(
             self.content
                 .iter()
-                .cloned()
-                .map(text)
-                .map(Element::from),
+                .map(Line::view),
         )
             .width(Length::Fill)
             .padding(1);
@@ -145,9 +228,31 @@ impl Tab for StdOutTab {
     }
 }
 
+// The factor each step of zooming in or out of images changes their scale by
+const ZOOM_STEP: f32 = 1.25;
+// The smallest and largest scales that images can be zoomed to
+const MIN_SCALE: f32 = 0.1;
+const MAX_SCALE: f32 = 16.0;
+
+/// The changes to how images are scaled that the user can select in the Images tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageZoom {
+    /// Scale images to fit the space available
+    Fit,
+    /// Show images at their actual size
+    Actual,
+    /// Enlarge images by one step
+    In,
+    /// Shrink images by one step
+    Out,
+}
+
 pub(crate) struct ImageTab {
     name: String,
     pub images: HashMap<String, ImageReference>,
+    pub drawings: HashMap<String, Drawing>,
+    /// The scale images are shown at, or None to fit them to the space available
+    pub scale: Option<f32>,
 }
 
 impl ImageTab {
@@ -155,8 +260,54 @@ impl ImageTab {
         Self {
             name: name.to_owned(),
             images: HashMap::default(),
+            drawings: HashMap::default(),
+            scale: None,
         }
     }
+
+    /// Get the drawing called `name`, creating it with the size given if it doesn't exist yet
+    pub fn drawing(&mut self, name: String, width: u32, height: u32) -> &mut Drawing {
+        self.drawings.entry(name).or_insert_with(|| Drawing::new(width, height))
+    }
+
+    /// Change the scale images are shown at. Zooming in or out when they are fit to the space
+    /// available is relative to their actual size
+    pub fn zoom(&mut self, zoom: ImageZoom) {
+        self.scale = match zoom {
+            ImageZoom::Fit => None,
+            ImageZoom::Actual => Some(1.0),
+            ImageZoom::In => Some((self.scale.unwrap_or(1.0) * ZOOM_STEP).min(MAX_SCALE)),
+            ImageZoom::Out => Some((self.scale.unwrap_or(1.0) / ZOOM_STEP).max(MIN_SCALE)),
+        };
+    }
+
+    // The width and height to show an image of `width` by `height` pixels at the current scale
+    #[allow(clippy::cast_precision_loss)]
+    fn size(&self, width: u32, height: u32) -> (Length, Length) {
+        match self.scale {
+            Some(scale) => (Length::Fixed(width as f32 * scale),
+                            Length::Fixed(height as f32 * scale)),
+            None => (Length::Fill, Length::Fill),
+        }
+    }
+
+    fn controls(&self) -> Row<'_, Message> {
+        let zoom_button = |label, zoom| Button::new(label)
+            .on_press(Message::ImageZoomSelected(zoom));
+        let scale = match self.scale {
+            Some(scale) => format!("{:.0}%", scale * 100.0),
+            None => "Fit".into(),
+        };
+
+        Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(zoom_button("Fit", ImageZoom::Fit))
+            .push(zoom_button("1:1", ImageZoom::Actual))
+            .push(zoom_button("+", ImageZoom::In))
+            .push(zoom_button("-", ImageZoom::Out))
+            .push(text(scale))
+    }
 }
 
 impl Tab for ImageTab {
@@ -167,28 +318,412 @@ impl Tab for ImageTab {
     }
 
     fn view(&self) -> Element<'_, Self::Message> {
-        let mut col = Column::new();
+        let mut col = Column::new().spacing(10);
+
+        for (name, image_ref) in &self.images {
+            let (width, height) = self.size(image_ref.width, image_ref.height);
+            col = col
+                .push(Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(name))
+                    .push(Button::new("Save as PNG")
+                        .on_press(Message::SaveImage(name.clone()))))
+                .push(Image::new(Handle::from_pixels(image_ref.width, image_ref.height,
+                                                     image_ref.data.as_raw().clone()))
+                    .content_fit(ContentFit::Contain)
+                    .width(width)
+                    .height(height));
+        }
 
-        for image_ref in self.images.values() {
-            col = col.push(Viewer::new(
-                Handle::from_pixels( image_ref.width, image_ref.height,
-                                     image_ref.data.as_raw().clone())));
+        for drawing in self.drawings.values() {
+            let (width, height) = self.size(drawing.width, drawing.height);
+            col = col.push(Canvas::new(drawing)
+                .width(width)
+                .height(height));
         }
 
-        col.into()
+        // images can be scrolled in both directions when zoomed in
+        let images: Element<'_, Message> = if self.scale.is_some() {
+            Scrollable::new(col)
+                .direction(scrollable::Direction::Both {
+                    vertical: scrollable::Properties::default(),
+                    horizontal: scrollable::Properties::default(),
+                })
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into()
+        } else {
+            col.into()
+        };
+
+        Column::new()
+            .spacing(10)
+            .push(self.controls())
+            .push(images)
+            .into()
     }
 
     fn clear(&mut self) {
         self.images = HashMap::default();
+        self.drawings = HashMap::default();
+    }
+}
+
+pub(crate) struct GraphTab {
+    name: String,
+    pub graph: FlowGraph,
+}
+
+impl GraphTab {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            graph: FlowGraph::default(),
+        }
+    }
+}
+
+impl Tab for GraphTab {
+    type Message = Message;
+
+    fn tab_label(&self) -> TabLabel {
+        TabLabel::Text(self.name.to_string())
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        if self.graph.function_count() == 0 {
+            return text("The graph of functions is shown here when a flow is submitted").into();
+        }
+
+        Column::new()
+            .push(text(self.graph.summary()))
+            .push(Canvas::new(&self.graph)
+                .width(Length::Fill)
+                .height(Length::Fill))
+            .into()
+    }
+
+    // Avoid clearing the graph, it is replaced when a new flow is submitted
+    fn clear(&mut self) {}
+}
+
+pub(crate) struct MetricsTab {
+    name: String,
+    pub history: MetricsHistory,
+}
+
+impl MetricsTab {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            history: MetricsHistory::default(),
+        }
+    }
+}
+
+impl Tab for MetricsTab {
+    type Message = Message;
+
+    fn tab_label(&self) -> TabLabel {
+        TabLabel::Text(self.name.to_string())
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        if self.history.is_empty() {
+            return text("Charts of metrics are shown here while a flow is running").into();
+        }
+
+        let chart = |kind| Canvas::new(self.history.chart(kind))
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        Column::new()
+            .spacing(10)
+            .push(Row::new()
+                .spacing(10)
+                .push(chart(ChartKind::JobsPerSecond))
+                .push(chart(ChartKind::OutstandingJobs)))
+            .push(chart(ChartKind::FunctionExecutions))
+            .into()
+    }
+
+    fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+/// The actions on the debugger that the user can select in the Debugger tab
+#[derive(Debug, Clone)]
+pub enum DebugAction {
+    /// Set a breakpoint using the spec entered
+    Breakpoint,
+    /// Delete the breakpoint previously set with this spec
+    DeleteBreakpoint(String),
+    /// Inspect the overall state, or the function, input, output or block of the spec entered
+    Inspect,
+    /// Continue execution until the next breakpoint
+    Continue,
+    /// Execute the next job then break
+    Step,
+    /// List the breakpoints set in the debugger
+    List,
+    /// List all the functions of the flow
+    Functions,
+}
+
+pub(crate) struct DebuggerTab {
+    name: String,
+    id: Id,
+    sender: Option<Sender<DebugCommand>>,
+    waiting_at: Option<usize>,
+    output: Vec<String>,
+    breakpoints: Vec<String>,
+    spec: String,
+}
+
+impl DebuggerTab {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            id: Lazy::new(Id::unique).clone(),
+            sender: None,
+            waiting_at: None,
+            output: vec!(),
+            breakpoints: vec!(),
+            spec: String::new(),
+        }
+    }
+
+    /// Process a message from the debugger, or an action selected by the user
+    pub fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::DebuggerSent(DebugEvent::Connected(sender)) => {
+                self.sender = Some(sender);
+                self.output.push("Connected to the debugger".into());
+            }
+            Message::DebuggerSent(DebugEvent::Output(lines)) => {
+                self.output.extend(lines);
+                return scrollable::snap_to(self.id.clone(), scrollable::RelativeOffset::END);
+            }
+            Message::DebuggerSent(DebugEvent::WaitingForCommand(job_number)) => {
+                self.waiting_at = Some(job_number);
+            }
+            Message::DebuggerSent(DebugEvent::Disconnected(reason)) => {
+                self.sender = None;
+                self.waiting_at = None;
+                self.output.push(format!("Disconnected from the debugger: {reason}"));
+            }
+            Message::DebugSpecChanged(spec) => self.spec = spec,
+            Message::DebugActionSelected(action) => self.action(action),
+            _ => {}
+        }
+
+        Command::none()
+    }
+
+    // Send the debugger the command for the action selected by the user
+    fn action(&mut self, action: DebugAction) {
+        let command = match action {
+            DebugAction::Breakpoint => match parse_breakpoint_spec(&self.spec) {
+                Some(spec) => {
+                    self.breakpoints.push(self.spec.trim().to_string());
+                    DebugCommand::Breakpoint(Some(spec))
+                }
+                None => {
+                    self.output.push(format!("Invalid breakpoint spec '{}'", self.spec));
+                    return;
+                }
+            },
+            DebugAction::DeleteBreakpoint(spec) => {
+                self.breakpoints.retain(|breakpoint| breakpoint != &spec);
+                DebugCommand::Delete(parse_breakpoint_spec(&spec))
+            }
+            DebugAction::Inspect => match parse_inspect_spec(&self.spec) {
+                Some(command) => command,
+                None => {
+                    self.output.push(format!("Cannot inspect '{}'", self.spec));
+                    return;
+                }
+            },
+            DebugAction::Continue => DebugCommand::Continue,
+            DebugAction::Step => DebugCommand::Step(None),
+            DebugAction::List => DebugCommand::List,
+            DebugAction::Functions => DebugCommand::FunctionList,
+        };
+
+        if let Some(sender) = &self.sender {
+            if sender.try_send(command).is_ok() {
+                self.waiting_at = None;
+            }
+        }
+    }
+
+    // A button for an action, that can only be pressed when the debugger is waiting for a command
+    fn button(&self, label: &str, action: DebugAction) -> Button<'_, Message> {
+        let button = Button::new(text(label));
+        if self.waiting_at.is_some() {
+            button.on_press(Message::DebugActionSelected(action))
+        } else {
+            button
+        }
+    }
+}
+
+impl Tab for DebuggerTab {
+    type Message = Message;
+
+    fn tab_label(&self) -> TabLabel {
+        TabLabel::Text(self.name.to_string())
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        let spec = TextInput::new(
+            "Spec: function, function/output, function:input or blocked->blocking",
+            &self.spec)
+            .on_input(Message::DebugSpecChanged)
+            .on_paste(Message::DebugSpecChanged)
+            .width(Length::Fill);
+
+        let commands = Row::new()
+            .spacing(10)
+            .push(spec)
+            .push(self.button("Breakpoint", DebugAction::Breakpoint))
+            .push(self.button("Inspect", DebugAction::Inspect))
+            .push(self.button("Continue", DebugAction::Continue))
+            .push(self.button("Step", DebugAction::Step))
+            .push(self.button("List", DebugAction::List))
+            .push(self.button("Functions", DebugAction::Functions));
+
+        let status = match (&self.sender, self.waiting_at) {
+            (None, _) => "Not connected to the debugger, run flowrgui with '--debugger'".to_string(),
+            (Some(_), Some(job_number)) => format!("Waiting for a command at Job #{job_number}"),
+            (Some(_), None) => "Running".to_string(),
+        };
+
+        let output = Scrollable::new(Column::with_children(
+            self.output.iter().cloned().map(text).map(Element::from))
+            .width(Length::Fill)
+            .padding(1))
+            .height(Length::Fill)
+            .id(self.id.clone());
+
+        let breakpoints = self.breakpoints.iter().fold(
+            Column::new().spacing(5).push(text("Breakpoints")),
+            |column, breakpoint| column.push(Row::new()
+                .spacing(10)
+                .push(text(breakpoint))
+                .push(self.button("Delete",
+                                  DebugAction::DeleteBreakpoint(breakpoint.clone())))));
+
+        Column::new()
+            .spacing(10)
+            .push(commands)
+            .push(text(status))
+            .push(Row::new()
+                .spacing(10)
+                .push(Column::new().push(output).width(Length::FillPortion(3)))
+                .push(breakpoints.width(Length::FillPortion(1))))
+            .into()
+    }
+
+    // Breakpoints are kept, as the debugger keeps them between runs of a flow
+    fn clear(&mut self) {
+        self.output.clear();
+        self.waiting_at = None;
+    }
+}
+
+// The maximum number of log records kept in the Log tab, older ones are discarded
+const MAX_LOG_RECORDS: usize = 10_000;
+
+pub(crate) struct LogTab {
+    name: String,
+    pub records: VecDeque<LogRecord>,
+    /// The most detailed level of log records shown
+    pub level: LevelFilter,
+    /// Text that log records shown must contain, ignoring case
+    pub search: String,
+}
+
+impl LogTab {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            records: VecDeque::new(),
+            level: LevelFilter::Info,
+            search: String::new(),
+        }
+    }
+
+    /// Add a log record captured, discarding the oldest one if there are too many
+    pub fn push(&mut self, record: LogRecord) {
+        if self.records.len() == MAX_LOG_RECORDS {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    // The log records at the level selected or more severe, containing the search text
+    fn shown(&self) -> impl Iterator<Item = &LogRecord> {
+        let search = self.search.to_lowercase();
+        self.records.iter().filter(move |record| record.level <= self.level
+            && (search.is_empty() || record.to_string().to_lowercase().contains(&search)))
     }
 }
 
+impl Tab for LogTab {
+    type Message = Message;
+
+    fn tab_label(&self) -> TabLabel {
+        TabLabel::Text(self.name.to_string())
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        let filters = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(text("Level"))
+            .push(pick_list(LEVELS.as_slice(), Some(self.level), Message::LogLevelSelected))
+            .push(TextInput::new("Search", &self.search)
+                .on_input(Message::LogSearchChanged)
+                .on_paste(Message::LogSearchChanged)
+                .width(Length::Fill));
+
+        let records = Column::with_children(self.shown()
+            .map(|record| text(record).font(Font::MONOSPACE).into()))
+            .width(Length::Fill);
+
+        Column::new()
+            .spacing(10)
+            .push(filters)
+            .push(Scrollable::new(records).height(Length::Fill))
+            .into()
+    }
+
+    // Log records are kept between runs of flows, as the coordinator keeps running
+    fn clear(&mut self) {}
+}
+
+/// A request from a flow to read from standard input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StdinRequest {
+    /// Read the next line, with the prompt to show
+    Line(String),
+    /// Read all the standard input available
+    All,
+}
+
 pub(crate) struct StdInTab {
     pub name: String,
     pub id: Id,
     pub content: Vec<String>,
     pub cursor: usize,
     pub text: String,
+    /// The user has signalled the end of standard input
+    pub eof: bool,
+    /// A request to read standard input the flow is waiting for a response to
+    pub waiting: Option<StdinRequest>,
 }
 
 impl StdInTab {
@@ -199,9 +734,39 @@ impl StdInTab {
             content: vec!(),
             cursor: 0,
             text: String::new(),
+            eof: false,
+            waiting: None,
         }
     }
 
+    /// A flow has made a `request` to read standard input. Return the response to send to it,
+    /// or None if it has to wait for the user to enter more input or signal the end of it
+    pub fn request(&mut self, request: StdinRequest) -> Option<ClientMessage> {
+        self.waiting = Some(request);
+        self.respond()
+    }
+
+    /// Return the response to the request the flow is waiting for, if there is one and it can
+    /// be answered with the input entered so far
+    pub fn respond(&mut self) -> Option<ClientMessage> {
+        let response = match self.waiting.clone()? {
+            StdinRequest::Line(prompt) => self.get_line(&prompt).map(ClientMessage::Line)
+                .or_else(|| self.eof.then_some(ClientMessage::GetLineEof)),
+            StdinRequest::All => self.get_all().map(ClientMessage::Stdin)
+                .or_else(|| self.eof.then_some(ClientMessage::GetStdinEof)),
+        };
+
+        if response.is_some() {
+            self.waiting = None;
+        }
+        response
+    }
+
+    /// Add all the lines of `contents`, such as those of a file, to standard input
+    pub fn load(&mut self, contents: &str) {
+        self.content.extend(contents.lines().map(ToString::to_string));
+    }
+
     /// New text has been typed into the STDIN text box
     pub fn text_entered(&mut self, text: String) {
         self.text = text;
@@ -234,6 +799,7 @@ impl StdInTab {
             for line in self.cursor..self.content.len() {
                 if let Some(line) = self.content.get(line) {
                     buf.push_str(line);
+                    buf.push('\n');
                 }
             }
             self.cursor = self.content.len();
@@ -274,13 +840,111 @@ impl Tab for StdInTab {
             .height(Length::Fill)
             .id(self.id.clone());
 
+        let mut eof = Button::new("EOF");
+        if !self.eof {
+            eof = eof.on_press(Message::StdinEof);
+        }
+        let status = match (&self.waiting, self.eof) {
+            (_, true) => "End of input",
+            (Some(_), false) => "Flow is waiting for input",
+            (None, false) => "",
+        };
+        let controls = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(Button::new("Load file...").on_press(Message::LoadStdinFile))
+            .push(eof)
+            .push(text(status));
+
         Column::new()
+            .spacing(5)
             .push(scrollable)
             .push(text_input)
+            .push(controls)
             .into()
     }
 
     // Avoid clearing standard input - to allow the user to type in input ahead of the
-    // flow being run
-    fn clear(&mut self) {}
-}
\ No newline at end of file
+    // flow being run - but a new flow has not reached the end of it yet
+    fn clear(&mut self) {
+        self.eof = false;
+        self.waiting = None;
+    }
+}
+#[cfg(test)]
+mod test {
+    use crate::gui::client_message::ClientMessage;
+
+    use log::{Level, LevelFilter};
+
+    use crate::log_capture::LogRecord;
+
+    use super::{ImageTab, ImageZoom, LogTab, StdInTab, StdinRequest, MAX_SCALE};
+
+    #[test]
+    fn waits_for_line_until_entered() {
+        let mut stdin = StdInTab::new("Stdin");
+        assert!(stdin.request(StdinRequest::Line(String::new())).is_none());
+        assert!(stdin.respond().is_none());
+
+        stdin.new_line("hello".into());
+        assert!(matches!(stdin.respond(), Some(ClientMessage::Line(line)) if line == "hello"));
+        assert!(stdin.waiting.is_none());
+    }
+
+    #[test]
+    fn loaded_lines_then_eof() {
+        let mut stdin = StdInTab::new("Stdin");
+        stdin.load("one\ntwo\n");
+        assert!(matches!(stdin.request(StdinRequest::All),
+            Some(ClientMessage::Stdin(contents)) if contents == "one\ntwo\n"));
+
+        assert!(stdin.request(StdinRequest::All).is_none());
+        stdin.eof = true;
+        assert!(matches!(stdin.respond(), Some(ClientMessage::GetStdinEof)));
+        assert!(matches!(stdin.request(StdinRequest::Line(String::new())),
+            Some(ClientMessage::GetLineEof)));
+    }
+
+    #[test]
+    fn zoom_images() {
+        let mut images = ImageTab::new("Images");
+        assert!(images.scale.is_none());
+
+        images.zoom(ImageZoom::In);
+        assert_eq!(images.scale, Some(1.25));
+        images.zoom(ImageZoom::Out);
+        assert_eq!(images.scale, Some(1.0));
+
+        for _ in 0..20 {
+            images.zoom(ImageZoom::In);
+        }
+        assert_eq!(images.scale, Some(MAX_SCALE));
+
+        images.zoom(ImageZoom::Fit);
+        assert!(images.scale.is_none());
+        images.zoom(ImageZoom::Actual);
+        assert_eq!(images.scale, Some(1.0));
+    }
+
+    #[test]
+    fn filter_log() {
+        let record = |level, message: &str| LogRecord {
+            level,
+            target: "flowrlib::coordinator".into(),
+            message: message.into(),
+        };
+        let mut log = LogTab::new("Log");
+        log.push(record(Level::Error, "Could not load flow"));
+        log.push(record(Level::Info, "Flow loaded"));
+        log.push(record(Level::Debug, "Job #1 created"));
+
+        assert_eq!(log.shown().count(), 2);
+        log.level = LevelFilter::Debug;
+        assert_eq!(log.shown().count(), 3);
+        log.search = "LOAD".into();
+        assert_eq!(log.shown().count(), 2);
+        log.level = LevelFilter::Error;
+        assert_eq!(log.shown().count(), 1);
+    }
+}
@@ -1,3 +1,9 @@
+// The invariant checks are only made in debug builds, the deadlock diagnosis in all builds
+#![cfg_attr(not(debug_assertions), allow(dead_code))]
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
 use error_chain::bail;
 use log::error;
 
@@ -225,20 +231,137 @@ pub(crate) fn check_invariants(state: &RunState, job_id: usize) -> Result<()> {
     //flow_checks(state, job_id)
 }
 
+// Describe a function by its id, and its route when that is known
+fn describe_function(state: &RunState, function_id: usize) -> String {
+    #[cfg(feature = "debugger")]
+    if let Some(function) = state.get_function(function_id) {
+        return format!("#{function_id} '{}'", function.route());
+    }
+    #[cfg(not(feature = "debugger"))]
+    let _ = state;
+
+    format!("#{function_id}")
+}
+
+// Search for a path from `function_id` back to `start_id` through functions that prevent each
+// other from running, either as they have a full input ("->") or they are the only sender to an
+// empty input ("<-"), adding each step to `path` and returning true if one is found
+fn find_cycle(state: &RunState, start_id: usize, function_id: usize, visited: &mut HashSet<usize>,
+              path: &mut Vec<(&'static str, usize)>) -> Result<bool> {
+    visited.insert(function_id);
+    let output_blockers = state.get_output_blockers(function_id).into_iter()
+        .map(|id| ("->", id));
+    let input_blockers = state.get_input_blockers(function_id)?.into_iter()
+        .map(|id| ("<-", id));
+
+    for (direction, blocker_id) in output_blockers.chain(input_blockers) {
+        path.push((direction, blocker_id));
+        if blocker_id == start_id ||
+            (!visited.contains(&blocker_id) &&
+                find_cycle(state, start_id, blocker_id, visited, path)?) {
+            return Ok(true);
+        }
+        path.pop();
+    }
+
+    Ok(false)
+}
+
+/// Diagnose a deadlock in the execution of a flow, where no jobs are running or ready to run, but
+/// functions are still blocked from sending their outputs to inputs that are full. Returns `None`
+/// if there is no deadlock, or a description of the outstanding blocks, any cycles of functions
+/// blocking each other and the empty inputs that prevent the blocking functions from running.
+///
+/// Unlike [`check_invariants`] this is done in release builds also.
+pub(crate) fn deadlock_diagnosis(state: &RunState) -> Result<Option<String>> {
+    if state.number_jobs_running() > 0 || state.number_jobs_ready() > 0 ||
+        state.get_blocks().is_empty() || state.aborted().is_some() || state.cancelled() {
+        return Ok(None);
+    }
+
+    let mut blocks: Vec<&Block> = state.get_blocks().iter().collect();
+    blocks.sort_by_key(|block| (block.blocked_function_id, block.blocking_function_id,
+                                block.blocking_io_number));
+
+    let mut diagnosis = format!("Deadlock: no jobs are running or ready to run, \
+                                 but {} block(s) remain:\n", blocks.len());
+    for block in &blocks {
+        let _ = writeln!(diagnosis, "    Function {} cannot send to full input :{} of function {}",
+                         describe_function(state, block.blocked_function_id),
+                         block.blocking_io_number,
+                         describe_function(state, block.blocking_function_id));
+    }
+
+    let mut cycle_sets: Vec<Vec<usize>> = vec![];
+    let mut blocked: Vec<usize> = blocks.iter().map(|block| block.blocked_function_id).collect();
+    blocked.dedup();
+    for blocked_id in blocked {
+        let mut path = vec![];
+        if find_cycle(state, blocked_id, blocked_id, &mut HashSet::new(), &mut path)? {
+            // the same cycle is found starting from each function in it, so report it once
+            let mut cycle_set: Vec<usize> = path.iter().map(|(_, id)| *id).collect();
+            cycle_set.sort_unstable();
+            if !cycle_sets.contains(&cycle_set) {
+                if cycle_sets.is_empty() {
+                    diagnosis.push_str("Cycles of functions preventing each other from running:\n");
+                }
+                let _ = write!(diagnosis, "    {}", describe_function(state, blocked_id));
+                for (direction, id) in &path {
+                    let _ = write!(diagnosis, " {direction} {}", describe_function(state, *id));
+                }
+                diagnosis.push('\n');
+                cycle_sets.push(cycle_set);
+            }
+        }
+    }
+
+    let mut blocking: Vec<usize> = blocks.iter().map(|block| block.blocking_function_id).collect();
+    blocking.sort_unstable();
+    blocking.dedup();
+    let mut starved = String::new();
+    for blocking_id in blocking {
+        let Some(function) = state.get_function(blocking_id) else { continue };
+        for (io_number, input) in function.inputs().iter().enumerate() {
+            if input.values_available() == 0 {
+                let senders: Vec<String> = state.get_functions().iter()
+                    .filter(|sender| sender.get_output_connections().iter()
+                        .any(|connection| connection.destination_id == blocking_id &&
+                            connection.destination_io_number == io_number))
+                    .map(|sender| describe_function(state, sender.id()))
+                    .collect();
+                let _ = writeln!(starved, "    Input :{io_number} of function {} is empty, {}",
+                                 describe_function(state, blocking_id),
+                                 if senders.is_empty() {
+                                     "and no function sends to it".to_string()
+                                 } else {
+                                     format!("waiting for a value from {}", senders.join(", "))
+                                 });
+            }
+        }
+    }
+    if !starved.is_empty() {
+        diagnosis.push_str("Empty inputs preventing blocking functions from running:\n");
+        diagnosis.push_str(&starved);
+    }
+
+    Ok(Some(diagnosis))
+}
+
 #[cfg(test)]
 mod test {
+    use serde_json::json;
     #[cfg(feature = "debugger")]
     use serde_json::Value;
 
     #[cfg(feature = "debugger")]
     use flowcore::errors::Result;
     use flowcore::model::flow_manifest::FlowManifest;
-    #[cfg(feature = "debugger")]
     use flowcore::model::input::Input;
     use flowcore::model::metadata::MetaData;
-    #[cfg(feature = "debugger")]
-    use flowcore::model::output_connection::OutputConnection;
+    use flowcore::model::output_connection::{OutputConnection, Source};
     use flowcore::model::runtime_function::RuntimeFunction;
+    #[cfg(feature = "debugger")]
+    use flowcore::model::source_location::SourceLocation;
     use flowcore::model::submission::Submission;
 
     #[cfg(feature = "debugger")]
@@ -255,6 +378,7 @@ mod test {
     use crate::run_state::{RunState, State};
 
     use super::blocked_check;
+    use super::deadlock_diagnosis;
     use super::ready_check;
     use super::running_check;
 
@@ -280,6 +404,8 @@ mod test {
             test_manifest(functions),
             None,
             None,
+            None,
+            None,
             #[cfg(feature = "debugger")]
                 true,
         )
@@ -351,9 +477,11 @@ mod test {
         fn block_breakpoint(&mut self, _block: &Block) {}
         fn flow_unblock_breakpoint(&mut self, _flow_id: usize) {}
         fn send_breakpoint(&mut self, _: &str, _source_process_id: usize, _output_route: &str, _value: &Value,
-                           _destination_id: usize, _destination_name:&str, _input_name: &str, _input_number: usize) {}
-        fn job_error(&mut self, _job: &Job) {}
+                           _destination_id: usize, _destination_name:&str, _input_name: &str, _input_number: usize,
+                           _source_location: Option<&SourceLocation>) {}
+        fn job_error(&mut self, _job: &Job, _source_location: Option<&SourceLocation>) {}
         fn job_completed(&mut self, _job: &Job) {}
+        fn watch_value(&mut self, _job: &Job, _function_name: &str, _output_route: &str, _value: &Value) {}
         fn blocks(&mut self, _blocks: Vec<Block>) {}
         fn outputs(&mut self, _output: Vec<OutputConnection>) {}
         fn input(&mut self, _input: Input) {}
@@ -442,4 +570,63 @@ mod test {
                                     .ok_or("No function").expect("No function"), &functions_states)
             .is_err());
     }
-}
\ No newline at end of file
+
+    // Function #0 sends to both inputs of function #1, whose first input is full
+    fn deadlocked_state() -> RunState {
+        let connections: Vec<OutputConnection> = (0..2).map(|io_number|
+            OutputConnection::new(Source::default(), 1, io_number, 0, "/b".to_string(),
+                                  #[cfg(feature = "debugger")] String::default())
+        ).collect();
+        let sender = RuntimeFunction::new(
+            #[cfg(feature = "debugger")] "a",
+            #[cfg(feature = "debugger")] "/a",
+            "file://fake/test",
+            vec![],
+            0,
+            0,
+            &connections,
+            true,
+        );
+        let mut receiver = RuntimeFunction::new(
+            #[cfg(feature = "debugger")] "b",
+            #[cfg(feature = "debugger")] "/b",
+            "file://fake/test",
+            vec![Input::new(#[cfg(feature = "debugger")] "", 0, false, None, None),
+                 Input::new(#[cfg(feature = "debugger")] "", 0, false, None, None)],
+            1,
+            0,
+            &[],
+            false,
+        );
+        receiver.send(0, json!(1)).expect("Could not send to input");
+        test_state(vec![sender, receiver])
+    }
+
+    #[test]
+    fn no_deadlock_without_blocks() {
+        let state = deadlocked_state();
+        assert!(deadlock_diagnosis(&state).expect("Could not diagnose").is_none());
+    }
+
+    #[test]
+    fn deadlock_diagnosed() {
+        let mut state = deadlocked_state();
+
+        #[cfg(feature = "debugger")]
+            let mut server = DummyServer{};
+        #[cfg(feature = "debugger")]
+            let mut debugger = dummy_debugger(&mut server);
+
+        // function #0 is blocked sending to the full input :0 of function #1
+        let _ = state.create_block(0, 1, 0, 0, 0,
+                                   #[cfg(feature = "debugger")] &mut debugger);
+
+        let diagnosis = deadlock_diagnosis(&state).expect("Could not diagnose")
+            .expect("Deadlock was not detected");
+        assert!(diagnosis.contains("1 block(s) remain"));
+        assert!(diagnosis.contains("Cycles of functions preventing each other from running"));
+        assert!(diagnosis.contains("Input :1 of function #1"));
+        assert!(diagnosis.contains("waiting for a value from #0"));
+        assert!(!diagnosis.contains("Input :0 of function #1"));
+    }
+}
@@ -1,24 +1,105 @@
-#[cfg(all(not(feature = "debugger"), not(feature = "submission")))]
+#[cfg(not(feature = "submission"))]
 use std::marker::PhantomData;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "debugger")]
+use log::warn;
 use log::{debug, error, info, trace};
-use serde_json::Value;
+use tracing::{info_span, Span};
 
 use flowcore::errors::Result;
 #[cfg(feature = "metrics")]
 use flowcore::model::metrics::Metrics;
 use flowcore::model::submission::Submission;
-use flowcore::RunAgain;
 
 #[cfg(feature = "debugger")]
 use crate::debugger::Debugger;
 #[cfg(feature = "debugger")]
 use crate::debugger_handler::DebuggerHandler;
-use crate::dispatcher::Dispatcher;
+use crate::checks;
+use crate::dispatcher::{Dispatcher, ExecutorMessage};
+use crate::event_sink::EventSink;
 use crate::job::Job;
-use crate::run_state::RunState;
+use crate::run_state::{RunState, TimedOutJob};
+#[cfg(feature = "submission")]
+use crate::submission_handler::{SubmissionHandler, SubmissionListener};
+
+/// The maximum time to wait for results from executors, before checking for new submissions and
+/// requests from the submitters of the flows being executed
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The interval between the snapshots of the metrics of a flow sent to the submitter of it
+#[cfg(all(feature = "submission", feature = "metrics"))]
+const METRICS_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+// The `SubmissionHandler` the execution of a flow is reported to, or `None` if it is reported to
+// the coordinator's own one
+#[cfg(feature = "submission")]
+type FlowHandler = Option<Box<dyn SubmissionHandler>>;
+#[cfg(not(feature = "submission"))]
+type FlowHandler = ();
+
+// A flow being executed by the `Coordinator`, possibly concurrently with others
+struct ActiveFlow {
+    state: RunState,
+    handler: FlowHandler,
+    #[cfg(feature = "metrics")]
+    metrics: Metrics,
+    last_checkpoint: Instant,
+    #[cfg(all(feature = "submission", feature = "metrics"))]
+    last_metrics_snapshot: Instant,
+    span: Span,
+}
+
+impl ActiveFlow {
+    // Return true if the flow is being debugged, which is only possible while it is the only
+    // flow being executed
+    #[cfg(feature = "debugger")]
+    fn debugged(&self) -> bool {
+        self.state.submission.debug_enabled
+    }
+
+    #[cfg(not(feature = "debugger"))]
+    fn debugged(&self) -> bool {
+        false
+    }
+
+    // Return true if execution of the flow has ended, as it has no jobs running and none ready
+    // to run, or it was aborted
+    fn ended(&self) -> bool {
+        self.state.number_jobs_running() == 0 &&
+            (self.state.number_jobs_ready() == 0 || self.state.aborted().is_some())
+    }
+}
+
+// Where the submissions of the flows executed by `Coordinator::run()` come from
+enum Submissions<'l> {
+    // A single submission, whose execution is reported to the coordinator's own
+    // `SubmissionHandler`, and the result of its execution once it has ended
+    Single(Option<Box<Submission>>, Result<()>),
+    // Submissions received one at a time by the coordinator's own `SubmissionHandler`, from a
+    // client that executes the context functions of the flow it submitted. Unless `loop_forever`
+    // is set, no more are received after the first one
+    #[cfg(feature = "submission")]
+    Received { loop_forever: bool },
+    // Submissions accepted by a `SubmissionListener`, each with its own `SubmissionHandler`
+    #[cfg(feature = "submission")]
+    Listener(&'l mut dyn SubmissionListener),
+    #[cfg(not(feature = "submission"))]
+    _Listener(PhantomData<&'l ()>),
+}
+
+// The `SubmissionHandler` to report the execution of a flow to: the one it was submitted with,
+// or else the coordinator's `own` one
 #[cfg(feature = "submission")]
-use crate::submission_handler::SubmissionHandler;
+fn handler<'h>(own: &'h mut dyn SubmissionHandler, flow_handler: &'h mut FlowHandler)
+    -> &'h mut dyn SubmissionHandler {
+    match flow_handler {
+        Some(handler) => handler.as_mut(),
+        None => own,
+    }
+}
 
 /// The `Coordinator` coordinates the dispatching of jobs for flow execution.
 ///
@@ -34,6 +115,14 @@ pub struct Coordinator<'a> {
     submission_handler: &'a mut dyn SubmissionHandler,
     /// Dispatcher to dispatch jobs for execution
     dispatcher: Dispatcher,
+    /// The tracing `Span`s of jobs dispatched that are running, by submission id and job id,
+    /// that end when they are retired
+    job_spans: HashMap<(usize, usize), Span>,
+    /// An optional `EventSink` to send the events that happen during execution to
+    event_sink: Option<Box<dyn EventSink>>,
+    /// The id given to the last submission executed, so the results of jobs for it can be
+    /// told apart from those of jobs of other (e.g. previously cancelled) submissions
+    submission_id: usize,
     #[cfg(feature = "debugger")]
     /// A `Debugger` to communicate with debug clients
     debugger: Debugger<'a>,
@@ -52,6 +141,9 @@ impl<'a> Coordinator<'a> {
             #[cfg(feature = "submission")]
             submission_handler: submitter,
             dispatcher,
+            job_spans: HashMap::new(),
+            event_sink: None,
+            submission_id: 0,
             #[cfg(feature = "debugger")]
             debugger: Debugger::new(debug_server),
             #[cfg(all(not(feature = "debugger"), not(feature = "submission")))]
@@ -59,7 +151,24 @@ impl<'a> Coordinator<'a> {
         }
     }
 
-    /// Enter a loop - waiting for a submission from the client, or disconnection of the client
+    /// Send the [Events][crate::event_sink::Event] that happen during the execution of flows
+    /// (jobs dispatched and completed, values sent, blocks created and removed) to `event_sink`
+    pub fn set_event_sink(&mut self, event_sink: Box<dyn EventSink>) {
+        self.event_sink = Some(event_sink);
+    }
+
+    /// Ask the executors getting jobs from this coordinator, including those of any `flowrex`
+    /// processes, to finish executing the jobs they have received and exit
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be sent to the executors
+    pub fn shutdown_executors(&mut self) -> Result<()> {
+        self.dispatcher.send_shutdown()
+    }
+
+    /// Enter a loop - waiting for a submission from the client, or disconnection of the client,
+    /// and executing each flow submitted before waiting for the next one
     ///
     /// # Errors
     ///
@@ -71,247 +180,899 @@ impl<'a> Coordinator<'a> {
         &mut self,
         loop_forever: bool,
     ) -> Result<()> {
-        while let Some(submission) = self.submission_handler.wait_for_submission()? {
-            let _ = self.execute_flow(submission);
-            if !loop_forever {
-                break;
-            }
-        }
+        self.run(&mut Submissions::Received { loop_forever })?;
 
         self.submission_handler.coordinator_is_exiting(Ok(()))
     }
 
-    //noinspection RsReassignImmutable
-    /// Execute a flow by looping while there are jobs to be processed in an inner loop.
-    /// There is an outer loop for the case when you are using the debugger, to allow entering
-    /// the debugger when the flow ends and at any point resetting all the state and starting
-    /// execution again from the initial state
+    /// Serve the submissions accepted by `listener`, executing the flows submitted concurrently
+    /// until it will not accept any more. Each flow has its own `RunState`, with its own job ids,
+    /// and its execution is reported to the `SubmissionHandler` it was accepted with, so that one
+    /// coordinator (and the executors connected to it) can serve many clients at once.
     ///
-    /// # Errors
+    /// A flow can only be debugged if it is the only one being executed when it is accepted, and
+    /// no more submissions are accepted while it is being debugged. The jobs of each flow are
+    /// timed out using the job timeout of their own submission, if it has one.
     ///
-    /// Returns an error if the execution of the flow did not complete normally.
+    /// # Errors
     ///
-    #[allow(unused_variables, unused_assignments, unused_mut)]
-    pub fn execute_flow(&mut self,
-                        submission: Submission, ) -> Result<()> {
-        self.dispatcher.set_results_timeout(submission.job_timeout)?;
-        let mut state = RunState::new(submission);
-
-        #[cfg(feature = "metrics")]
-            let mut metrics = Metrics::new(state.num_functions());
+    /// Returns an error if there was some issue accepting submissions, or sending jobs to and
+    /// receiving results from executors
+    #[cfg(feature = "submission")]
+    pub fn serve(&mut self, listener: &mut dyn SubmissionListener) -> Result<()> {
+        self.run(&mut Submissions::Listener(listener))
+    }
 
-        #[cfg(feature = "debugger")]
-        if state.submission.debug_enabled {
-            self.debugger.start();
+    /// Execute a flow, reporting its execution to the coordinator's own `SubmissionHandler`.
+    /// When using the debugger, it is entered when the flow ends, and at any point all the state
+    /// can be reset and execution started again from the initial state
+    ///
+    /// If the `Submission` has a `CheckpointPolicy` then checkpoints of the state of execution
+    /// are written periodically, and execution may be resumed from a previous checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the execution of the flow did not complete normally, including if it
+    /// was aborted due to a job failing after all the retries allowed by its `RetryPolicy`, or
+    /// if a checkpoint could not be written or resumed from.
+    ///
+    pub fn execute_flow(&mut self, submission: Submission) -> Result<()> {
+        let mut submissions = Submissions::Single(Some(Box::new(submission)), Ok(()));
+        self.run(&mut submissions)?;
+        match submissions {
+            Submissions::Single(_, result) => result,
+            _ => Ok(()),
         }
+    }
 
-        let mut restart = false;
-        let mut display_next_output = false;
+    // Execute the flows submitted from `submissions` concurrently, until no more will be
+    // submitted and all of those submitted have ended. Each flow has its own `RunState`, with
+    // its own job ids, and the results returned by executors are delivered to the flow they are for
+    #[allow(unused_variables)]
+    fn run(&mut self, submissions: &mut Submissions) -> Result<()> {
+        let mut flows: Vec<ActiveFlow> = vec![];
+        // jobs whose results are not received within the job timeout are handled by the
+        // coordinator, according to the submission's `JobTimeoutPolicy`
+        self.dispatcher.set_results_timeout(None)?;
 
-        // This outer loop is just a way of restarting execution from scratch if the debugger requests it
-        'flow_execution:
         loop {
-            state.init()?;
-            #[cfg(feature = "metrics")]
-            metrics.reset();
+            // block waiting for a submission only when there are no flows being executed, and
+            // accept none while a flow is being debugged
+            while !flows.iter().any(ActiveFlow::debugged) {
+                let Some((submission, handler)) = self.next_submission(submissions,
+                                                                       flows.is_empty())? else {
+                    break;
+                };
+                if let Some(flow) = self.start_flow(submissions, submission, handler,
+                                                    flows.is_empty())? {
+                    flows.push(flow);
+                }
+            }
 
-            // If debugging - then prior to starting execution - enter the debugger
-            #[cfg(feature = "debugger")]
-            if state.submission.debug_enabled {
-                (display_next_output, restart) = self.debugger.wait_for_command(&mut state)?;
+            if flows.is_empty() {
+                break;
             }
 
-            #[cfg(feature = "submission")]
-            self.submission_handler.flow_execution_starting()?;
+            for flow in &mut flows {
+                self.step(flow)?;
+            }
+            // send any jobs still waiting to be sent to executors in a batch
+            self.dispatcher.flush()?;
+
+            // don't wait for results if there are jobs ready to be dispatched, nor beyond the
+            // time when a job waiting to be retried can be dispatched, or a running job times out
+            let timeout = if flows.iter().any(|flow| flow.state.number_jobs_due() > 0) {
+                Duration::ZERO
+            } else {
+                flows.iter()
+                    .flat_map(|flow| [flow.state.retry_delay(), flow.state.job_timeout_delay()])
+                    .flatten()
+                    .fold(POLL_INTERVAL, Duration::min)
+            };
+            let received = match self.dispatcher.wait_for_result(timeout) {
+                Ok(received) => received,
+                Err(e) => {
+                    error!("\t{e}");
+                    // enter the debugger on the error, if a flow is being debugged
+                    #[cfg(feature = "debugger")]
+                    if let Some(flow) = flows.iter_mut().find(|flow| flow.debugged()) {
+                        let (_, restart) = self.debugger.error(&mut flow.state, e.to_string())?;
+                        if restart {
+                            self.restart_flow(flow)?;
+                        }
+                    }
+                    false
+                },
+            };
+            if received {
+                while let Ok(message) = self.dispatcher.get_next_result(false) {
+                    self.route_message(&mut flows, message)?;
+                }
+            }
+            if let Some(executor_metrics) = self.dispatcher.updated_executor_metrics() {
+                for flow in &mut flows {
+                    flow.state.set_executor_metrics(executor_metrics.clone());
+                }
+            }
+            for lost in self.dispatcher.take_lost_executors() {
+                for flow in &mut flows {
+                    #[cfg(feature = "metrics")]
+                    flow.metrics.increment_executors_lost();
+                    #[cfg(feature = "debugger")]
+                    if flow.debugged() {
+                        self.debugger.executor_lost(&lost.executor_id, lost.jobs_requeued);
+                    }
+                }
+            }
 
-            'jobs: loop {
-                trace!("{}", state);
+            let mut index = 0;
+            while let Some(flow) = flows.get_mut(index) {
+                if !flow.ended() {
+                    index += 1;
+                    continue;
+                }
+                // If debugging then enter the debugger for a final time before ending flow
+                // execution, as it may be reset and executed again
                 #[cfg(feature = "debugger")]
-                if state.submission.debug_enabled && self.submission_handler.should_enter_debugger()? {
-                    (display_next_output, restart) = self.debugger.wait_for_command(&mut state)?;
+                if flow.debugged() && !flow.state.cancelled() {
+                    let (_, restart) = self.debugger.execution_ended(&mut flow.state)?;
                     if restart {
-                        break 'jobs;
+                        self.restart_flow(flow)?;
+                        index += 1;
+                        continue;
                     }
                 }
+                let flow = flows.remove(index);
+                self.end_flow(submissions, flow)?;
+            }
 
-                (display_next_output, restart) = self.dispatch_jobs(
-                    &mut state,
-                    #[cfg(feature = "metrics")]
-                        &mut metrics,
-                )?;
-
-                if restart {
-                    break 'jobs;
+            for flow in &mut flows {
+                Self::checkpoint(&flow.state, &mut flow.last_checkpoint)?;
+                #[cfg(all(feature = "submission", feature = "metrics"))]
+                if let Err(e) = Self::send_metrics_snapshot(
+                    handler(self.submission_handler, &mut flow.handler), &flow.state,
+                    &flow.metrics, &mut flow.last_metrics_snapshot) {
+                    error!("Could not send metrics to client of submission #{}: {e}",
+                        flow.state.submission_id());
                 }
+            }
+        }
 
-                (display_next_output, restart) = self.retire_jobs(
-                    &mut state,
-                    #[cfg(feature = "metrics")]
-                        &mut metrics,
-                )?;
+        Ok(())
+    }
 
-                if restart {
-                    break 'jobs;
+    // Get the next submission of a flow to execute from `submissions`, and the handler to report
+    // its execution to, blocking until there is one only if `wait` is set. Returns `None` if there
+    // is none waiting, or if `wait` is set and no more will be submitted
+    #[allow(unused_variables)]
+    fn next_submission(&mut self, submissions: &mut Submissions, wait: bool)
+        -> Result<Option<(Submission, FlowHandler)>> {
+        match submissions {
+            Submissions::Single(submission, _) =>
+                Ok(submission.take().map(|submission| (*submission, FlowHandler::default()))),
+            #[cfg(feature = "submission")]
+            Submissions::Received { loop_forever } => {
+                // the client executes the context functions of the flow it submitted, so wait
+                // for it to end before receiving the next submission
+                if !wait {
+                    return Ok(None);
                 }
-
-                if state.number_jobs_running() == 0 && state.number_jobs_ready() == 0 {
-                    // execution is done - but not returning here allows us to go into debugger
-                    // at the end of execution, inspect state and possibly reset and rerun
-                    break 'jobs;
+                let loop_forever = *loop_forever;
+                let submission = self.submission_handler.wait_for_submission()?;
+                if !loop_forever {
+                    *submissions = Submissions::Single(None, Ok(()));
                 }
-            } // 'jobs loop end
+                Ok(submission.map(|submission| (submission, None)))
+            },
+            #[cfg(feature = "submission")]
+            Submissions::Listener(listener) => Ok(listener.accept(wait)?
+                .map(|(submission, handler)| (submission, Some(handler)))),
+            #[cfg(not(feature = "submission"))]
+            Submissions::_Listener(_) => Ok(None),
+        }
+    }
 
-            // flow execution has ended
-            #[allow(clippy::collapsible_if)]
-            #[cfg(feature = "debugger")]
-            if !restart {
-                {
-                    // If debugging then enter the debugger for a final time before ending flow execution
-                    if state.submission.debug_enabled {
-                        (display_next_output, restart) = self.debugger.execution_ended(&mut state)?;
+    // Start executing `submission`, reporting its execution to `handler`. It can only be debugged
+    // if it will be the `only` flow being executed. Returns `None` if it could not be started
+    #[allow(unused_mut)]
+    fn start_flow(&mut self, submissions: &mut Submissions, mut submission: Submission,
+                  mut handler: FlowHandler, only: bool) -> Result<Option<ActiveFlow>> {
+        self.submission_id += 1;
+        let submission_id = self.submission_id;
+        if only {
+            self.dispatcher.send_clear()?;
+            self.dispatcher.set_batch_size(submission.batch_size);
+        }
+
+        #[cfg(feature = "debugger")]
+        if submission.debug_enabled && !only {
+            warn!("Submission #{submission_id} will not be debugged, as debugging is not supported \
+            while other submissions are being executed");
+            submission.debug_enabled = false;
+        }
+
+        let state = match self.initial_state(submission_id, submission) {
+            Ok(state) => state,
+            Err(e) => {
+                error!("Could not start execution of submission #{submission_id}: {e}");
+                Self::report_result(submissions, &mut handler, submission_id, Err(e));
+                return Ok(None);
+            },
+        };
+
+        let span = info_span!("submission", submission_id,
+            flow = %state.submission.manifest.get_metadata().name);
+        let mut flow = ActiveFlow {
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::new(state.num_functions()),
+            state,
+            handler,
+            last_checkpoint: Instant::now(),
+            #[cfg(all(feature = "submission", feature = "metrics"))]
+            last_metrics_snapshot: Instant::now(),
+            span,
+        };
+
+        #[cfg(feature = "debugger")]
+        if flow.debugged() {
+            self.debugger.start();
+        }
+        if let Err(e) = self.begin_flow(&mut flow) {
+            error!("Could not start execution of submission #{submission_id}: {e}");
+            Self::report_result(submissions, &mut flow.handler, submission_id, Err(e));
+            return Ok(None);
+        }
+        info!("Started execution of submission #{submission_id}");
+
+        Ok(Some(flow))
+    }
+
+    // Create the `RunState` to execute a submission with `submission_id`, resuming from a
+    // checkpoint if the submission's `CheckpointPolicy` requests it
+    fn initial_state(&self, submission_id: usize, submission: Submission) -> Result<RunState> {
+        let mut state = match submission.checkpoint_policy.clone() {
+            Some(policy) if policy.resume => {
+                let mut state = RunState::from_checkpoint(&policy.path, submission)?;
+                state.set_submission_id(submission_id);
+                state
+            },
+            _ => {
+                let mut state = RunState::new(submission);
+                state.set_submission_id(submission_id);
+                state.init()?;
+                state
+            },
+        };
+        if self.event_sink.is_some() {
+            state.record_events();
+        }
+
+        Ok(state)
+    }
+
+    // Begin (or begin again, after it was reset) the execution of a flow whose state has been
+    // initialized, entering the debugger first if it is being debugged
+    #[allow(unused_variables)]
+    fn begin_flow(&mut self, flow: &mut ActiveFlow) -> Result<()> {
+        #[cfg(feature = "debugger")]
+        if flow.debugged() {
+            self.debugger.wait_for_command(&mut flow.state)?;
+        }
+
+        #[cfg(feature = "submission")]
+        handler(self.submission_handler, &mut flow.handler).flow_execution_starting()?;
+
+        Ok(())
+    }
+
+    // Check if the submitter of a flow has requested that its execution be cancelled, or that
+    // the debugger be entered, enforce the limits of its submission, and then dispatch the jobs
+    // of it that are ready and apply the `JobTimeoutPolicy` to those that have timed out
+    #[allow(unused_variables)]
+    fn step(&mut self, flow: &mut ActiveFlow) -> Result<()> {
+        trace!("{}", flow.state);
+        let submission_id = flow.state.submission_id();
+
+        #[cfg(feature = "submission")]
+        match handler(self.submission_handler, &mut flow.handler).should_cancel() {
+            Ok(true) => self.cancel(&mut flow.state)?,
+            Ok(false) => {},
+            Err(e) => error!("Could not check for cancellation of submission #{submission_id}: {e}"),
+        }
+        self.enforce_limits(&mut flow.state)?;
+
+        #[cfg(all(feature = "submission", feature = "debugger"))]
+        if flow.debugged() && !flow.state.cancelled() {
+            match handler(self.submission_handler, &mut flow.handler).should_enter_debugger() {
+                Ok(true) => {
+                    let (_, restart) = self.debugger.wait_for_command(&mut flow.state)?;
+                    if restart {
+                        return self.restart_flow(flow);
                     }
-                }
+                },
+                Ok(false) => {},
+                Err(e) => error!("Could not check for debugger entry for submission #{submission_id}: {e}"),
+            }
+        }
+
+        while let Some(job) = flow.state.get_next_job() {
+            if self.dispatch_a_job(job, flow)? {
+                return self.restart_flow(flow);
+            }
+        }
+        self.check_job_timeouts(&mut flow.state)?;
+        self.send_events(&mut flow.state)
+    }
+
+    // Dispatch a job of a flow for execution, returning true if the debugger requested that
+    // execution of the flow be reset and started again
+    fn dispatch_a_job(&mut self, job: Job, flow: &mut ActiveFlow) -> Result<bool> {
+        #[cfg(feature = "debugger")]
+        if flow.debugged() {
+            let (_, restart) = self.debugger.check_prior_to_job(&mut flow.state, &job)?;
+            // If the state was stepped back prior to an earlier job this job is not dispatched,
+            // as it will be created again
+            if self.debugger.take_rewound() {
+                self.rewind(&mut flow.state)?;
+                return Ok(false);
+            }
+            if restart {
+                return Ok(true);
             }
+        }
 
-            // if no debugger then end execution always
-            // if a debugger - then end execution if the debugger has not requested a restart
-            if !restart {
-                break 'flow_execution;
+        // The span of the job is a child of the submission's span, and lasts until it is retired
+        let span = info_span!(parent: &flow.span, "job", job_id = job.payload.job_id,
+            function_id = job.function_id, flow_id = job.flow_id,
+            input_sizes = ?job.payload.input_sizes(), success = tracing::field::Empty);
+        if let Err(e) = span.in_scope(|| self.dispatcher.send_job_for_execution(&job.payload)) {
+            #[cfg(feature = "debugger")]
+            if flow.debugged() {
+                error!("Could not send job for execution: {e}");
+                let (_, restart) = self.debugger.job_error(&mut flow.state, &job)?;
+                return Ok(restart);
             }
+            return Err(e);
+        }
+        let submission_id = flow.state.submission_id();
+        self.job_spans.insert((submission_id, job.payload.job_id), span);
+
+        #[cfg(feature = "submission")]
+        if let Err(e) = handler(self.submission_handler, &mut flow.handler).job_dispatched(&job) {
+            error!("Could not report job dispatched to client of submission #{submission_id}: {e}");
         }
 
+        flow.state.start_job(job);
+
         #[cfg(feature = "metrics")]
-        metrics.stop_timer();
+        flow.metrics.track_max_jobs(flow.state.number_jobs_running());
+
+        Ok(false)
+    }
+
+    // Deliver a result, or chunk of a streamed result, returned by an executor to the flow being
+    // executed with the submission id it is for
+    #[allow(unused_variables, unused_mut)]
+    fn route_message(&mut self, flows: &mut [ActiveFlow], message: ExecutorMessage) -> Result<()> {
+        let (submission_id, job_id) = match &message {
+            ExecutorMessage::Chunk(chunk) => (chunk.submission_id, chunk.job_id),
+            ExecutorMessage::JobResult(submission_id, result) => (*submission_id, result.0),
+        };
+        let Some(flow) = flows.iter_mut()
+            .find(|flow| flow.state.submission_id() == submission_id) else {
+            debug!("Discarded result of Job #{job_id} of submission #{submission_id} that is not executing");
+            return Ok(());
+        };
+
+        let restart = match message {
+            ExecutorMessage::Chunk(chunk) => {
+                let (_, restart) = flow.state.deliver_chunk(
+                    #[cfg(feature = "metrics")] &mut flow.metrics,
+                    chunk,
+                    #[cfg(feature = "debugger")] &mut self.debugger,
+                )?;
+                restart
+            },
+            ExecutorMessage::JobResult(_, result) => {
+                let (display_next_output, mut restart, job) = flow.state.retire_a_job(
+                    #[cfg(feature = "metrics")] &mut flow.metrics,
+                    result,
+                    #[cfg(feature = "debugger")] &mut self.debugger,
+                )?;
+                if let Some(span) = self.job_spans.remove(&(submission_id, job.payload.job_id)) {
+                    span.record("success", job.result.is_ok());
+                }
+                #[cfg(feature = "submission")]
+                if let Err(e) = handler(self.submission_handler, &mut flow.handler).job_completed(&job) {
+                    error!("Could not report job completed to client of submission #{submission_id}: {e}");
+                }
+                #[cfg(feature = "debugger")]
+                if flow.debugged() {
+                    self.debugger.check_watches(&flow.state, &job);
+                    if display_next_output {
+                        (_, restart) = self.debugger.job_done(&mut flow.state, &job);
+                    }
+                }
+                restart
+            },
+        };
+        self.send_events(&mut flow.state)?;
+
+        if restart {
+            self.restart_flow(flow)?;
+        }
+
+        Ok(())
+    }
+
+    // The debugger requested that the execution of a flow be reset and started again from its
+    // initial state, so discard the jobs of it that were dispatched and reinitialize its state
+    #[cfg(feature = "debugger")]
+    fn restart_flow(&mut self, flow: &mut ActiveFlow) -> Result<()> {
+        self.rewind(&mut flow.state)?;
+        flow.state.init()?;
         #[cfg(feature = "metrics")]
-        metrics.set_jobs_created(state.get_number_of_jobs_created());
-        #[cfg(all(feature = "submission", feature = "metrics"))]
-        self.submission_handler.flow_execution_ended(&state, metrics)?;
-        #[cfg(all(feature = "submission", not(feature = "metrics")))]
-        self.submitter.flow_execution_ended(&state)?;
+        flow.metrics.reset();
+        self.begin_flow(flow)
+    }
 
-        Ok(()) // Normal flow completion exit
+    // Without the debugger, execution is never restarted
+    #[cfg(not(feature = "debugger"))]
+    fn restart_flow(&mut self, _flow: &mut ActiveFlow) -> Result<()> {
+        Ok(())
     }
 
-    // Get a result back from an executor
-    #[allow(clippy::type_complexity)]
-    fn get_result(&mut self, state: &RunState) -> Result<Option<(usize, Result<(Option<Value>, RunAgain)>)>> {
-        if let Ok(result) = self.dispatcher.get_next_result(false) {
-            return Ok(Some(result));
+    // End the execution of a flow, reporting it to its handler
+    #[allow(unused_mut)]
+    fn end_flow(&mut self, submissions: &mut Submissions, mut flow: ActiveFlow) -> Result<()> {
+        let submission_id = flow.state.submission_id();
+        self.send_events(&mut flow.state)?;
+        if let Some(event_sink) = &mut self.event_sink {
+            event_sink.flush()?;
         }
 
-        if state.number_jobs_ready() > 0 {
-            return Ok(None);
+        // report before the end of execution is notified, so it is seen before a client exits
+        Self::report_deadlock(&flow.state);
+        let result = match flow.state.aborted() {
+            Some(reason) => {
+                error!("Execution of submission #{submission_id} was aborted: {reason}");
+                Err(format!("Flow execution was aborted: {reason}").into())
+            },
+            None if flow.state.cancelled() => {
+                info!("Execution of submission #{submission_id} was cancelled");
+                Ok(())
+            },
+            None => {
+                info!("Execution of submission #{submission_id} has ended");
+                Ok(())
+            },
+        };
+
+        #[cfg(feature = "metrics")]
+        flow.metrics.stop_timer();
+        #[cfg(feature = "metrics")]
+        flow.metrics.set_jobs_created(flow.state.get_number_of_jobs_created());
+        #[cfg(feature = "metrics")]
+        flow.metrics.set_executor_metrics(self.dispatcher.executor_metrics());
+        #[cfg(feature = "submission")]
+        if let Err(e) = handler(self.submission_handler, &mut flow.handler)
+            .flow_execution_ended(&flow.state, #[cfg(feature = "metrics")] flow.metrics) {
+            error!("Could not report end of execution to client of submission #{submission_id}: {e}");
+        }
+        Self::report_result(submissions, &mut flow.handler, submission_id, result);
+
+        Ok(())
+    }
+
+    // Report the `result` of the execution of a flow that has ended to the `handler` it was
+    // submitted with, or if it was reported to the coordinator's own handler, keep it for
+    // `execute_flow()` to return
+    #[allow(unused_variables)]
+    fn report_result(submissions: &mut Submissions, handler: &mut FlowHandler, submission_id: usize,
+                     result: Result<()>) {
+        #[cfg(feature = "submission")]
+        if let Some(handler) = handler {
+            if let Err(e) = handler.coordinator_is_exiting(result) {
+                error!("Could not report exit to client of submission #{submission_id}: {e}");
+            }
+            return;
         }
 
-        match self.dispatcher.get_next_result(true) {
-            Ok(result) => Ok(Some(result)),
-            Err(e) => Err(e)
+        if let Submissions::Single(_, single_result) = submissions {
+            *single_result = result;
         }
     }
 
-    // Retire as many jobs as possible, based on returned results.
-    // NOTE: This will block waiting for the last pending result
-    fn retire_jobs(&mut self,
-                   state: &mut RunState,
-                   #[cfg(feature = "metrics")] metrics: &mut Metrics,
-    ) -> Result<(bool, bool)> {
-        let mut display_next_output = false;
-        let mut restart = false;
+    // Cancel the execution of the flow with `state`, telling executors to discard its jobs that
+    // have not been executed yet. The results of those that are executing are ignored
+    #[cfg(feature = "submission")]
+    fn cancel(&mut self, state: &mut RunState) -> Result<()> {
+        let submission_id = state.submission_id();
+        info!("Cancelling execution of submission #{submission_id}");
+        state.cancel();
+        self.job_spans.retain(|(span_submission_id, _), _| *span_submission_id != submission_id);
+        self.dispatcher.send_cancel(submission_id)
+    }
+
+    // The debugger stepped back or reset the `state` of the flow, so the jobs that were running
+    // will be dispatched again: discard those already dispatched, and give the flow a new
+    // submission id so that the results of any still executing are ignored
+    #[cfg(feature = "debugger")]
+    fn rewind(&mut self, state: &mut RunState) -> Result<()> {
+        let submission_id = state.submission_id();
+        debug!("Discarding jobs dispatched for submission #{submission_id}");
+        self.job_spans.retain(|(span_submission_id, _), _| *span_submission_id != submission_id);
+        self.dispatcher.send_cancel(submission_id)?;
+        self.submission_id += 1;
+        state.set_submission_id(self.submission_id);
+        Ok(())
+    }
 
-        if state.number_jobs_running() > 0 {
-            match self.get_result(state) {
-                Ok(Some(result)) => {
-                    let job;
+    // Abort the execution of the flow with `state` if it has exceeded one of the `ResourceLimits`
+    // of its submission, telling executors to discard its jobs that have not been executed yet
+    fn enforce_limits(&mut self, state: &mut RunState) -> Result<()> {
+        let Some(exceeded) = state.limit_exceeded() else {
+            return Ok(());
+        };
 
-                    (display_next_output, restart, job) = state.retire_a_job(
-                        #[cfg(feature = "metrics")] metrics,
-                        result,
-                        #[cfg(feature = "debugger")] &mut self.debugger,
-                    )?;
-                    #[cfg(feature = "debugger")]
-                    if display_next_output {
-                        (display_next_output, restart) = self.debugger.job_done(state, &job);
-                        if restart {
-                            return Ok((display_next_output, restart));
-                        }
-                    }
-                }
+        let submission_id = state.submission_id();
+        error!("Aborting execution of submission #{submission_id} as {exceeded}");
+        state.abort(format!("Resource limit exceeded: {exceeded}"));
+        self.job_spans.retain(|(span_submission_id, _), _| *span_submission_id != submission_id);
+        self.dispatcher.send_cancel(submission_id)?;
 
-                Ok(None) => {
-                    info!("No result was immediately available, but jobs are ready to be dispatched.\
-                     So coordinator avoided blocking for result. Will be received next time around");
-                }
+        Ok(())
+    }
 
-                Err(err) => {
-                    error!("\t{}", err.to_string());
-                    #[cfg(feature = "debugger")]
-                    if state.submission.debug_enabled {
-                        return self.debugger.error(state, err.to_string());
-                    }
-                    return Ok((display_next_output, restart));
-                }
+    // Send the events recorded in `state` since they were last sent to the `EventSink`, if any
+    fn send_events(&mut self, state: &mut RunState) -> Result<()> {
+        if let Some(event_sink) = &mut self.event_sink {
+            for event in state.take_events() {
+                event_sink.event(&event)?;
             }
         }
 
-        Ok((display_next_output, restart))
+        Ok(())
     }
 
-    // Dispatch as many jobs as possible for parallel execution.
-    // Return if the debugger is requesting (display output, restart)
-    fn dispatch_jobs(
-        &mut self,
-        state: &mut RunState,
-        #[cfg(feature = "metrics")] metrics: &mut Metrics,
-    ) -> Result<(bool, bool)> {
-        let mut display_next_output = false;
-        let mut restart = false;
-
-        while let Some(job) = state.get_next_job() {
-            match self.dispatch_a_job(
-                job.clone(),
-                state,
-                #[cfg(feature = "metrics")]
-                    metrics,
-            ) {
-                Ok((display, rest)) => {
-                    display_next_output = display;
-                    restart = rest;
-                }
-                Err(err) => {
-                    error!("Error sending on 'job_tx': {}", err.to_string());
-                    debug!("{}", state);
+    // Report the diagnosis of a deadlock that stopped execution of a flow, if there was one
+    fn report_deadlock(state: &RunState) {
+        match checks::deadlock_diagnosis(state) {
+            Ok(Some(diagnosis)) => error!("{diagnosis}"),
+            Ok(None) => {},
+            Err(e) => error!("Could not diagnose deadlock: {e}"),
+        }
+    }
 
-                    #[cfg(feature = "debugger")]
-                    return self.debugger.job_error(state, &job); // TODO avoid above clone() ?
-                }
+    // Write a checkpoint of the state of execution, if the submission has a `CheckpointPolicy`
+    // and its interval has elapsed since the last checkpoint was written
+    fn checkpoint(state: &RunState, last_checkpoint: &mut Instant) -> Result<()> {
+        if let Some(policy) = &state.submission.checkpoint_policy {
+            if last_checkpoint.elapsed() >= policy.interval {
+                state.checkpoint(&policy.path)?;
+                *last_checkpoint = Instant::now();
             }
         }
 
-        Ok((display_next_output, restart))
+        Ok(())
     }
 
-    // Dispatch a job for execution
-    fn dispatch_a_job(
-        &mut self,
-        job: Job,
-        state: &mut RunState,
-        #[cfg(feature = "metrics")] metrics: &mut Metrics,
-    ) -> Result<(bool, bool)> {
-        #[cfg(not(feature = "debugger"))]
-            let debug_options = (false, false);
+    // Send `handler` a snapshot of the metrics of the flow, if the interval between snapshots has
+    // passed since the last one was sent
+    #[cfg(all(feature = "submission", feature = "metrics"))]
+    fn send_metrics_snapshot(handler: &mut dyn SubmissionHandler, state: &RunState,
+                             metrics: &Metrics, last_snapshot: &mut Instant) -> Result<()> {
+        if last_snapshot.elapsed() >= METRICS_SNAPSHOT_INTERVAL {
+            handler.metrics_snapshot(metrics.snapshot(state.get_number_of_jobs_created(),
+                                                      state.number_jobs_running()))?;
+            *last_snapshot = Instant::now();
+        }
+
+        Ok(())
+    }
+
+    // Apply the `JobTimeoutPolicy` of the submission to running jobs whose results have not been
+    // received within its job timeout, dispatching them again or failing them
+    fn check_job_timeouts(&mut self, state: &mut RunState) -> Result<()> {
+        let submission_id = state.submission_id();
+        let dispatcher = &self.dispatcher;
+        for timed_out in state.take_timed_out_jobs(|job_id| dispatcher.held_back(submission_id, job_id)) {
+            match timed_out {
+                TimedOutJob::Redispatch(payload) => self.dispatcher.redispatch(&payload)?,
+                TimedOutJob::Fail(job_id, error) =>
+                    self.dispatcher.abandon(state.submission_id(), job_id, error),
+            }
+        }
+
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+#[cfg(feature = "submission")]
+mod test {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use portpicker::pick_unused_port;
+    use serde_json::{json, Value};
+    use serial_test::serial;
+
+    use flowcore::errors::Result;
+    use flowcore::model::flow_manifest::FlowManifest;
+    use flowcore::model::input::Input;
+    use flowcore::model::input::InputInitializer::Once;
+    use flowcore::model::metadata::MetaData;
+    #[cfg(feature = "metrics")]
+    use flowcore::model::metrics::Metrics;
+    #[cfg(feature = "debugger")]
+    use flowcore::model::output_connection::OutputConnection;
+    use flowcore::model::runtime_function::RuntimeFunction;
+    #[cfg(feature = "debugger")]
+    use flowcore::model::source_location::SourceLocation;
+    use flowcore::model::submission::Submission;
+    use flowcore::RunAgain;
+
+    #[cfg(feature = "debugger")]
+    use crate::block::Block;
+    #[cfg(feature = "debugger")]
+    use crate::debug_command::DebugCommand;
+    #[cfg(feature = "debugger")]
+    use crate::debugger_handler::DebuggerHandler;
+    use crate::dispatcher::Dispatcher;
+    use crate::job::{Job, Payload};
+    use crate::run_state::RunState;
+    #[cfg(feature = "debugger")]
+    use crate::run_state::State;
+    use crate::submission_handler::{SubmissionHandler, SubmissionListener};
+
+    use super::Coordinator;
+
+    // Records the ids of the jobs completed, and when execution ended if it was cancelled, for
+    // one submission, cancelling its execution if `cancel` is set
+    #[derive(Default)]
+    struct RecordingHandler {
+        cancel: bool,
+        completed: Arc<Mutex<Vec<usize>>>,
+        ended: Arc<Mutex<Option<bool>>>,
+    }
+
+    impl SubmissionHandler for RecordingHandler {
+        fn flow_execution_starting(&mut self) -> Result<()> {
+            Ok(())
+        }
 
         #[cfg(feature = "debugger")]
-            let debug_options = self
-            .debugger
-            .check_prior_to_job(state, &job)?;
+        fn should_enter_debugger(&mut self) -> Result<bool> {
+            Ok(false)
+        }
 
-        self.dispatcher.send_job_for_execution(&job.payload)?;
+        fn should_cancel(&mut self) -> Result<bool> {
+            Ok(self.cancel)
+        }
 
-        state.start_job(job);
+        fn flow_execution_ended(&mut self, state: &RunState,
+                                #[cfg(feature = "metrics")] _metrics: Metrics) -> Result<()> {
+            *self.ended.lock().expect("Could not lock") = Some(state.cancelled());
+            Ok(())
+        }
 
-        #[cfg(feature = "metrics")]
-        metrics.track_max_jobs(state.number_jobs_running());
+        fn job_completed(&mut self, job: &Job) -> Result<()> {
+            self.completed.lock().expect("Could not lock").push(job.payload.job_id);
+            Ok(())
+        }
+
+        fn wait_for_submission(&mut self) -> Result<Option<Submission>> {
+            Ok(None)
+        }
+
+        fn coordinator_is_exiting(&mut self, _result: Result<()>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    // Accepts all its submissions at once, so they are executed concurrently
+    struct TestListener {
+        submissions: Vec<(Submission, Box<dyn SubmissionHandler>)>,
+    }
+
+    impl SubmissionListener for TestListener {
+        fn accept(&mut self, _wait: bool) -> Result<Option<(Submission, Box<dyn SubmissionHandler>)>> {
+            Ok(self.submissions.pop())
+        }
+    }
+
+    #[cfg(feature = "debugger")]
+    struct DummyServer;
+
+    #[cfg(feature = "debugger")]
+    impl DebuggerHandler for DummyServer {
+        fn start(&mut self) {}
+        fn job_breakpoint(&mut self, _job: &Job, _function: &RuntimeFunction, _states: Vec<State>) {}
+        fn block_breakpoint(&mut self, _block: &Block) {}
+        fn flow_unblock_breakpoint(&mut self, _flow_id: usize) {}
+        fn send_breakpoint(
+            &mut self,
+            _: &str,
+            _source_process_id: usize,
+            _output_route: &str,
+            _value: &Value,
+            _destination_id: usize,
+            _destination_name: &str,
+            _input_name: &str,
+            _input_number: usize,
+            _source_location: Option<&SourceLocation>,
+        ) {}
+        fn job_error(&mut self, _job: &Job, _source_location: Option<&SourceLocation>) {}
+        fn job_completed(&mut self, _job: &Job) {}
+        fn watch_value(&mut self, _job: &Job, _function_name: &str, _output_route: &str, _value: &Value) {}
+        fn blocks(&mut self, _blocks: Vec<Block>) {}
+        fn outputs(&mut self, _output: Vec<OutputConnection>) {}
+        fn input(&mut self, _input: Input) {}
+        fn function_list(&mut self, _functions: &[RuntimeFunction]) {}
+        fn function_states(&mut self, _function: RuntimeFunction, _function_states: Vec<State>) {}
+        fn run_state(&mut self, _run_state: &RunState) {}
+        fn message(&mut self, _message: String) {}
+        fn panic(&mut self, _state: &RunState, _error_message: String) {}
+        fn debugger_exiting(&mut self) {}
+        fn debugger_resetting(&mut self) {}
+        fn debugger_error(&mut self, _error: String) {}
+        fn execution_starting(&mut self) {}
+        fn execution_ended(&mut self) {}
+        fn get_command(&mut self, _state: &RunState) -> Result<DebugCommand> {
+            unimplemented!();
+        }
+    }
+
+    fn test_submission() -> Submission {
+        let function = RuntimeFunction::new(
+            #[cfg(feature = "debugger")]
+                "fA",
+            #[cfg(feature = "debugger")]
+                "/fA",
+            "file://fake/test",
+            vec![Input::new(
+                #[cfg(feature = "debugger")]
+                    "",
+                0,
+                false,
+                Some(Once(json!(1))),
+                None,
+            )],
+            0,
+            0,
+            &[],
+            false,
+        );
+        let mut manifest = FlowManifest::new(MetaData {
+            name: "test".into(),
+            version: "0.0.0".into(),
+            description: "a test".into(),
+            authors: vec!["me".into()],
+        });
+        manifest.add_function(function);
+        Submission::new(manifest, None, None, None, None,
+                        #[cfg(feature = "debugger")] false)
+    }
+
+    // An executor that returns a result for each of `jobs` jobs sent to it, with no output
+    fn fake_executor(job_address: String, results_address: String, jobs: usize) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let context = zmq::Context::new();
+            let job_source = context.socket(zmq::PULL).expect("Could not create job socket");
+            job_source.connect(&job_address).expect("Could not connect to job socket");
+            job_source.set_rcvtimeo(5000).expect("Could not set timeout");
+            let results_sink = context.socket(zmq::PUSH).expect("Could not create results socket");
+            results_sink.connect(&results_address).expect("Could not connect to results socket");
+
+            for _ in 0..jobs {
+                let msg = job_source.recv_msg(0).expect("Did not receive a job");
+                let payload: Payload = serde_json::from_str(msg.as_str().expect("Not a str"))
+                    .expect("Could not deserialize Payload");
+                let result: Result<(Option<Value>, RunAgain)> = Ok((None, false));
+                results_sink.send(serde_json::to_string(
+                    &(payload.submission_id, payload.job_id, result))
+                    .expect("Could not serialize result").as_bytes(), 0)
+                    .expect("Could not send result");
+            }
+        })
+    }
+
+    #[test]
+    #[serial]
+    fn flow_executed() {
+        let ports: Vec<u16> = (0..5).map(|_| pick_unused_port().expect("No ports free")).collect();
+        let address = |index: usize| format!("tcp://127.0.0.1:{}", ports.get(index)
+            .expect("No port"));
+        let dispatcher = Dispatcher::new(&(address(0), address(1), address(2),
+                                           address(3), address(4)), None)
+            .expect("Could not create dispatcher");
+        let executor = fake_executor(address(1), address(2), 1);
+
+        let mut submission_handler = RecordingHandler::default();
+        let (completed, ended) = (submission_handler.completed.clone(),
+                                  submission_handler.ended.clone());
+        #[cfg(feature = "debugger")]
+        let mut debug_server = DummyServer;
+        let mut coordinator = Coordinator::new(
+            dispatcher,
+            &mut submission_handler,
+            #[cfg(feature = "debugger")] &mut debug_server,
+        );
+        coordinator.execute_flow(test_submission()).expect("Could not execute flow");
+        executor.join().expect("Fake executor failed");
+
+        assert_eq!(*completed.lock().expect("Could not lock"), vec![1]);
+        assert_eq!(*ended.lock().expect("Could not lock"), Some(false));
+    }
+
+    #[test]
+    #[serial]
+    fn concurrent_submissions_served() {
+        let ports: Vec<u16> = (0..5).map(|_| pick_unused_port().expect("No ports free")).collect();
+        let address = |index: usize| format!("tcp://127.0.0.1:{}", ports.get(index)
+            .expect("No port"));
+        let dispatcher = Dispatcher::new(&(address(0), address(1), address(2),
+                                           address(3), address(4)), None)
+            .expect("Could not create dispatcher");
+        let executor = fake_executor(address(1), address(2), 2);
+
+        let handlers = [RecordingHandler::default(), RecordingHandler::default()];
+        let recorded: Vec<_> = handlers.iter()
+            .map(|handler| (handler.completed.clone(), handler.ended.clone()))
+            .collect();
+        let mut listener = TestListener {
+            submissions: handlers.into_iter()
+                .map(|handler| (test_submission(), Box::new(handler) as Box<dyn SubmissionHandler>))
+                .collect(),
+        };
+
+        #[cfg(feature = "debugger")]
+        let mut debug_server = DummyServer;
+        let mut submission_handler = RecordingHandler::default();
+        let mut coordinator = Coordinator::new(
+            dispatcher,
+            &mut submission_handler,
+            #[cfg(feature = "debugger")] &mut debug_server,
+        );
+        coordinator.serve(&mut listener).expect("Could not serve submissions");
+        executor.join().expect("Fake executor failed");
+
+        // each submission has its own job ids, so both complete a job with the same id
+        for (completed, ended) in recorded {
+            assert_eq!(*completed.lock().expect("Could not lock"), vec![1]);
+            assert_eq!(*ended.lock().expect("Could not lock"), Some(false));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn submission_cancelled() {
+        let ports: Vec<u16> = (0..5).map(|_| pick_unused_port().expect("No ports free")).collect();
+        let address = |index: usize| format!("tcp://127.0.0.1:{}", ports.get(index)
+            .expect("No port"));
+        let dispatcher = Dispatcher::new(&(address(0), address(1), address(2),
+                                           address(3), address(4)), None)
+            .expect("Could not create dispatcher");
+
+        let handler = RecordingHandler { cancel: true, ..Default::default() };
+        let (completed, ended) = (handler.completed.clone(), handler.ended.clone());
+        let mut listener = TestListener {
+            submissions: vec![(test_submission(), Box::new(handler))],
+        };
+
+        #[cfg(feature = "debugger")]
+        let mut debug_server = DummyServer;
+        let mut submission_handler = RecordingHandler::default();
+        let mut coordinator = Coordinator::new(
+            dispatcher,
+            &mut submission_handler,
+            #[cfg(feature = "debugger")] &mut debug_server,
+        );
+        coordinator.serve(&mut listener).expect("Could not serve submissions");
 
-        Ok(debug_options)
+        assert!(completed.lock().expect("Could not lock").is_empty());
+        assert_eq!(*ended.lock().expect("Could not lock"), Some(true));
     }
 }
@@ -1,6 +1,7 @@
 use std::fmt;
 
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Types of `Params` used in communications between the debugger and the `debug_client`
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
@@ -54,10 +55,20 @@ pub enum DebugCommand {
     Modify(Option<Vec<String>>),
     /// `reset` flow execution back to the initial state, or run the flow from the start
     RunReset,
+    /// `set` the value being sent when stopped at a breakpoint prior to sending it (no input), or
+    /// the values queued up at an Input (`function_id`, `input_number`) to those in an array
+    Set(Option<(usize, usize)>, Value),
     /// `step` forward in flow execution by executing one (default) or more `Jobs`
     Step(Option<usize>),
+    /// step `back` in flow execution to the state prior to the previous (default) or an earlier `Job`
+    StepBack(Option<usize>),
+    /// `unwatch` an output - with an optional parameter
+    Unwatch(Option<BreakpointSpec>),
     /// `validate` the current state
     Validate,
+    /// `watch` the values sent on an output without stopping execution, or list watches if
+    /// no parameter
+    Watch(Option<BreakpointSpec>),
 }
 
 impl fmt::Display for DebugCommand {
@@ -89,6 +100,8 @@ impl From<String> for DebugCommand {
 
 #[cfg(test)]
 mod test {
+    use serde_json::json;
+
     use crate::debug_command::DebugCommand;
 
     #[test]
@@ -108,8 +121,12 @@ mod test {
         println!("{}", DebugCommand::Invalid);
         println!("{}", DebugCommand::List);
         println!("{}", DebugCommand::RunReset);
+        println!("{}", DebugCommand::Set(Some((0, 0)), json!([1])));
         println!("{}", DebugCommand::Step(None));
+        println!("{}", DebugCommand::StepBack(None));
         println!("{}", DebugCommand::Validate);
+        println!("{}", DebugCommand::Watch(None));
+        println!("{}", DebugCommand::Unwatch(None));
         println!("{}", DebugCommand::DebugClientStarting);
         println!("{}", DebugCommand::Modify(None));
     }
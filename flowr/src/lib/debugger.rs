@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::fmt;
 use std::fmt::Write;
 
@@ -7,15 +7,20 @@ use log::error;
 use serde_json::Value;
 
 use flowcore::errors::Result;
+use flowcore::model::output_connection::OutputConnection;
 use flowcore::model::output_connection::Source::{Input, Output};
+use flowcore::model::runtime_function::RuntimeFunction;
 
 use crate::block::Block;
 use crate::debug_command::BreakpointSpec;
 use crate::debug_command::DebugCommand;
-use crate::debug_command::DebugCommand::{Ack, Breakpoint, Continue, DebugClientStarting, Delete, Error, ExitDebugger, Inspect, InspectBlock, InspectFunction, InspectInput, InspectOutput, Invalid, List, Modify, RunReset, Step, Validate};
+use crate::debug_command::DebugCommand::{Ack, Breakpoint, Continue, DebugClientStarting, Delete, Error, ExitDebugger, Inspect, InspectBlock, InspectFunction, InspectInput, InspectOutput, Invalid, List, Modify, RunReset, Set, Step, StepBack, Unwatch, Validate, Watch};
 use crate::debugger_handler::DebuggerHandler;
 use crate::job::Job;
-use crate::run_state::RunState;
+use crate::run_state::{RunState, Snapshot};
+
+// The default number of jobs that the state prior to is recorded, so execution can be stepped back
+const HISTORY_LENGTH: usize = 10;
 
 /// Debugger struct contains all the info necessary to conduct a debugging session, storing
 /// set breakpoints, connections to the debug client etc
@@ -28,6 +33,16 @@ pub struct Debugger<'a> {
     break_at_job: usize,
     function_breakpoints: HashSet<usize>,
     flow_unblock_breakpoints: HashSet<usize>,
+    /* function_id, output_route */
+    watches: HashSet<(usize, String)>,
+    /* the most recent jobs started, each with a snapshot of the state prior to it */
+    history: VecDeque<(Job, Snapshot)>,
+    history_length: usize,
+    stopped_prior_to_job: bool,
+    rewound: bool,
+    /* set when stopped prior to sending a value, and the value to send instead if it was set */
+    stopped_prior_to_send: bool,
+    value_to_send: Option<Value>,
 }
 
 #[derive(Debug, Clone)]
@@ -75,14 +90,34 @@ impl<'a> Debugger<'a> {
             break_at_job: usize::MAX,
             function_breakpoints: HashSet::<usize>::new(),
             flow_unblock_breakpoints: HashSet::<usize>::new(),
+            watches: HashSet::<(usize, String)>::new(),
+            history: VecDeque::new(),
+            history_length: HISTORY_LENGTH,
+            stopped_prior_to_job: false,
+            rewound: false,
+            stopped_prior_to_send: false,
+            value_to_send: None,
         }
     }
 
     /// Start the debugger
     pub fn start(&mut self) {
+        self.history.clear();
         self.debug_server.start();
     }
 
+    /// Return true if the state was stepped back while stopped prior to a job, so that job
+    /// should not be started, as it will be created again
+    pub fn take_rewound(&mut self) -> bool {
+        std::mem::take(&mut self.rewound)
+    }
+
+    /// Return the value set from the debug client to be sent instead of the value that was
+    /// about to be sent when stopped at a breakpoint prior to sending it, if one was set
+    pub fn take_value_to_send(&mut self) -> Option<Value> {
+        self.value_to_send.take()
+    }
+
     /// Check if there is a breakpoint at this job prior to starting executing it.
     /// Return values are (display next output, reset execution)
     pub fn check_prior_to_job(
@@ -90,16 +125,35 @@ impl<'a> Debugger<'a> {
         state: &mut RunState,
         job: &Job,
     ) -> Result<(bool, bool)> {
+        if state.submission.debug_enabled && self.history_length > 0 && self.will_stop_prior_to_job(job) {
+            if self.history.len() >= self.history_length {
+                self.history.pop_front();
+            }
+            self.history.push_back((job.clone(), state.snapshot_prior_to(job)));
+        } else {
+            self.history.clear();
+        }
+
         if self.break_at_job == job.payload.job_id || self.function_breakpoints.contains(&job.function_id) {
             self.debug_server.job_breakpoint(job, state.get_function(job.function_id)
                 .ok_or("Could not get function")?,
                                              state.get_function_states(job.function_id));
-            return self.wait_for_command(state);
+            self.stopped_prior_to_job = true;
+            let command = self.wait_for_command(state);
+            self.stopped_prior_to_job = false;
+            return command;
         }
 
         Ok((false, false))
     }
 
+    // Return true if a breakpoint is set that will stop prior to `job` or a later one, where
+    // execution can be stepped back, so the state prior to jobs needs to be recorded until then
+    fn will_stop_prior_to_job(&self, job: &Job) -> bool {
+        (self.break_at_job != usize::MAX && self.break_at_job >= job.payload.job_id)
+            || !self.function_breakpoints.is_empty()
+    }
+
     /// Called from flowrlib during execution when it is about to create a block on one function
     /// due to not being able to send outputs to another function.
     ///
@@ -146,11 +200,18 @@ impl<'a> Debugger<'a> {
             let destination_function = state.get_function(destination_id)
                 .ok_or("Could not get function")?;
             let io_name = destination_function.input(input_number).ok_or("Could not get input")?.name();
+            let source_location = source_function.get_output_connections().iter()
+                .find(|connection| connection.destination_id == destination_id
+                    && connection.destination_io_number == input_number)
+                .and_then(OutputConnection::source_location);
 
             self.debug_server.send_breakpoint(source_function.name(), source_function_id, output_route, value,
                                               destination_id, destination_function.name(),
-                                              io_name, input_number);
-            return self.wait_for_command(state);
+                                              io_name, input_number, source_location);
+            self.stopped_prior_to_send = true;
+            let command = self.wait_for_command(state);
+            self.stopped_prior_to_send = false;
+            return command;
         }
 
         Ok((false, false))
@@ -184,7 +245,9 @@ impl<'a> Debugger<'a> {
     /// breakpoint it will enter the debugger on an error and let the user inspect the flow's
     /// state etc.
     pub fn job_error(&mut self, state: &mut RunState, job: &Job) -> Result<(bool, bool)> {
-        self.debug_server.job_error(job);
+        let source_location = state.get_function(job.function_id)
+            .and_then(RuntimeFunction::source_location);
+        self.debug_server.job_error(job, source_location);
         self.wait_for_command(state)
     }
 
@@ -201,6 +264,34 @@ impl<'a> Debugger<'a> {
         (false, false)
     }
 
+    /// Called from the flowrlib coordinator when a job has completed, to report the values it
+    /// output on any watched output routes to the debug client, without stopping execution.
+    pub fn check_watches(&mut self, state: &RunState, job: &Job) {
+        if self.watches.is_empty() {
+            return;
+        }
+
+        if let Ok((Some(output_value), _)) = &job.result {
+            let function_name = state.get_function(job.function_id)
+                .map(|function| function.name().to_string())
+                .unwrap_or_default();
+            for (function_id, output_route) in &self.watches {
+                if *function_id == job.function_id {
+                    if let Some(value) = output_value.pointer(output_route) {
+                        self.debug_server.watch_value(job, &function_name, output_route, value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Called from the flowrlib coordinator when an executor stopped responding and was considered
+    /// lost, to let the debug client know how many of its jobs are being executed again
+    pub fn executor_lost(&mut self, executor_id: &str, jobs_requeued: usize) {
+        self.debug_server.message(format!("Executor '{executor_id}' was lost, \
+            {jobs_requeued} of its jobs will be executed again"));
+    }
+
     /// An error occurred while executing a flow. Let the debug client know, enter the client
     /// and wait for a user command.
     ///
@@ -244,6 +335,16 @@ impl<'a> Debugger<'a> {
                     let message = result.unwrap_or_else(|e| e.to_string());
                     self.debug_server.message(message);
                 }
+                Ok(Watch(param)) => {
+                    let result = self.add_watch(state, param);
+                    let message = result.unwrap_or_else(|e| e.to_string());
+                    self.debug_server.message(message);
+                }
+                Ok(Unwatch(param)) => {
+                    let result = self.delete_watch(param);
+                    let message = result.unwrap_or_else(|e| e.to_string());
+                    self.debug_server.message(message);
+                }
                 Ok(Validate) => {
                     let message = Self::validate(state)?;
                     self.debug_server.message(message);
@@ -314,6 +415,8 @@ impl<'a> Debugger<'a> {
                     self.debug_server.blocks(blocks);
                 }
                 Ok(Modify(specs)) => self.modify_variables(state, &specs),
+                Ok(StepBack(param)) => self.step_back(state, param)?,
+                Ok(Set(input, value)) => self.set_value(state, input, value),
                 Ok(DebugClientStarting) => { // TODO remove
                     error!("Unexpected message 'DebugClientStarting' after started");
                 }
@@ -501,6 +604,73 @@ impl<'a> Debugger<'a> {
         }
     }
 
+    /*
+       Get the (function_id, output_route) to watch for a spec, where the whole output of a
+       function can be specified with just it's id or with a "/" route
+    */
+    fn watch_spec(spec: BreakpointSpec) -> Result<(usize, String)> {
+        match spec {
+            BreakpointSpec::Numeric(function_id) => Ok((function_id, String::new())),
+            BreakpointSpec::Output((function_id, output_route)) =>
+                Ok((function_id, output_route.trim_end_matches('/').to_string())),
+            _ => bail!("A watch must be on a Function's output, e.g. '1' or '1/sub_route'\n"),
+        }
+    }
+
+    /*
+       Add a watch on the output of a function, or list the watches set if no `Param` is given
+    */
+    fn add_watch(&mut self, state: &RunState, param: Option<BreakpointSpec>) -> Result<String> {
+        match param {
+            None => Ok(self.list_watches()),
+            Some(spec) => {
+                let (function_id, output_route) = Self::watch_spec(spec)?;
+                let function = state.get_function(function_id)
+                    .ok_or(format!("There is no Function #{function_id} to set a watch on"))?;
+                let message = format!("Watch set on Function #{function_id} ({}) output '{output_route}'",
+                                      function.name());
+                self.watches.insert((function_id, output_route));
+                Ok(message)
+            }
+        }
+    }
+
+    /*
+       Delete a watch on the output of a function, or all watches
+    */
+    fn delete_watch(&mut self, param: Option<BreakpointSpec>) -> Result<String> {
+        match param {
+            None => bail!("'unwatch' command must specify a watch, or '*' for all\n"),
+            Some(BreakpointSpec::All) => {
+                self.watches.clear();
+                Ok("Deleted all watches\n".into())
+            }
+            Some(spec) => {
+                let (function_id, output_route) = Self::watch_spec(spec)?;
+                if self.watches.remove(&(function_id, output_route.clone())) {
+                    Ok(format!("Watch on Function #{function_id} output '{output_route}' removed\n"))
+                } else {
+                    bail!("No watch on Function #{} output '{}' exists\n", function_id, output_route)
+                }
+            }
+        }
+    }
+
+    /*
+       List all watches set
+    */
+    fn list_watches(&self) -> String {
+        if self.watches.is_empty() {
+            return "No Watches set. Use the 'w' command to set a watch. Use 'h' for help.\n".into();
+        }
+
+        let mut response = String::from("Watches: \n");
+        for (function_id, route) in &self.watches {
+            let _ = writeln!(response, "\tOutput #{function_id}{route}");
+        }
+        response
+    }
+
     /*
        List all debugger breakpoints of all types.
        // TODO make structs not a string
@@ -547,6 +717,10 @@ impl<'a> Debugger<'a> {
             );
         }
 
+        if !self.watches.is_empty() {
+            response.push_str(&self.list_watches());
+        }
+
         response
     }
 
@@ -566,8 +740,11 @@ impl<'a> Debugger<'a> {
 
     // Get ready to start execution (and debugging) from scratch at the start of the flow
     fn reset(&mut self) {
-        // Leave all the breakpoints untouched for the repeat run
+        // Leave all the breakpoints and watches untouched for the repeat run
         self.break_at_job = usize::MAX;
+        self.history.clear();
+        self.rewound = false;
+        self.value_to_send = None;
     }
 
     // Parse a series of specs to modify a state value
@@ -575,7 +752,8 @@ impl<'a> Debugger<'a> {
     fn modify_variables(&mut self, state: &mut RunState, specs: &Option<Vec<String>>) {
         match specs.as_deref() {
             None | Some([]) => self.debug_server.message("State variables that can be modified are:\
-            \n'jobs' - maximum number of parallel jobs (integer) or 0 for no limit".to_string()),
+            \n'jobs' - maximum number of parallel jobs (integer) or 0 for no limit\
+            \n'history' - number of jobs that execution can be stepped back over (integer)".to_string()),
             Some(specs) => {
                 for spec in specs {
                     let parts: Vec<&str> = spec.trim().split('=').collect();
@@ -601,6 +779,19 @@ impl<'a> Debugger<'a> {
                                 }
                             }
                         }
+                        Some(&"history") => {
+                            if let Some(Ok(value)) = parts.get(1).map(|var| var.parse::<usize>()) {
+                                self.history_length = value;
+                                while self.history.len() > value {
+                                    self.history.pop_front();
+                                }
+                                self.debug_server.message(format!("State variable 'history' set to {value}"));
+                            } else {
+                                self.debug_server.message(
+                                    format!("Invalid value '{}' for variable 'history'",
+                                            parts.get(1).unwrap_or(&"")));
+                            }
+                        }
                         _ => self.debug_server.message("Unknown state variable".to_string())
                     }
                 }
@@ -628,6 +819,70 @@ impl<'a> Debugger<'a> {
         }
     }
 
+    /*
+     Set the value about to be sent when stopped prior to sending it, or replace the values queued
+     up at an input with those in the `value` array, when stopped prior to a job or sending a value
+    */
+    fn set_value(&mut self, state: &mut RunState, input: Option<(usize, usize)>, value: Value) {
+        if !self.stopped_prior_to_send && !(input.is_some() && self.stopped_prior_to_job) {
+            self.debug_server.debugger_error(
+                "Can only set the value being sent when stopped at a breakpoint prior to sending it, \
+                or the values queued at an input when stopped there or prior to a job\n".into());
+            return;
+        }
+
+        match (input, value) {
+            (None, value) => {
+                self.debug_server.message(format!("Value to be sent set to '{value}'"));
+                self.value_to_send = Some(value);
+            }
+            (Some((function_id, input_number)), Value::Array(values)) => {
+                match state.set_input_values(function_id, input_number, values) {
+                    Ok(()) => self.debug_server.message(
+                        format!("Values queued at input {function_id}:{input_number} set")),
+                    Err(e) => self.debug_server.debugger_error(format!("{e}\n")),
+                }
+            }
+            (Some(_), value) => self.debug_server.debugger_error(
+                format!("Values to queue at an input must be an array, not '{value}'\n")),
+        }
+    }
+
+    /*
+     Step back over one (default) or more jobs, by restoring the state recorded prior to the job
+     that many jobs before the one about to be started, which is then the next to be started.
+     Jobs dispatched since then are executed again, and the results of them already running are
+     discarded by the coordinator
+    */
+    fn step_back(&mut self, state: &mut RunState, steps: Option<usize>) -> Result<()> {
+        if !self.stopped_prior_to_job {
+            self.debug_server.debugger_error(
+                "Can only step back when stopped at a breakpoint prior to a job\n".into());
+            return Ok(());
+        }
+
+        // the state prior to the job about to be started is recorded, unless already stepped back
+        let current = usize::from(!self.rewound);
+        let steps = steps.unwrap_or(1);
+        let recorded = self.history.len().saturating_sub(current);
+        if steps == 0 || steps > recorded {
+            self.debug_server.debugger_error(
+                format!("Number of jobs to step back must be between 1 and {recorded}\n"));
+            return Ok(());
+        }
+
+        let (jobs, snapshots): (Vec<Job>, Vec<Snapshot>) = self.history
+            .drain(self.history.len() - steps - current..)
+            .unzip();
+        let job = jobs.into_iter().next().ok_or("No state was recorded to step back to")?;
+        state.restore(snapshots);
+        self.rewound = true;
+        self.debug_server.message(format!("Stepped back {steps} job(s)"));
+        self.debug_server.job_breakpoint(&job, state.get_function(job.function_id)
+            .ok_or("Could not get function")?, state.get_function_states(job.function_id));
+        Ok(())
+    }
+
     /*
         Return a vector of all the processes preventing process_id from running, which can be:
         - other process has input full and hence is blocking running of this process
@@ -726,6 +981,8 @@ impl<'a> Debugger<'a> {
 
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
+
     use serde_json::{json, Value};
     use url::Url;
 
@@ -735,7 +992,8 @@ mod test {
     use flowcore::model::input::InputInitializer::Once;
     use flowcore::model::metadata::MetaData;
     use flowcore::model::output_connection::OutputConnection;
-    use flowcore::model::runtime_function::RuntimeFunction;
+    use flowcore::model::runtime_function::{Priority, RuntimeFunction};
+    use flowcore::model::source_location::SourceLocation;
     use flowcore::model::submission::Submission;
 
     use crate::block::Block;
@@ -753,6 +1011,7 @@ mod test {
         job_completed: bool,
         job_errored: bool,
         panicked: bool,
+        watched_values: Vec<(String, Value)>,
     }
 
     impl DummyServer {
@@ -765,6 +1024,7 @@ mod test {
                 job_completed: false,
                 job_errored: false,
                 panicked: false,
+                watched_values: vec![],
             }
         }
     }
@@ -781,15 +1041,19 @@ mod test {
             self.flow_unblock_breakpoint = flow_id;
         }
         fn send_breakpoint(&mut self, _: &str, source_process_id: usize, _output_route: &str, _value: &Value,
-                           destination_id: usize, _destination_name: &str, _input_name: &str, _input_number: usize) {
+                           destination_id: usize, _destination_name: &str, _input_name: &str, _input_number: usize,
+                           _source_location: Option<&SourceLocation>) {
             self.send_breakpoint = (source_process_id, destination_id);
         }
-        fn job_error(&mut self, _job: &Job) {
+        fn job_error(&mut self, _job: &Job, _source_location: Option<&SourceLocation>) {
             self.job_errored = true;
         }
         fn job_completed(&mut self, _job: &Job) {
             self.job_completed = true;
         }
+        fn watch_value(&mut self, _job: &Job, _function_name: &str, output_route: &str, value: &Value) {
+            self.watched_values.push((output_route.to_string(), value.clone()));
+        }
         fn blocks(&mut self, _blocks: Vec<Block>) {}
         fn outputs(&mut self, _output: Vec<OutputConnection>) {}
         fn input(&mut self, _input: Input) {}
@@ -832,6 +1096,8 @@ mod test {
             test_manifest(functions),
             None,
             None,
+            None,
+            None,
             #[cfg(feature = "debugger")]
                 true,
         )
@@ -862,9 +1128,19 @@ mod test {
             payload: Payload {
                 job_id: 0,
                 implementation_url: Url::parse("file://test").expect("Could not parse Url"),
-                input_set: vec![json!(1)],
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
+                input_set: vec![Arc::new(json!(1))],
             },
             result: Ok((Some(json!(1)), true)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         }
     }
 
@@ -893,6 +1169,80 @@ mod test {
         assert_eq!(server.job_breakpoint, job.payload.job_id);
     }
 
+    #[test]
+    fn test_no_history_without_breakpoint() {
+        let mut state = RunState::new(test_submission(vec![test_function(0)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+
+        let _ = debugger.check_prior_to_job(&mut state, &test_job());
+        assert!(debugger.history.is_empty());
+    }
+
+    #[test]
+    fn test_step_back() {
+        let mut state = RunState::new(test_submission(vec![test_function(0)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        let first_job = test_job();
+        let mut second_job = test_job();
+        second_job.payload.job_id = 1;
+
+        // record the state prior to each job, while a later job will be stopped at
+        debugger.break_at_job = 2;
+        let _ = debugger.check_prior_to_job(&mut state, &first_job);
+        let _ = debugger.check_prior_to_job(&mut state, &second_job);
+        assert_eq!(debugger.history.len(), 2);
+
+        // can only step back when stopped prior to a job
+        assert!(debugger.step_back(&mut state, None).is_ok());
+        assert!(!debugger.take_rewound());
+
+        // can't step back over more jobs than were recorded
+        debugger.stopped_prior_to_job = true;
+        assert!(debugger.step_back(&mut state, Some(2)).is_ok());
+        assert!(!debugger.take_rewound());
+
+        assert!(debugger.step_back(&mut state, None).is_ok());
+        assert!(debugger.take_rewound());
+        assert!(debugger.history.is_empty());
+
+        // stopped prior to the first job again
+        assert_eq!(server.job_breakpoint, first_job.payload.job_id);
+    }
+
+    #[test]
+    fn test_set_value_to_send() {
+        let mut state = RunState::new(test_submission(vec![test_function(0), test_function(1)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+
+        // can only set the value to send when stopped prior to sending a value
+        debugger.set_value(&mut state, None, json!(2));
+        assert_eq!(debugger.take_value_to_send(), None);
+
+        debugger.stopped_prior_to_send = true;
+        debugger.set_value(&mut state, None, json!(2));
+        assert_eq!(debugger.take_value_to_send(), Some(json!(2)));
+    }
+
+    #[test]
+    fn test_set_input_values() {
+        let mut state = RunState::new(test_submission(vec![test_function(0), test_function(1)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        debugger.stopped_prior_to_job = true;
+
+        // values to queue must be in an array
+        debugger.set_value(&mut state, Some((1, 0)), json!(2));
+        assert_eq!(state.get_function(1).and_then(|f| f.input(0)).map(Input::values_available),
+                   Some(0));
+
+        // setting the queued values of a function's only input creates jobs for it
+        debugger.set_value(&mut state, Some((1, 0)), json!([2, 3]));
+        assert_eq!(state.number_jobs_ready(), 2);
+    }
+
     #[test]
     fn test_check_on_block_creation() {
         let mut state = RunState::new(test_submission(vec![test_function(0)]));
@@ -1260,4 +1610,74 @@ mod test {
             .expect("Couldn't add breakpoint");
         assert!(debugger.delete_breakpoint(&state, Some(BreakpointSpec::Output((0, String::new())))).is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_watch_no_such_function_fails() {
+        let state = RunState::new(test_submission(vec![test_function(0)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        assert!(debugger.add_watch(&state, Some(BreakpointSpec::Numeric(1))).is_err());
+    }
+
+    #[test]
+    fn test_watch_input_fails() {
+        let state = RunState::new(test_submission(vec![test_function(0)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        assert!(debugger.add_watch(&state, Some(BreakpointSpec::Input((0, 0)))).is_err());
+    }
+
+    #[test]
+    fn test_watch_whole_output() {
+        let state = RunState::new(test_submission(vec![test_function(0)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        debugger.add_watch(&state, Some(BreakpointSpec::Numeric(0)))
+            .expect("Couldn't add watch");
+        debugger.check_watches(&state, &test_job());
+
+        assert_eq!(server.watched_values, vec![(String::new(), json!(1))]);
+    }
+
+    #[test]
+    fn test_watch_output_sub_route() {
+        let state = RunState::new(test_submission(vec![test_function(0)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        debugger.add_watch(&state, Some(BreakpointSpec::Output((0, "/sum".into()))))
+            .expect("Couldn't add watch");
+        debugger.add_watch(&state, Some(BreakpointSpec::Output((0, "/missing".into()))))
+            .expect("Couldn't add watch");
+        let mut job = test_job();
+        job.result = Ok((Some(json!({"sum": 42, "carry": 0})), true));
+        debugger.check_watches(&state, &job);
+
+        assert_eq!(server.watched_values, vec![("/sum".into(), json!(42))]);
+    }
+
+    #[test]
+    fn test_watch_other_function_not_reported() {
+        let state = RunState::new(test_submission(vec![test_function(0), test_function(1)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        debugger.add_watch(&state, Some(BreakpointSpec::Numeric(1)))
+            .expect("Couldn't add watch");
+        debugger.check_watches(&state, &test_job());
+
+        assert!(server.watched_values.is_empty());
+    }
+
+    #[test]
+    fn test_unwatch() {
+        let state = RunState::new(test_submission(vec![test_function(0)]));
+        let mut server = DummyServer::new();
+        let mut debugger = Debugger::new(&mut server);
+        debugger.add_watch(&state, Some(BreakpointSpec::Output((0, "/".into()))))
+            .expect("Couldn't add watch");
+        assert!(debugger.delete_watch(Some(BreakpointSpec::Numeric(0))).is_ok());
+        assert!(debugger.delete_watch(Some(BreakpointSpec::Numeric(0))).is_err());
+        debugger.check_watches(&state, &test_job());
+
+        assert!(server.watched_values.is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use flowcore::errors::Result;
 use flowcore::model::input::Input;
 use flowcore::model::output_connection::OutputConnection;
 use flowcore::model::runtime_function::RuntimeFunction;
+use flowcore::model::source_location::SourceLocation;
 
 use crate::block::Block;
 use crate::debug_command::DebugCommand;
@@ -25,15 +26,20 @@ pub trait DebuggerHandler {
     fn block_breakpoint(&mut self, block: &Block);
     /// A breakpoint set on the unblocking of a flow has been hit
     fn flow_unblock_breakpoint(&mut self, flow_id: usize);
-    /// A breakpoint on sending a value from a specific function or to a specific function was hit
+    /// A breakpoint on sending a value from a specific function or to a specific function was hit,
+    /// including where the connection was defined if the flow was compiled with debug symbols
     #[allow(clippy::too_many_arguments)]
     fn send_breakpoint(&mut self, source_function_name: &str, source_function_id: usize,
                        output_route: &str, value: &Value, destination_id: usize,
-                       destination_name: &str, io_name: &str, input_number: usize);
-    /// A job error occurred during execution of the flow
-    fn job_error(&mut self, job: &Job);
+                       destination_name: &str, io_name: &str, input_number: usize,
+                       source_location: Option<&SourceLocation>);
+    /// A job error occurred during execution of the flow, including where the function was
+    /// referenced from if the flow was compiled with debug symbols
+    fn job_error(&mut self, job: &Job, source_location: Option<&SourceLocation>);
     /// A specific job completed
     fn job_completed(&mut self, job: &Job);
+    /// A job completed that output `value` on a watched output route of a function
+    fn watch_value(&mut self, job: &Job, function_name: &str, output_route: &str, value: &Value);
     /// returns a set of blocks
     fn blocks(&mut self, blocks: Vec<Block>);
     /// returns an output's connections
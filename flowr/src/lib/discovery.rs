@@ -0,0 +1,122 @@
+use std::net::{IpAddr, SocketAddr};
+
+use log::{debug, info, trace};
+use mdns_sd::{Receiver, ServiceDaemon, ServiceEvent, ServiceInfo};
+use once_cell::sync::OnceCell;
+
+use flowcore::errors::{Result, ResultExt};
+
+/// The DNS-SD service type that flow services are announced as, so they can be browsed for using
+/// standard tools such as `avahi-browse _flowr._tcp` or `dns-sd -B _flowr._tcp`
+pub const SERVICE_TYPE: &str = "_flowr._tcp.local.";
+
+// The TXT record properties of an announced service, with its full name and the discovery port
+// used to tell apart the services of different coordinators
+const SERVICE_PROPERTY: &str = "service";
+const DISCOVERY_PORT_PROPERTY: &str = "discovery";
+
+// The mDNS responder shared by all the services announced, and browsers created, by this process
+static DAEMON: OnceCell<ServiceDaemon> = OnceCell::new();
+
+fn daemon() -> Result<&'static ServiceDaemon> {
+    DAEMON.get_or_try_init(|| ServiceDaemon::new()
+        .map_err(|e| format!("Could not start mDNS responder: {e}").into()))
+}
+
+// The DNS-SD instance name of a service: the first label of its name (e.g. "jobs" for
+// "jobs._flowr._tcp.local") with the port, so that several can be announced from the same host
+fn instance_name(name: &str, service_port: u16) -> String {
+    format!("{}-{service_port}", name.split('.').next().unwrap_or(name))
+}
+
+/// Announce the service called `name` listening on `service_port` of this host, using mDNS and
+/// DNS-SD, as an instance of [`SERVICE_TYPE`] that clients looking for services announced with
+/// `discovery_port` can discover using a [`ServiceBrowser`]. If the service is bound to a
+/// specific `address` only that address is announced, otherwise those of all interfaces are
+///
+/// # Errors
+///
+/// Returns an error if the mDNS responder could not be started or the service registered with it
+pub fn announce_service(discovery_port: u16, name: &str, service_port: u16,
+                        address: Option<IpAddr>) -> Result<()> {
+    let instance = instance_name(name, service_port);
+    let host_name = format!("{instance}.local.");
+    let properties = [
+        (SERVICE_PROPERTY, name.to_string()),
+        (DISCOVERY_PORT_PROPERTY, discovery_port.to_string()),
+    ];
+    let service = match address.filter(|ip| !ip.is_unspecified()) {
+        Some(ip) => ServiceInfo::new(SERVICE_TYPE, &instance, &host_name, ip, service_port,
+                                     &properties[..]),
+        None => ServiceInfo::new(SERVICE_TYPE, &instance, &host_name, (), service_port,
+                                 &properties[..]).map(ServiceInfo::enable_addr_auto),
+    }.chain_err(|| format!("Could not create mDNS service info for '{name}'"))?;
+    daemon()?.register(service)
+        .chain_err(|| format!("Could not announce service '{name}' using mDNS"))?;
+    info!("mDNS announcing service named '{name}' as '{instance}.{SERVICE_TYPE}', on port: {service_port}");
+    Ok(())
+}
+
+/// A `ServiceBrowser` browses for the flow services announced using mDNS and DNS-SD in the
+/// background, so that [`ServiceBrowser::discovered`] can be polled for a particular one
+pub struct ServiceBrowser {
+    name: String,
+    discovery_port: u16,
+    events: Receiver<ServiceEvent>,
+}
+
+impl ServiceBrowser {
+    /// Start browsing for the service called `name` announced with `discovery_port`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mDNS responder could not be started or browsing started
+    pub fn new(discovery_port: u16, name: &str) -> Result<Self> {
+        let events = daemon()?.browse(SERVICE_TYPE)
+            .chain_err(|| format!("Could not browse for '{SERVICE_TYPE}' services"))?;
+        Ok(ServiceBrowser {
+            name: name.to_string(),
+            discovery_port,
+            events,
+        })
+    }
+
+    /// Return the "ip:port" address of the service, if it has been discovered, without waiting
+    pub fn discovered(&self) -> Option<String> {
+        while let Ok(event) = self.events.try_recv() {
+            if let ServiceEvent::ServiceResolved(service) = event {
+                trace!("mDNS resolved service '{}'", service.get_fullname());
+                if service.get_property_val_str(SERVICE_PROPERTY) == Some(self.name.as_str())
+                    && service.get_property_val_str(DISCOVERY_PORT_PROPERTY)
+                    == Some(self.discovery_port.to_string().as_str()) {
+                    // prefer an IPv4 address, as the beacons used to discover services do
+                    let mut addresses: Vec<&IpAddr> = service.get_addresses().iter().collect();
+                    addresses.sort_by_key(|address| address.is_ipv6());
+                    if let Some(ip) = addresses.first() {
+                        let address = SocketAddr::new(**ip, service.get_port()).to_string();
+                        debug!("Discovered service '{}' at '{address}' using mDNS", self.name);
+                        return Some(address);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Drop for ServiceBrowser {
+    fn drop(&mut self) {
+        if let Ok(daemon) = daemon() {
+            let _ = daemon.stop_browse(SERVICE_TYPE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn instance_names() {
+        assert_eq!(super::instance_name("jobs._flowr._tcp.local", 4000), "jobs-4000");
+        assert_eq!(super::instance_name("runtime", 4001), "runtime-4001");
+    }
+}
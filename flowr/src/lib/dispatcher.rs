@@ -1,108 +1,536 @@
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::mem;
+use std::time::{Duration, Instant};
 
-use log::{debug, error, trace};
+use log::{debug, error, info, trace, warn};
+use tracing::info_span;
 use serde_json::Value;
-use zmq::DONTWAIT;
 
-use flowcore::errors::{Result, ResultExt};
+use flowcore::errors::{bail, Result, ResultExt};
+use flowcore::model::metrics::ExecutorMetrics;
+use flowcore::model::runtime_function::Priority;
 use flowcore::RunAgain;
 
-use crate::job::Payload;
+use crate::executor::{Capabilities, Heartbeat};
+use crate::job::{Chunk, Payload};
+use crate::security::CurveKeys;
+use crate::transport::{JobQueue, JobTransport, ZmqJobTransport};
 
-const WAIT:i32 = 0;
+// The largest serialized size, in bytes, of a job that is sent to executors in a batch
+const MAX_BATCHED_JOB_BYTES: usize = 4096;
+
+// How often executors are sent a "HEARTBEAT" control message, that they respond to
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+// How long since an executor last responded to a heartbeat before it is considered lost, and the
+// jobs it held are sent for execution again
+const EXECUTOR_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The results of a batch of jobs returned together by an executor. Each is the id of the
+// submission the job is for, the job id and the result of executing it
+type BatchResults = Vec<(usize, usize, Result<(Option<Value>, RunAgain)>)>;
+
+/// A message returned by executors from the execution of a `Job`
+pub(crate) enum ExecutorMessage {
+    /// A `Chunk` of an array output by a running job, streamed back ahead of its result
+    Chunk(Chunk),
+    /// The result of a job: the id of the submission it is for, then its id, the optional output
+    /// value and if the function can run again
+    JobResult(usize, (usize, Result<(Option<Value>, RunAgain)>)),
+}
+
+/// An executor that stopped responding to heartbeats, and is considered lost
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LostExecutor {
+    /// The id of the executor
+    pub(crate) executor_id: String,
+    /// The number of jobs lost with it that were sent for execution again
+    pub(crate) jobs_requeued: usize,
+}
+
+// What the `Dispatcher` knows about an executor that responds to heartbeats
+struct ExecutorLiveness {
+    // The number of threads it advertised in its `Capabilities`
+    threads: usize,
+    // When it last responded to a heartbeat, or advertised its `Capabilities`
+    last_heartbeat: Instant,
+    // The jobs it held when it last responded, by submission id and job id
+    jobs: HashSet<(usize, usize)>,
+}
 
 /// `Dispatcher` structure holds information required to send jobs for execution and receive results back
 pub struct Dispatcher {
-    // A source of lib jobs to be executed
-    lib_job_socket: zmq::Socket,
-    // A source of jobs to be executed for context:// and provided functions
-    general_job_socket: zmq::Socket,
-    // A sink where to send jobs (with results)
-    results_socket: zmq::Socket,
-    // a socket to send control information to subscribing executors
-    control_socket: zmq::Socket,
+    // The transport used to send jobs and control messages to executors and receive results
+    transport: Box<dyn JobTransport>,
+    // The capabilities advertised by executors that have native implementations of libraries
+    native_executors: Vec<Capabilities>,
+    // The total number of threads advertised by executors, zero until one has advertised
+    executor_threads: usize,
+    // The number of jobs sent for execution that have not returned a result, per submission
+    jobs_in_flight: HashMap<usize, usize>,
+    // Jobs held back while executors are saturated, in a queue per `Priority`
+    pending_jobs: BTreeMap<Priority, VecDeque<Payload>>,
+    // The latest `ExecutorMetrics` reported by each executor, by executor id
+    executor_metrics: BTreeMap<String, ExecutorMetrics>,
+    // If executors have reported metrics since they were last taken by `updated_executor_metrics()`
+    executor_metrics_updated: bool,
+    // The maximum number of small lib jobs sent to executors in one message
+    batch_size: usize,
+    // Small lib jobs waiting to be sent in a batch to executors that run them natively
+    native_batch: Vec<Payload>,
+    // Small lib jobs waiting to be sent in a batch on the lib job queue
+    lib_batch: Vec<Payload>,
+    // Messages received from executors in a batch, not yet returned by `get_next_result()`
+    received: VecDeque<ExecutorMessage>,
+    // The timeout when blocked waiting for a result, or `None` to wait forever
+    results_timeout: Option<Duration>,
+    // The jobs sent for execution that have not returned a result, with when they were sent,
+    // by submission id and job id
+    outstanding: HashMap<(usize, usize), (Payload, Instant)>,
+    // The executors that respond to heartbeats, by executor id
+    executors: HashMap<String, ExecutorLiveness>,
+    // When the last "HEARTBEAT" was sent to executors
+    last_heartbeat: Instant,
+    // The number of results that may still be returned for jobs that are no longer needed, by
+    // submission id and job id. e.g. when a job is sent for execution again after its executor
+    // was lost, it may return a result if it was only slow to respond. Only the first is used
+    surplus_results: HashMap<(usize, usize), usize>,
+    // Jobs that have streamed `Chunk`s of their output, and so cannot be executed again
+    streaming: HashSet<(usize, usize)>,
+    // Executors lost, not yet taken by `take_lost_executors()`
+    lost_executors: Vec<LostExecutor>,
 }
 
 /// `Dispatcher` struct takes care of ending jobs for execution and receiving results
 impl Dispatcher {
-    /// Create a new `Dispatcher` of `Job`s using three addresses of job queues
+    /// Create a new `Dispatcher` of `Job`s using `zmq` sockets bound to the addresses of the lib
+    /// job queue, the general job queue, the results queue, the control socket and the native lib
+    /// job queue.
+    /// If `keys` are provided then jobs and results are encrypted, and only executors using the
+    /// same keys can connect.
     ///
     /// # Errors
     ///
     /// Returns an error if the zmq sockets used to send messages between client and coordinator
     /// cannot be bound.
     ///
-    pub fn new(job_queues: &(String, String, String, String)) -> Result<Self> {
-        let context = zmq::Context::new();
-        let lib_job_socket = context.socket(zmq::PUSH)
-            .map_err(|_| "Could not create job socket")?;
-        lib_job_socket.bind(&job_queues.0)
-            .map_err(|_| "Could not bind to job socket")?;
-
-        let general_job_socket = context.socket(zmq::PUSH)
-            .map_err(|_| "Could not create context job socket")?;
-        general_job_socket.bind(&job_queues.1)
-            .map_err(|_| "Could not bind to context job socket")?;
-
-        let results_socket = context.socket(zmq::PULL)
-            .map_err(|_| "Could not create results socket")?;
-        results_socket.bind(&job_queues.2)
-            .map_err(|_| "Could not bind to results socket")?;
-
-        let control_socket = context.socket(zmq::PUB)
-            .map_err(|_| "Could not create control socket")?;
-        control_socket.bind(&job_queues.3)
-            .map_err(|_| "Could not bind to control socket")?;
-
-        Ok(Dispatcher {
-            lib_job_socket,
-            general_job_socket,
-            results_socket,
-            control_socket
-        })
+    pub fn new(job_queues: &(String, String, String, String, String),
+               keys: Option<&CurveKeys>) -> Result<Self> {
+        Ok(Self::with_transport(Box::new(ZmqJobTransport::bind(job_queues, keys)?)))
+    }
+
+    /// Create a new `Dispatcher` of `Job`s that sends them to executors, and receives their
+    /// results, using `transport`. e.g. a `ChannelJobTransport` to run executors in the same
+    /// process without any sockets
+    #[must_use]
+    pub fn with_transport(transport: Box<dyn JobTransport>) -> Self {
+        Dispatcher {
+            transport,
+            native_executors: vec![],
+            executor_threads: 0,
+            jobs_in_flight: HashMap::new(),
+            pending_jobs: BTreeMap::new(),
+            executor_metrics: BTreeMap::new(),
+            executor_metrics_updated: false,
+            batch_size: 1,
+            native_batch: vec![],
+            lib_batch: vec![],
+            received: VecDeque::new(),
+            results_timeout: None,
+            outstanding: HashMap::new(),
+            executors: HashMap::new(),
+            last_heartbeat: Instant::now(),
+            surplus_results: HashMap::new(),
+            streaming: HashSet::new(),
+            lost_executors: vec![],
+        }
     }
 
     // Set the timeout to use when waiting for job results
     // Setting to `None` will disable timeouts and block forever
     pub(crate) fn set_results_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
-        #[allow(clippy::single_match_else)]
         match timeout {
-            Some(time) => {
-                debug!("Setting results timeout to: {}ms", time.as_millis());
-                //assert!(time.as_millis() < i32::MAX, "Truncation");
-                self.results_socket.set_rcvtimeo(i32::try_from(time.as_millis())?)
-            },
-            None => {
-                debug!("Disabling results timeout");
-                self.results_socket.set_rcvtimeo(-1)
-            },
-        }.map_err(|e| format!("Error setting results timeout: {e}").into())
-    }
-
-    // Wait for, then return the next Result returned from executors
-    #[allow(clippy::type_complexity)]
-    pub(crate) fn get_next_result(&mut self, block: bool) -> Result<(usize, Result<(Option<Value>, RunAgain)>)> {
-        let flags = if block {
-            WAIT
-        } else {
-            DONTWAIT
+            Some(time) => debug!("Setting results timeout to: {}ms", time.as_millis()),
+            None => debug!("Disabling results timeout"),
+        }
+        self.results_timeout = timeout;
+        self.transport.set_results_timeout(timeout)
+    }
+
+    // Set the maximum number of small lib jobs sent to executors together in one message, or
+    // `None` to send them one at a time
+    pub(crate) fn set_batch_size(&mut self, batch_size: Option<usize>) {
+        self.batch_size = batch_size.unwrap_or(1).max(1);
+    }
+
+    // Wait for, then return the next Result, or Chunk of a streamed result, returned from
+    // executors. `Capabilities` advertised, `Heartbeat`s and `ExecutorMetrics` reported by executors
+    // are received on the same queue, and are recorded while waiting. The results of a batch of
+    // jobs are received together, and then returned one at a time
+    pub(crate) fn get_next_result(&mut self, block: bool) -> Result<ExecutorMessage> {
+        loop {
+            self.check_executors()?;
+            if let Some(message) = self.received.pop_front() {
+                return Ok(message);
+            }
+
+            let message = if block {
+                match self.wait_for_message()? {
+                    Some(message) => message,
+                    None => continue,
+                }
+            } else {
+                self.transport.receive_result(false)?
+            };
+            let message_string = message.as_str();
+            if let Ok((submission_id, job_id, result)) = serde_json::from_str(message_string) {
+                if self.job_returned(submission_id, job_id)? {
+                    self.received.push_back(ExecutorMessage::JobResult(submission_id,
+                                                                       (job_id, result)));
+                }
+                self.flush()?;
+                continue;
+            }
+
+            if let Ok(results) = serde_json::from_str::<BatchResults>(message_string) {
+                for (submission_id, job_id, result) in results {
+                    if self.job_returned(submission_id, job_id)? {
+                        self.received.push_back(ExecutorMessage::JobResult(submission_id,
+                                                                           (job_id, result)));
+                    }
+                }
+                self.flush()?;
+                continue;
+            }
+
+            if let Ok(chunk) = serde_json::from_str::<Chunk>(message_string) {
+                self.streaming.insert((chunk.submission_id, chunk.job_id));
+                return Ok(ExecutorMessage::Chunk(chunk));
+            }
+
+            if let Ok(metrics) = serde_json::from_str::<ExecutorMetrics>(message_string) {
+                trace!("Executor reported metrics: {metrics}");
+                self.executor_metrics.insert(metrics.executor_id.clone(), metrics);
+                self.executor_metrics_updated = true;
+                continue;
+            }
+
+            if let Ok(heartbeat) = serde_json::from_str::<Heartbeat>(message_string) {
+                self.heartbeat_received(heartbeat);
+                continue;
+            }
+
+            let capabilities: Capabilities = serde_json::from_str(message_string)
+                .map_err(|_| "Could not Deserialize from message string")?;
+            self.add_executor(capabilities);
+        }
+    }
+
+    // Wait for the next message from executors, up to the results timeout if there is one.
+    // Executors are sent heartbeats meanwhile, so that if one is lost the jobs it held are sent
+    // for execution again, instead of waiting forever for their results. Returns `None` if
+    // results of jobs lost with an executor are waiting to be returned instead
+    fn wait_for_message(&mut self) -> Result<Option<String>> {
+        let start = Instant::now();
+        loop {
+            let wait = match self.results_timeout {
+                Some(timeout) => {
+                    let remaining = timeout.saturating_sub(start.elapsed());
+                    if remaining.is_zero() {
+                        bail!("Timed out waiting for a result after {}ms", timeout.as_millis());
+                    }
+                    remaining.min(HEARTBEAT_INTERVAL)
+                }
+                None => HEARTBEAT_INTERVAL,
+            };
+
+            if self.transport.wait_for_result(wait)? {
+                return self.transport.receive_result(false).map(Some);
+            }
+
+            self.check_executors()?;
+            if !self.received.is_empty() {
+                return Ok(None);
+            }
+        }
+    }
+
+    // Record the `Capabilities` advertised by an executor
+    fn add_executor(&mut self, capabilities: Capabilities) {
+        info!("Executor advertised capabilities: {capabilities}");
+        self.executor_threads += capabilities.threads;
+        if !capabilities.executor_id.is_empty() {
+            self.liveness(&capabilities.executor_id).threads = capabilities.threads;
+        }
+        if !capabilities.native_libs.is_empty() {
+            self.native_executors.push(capabilities);
+        }
+    }
+
+    // Return what is known about the liveness of an executor, starting to monitor it if it was
+    // not being monitored, e.g. if it was considered lost but has responded since
+    fn liveness(&mut self, executor_id: &str) -> &mut ExecutorLiveness {
+        self.executors.entry(executor_id.to_string()).or_insert_with(|| ExecutorLiveness {
+            threads: 0,
+            last_heartbeat: Instant::now(),
+            jobs: HashSet::new(),
+        })
+    }
+
+    // Record a `Heartbeat` from an executor, showing it is alive and which jobs it holds
+    fn heartbeat_received(&mut self, heartbeat: Heartbeat) {
+        trace!("Heartbeat from executor '{}' holding {} jobs", heartbeat.executor_id,
+            heartbeat.jobs.len());
+        let executor = self.liveness(&heartbeat.executor_id);
+        executor.last_heartbeat = Instant::now();
+        executor.jobs = heartbeat.jobs.into_iter().collect();
+    }
+
+    // Send executors a "HEARTBEAT" if it is time to, and consider lost any executor that has not
+    // responded to one for longer than `EXECUTOR_TIMEOUT`
+    fn check_executors(&mut self) -> Result<()> {
+        let since_heartbeat = self.last_heartbeat.elapsed();
+        if since_heartbeat < HEARTBEAT_INTERVAL {
+            return Ok(());
+        }
+
+        // if no heartbeats were sent for a while, e.g. while paused in the debugger, executors
+        // have not had a chance to respond, so give them one before considering any lost
+        if since_heartbeat > EXECUTOR_TIMEOUT {
+            for executor in self.executors.values_mut() {
+                executor.last_heartbeat = Instant::now();
+            }
+        }
+
+        self.transport.send_control("HEARTBEAT")
+            .chain_err(|| "Could not send 'HEARTBEAT' message")?;
+        self.last_heartbeat = Instant::now();
+
+        let lost: Vec<String> = self.executors.iter()
+            .filter(|(_, executor)| executor.last_heartbeat.elapsed() > EXECUTOR_TIMEOUT)
+            .map(|(executor_id, _)| executor_id.clone())
+            .collect();
+        for executor_id in lost {
+            self.executor_lost(&executor_id)?;
+        }
+
+        Ok(())
+    }
+
+    // Stop sending jobs to an executor that has not responded to heartbeats, and send the jobs
+    // it held for execution again. Jobs sent long enough ago that no other executor holds are
+    // considered lost with it too, e.g. if they were queued for it to receive. A job that has
+    // already streamed `Chunk`s of its output fails instead, as executing it again would repeat them
+    fn executor_lost(&mut self, executor_id: &str) -> Result<()> {
+        let Some(lost) = self.executors.remove(executor_id) else {
+            return Ok(());
         };
+        self.executor_threads = self.executor_threads.saturating_sub(lost.threads);
+        self.native_executors.retain(|capabilities| capabilities.executor_id != executor_id);
+
+        let held: HashSet<(usize, usize)> = self.executors.values()
+            .flat_map(|executor| executor.jobs.iter().copied())
+            .collect();
+        let lost_jobs: Vec<(usize, usize)> = self.outstanding.iter()
+            .filter(|(key, (_, sent))| lost.jobs.contains(key) ||
+                (!held.contains(key) && sent.elapsed() > EXECUTOR_TIMEOUT))
+            .map(|(key, _)| *key)
+            .collect();
+        warn!("Executor '{executor_id}' has not responded for over {}s, {} jobs lost with it \
+            will be executed again", EXECUTOR_TIMEOUT.as_secs(), lost_jobs.len());
 
-        let msg = self.results_socket.recv_msg(flags)
-            .map_err(|_| "Error receiving result")?;
-        let message_string = msg.as_str().ok_or("Could not get message as str")?;
-        serde_json::from_str(message_string)
-            .map_err(|_| "Could not Deserialize from zmq message string".into())
+        for key in &lost_jobs {
+            let Some((payload, _)) = self.outstanding.remove(key) else {
+                continue;
+            };
+            if let Some(count) = self.jobs_in_flight.get_mut(&payload.submission_id) {
+                *count = count.saturating_sub(1);
+            }
+            if self.streaming.remove(key) {
+                let error = format!("Executor '{executor_id}' was lost while streaming the \
+                    output of Job #{}", payload.job_id);
+                self.received.push_back(ExecutorMessage::JobResult(payload.submission_id,
+                    (payload.job_id, Err(error.into()))));
+            } else {
+                *self.surplus_results.entry(*key).or_default() += 1;
+                self.dispatch(&payload)?;
+            }
+        }
+        self.flush()?;
+
+        self.lost_executors.push(LostExecutor {
+            executor_id: executor_id.to_string(),
+            jobs_requeued: lost_jobs.len(),
+        });
+
+        Ok(())
+    }
+
+    // Return the executors considered lost since this was last called
+    pub(crate) fn take_lost_executors(&mut self) -> Vec<LostExecutor> {
+        mem::take(&mut self.lost_executors)
+    }
+
+    // Return the latest `ExecutorMetrics` reported by each executor
+    #[cfg(feature = "metrics")]
+    pub(crate) fn executor_metrics(&self) -> Vec<ExecutorMetrics> {
+        self.executor_metrics.values().cloned().collect()
+    }
+
+    // Return the latest `ExecutorMetrics` reported by each executor, if any have been reported
+    // since this was last called
+    pub(crate) fn updated_executor_metrics(&mut self) -> Option<Vec<ExecutorMetrics>> {
+        if !self.executor_metrics_updated {
+            return None;
+        }
+        self.executor_metrics_updated = false;
+        Some(self.executor_metrics.values().cloned().collect())
     }
 
-    // Send a `Job` for execution to executors
+    // Return true if all executors getting jobs from the native job queue can run the job natively
+    fn runs_natively(&self, payload: &Payload) -> bool {
+        !self.native_executors.is_empty() && self.native_executors.iter()
+            .all(|capabilities| capabilities.runs_natively(&payload.implementation_url))
+    }
+
+    // Wait up to `timeout` for a result to be returned from executors, returning true if there is
+    // one that can be received without blocking
+    pub(crate) fn wait_for_result(&mut self, timeout: Duration) -> Result<bool> {
+        self.check_executors()?;
+        if !self.received.is_empty() {
+            return Ok(true);
+        }
+
+        self.transport.wait_for_result(timeout)
+    }
+
+    // Return true if as many jobs are executing as executors have threads to execute them, or
+    // when batching, enough for a full batch for each of them
+    fn saturated(&self) -> bool {
+        self.executor_threads > 0 &&
+            self.jobs_in_flight.values().sum::<usize>() >= self.executor_threads * self.batch_size
+    }
+
+    // Record that a job of a submission has returned its result, and if executors are no longer
+    // saturated send the highest priority job being held back for execution. Returns false if
+    // the result is not needed, as the job was executed more than once and its result has already
+    // been returned, or it was abandoned
+    fn job_returned(&mut self, submission_id: usize, job_id: usize) -> Result<bool> {
+        let key = (submission_id, job_id);
+        self.streaming.remove(&key);
+        if self.outstanding.remove(&key).is_none() {
+            if let Some(count) = self.surplus_results.get_mut(&key) {
+                *count -= 1;
+                if *count == 0 {
+                    self.surplus_results.remove(&key);
+                }
+                debug!("Discarded surplus result of Job #{job_id} of submission #{submission_id}");
+                return Ok(false);
+            }
+        }
+
+        if let Some(count) = self.jobs_in_flight.get_mut(&submission_id) {
+            *count = count.saturating_sub(1);
+        }
+
+        if !self.saturated() {
+            if let Some(payload) = self.pending_jobs.values_mut().rev()
+                .find_map(VecDeque::pop_front) {
+                self.dispatch(&payload)?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    // Send a `Job` whose result was not received in time, e.g. because the executor running it
+    // hung, for execution again. Only the first result returned of its executions is used
+    pub(crate) fn redispatch(&mut self, payload: &Payload) -> Result<()> {
+        let key = (payload.submission_id, payload.job_id);
+        if self.outstanding.contains_key(&key) {
+            if let Some(count) = self.jobs_in_flight.get_mut(&payload.submission_id) {
+                *count = count.saturating_sub(1);
+            }
+            *self.surplus_results.entry(key).or_default() += 1;
+        }
+        self.dispatch(payload)?;
+        self.flush()
+    }
+
+    // Return true if a job of a submission is held back while executors are saturated, and so
+    // has not been sent for execution yet
+    pub(crate) fn held_back(&self, submission_id: usize, job_id: usize) -> bool {
+        self.pending_jobs.values().flatten()
+            .any(|payload| payload.submission_id == submission_id && payload.job_id == job_id)
+    }
+
+    // Stop waiting for the result of a job, which fails with `error` instead. A result returned
+    // by its execution later is discarded, and if it has not been sent for execution yet it is not
+    pub(crate) fn abandon(&mut self, submission_id: usize, job_id: usize, error: String) {
+        let key = (submission_id, job_id);
+        for queue in self.pending_jobs.values_mut() {
+            queue.retain(|payload| payload.submission_id != submission_id || payload.job_id != job_id);
+        }
+        if self.outstanding.remove(&key).is_some() {
+            if let Some(count) = self.jobs_in_flight.get_mut(&submission_id) {
+                *count = count.saturating_sub(1);
+            }
+            *self.surplus_results.entry(key).or_default() += 1;
+        }
+        self.streaming.remove(&key);
+        self.received.push_back(ExecutorMessage::JobResult(submission_id,
+                                                           (job_id, Err(error.into()))));
+    }
+
+    // Send a `Job` for execution to executors, or if they are all busy hold it back until they
+    // are not, so that jobs of higher `Priority` functions held back meanwhile are sent before it
     pub(crate) fn send_job_for_execution(&mut self, payload: &Payload) -> Result<()> {
+        if self.saturated() {
+            trace!("Job #{}: Held back with priority {:?}", payload.job_id, payload.priority);
+            self.pending_jobs.entry(payload.priority).or_default().push_back(payload.clone());
+            return Ok(());
+        }
+
+        self.dispatch(payload)
+    }
+
+    // Send a `Job` to executors for execution
+    // Lib jobs are sent to executors that can run them natively if there are any, falling back to
+    // the general lib job queue if they are not able to accept it. Small lib jobs are added to a
+    // batch instead, if batching, that is sent once full or when `flush()`ed
+    fn dispatch(&mut self, payload: &Payload) -> Result<()> {
+        *self.jobs_in_flight.entry(payload.submission_id).or_default() += 1;
+        self.outstanding.insert((payload.submission_id, payload.job_id),
+                                (payload.clone(), Instant::now()));
+        let span = info_span!("dispatch", job_id = payload.job_id, queue = tracing::field::Empty)
+            .entered();
         if payload.implementation_url.scheme() == "lib" {
-            self.lib_job_socket.send(serde_json::to_string(payload)?.as_bytes(), 0)
-                .map_err(|e| format!("Could not send context Job for execution: {e}"))?;
+            let message = serde_json::to_string(payload)?;
+            if self.batch_size > 1 && message.len() <= MAX_BATCHED_JOB_BYTES {
+                let native = self.runs_natively(payload);
+                span.record("queue", if native { "native batch" } else { "lib batch" });
+                let batch = if native { &mut self.native_batch } else { &mut self.lib_batch };
+                batch.push(payload.clone());
+                trace!("Job #{}: Payload added to batch", payload.job_id);
+                if batch.len() >= self.batch_size {
+                    self.send_batch(native)?;
+                }
+                return Ok(());
+            }
+
+            if self.runs_natively(payload) &&
+                self.transport.send_job(JobQueue::Native, &message).is_ok() {
+                span.record("queue", "native");
+                trace!("Job #{}: Payload sent for native execution", payload.job_id);
+                return Ok(());
+            }
+            span.record("queue", "lib");
+            self.transport.send_job(JobQueue::Lib, &message)
+                .chain_err(|| "Could not send lib Job for execution")?;
         } else {
-            self.general_job_socket.send(serde_json::to_string(payload)?.as_bytes(), 0)
-                .map_err(|e| format!("Could not send Job for execution: {e}"))?;
+            span.record("queue", "general");
+            self.transport.send_job(JobQueue::General, &serde_json::to_string(payload)?)
+                .chain_err(|| "Could not send Job for execution")?;
         }
 
         trace!("Job #{}: Payload sent for execution", payload.job_id);
@@ -110,17 +538,84 @@ impl Dispatcher {
         Ok(())
     }
 
-    /// Send a "DONE"" message to subscribed executors on the `control_socket`
+    // Send the jobs waiting in the native or lib batch to executors in one message. A native batch
+    // that executors running them natively are not able to accept is sent on the lib job queue
+    fn send_batch(&mut self, native: bool) -> Result<()> {
+        let batch = mem::take(if native { &mut self.native_batch } else { &mut self.lib_batch });
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let message = serde_json::to_string(&batch)?;
+        if native && self.transport.send_job(JobQueue::Native, &message).is_ok() {
+            trace!("Batch of {} jobs sent for native execution", batch.len());
+            return Ok(());
+        }
+        self.transport.send_job(JobQueue::Lib, &message)
+            .chain_err(|| "Could not send batch of Jobs for execution")?;
+        trace!("Batch of {} jobs sent for execution", batch.len());
+
+        Ok(())
+    }
+
+    // Send the jobs waiting to be sent in batches, even if the batches are not full
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        self.send_batch(true)?;
+        self.send_batch(false)
+    }
+
+    // Send a "CLEAR" message to subscribed executors on the control channel, so that they discard
+    // the results of jobs for pure functions memoized during the execution of previous flows
+    pub(crate) fn send_clear(&mut self) -> Result<()> {
+        debug!("Dispatcher announcing CLEAR");
+        // jobs of flows executed previously are no longer waited for
+        self.outstanding.clear();
+        self.surplus_results.clear();
+        self.streaming.clear();
+        self.transport.send_control("CLEAR")
+            .chain_err(|| "Could not send 'CLEAR' message")
+    }
+
+    // Send a "CANCEL" message with the id of a submission whose execution has been cancelled to
+    // subscribed executors on the control channel, so that they discard its jobs not yet executed.
+    // Its jobs still waiting to be sent to executors are purged
+    pub(crate) fn send_cancel(&mut self, submission_id: usize) -> Result<()> {
+        debug!("Dispatcher announcing CANCEL of submission #{submission_id}");
+        self.jobs_in_flight.remove(&submission_id);
+        self.outstanding.retain(|(id, _), _| *id != submission_id);
+        for queue in self.pending_jobs.values_mut() {
+            queue.retain(|payload| payload.submission_id != submission_id);
+        }
+        self.native_batch.retain(|payload| payload.submission_id != submission_id);
+        self.lib_batch.retain(|payload| payload.submission_id != submission_id);
+        self.transport.send_control(&format!("CANCEL {submission_id}"))
+            .chain_err(|| "Could not send 'CANCEL' message")
+    }
+
+    /// Send a "DONE"" message to subscribed executors on the control channel
     ///
     /// # Errors
     ///
-    /// Returns an error if the message bytes cannot be sent over the control socket
+    /// Returns an error if the message bytes cannot be sent over the control channel
     ///
     pub fn send_done(&mut self) -> Result<()> {
         debug!("Dispatcher announcing DONE");
-        self.control_socket.send("DONE".as_bytes(), DONTWAIT)
+        self.transport.send_control("DONE")
             .chain_err(|| "Could not send 'DONE' message")
     }
+
+    /// Send a "SHUTDOWN" message to subscribed executors on the control channel, asking them
+    /// to finish executing the jobs they have received and exit, instead of waiting for more
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message bytes cannot be sent over the control channel
+    ///
+    pub fn send_shutdown(&mut self) -> Result<()> {
+        debug!("Dispatcher announcing SHUTDOWN");
+        self.transport.send_control("SHUTDOWN")
+            .chain_err(|| "Could not send 'SHUTDOWN' message")
+    }
 }
 
 impl Drop for Dispatcher {
@@ -133,33 +628,55 @@ impl Drop for Dispatcher {
 
 #[cfg(test)]
 mod test {
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     use portpicker::pick_unused_port;
-    use serde_json::Value;
+    use serde_json::{json, Value};
     use serial_test::serial;
     use url::Url;
 
     use flowcore::DONT_RUN_AGAIN;
     use flowcore::errors::*;
+    use flowcore::model::metrics::ExecutorMetrics;
+    use flowcore::model::runtime_function::Priority;
     use flowcore::RunAgain;
 
-    use crate::job::Payload;
+    use crate::dispatcher::{ExecutorMessage, LostExecutor};
+    use crate::executor::{Capabilities, Heartbeat};
+    use crate::job::{Chunk, Payload};
+    use crate::transport::{ChannelJobTransport, CoordinatorMessage, ExecutorConnector, JobQueue};
+
+    fn lib_payload(implementation_url: &str) -> Payload {
+        Payload {
+            job_id: 0,
+            input_set: vec![],
+            implementation_url: Url::parse(implementation_url).expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        }
+    }
 
-    fn get_bind_addresses(ports: (u16, u16, u16, u16)) -> (String, String, String, String) {
+    fn get_bind_addresses(ports: (u16, u16, u16, u16, u16)) -> (String, String, String, String, String) {
         (
             format!("tcp://*:{}", ports.0),
             format!("tcp://*:{}", ports.1),
             format!("tcp://*:{}", ports.2),
             format!("tcp://*:{}", ports.3),
+            format!("tcp://*:{}", ports.4),
         )
     }
 
-    fn get_four_ports() -> (u16, u16, u16, u16) {
+    fn get_five_ports() -> (u16, u16, u16, u16, u16) {
         (pick_unused_port().expect("No ports free"),
             pick_unused_port().expect("No ports free"),
             pick_unused_port().expect("No ports free"),
             pick_unused_port().expect("No ports free"),
+            pick_unused_port().expect("No ports free"),
         )
     }
 
@@ -167,7 +684,7 @@ mod test {
     #[serial]
     fn test_constructor() {
         let dispatcher = super::Dispatcher::new(
-            &get_bind_addresses(get_four_ports()));
+            &get_bind_addresses(get_five_ports()), None);
         assert!(dispatcher.is_ok());
     }
 
@@ -175,7 +692,7 @@ mod test {
     #[serial]
     fn set_timeout_to_none() {
         let mut dispatcher = super::Dispatcher::new(
-            &get_bind_addresses(get_four_ports())
+            &get_bind_addresses(get_five_ports()), None
         ).expect("Could not create dispatcher");
         assert!(dispatcher.set_results_timeout(None).is_ok());
     }
@@ -184,7 +701,7 @@ mod test {
     #[serial]
     fn set_timeout() {
         let mut dispatcher = super::Dispatcher::new(
-            &get_bind_addresses(get_four_ports())
+            &get_bind_addresses(get_five_ports()), None
         ).expect("Could not create dispatcher");
         assert!(dispatcher.set_results_timeout(Some(Duration::from_millis(10))).is_ok());
     }
@@ -196,11 +713,18 @@ mod test {
             job_id: 0,
             input_set: vec![],
             implementation_url: Url::parse("lib://flowstdlib/math/add").expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
         };
 
-        let ports = get_four_ports();
+        let ports = get_five_ports();
         let mut dispatcher = super::Dispatcher::new(
-            &get_bind_addresses(ports)
+            &get_bind_addresses(ports), None
         ).expect("Could not create dispatcher");
 
         let context = zmq::Context::new();
@@ -219,11 +743,18 @@ mod test {
             job_id: 0,
             input_set: vec![],
             implementation_url: Url::parse("context://stdio/stdout").expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
         };
 
-        let ports = get_four_ports();
+        let ports = get_five_ports();
         let mut dispatcher = super::Dispatcher::new(
-            &get_bind_addresses(ports)
+            &get_bind_addresses(ports), None
         ).expect("Could not create dispatcher");
 
         let context = zmq::Context::new();
@@ -238,9 +769,9 @@ mod test {
     #[test]
     #[serial]
     fn get_job() {
-        let ports = get_four_ports();
+        let ports = get_five_ports();
         let mut dispatcher = super::Dispatcher::new(
-            &get_bind_addresses(ports)
+            &get_bind_addresses(ports), None
         ).expect("Could not create dispatcher");
 
         let context = zmq::Context::new();
@@ -249,10 +780,409 @@ mod test {
         results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
             .expect("Could not connect to PULL end of results socket");
         let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
-        results_sink.send(serde_json::to_string(&(0, result))
+        results_sink.send(serde_json::to_string(&(0, 0, result))
                               .expect("Could not convert to serde")
                               .as_bytes(), 0).expect("Could not send result of Job");
 
         assert!(dispatcher.get_next_result(true).is_ok());
     }
+
+    fn advertise(results_sink: &zmq::Socket, native_libs: Vec<Url>) {
+        let capabilities = Capabilities {
+            executor_id: String::new(),
+            native_libs,
+            threads: 1,
+            architecture: std::env::consts::ARCH.into(),
+        };
+        results_sink.send(serde_json::to_string(&capabilities)
+                              .expect("Could not convert to serde")
+                              .as_bytes(), 0).expect("Could not send capabilities");
+        let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
+        results_sink.send(serde_json::to_string(&(0, 0, result))
+                              .expect("Could not convert to serde")
+                              .as_bytes(), 0).expect("Could not send result of Job");
+    }
+
+    #[test]
+    #[serial]
+    fn capabilities_are_recorded() {
+        let ports = get_five_ports();
+        let mut dispatcher = super::Dispatcher::new(
+            &get_bind_addresses(ports), None
+        ).expect("Could not create dispatcher");
+
+        let context = zmq::Context::new();
+        let results_sink = context.socket(zmq::PUSH)
+            .expect("Could not create PUSH end of results socket");
+        results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
+            .expect("Could not connect to PULL end of results socket");
+        advertise(&results_sink, vec![Url::parse("lib://flowstdlib").expect("Could not parse Url")]);
+
+        let message = dispatcher.get_next_result(true).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (0, _))));
+        assert_eq!(dispatcher.native_executors.len(), 1);
+        assert!(dispatcher.runs_natively(&lib_payload("lib://flowstdlib/math/add")));
+        assert!(!dispatcher.runs_natively(&lib_payload("lib://otherlib/math/add")));
+    }
+
+    #[test]
+    #[serial]
+    fn executor_metrics_are_recorded() {
+        let ports = get_five_ports();
+        let mut dispatcher = super::Dispatcher::new(
+            &get_bind_addresses(ports), None
+        ).expect("Could not create dispatcher");
+        assert!(dispatcher.updated_executor_metrics().is_none());
+
+        let context = zmq::Context::new();
+        let results_sink = context.socket(zmq::PUSH)
+            .expect("Could not create PUSH end of results socket");
+        results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
+            .expect("Could not connect to PULL end of results socket");
+        let metrics = ExecutorMetrics {
+            executor_id: "test".into(),
+            threads: 1,
+            jobs_executed: 3,
+            average_job_ms: 2.0,
+            implementation_cache_hits: 2,
+            load: 0.5,
+        };
+        results_sink.send(serde_json::to_string(&metrics)
+                              .expect("Could not convert to serde")
+                              .as_bytes(), 0).expect("Could not send metrics");
+        advertise(&results_sink, vec![]);
+
+        let message = dispatcher.get_next_result(true).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (0, _))));
+        assert_eq!(dispatcher.updated_executor_metrics(), Some(vec![metrics]));
+        assert!(dispatcher.updated_executor_metrics().is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn batch_of_jobs_sent_and_results_received() {
+        let ports = get_five_ports();
+        let mut dispatcher = super::Dispatcher::new(
+            &get_bind_addresses(ports), None
+        ).expect("Could not create dispatcher");
+        dispatcher.set_batch_size(Some(2));
+
+        let context = zmq::Context::new();
+        let job_source = context.socket(zmq::PULL)
+            .expect("Could not create PULL end of job socket");
+        job_source.connect(&format!("tcp://127.0.0.1:{}", ports.0))
+            .expect("Could not connect to PUSH end of job socket");
+        job_source.set_rcvtimeo(1000).expect("Could not set timeout");
+
+        for job_id in [1, 2] {
+            let mut payload = lib_payload("lib://flowstdlib/math/add");
+            payload.job_id = job_id;
+            dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+        }
+
+        let msg = job_source.recv_msg(0).expect("Batch of jobs was not sent");
+        let batch: Vec<Payload> = serde_json::from_str(msg.as_str().expect("Not a str"))
+            .expect("Could not deserialize batch of Payloads");
+        assert_eq!(batch.iter().map(|payload| payload.job_id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let results_sink = context.socket(zmq::PUSH)
+            .expect("Could not create PUSH end of results socket");
+        results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
+            .expect("Could not connect to PULL end of results socket");
+        let results: super::BatchResults = vec![
+            (0, 1, Ok((Some(json!(1)), DONT_RUN_AGAIN))),
+            (0, 2, Ok((Some(json!(2)), DONT_RUN_AGAIN))),
+        ];
+        results_sink.send(serde_json::to_string(&results)
+                              .expect("Could not convert to serde")
+                              .as_bytes(), 0).expect("Could not send results of batch");
+
+        let first = dispatcher.get_next_result(true).expect("Could not get result");
+        assert!(matches!(first, ExecutorMessage::JobResult(0, (1, _))));
+        assert!(dispatcher.wait_for_result(Duration::ZERO).expect("Could not wait for result"));
+        let second = dispatcher.get_next_result(false).expect("Could not get result");
+        assert!(matches!(second, ExecutorMessage::JobResult(0, (2, _))));
+    }
+
+    #[test]
+    #[serial]
+    fn executors_without_native_libs_not_used_for_native_jobs() {
+        let ports = get_five_ports();
+        let mut dispatcher = super::Dispatcher::new(
+            &get_bind_addresses(ports), None
+        ).expect("Could not create dispatcher");
+
+        let context = zmq::Context::new();
+        let results_sink = context.socket(zmq::PUSH)
+            .expect("Could not create PUSH end of results socket");
+        results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
+            .expect("Could not connect to PULL end of results socket");
+        advertise(&results_sink, vec![]);
+
+        assert!(dispatcher.get_next_result(true).is_ok());
+        assert!(dispatcher.native_executors.is_empty());
+        assert!(!dispatcher.runs_natively(&lib_payload("lib://flowstdlib/math/add")));
+    }
+
+    #[test]
+    #[serial]
+    fn send_lib_job_to_native_executor() {
+        let ports = get_five_ports();
+        let mut dispatcher = super::Dispatcher::new(
+            &get_bind_addresses(ports), None
+        ).expect("Could not create dispatcher");
+
+        let context = zmq::Context::new();
+        let native_job_source = context.socket(zmq::PULL)
+            .expect("Could not create PULL end of native job socket");
+        native_job_source.connect(&format!("tcp://127.0.0.1:{}", ports.4))
+            .expect("Could not connect to PUSH end of native job socket");
+        let results_sink = context.socket(zmq::PUSH)
+            .expect("Could not create PUSH end of results socket");
+        results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
+            .expect("Could not connect to PULL end of results socket");
+        advertise(&results_sink, vec![Url::parse("lib://flowstdlib").expect("Could not parse Url")]);
+        assert!(dispatcher.get_next_result(true).is_ok());
+
+        let mut payload = lib_payload("lib://flowstdlib/math/add");
+        payload.job_id = 42;
+        dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+
+        native_job_source.set_rcvtimeo(1000).expect("Could not set timeout");
+        let msg = native_job_source.recv_msg(0).expect("Job was not sent to native executor");
+        let received: Payload = serde_json::from_str(msg.as_str().expect("Not a str"))
+            .expect("Could not deserialize Payload");
+        assert_eq!(received.job_id, 42);
+    }
+
+    #[test]
+    #[serial]
+    fn higher_priority_jobs_dispatched_first_when_saturated() {
+        let ports = get_five_ports();
+        let mut dispatcher = super::Dispatcher::new(
+            &get_bind_addresses(ports), None
+        ).expect("Could not create dispatcher");
+
+        let context = zmq::Context::new();
+        let context_job_source = context.socket(zmq::PULL)
+            .expect("Could not create PULL end of context-job socket");
+        context_job_source.connect(&format!("tcp://127.0.0.1:{}", ports.1))
+            .expect("Could not connect to PUSH end of context-job socket");
+        context_job_source.set_rcvtimeo(1000).expect("Could not set timeout");
+        let results_sink = context.socket(zmq::PUSH)
+            .expect("Could not create PUSH end of results socket");
+        results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
+            .expect("Could not connect to PULL end of results socket");
+        advertise(&results_sink, vec![]);
+        assert!(dispatcher.get_next_result(true).is_ok());
+
+        // the executor's single thread is busy with the first job, so the others are held back
+        for (job_id, priority) in [(1, Priority::Normal), (2, Priority::Low), (3, Priority::High)] {
+            let mut payload = lib_payload("context://stdio/stdout");
+            payload.job_id = job_id;
+            payload.priority = priority;
+            dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+        }
+
+        let mut received_job_ids = vec![];
+        for _ in 0..3 {
+            let msg = context_job_source.recv_msg(0).expect("Job was not sent");
+            let received: Payload = serde_json::from_str(msg.as_str().expect("Not a str"))
+                .expect("Could not deserialize Payload");
+            received_job_ids.push(received.job_id);
+
+            let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
+            results_sink.send(serde_json::to_string(&(0, received.job_id, result))
+                                  .expect("Could not convert to serde")
+                                  .as_bytes(), 0).expect("Could not send result of Job");
+            assert!(dispatcher.get_next_result(true).is_ok());
+        }
+
+        assert_eq!(received_job_ids, vec![1, 3, 2]);
+    }
+
+    #[test]
+    #[serial]
+    fn get_chunk() {
+        let ports = get_five_ports();
+        let mut dispatcher = super::Dispatcher::new(
+            &get_bind_addresses(ports), None
+        ).expect("Could not create dispatcher");
+
+        let context = zmq::Context::new();
+        let results_sink = context.socket(zmq::PUSH)
+            .expect("Could not create PUSH end of results socket");
+        results_sink.connect(&format!("tcp://127.0.0.1:{}", ports.2))
+            .expect("Could not connect to PULL end of results socket");
+        let chunk = Chunk { submission_id: 1, job_id: 3, elements: vec![json!(1), json!(2)] };
+        results_sink.send(serde_json::to_string(&chunk)
+                              .expect("Could not convert to serde")
+                              .as_bytes(), 0).expect("Could not send chunk");
+
+        match dispatcher.get_next_result(true).expect("Could not get chunk") {
+            ExecutorMessage::Chunk(received) => assert_eq!(received, chunk),
+            ExecutorMessage::JobResult(..) => panic!("Expected a Chunk"),
+        }
+    }
+
+    #[test]
+    fn jobs_dispatched_over_channel_transport() {
+        let transport = ChannelJobTransport::new();
+        let mut executor = transport.connector(JobQueue::General)
+            .expect("Could not get connector")
+            .connect(false)
+            .expect("Could not connect executor");
+        let mut dispatcher = super::Dispatcher::with_transport(Box::new(transport));
+
+        let mut payload = lib_payload("context://stdio/stdout");
+        payload.job_id = 7;
+        dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+
+        let messages = executor.receive(Duration::from_secs(1)).expect("Could not receive job");
+        let Some(CoordinatorMessage::Job(job)) = messages.first() else {
+            panic!("Job was not sent")
+        };
+        let received: Payload = serde_json::from_str(job).expect("Could not deserialize Payload");
+        assert_eq!(received.job_id, 7);
+
+        let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
+        executor.send(&serde_json::to_string(&(0, 7, result)).expect("Could not convert to serde"))
+            .expect("Could not send result of Job");
+        let message = dispatcher.get_next_result(true).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (7, _))));
+    }
+
+    #[test]
+    fn heartbeat_records_jobs_held() {
+        let transport = ChannelJobTransport::new();
+        let executor = transport.connector(JobQueue::General)
+            .expect("Could not get connector")
+            .connect(false)
+            .expect("Could not connect executor");
+        let mut dispatcher = super::Dispatcher::with_transport(Box::new(transport));
+
+        let heartbeat = Heartbeat { executor_id: "test".into(), jobs: vec![(0, 7)] };
+        executor.send(&serde_json::to_string(&heartbeat).expect("Could not convert to serde"))
+            .expect("Could not send heartbeat");
+        let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
+        executor.send(&serde_json::to_string(&(0, 7, result)).expect("Could not convert to serde"))
+            .expect("Could not send result of Job");
+
+        assert!(dispatcher.get_next_result(true).is_ok());
+        let liveness = dispatcher.executors.get("test").expect("Executor was not recorded");
+        assert!(liveness.jobs.contains(&(0, 7)));
+    }
+
+    #[test]
+    fn lost_executor_jobs_requeued() {
+        let transport = ChannelJobTransport::new();
+        let mut executor = transport.connector(JobQueue::General)
+            .expect("Could not get connector")
+            .connect(false)
+            .expect("Could not connect executor");
+        let mut dispatcher = super::Dispatcher::with_transport(Box::new(transport));
+
+        let mut payload = lib_payload("context://stdio/stdout");
+        payload.job_id = 7;
+        dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+        let heartbeat = Heartbeat { executor_id: "lost".into(), jobs: vec![(0, 7)] };
+        dispatcher.heartbeat_received(heartbeat);
+
+        // the executor last responded too long ago, and it is time to send another heartbeat
+        let now = Instant::now();
+        if let Some(liveness) = dispatcher.executors.get_mut("lost") {
+            liveness.last_heartbeat = now.checked_sub(Duration::from_secs(10))
+                .expect("Could not age heartbeat");
+        }
+        dispatcher.last_heartbeat = now.checked_sub(Duration::from_secs(2))
+            .expect("Could not age heartbeat");
+        dispatcher.check_executors().expect("Could not check executors");
+
+        assert_eq!(dispatcher.take_lost_executors(),
+                   vec![LostExecutor { executor_id: "lost".into(), jobs_requeued: 1 }]);
+        assert!(dispatcher.take_lost_executors().is_empty());
+
+        let mut job_ids = vec![];
+        for _ in 0..2 {
+            for message in executor.receive(Duration::from_secs(1)).expect("Could not receive") {
+                if let CoordinatorMessage::Job(job) = message {
+                    let received: Payload = serde_json::from_str(&job)
+                        .expect("Could not deserialize Payload");
+                    job_ids.push(received.job_id);
+                }
+            }
+        }
+        assert_eq!(job_ids, vec![7, 7]);
+
+        // the result of the job is returned once, even if both executions return one
+        for _ in 0..2 {
+            let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
+            executor.send(&serde_json::to_string(&(0, 7, result))
+                .expect("Could not convert to serde"))
+                .expect("Could not send result of Job");
+        }
+        let message = dispatcher.get_next_result(true).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (7, _))));
+        assert!(dispatcher.get_next_result(false).is_err());
+    }
+
+    #[test]
+    fn abandoned_job_fails_and_late_result_discarded() {
+        let transport = ChannelJobTransport::new();
+        let executor = transport.connector(JobQueue::General)
+            .expect("Could not get connector")
+            .connect(false)
+            .expect("Could not connect executor");
+        let mut dispatcher = super::Dispatcher::with_transport(Box::new(transport));
+
+        let mut payload = lib_payload("context://stdio/stdout");
+        payload.job_id = 7;
+        dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+        dispatcher.abandon(0, 7, "Timed out".into());
+
+        let message = dispatcher.get_next_result(false).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (7, Err(_)))));
+
+        let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
+        executor.send(&serde_json::to_string(&(0, 7, result)).expect("Could not convert to serde"))
+            .expect("Could not send result of Job");
+        assert!(dispatcher.wait_for_result(Duration::from_secs(1)).expect("Could not wait"));
+        assert!(dispatcher.get_next_result(false).is_err(), "Late result should be discarded");
+    }
+
+    #[test]
+    fn abandoned_held_back_job_not_sent() {
+        let transport = ChannelJobTransport::new();
+        let mut executor = transport.connector(JobQueue::General)
+            .expect("Could not get connector")
+            .connect(false)
+            .expect("Could not connect executor");
+        let mut dispatcher = super::Dispatcher::with_transport(Box::new(transport));
+        // the executor's single thread is busy with the first job, so the second is held back
+        dispatcher.executor_threads = 1;
+
+        for job_id in [1, 2] {
+            let mut payload = lib_payload("context://stdio/stdout");
+            payload.job_id = job_id;
+            dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+        }
+        assert!(!dispatcher.held_back(0, 1));
+        assert!(dispatcher.held_back(0, 2));
+
+        dispatcher.abandon(0, 2, "Timed out".into());
+        assert!(!dispatcher.held_back(0, 2));
+        let message = dispatcher.get_next_result(false).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (2, Err(_)))));
+
+        let messages = executor.receive(Duration::from_secs(1)).expect("Could not receive job");
+        assert!(matches!(messages.as_slice(), [CoordinatorMessage::Job(_)]));
+        let result:Result<(Option<Value>, RunAgain)> = Ok((None, DONT_RUN_AGAIN));
+        executor.send(&serde_json::to_string(&(0, 1, result)).expect("Could not convert to serde"))
+            .expect("Could not send result of Job");
+        let message = dispatcher.get_next_result(true).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (1, Ok(_)))));
+
+        assert!(executor.receive(Duration::from_millis(100)).expect("Could not receive")
+            .is_empty(), "Abandoned job should not be sent");
+    }
 }
@@ -0,0 +1,162 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde_derive::Serialize;
+use serde_json::Value;
+
+use flowcore::errors::{Result, ResultExt};
+
+use crate::block::Block;
+
+/// An `Event` that happened during the execution of a flow, that is sent to an [`EventSink`]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event")]
+pub enum Event {
+    /// A job was dispatched for execution
+    JobDispatched {
+        /// The id of the job
+        job_id: usize,
+        /// The id of the function the job is for
+        function_id: usize,
+        /// The id of the flow the function is in
+        flow_id: usize,
+        /// The values of the inputs of the job
+        inputs: Vec<Arc<Value>>,
+    },
+    /// A job completed, successfully or with an error
+    JobCompleted {
+        /// The id of the job
+        job_id: usize,
+        /// The id of the function the job is for
+        function_id: usize,
+        /// The id of the flow the function is in
+        flow_id: usize,
+        /// The value output by the job, if any
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<Value>,
+        /// The error the job failed with, if it failed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A value was sent from the output of a function to an input of a function
+    ValueSent {
+        /// The id of the function that sent the value
+        source_id: usize,
+        /// The id of the function the value was sent to
+        destination_id: usize,
+        /// The number of the input the value was sent to
+        input_number: usize,
+        /// The value sent
+        value: Arc<Value>,
+    },
+    /// A function was blocked from sending to another function whose input is full
+    BlockCreated(Block),
+    /// A block between two functions was removed
+    BlockRemoved(Block),
+}
+
+/// An `EventSink` receives the [`Event`]s that happen during the execution of flows by a
+/// [Coordinator][crate::coordinator::Coordinator], for example to record them for later analysis
+pub trait EventSink {
+    /// Receive an `Event`, in the order they happened
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event could not be handled
+    fn event(&mut self, event: &Event) -> Result<()>;
+
+    /// Called when execution of a flow has ended, so any buffered events can be written
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if buffered events could not be written
+    fn flush(&mut self) -> Result<()>;
+}
+
+// An `Event` as written to the log, with the time since the log was created
+#[derive(Serialize)]
+struct LoggedEvent<'a> {
+    elapsed_micros: u64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// A `JsonEventLog` is an [`EventSink`] that writes each [`Event`] to a file as a line of JSON
+/// (newline delimited JSON), with an `elapsed_micros` field of the time since it was created
+pub struct JsonEventLog {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl JsonEventLog {
+    /// Create a `JsonEventLog` that writes to the file at `path`, replacing it if it exists
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be created
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .chain_err(|| format!("Could not create event log file '{}'", path.display()))?;
+        Ok(JsonEventLog {
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+        })
+    }
+}
+
+impl EventSink for JsonEventLog {
+    fn event(&mut self, event: &Event) -> Result<()> {
+        let logged_event = LoggedEvent {
+            elapsed_micros: u64::try_from(self.start.elapsed().as_micros()).unwrap_or(u64::MAX),
+            event,
+        };
+        serde_json::to_writer(&mut self.writer, &logged_event)?;
+        self.writer.write_all(b"\n").chain_err(|| "Could not write to event log")
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush().chain_err(|| "Could not flush event log")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    use crate::block::Block;
+
+    use super::{Event, EventSink, JsonEventLog};
+
+    #[test]
+    fn events_logged_as_json_lines() {
+        let dir = tempdir().expect("Could not create temp dir");
+        let path = dir.path().join("events.json");
+        let mut log = JsonEventLog::create(&path).expect("Could not create event log");
+        log.event(&Event::JobDispatched { job_id: 1, function_id: 0, flow_id: 0,
+            inputs: vec![Arc::new(json!(42))] }).expect("Could not log event");
+        log.event(&Event::ValueSent { source_id: 0, destination_id: 1, input_number: 0,
+            value: Arc::new(json!("hello")) }).expect("Could not log event");
+        log.event(&Event::BlockCreated(Block::new(0, 1, 0, 0, 0)))
+            .expect("Could not log event");
+        log.flush().expect("Could not flush event log");
+
+        let contents = fs::read_to_string(&path).expect("Could not read event log");
+        let events: Vec<Value> = contents.lines()
+            .map(|line| serde_json::from_str(line).expect("Invalid JSON line"))
+            .collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events.first().and_then(|e| e.get("event")), Some(&json!("JobDispatched")));
+        assert_eq!(events.first().and_then(|e| e.get("inputs")), Some(&json!([42])));
+        assert!(events.first().and_then(|e| e.get("elapsed_micros")).is_some());
+        assert_eq!(events.get(1).and_then(|e| e.get("value")), Some(&json!("hello")));
+        assert_eq!(events.get(2).and_then(|e| e.get("event")), Some(&json!("BlockCreated")));
+        assert_eq!(events.get(2).and_then(|e| e.get("blocking_function_id")), Some(&json!(1)));
+    }
+}
@@ -1,22 +1,190 @@
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::num::NonZeroUsize;
 use std::panic;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, Once, RwLock};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use log::{debug, error, info, trace};
+use lru::LruCache;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info_span;
 use url::Url;
 
 use flowcore::errors::{bail, Result, ResultExt};
 use flowcore::model::lib_manifest::{
     ImplementationLocator::Native, ImplementationLocator::RelativePath, LibraryManifest,
 };
+use flowcore::model::lock_file::pinned_version;
+use flowcore::model::metrics::ExecutorMetrics;
+use flowcore::model::schema;
 use flowcore::provider::Provider;
-use flowcore::Implementation;
+use flowcore::{Implementation, RunAgain};
 
-use crate::job::Payload;
+use crate::job::{Chunk, Payload};
+use crate::security::CurveKeys;
+use crate::transport::{CoordinatorMessage, ExecutorConnector, ExecutorTransport, ZmqConnector};
 use crate::wasm;
 
+// The results of jobs for pure functions, the least recently used being discarded when there are
+// `MEMOIZED_RESULTS_CAPACITY` of them. The key is the implementation Url and the serialized
+// input set of the job
+type MemoizedResults = LruCache<(Url, String), (Option<Value>, RunAgain)>;
+
+// The maximum number of results of jobs for pure functions memoized by an executor, so that
+// memory use is bounded for functions whose jobs rarely have the same inputs
+const MEMOIZED_RESULTS_CAPACITY: NonZeroUsize = NonZeroUsize::new(1024)
+    .expect("The capacity of memoized results must not be zero");
+
+// The results of a batch of jobs, to be sent back to the coordinator together. Each is the id of
+// the submission the job is for, the job id and the result of executing it
+type BatchResults = Vec<(usize, usize, Result<(Option<Value>, RunAgain)>)>;
+
+// How long executor threads wait for a job or control message before checking if they have been
+// asked to shut down
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// The jobs received by an executor that it has not returned the results of yet, each as the id of
+// the submission and the id of the job, shared by all its threads
+type JobsHeld = Mutex<HashSet<(usize, usize)>>;
+
+// How often an executor reports its `ExecutorMetrics` to the coordinator, while executing jobs
+const METRICS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+// Counters of the jobs executed by all the threads of an executor
+#[derive(Default)]
+struct JobCounters {
+    jobs_executed: AtomicUsize,
+    busy_micros: AtomicU64,
+    implementation_cache_hits: AtomicUsize,
+}
+
+impl JobCounters {
+    // Count a job that took `duration` to execute
+    fn job_executed(&self, duration: Duration) {
+        self.jobs_executed.fetch_add(1, Ordering::Relaxed);
+        self.busy_micros.fetch_add(u64::try_from(duration.as_micros()).unwrap_or(u64::MAX),
+                                   Ordering::Relaxed);
+    }
+}
+
+// Reports the `ExecutorMetrics` of an executor to the coordinator periodically, from one of its
+// threads
+struct MetricsReporter {
+    executor_id: String,
+    threads: usize,
+    counters: Arc<JobCounters>,
+    last_report: Instant,
+    last_jobs_executed: usize,
+    last_busy_micros: u64,
+    last_load: f64,
+}
+
+impl MetricsReporter {
+    // Return the `ExecutorMetrics` since the last report, if it is time to report them and they
+    // have changed
+    #[allow(clippy::cast_precision_loss)]
+    fn due(&mut self) -> Option<ExecutorMetrics> {
+        let elapsed = self.last_report.elapsed();
+        if elapsed < METRICS_REPORT_INTERVAL {
+            return None;
+        }
+
+        let jobs_executed = self.counters.jobs_executed.load(Ordering::Relaxed);
+        let busy_micros = self.counters.busy_micros.load(Ordering::Relaxed);
+        // once idle, report that once, then nothing more until more jobs are executed
+        if jobs_executed == self.last_jobs_executed && self.last_load <= 0.0 {
+            return None;
+        }
+
+        let available_micros = elapsed.as_micros() as f64 * self.threads.max(1) as f64;
+        let load = (busy_micros.saturating_sub(self.last_busy_micros) as f64 / available_micros)
+            .min(1.0);
+        let average_job_ms = if jobs_executed == 0 {
+            0.0
+        } else {
+            busy_micros as f64 / 1000.0 / jobs_executed as f64
+        };
+
+        self.last_report = Instant::now();
+        self.last_jobs_executed = jobs_executed;
+        self.last_busy_micros = busy_micros;
+        self.last_load = load;
+
+        Some(ExecutorMetrics {
+            executor_id: self.executor_id.clone(),
+            threads: self.threads,
+            jobs_executed,
+            average_job_ms,
+            implementation_cache_hits: self.counters.implementation_cache_hits
+                .load(Ordering::Relaxed),
+            load,
+        })
+    }
+
+    // Send the `ExecutorMetrics` to the coordinator on `results_sink`, if it is time to
+    fn report(&mut self, results_sink: &dyn ExecutorTransport) -> Result<()> {
+        if let Some(metrics) = self.due() {
+            trace!("Reporting executor metrics: {metrics}");
+            results_sink.send(&serde_json::to_string(&metrics)?)
+                .chain_err(|| "Could not report executor metrics")?;
+        }
+        Ok(())
+    }
+}
+
+/// `Capabilities` describe what an `Executor` can do. They are advertised to the `Dispatcher` it
+/// gets jobs from, so that jobs can be sent preferentially to executors that can run them natively
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The id of the executor, that it also sends in its `Heartbeat`s
+    #[serde(default)]
+    pub executor_id: String,
+    /// The libraries (e.g. `lib://flowstdlib`) that the executor has native implementations of
+    pub native_libs: Vec<Url>,
+    /// The number of threads the executor uses to run jobs in parallel
+    pub threads: usize,
+    /// The CPU architecture the executor is running on
+    pub architecture: String,
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let native_libs: Vec<&str> = self.native_libs.iter().map(Url::as_str).collect();
+        write!(f, "native libs: [{}], threads: {}, architecture: {}",
+               native_libs.join(", "), self.threads, self.architecture)
+    }
+}
+
+/// A `Heartbeat` is sent by an `Executor` to the `Dispatcher` it gets jobs from in response to
+/// each "HEARTBEAT" control message, to show that it is alive and which jobs it holds. If an
+/// executor stops sending them, the `Dispatcher` sends the jobs it held for execution again
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Heartbeat {
+    /// The id of the executor
+    pub executor_id: String,
+    /// The jobs received by the executor that it has not returned the results of yet, each as
+    /// the id of the submission and the id of the job
+    pub jobs: Vec<(usize, usize)>,
+}
+
+impl Capabilities {
+    /// Return true if the function implementation at `implementation_url` is in one of the
+    /// libraries the executor has a native implementation of
+    #[must_use]
+    pub fn runs_natively(&self, implementation_url: &Url) -> bool {
+        self.native_libs.iter().any(|lib_url|
+            lib_url.scheme() == implementation_url.scheme() &&
+                lib_url.host_str() == implementation_url.host_str())
+    }
+}
+
 /// An `Executor` struct is used to receive jobs, execute them and return results.
 /// It can load libraries and keep track of the `Function` `Implementations` loaded for use
 /// in job execution.
@@ -25,7 +193,17 @@ pub struct Executor {
     // (e.g. lib:://flowstdlib) and the entry is a tuple of the LibraryManifest
     // and the resolved Url of where the manifest was read from
     loaded_lib_manifests: Arc<RwLock<HashMap<Url, (LibraryManifest, Url)>>>,
-    executors: Vec<JoinHandle<()>>,
+    // The results of jobs for pure functions, shared by all executor threads
+    memoized_results: Arc<Mutex<MemoizedResults>>,
+    // Optional keys used to encrypt the connections to the job queues
+    keys: Option<CurveKeys>,
+    // Set to ask executor threads to stop taking new jobs and exit, shared by all of them
+    shutdown: Arc<AtomicBool>,
+    // An id that identifies this executor in the `ExecutorMetrics` it reports
+    id: String,
+    // Counters of the jobs executed by all executor threads, for the `ExecutorMetrics` reported
+    counters: Arc<JobCounters>,
+    executors: Vec<JoinHandle<usize>>,
 }
 
 impl Default for Executor {
@@ -42,6 +220,11 @@ impl Executor {
             loaded_lib_manifests: Arc::new(RwLock::new(
                 HashMap::<Url, (LibraryManifest, Url)>::new(),
             )),
+            memoized_results: Arc::new(Mutex::new(LruCache::new(MEMOIZED_RESULTS_CAPACITY))),
+            keys: None,
+            shutdown: Arc::new(AtomicBool::new(false)),
+            id: format!("{}-{:04x}", process::id(), rand::random::<u16>()),
+            counters: Arc::new(JobCounters::default()),
             executors: vec![],
         }
     }
@@ -71,55 +254,187 @@ impl Executor {
         Ok(())
     }
 
+    /// Set the `CurveKeys` to use to encrypt the connections to the job queues. They must be the
+    /// same keys as those used by the `Dispatcher` that sends the jobs, and must be set before
+    /// calling `start()`
+    pub fn set_keys(&mut self, keys: CurveKeys) {
+        self.keys = Some(keys);
+    }
+
+    /// Set the flag used to ask the executor threads to shut down. When it is set, each thread
+    /// finishes the job it is executing (if any) and exits without taking new ones. It is also
+    /// set when a "SHUTDOWN" control message is received from the `Dispatcher`, so that whoever
+    /// holds it can tell that a shutdown was requested. Must be set before calling `start()`
+    pub fn set_shutdown_flag(&mut self, shutdown: Arc<AtomicBool>) {
+        self.shutdown = shutdown;
+    }
+
+    /// Return the flag used to ask the executor threads to shut down
+    #[must_use]
+    pub fn shutdown_flag(&self) -> Arc<AtomicBool> {
+        self.shutdown.clone()
+    }
+
+    /// Return the `Capabilities` of this executor when started with `threads` threads
+    #[must_use]
+    pub fn capabilities(&self, threads: usize) -> Capabilities {
+        let native_libs = self.loaded_lib_manifests.read()
+            .map(|lib_manifests| lib_manifests.values()
+                .filter(|(manifest, _)| manifest.lib_url.scheme() == "lib" &&
+                    manifest.locators.values().any(|locator| matches!(locator, Native(_))))
+                .map(|(manifest, _)| manifest.lib_url.clone())
+                .collect())
+            .unwrap_or_default();
+
+        Capabilities {
+            executor_id: self.id.clone(),
+            native_libs,
+            threads,
+            architecture: std::env::consts::ARCH.into(),
+        }
+    }
+
     /// Start executing jobs, specifying:
     /// - the `Provider` to use to fetch implementation content
     /// - the number of executor threads
     /// - the address of the job socket to get jobs from
+    /// - the address of the job socket to get jobs for natively implemented libraries from
     /// - the address of the results socket to return results from executed jobs to
+    /// - the address of the control socket to receive control messages from
+    ///
+    /// The `Capabilities` of the executor are advertised over the results socket, and the
+    /// native job socket is only used if it has native implementations of some libraries
     pub fn start(
         &mut self,
         provider: &Arc<dyn Provider>,
         number_of_executors: usize,
         job_service: &str,
+        native_job_service: &str,
         results_service: &str,
         control_service: &str,
+    ) {
+        let connector = ZmqConnector::new(job_service, native_job_service, results_service,
+                                          control_service, self.keys.clone());
+        self.start_with_connector(provider, number_of_executors, Arc::new(connector));
+    }
+
+    /// Start executing jobs, with `number_of_executors` threads that use the `Provider` to fetch
+    /// implementation content, and each connect to the coordinator using `connector`.
+    /// e.g. a `ChannelConnector` to execute jobs of a coordinator in the same process without
+    /// any sockets
+    ///
+    /// The `Capabilities` of the executor are advertised to the coordinator, and jobs for
+    /// natively implemented libraries are only taken if it has native implementations of some.
+    /// Another thread responds to heartbeats from the coordinator while they execute jobs
+    pub fn start_with_connector(
+        &mut self,
+        provider: &Arc<dyn Provider>,
+        number_of_executors: usize,
+        connector: Arc<dyn ExecutorConnector>,
     ) {
         let loaded_implementations =
             Arc::new(RwLock::new(HashMap::<Url, Arc<dyn Implementation>>::new()));
+        let capabilities = self.capabilities(number_of_executors);
+        info!("Executor capabilities: {capabilities}");
+        let native_jobs = !capabilities.native_libs.is_empty();
+        let mut advertisement = Some(capabilities);
+        let jobs_held = Arc::new(JobsHeld::default());
+        let threads_running = Arc::new(AtomicUsize::new(number_of_executors));
 
         info!("Starting {number_of_executors} executor threads");
         for executor_number in 0..number_of_executors {
             let thread_provider = provider.clone();
-            let thread_context = zmq::Context::new();
+            let thread_connector = connector.clone();
             let thread_implementations = loaded_implementations.clone();
             let thread_loaded_manifests = self.loaded_lib_manifests.clone();
-            let results_sink = results_service.into();
-            let job_source = job_service.into();
-            let control_address = control_service.into();
+            let thread_memoized_results = self.memoized_results.clone();
+            let thread_shutdown = self.shutdown.clone();
+            let thread_counters = self.counters.clone();
+            let thread_jobs_held = jobs_held.clone();
+            let thread_threads_running = threads_running.clone();
+            // only one thread per executor advertises its capabilities and reports its metrics
+            let thread_advertisement = advertisement.take();
+            let thread_reporter = thread_advertisement.as_ref().map(|_| MetricsReporter {
+                executor_id: self.id.clone(),
+                threads: number_of_executors,
+                counters: self.counters.clone(),
+                last_report: Instant::now(),
+                last_jobs_executed: 0,
+                last_busy_micros: 0,
+                last_load: 0.0,
+            });
             self.executors.push(thread::spawn(move || {
                 trace!("Executor #{executor_number} entering execution loop");
-                if let Err(e) = execution_loop(
+                let jobs_executed = execution_loop(
                     &thread_provider,
                     &format!("Executor #{executor_number}"),
-                    &thread_context,
+                    thread_connector.as_ref(),
+                    native_jobs,
                     &thread_implementations,
                     &thread_loaded_manifests,
-                    job_source,
-                    results_sink,
-                    control_address,
-                ) {
+                    &thread_memoized_results,
+                    &thread_shutdown,
+                    &thread_counters,
+                    &thread_jobs_held,
+                    thread_advertisement,
+                    thread_reporter,
+                ).unwrap_or_else(|e| {
                     error!("Execution loop error: {e}");
-                }
+                    0
+                });
+                thread_threads_running.fetch_sub(1, Ordering::SeqCst);
+                jobs_executed
             }));
         }
+
+        let executor_id = self.id.clone();
+        thread::spawn(move || {
+            if let Err(e) = heartbeat_loop(connector.as_ref(), &executor_id, &jobs_held,
+                                           &threads_running) {
+                error!("Heartbeat loop error: {e}");
+            }
+        });
     }
 
-    /// Wait until all threads end
-    pub fn wait(self) {
-        for executor in self.executors {
-            let _ = executor.join();
+    /// Wait until all threads end, returning the total number of jobs they executed
+    pub fn wait(self) -> usize {
+        self.executors.into_iter()
+            .map(|executor| executor.join().unwrap_or(0))
+            .sum()
+    }
+}
+
+// Respond to each "HEARTBEAT" control message from the coordinator with a `Heartbeat` listing the
+// jobs held by the executor, until all its execution threads have exited. It has a connection of
+// its own, so that it responds while they are all busy executing jobs
+fn heartbeat_loop(
+    connector: &dyn ExecutorConnector,
+    executor_id: &str,
+    jobs_held: &JobsHeld,
+    threads_running: &AtomicUsize,
+) -> Result<()> {
+    let mut transport = connector.connect_control()?;
+
+    while threads_running.load(Ordering::SeqCst) > 0 {
+        for message in transport.receive(SHUTDOWN_POLL_INTERVAL)? {
+            match message {
+                CoordinatorMessage::Control(control) if control == "HEARTBEAT" => {
+                    let heartbeat = Heartbeat {
+                        executor_id: executor_id.into(),
+                        jobs: jobs_held.lock()
+                            .map_err(|_| "Could not lock jobs held")?
+                            .iter().copied().collect(),
+                    };
+                    transport.send(&serde_json::to_string(&heartbeat)?)
+                        .chain_err(|| "Could not send heartbeat")?;
+                }
+                CoordinatorMessage::Control(control) if control == "DONE" => return Ok(()),
+                _ => {}
+            }
         }
     }
+
+    Ok(())
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -127,180 +442,362 @@ impl Executor {
 fn execution_loop(
     provider: &Arc<dyn Provider>,
     name: &str,
-    context: &zmq::Context,
+    connector: &dyn ExecutorConnector,
+    native_jobs: bool,
     loaded_implementations: &Arc<RwLock<HashMap<Url, Arc<dyn Implementation>>>>,
     loaded_lib_manifests: &Arc<RwLock<HashMap<Url, (LibraryManifest, Url)>>>,
-    job_service: String,
-    results_service: String,
-    control_address: String,
-) -> Result<()> {
-    let job_source = context
-        .socket(zmq::PULL)
-        .map_err(|e| format!("Could not create PULL end of job socket: {e}"))?;
-    job_source.connect(&job_service).map_err(|e| {
-        format!("Could not connect to PULL end of job socket: '{job_service}' {e}",)
-    })?;
-
-    let results_sink = context
-        .socket(zmq::PUSH)
-        .map_err(|e| format!("Could not create PUSH end of results socket: {e}"))?;
-    results_sink
-        .connect(&results_service)
-        .map_err(|e| format!("Could not connect to PUSH end of results socket: {e}"))?;
-
-    let control_socket = context
-        .socket(zmq::SocketType::SUB)
-        .map_err(|e| format!("Could not create SUB end of control socket: {e}"))?;
-    control_socket
-        .connect(&control_address)
-        .map_err(|e| format!("Could not connect to SUB end of control socket: {e}"))?;
-    control_socket
-        .set_subscribe(&[])
-        .map_err(|e| format!("Could not subscribe to SUB end of control socket: {e}"))?;
+    memoized_results: &Arc<Mutex<MemoizedResults>>,
+    shutdown: &AtomicBool,
+    counters: &JobCounters,
+    jobs_held: &JobsHeld,
+    advertisement: Option<Capabilities>,
+    mut reporter: Option<MetricsReporter>,
+) -> Result<usize> {
+    let mut transport = connector.connect(native_jobs)?;
+
+    if let Some(capabilities) = advertisement {
+        transport.send(&serde_json::to_string(&capabilities)?)
+            .chain_err(|| "Could not advertise executor capabilities")?;
+    }
 
     let mut process_jobs = true;
+    let mut jobs_executed = 0;
+    // the ids of submissions that have been cancelled, whose jobs should not be executed
+    let mut cancelled_submissions = HashSet::<usize>::new();
 
     set_panic_hook();
 
-    let mut items: Vec<zmq::PollItem> = vec![
-        job_source.as_poll_item(zmq::POLLIN),
-        control_socket.as_poll_item(zmq::POLLIN),
-    ];
-
     while process_jobs {
-        trace!("{name} waiting for a job to execute or a DONE signal");
-        match zmq::poll(&mut items, -1).map_err(|_| "Error while polling for Jobs to execute") {
-            Ok(_) => {
-                if items
-                    .first()
-                    .ok_or("Could not get poll item 0")?
-                    .is_readable()
-                {
-                    let msg = job_source
-                        .recv_msg(0)
-                        .map_err(|_| "Error receiving Job for execution")?;
-                    let message_string = msg.as_str().ok_or("Could not get message as str")?;
-                    let payload: Payload = serde_json::from_str(message_string)
-                        .map_err(|_| "Could not deserialize Message to Job")?;
-
-                    trace!("Job #{}: Received by {}", payload.job_id, name);
-                    match execute_job(
-                        provider,
-                        &payload,
-                        &results_sink,
-                        name,
-                        &loaded_implementations.clone(),
-                        &loaded_lib_manifests.clone(),
-                    ) {
-                        Ok(keep_processing) => process_jobs = keep_processing,
-                        Err(e) => error!("{}", e),
-                    }
-                }
+        // when shutting down, only execute the jobs already received, without waiting for more
+        let shutting_down = shutdown.load(Ordering::SeqCst);
+        let timeout = if shutting_down { Duration::ZERO } else { SHUTDOWN_POLL_INTERVAL };
 
-                if items
-                    .get(1)
-                    .ok_or("Could not get poll item 1")?
-                    .is_readable()
-                {
-                    let msg = control_socket
-                        .recv_msg(0)
-                        .map_err(|_| "Error receiving Control message")?;
-                    match msg.as_str().ok_or("Could not get message as str") {
-                        Ok("DONE") => {
+        trace!("{name} waiting for a job to execute or a DONE signal");
+        match transport.receive(timeout) {
+            Ok(messages) if messages.is_empty() && shutting_down => {
+                trace!("{name} shutting down");
+                break;
+            },
+            // control messages come first, so jobs of a cancelled submission are not executed
+            Ok(messages) => for message in messages {
+                match message {
+                    CoordinatorMessage::Control(control) => match control.as_str() {
+                        "DONE" => {
                             trace!("'DONE' message received in executor");
-                            return Ok(());
+                            return Ok(jobs_executed);
+                        }
+                        "SHUTDOWN" => {
+                            trace!("'SHUTDOWN' message received in executor");
+                            shutdown.store(true, Ordering::SeqCst);
+                        }
+                        "CLEAR" => {
+                            trace!("'CLEAR' message received in executor");
+                            memoized_results
+                                .lock()
+                                .map_err(|_| "Could not lock memoized results")?
+                                .clear();
+                        }
+                        message => match message.strip_prefix("CANCEL ")
+                            .and_then(|id| id.parse::<usize>().ok()) {
+                            Some(submission_id) => {
+                                trace!("'CANCEL' of submission #{submission_id} received in executor");
+                                cancelled_submissions.insert(submission_id);
+                            }
+                            None => error!("Unexpected Control message"),
+                        },
+                    },
+                    CoordinatorMessage::Job(job) => {
+                        let payloads = parse_jobs(&job)?;
+                        let job_keys: Vec<(usize, usize)> = payloads.iter()
+                            .map(|payload| (payload.submission_id, payload.job_id))
+                            .collect();
+                        jobs_held.lock().map_err(|_| "Could not lock jobs held")?
+                            .extend(job_keys.iter().copied());
+                        // the results of a batch of jobs are sent back together in one message
+                        let mut batch_results = (payloads.len() > 1).then(Vec::new);
+                        for mut payload in payloads {
+                            trace!("Job #{}: Received by {}", payload.job_id, name);
+                            if cancelled_submissions.contains(&payload.submission_id) {
+                                trace!("Job #{}: Discarded as submission #{} was cancelled",
+                                    payload.job_id, payload.submission_id);
+                                continue;
+                            }
+                            match execute_job(
+                                provider,
+                                &mut payload,
+                                transport.as_ref(),
+                                name,
+                                &loaded_implementations.clone(),
+                                &loaded_lib_manifests.clone(),
+                                memoized_results,
+                                counters,
+                                batch_results.as_mut(),
+                            ) {
+                                Ok(keep_processing) => {
+                                    jobs_executed += 1;
+                                    process_jobs = keep_processing;
+                                }
+                                Err(e) => error!("{}", e),
+                            }
+                        }
+                        if let Some(results) = batch_results.filter(|results| !results.is_empty()) {
+                            transport.send(&serde_json::to_string(&results)?)
+                                .chain_err(|| "Could not send results of batch of Jobs")?;
+                        }
+                        let mut held = jobs_held.lock().map_err(|_| "Could not lock jobs held")?;
+                        for key in &job_keys {
+                            held.remove(key);
                         }
-                        Ok(_) => error!("Unexpected Control message"),
-                        _ => error!("Error parsing Control message"),
                     }
                 }
-            }
+            },
             Err(e) => {
-                error!("Error while polling for Jobs or Control messages: {e}");
+                error!("Error while receiving Jobs or Control messages: {e}");
+            }
+        }
+
+        if let Some(metrics_reporter) = &mut reporter {
+            if let Err(e) = metrics_reporter.report(transport.as_ref()) {
+                error!("{e}");
             }
         }
     }
 
-    Ok(())
+    Ok(jobs_executed)
 }
 
-// Replace the standard panic hook with one that just outputs the file and line of any panic.
+// Get the `Payload` of the next job to execute, or the `Payload`s of a batch of jobs, from a
+// `message` received from the coordinator
+fn parse_jobs(message: &str) -> Result<Vec<Payload>> {
+    if let Ok(payload) = serde_json::from_str(message) {
+        return Ok(vec![payload]);
+    }
+    serde_json::from_str(message)
+        .map_err(|_| "Could not deserialize Message to Job".into())
+}
+
+thread_local! {
+    // Set in executor threads, whose panics are reported by the hook set in `set_panic_hook()`
+    static EXECUTOR_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+// Ensures the panic hook is only replaced once, however many executor threads are started
+static PANIC_HOOK: Once = Once::new();
+
+// Replace the standard panic hook with one that just outputs the file and line of any panic in
+// an executor thread. Panics in other threads (e.g. tests running executors in the same process)
+// are still reported by the hook it replaced
 fn set_panic_hook() {
-    panic::set_hook(Box::new(|panic_info| {
-        if let Some(location) = panic_info.location() {
-            error!(
-                "Panic in file '{}' at line {}",
-                location.file(),
-                location.line()
-            );
-        }
-    }));
+    EXECUTOR_THREAD.with(|executor_thread| executor_thread.set(true));
+    PANIC_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |panic_info| {
+            if !EXECUTOR_THREAD.with(Cell::get) {
+                previous_hook(panic_info);
+            } else if let Some(location) = panic_info.location() {
+                error!(
+                    "Panic in file '{}' at line {}",
+                    location.file(),
+                    location.line()
+                );
+            }
+        }));
+    });
 }
 
 // Return Ok(keep_processing) flag as true or false to keep processing
+#[allow(clippy::too_many_arguments)]
 fn execute_job(
     provider: &Arc<dyn Provider>,
-    payload: &Payload,
-    results_sink: &zmq::Socket,
+    payload: &mut Payload,
+    results_sink: &dyn ExecutorTransport,
     name: &str,
     loaded_implementations: &Arc<RwLock<HashMap<Url, Arc<dyn Implementation>>>>,
     loaded_lib_manifests: &Arc<RwLock<HashMap<Url, (LibraryManifest, Url)>>>,
+    memoized_results: &Arc<Mutex<MemoizedResults>>,
+    counters: &JobCounters,
+    mut batch_results: Option<&mut BatchResults>,
 ) -> Result<bool> {
-    // TODO see if we can avoid write access until we know it's needed
-    let mut implementations = loaded_implementations
-        .write()
-        .map_err(|_| "Could not gain read access to loaded implementations map")?;
-    if implementations.get(&payload.implementation_url).is_none() {
-        trace!(
-            "Implementation '{}' is not loaded",
-            payload.implementation_url
-        );
-        let implementation = match payload.implementation_url.scheme() {
-            "lib" => {
-                let mut lib_root_url = payload.implementation_url.clone();
-                lib_root_url.set_path("");
-                load_referenced_implementation(
-                    provider,
-                    &lib_root_url,
-                    loaded_lib_manifests,
-                    &payload.implementation_url,
-                )?
-            }
-            "context" => {
-                let mut lib_root_url = payload.implementation_url.clone();
-                let _ = lib_root_url.set_host(Some(""));
-                lib_root_url.set_path("");
-                load_referenced_implementation(
-                    provider,
-                    &lib_root_url,
-                    loaded_lib_manifests,
-                    &payload.implementation_url,
-                )?
-            }
-            "file" => Arc::new(wasm::load(provider, &payload.implementation_url)?),
-            _ => bail!("Unsupported scheme on implementation_url"),
-        };
-        implementations.insert(payload.implementation_url.clone(), implementation);
-        trace!(
-            "Implementation '{}' added to executor",
-            payload.implementation_url
-        );
+    let span = info_span!("execute", job_id = payload.job_id, executor = name,
+        implementation = %payload.implementation_url, input_sizes = ?payload.input_sizes(),
+        success = tracing::field::Empty).entered();
+
+    // A job with input values that do not conform to the JSON Schemas declared for the inputs
+    // fails without being executed
+    if let Err(e) = validate_inputs(payload) {
+        send_result(results_sink, payload, Err(e), batch_results)?;
+        return Ok(true);
     }
 
-    let implementation = implementations
-        .get(&payload.implementation_url)
-        .ok_or("Could not find implementation")?;
+    // The result of a job for a pure function maybe memoized from a previous job with the same
+    // implementation and inputs, in which case there is no need to execute it again
+    let memo_key = if payload.pure {
+        Some((payload.implementation_url.clone(), serde_json::to_string(&payload.input_set)?))
+    } else {
+        None
+    };
+
+    if let Some(key) = &memo_key {
+        let memoized = memoized_results
+            .lock()
+            .map_err(|_| "Could not lock memoized results")?
+            .get(key)
+            .cloned();
+        if let Some(output) = memoized {
+            trace!("Job #{}: Memoized result used by '{name}'", payload.job_id);
+            send_result(results_sink, payload, Ok(output), batch_results.as_deref_mut())?;
+            return Ok(true);
+        }
+    }
+
+    let implementation = get_implementation(provider, payload, loaded_implementations,
+                                            loaded_lib_manifests, counters)?;
 
     trace!("Job #{}: Started executing on '{name}'", payload.job_id);
-    let result = implementation.run(&payload.input_set);
+    let start = Instant::now();
+    let result = implementation.run_metered(&payload.take_inputs(), payload.fuel);
+    counters.job_executed(start.elapsed());
     trace!("Job #{}: Finished executing on '{name}'", payload.job_id);
+    span.record("success", result.is_ok());
+
+    // errors are not memoized, so that a job that fails can be retried
+    if let (Some(key), Ok(output)) = (memo_key, &result) {
+        memoized_results
+            .lock()
+            .map_err(|_| "Could not lock memoized results")?
+            .put(key, output.clone());
+    }
+
+    send_result(results_sink, payload, result, batch_results)?;
+
+    Ok(true)
+}
+
+// Get the implementation of the function the job with `payload` is for. Implementations are only
+// loaded (and compiled, if WASM) when the first job for the function arrives, and without holding
+// the lock on those already loaded, so that other threads can keep executing jobs using them
+fn get_implementation(
+    provider: &Arc<dyn Provider>,
+    payload: &Payload,
+    loaded_implementations: &Arc<RwLock<HashMap<Url, Arc<dyn Implementation>>>>,
+    loaded_lib_manifests: &Arc<RwLock<HashMap<Url, (LibraryManifest, Url)>>>,
+    counters: &JobCounters,
+) -> Result<Arc<dyn Implementation>> {
+    let loaded = loaded_implementations
+        .read()
+        .map_err(|_| "Could not gain read access to loaded implementations map")?
+        .get(&payload.implementation_url)
+        .cloned();
+    if let Some(implementation) = loaded {
+        counters.implementation_cache_hits.fetch_add(1, Ordering::Relaxed);
+        return Ok(implementation);
+    }
+
+    trace!(
+        "Implementation '{}' is not loaded",
+        payload.implementation_url
+    );
+    let implementation = match payload.implementation_url.scheme() {
+        "lib" => {
+            let mut lib_root_url = payload.implementation_url.clone();
+            lib_root_url.set_path("");
+            load_referenced_implementation(
+                provider,
+                &lib_root_url,
+                loaded_lib_manifests,
+                &payload.implementation_url,
+            )?
+        }
+        "context" => {
+            let mut lib_root_url = payload.implementation_url.clone();
+            let _ = lib_root_url.set_host(Some(""));
+            lib_root_url.set_path("");
+            load_referenced_implementation(
+                provider,
+                &lib_root_url,
+                loaded_lib_manifests,
+                &payload.implementation_url,
+            )?
+        }
+        "file" => Arc::new(wasm::load(provider, &payload.implementation_url,
+                                      payload.implementation_hash.as_deref())?),
+        _ => bail!("Unsupported scheme on implementation_url"),
+    };
+
+    // another thread may have loaded it meanwhile, in which case that one is used
+    let implementation = loaded_implementations
+        .write()
+        .map_err(|_| "Could not gain write access to loaded implementations map")?
+        .entry(payload.implementation_url.clone())
+        .or_insert(implementation)
+        .clone();
+    trace!(
+        "Implementation '{}' added to executor",
+        payload.implementation_url
+    );
+
+    Ok(implementation)
+}
+
+// Validate the input values of the job with `payload` against the JSON Schemas (if any) it has
+// for them
+fn validate_inputs(payload: &Payload) -> Result<()> {
+    for (input_number, (schema, value)) in payload.input_schemas.iter()
+        .zip(&payload.input_set).enumerate() {
+        if let Some(schema) = schema {
+            schema::validate(schema, value).map_err(|e|
+                format!("Input #{input_number} of Job #{} is not valid: {e}", payload.job_id))?;
+        }
+    }
+    Ok(())
+}
+
+// Send the result of executing the job with `payload` back to the coordinator. If the job has a
+// `chunk_size` and output a larger array, then the array is streamed back as a series of `Chunk`s
+// ahead of the result, which is then sent without the output value. If the job was received in a
+// batch, then the result is added to `batch_results` to be sent with those of the rest of the batch
+fn send_result(
+    results_sink: &dyn ExecutorTransport,
+    payload: &Payload,
+    mut result: Result<(Option<Value>, RunAgain)>,
+    batch_results: Option<&mut BatchResults>,
+) -> Result<()> {
+    if let (Some(chunk_size), Ok((output, _))) = (payload.chunk_size, &mut result) {
+        if send_chunks(results_sink, payload, output.as_ref(), chunk_size)? {
+            *output = None;
+        }
+    }
+
+    if let Some(results) = batch_results {
+        results.push((payload.submission_id, payload.job_id, result));
+        return Ok(());
+    }
 
     results_sink
-        .send(
-            serde_json::to_string(&(payload.job_id, result))?.as_bytes(),
-            0,
-        )
-        .map_err(|_| "Could not send result of Job")?;
+        .send(&serde_json::to_string(&(payload.submission_id, payload.job_id, result))?)
+        .chain_err(|| "Could not send result of Job")?;
+
+    Ok(())
+}
+
+// Send an array `output` of the job with `payload` back to the coordinator as a series of `Chunk`s
+// if it has more than `chunk_size` elements, returning true if it was sent
+fn send_chunks(
+    results_sink: &dyn ExecutorTransport,
+    payload: &Payload,
+    output: Option<&Value>,
+    chunk_size: usize,
+) -> Result<bool> {
+    let Some(chunks) = output.and_then(|value| Chunk::split(payload, value, chunk_size)) else {
+        return Ok(false);
+    };
+
+    for chunk in chunks {
+        results_sink
+            .send(&serde_json::to_string(&chunk)?)
+            .chain_err(|| "Could not send chunk of result of Job")?;
+    }
+    trace!("Job #{}: Output streamed in chunks of {chunk_size} elements", payload.job_id);
 
     Ok(true)
 }
@@ -315,9 +812,12 @@ fn load_referenced_implementation(
     let (lib_manifest, resolved_lib_url) =
         get_lib_manifest_tuple(provider, loaded_lib_manifests, lib_root_url)?;
 
+    // locators are referenced without the version of the library the flow was compiled with
+    let mut locator_url = implementation_url.clone();
+    locator_url.set_query(None);
     let locator = lib_manifest
         .locators
-        .get(implementation_url)
+        .get(&locator_url)
         .ok_or(format!(
             "Could not find ImplementationLocator for '{implementation_url}' in library"
         ))?;
@@ -331,7 +831,9 @@ fn load_referenced_implementation(
                 .map_err(|e| e.to_string())?;
             debug!("Attempting to load wasm from source file: '{}'", wasm_url);
             // Wasm implementation being added. Wrap it with the Wasm Native Implementation
-            let wasm_executor = wasm::load(provider, &wasm_url)?;
+            let expected_hash = lib_manifest.implementation_hashes.get(wasm_source_relative);
+            let wasm_executor = wasm::load(provider, &wasm_url,
+                                           expected_hash.map(String::as_str))?;
             Arc::new(wasm_executor) as Arc<dyn Implementation>
         }
         Native(native_impl) => native_impl.clone(),
@@ -340,7 +842,10 @@ fn load_referenced_implementation(
     Ok(implementation)
 }
 
-// Get the tuple of the lib manifest and the url from where it was loaded from
+// Get the tuple of the lib manifest and the url from where it was loaded from. If `lib_root_url`
+// is pinned to the version of the library the flow was compiled with, then a library already
+// loaded without a version (such as one linked natively) is only used if it is that version,
+// otherwise that version is loaded
 fn get_lib_manifest_tuple(
     provider: &Arc<dyn Provider>,
     loaded_lib_manifests: &Arc<RwLock<HashMap<Url, (LibraryManifest, Url)>>>,
@@ -351,9 +856,21 @@ fn get_lib_manifest_tuple(
         .map_err(|_| "Could not get write access to the loaded lib manifests")?;
 
     if lib_manifests.get(lib_root_url).is_none() {
-        info!("Attempting to load library manifest'{}'", lib_root_url);
-        let manifest_tuple = LibraryManifest::load(provider, lib_root_url)
-            .chain_err(|| format!("Could not load library with root url: '{lib_root_url}'"))?;
+        let loaded = pinned_version(lib_root_url).and_then(|version| {
+            let mut unversioned_url = lib_root_url.clone();
+            unversioned_url.set_query(None);
+            lib_manifests.get(&unversioned_url)
+                .filter(|(manifest, _)| manifest.metadata.version == version)
+                .cloned()
+        });
+        let manifest_tuple = match loaded {
+            Some(manifest_tuple) => manifest_tuple,
+            None => {
+                info!("Attempting to load library manifest'{}'", lib_root_url);
+                LibraryManifest::load(provider, lib_root_url)
+                    .chain_err(|| format!("Could not load library with root url: '{lib_root_url}'"))?
+            }
+        };
         lib_manifests.insert(lib_root_url.clone(), manifest_tuple);
     }
 
@@ -367,19 +884,31 @@ fn get_lib_manifest_tuple(
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
-    use std::sync::{Arc, RwLock};
+    use std::num::NonZeroUsize;
+    use std::sync::{Arc, Mutex, RwLock};
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, Instant};
 
+    use lru::LruCache;
+    use portpicker::pick_unused_port;
+    use serde_json::{json, Value};
+    use serial_test::serial;
     use url::Url;
 
     use flowcore::errors::Result;
-    use flowcore::model::lib_manifest::LibraryManifest;
+    use flowcore::model::lib_manifest::{ImplementationLocator::Native, LibraryManifest};
+    use flowcore::model::lock_file::pin_version;
     use flowcore::model::metadata::MetaData;
     use flowcore::provider::Provider;
-    use flowcore::Implementation;
+    use flowcore::model::runtime_function::Priority;
+    use flowcore::{DONT_RUN_AGAIN, Implementation, RunAgain};
 
-    use crate::job::{Job, Payload};
+    use crate::dispatcher::{Dispatcher, ExecutorMessage};
+    use crate::job::{Chunk, Job, Payload};
+    use crate::transport::{ChannelJobTransport, ExecutorConnector, ExecutorTransport, JobQueue,
+                           JobTransport};
 
-    use super::Executor;
+    use super::{Executor, JobCounters, MetricsReporter};
 
     fn test_meta_data() -> MetaData {
         MetaData {
@@ -410,6 +939,16 @@ mod test {
         }
     }
 
+    // A `ChannelJobTransport` and an `ExecutorTransport` connected to it, to send results on
+    fn results_transport() -> (ChannelJobTransport, Box<dyn ExecutorTransport>) {
+        let transport = ChannelJobTransport::new();
+        let results_sink = transport.connector(JobQueue::Lib)
+            .expect("Could not get connector")
+            .connect(false)
+            .expect("Could not connect to transport");
+        (transport, results_sink)
+    }
+
     #[test]
     fn add_a_lib() {
         let library = LibraryManifest::new(
@@ -426,6 +965,49 @@ mod test {
             .is_ok());
     }
 
+    struct TestImplementation;
+
+    impl Implementation for TestImplementation {
+        fn run(&self, _inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+            Ok((None, DONT_RUN_AGAIN))
+        }
+    }
+
+    #[test]
+    fn capabilities_include_native_libs() {
+        let mut native_library = LibraryManifest::new(
+            Url::parse("lib://nativelib").expect("Could not parse lib url"),
+            test_meta_data(),
+        );
+        native_library.locators.insert(
+            Url::parse("lib://nativelib/math/add").expect("Could not parse Url"),
+            Native(Arc::new(TestImplementation)),
+        );
+        let wasm_library = LibraryManifest::new(
+            Url::parse("lib://testlib").expect("Could not parse lib url"),
+            test_meta_data(),
+        );
+
+        let mut executor = Executor::new();
+        executor.add_lib(native_library, Url::parse("memory://").expect("Could not parse Url"))
+            .expect("Could not add native lib");
+        executor.add_lib(wasm_library,
+                         Url::parse("file://fake/lib/location").expect("Could not parse Url"))
+            .expect("Could not add lib");
+
+        let capabilities = executor.capabilities(4);
+        assert_eq!(capabilities.native_libs,
+                   vec![Url::parse("lib://nativelib").expect("Could not parse Url")]);
+        assert_eq!(capabilities.threads, 4);
+        assert_eq!(capabilities.architecture, std::env::consts::ARCH);
+        assert!(capabilities.runs_natively(&Url::parse("lib://nativelib/math/add")
+            .expect("Could not parse Url")));
+        assert!(!capabilities.runs_natively(&Url::parse("lib://testlib/math/add")
+            .expect("Could not parse Url")));
+        assert!(!capabilities.runs_natively(&Url::parse("context://nativelib/stdout")
+            .expect("Could not parse Url")));
+    }
+
     #[test]
     fn execute_job() {
         let job1 = Job {
@@ -437,8 +1019,18 @@ mod test {
                 input_set: vec![],
                 implementation_url: Url::parse("lib://flowstdlib/math/add")
                     .expect("Could not parse Url"),
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
             },
             result: Ok((None, false)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         };
 
         let job2 = Job {
@@ -450,8 +1042,18 @@ mod test {
                 input_set: vec![],
                 implementation_url: Url::parse("context://stdio/stdout")
                     .expect("Could not parse Url"),
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
             },
             result: Ok((None, false)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         };
 
         let job3 = Job {
@@ -462,33 +1064,353 @@ mod test {
                 job_id: 0,
                 input_set: vec![],
                 implementation_url: Url::parse("file://fake/path").expect("Could not parse Url"),
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
             },
             result: Ok((None, false)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         };
 
-        for job in vec![job1, job2, job3] {
+        for mut job in [job1, job2, job3] {
             let loaded_implementations =
                 Arc::new(RwLock::new(HashMap::<Url, Arc<dyn Implementation>>::new()));
             let loaded_lib_manifests =
                 Arc::new(RwLock::new(HashMap::<Url, (LibraryManifest, Url)>::new()));
             let provider = Arc::new(TestProvider { test_content: "" }) as Arc<dyn Provider>;
-            let context = zmq::Context::new();
-            let results_sink = context
-                .socket(zmq::PUSH)
-                .expect("Could not createPUSH end of results-sink socket");
-            results_sink
-                .connect("tcp://127.0.0.1:3458")
-                .expect("Could not connect to PULL end of results-sink socket");
+            let (_results_source, results_sink) = results_transport();
 
             assert!(super::execute_job(
                 &provider,
-                &job.payload,
-                &results_sink,
+                &mut job.payload,
+                results_sink.as_ref(),
                 "test executor",
                 &loaded_implementations,
                 &loaded_lib_manifests,
+                &Arc::new(Mutex::new(LruCache::new(NonZeroUsize::MIN))),
+                &JobCounters::default(),
+                None,
             )
             .is_err());
         }
     }
+
+    #[test]
+    fn execute_memoized_job() {
+        let mut payload = Payload {
+            job_id: 0,
+            input_set: vec![Arc::new(json!(1)), Arc::new(json!(2))],
+            implementation_url: Url::parse("lib://flowstdlib/math/add")
+                .expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: true,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        };
+
+        let loaded_implementations =
+            Arc::new(RwLock::new(HashMap::<Url, Arc<dyn Implementation>>::new()));
+        let loaded_lib_manifests =
+            Arc::new(RwLock::new(HashMap::<Url, (LibraryManifest, Url)>::new()));
+        let memoized_results = Arc::new(Mutex::new(LruCache::new(NonZeroUsize::MIN)));
+        let provider = Arc::new(TestProvider { test_content: "" }) as Arc<dyn Provider>;
+        let (mut results_source, results_sink) = results_transport();
+
+        // the implementation cannot be loaded, so the job fails and the error is not memoized
+        assert!(super::execute_job(&provider, &mut payload, results_sink.as_ref(), "test executor",
+                                   &loaded_implementations, &loaded_lib_manifests,
+                                   &memoized_results, &JobCounters::default(), None).is_err());
+        assert!(memoized_results.lock().expect("Could not lock memoized results").is_empty());
+
+        // a memoized result is used without needing to load the implementation
+        memoized_results.lock().expect("Could not lock memoized results")
+            .put((payload.implementation_url.clone(),
+                     serde_json::to_string(&payload.input_set).expect("Could not serialize")),
+                    (Some(json!(3)), false));
+        assert!(super::execute_job(&provider, &mut payload, results_sink.as_ref(), "test executor",
+                                   &loaded_implementations, &loaded_lib_manifests,
+                                   &memoized_results, &JobCounters::default(), None).is_ok());
+
+        let msg = results_source.receive_result(true).expect("Could not receive result");
+        let (_, job_id, result): (usize, usize, Result<(Option<Value>, bool)>) =
+            serde_json::from_str(&msg)
+                .expect("Could not deserialize result");
+        assert_eq!(job_id, 0);
+        assert_eq!(result.expect("Job should not fail"), (Some(json!(3)), false));
+    }
+
+    #[test]
+    fn inputs_validated_against_schemas() {
+        let mut payload = Payload {
+            job_id: 1,
+            input_set: vec![Arc::new(json!(1)), Arc::new(json!("hello"))],
+            implementation_url: Url::parse("lib://flowstdlib/math/add")
+                .expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![Some(json!({"type": "number", "minimum": 0})), None],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        };
+        assert!(super::validate_inputs(&payload).is_ok());
+
+        payload.input_set = vec![Arc::new(json!(-1)), Arc::new(json!("hello"))];
+        assert!(super::validate_inputs(&payload).is_err());
+    }
+
+    #[test]
+    fn array_output_streamed_in_chunks() {
+        let (mut results_source, results_sink) = results_transport();
+
+        let payload = Payload {
+            job_id: 1,
+            input_set: vec![],
+            implementation_url: Url::parse("lib://flowstdlib/math/add")
+                .expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: Some(2),
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        };
+        super::send_result(results_sink.as_ref(), &payload, Ok((Some(json!([1, 2, 3])), true)),
+                           None)
+            .expect("Could not send result");
+
+        let mut chunks = vec![];
+        for _ in 0..2 {
+            let msg = results_source.receive_result(true).expect("Could not receive chunk");
+            let chunk: Chunk = serde_json::from_str(&msg)
+                .expect("Could not deserialize chunk");
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks, vec![
+            Chunk { submission_id: 0, job_id: 1, elements: vec![json!(1), json!(2)] },
+            Chunk { submission_id: 0, job_id: 1, elements: vec![json!(3)] },
+        ]);
+
+        let msg = results_source.receive_result(true).expect("Could not receive result");
+        let (_, job_id, result): (usize, usize, Result<(Option<Value>, bool)>) =
+            serde_json::from_str(&msg)
+                .expect("Could not deserialize result");
+        assert_eq!(job_id, 1);
+        assert_eq!(result.expect("Job should not fail"), (None, true));
+    }
+
+    #[test]
+    #[serial]
+    fn shutdown_ends_executor_threads() {
+        let ports: Vec<u16> = (0..5).map(|_| pick_unused_port().expect("No ports free")).collect();
+        let bind = |port: &u16| format!("tcp://*:{port}");
+        let connect = |port: &u16| format!("tcp://127.0.0.1:{port}");
+        let [job, general, results, control, native] = ports.as_slice() else {
+            panic!("Could not get five ports")
+        };
+        let _dispatcher = Dispatcher::new(&(bind(job), bind(general), bind(results),
+                                            bind(control), bind(native)), None)
+            .expect("Could not create dispatcher");
+
+        let mut executor = Executor::new();
+        let provider = Arc::new(TestProvider { test_content: "" }) as Arc<dyn Provider>;
+        executor.start(&provider, 2, &connect(job), &connect(native), &connect(results),
+                       &connect(control));
+
+        executor.shutdown_flag().store(true, Ordering::SeqCst);
+        assert_eq!(executor.wait(), 0);
+    }
+
+    #[test]
+    fn loaded_implementations_counted_as_cache_hits() {
+        let payload = Payload {
+            job_id: 0,
+            input_set: vec![],
+            implementation_url: Url::parse("lib://testlib/test").expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        };
+        let mut implementations = HashMap::<Url, Arc<dyn Implementation>>::new();
+        implementations.insert(payload.implementation_url.clone(), Arc::new(TestImplementation));
+        let loaded_implementations = Arc::new(RwLock::new(implementations));
+        let loaded_lib_manifests =
+            Arc::new(RwLock::new(HashMap::<Url, (LibraryManifest, Url)>::new()));
+        let provider = Arc::new(TestProvider { test_content: "" }) as Arc<dyn Provider>;
+        let (_results_source, results_sink) = results_transport();
+
+        let counters = JobCounters::default();
+        for _ in 0..2 {
+            assert!(super::execute_job(&provider, &mut payload.clone(), results_sink.as_ref(),
+                                       "test executor",
+                                       &loaded_implementations, &loaded_lib_manifests,
+                                       &Arc::new(Mutex::new(LruCache::new(NonZeroUsize::MIN))), &counters, None)
+                .is_ok());
+        }
+        assert_eq!(counters.jobs_executed.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.implementation_cache_hits.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn metrics_reported_when_due() {
+        let counters = Arc::new(JobCounters::default());
+        let mut reporter = MetricsReporter {
+            executor_id: "test".into(),
+            threads: 2,
+            counters: counters.clone(),
+            last_report: Instant::now(),
+            last_jobs_executed: 0,
+            last_busy_micros: 0,
+            last_load: 0.0,
+        };
+        counters.job_executed(Duration::from_millis(4));
+        counters.job_executed(Duration::from_millis(2));
+        assert!(reporter.due().is_none(), "Metrics should not be reported before they are due");
+
+        reporter.last_report = Instant::now() - Duration::from_secs(1);
+        let metrics = reporter.due().expect("Metrics should be due");
+        assert_eq!(metrics.executor_id, "test");
+        assert_eq!(metrics.jobs_executed, 2);
+        assert!((metrics.average_job_ms - 3.0).abs() < f64::EPSILON);
+        assert!(metrics.load > 0.0 && metrics.load < 0.01);
+
+        // idle is reported once, then nothing until more jobs are executed
+        reporter.last_report = Instant::now() - Duration::from_secs(1);
+        assert!(reporter.due().is_some_and(|metrics| metrics.load <= 0.0));
+        reporter.last_report = Instant::now() - Duration::from_secs(1);
+        assert!(reporter.due().is_none());
+    }
+
+    #[test]
+    fn implementation_loaded_on_first_use() {
+        let lib_url = Url::parse("lib://testlib").expect("Could not parse lib url");
+        let implementation_url = Url::parse("lib://testlib/test").expect("Could not parse Url");
+        let mut library = LibraryManifest::new(lib_url.clone(), test_meta_data());
+        library.locators.insert(implementation_url.clone(), Native(Arc::new(TestImplementation)));
+        let mut lib_manifests = HashMap::new();
+        lib_manifests.insert(lib_url,
+                             (library, Url::parse("memory://").expect("Could not parse Url")));
+        let loaded_lib_manifests = Arc::new(RwLock::new(lib_manifests));
+        let loaded_implementations =
+            Arc::new(RwLock::new(HashMap::<Url, Arc<dyn Implementation>>::new()));
+        let provider = Arc::new(TestProvider { test_content: "" }) as Arc<dyn Provider>;
+        let payload = Payload {
+            job_id: 0,
+            input_set: vec![],
+            implementation_url: implementation_url.clone(),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        };
+        let counters = JobCounters::default();
+
+        super::get_implementation(&provider, &payload, &loaded_implementations,
+                                  &loaded_lib_manifests, &counters)
+            .expect("Could not get implementation");
+        assert!(loaded_implementations.read().expect("Could not read implementations")
+            .contains_key(&implementation_url));
+        assert_eq!(counters.implementation_cache_hits.load(Ordering::Relaxed), 0);
+
+        super::get_implementation(&provider, &payload, &loaded_implementations,
+                                  &loaded_lib_manifests, &counters)
+            .expect("Could not get implementation");
+        assert_eq!(counters.implementation_cache_hits.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn loaded_library_only_used_for_its_version() {
+        let lib_url = Url::parse("lib://testlib").expect("Could not parse lib url");
+        let mut library = LibraryManifest::new(lib_url.clone(), test_meta_data());
+        library.locators.insert(Url::parse("lib://testlib/test").expect("Could not parse Url"),
+                                Native(Arc::new(TestImplementation)));
+        let mut lib_manifests = HashMap::new();
+        lib_manifests.insert(lib_url,
+                             (library, Url::parse("memory://").expect("Could not parse Url")));
+        let loaded_lib_manifests = Arc::new(RwLock::new(lib_manifests));
+        let provider = Arc::new(TestProvider { test_content: "" }) as Arc<dyn Provider>;
+
+        let pinned_url = |version: &str| {
+            let mut url = Url::parse("lib://testlib/test").expect("Could not parse Url");
+            pin_version(&mut url, version);
+            url
+        };
+        let get_implementation = |implementation_url: Url| {
+            let payload = Payload {
+                job_id: 0,
+                input_set: vec![],
+                implementation_url,
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
+            };
+            super::get_implementation(&provider, &payload,
+                                      &Arc::new(RwLock::new(HashMap::new())),
+                                      &loaded_lib_manifests, &JobCounters::default())
+        };
+
+        assert!(get_implementation(pinned_url("0.0.0")).is_ok());
+        // another version is loaded using the provider, which has no library manifest
+        assert!(get_implementation(pinned_url("1.0.0")).is_err());
+    }
+
+    #[test]
+    fn jobs_executed_without_sockets() {
+        let lib_url = Url::parse("lib://testlib").expect("Could not parse lib url");
+        let implementation_url = Url::parse("lib://testlib/test").expect("Could not parse Url");
+        let mut library = LibraryManifest::new(lib_url, test_meta_data());
+        library.locators.insert(implementation_url.clone(), Native(Arc::new(TestImplementation)));
+
+        let transport = ChannelJobTransport::new();
+        let connector = transport.connector(JobQueue::Lib).expect("Could not get connector");
+        let mut dispatcher = Dispatcher::with_transport(Box::new(transport));
+
+        let mut executor = Executor::new();
+        executor.add_lib(library, Url::parse("memory://").expect("Could not parse Url"))
+            .expect("Could not add lib");
+        let provider = Arc::new(TestProvider { test_content: "" }) as Arc<dyn Provider>;
+        executor.start_with_connector(&provider, 1, Arc::new(connector));
+
+        let payload = Payload {
+            job_id: 5,
+            input_set: vec![],
+            implementation_url,
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        };
+        dispatcher.send_job_for_execution(&payload).expect("Could not send job");
+        let message = dispatcher.get_next_result(true).expect("Could not get result");
+        assert!(matches!(message, ExecutorMessage::JobResult(0, (5, Ok((None, DONT_RUN_AGAIN))))));
+
+        // dropping the dispatcher sends "DONE" to the executor threads, which then end
+        drop(dispatcher);
+        assert_eq!(executor.wait(), 1);
+    }
 }
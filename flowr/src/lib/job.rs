@@ -1,22 +1,99 @@
 use std::fmt;
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 use url::Url;
 
-use flowcore::errors::Result;
+use flowcore::errors::{Error, Result};
 use flowcore::model::output_connection::OutputConnection;
+use flowcore::model::runtime_function::Priority;
+use flowcore::model::submission::RetryPolicy;
 use flowcore::RunAgain;
 
+/// The start of the message of the error that a [Job] fails with if it runs out of fuel
+pub const OUT_OF_FUEL: &str = "Out of fuel";
+
+/// Return true if `error` is the error a [Job] fails with when it runs out of fuel
+#[must_use]
+pub fn is_out_of_fuel(error: &Error) -> bool {
+    error.to_string().starts_with(OUT_OF_FUEL)
+}
+
 /// Conatins the minimum amount of information required to execute a [Job] and return the result
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Payload {
     /// Each `Job` has a unique id that increments as jobs are executed
     pub job_id: usize,
-    /// The set of input values to be used by the function when executing this job
-    pub input_set: Vec<Value>,
+    /// The set of input values to be used by the function when executing this job. They are shared
+    /// with other jobs and inputs the same values were sent to, and only copied when serialized
+    /// to send the job to an executor
+    pub input_set: Vec<Arc<Value>>,
     /// The url of the implementation to be run for this job
     pub implementation_url: Url,
+    /// The SHA-256 hash recorded for a provided WASM implementation when it was compiled, that
+    /// the implementation loaded must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation_hash: Option<String>,
+    /// Is the function pure, so the result of the job can be memoized and reused for later jobs
+    /// with the same implementation and input values
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pure: bool,
+    /// If set, an array output with more elements than this is streamed back in [Chunk]s of at
+    /// most this many elements, ahead of the result of the job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<usize>,
+    /// The JSON Schemas (if any) that the values of each input should be validated against
+    /// before executing the job. Empty if they should not be validated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_schemas: Vec<Option<Value>>,
+    /// The maximum amount of fuel (roughly, the number of instructions) that executing the job
+    /// may consume, if its implementation is WASM. `None` if it is not limited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// The id the [Coordinator][crate::coordinator::Coordinator] gave the submission the job is
+    /// for, so that its result can be returned to the right flow when several are executing
+    #[serde(default)]
+    pub submission_id: usize,
+    /// The `Priority` of the function the job is for, used to decide which jobs to dispatch first
+    /// when executors are busy
+    #[serde(default, skip_serializing_if = "Priority::is_normal")]
+    pub priority: Priority,
+}
+
+/// A `Chunk` of the elements of an array output by a [Job], that is streamed back by an executor
+/// ahead of the job's result, so that large arrays can be delivered incrementally to destinations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Chunk {
+    /// The id of the submission the `Job` is for
+    #[serde(default)]
+    pub submission_id: usize,
+    /// The `id` of the `Job` that output the array
+    pub job_id: usize,
+    /// The next elements of the array, in order
+    pub elements: Vec<Value>,
+}
+
+impl Chunk {
+    /// Split an array `output` of the job with `payload` into a series of `Chunk`s of at most
+    /// `chunk_size` elements. Returns `None` if `output` is not an array with more than
+    /// `chunk_size` elements, as then there is no need to stream it
+    #[must_use]
+    pub fn split<'v>(payload: &Payload, output: &'v Value, chunk_size: usize) -> Option<impl Iterator<Item=Chunk> + 'v> {
+        let (submission_id, job_id) = (payload.submission_id, payload.job_id);
+        match output {
+            Value::Array(elements) if chunk_size > 0 && elements.len() > chunk_size => {
+                Some(elements.chunks(chunk_size).map(move |elements| Chunk {
+                    submission_id,
+                    job_id,
+                    elements: elements.to_vec(),
+                }))
+            }
+            _ => None,
+        }
+    }
 }
 
 /// A `Job` contains the information necessary to manage the execution of a function in the
@@ -34,6 +111,47 @@ pub struct Job {
     pub result: Result<(Option<Value>, RunAgain)>,
     /// The destinations (other function's inputs) where any output should be sent
     pub connections: Vec<OutputConnection>,
+    /// The `RetryPolicy` to apply if the job fails, `None` if it should not be retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_policy: Option<RetryPolicy>,
+    /// The number of times the job has been retried after failing
+    #[serde(default)]
+    pub retries: usize,
+    /// If the job failed and is waiting to be retried, the time when it can be retried
+    #[serde(skip)]
+    pub retry_at: Option<Instant>,
+}
+
+impl Job {
+    /// If the job failed and can be retried under its `RetryPolicy`, return how long to wait
+    /// before retrying it. The wait doubles with each retry of the job.
+    #[must_use]
+    pub fn retry_backoff(&self) -> Option<Duration> {
+        let policy = self.retry_policy?;
+        if self.retries >= policy.retries {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(u32::try_from(self.retries).unwrap_or(u32::MAX));
+        Some(policy.backoff.saturating_mul(factor))
+    }
+
+    /// Has the job failed and exhausted all the retries allowed by its `RetryPolicy`
+    #[must_use]
+    pub fn retries_exhausted(&self) -> bool {
+        self.result.is_err() && self.retry_policy.is_some() && self.retry_backoff().is_none()
+    }
+
+    /// Did the job fail because it ran out of fuel
+    #[must_use]
+    pub fn out_of_fuel(&self) -> bool {
+        matches!(&self.result, Err(e) if is_out_of_fuel(e))
+    }
+
+    /// Return true if the job can be dispatched at `now`, i.e. it is not waiting to be retried
+    #[must_use]
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.retry_at.is_none_or(|retry_at| retry_at <= now)
+    }
 }
 
 impl fmt::Display for Job {
@@ -41,10 +159,29 @@ impl fmt::Display for Job {
         writeln!(f, "{}", self.payload)?;
         writeln!(f, "Connections: {:?}", self.connections)?;
         writeln!(f, "Function Id: {}, Flow Id: {}", self.function_id, self.flow_id)?;
+        if self.retries > 0 {
+            writeln!(f, "Retries: {}", self.retries)?;
+        }
         write!(f, "Result: {:?}", self.result)
     }
 }
 
+impl Payload {
+    /// Return the size (in bytes, when serialized as JSON) of the value of each input
+    #[must_use]
+    pub fn input_sizes(&self) -> Vec<usize> {
+        self.input_set.iter().map(|value| value.to_string().len()).collect()
+    }
+
+    /// Take the input values out of the payload, to run the implementation of the function with.
+    /// Values not shared with anything else (as when the payload was received from the network)
+    /// are moved out without copying them
+    #[must_use]
+    pub fn take_inputs(&mut self) -> Vec<Value> {
+        mem::take(&mut self.input_set).into_iter().map(Arc::unwrap_or_clone).collect()
+    }
+}
+
 impl fmt::Display for Payload {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Job #: {}", self.job_id)?;
@@ -56,13 +193,17 @@ impl fmt::Display for Payload {
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
 
     use serde_json::json;
     use url::Url;
 
     use flowcore::model::datatype::ARRAY_TYPE;
+    use flowcore::model::runtime_function::Priority;
+    use flowcore::model::submission::RetryPolicy;
 
-    use crate::job::Payload;
+    use crate::job::{Chunk, Payload};
 
     #[test]
     fn display_job_test() {
@@ -74,8 +215,18 @@ mod test {
                 job_id: 0,
                 input_set: vec![],
                 implementation_url: Url::parse("lib://flowstdlib/math/add").expect("Could not parse Url"),
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
             },
-            result: Ok((None, false))
+            result: Ok((None, false)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         };
         println!("Job: {job}");
     }
@@ -90,8 +241,18 @@ mod test {
                 job_id: 0,
                 input_set: vec![],
                 implementation_url: Url::parse("lib://flowstdlib/math/add").expect("Could not parse Url"),
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
             },
-            result: Ok((Some(json!(42u64)), false))
+            result: Ok((Some(json!(42u64)), false)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         };
 
         assert_eq!(
@@ -118,8 +279,18 @@ mod test {
                 job_id: 0,
                 input_set: vec![],
                 implementation_url: Url::parse("lib://flowstdlib/math/add").expect("Could not parse Url"),
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
             },
             result: Ok((Some(json!(value)), false)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         };
 
         assert_eq!(
@@ -132,4 +303,118 @@ mod test {
                 .expect("Could not get value using json pointer")
         );
     }
+
+    fn failed_job(retry_policy: Option<RetryPolicy>, retries: usize) -> super::Job {
+        super::Job {
+            function_id: 1,
+            flow_id: 0,
+            connections: vec![],
+            payload: Payload {
+                job_id: 0,
+                input_set: vec![],
+                implementation_url: Url::parse("lib://flowstdlib/math/add").expect("Could not parse Url"),
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
+            },
+            result: Err("Failed".into()),
+            retry_policy,
+            retries,
+            retry_at: None,
+        }
+    }
+
+    #[test]
+    fn split_array_into_chunks() {
+        let mut payload = failed_job(None, 0).payload;
+        payload.submission_id = 2;
+        payload.job_id = 7;
+        let output = json!([1, 2, 3, 4, 5]);
+        let chunks: Vec<Chunk> = Chunk::split(&payload, &output, 2)
+            .expect("Array was not split")
+            .collect();
+        assert_eq!(chunks, vec![
+            Chunk { submission_id: 2, job_id: 7, elements: vec![json!(1), json!(2)] },
+            Chunk { submission_id: 2, job_id: 7, elements: vec![json!(3), json!(4)] },
+            Chunk { submission_id: 2, job_id: 7, elements: vec![json!(5)] },
+        ]);
+    }
+
+    #[test]
+    fn small_array_not_split() {
+        assert!(Chunk::split(&failed_job(None, 0).payload, &json!([1, 2]), 2).is_none());
+    }
+
+    #[test]
+    fn non_array_not_split() {
+        assert!(Chunk::split(&failed_job(None, 0).payload, &json!(42), 1).is_none());
+    }
+
+    #[test]
+    fn no_retry_policy() {
+        let job = failed_job(None, 0);
+        assert_eq!(job.retry_backoff(), None);
+        assert!(!job.retries_exhausted());
+    }
+
+    #[test]
+    fn retry_backoff_doubles() {
+        let policy = RetryPolicy { retries: 3, backoff: Duration::from_millis(10) };
+        assert_eq!(failed_job(Some(policy), 0).retry_backoff(), Some(Duration::from_millis(10)));
+        assert_eq!(failed_job(Some(policy), 1).retry_backoff(), Some(Duration::from_millis(20)));
+        assert_eq!(failed_job(Some(policy), 2).retry_backoff(), Some(Duration::from_millis(40)));
+        assert!(!failed_job(Some(policy), 2).retries_exhausted());
+        assert_eq!(failed_job(Some(policy), 3).retry_backoff(), None);
+        assert!(failed_job(Some(policy), 3).retries_exhausted());
+    }
+
+    #[test]
+    fn payload_input_sizes() {
+        let payload = Payload {
+            job_id: 0,
+            input_set: vec![json!(42), json!("hello"), json!([1, 2])].into_iter().map(Arc::new)
+                .collect(),
+            implementation_url: Url::parse("lib://flowstdlib/math/add").expect("Could not parse Url"),
+            implementation_hash: None,
+            pure: false,
+            chunk_size: None,
+            input_schemas: vec![],
+            fuel: None,
+            submission_id: 0,
+            priority: Priority::Normal,
+        };
+        assert_eq!(payload.input_sizes(), vec![2, 7, 5]);
+    }
+
+    #[test]
+    fn take_inputs_of_payload() {
+        let shared = Arc::new(json!([1, 2]));
+        let mut payload = failed_job(None, 0).payload;
+        payload.input_set = vec![Arc::new(json!(42)), shared.clone()];
+        assert_eq!(payload.take_inputs(), vec![json!(42), json!([1, 2])]);
+        assert!(payload.input_set.is_empty());
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn job_out_of_fuel() {
+        let mut job = failed_job(None, 0);
+        assert!(!job.out_of_fuel());
+        job.result = Err(format!("{}: limit exceeded", super::OUT_OF_FUEL).into());
+        assert!(job.out_of_fuel());
+    }
+
+    #[test]
+    fn job_due_for_retry() {
+        let now = Instant::now();
+        let mut job = failed_job(None, 0);
+        assert!(job.is_due(now));
+        job.retry_at = Some(now + Duration::from_secs(1));
+        assert!(!job.is_due(now));
+        assert!(job.is_due(now + Duration::from_secs(1)));
+    }
 }
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use flowcore::errors::{Result, ResultExt};
+
+// The file, in the `.flow` directory of the user's home directory, where the store is kept
+const KV_STORE_FILENAME: &str = "kv.json";
+
+/// A simple persistent key-value store, kept as a JSON file on the client, that flows can use
+/// to keep state between runs
+pub struct KvStore {
+    path: PathBuf,
+}
+
+impl KvStore {
+    /// Create a `KvStore` kept in the file at `path`, which is created when a value is first put
+    #[must_use]
+    pub fn new(path: &Path) -> Self {
+        KvStore {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Open the `KvStore` kept in the `.flow` directory of the user's home directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user's home directory is not known
+    pub fn open() -> Result<Self> {
+        let home_dir = env::var("HOME").chain_err(|| "Could not get $HOME for the key-value store")?;
+        Ok(Self::new(&Path::new(&home_dir).join(".flow").join(KV_STORE_FILENAME)))
+    }
+
+    /// Get the value stored for `key`, if there is one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store could not be read
+    pub fn get(&self, key: &str) -> Result<Option<Value>> {
+        Ok(self.read()?.remove(key))
+    }
+
+    /// Store `value` for `key`, replacing any previous value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store could not be read or written
+    pub fn put(&self, key: String, value: Value) -> Result<()> {
+        let mut store = self.read()?;
+        store.insert(key, value);
+        self.write(&store)
+    }
+
+    /// Delete the value stored for `key`, returning it if there was one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store could not be read or written
+    pub fn delete(&self, key: &str) -> Result<Option<Value>> {
+        let mut store = self.read()?;
+        let value = store.remove(key);
+        if value.is_some() {
+            self.write(&store)?;
+        }
+        Ok(value)
+    }
+
+    // Read all the keys and values in the store, which is empty if the file doesn't exist yet
+    fn read(&self) -> Result<BTreeMap<String, Value>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let contents = fs::read_to_string(&self.path)
+            .chain_err(|| format!("Could not read key-value store '{}'", self.path.display()))?;
+        serde_json::from_str(&contents)
+            .chain_err(|| format!("Could not parse key-value store '{}'", self.path.display()))
+    }
+
+    // Write all the keys and values to the store, creating the directory for it if needed
+    fn write(&self, store: &BTreeMap<String, Value>) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .chain_err(|| format!("Could not create directory '{}'", dir.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(store)
+            .chain_err(|| "Could not serialize key-value store")?;
+        fs::write(&self.path, contents)
+            .chain_err(|| format!("Could not write key-value store '{}'", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::KvStore;
+
+    #[test]
+    fn put_get_delete() {
+        let temp = tempdir().expect("Couldn't get temporary directory");
+        let store = KvStore::new(&temp.path().join("store").join("kv.json"));
+
+        assert_eq!(store.get("count").expect("get failed"), None);
+        store.put("count".into(), json!(1)).expect("put failed");
+        store.put("names".into(), json!(["a", "b"])).expect("put failed");
+        assert_eq!(store.get("count").expect("get failed"), Some(json!(1)));
+
+        // values persist, so a new store using the same file sees them
+        let store = KvStore::new(&temp.path().join("store").join("kv.json"));
+        assert_eq!(store.get("names").expect("get failed"), Some(json!(["a", "b"])));
+
+        assert_eq!(store.delete("count").expect("delete failed"), Some(json!(1)));
+        assert_eq!(store.get("count").expect("get failed"), None);
+        assert_eq!(store.delete("count").expect("delete failed"), None);
+    }
+
+    #[test]
+    fn invalid_store() {
+        let temp = tempdir().expect("Couldn't get temporary directory");
+        let path = temp.path().join("kv.json");
+        std::fs::write(&path, "not json").expect("Could not write file");
+
+        assert!(KvStore::new(&path).get("count").is_err());
+    }
+}
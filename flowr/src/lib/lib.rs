@@ -23,6 +23,11 @@ pub mod debug_command;
 /// to dispatch [Jobs][job::Job] for execution by an [Executor][executor::Executor]
 pub mod dispatcher;
 
+/// Provides [announce_service][discovery::announce_service] and
+/// [ServiceBrowser][discovery::ServiceBrowser] to announce and discover flow services using
+/// standard mDNS and DNS-SD
+pub mod discovery;
+
 /// Holds all [Error][errors::Error] types, and other modules in this crate will `use errors::*;`
 /// to get access to everything `error_chain` creates.
 pub mod errors;
@@ -30,14 +35,28 @@ pub mod errors;
 /// Provides [Executor][executor::Executor] that receives jobs for execution, executes them and returns results
 pub mod executor;
 
+/// Provides the [EventSink][event_sink::EventSink] trait for receiving the [Events][event_sink::Event]
+/// that happen during the execution of flows, and a [JsonEventLog][event_sink::JsonEventLog] that
+/// writes them to a file
+pub mod event_sink;
+
 /// Provides methods to get information about this library
 pub mod info;
 
 /// Provides [Job][job::Job] that holds jobs before and after their execution
 pub mod job;
 
+/// Provides [`KvStore`][kv_store::KvStore], a persistent key-value store used by clients to implement the
+/// `kv` context functions
+pub mod kv_store;
+
+/// Provides [exec][process::exec] used by clients to run a command in a separate process, when
+/// requested by the `exec` context function
+pub mod process;
+
 /// The `SubmissionHandler`[`submission_handler::SubmissionHandler`] trait defines  methods a client
-/// must implement in order to handle submissions from a client
+/// must implement in order to handle submissions from a client, and the `SubmissionListener`
+/// trait for accepting submissions from many clients to be executed concurrently
 #[cfg(feature = "submission")]
 pub mod submission_handler;
 
@@ -50,14 +69,36 @@ pub mod debugger_handler;
 /// used by [`Coordinator`][coordinator::Coordinator]
 pub mod run_state;
 
-/// Provides well-known service names used across multiple binary crates
+/// Provides well-known service names, and helpers to form the addresses to bind and connect
+/// sockets to, used across multiple binary crates
 pub mod services;
 
+/// Provides [CurveKeys][security::CurveKeys] used to encrypt and authenticate the connections
+/// between clients, coordinator and executors
+pub mod security;
+
+/// Provides [`ZmqRequester`][session::ZmqRequester] and [`ZmqResponder`][session::ZmqResponder] used by
+/// clients and the coordinator to resume their session over `zmq` after an interruption of the connection
+pub mod session;
+
+/// Provides [send][tcp::send] used by clients to send bytes to a TCP server, and read its response,
+/// when requested by the `tcp_send` context function
+pub mod tcp;
+
+/// Provides [Telemetry][telemetry::Telemetry] to export traces of the execution of submissions
+/// and jobs to an OpenTelemetry collector
+#[cfg(feature = "otlp")]
+pub mod telemetry;
+
+/// Provides the [JobTransport][transport::JobTransport] trait used by the
+/// [Dispatcher][dispatcher::Dispatcher] to send jobs to executors and receive their results, with
+/// a `zmq` socket backend and an in-process channel backend for running without sockets
+pub mod transport;
+
 #[cfg(feature = "debugger")]
 mod debugger;
 
 /// `wasmtime` module contains a number of implementations of the wasm execution
 mod wasm;
 
-#[cfg(debug_assertions)]
 mod checks;
@@ -0,0 +1,52 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::errors::Result;
+
+/// Run `command` with `args`, writing `stdin` to its standard input, and wait for it to exit.
+/// Returns its exit code (if it exited normally) and what it wrote to `stdout` and `stderr`
+///
+/// # Errors
+///
+/// Returns an error if `command` could not be run, or its output could not be read
+pub fn exec(command: &str, args: &[String], stdin: String) -> Result<(Option<i32>, String, String)> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run command '{command}': '{e}'"))?;
+
+    // write stdin from another thread, so a command that writes a lot of output before reading
+    // all its input cannot block
+    if let Some(mut child_stdin) = child.stdin.take() {
+        thread::spawn(move || {
+            let _ = child_stdin.write_all(stdin.as_bytes());
+        });
+    }
+
+    let output = child.wait_with_output()
+        .map_err(|e| format!("Could not get output of command '{command}': '{e}'"))?;
+    Ok((output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned()))
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn exec_echoes_stdin() {
+        let (code, stdout, stderr) = super::exec("cat", &[], "hello".into())
+            .expect("Could not exec 'cat'");
+        assert_eq!(code, Some(0));
+        assert_eq!(stdout, "hello");
+        assert!(stderr.is_empty());
+    }
+
+    #[test]
+    fn exec_unknown_command() {
+        assert!(super::exec("no-such-command-exists", &[], String::new()).is_err());
+    }
+}
@@ -1,20 +1,27 @@
 use std::collections::hash_map::Entry;
 use std::collections::VecDeque;
 use std::collections::{HashMap, HashSet};
-use std::fmt;
+use std::{fmt, fs};
+use std::cmp::Reverse;
+use std::path::Path;
+use std::sync::Arc;
+#[cfg(feature = "debugger")]
+use std::sync::{Mutex, PoisonError, Weak};
+use std::time::{Duration, Instant};
 
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use multimap::MultiMap;
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 
-use flowcore::errors::Result;
+use flowcore::errors::{bail, Result};
+use flowcore::model::metrics::ExecutorMetrics;
 #[cfg(feature = "metrics")]
 use flowcore::model::metrics::Metrics;
 use flowcore::model::output_connection::OutputConnection;
 use flowcore::model::output_connection::Source::{Input, Output};
 use flowcore::model::runtime_function::RuntimeFunction;
-use flowcore::model::submission::Submission;
+use flowcore::model::submission::{JobTimeoutPolicy, RetryPolicy, Submission};
 use flowcore::RunAgain;
 
 use crate::block::Block;
@@ -22,10 +29,14 @@ use crate::block::Block;
 use crate::checks;
 #[cfg(feature = "debugger")]
 use crate::debugger::Debugger;
-use crate::job::{Job, Payload};
+use crate::event_sink::Event;
+use crate::job::{Chunk, is_out_of_fuel, Job, Payload};
+
+// The minimum interval between estimates of the memory used by the values held in a flow, when
+// its memory is limited, as estimating it requires serializing all the values
+const MEMORY_ESTIMATE_INTERVAL: Duration = Duration::from_secs(1);
 
 /// `State` represents the possible states it is possible for a function to be in
-#[cfg(any(debug_assertions, feature = "debugger", test))]
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum State {
     /// Ready     - Function will be in Ready state when all of it's inputs are full and there are
@@ -44,6 +55,55 @@ pub enum State {
     Completed,
 }
 
+// When a running job was dispatched for execution, or last found still held back by the
+// dispatcher, and how many times it has been dispatched again since, as its result was not
+// received within the job timeout
+#[derive(Clone, Copy)]
+struct Dispatched {
+    at: Instant,
+    redispatches: usize,
+}
+
+/// What to do with a running job whose result was not received within the job timeout of the
+/// `Submission`, according to its `JobTimeoutPolicy`
+pub(crate) enum TimedOutJob {
+    /// Dispatch the job with this `Payload` for execution again
+    Redispatch(Payload),
+    /// Fail the job with this `job_id`, with the error message
+    Fail(usize, String),
+}
+
+// Tracks the chunks of an array output streamed back by a running job
+#[derive(Clone, Default)]
+struct StreamedOutput {
+    // indexes of the job's connections that the elements are sent to as chunks are received
+    streamed_connections: Vec<usize>,
+    // elements kept to re-assemble the output for the other connections, when there are any
+    kept_elements: Option<Vec<Value>>,
+}
+
+// The functions changed after the job of a `Snapshot` was started, by id, as they were prior to it
+#[cfg(feature = "debugger")]
+type FunctionChanges = Mutex<HashMap<usize, RuntimeFunction>>;
+
+/// The state of execution prior to a job being started, taken using `snapshot_prior_to()`, that
+/// execution can be stepped back to using `restore()`.
+///
+/// Instead of copying all the functions for each job, a function is only copied into the most
+/// recent `Snapshot` when it is first changed after that `Snapshot` was taken
+#[cfg(feature = "debugger")]
+pub(crate) struct Snapshot {
+    blocked: HashSet<usize>,
+    blocks: HashSet<Block>,
+    ready_jobs: VecDeque<Job>,
+    completed: HashSet<usize>,
+    number_of_jobs_created: usize,
+    busy_flows: MultiMap<usize, usize>,
+    flow_blocks: HashMap<usize, HashSet<usize>>,
+    aborted: Option<String>,
+    functions: Arc<FunctionChanges>,
+}
+
 /// `RunState` is a structure that maintains the state of all the functions in the currently
 /// executing flow.
 ///
@@ -208,6 +268,37 @@ pub struct RunState {
     /// Track which functions have finished and can be unblocked when flow goes not "busy"
     /// `HashMap`< <`flow_id`>, (`function_id`, set of refilled io numbers of that function)>
     flow_blocks: HashMap<usize, HashSet<usize>>,
+    /// The reason execution of the flow was aborted, if it was
+    aborted: Option<String>,
+    /// The latest `ExecutorMetrics` reported by the executors executing the flow's jobs
+    #[serde(default)]
+    executor_metrics: Vec<ExecutorMetrics>,
+    /// Outputs of running jobs being streamed back in chunks, by `job_id`
+    #[serde(skip)]
+    streamed_outputs: HashMap<usize, StreamedOutput>,
+    /// When running jobs were dispatched for execution, by `job_id`
+    #[serde(skip)]
+    dispatched: HashMap<usize, Dispatched>,
+    /// When the `ResourceLimits` of the submission were first checked, as execution started
+    #[serde(skip)]
+    started: Option<Instant>,
+    /// When the memory used by the values held in the flow was last estimated
+    #[serde(skip)]
+    memory_estimated: Option<Instant>,
+    /// `Event`s that happened and have not yet been taken, `None` if they are not being recorded
+    #[serde(skip)]
+    events: Option<Vec<Event>>,
+    /// The id given to the submission by the coordinator, that is included in the jobs created
+    #[serde(skip)]
+    submission_id: usize,
+    /// Has execution of the flow been cancelled by the client that submitted it
+    #[serde(skip)]
+    cancelled: bool,
+    /// Where functions are copied to prior to being changed, for the most recent `Snapshot`
+    /// taken if it has not been dropped
+    #[cfg(feature = "debugger")]
+    #[serde(skip)]
+    changes: Weak<FunctionChanges>,
 }
 
 impl RunState {
@@ -225,10 +316,160 @@ impl RunState {
             number_of_jobs_created: 0,
             busy_flows: MultiMap::<usize, usize>::new(),
             flow_blocks: HashMap::<usize, HashSet<usize>>::new(),
+            aborted: None,
+            executor_metrics: vec![],
+            streamed_outputs: HashMap::<usize, StreamedOutput>::new(),
+            dispatched: HashMap::<usize, Dispatched>::new(),
+            started: None,
+            memory_estimated: None,
+            events: None,
+            submission_id: 0,
+            cancelled: false,
+            #[cfg(feature = "debugger")]
+            changes: Weak::new(),
+        }
+    }
+
+    /// Write a checkpoint of the current state of execution to the file at `path`, from which
+    /// execution can later be resumed using `from_checkpoint()`.
+    ///
+    /// Jobs that are running have already taken their input values from their function, so they
+    /// are saved as ready to run, and will be executed again when execution is resumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state could not be serialized or the file could not be written
+    pub fn checkpoint(&self, path: &Path) -> Result<()> {
+        let mut checkpoint = self.clone();
+        checkpoint.make_running_jobs_ready();
+
+        // write to a temporary file first, so a crash while writing doesn't corrupt the checkpoint
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, serde_json::to_string(&checkpoint)?)?;
+        fs::rename(&temp_path, path)?;
+        debug!("Checkpoint of execution state written to '{}'", path.display());
+        Ok(())
+    }
+
+    // Make the jobs that are running ready to run again, at the front of the queue in the order
+    // they were created, as they have already taken their input values from their functions
+    fn make_running_jobs_ready(&mut self) {
+        let mut running_jobs: Vec<Job> = self.running_jobs.drain()
+            .map(|(_, job)| job).collect();
+        running_jobs.sort_by_key(|job| Reverse(job.payload.job_id));
+        for job in running_jobs {
+            self.ready_jobs.push_front(job);
+        }
+        self.dispatched.clear();
+        self.streamed_outputs.clear();
+    }
+
+    /// Take a snapshot of the state prior to `job`, just taken from the jobs ready to run, being
+    /// started. Execution can later be stepped back to it using `restore()`, when `job` and the
+    /// jobs that are running now will be executed again
+    #[cfg(feature = "debugger")]
+    pub(crate) fn snapshot_prior_to(&mut self, job: &Job) -> Snapshot {
+        let mut ready_jobs = self.ready_jobs.clone();
+        ready_jobs.push_front(job.clone());
+        let mut running_jobs: Vec<&Job> = self.running_jobs.values().collect();
+        running_jobs.sort_by_key(|running_job| Reverse(running_job.payload.job_id));
+        for running_job in running_jobs {
+            ready_jobs.push_front(running_job.clone());
+        }
+
+        let functions = Arc::new(Mutex::new(HashMap::new()));
+        self.changes = Arc::downgrade(&functions);
+        Snapshot {
+            blocked: self.blocked.clone(),
+            blocks: self.blocks.clone(),
+            ready_jobs,
+            completed: self.completed.clone(),
+            number_of_jobs_created: self.number_of_jobs_created,
+            busy_flows: self.busy_flows.clone(),
+            flow_blocks: self.flow_blocks.clone(),
+            aborted: self.aborted.clone(),
+            functions,
+        }
+    }
+
+    /// Step execution back to the first of `snapshots`, taken using `snapshot_prior_to()` in the
+    /// order given, and including all those taken after it, as each one only holds the functions
+    /// changed until the next one was taken
+    #[cfg(feature = "debugger")]
+    pub(crate) fn restore(&mut self, snapshots: Vec<Snapshot>) {
+        self.changes = Weak::new();
+
+        let mut restored = None;
+        for snapshot in snapshots.into_iter().rev() {
+            let changes = std::mem::take(&mut *snapshot.functions.lock()
+                .unwrap_or_else(PoisonError::into_inner));
+            for (id, function) in changes {
+                if let Some(current) = self.submission.manifest.get_functions().get_mut(id) {
+                    *current = function;
+                }
+            }
+            restored = Some(snapshot);
+        }
+
+        if let Some(snapshot) = restored {
+            self.blocked = snapshot.blocked;
+            self.blocks = snapshot.blocks;
+            self.ready_jobs = snapshot.ready_jobs;
+            self.running_jobs.clear();
+            self.completed = snapshot.completed;
+            self.number_of_jobs_created = snapshot.number_of_jobs_created;
+            self.busy_flows = snapshot.busy_flows;
+            self.flow_blocks = snapshot.flow_blocks;
+            self.aborted = snapshot.aborted;
+            self.dispatched.clear();
+            self.streamed_outputs.clear();
+        }
+    }
+
+    /// Replace the values queued up on input `input_number` of function `function_id` with
+    /// `values`, creating jobs for the function if that gives it more complete sets of inputs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the function or the input does not exist
+    #[cfg(feature = "debugger")]
+    pub(crate) fn set_input_values(&mut self, function_id: usize, input_number: usize,
+                                   values: Vec<Value>) -> Result<()> {
+        let function = self.get_mut(function_id).ok_or("Could not get function")?;
+        let job_count_before = function.input_sets_available();
+        function.set_input_values(input_number, values)?;
+        let flow_id = function.get_flow_id();
+        if function.input_sets_available() > job_count_before {
+            self.create_jobs_or_block(function_id, flow_id)?;
+        }
+        Ok(())
+    }
+
+    /// Create a `RunState` to resume execution from the checkpoint in the file at `path`, using
+    /// the options (parallel jobs, timeout, retries etc.) of the new `submission`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint could not be read, or is not of the flow in `submission`
+    pub fn from_checkpoint(path: &Path, submission: Submission) -> Result<Self> {
+        let mut state: RunState = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        if state.submission.manifest.get_metadata() != submission.manifest.get_metadata() ||
+            state.submission.manifest.functions().len() != submission.manifest.functions().len() {
+            bail!("Checkpoint '{}' is not of the flow submitted", path.display());
         }
+
+        // keep the manifest of the checkpoint, as its functions hold the values on their inputs
+        state.submission = Submission {
+            manifest: state.submission.manifest,
+            ..submission
+        };
+        state.aborted = None;
+        info!("Resuming execution from checkpoint '{}'", path.display());
+
+        Ok(state)
     }
 
-    #[cfg(any(debug_assertions, feature = "debugger"))]
     /// Get a reference to the vector of all functions
     pub(crate) fn get_functions(&self) -> &Vec<RuntimeFunction> {
         self.submission.manifest.functions()
@@ -245,10 +486,15 @@ impl RunState {
         self.blocks.clear();
         self.ready_jobs.clear();
         self.running_jobs.clear();
+        self.streamed_outputs.clear();
+        self.dispatched.clear();
         self.completed.clear();
         self.number_of_jobs_created = 0;
         self.busy_flows.clear();
         self.flow_blocks.clear();
+        self.aborted = None;
+        self.started = None;
+        self.memory_estimated = None;
     }
 
     /// The `ìnit()` function is responsible for initializing all functions, and it returns a 
@@ -288,7 +534,6 @@ impl RunState {
     }
 
     /// Return the states a function is in
-    #[cfg(any(debug_assertions, feature = "debugger", test))]
     #[must_use]
     pub fn get_function_states(&self, function_id: usize) -> Vec<State> {
         let mut states = vec![];
@@ -329,7 +574,6 @@ impl RunState {
     }
 
     /// Get a Set (`job_id`) of the currently running jobs
-    #[cfg(any(feature = "debugger", debug_assertions))]
     #[must_use]
     pub fn get_running(&self) -> &HashMap<usize, Job> {
         &self.running_jobs
@@ -341,47 +585,88 @@ impl RunState {
         self.submission.manifest.functions().get(id)
     }
 
-    // Get a mutable reference to the function with `id`
+    // Get a mutable reference to the function with `id`, first copying it into the most recent
+    // `Snapshot` if it is the first time it is changed since that was taken
     fn get_mut(&mut self, id: usize) -> Option<&mut RuntimeFunction> {
-        self.submission.manifest.get_functions().get_mut(id)
+        let function = self.submission.manifest.get_functions().get_mut(id)?;
+        #[cfg(feature = "debugger")]
+        if let Some(changes) = self.changes.upgrade() {
+            changes.lock().unwrap_or_else(PoisonError::into_inner)
+                .entry(id).or_insert_with(|| function.clone());
+        }
+        Some(function)
     }
 
     /// Get the `HashSet` of blocked function ids
-    #[cfg(any(debug_assertions, feature = "debugger"))]
     #[must_use]
     pub fn get_blocks(&self) -> &HashSet<Block> {
         &self.blocks
     }
 
-    #[cfg(debug_assertions)]
     /// Return the list of busy flows and what functions in each flow are busy
     #[must_use]
     pub fn get_busy_flows(&self) -> &MultiMap<usize, usize> {
         &self.busy_flows
     }
 
-    #[cfg(debug_assertions)]
     /// Return the list of pending unblocks
     #[must_use]
     pub fn get_flow_blocks(&self) -> &HashMap<usize, HashSet<usize>> {
         &self.flow_blocks
     }
 
-    // Return a new job to run, if there is one and there are not too many jobs already running
+    // Return a new job to run, if there is one that is not waiting to be retried later, there are
+    // not too many jobs already running and execution has not been aborted
     pub(crate) fn get_next_job(&mut self) -> Option<Job> {
-        if let Some(limit) = self.submission.max_parallel_jobs {
-            if self.number_jobs_running() >= limit {
+        if self.aborted.is_some() {
+            return None;
+        }
+
+        if self.parallel_jobs_limit_reached() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let index = self.dispatchable_jobs().position(|job| job.is_due(now))?;
+        self.ready_jobs.remove(index)
+    }
+
+    // Return true if the maximum number of jobs allowed to run in parallel are already running.
+    // In deterministic mode only one job is run at a time, so results are always received in the
+    // same order as the jobs were dispatched
+    fn parallel_jobs_limit_reached(&self) -> bool {
+        let limit = if self.submission.deterministic {
+            Some(1)
+        } else {
+            self.submission.max_parallel_jobs
+        };
+
+        match limit {
+            Some(limit) if self.number_jobs_running() >= limit => {
                 trace!("max_parallel_jobs limit of {limit} reached");
-                return None;
+                true
             }
+            _ => false,
         }
+    }
 
-        self.ready_jobs.remove(0)
+    // Return the ready jobs that are candidates to be dispatched next. In deterministic mode jobs
+    // are dispatched strictly in the order they became ready, so only the first one is
+    fn dispatchable_jobs(&self) -> impl Iterator<Item = &Job> {
+        let count = if self.submission.deterministic { 1 } else { self.ready_jobs.len() };
+        self.ready_jobs.iter().take(count)
     }
 
     // Update the run_state to reflect that the job is now running
     pub(crate) fn start_job(&mut self, job: Job) {
+        self.record(|| Event::JobDispatched {
+            job_id: job.payload.job_id,
+            function_id: job.function_id,
+            flow_id: job.flow_id,
+            inputs: job.payload.input_set.clone(),
+        });
         self.block_external_flow_senders(job.payload.job_id, job.function_id, job.flow_id);
+        self.dispatched.insert(job.payload.job_id, Dispatched { at: Instant::now(), redispatches: 0 });
         self.running_jobs.insert(job.payload.job_id, job);
     }
 
@@ -420,6 +705,17 @@ impl RunState {
         trace!("Job #{job_id}:\t\tAdded a flow_block -> #{blocker_function_id}({blocker_flow_id})");
     }
 
+    /// Set the latest `ExecutorMetrics` reported by the executors executing the flow's jobs
+    pub fn set_executor_metrics(&mut self, executor_metrics: Vec<ExecutorMetrics>) {
+        self.executor_metrics = executor_metrics;
+    }
+
+    /// Get the latest `ExecutorMetrics` reported by the executors executing the flow's jobs
+    #[must_use]
+    pub fn executor_metrics(&self) -> &[ExecutorMetrics] {
+        &self.executor_metrics
+    }
+
     /// get the number of jobs created to date in the flow's execution
     #[cfg(any(feature = "metrics", feature = "debugger"))]
     #[must_use]
@@ -427,6 +723,90 @@ impl RunState {
         self.number_of_jobs_created
     }
 
+    // Deliver a `Chunk` of an array output streamed back by a running job. Its elements are sent
+    // to the destinations of the job's connections that take the entire output one element at a
+    // time as they arrive, and kept to re-assemble the output for any other connections when the
+    // job is retired
+    #[allow(unused_variables, unused_assignments, unused_mut)]
+    pub(crate) fn deliver_chunk(
+        &mut self,
+        #[cfg(feature = "metrics")] metrics: &mut Metrics,
+        chunk: Chunk,
+        #[cfg(feature = "debugger")] debugger: &mut Debugger,
+    ) -> Result<(bool, bool)> {
+        let mut display_next_output = false;
+        let mut restart = false;
+
+        let job = self
+            .running_jobs
+            .get(&chunk.job_id)
+            .ok_or_else(|| format!("Could not find Job#{} to deliver a chunk of its output",
+                                   chunk.job_id))?;
+        let (function_id, flow_id) = (job.function_id, job.flow_id);
+        let connections = job.connections.clone();
+
+        let streamed_connections = match self.streamed_outputs.get(&chunk.job_id) {
+            Some(streamed_output) => streamed_output.streamed_connections.clone(),
+            None => {
+                let streamed_output = self.stream_output(function_id, &connections,
+                                                         chunk.elements.first());
+                let streamed_connections = streamed_output.streamed_connections.clone();
+                self.streamed_outputs.insert(chunk.job_id, streamed_output);
+                streamed_connections
+            }
+        };
+
+        trace!("Job #{}: Chunk of {} elements received", chunk.job_id, chunk.elements.len());
+        for element in &chunk.elements {
+            let element = Arc::new(element.clone());
+            for connection in streamed_connections.iter().filter_map(|index| connections.get(*index)) {
+                (display_next_output, restart) = self.send_a_value(
+                    function_id,
+                    flow_id,
+                    connection,
+                    element.clone(),
+                    #[cfg(feature = "metrics")]
+                        metrics,
+                    #[cfg(feature = "debugger")]
+                        debugger,
+                )?;
+            }
+        }
+
+        if let Some(kept_elements) = self.streamed_outputs.get_mut(&chunk.job_id)
+            .and_then(|streamed_output| streamed_output.kept_elements.as_mut()) {
+            kept_elements.extend(chunk.elements);
+        }
+
+        Ok((display_next_output, restart))
+    }
+
+    // Determine which of the `connections` of a function the elements of an array output, such
+    // as `element`, can be sent to one at a time as they are streamed back. Loopbacks are excluded
+    // as the function is still running
+    fn stream_output(&self, function_id: usize, connections: &[OutputConnection],
+                     element: Option<&Value>) -> StreamedOutput {
+        let mut streamed_output = StreamedOutput::default();
+        let mut keep_elements = false;
+
+        for (index, connection) in connections.iter().enumerate() {
+            let accepts_elements = connection.forwards_entire_output()
+                && connection.destination_id != function_id
+                && element.is_some_and(|element| self.get_function(connection.destination_id)
+                    .is_some_and(|function|
+                        function.accepts_elements(connection.destination_io_number, element)));
+
+            if accepts_elements {
+                streamed_output.streamed_connections.push(index);
+            } else if matches!(connection.source, Output(_)) {
+                keep_elements = true;
+            }
+        }
+
+        streamed_output.kept_elements = keep_elements.then(Vec::new);
+        streamed_output
+    }
+
     // Complete a Job by taking its output and updating the run-list accordingly.
     //
     // If other functions were blocked trying to send to this one - we can now unblock them
@@ -439,7 +819,7 @@ impl RunState {
     pub(crate) fn retire_a_job(
         &mut self,
         #[cfg(feature = "metrics")] metrics: &mut Metrics,
-        result: (usize, Result<(Option<Value>, RunAgain)>),
+        mut result: (usize, Result<(Option<Value>, RunAgain)>),
         #[cfg(feature = "debugger")] debugger: &mut Debugger,
     ) -> Result<(bool, bool, Job)> {
         let mut display_next_output = false;
@@ -449,6 +829,45 @@ impl RunState {
             .running_jobs
             .remove(&result.0)
             .ok_or_else(|| format!("Could not find Job#{} to retire it", result.0))?;
+        self.dispatched.remove(&result.0);
+
+        // If the output was streamed back in chunks, then re-assemble it from the elements kept
+        // for the connections that they were not already sent to
+        let streamed_connections = match self.streamed_outputs.remove(&result.0) {
+            Some(streamed_output) => {
+                if let (Ok((output, _)), Some(elements)) =
+                    (&mut result.1, streamed_output.kept_elements) {
+                    *output = Some(Value::Array(elements));
+                }
+                streamed_output.streamed_connections
+            }
+            None => vec![],
+        };
+
+        self.record(|| Event::JobCompleted {
+            job_id: job.payload.job_id,
+            function_id: job.function_id,
+            flow_id: job.flow_id,
+            output: result.1.as_ref().ok().and_then(|(output, _)| output.clone()),
+            error: result.1.as_ref().err().map(ToString::to_string),
+        });
+
+        #[cfg(feature = "metrics")]
+        metrics.increment_function_executions(job.function_id);
+
+        // If the job failed and its retry policy allows, put it back in the ready queue to be
+        // retried later, leaving its flow busy as it has not completed. A job that ran out of
+        // fuel is not retried, as it would run out of fuel again
+        if let Err(e) = &result.1 {
+            if let Some(backoff) = job.retry_backoff().filter(|_| !is_out_of_fuel(e)) {
+                warn!("Error in Job #{}: {e}. Retrying in {backoff:?}", job.payload.job_id);
+                job.retries += 1;
+                job.retry_at = Some(Instant::now() + backoff);
+                job.result = result.1;
+                self.ready_jobs.push_back(job.clone());
+                return Ok((display_next_output, restart, job));
+            }
+        }
 
         match &result.1 {
             Ok((output_value, function_can_run_again)) => {
@@ -469,13 +888,28 @@ impl RunState {
                     job.payload.job_id, job.function_id, job.payload.input_set, output_value
                 );
 
-                for connection in &job.connections {
+                // The output (or the part of it selected by a route) is copied once, and then
+                // shared by all the inputs it is sent to, however many connections there are
+                let mut output_values: HashMap<&str, Arc<Value>> = HashMap::new();
+                for (index, connection) in job.connections.iter().enumerate() {
+                    if streamed_connections.contains(&index) {
+                        continue;
+                    }
+
                     let value_to_send = match &connection.source {
-                        Output(route) => match output_value {
-                            Some(output_v) => output_v.pointer(route),
-                            None => None,
+                        Output(route) => match output_values.get(route.as_str()) {
+                            Some(value) => Some(value.clone()),
+                            None => {
+                                let value = output_value.as_ref()
+                                    .and_then(|output_v| output_v.pointer(route))
+                                    .map(|value| Arc::new(value.clone()));
+                                if let Some(value) = &value {
+                                    output_values.insert(route, value.clone());
+                                }
+                                value
+                            }
                         },
-                        Input(index) => job.payload.input_set.get(*index),
+                        Input(index) => job.payload.input_set.get(*index).cloned(),
                     };
 
                     if let Some(value) = value_to_send {
@@ -483,7 +917,7 @@ impl RunState {
                             job.function_id,
                             job.flow_id,
                             connection,
-                            value.clone(),
+                            value,
                             #[cfg(feature = "metrics")]
                                 metrics,
                             #[cfg(feature = "debugger")]
@@ -516,6 +950,14 @@ impl RunState {
             }
             Err(e) => {
                 error!("Error in Job #{}: {e}", job.payload.job_id);
+                #[cfg(feature = "metrics")]
+                if is_out_of_fuel(e) {
+                    metrics.increment_jobs_out_of_fuel();
+                }
+                if job.retry_policy.is_some() && self.aborted.is_none() {
+                    self.aborted = Some(format!("Job #{} for Function #{} failed after {} retries: {e}",
+                                                job.payload.job_id, job.function_id, job.retries));
+                }
             }
         }
 
@@ -546,7 +988,7 @@ impl RunState {
         source_id: usize,
         source_flow_id: usize,
         connection: &OutputConnection,
-        output_value: Value,
+        output_value: Arc<Value>,
         #[cfg(feature = "metrics")] metrics: &mut Metrics,
         #[cfg(feature = "debugger")] debugger: &mut Debugger,
     ) -> Result<(bool, bool)> {
@@ -572,6 +1014,9 @@ impl RunState {
             );
         };
 
+        // the value being sent can be replaced from the debugger while stopped prior to sending it
+        #[cfg(feature = "debugger")]
+        let mut output_value = output_value;
         #[cfg(feature = "debugger")]
         if let Output(route) = &connection.source {
             (display_next_output, restart) = debugger.check_prior_to_send(
@@ -582,8 +1027,19 @@ impl RunState {
                 connection.destination_id,
                 connection.destination_io_number,
             )?;
+
+            if let Some(value) = debugger.take_value_to_send() {
+                output_value = Arc::new(value);
+            }
         }
 
+        self.record(|| Event::ValueSent {
+            source_id,
+            destination_id: connection.destination_id,
+            input_number: connection.destination_io_number,
+            value: output_value.clone(),
+        });
+
         let function = self
             .get_mut(connection.destination_id)
             .ok_or("Could not get function")?;
@@ -623,7 +1079,6 @@ impl RunState {
     }
 
     /// Get the set of `blocking_function_id` causing the block on function `id`
-    #[cfg(any(feature = "debugger", debug_assertions))]
     #[must_use]
     pub fn get_output_blockers(&self, id: usize) -> Vec<usize> {
         let mut blockers = vec![];
@@ -659,9 +1114,173 @@ impl RunState {
         self.ready_jobs.len()
     }
 
+    // Return how many ready jobs can be dispatched now, i.e. excluding those waiting to be retried
+    // later, or none if execution has been aborted or the limit of parallel jobs has been reached
+    pub(crate) fn number_jobs_due(&self) -> usize {
+        if self.aborted.is_some() || self.parallel_jobs_limit_reached() {
+            return 0;
+        }
+        let now = Instant::now();
+        self.dispatchable_jobs().filter(|job| job.is_due(now)).count()
+    }
+
+    // If there are ready jobs but none of them can be dispatched now as they are waiting to be
+    // retried, return how long until the first of them can be
+    pub(crate) fn retry_delay(&self) -> Option<Duration> {
+        if self.aborted.is_some() {
+            return None;
+        }
+        let now = Instant::now();
+        self.dispatchable_jobs()
+            .map(|job| job.retry_at.map_or(Duration::ZERO,
+                                           |retry_at| retry_at.saturating_duration_since(now)))
+            .min()
+            .filter(|delay| !delay.is_zero())
+    }
+
+    // If the `Submission` has a job timeout, return how long until the first running job whose
+    // result has not been received times out
+    pub(crate) fn job_timeout_delay(&self) -> Option<Duration> {
+        let timeout = self.submission.job_timeout?;
+        let now = Instant::now();
+        self.dispatched.values()
+            .map(|dispatched| (dispatched.at + timeout).saturating_duration_since(now))
+            .min()
+    }
+
+    // Return what to do with the running jobs whose results have not been received within the
+    // job timeout of the `Submission`. A job is dispatched again as many times as its
+    // `JobTimeoutPolicy` allows, and then fails. A job that has streamed chunks of its output
+    // fails straight away, as executing it again would stream them again. The timeout of a job
+    // that is `held_back` by the dispatcher, so not yet sent to executors, starts when it is sent
+    pub(crate) fn take_timed_out_jobs(&mut self, held_back: impl Fn(usize) -> bool)
+        -> Vec<TimedOutJob> {
+        let Some(timeout) = self.submission.job_timeout else {
+            return vec![];
+        };
+        let redispatches = match self.submission.job_timeout_policy {
+            JobTimeoutPolicy::Fail => 0,
+            JobTimeoutPolicy::Redispatch(times) => times,
+        };
+
+        let mut timed_out = vec![];
+        for (job_id, dispatched) in &mut self.dispatched {
+            if held_back(*job_id) {
+                dispatched.at = Instant::now();
+                continue;
+            }
+            if dispatched.at.elapsed() < timeout {
+                continue;
+            }
+            let Some(job) = self.running_jobs.get(job_id) else {
+                continue;
+            };
+
+            if dispatched.redispatches < redispatches && !self.streamed_outputs.contains_key(job_id) {
+                dispatched.at = Instant::now();
+                dispatched.redispatches += 1;
+                warn!("No result received for Job #{job_id} within {timeout:?}, dispatching it again");
+                timed_out.push(TimedOutJob::Redispatch(job.payload.clone()));
+            } else {
+                timed_out.push(TimedOutJob::Fail(*job_id,
+                    format!("No result received for Job #{job_id} within the job timeout of {timeout:?}")));
+            }
+        }
+
+        // a job that fails is not checked again, as failing is its result
+        for timed_out_job in &timed_out {
+            if let TimedOutJob::Fail(job_id, _) = timed_out_job {
+                self.dispatched.remove(job_id);
+            }
+        }
+
+        timed_out
+    }
+
+    /// Return true if execution of the flow was cancelled by the client that submitted it
+    #[must_use]
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    // Cancel execution of the flow. Jobs ready to run are discarded, and jobs running are
+    // forgotten, as their results will not be used
+    #[cfg(any(feature = "submission", test))]
+    pub(crate) fn cancel(&mut self) {
+        self.cancelled = true;
+        self.ready_jobs.clear();
+        self.running_jobs.clear();
+        self.streamed_outputs.clear();
+        self.dispatched.clear();
+    }
+
+    // Check if execution has exceeded one of the `ResourceLimits` of the `Submission`, returning
+    // a description of the limit exceeded if it has. The memory used by values is estimated at
+    // most once every `MEMORY_ESTIMATE_INTERVAL`
+    pub(crate) fn limit_exceeded(&mut self) -> Option<String> {
+        let limits = self.submission.limits;
+        if limits.is_unlimited() || self.aborted.is_some() {
+            return None;
+        }
+
+        if let Some(max_jobs) = limits.max_jobs {
+            if self.number_of_jobs_created > max_jobs {
+                return Some(format!("it created more than the limit of {max_jobs} jobs"));
+            }
+        }
+
+        if let Some(max_time) = limits.max_time {
+            let started = *self.started.get_or_insert_with(Instant::now);
+            if started.elapsed() > max_time {
+                return Some(format!("it executed for longer than the limit of {max_time:?}"));
+            }
+        }
+
+        if let Some(max_memory) = limits.max_memory {
+            if self.memory_estimated.is_none_or(|at| at.elapsed() >= MEMORY_ESTIMATE_INTERVAL) {
+                self.memory_estimated = Some(Instant::now());
+                let memory = self.memory_estimate();
+                if memory > max_memory {
+                    return Some(format!("its values used an estimated {memory} bytes, more than \
+                    the limit of {max_memory} bytes"));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Estimate the memory used by the values held on the inputs of functions, and of jobs ready
+    // to run or running, as their size when serialized as JSON
+    fn memory_estimate(&self) -> usize {
+        let inputs: usize = self.get_functions().iter()
+            .flat_map(RuntimeFunction::inputs)
+            .map(flowcore::model::input::Input::values_size)
+            .sum();
+        let jobs: usize = self.ready_jobs.iter().chain(self.running_jobs.values())
+            .flat_map(|job| job.payload.input_sizes())
+            .sum();
+        inputs + jobs
+    }
+
+    // Abort execution of the flow for `reason`. Jobs ready to run are discarded, and jobs running
+    // are forgotten, as their results will not be used
+    pub(crate) fn abort(&mut self, reason: String) {
+        self.aborted = Some(reason);
+        self.ready_jobs.clear();
+        self.running_jobs.clear();
+        self.streamed_outputs.clear();
+        self.dispatched.clear();
+    }
+
+    /// Return the reason execution of the flow was aborted, if it was
+    #[must_use]
+    pub fn aborted(&self) -> Option<&str> {
+        self.aborted.as_deref()
+    }
+
     /// An input blocker is another function that is the only function connected to an empty input
     /// of target function, and which is not ready to run, hence target function cannot run.
-    #[cfg(feature = "debugger")]
     pub(crate) fn get_input_blockers(&self, target_id: usize) -> Result<Vec<usize>> {
         let mut input_blockers = vec![];
         let target_function = self.get_function(target_id).ok_or("No such function")?;
@@ -729,6 +1348,11 @@ impl RunState {
         loop {
             self.number_of_jobs_created += 1;
             let job_id = self.number_of_jobs_created;
+            let default_retry_policy = self.submission.retry_policy;
+            let chunk_size = self.submission.chunk_size;
+            let validate = self.submission.validate;
+            let fuel = self.submission.fuel;
+            let submission_id = self.submission_id;
             let function = self.get_mut(function_id).ok_or("Could not get function")?;
             if let Some(input_set) = function.take_input_set() {
                 let implementation_url = function.get_implementation_url().clone();
@@ -744,8 +1368,19 @@ impl RunState {
                         job_id,
                         input_set,
                         implementation_url,
+                        implementation_hash: function.implementation_hash().cloned(),
+                        pure: function.is_pure(),
+                        chunk_size,
+                        input_schemas: if validate { function.input_schemas() } else { vec![] },
+                        fuel,
+                        submission_id,
+                        priority: function.priority(),
                     },
                     result: Ok((None, false)),
+                    retry_policy: RetryPolicy::for_function(function.retries(),
+                                                            default_retry_policy),
+                    retries: 0,
+                    retry_at: None,
                 };
 
                 // avoid getting stuck in a loop generating jobs for a function - generate just one
@@ -796,6 +1431,9 @@ impl RunState {
             }
 
             if let Some(blocker_functions) = self.flow_blocks.remove(&job.flow_id) {
+                // unblock in a fixed order, so jobs are created in the same order on every run
+                let mut blocker_functions: Vec<usize> = blocker_functions.into_iter().collect();
+                blocker_functions.sort_unstable();
                 for blocker_function_id in blocker_functions {
                     self.remove_blocks(blocker_function_id)?;
                 }
@@ -834,11 +1472,14 @@ impl RunState {
             }
         }
 
-        // Remove blocks between the sender and the destination. Note that a sender can send to
-        // multiple destinations and so could still be blocked sending to other functions
+        // Remove blocks between the sender and the destination in a fixed order, so jobs are created
+        // in the same order on every run. Note that a sender can send to multiple destinations
+        // and so could still be blocked sending to other functions
+        blocks_to_remove.sort_by_key(|block| (block.blocked_function_id, block.blocking_io_number));
         for block in blocks_to_remove {
             self.blocks.remove(&block);
             trace!("\t\t\tBlock removed {:?}", block);
+            self.record(|| Event::BlockRemoved(block.clone()));
 
             if self.blocked.contains(&block.blocked_function_id)
                 && !self.block_exists(block.blocked_function_id)
@@ -863,16 +1504,21 @@ impl RunState {
 
     // Do not run initializers on functions that have completed
     fn run_flow_initializers(&mut self, flow_id: usize) -> Result<()> {
+        let flow_function_ids: Vec<usize> = self.get_functions().iter()
+            .filter(|function| function.get_flow_id() == flow_id
+                && !self.completed.contains(&function.id()))
+            .map(RuntimeFunction::id)
+            .collect();
+
         let mut initialized_functions = Vec::<usize>::new();
-        for function in &mut self.submission.manifest.get_functions().iter_mut() {
-            if function.get_flow_id() == flow_id && !self.completed.contains(&function.id()) {
-                let could_run_before = function.can_run();
-                function.init_inputs(false, true);
-                let can_run_now = function.can_run();
-
-                if can_run_now && !could_run_before {
-                    initialized_functions.push(function.id());
-                }
+        for function_id in flow_function_ids {
+            let function = self.get_mut(function_id).ok_or("Could not get function")?;
+            let could_run_before = function.can_run();
+            function.init_inputs(false, true);
+            let can_run_now = function.can_run();
+
+            if can_run_now && !could_run_before {
+                initialized_functions.push(function_id);
             }
         }
 
@@ -883,6 +1529,39 @@ impl RunState {
         Ok(())
     }
 
+    // Get the id of the submission being executed
+    pub(crate) fn submission_id(&self) -> usize {
+        self.submission_id
+    }
+
+    // Set the id of the submission being executed, that is included in jobs created from now on
+    // and in jobs ready to run (such as those restored from a checkpoint), so that their results
+    // can be returned to this `RunState` when several submissions are being executed
+    pub(crate) fn set_submission_id(&mut self, submission_id: usize) {
+        self.submission_id = submission_id;
+        for job in &mut self.ready_jobs {
+            job.payload.submission_id = submission_id;
+        }
+    }
+
+    // Start recording `Event`s that happen, so they can be taken using `take_events()`
+    pub(crate) fn record_events(&mut self) {
+        self.events.get_or_insert_with(Vec::new);
+    }
+
+    // Take the `Event`s that have happened since they were last taken
+    pub(crate) fn take_events(&mut self) -> Vec<Event> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    // Record an `Event` created by `event` if events are being recorded, avoiding creating it if
+    // they are not
+    fn record(&mut self, event: impl FnOnce() -> Event) {
+        if let Some(events) = &mut self.events {
+            events.push(event());
+        }
+    }
+
     // Mark a function (via its ID) as having run to completion
     pub(crate) fn mark_as_completed(&mut self, function_id: usize) {
         self.completed.insert(function_id);
@@ -910,6 +1589,7 @@ impl RunState {
 
         trace!("\t\t\t\t\tCreating Block {:?}", block);
         self.blocks.insert(block.clone());
+        self.record(|| Event::BlockCreated(block.clone()));
         #[cfg(feature = "debugger")]
         return debugger.check_on_block_creation(self, &block);
         #[cfg(not(feature = "debugger"))]
@@ -937,12 +1617,20 @@ impl fmt::Display for RunState {
         )?;
         writeln!(f, "   Functions Completed: {:?}", self.completed)?;
         writeln!(f, "            Flows Busy: {:?}", self.busy_flows)?;
+        if let Some(reason) = &self.aborted {
+            writeln!(f, "               Aborted: {reason}")?;
+        }
+        for executor in &self.executor_metrics {
+            writeln!(f, "              Executor: {executor}")?;
+        }
         write!(f, "        Pending Unblocks: {:?}", self.flow_blocks)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
+
     use serde_json::{json, Value};
     use url::Url;
 
@@ -952,7 +1640,9 @@ mod test {
     use flowcore::model::input::InputInitializer::Once;
     use flowcore::model::metadata::MetaData;
     use flowcore::model::output_connection::{OutputConnection, Source};
-    use flowcore::model::runtime_function::RuntimeFunction;
+    use flowcore::model::runtime_function::{Priority, RuntimeFunction};
+    #[cfg(feature = "debugger")]
+    use flowcore::model::source_location::SourceLocation;
     use flowcore::model::submission::Submission;
 
     #[cfg(feature = "debugger")]
@@ -1093,9 +1783,19 @@ mod test {
             payload: Payload {
                 job_id: 1,
                 implementation_url: Url::parse("file://test").expect("Could not parse Url"),
-                input_set: vec![json!(1)],
+                implementation_hash: None,
+                pure: false,
+                chunk_size: None,
+                input_schemas: vec![],
+                fuel: None,
+                submission_id: 0,
+                priority: Priority::Normal,
+                input_set: vec![Arc::new(json!(1))],
             },
             result: Ok((Some(json!(1)), true)),
+            retry_policy: None,
+            retries: 0,
+            retry_at: None,
         }
     }
 
@@ -1118,9 +1818,11 @@ mod test {
             _destination_name: &str,
             _input_name: &str,
             _input_number: usize,
+            _source_location: Option<&SourceLocation>,
         ) {}
-        fn job_error(&mut self, _job: &Job) {}
+        fn job_error(&mut self, _job: &Job, _source_location: Option<&SourceLocation>) {}
         fn job_completed(&mut self, _job: &Job) {}
+        fn watch_value(&mut self, _job: &Job, _function_name: &str, _output_route: &str, _value: &Value) {}
         fn blocks(&mut self, _blocks: Vec<Block>) {}
         fn outputs(&mut self, _output: Vec<OutputConnection>) {}
         fn input(&mut self, _input: Input) {}
@@ -1166,15 +1868,28 @@ mod test {
             test_manifest(functions),
             None,
             None,
+            None,
+            None,
             #[cfg(feature = "debugger")]
                 true,
         )
     }
 
+    // Create an initialized state for `functions`, with their `Submission` adjusted by `adjust`
+    fn test_state(functions: Vec<RuntimeFunction>, adjust: impl FnOnce(&mut Submission)) -> RunState {
+        let mut submission = test_submission(functions);
+        adjust(&mut submission);
+        let mut state = RunState::new(submission);
+        state.init().expect("Could not init state");
+        state
+    }
+
     mod general_run_state_tests {
         #[cfg(feature = "debugger")]
         use std::collections::HashSet;
 
+        use flowcore::model::metrics::ExecutorMetrics;
+
         use super::super::RunState;
 
         #[test]
@@ -1190,6 +1905,22 @@ mod test {
             println!("Run state: {state}");
         }
 
+        #[test]
+        fn display_executor_metrics() {
+            let mut state = RunState::new(super::test_submission(vec![]));
+            state.set_executor_metrics(vec![ExecutorMetrics {
+                executor_id: "test".into(),
+                threads: 2,
+                jobs_executed: 10,
+                average_job_ms: 0.5,
+                implementation_cache_hits: 9,
+                load: 0.25,
+            }]);
+            assert_eq!(state.executor_metrics().len(), 1);
+            assert!(state.to_string().contains(
+                "Executor: test (2 threads): 10 jobs, 0.5ms average, 9 cache hits, 25% load"));
+        }
+
         #[cfg(feature = "metrics")]
         #[test]
         fn jobs_created_zero_at_init() {
@@ -1241,6 +1972,8 @@ mod test {
 
     /********************************* State Transition Tests *********************************/
     mod state_transitions {
+        use std::sync::Arc;
+
         use serde_json::json;
         use serial_test::serial;
         use url::Url;
@@ -1250,8 +1983,9 @@ mod test {
         #[cfg(feature = "metrics")]
         use flowcore::model::metrics::Metrics;
         use flowcore::model::output_connection::{OutputConnection, Source};
-        use flowcore::model::runtime_function::RuntimeFunction;
+        use flowcore::model::runtime_function::{Priority, RuntimeFunction};
 
+        use crate::event_sink::Event;
         use crate::run_state::test::test_function_b_not_init;
 
         use super::super::RunState;
@@ -1427,9 +2161,19 @@ mod test {
                 payload: Payload {
                     job_id: 1,
                     implementation_url: Url::parse("file://test").expect("Could not parse Url"),
-                    input_set: vec![json!(1)],
+                    implementation_hash: None,
+                    pure: false,
+                    chunk_size: None,
+                    input_schemas: vec![],
+                    fuel: None,
+                    submission_id: 0,
+                    priority: Priority::Normal,
+                    input_set: vec![Arc::new(json!(1))],
                 },
                 result: (Ok((None, true))),
+                retry_policy: None,
+                retries: 0,
+                retry_at: None,
             }
         }
 
@@ -1689,6 +2433,63 @@ mod test {
                 "f_a should be Ready"
             );
         }
+
+        #[test]
+        #[serial]
+        fn cancel_discards_jobs() {
+            let f_a = super::test_function_a_init();
+            let f_b = super::test_function_b_not_init();
+            let mut state = RunState::new(super::test_submission(vec![f_a, f_b]));
+            state.init().expect("Could not init state");
+            assert_eq!(state.number_jobs_ready(), 1);
+            state.start_job(super::test_job(0, 1));
+            assert!(!state.cancelled());
+
+            state.cancel();
+
+            assert!(state.cancelled());
+            assert_eq!(state.number_jobs_ready(), 0);
+            assert_eq!(state.number_jobs_running(), 0);
+        }
+
+        #[test]
+        #[serial]
+        fn events_recorded() {
+            let f_a = super::test_function_a_to_b_not_init();
+            let f_b = super::test_function_b_not_init();
+            let mut state = RunState::new(super::test_submission(vec![f_a, f_b]));
+            #[cfg(feature = "metrics")]
+                let mut metrics = Metrics::new(1);
+            #[cfg(feature = "debugger")]
+                let mut server = super::DummyServer {};
+            #[cfg(feature = "debugger")]
+                let mut debugger = super::dummy_debugger(&mut server);
+
+            state.init().expect("Could not init state");
+            assert!(state.take_events().is_empty(), "Events should not be recorded by default");
+            state.record_events();
+
+            let job = super::test_job(0, 1);
+            state.start_job(job.clone());
+            state
+                .retire_a_job(
+                    #[cfg(feature = "metrics")]
+                        &mut metrics,
+                    (job.payload.job_id, job.result),
+                    #[cfg(feature = "debugger")]
+                        &mut debugger,
+                )
+                .expect("Problem retiring job");
+
+            let events = state.take_events();
+            assert_eq!(events.first(), Some(&Event::JobDispatched {
+                job_id: 1, function_id: 0, flow_id: 0, inputs: vec![Arc::new(json!(1))] }));
+            assert_eq!(events.get(1), Some(&Event::JobCompleted {
+                job_id: 1, function_id: 0, flow_id: 0, output: Some(json!(1)), error: None }));
+            assert_eq!(events.get(2), Some(&Event::ValueSent {
+                source_id: 0, destination_id: 1, input_number: 0, value: Arc::new(json!(1)) }));
+            assert!(state.take_events().is_empty(), "Events should have been taken");
+        }
     }
 
     /****************************** Miscellaneous tests **************************/
@@ -1961,4 +2762,432 @@ mod test {
                 .expect("Failed to retire job correctly");
         }
     }
+
+    mod retry_tests {
+        use std::time::Duration;
+
+        #[cfg(feature = "metrics")]
+        use flowcore::model::metrics::Metrics;
+        use flowcore::model::submission::RetryPolicy;
+
+        use super::super::RunState;
+
+        // Create a state for a function that is ready to run, with `retry_policy`
+        fn test_state(retry_policy: RetryPolicy) -> RunState {
+            super::test_state(vec![super::test_function_a_init()],
+                              |submission| submission.retry_policy = Some(retry_policy))
+        }
+
+        // Dispatch the next job, and retire it as having failed
+        fn run_failing_job(state: &mut RunState) {
+            #[cfg(feature = "metrics")]
+                let mut metrics = Metrics::new(1);
+            #[cfg(feature = "debugger")]
+                let mut server = super::DummyServer {};
+            #[cfg(feature = "debugger")]
+                let mut debugger = super::dummy_debugger(&mut server);
+
+            let job = state.get_next_job().expect("Couldn't get next job");
+            let job_id = job.payload.job_id;
+            state.start_job(job);
+            state
+                .retire_a_job(
+                    #[cfg(feature = "metrics")]
+                        &mut metrics,
+                    (job_id, Err("Failed".into())),
+                    #[cfg(feature = "debugger")]
+                        &mut debugger,
+                )
+                .expect("Problem retiring job");
+        }
+
+        #[test]
+        fn failed_job_waits_for_retry() {
+            let mut state = test_state(RetryPolicy { retries: 1, backoff: Duration::from_secs(60) });
+            run_failing_job(&mut state);
+
+            assert_eq!(state.number_jobs_running(), 0);
+            assert_eq!(state.number_jobs_ready(), 1);
+            assert_eq!(state.number_jobs_due(), 0);
+            assert!(state.retry_delay().is_some_and(|delay| delay <= Duration::from_secs(60)));
+            assert!(state.get_next_job().is_none(), "Job should not be retried yet");
+            assert!(state.aborted().is_none());
+        }
+
+        #[test]
+        fn failed_job_is_retried() {
+            let mut state = test_state(RetryPolicy { retries: 2, backoff: Duration::ZERO });
+            run_failing_job(&mut state);
+            assert_eq!(state.retry_delay(), None);
+
+            let job = state.get_next_job().expect("Job should be retried");
+            assert_eq!(job.function_id, 0);
+            assert_eq!(job.retries, 1);
+        }
+
+        #[test]
+        fn retries_exhausted_aborts() {
+            let mut state = test_state(RetryPolicy { retries: 1, backoff: Duration::ZERO });
+            run_failing_job(&mut state);
+            assert!(state.aborted().is_none());
+            run_failing_job(&mut state);
+
+            assert!(state.aborted().is_some_and(|reason| reason.contains("failed after 1 retries")));
+            assert_eq!(state.number_jobs_due(), 0);
+            assert!(state.get_next_job().is_none());
+        }
+    }
+
+    mod timeout_tests {
+        use std::time::Duration;
+
+        use flowcore::model::submission::JobTimeoutPolicy;
+
+        use super::super::{RunState, TimedOutJob};
+
+        // Create a state for a function that is ready to run, and dispatch its job
+        fn test_state(job_timeout: Duration, job_timeout_policy: JobTimeoutPolicy) -> (RunState, usize) {
+            let mut state = super::test_state(vec![super::test_function_a_init()], |submission| {
+                submission.job_timeout = Some(job_timeout);
+                submission.job_timeout_policy = job_timeout_policy;
+            });
+            let job = state.get_next_job().expect("Couldn't get next job");
+            let job_id = job.payload.job_id;
+            state.start_job(job);
+            (state, job_id)
+        }
+
+        #[test]
+        fn no_timeout_before_job_timeout() {
+            let (mut state, _) = test_state(Duration::from_secs(60), JobTimeoutPolicy::Fail);
+            assert!(state.take_timed_out_jobs(|_| false).is_empty());
+            assert!(state.job_timeout_delay().is_some_and(|delay| delay <= Duration::from_secs(60)
+                && !delay.is_zero()));
+        }
+
+        #[test]
+        fn timed_out_job_fails() {
+            let (mut state, job_id) = test_state(Duration::ZERO, JobTimeoutPolicy::Fail);
+            let timed_out = state.take_timed_out_jobs(|_| false);
+            assert!(matches!(timed_out.as_slice(), [TimedOutJob::Fail(id, _)] if *id == job_id));
+            assert!(state.take_timed_out_jobs(|_| false).is_empty(), "Job should only fail once");
+            assert_eq!(state.number_jobs_running(), 1);
+        }
+
+        #[test]
+        fn timed_out_job_redispatched_then_fails() {
+            let (mut state, job_id) = test_state(Duration::ZERO, JobTimeoutPolicy::Redispatch(1));
+            let timed_out = state.take_timed_out_jobs(|_| false);
+            assert!(matches!(timed_out.as_slice(),
+                [TimedOutJob::Redispatch(payload)] if payload.job_id == job_id));
+            let timed_out = state.take_timed_out_jobs(|_| false);
+            assert!(matches!(timed_out.as_slice(), [TimedOutJob::Fail(id, _)] if *id == job_id));
+        }
+
+        #[test]
+        fn held_back_job_not_timed_out() {
+            let (mut state, job_id) = test_state(Duration::ZERO, JobTimeoutPolicy::Fail);
+            assert!(state.take_timed_out_jobs(|id| id == job_id).is_empty());
+            let timed_out = state.take_timed_out_jobs(|_| false);
+            assert!(matches!(timed_out.as_slice(), [TimedOutJob::Fail(id, _)] if *id == job_id));
+        }
+    }
+
+    mod limits_tests {
+        use flowcore::model::submission::ResourceLimits;
+
+        use super::super::RunState;
+
+        // Create a state for a function that is ready to run, with `limits`
+        fn test_state(limits: ResourceLimits) -> RunState {
+            super::test_state(vec![super::test_function_a_init()],
+                              |submission| submission.limits = limits)
+        }
+
+        #[test]
+        fn unlimited_not_exceeded() {
+            let mut state = test_state(ResourceLimits::default());
+            assert!(state.limit_exceeded().is_none());
+        }
+
+        #[test]
+        fn jobs_limit_exceeded() {
+            let mut state = test_state(ResourceLimits { max_jobs: Some(0), ..Default::default() });
+            assert!(state.limit_exceeded().is_some_and(|reason| reason.contains("0 jobs")));
+        }
+
+        #[test]
+        fn jobs_limit_not_exceeded() {
+            let mut state = test_state(ResourceLimits { max_jobs: Some(1), ..Default::default() });
+            assert!(state.limit_exceeded().is_none());
+        }
+
+        #[test]
+        fn memory_limit_exceeded() {
+            let mut state = test_state(ResourceLimits { max_memory: Some(0), ..Default::default() });
+            assert!(state.limit_exceeded().is_some_and(|reason| reason.contains("bytes")));
+        }
+
+        #[test]
+        fn abort_discards_jobs() {
+            let mut state = test_state(ResourceLimits { max_jobs: Some(0), ..Default::default() });
+            let reason = state.limit_exceeded().expect("Limit should be exceeded");
+            state.abort(reason);
+            assert_eq!(state.number_jobs_ready(), 0);
+            assert!(state.get_next_job().is_none());
+            assert!(state.aborted().is_some());
+            assert!(state.limit_exceeded().is_none(), "Limits not checked once aborted");
+        }
+    }
+
+    mod deterministic_tests {
+        use serde_json::json;
+
+        use flowcore::model::input::Input;
+        use flowcore::model::input::InputInitializer::Once;
+        use flowcore::model::runtime_function::RuntimeFunction;
+
+        use super::super::RunState;
+
+        fn test_function_b_init() -> RuntimeFunction {
+            RuntimeFunction::new(
+                #[cfg(feature = "debugger")]
+                    "fB",
+                #[cfg(feature = "debugger")]
+                    "/fB",
+                "file://fake/test",
+                vec![Input::new(
+                    #[cfg(feature = "debugger")]
+                        "",
+                    0,
+                    false,
+                    Some(Once(json!(1))),
+                    None,
+                )],
+                1,
+                0,
+                &[],
+                false,
+            )
+        }
+
+        // Create a state with two functions that are ready to run
+        fn test_state(deterministic: bool) -> RunState {
+            super::test_state(vec![super::test_function_a_init(), test_function_b_init()],
+                              |submission| submission.deterministic = deterministic)
+        }
+
+        #[test]
+        fn jobs_run_in_parallel() {
+            let mut state = test_state(false);
+            let job = state.get_next_job().expect("Couldn't get next job");
+            state.start_job(job);
+
+            assert_eq!(state.number_jobs_due(), 1);
+            assert!(state.get_next_job().is_some());
+        }
+
+        #[test]
+        fn deterministic_runs_one_job_at_a_time_in_order() {
+            let mut state = test_state(true);
+            let job = state.get_next_job().expect("Couldn't get next job");
+            assert_eq!(job.function_id, 0);
+            state.start_job(job);
+
+            assert_eq!(state.number_jobs_due(), 0);
+            assert!(state.get_next_job().is_none(), "Only one job should run at a time");
+        }
+    }
+
+    mod streaming_tests {
+        use std::sync::Arc;
+
+        use serde_json::json;
+
+        #[cfg(feature = "metrics")]
+        use flowcore::model::metrics::Metrics;
+        use flowcore::model::input::Input;
+        use flowcore::model::runtime_function::RuntimeFunction;
+
+        use crate::job::Chunk;
+
+        use super::super::RunState;
+
+        // fB with an input that takes an array of numbers
+        fn test_function_b_array_input() -> RuntimeFunction {
+            RuntimeFunction::new(
+                #[cfg(feature = "debugger")]
+                    "fB",
+                #[cfg(feature = "debugger")]
+                    "/fB",
+                "file://fake/test",
+                vec![Input::new(
+                    #[cfg(feature = "debugger")]
+                        "",
+                    1,
+                    false,
+                    None,
+                    None,
+                )],
+                1,
+                0,
+                &[],
+                false,
+            )
+        }
+
+        // Run fA, that outputs to fB, streaming back its array output as one chunk then retiring it
+        fn stream_output(functions: Vec<RuntimeFunction>) -> (RunState, usize) {
+            #[cfg(feature = "metrics")]
+                let mut metrics = Metrics::new(1);
+            #[cfg(feature = "debugger")]
+                let mut server = super::DummyServer {};
+            #[cfg(feature = "debugger")]
+                let mut debugger = super::dummy_debugger(&mut server);
+
+            let mut state = RunState::new(super::test_submission(functions));
+            state.init().expect("Could not init state");
+            let job = state.get_next_job().expect("Couldn't get next job");
+            let job_id = job.payload.job_id;
+            state.start_job(job);
+
+            state.deliver_chunk(
+                #[cfg(feature = "metrics")]
+                    &mut metrics,
+                Chunk { submission_id: 0, job_id, elements: vec![json!(10), json!(20)] },
+                #[cfg(feature = "debugger")]
+                    &mut debugger,
+            ).expect("Could not deliver chunk");
+            let ready_after_chunk = state.number_jobs_ready();
+
+            state.retire_a_job(
+                #[cfg(feature = "metrics")]
+                    &mut metrics,
+                (job_id, Ok((None, false))),
+                #[cfg(feature = "debugger")]
+                    &mut debugger,
+            ).expect("Could not retire job");
+
+            (state, ready_after_chunk)
+        }
+
+        #[test]
+        fn elements_delivered_as_chunk_arrives() {
+            let (state, ready_after_chunk) = stream_output(vec![super::test_function_a_to_b(),
+                                                                super::test_function_b_not_init()]);
+            assert_eq!(ready_after_chunk, 2);
+            assert_eq!(state.number_jobs_ready(), 2, "Elements should not be sent again");
+            assert!(state.streamed_outputs.is_empty());
+        }
+
+        #[test]
+        fn array_reassembled_for_array_input() {
+            let (mut state, ready_after_chunk) = stream_output(vec![super::test_function_a_to_b(),
+                                                                    test_function_b_array_input()]);
+            assert_eq!(ready_after_chunk, 0);
+            let job = state.get_next_job().expect("Couldn't get next job");
+            assert_eq!(job.payload.input_set, vec![Arc::new(json!([10, 20]))]);
+        }
+    }
+
+    mod checkpoint_tests {
+        use tempfile::tempdir;
+
+        use super::super::RunState;
+
+        #[test]
+        fn running_job_is_ready_on_resume() {
+            let temp_dir = tempdir().expect("Could not create temp dir");
+            let path = temp_dir.path().join("checkpoint.json");
+            let functions = vec![super::test_function_a_to_b(), super::test_function_b_not_init()];
+            let mut state = RunState::new(super::test_submission(functions.clone()));
+            state.init().expect("Could not init state");
+            let job = state.get_next_job().expect("Couldn't get next job");
+            state.start_job(job);
+            assert_eq!(state.number_jobs_running(), 1);
+
+            state.checkpoint(&path).expect("Could not write checkpoint");
+
+            let resumed = RunState::from_checkpoint(&path, super::test_submission(functions))
+                .expect("Could not resume from checkpoint");
+            assert_eq!(resumed.number_jobs_running(), 0);
+            assert_eq!(resumed.number_jobs_ready(), 1);
+            assert!(resumed.blocks == state.blocks);
+            assert_eq!(resumed.busy_flows, state.busy_flows);
+        }
+
+        #[test]
+        fn checkpoint_of_other_flow_fails() {
+            let temp_dir = tempdir().expect("Could not create temp dir");
+            let path = temp_dir.path().join("checkpoint.json");
+            let mut state = RunState::new(super::test_submission(vec![super::test_function_a_init()]));
+            state.init().expect("Could not init state");
+            state.checkpoint(&path).expect("Could not write checkpoint");
+
+            let other_submission = super::test_submission(vec![super::test_function_a_to_b(),
+                                                                super::test_function_b_not_init()]);
+            assert!(RunState::from_checkpoint(&path, other_submission).is_err());
+        }
+    }
+
+    #[cfg(feature = "debugger")]
+    mod snapshot_tests {
+        #[cfg(feature = "metrics")]
+        use flowcore::model::metrics::Metrics;
+        use serde_json::json;
+
+        use super::super::{RunState, State};
+
+        #[test]
+        fn restore_snapshot_prior_to_job() {
+            let functions = vec![super::test_function_a_to_b(), super::test_function_b_not_init()];
+            let mut state = RunState::new(super::test_submission(functions));
+            state.init().expect("Could not init state");
+            state.set_submission_id(1);
+            let job = state.get_next_job().expect("Couldn't get next job");
+            let job_id = job.payload.job_id;
+            let snapshot = state.snapshot_prior_to(&job);
+            state.start_job(job);
+            assert_eq!(state.number_jobs_running(), 1);
+            assert_eq!(state.number_jobs_ready(), 0);
+
+            state.set_submission_id(2);
+            state.restore(vec![snapshot]);
+            assert_eq!(state.number_jobs_running(), 0);
+            assert_eq!(state.number_jobs_ready(), 1);
+            assert_eq!(state.submission_id(), 2);
+            let job = state.get_next_job().expect("Couldn't get next job");
+            assert_eq!(job.payload.job_id, job_id);
+        }
+
+        #[test]
+        fn restore_function_changed_after_snapshot() {
+            #[cfg(feature = "metrics")]
+                let mut metrics = Metrics::new(1);
+            let mut server = super::DummyServer {};
+            let mut debugger = super::dummy_debugger(&mut server);
+            let functions = vec![super::test_function_a_to_b(), super::test_function_b_not_init()];
+            let mut state = RunState::new(super::test_submission(functions));
+            state.init().expect("Could not init state");
+            let job = state.get_next_job().expect("Couldn't get next job");
+            let job_id = job.payload.job_id;
+            let snapshot = state.snapshot_prior_to(&job);
+            state.start_job(job);
+
+            // fA's output fills fB's input, so fB is changed after the snapshot and copied into it
+            state.retire_a_job(
+                #[cfg(feature = "metrics")]
+                    &mut metrics,
+                (job_id, Ok((Some(json!(1)), false))),
+                &mut debugger,
+            ).expect("Could not retire job");
+            assert!(state.function_state_is_only(1, &State::Ready));
+            assert!(snapshot.functions.lock().expect("Could not lock changes").contains_key(&1));
+
+            state.restore(vec![snapshot]);
+            assert!(state.function_state_is_only(1, &State::Waiting));
+            let job = state.get_next_job().expect("Couldn't get next job");
+            assert_eq!(job.function_id, 0);
+        }
+    }
 }
@@ -0,0 +1,312 @@
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::thread;
+
+use log::{debug, info, trace, warn};
+
+use flowcore::errors::{bail, Result, ResultExt};
+
+// The well-known inproc address that libzmq sends ZAP authentication requests to
+const ZAP_ENDPOINT: &str = "inproc://zeromq.zap.01";
+
+// The ZAP domain used for all sockets secured with `CurveKeys`
+const ZAP_DOMAIN: &str = "flowr";
+
+/// `CurveKeys` is a `CurveZMQ` key pair, shared by all the processes (clients, coordinator and
+/// executors such as `flowrex`) that take part in the execution of a flow, so that the messages
+/// sent between them are encrypted, and only peers that hold the same key pair can connect.
+///
+/// A key file holds the Z85 encoded public key on the first line and the Z85 encoded
+/// secret key on the second line.
+#[derive(Clone)]
+pub struct CurveKeys {
+    public_key: Vec<u8>,
+    secret_key: Vec<u8>,
+}
+
+impl CurveKeys {
+    /// Generate a new random key pair
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `libzmq` in use was built without `CURVE` support
+    pub fn generate() -> Result<Self> {
+        check_curve_support()?;
+        let pair = zmq::CurveKeyPair::new()
+            .map_err(|e| format!("Could not generate CURVE key pair: {e}"))?;
+        Ok(CurveKeys {
+            public_key: pair.public_key.to_vec(),
+            secret_key: pair.secret_key.to_vec(),
+        })
+    }
+
+    /// Load a key pair from the key file at `path`. If it does not exist then a new key pair is
+    /// generated and written to it, so it can be copied to the other machines involved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key file cannot be read or created, if it does not contain a
+    /// valid key pair or if `libzmq` was built without `CURVE` support
+    pub fn load_or_generate(path: &Path) -> Result<Self> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            let keys = Self::generate()?;
+            keys.save(path)?;
+            info!("New CURVE key pair written to '{}'", path.display());
+            Ok(keys)
+        }
+    }
+
+    /// Load a key pair from the key file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key file cannot be read, if it does not contain a valid key pair
+    /// or if `libzmq` was built without `CURVE` support
+    pub fn load(path: &Path) -> Result<Self> {
+        check_curve_support()?;
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("Could not read key file '{}'", path.display()))?;
+        Self::parse(&contents)
+            .chain_err(|| format!("Invalid key file '{}'", path.display()))
+    }
+
+    /// Write this key pair to a key file at `path`. On unix a new key file is created so that only
+    /// its owner can read it, as the secret key must be kept private
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keys cannot be encoded or the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let public_key = zmq::z85_encode(&self.public_key)
+            .map_err(|e| format!("Could not encode public key: {e}"))?;
+        let secret_key = zmq::z85_encode(&self.secret_key)
+            .map_err(|e| format!("Could not encode secret key: {e}"))?;
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        options.open(path)
+            .and_then(|mut file| file.write_all(format!("{public_key}\n{secret_key}\n").as_bytes()))
+            .chain_err(|| format!("Could not write key file '{}'", path.display()))
+    }
+
+    // Parse the contents of a key file
+    fn parse(contents: &str) -> Result<Self> {
+        let mut lines = contents.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        let public_key = decode_key("public", lines.next().ok_or("Public key is missing")?)?;
+        let secret_key = decode_key("secret", lines.next().ok_or("Secret key is missing")?)?;
+        if lines.next().is_some() {
+            bail!("Unexpected content after the secret key");
+        }
+
+        Ok(CurveKeys {
+            public_key,
+            secret_key,
+        })
+    }
+
+    /// Start authenticating connections to sockets created in `context`, so that only
+    /// peers using this key pair are accepted. It should be called once per context, before
+    /// any server socket is bound.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the authentication handler socket cannot be created
+    pub fn authenticate(&self, context: &zmq::Context) -> Result<()> {
+        let handler = context.socket(zmq::REP)
+            .map_err(|e| format!("Could not create authentication socket: {e}"))?;
+        handler.bind(ZAP_ENDPOINT)
+            .map_err(|e| format!("Could not bind authentication socket: {e}"))?;
+
+        let client_key = self.public_key.clone();
+        thread::spawn(move || {
+            trace!("Authentication handler started");
+            while let Ok(request) = handler.recv_multipart(0) {
+                let reply = authentication_reply(&request, &client_key);
+                if let Err(e) = handler.send_multipart(reply, 0) {
+                    warn!("Could not send authentication reply: {e}");
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Configure `socket` as the server (binding) end of an encrypted connection.
+    /// This must be done before the socket is bound.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket options to enable `CURVE` cannot be set
+    pub fn secure_server(&self, socket: &zmq::Socket) -> Result<()> {
+        socket.set_zap_domain(ZAP_DOMAIN)
+            .and_then(|()| socket.set_curve_server(true))
+            .and_then(|()| socket.set_curve_secretkey(&self.secret_key))
+            .map_err(|e| format!("Could not enable CURVE on server socket: {e}").into())
+    }
+
+    /// Configure `socket` as the client (connecting) end of an encrypted connection.
+    /// This must be done before the socket is connected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket options to enable `CURVE` cannot be set
+    pub fn secure_client(&self, socket: &zmq::Socket) -> Result<()> {
+        socket.set_curve_serverkey(&self.public_key)
+            .and_then(|()| socket.set_curve_publickey(&self.public_key))
+            .and_then(|()| socket.set_curve_secretkey(&self.secret_key))
+            .map_err(|e| format!("Could not enable CURVE on client socket: {e}").into())
+    }
+}
+
+// Check that the `libzmq` library in use supports the `CURVE` security mechanism
+fn check_curve_support() -> Result<()> {
+    if zmq::has("curve") != Some(true) {
+        bail!("The 'libzmq' library in use was built without CURVE encryption support");
+    }
+    Ok(())
+}
+
+// Decode the Z85 encoded 32 byte key called `name`. Errors only name the key, as the text of
+// a secret key must not be written to logs or error output
+fn decode_key(name: &str, encoded: &str) -> Result<Vec<u8>> {
+    let key = zmq::z85_decode(encoded)
+        .map_err(|e| format!("Could not decode the {name} key: {e}"))?;
+    if key.len() != 32 {
+        bail!("The {} key is not 32 bytes long", name);
+    }
+    Ok(key)
+}
+
+// Build the reply to a ZAP request, accepting only CURVE clients using `client_key`.
+// A request is: version, request id, domain, address, identity, mechanism, credentials...
+// A reply is: version, request id, status code, status text, user id, metadata
+fn authentication_reply(request: &[Vec<u8>], client_key: &[u8]) -> Vec<Vec<u8>> {
+    let version = request.first().cloned().unwrap_or_default();
+    let request_id = request.get(1).cloned().unwrap_or_default();
+    let mechanism = request.get(5).map(Vec::as_slice);
+    let credentials = request.get(6).map(Vec::as_slice);
+
+    let (status_code, status_text) = if mechanism == Some(b"CURVE".as_slice())
+        && credentials == Some(client_key) {
+        ("200", "OK")
+    } else {
+        debug!("Rejecting connection from a peer with an unknown key");
+        ("400", "Unknown key")
+    };
+
+    vec![version, request_id, status_code.into(), status_text.into(), vec![], vec![]]
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::CurveKeys;
+
+    const PUBLIC_KEY: &str = "rq:rM>}U?@Lns47E1%kR.o@n%FcmmsL/@{H8]yf7";
+    const SECRET_KEY: &str = "JTKVSB%%)wK0E.X)V>+}o?pNmC{O&4W4b!Ni{Lh6";
+    const OTHER_PUBLIC_KEY: &str = "Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hID";
+
+    fn test_keys() -> CurveKeys {
+        #[allow(clippy::unwrap_used)]
+        CurveKeys::parse(&format!("{PUBLIC_KEY}\n{SECRET_KEY}\n")).unwrap()
+    }
+
+    #[test]
+    fn parse_keys() {
+        let keys = test_keys();
+        assert_eq!(keys.public_key.len(), 32);
+        assert_eq!(keys.secret_key.len(), 32);
+    }
+
+    #[test]
+    fn missing_secret_key() {
+        assert!(CurveKeys::parse(PUBLIC_KEY).is_err());
+    }
+
+    #[test]
+    fn invalid_key() {
+        assert!(CurveKeys::parse("not a key\nnot a key either").is_err());
+    }
+
+    #[test]
+    fn invalid_key_not_in_error() {
+        let secret_key = &SECRET_KEY[..35];
+        let error = CurveKeys::parse(&format!("{PUBLIC_KEY}\n{secret_key}\n"))
+            .err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(error.contains("secret key"));
+        assert!(!error.contains(secret_key));
+    }
+
+    #[test]
+    fn save_and_parse() {
+        let dir = tempdir().expect("Could not create temp dir");
+        let path = dir.path().join("flowr.key");
+        let keys = test_keys();
+        keys.save(&path).expect("Could not save keys");
+
+        let contents = fs::read_to_string(&path).expect("Could not read key file");
+        assert_eq!(contents, format!("{PUBLIC_KEY}\n{SECRET_KEY}\n"));
+        let loaded = CurveKeys::parse(&contents).expect("Could not parse key file");
+        assert_eq!(loaded.public_key, keys.public_key);
+        assert_eq!(loaded.secret_key, keys.secret_key);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_key_file_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().expect("Could not create temp dir");
+        let path = dir.path().join("flowr.key");
+        test_keys().save(&path).expect("Could not save keys");
+
+        let mode = fs::metadata(&path).expect("Could not get key file metadata").permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    fn authenticate(mechanism: &str, public_key: &str) -> String {
+        let context = zmq::Context::new();
+        test_keys().authenticate(&context).expect("Could not start authentication");
+
+        let requester = context.socket(zmq::REQ).expect("Could not create socket");
+        requester.connect(super::ZAP_ENDPOINT).expect("Could not connect");
+        let credentials = zmq::z85_decode(public_key).expect("Could not decode key");
+        let request: Vec<Vec<u8>> = vec![b"1.0".to_vec(), b"1".to_vec(), b"flowr".to_vec(),
+                                         b"127.0.0.1".to_vec(), vec![], mechanism.into(),
+                                         credentials];
+        requester.send_multipart(request, 0).expect("Could not send request");
+        let reply = requester.recv_multipart(0).expect("Could not receive reply");
+        assert_eq!(reply.len(), 6);
+        assert_eq!(reply.get(1), Some(&b"1".to_vec()));
+        String::from_utf8(reply.get(2).cloned().unwrap_or_default())
+            .expect("Invalid status code")
+    }
+
+    #[test]
+    fn authenticate_known_key() {
+        assert_eq!(authenticate("CURVE", PUBLIC_KEY), "200");
+    }
+
+    #[test]
+    fn reject_unknown_key() {
+        assert_eq!(authenticate("CURVE", OTHER_PUBLIC_KEY), "400");
+    }
+
+    #[test]
+    fn reject_other_mechanism() {
+        assert_eq!(authenticate("PLAIN", PUBLIC_KEY), "400");
+    }
+}
@@ -1,12 +1,84 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
 /// `JOB_SERVICE_NAME` can be used to discover the queue serving jobs for execution
 pub const JOB_SERVICE_NAME: &str = "jobs._flowr._tcp.local";
 
 /// `RESULTS_JOB_SERVICE_NAME` can be used to discover the queue where to send job results
 pub const RESULTS_JOB_SERVICE_NAME: &str = "results._flowr._tcp.local";
 
+/// `NATIVE_JOB_SERVICE_NAME` can be used to discover the queue serving jobs for executors that have
+/// native implementations of the libraries they use
+pub const NATIVE_JOB_SERVICE_NAME: &str = "native-jobs._flowr._tcp.local";
+
 /// `CONTROL_SERVICE_NAME` is a control PUB/SUB socket used to control executors that
 /// are listening on the `JOB_SERVICE` and sending results back via the `RESULTS_SERVICE`
 pub const CONTROL_SERVICE_NAME: &str = "control._flowr._tcp.local";
 
 /// This is the port for announcing and discovering the job queues
 pub const JOB_QUEUES_DISCOVERY_PORT:u16 = 15003;
+
+/// `JOBS_EXECUTED_MESSAGE` is logged (at `info` level) by `flowrex` when a flow is done,
+/// followed by the number of jobs it executed for that flow
+pub const JOBS_EXECUTED_MESSAGE: &str = "Jobs executed: ";
+
+/// Get the `zmq` endpoint to bind a socket to `port` on the interface with `address`, or on all
+/// interfaces (IPv4 and IPv6) if no address, or an unspecified address, is given
+#[must_use]
+pub fn bind_endpoint(address: Option<IpAddr>, port: u16) -> String {
+    match address.filter(|ip| !ip.is_unspecified()) {
+        Some(ip) => tcp_endpoint(ip, port),
+        None => format!("tcp://*:{port}"),
+    }
+}
+
+/// Get the `zmq` endpoint for a process on this host to connect to a socket bound to `port` using
+/// [`bind_endpoint`] with the same `address`. That is `address` itself if it is a specific one,
+/// otherwise the loopback address of its family (IPv4 if no address is given)
+#[must_use]
+pub fn local_endpoint(address: Option<IpAddr>, port: u16) -> String {
+    let ip = match address {
+        Some(ip) if !ip.is_unspecified() => ip,
+        Some(IpAddr::V6(_)) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        _ => IpAddr::V4(Ipv4Addr::LOCALHOST),
+    };
+    tcp_endpoint(ip, port)
+}
+
+/// Get the `zmq` endpoint of `port` at `ip`, with an IPv6 address enclosed in brackets
+#[must_use]
+pub fn tcp_endpoint(ip: IpAddr, port: u16) -> String {
+    format!("tcp://{}", SocketAddr::new(ip, port))
+}
+
+/// Get the socket address to bind a listener to `port` on the interface with `address`, or on
+/// all IPv4 interfaces if no address is given
+#[must_use]
+pub fn bind_socket_address(address: Option<IpAddr>, port: u16) -> SocketAddr {
+    SocketAddr::new(address.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)), port)
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::IpAddr;
+
+    use super::{bind_endpoint, local_endpoint};
+
+    #[test]
+    fn ipv4_endpoints() {
+        assert_eq!(bind_endpoint(None, 4000), "tcp://*:4000");
+        assert_eq!(local_endpoint(None, 4000), "tcp://127.0.0.1:4000");
+        let ip: IpAddr = "192.168.1.10".parse().expect("Could not parse address");
+        assert_eq!(bind_endpoint(Some(ip), 4000), "tcp://192.168.1.10:4000");
+        assert_eq!(local_endpoint(Some(ip), 4000), "tcp://192.168.1.10:4000");
+    }
+
+    #[test]
+    fn ipv6_endpoints() {
+        let any: IpAddr = "::".parse().expect("Could not parse address");
+        assert_eq!(bind_endpoint(Some(any), 4000), "tcp://*:4000");
+        assert_eq!(local_endpoint(Some(any), 4000), "tcp://[::1]:4000");
+        let ip: IpAddr = "fd00::10".parse().expect("Could not parse address");
+        assert_eq!(bind_endpoint(Some(ip), 4000), "tcp://[fd00::10]:4000");
+        assert_eq!(local_endpoint(Some(ip), 4000), "tcp://[fd00::10]:4000");
+    }
+}
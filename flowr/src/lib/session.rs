@@ -0,0 +1,329 @@
+use std::net::IpAddr;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{debug, info};
+use lru::LruCache;
+use zmq::Socket;
+
+use flowcore::errors::{Result, ResultExt};
+
+use crate::security::CurveKeys;
+use crate::services::bind_endpoint;
+
+/// The time a client waits for the reply to a request before reconnecting to the coordinator and
+/// sending the request again, in case either was lost in an interruption of the connection
+pub const RESEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The number of client sessions the coordinator keeps the last reply of, to send again to a
+/// client that resends its request after an interruption
+pub const MAX_SESSIONS: NonZeroUsize = NonZeroUsize::new(100)
+    .expect("MAX_SESSIONS must be greater than zero");
+
+/// The client end of a `zmq` request/reply session with the coordinator. Each request is sent with
+/// the token identifying the session and its sequence number in it. If no reply is received within
+/// [`RESEND_TIMEOUT`] the client reconnects and sends the request again, and the coordinator
+/// (using a [`ZmqResponder`]) replies to it with the reply it sent before if it had received it,
+/// so the run continues where it left off after an interruption of the connection.
+pub struct ZmqRequester {
+    context: zmq::Context,
+    coordinator_address: String,
+    keys: Option<CurveKeys>,
+    socket: Socket,
+    token: String,
+    sent: u64,
+    last_request: Option<String>,
+}
+
+impl ZmqRequester {
+    /// Connect to the coordinator at `coordinator_address`, starting a new session. If `keys`
+    /// are provided the connection is encrypted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket could not be created or connected
+    pub fn connect(coordinator_address: &str, keys: Option<&CurveKeys>) -> Result<Self> {
+        let context = zmq::Context::new();
+        let socket = request_socket(&context, coordinator_address, keys)?;
+        Ok(ZmqRequester {
+            context,
+            coordinator_address: coordinator_address.to_string(),
+            keys: keys.cloned(),
+            socket,
+            token: format!("{:016x}", rand::random::<u64>()),
+            sent: 0,
+            last_request: None,
+        })
+    }
+
+    /// Send a request to the coordinator
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request could not be sent
+    pub fn send(&mut self, request: String) -> Result<()> {
+        self.sent += 1;
+        self.last_request = Some(request);
+        self.send_last_request()
+    }
+
+    /// Receive the reply to the last request sent. If none arrives within [`RESEND_TIMEOUT`],
+    /// reconnect to the coordinator and send the request again, until one does
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error receiving the reply, or reconnecting
+    pub fn receive(&mut self) -> Result<String> {
+        let timeout = i64::try_from(RESEND_TIMEOUT.as_millis())?;
+        loop {
+            let ready = self.socket.poll(zmq::POLLIN, timeout)
+                .chain_err(|| "Error receiving from coordinator")?;
+            if ready > 0 {
+                let reply = self.socket.recv_msg(0)
+                    .chain_err(|| "Error receiving from coordinator")?;
+                return Ok(reply.as_str().ok_or("Could not get message as str")?.to_string());
+            }
+
+            // the request or reply may have been lost, so send it again on a new connection.
+            // A reply still to come on the previous connection will also be sent on the new one
+            debug!("No reply from coordinator at '{}', reconnecting to send request again",
+                self.coordinator_address);
+            self.socket = request_socket(&self.context, &self.coordinator_address,
+                                         self.keys.as_ref())?;
+            self.send_last_request()?;
+        }
+    }
+
+    fn send_last_request(&self) -> Result<()> {
+        let request = self.last_request.as_deref().ok_or("No request to send")?;
+        self.socket.send_multipart([self.token.as_bytes(), self.sent.to_string().as_bytes(),
+                                       request.as_bytes()], 0)
+            .chain_err(|| "Error sending to coordinator")
+    }
+}
+
+// Create a request socket connected to the coordinator, that discards requests not yet sent
+// when it is closed to reconnect
+fn request_socket(context: &zmq::Context, coordinator_address: &str, keys: Option<&CurveKeys>)
+    -> Result<Socket> {
+    let socket = context
+        .socket(zmq::REQ)
+        .chain_err(|| "Client could not connect to coordinator service")?;
+
+    socket.set_ipv6(true)
+        .chain_err(|| "Client Connection - could not enable IPv6")?;
+    socket.set_linger(0)
+        .chain_err(|| "Client Connection - could not set linger")?;
+    if let Some(curve_keys) = keys {
+        curve_keys.secure_client(&socket)?;
+    }
+
+    socket
+        .connect(&format!("tcp://{coordinator_address}"))
+        .chain_err(|| format!("Client Connection - Could not connect to socket at: {coordinator_address}"))?;
+
+    Ok(socket)
+}
+
+// The sequence number of the last request received in a client session, and the reply sent to it
+struct Session {
+    received: u64,
+    last_reply: Option<String>,
+}
+
+// The sessions of the most recent clients, and the token of the session of the last request received
+struct Sessions {
+    sessions: LruCache<String, Session>,
+    current: Option<String>,
+}
+
+/// The coordinator end of `zmq` request/reply sessions with clients using a [`ZmqRequester`].
+/// The last reply sent to each client is kept, for the most recent [`MAX_SESSIONS`] sessions, so
+/// that when a client sends a request again after an interruption, the reply it did not receive
+/// is sent again without the coordinator seeing the request twice.
+pub struct ZmqResponder {
+    socket: Socket,
+    sessions: Mutex<Sessions>,
+}
+
+impl ZmqResponder {
+    /// Bind to `port` on the interface with `bind_address`, or on all interfaces if none is
+    /// given. If `keys` are provided the connection is encrypted and only clients using the
+    /// same keys can connect
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket could not be created or bound
+    pub fn bind(port: u16, bind_address: Option<IpAddr>, keys: Option<&CurveKeys>)
+        -> Result<Self> {
+        let context = zmq::Context::new();
+        let socket = context
+            .socket(zmq::REP)
+            .chain_err(|| "Coordinator Connection - could not create Socket")?;
+
+        socket.set_ipv6(true)
+            .chain_err(|| "Coordinator Connection - could not enable IPv6")?;
+        if let Some(curve_keys) = keys {
+            curve_keys.authenticate(&context)?;
+            curve_keys.secure_server(&socket)?;
+        }
+
+        let endpoint = bind_endpoint(bind_address, port);
+        debug!("Coordinator Connection attempting to bind to: {endpoint}");
+        socket.bind(&endpoint)
+            .chain_err(||
+                format!("Coordinator Connection - could not bind on TCP Socket on: {endpoint}"))?;
+
+        Ok(ZmqResponder {
+            socket,
+            sessions: Mutex::new(Sessions {
+                sessions: LruCache::new(MAX_SESSIONS),
+                current: None,
+            }),
+        })
+    }
+
+    /// Receive the next new request from a client, waiting for one unless `flags` is
+    /// `zmq::DONTWAIT`. Requests sent again by clients after an interruption are replied to with
+    /// the reply sent to them before, and not returned
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error receiving, or no request when not waiting
+    pub fn receive(&self, flags: i32) -> Result<String> {
+        loop {
+            let mut frames = self.socket.recv_multipart(flags)
+                .map_err(|e| format!("Coordinator error getting message: '{e}'"))?;
+            let request = String::from_utf8(frames.pop().ok_or("Empty message received")?)
+                .map_err(|_| "Could not get message as str")?;
+            let mut sessions = self.sessions.lock()
+                .map_err(|_| "Could not lock client sessions")?;
+
+            let (token, sequence) = match frames.as_slice() {
+                [token, sequence] => (String::from_utf8_lossy(token).into_owned(),
+                                      String::from_utf8_lossy(sequence).parse::<u64>()
+                                          .chain_err(|| "Invalid request sequence number")?),
+                _ => {
+                    // a request that is not part of a session cannot be sent again
+                    sessions.current = None;
+                    return Ok(request);
+                }
+            };
+
+            let reply = match sessions.sessions.get(&token) {
+                Some(session) if sequence < session.received => {
+                    debug!("Request {sequence} of session '{token}' was already replied to");
+                    String::new()
+                }
+                Some(Session { received, last_reply: Some(reply) }) if sequence == *received => {
+                    info!("Client resumed session '{token}', sending the reply to request {sequence} again");
+                    reply.clone()
+                }
+                _ => {
+                    sessions.sessions.put(token.clone(), Session { received: sequence, last_reply: None });
+                    sessions.current = Some(token);
+                    return Ok(request);
+                }
+            };
+            self.reply(&reply)?;
+        }
+    }
+
+    /// Send the reply to the last request received, keeping it to send again if the client
+    /// sends the request again
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reply could not be sent
+    pub fn send(&self, reply: String) -> Result<()> {
+        let mut sessions = self.sessions.lock()
+            .map_err(|_| "Could not lock client sessions")?;
+        let Sessions { sessions, current } = &mut *sessions;
+        if let Some(session) = current.as_ref().and_then(|token| sessions.get_mut(token)) {
+            session.last_reply = Some(reply.clone());
+        }
+        self.reply(&reply)
+    }
+
+    fn reply(&self, reply: &str) -> Result<()> {
+        self.socket.send(reply.as_bytes(), 0)
+            .map_err(|e| format!("Coordinator error sending to client: '{e}'").into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use portpicker::pick_unused_port;
+    use zmq::DONTWAIT;
+
+    use super::{ZmqRequester, ZmqResponder};
+
+    #[test]
+    fn request_and_reply() {
+        let port = pick_unused_port().expect("No ports free");
+        let responder = ZmqResponder::bind(port, None, None)
+            .expect("Could not bind responder");
+        let mut requester = ZmqRequester::connect(&format!("127.0.0.1:{port}"), None)
+            .expect("Could not connect requester");
+
+        requester.send("hello".into()).expect("Could not send request");
+        assert_eq!(responder.receive(0).expect("Could not receive request"), "hello");
+        responder.send("world".into()).expect("Could not send reply");
+        assert_eq!(requester.receive().expect("Could not receive reply"), "world");
+    }
+
+    #[test]
+    fn resent_request_gets_same_reply() {
+        let port = pick_unused_port().expect("No ports free");
+        let responder = ZmqResponder::bind(port, None, None)
+            .expect("Could not bind responder");
+        let mut requester = ZmqRequester::connect(&format!("127.0.0.1:{port}"), None)
+            .expect("Could not connect requester");
+
+        requester.send("hello".into()).expect("Could not send request");
+        assert_eq!(responder.receive(0).expect("Could not receive request"), "hello");
+
+        // the reply is lost as the client's connection is interrupted before it is received
+        requester.socket = super::request_socket(&requester.context, &requester.coordinator_address,
+                                                 None).expect("Could not reconnect");
+        responder.send("world".into()).expect("Could not send reply");
+
+        // the client sends the request again, and gets the reply it missed
+        requester.send_last_request().expect("Could not send request again");
+        let client = std::thread::spawn(move || {
+            let reply = requester.receive().expect("Could not receive reply");
+            requester.send("again".into()).expect("Could not send request");
+            reply
+        });
+
+        // the coordinator does not see the request sent again, only the next one
+        assert_eq!(responder.receive(0).expect("Could not receive request"), "again");
+        assert_eq!(client.join().expect("Client thread panicked"), "world");
+    }
+
+    #[test]
+    fn resent_request_after_timeout() {
+        let port = pick_unused_port().expect("No ports free");
+        let responder = ZmqResponder::bind(port, None, None)
+            .expect("Could not bind responder");
+        let mut requester = ZmqRequester::connect(&format!("127.0.0.1:{port}"), None)
+            .expect("Could not connect requester");
+
+        requester.send("hello".into()).expect("Could not send request");
+        assert_eq!(responder.receive(0).expect("Could not receive request"), "hello");
+
+        // reply after the client has timed out and sent the request again on a new connection
+        let client = std::thread::spawn(move || requester.receive()
+            .expect("Could not receive reply"));
+        std::thread::sleep(super::RESEND_TIMEOUT + Duration::from_millis(500));
+        responder.send("world".into()).expect("Could not send reply");
+
+        // the request sent again is replied to while checking for the next one
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(responder.receive(DONTWAIT).is_err());
+        assert_eq!(client.join().expect("Client thread panicked"), "world");
+    }
+}
@@ -1,8 +1,9 @@
 use flowcore::errors::Result;
 #[cfg(feature = "metrics")]
-use flowcore::model::metrics::Metrics;
+use flowcore::model::metrics::{Metrics, MetricsSnapshot};
 use flowcore::model::submission::Submission;
 
+use crate::job::Job;
 use crate::run_state::RunState;
 
 /// Programs that wish to submit a flow for execution via a
@@ -25,6 +26,18 @@ pub trait SubmissionHandler {
     #[cfg(feature = "debugger")]
     fn should_enter_debugger(&mut self) -> Result<bool>;
 
+    /// The [Coordinator][crate::coordinator::Coordinator] executing the flow periodically
+    /// will check if the client has requested that its execution be cancelled. If so, jobs not
+    /// yet executed are discarded and execution ends, with `flow_execution_ended()` being called
+    /// with a [`RunState`] that is `cancelled()`. The default implementation never cancels it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the check for a request to cancel execution fails
+    fn should_cancel(&mut self) -> Result<bool> {
+        Ok(false)
+    }
+
     /// The [Coordinator][crate::coordinator::Coordinator] informs the submitter that the execution
     /// of the flow has ended
     ///
@@ -35,6 +48,37 @@ pub trait SubmissionHandler {
                             #[cfg(feature = "metrics")] metrics: Metrics
     ) -> Result<()>;
 
+    /// The [Coordinator][crate::coordinator::Coordinator] has dispatched `job` for execution.
+    /// The default implementation ignores it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message corresponding to the job being dispatched cannot be sent
+    fn job_dispatched(&mut self, _job: &Job) -> Result<()> {
+        Ok(())
+    }
+
+    /// The [Coordinator][crate::coordinator::Coordinator] has retired `job` after it was executed,
+    /// with the result of its execution in `job.result`. The default implementation ignores it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message corresponding to the job being completed cannot be sent
+    fn job_completed(&mut self, _job: &Job) -> Result<()> {
+        Ok(())
+    }
+
+    /// The [Coordinator][crate::coordinator::Coordinator] periodically sends a `snapshot` of the
+    /// metrics of the flow while it is being executed. The default implementation ignores it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message with the metrics snapshot cannot be sent
+    #[cfg(feature = "metrics")]
+    fn metrics_snapshot(&mut self, _snapshot: MetricsSnapshot) -> Result<()> {
+        Ok(())
+    }
+
     /// The [Coordinator][crate::coordinator::Coordinator] wait for a
     /// [Submission][flowcore::model::submission::Submission] to be sent for execution
     ///
@@ -50,4 +94,22 @@ pub trait SubmissionHandler {
     ///
     /// Will return `Err` if this cannot be communicated to the client
     fn coordinator_is_exiting(&mut self, result: Result<()>) -> Result<()>;
-}
\ No newline at end of file
+}
+/// Programs that accept [Submissions][flowcore::model::submission::Submission] from many
+/// clients, to be executed concurrently by a [Coordinator][crate::coordinator::Coordinator]
+/// using `serve()`, should implement this trait
+pub trait SubmissionListener {
+    /// Accept the next `Submission`, along with the [`SubmissionHandler`] to inform of its
+    /// execution. If `wait` is false, return `None` immediately if no submission is waiting to be
+    /// accepted. If `wait` is true, block until there is one, returning `None` only if no more
+    /// submissions will be accepted, and the coordinator should stop serving.
+    ///
+    /// The handler's `wait_for_submission()` is not used. When execution of the flow submitted
+    /// has ended, the handler's `coordinator_is_exiting()` is called, as it will not be used again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is an error while waiting for a submission (usually networking)
+    #[allow(clippy::type_complexity)]
+    fn accept(&mut self, wait: bool) -> Result<Option<(Submission, Box<dyn SubmissionHandler>)>>;
+}
@@ -0,0 +1,113 @@
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::errors::Result;
+
+/// How long to wait to connect to, send to, or for a response from, a TCP server before giving up
+pub const TCP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The largest response read from a TCP server, so a server that never ends a line cannot fill memory
+pub const MAX_TCP_RESPONSE: u64 = 1024 * 1024;
+
+/// Connect to the TCP server at `address`, send `bytes` to it and if `await_response` is set then
+/// read a response, up to the end of the first line, until the server closes the connection or
+/// until [`MAX_TCP_RESPONSE`] bytes are read. Returns the response, if one was awaited
+///
+/// # Errors
+///
+/// Returns an error if it could not connect to the server, send to it, or read its response
+pub fn send(address: &str, bytes: &[u8], await_response: bool) -> Result<Option<Vec<u8>>> {
+    let mut stream = connect(address)
+        .map_err(|e| format!("Could not connect to '{address}': '{e}'"))?;
+
+    stream.set_write_timeout(Some(TCP_TIMEOUT))
+        .and_then(|()| stream.set_read_timeout(Some(TCP_TIMEOUT)))
+        .map_err(|e| format!("Could not set timeout on '{address}': '{e}'"))?;
+
+    stream.write_all(bytes)
+        .map_err(|e| format!("Could not send to '{address}': '{e}'"))?;
+
+    if !await_response {
+        return Ok(None);
+    }
+
+    let mut response = Vec::new();
+    BufReader::new(stream).take(MAX_TCP_RESPONSE).read_until(b'\n', &mut response)
+        .map_err(|e| format!("Could not read response from '{address}': '{e}'"))?;
+    Ok(Some(response))
+}
+
+/// Connect to the first of the socket addresses `address` resolves to that accepts a connection
+/// within [`TCP_TIMEOUT`]
+///
+/// # Errors
+///
+/// Returns the error of the last address tried if none accepted a connection, or an error if
+/// `address` could not be resolved
+pub fn connect(address: &str) -> io::Result<TcpStream> {
+    let mut error = io::Error::new(io::ErrorKind::NotFound, "The address could not be resolved");
+    for socket_address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket_address, TCP_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::MAX_TCP_RESPONSE;
+
+    #[test]
+    fn send_without_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let address = listener.local_addr().expect("Could not get address").to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("Could not accept connection");
+            let mut request = String::new();
+            BufReader::new(stream).read_line(&mut request).expect("Could not read request");
+            request
+        });
+
+        assert_eq!(super::send(&address, b"PING\n", false).expect("Could not send"), None);
+        assert_eq!(server.join().expect("Could not join server thread"), "PING\n");
+    }
+
+    #[test]
+    fn send_response_limited() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let address = listener.local_addr().expect("Could not get address").to_string();
+        let (done_sender, done) = mpsc::channel();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("Could not accept connection");
+            // read the request, so closing the connection with it unread does not reset it
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            reader.read_line(&mut request).expect("Could not read request");
+            // a response that never ends a line, the client closes the connection before the end
+            #[allow(clippy::cast_possible_truncation)]
+            let _ = reader.get_mut().write_all(&vec![b'a'; 2 * MAX_TCP_RESPONSE as usize]);
+            // keep the connection open until the client has read its response
+            let _ = done.recv();
+        });
+
+        let response = super::send(&address, b"PING\n", true);
+        let _ = done_sender.send(());
+        server.join().expect("Could not join server thread");
+        assert_eq!(response.expect("Could not send").map(|response| response.len() as u64),
+                   Some(MAX_TCP_RESPONSE));
+    }
+
+    #[test]
+    fn connect_unresolved() {
+        assert!(super::connect("no-such-host.invalid:80").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use log::{info, warn};
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use flowcore::errors::Result;
+
+/// The environment variable that, when set to the Url of an OpenTelemetry collector (such as
+/// Jaeger or Grafana Tempo), enables exporting of traces of flow execution to it using OTLP
+pub const OTLP_ENDPOINT_ENV_VAR: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// `Telemetry` exports the tracing spans of submissions and jobs to an OpenTelemetry collector
+/// while it exists. Spans not yet exported are flushed when it is dropped, so it should be kept
+/// until execution has ended.
+pub struct Telemetry {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            warn!("Could not flush traces to OpenTelemetry collector: {e}");
+        }
+    }
+}
+
+/// Start exporting traces to the OpenTelemetry collector at the Url in the
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable, identifying them with `service_name`.
+/// Returns `None` if the environment variable is not set, and traces are not exported.
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be created or a global tracing subscriber
+/// has already been set
+pub fn init(service_name: &str) -> Result<Option<Telemetry>> {
+    let Ok(endpoint) = std::env::var(OTLP_ENDPOINT_ENV_VAR) else {
+        return Ok(None);
+    };
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .build()
+        .map_err(|e| format!("Could not create OTLP exporter: {e}"))?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name.to_string()).build())
+        .build();
+
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("flowr")))
+        .try_init()
+        .map_err(|e| format!("Could not set tracing subscriber: {e}"))?;
+
+    info!("Exporting traces of '{service_name}' to OpenTelemetry collector at '{endpoint}'");
+
+    Ok(Some(Telemetry { provider }))
+}
@@ -0,0 +1,709 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crossbeam_channel::{never, select, unbounded, Receiver, RecvTimeoutError, Sender};
+use log::debug;
+use zmq::DONTWAIT;
+
+use flowcore::errors::{bail, Result};
+
+use crate::security::CurveKeys;
+
+// A socket an executor may receive messages on, with the kind of message received on it
+type MessageSource<'s> = (fn(String) -> CoordinatorMessage, Option<&'s zmq::Socket>);
+
+/// The queues that jobs are sent to executors on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobQueue {
+    /// Jobs for library functions
+    Lib,
+    /// Jobs for context functions and functions provided by the flow
+    General,
+    /// Jobs for library functions, for executors that have native implementations of them
+    Native,
+}
+
+/// A message received by an executor from the coordinator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoordinatorMessage {
+    /// A control message, such as "DONE" or "CANCEL <submission_id>"
+    Control(String),
+    /// A serialized job `Payload`, or a batch of them
+    Job(String),
+}
+
+/// A `JobTransport` carries messages between the `Dispatcher` of a coordinator and the
+/// executors that execute its jobs: jobs to the executors, control messages broadcast to all of
+/// them, and results (and everything else executors send) back from them
+pub trait JobTransport: Send {
+    /// Send a serialized job, or batch of jobs, to executors on `queue`. Sending on the
+    /// `Native` queue does not wait for an executor to take the job
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job could not be sent, or on the `Native` queue if there is no
+    /// executor ready to take it
+    fn send_job(&mut self, queue: JobQueue, message: &str) -> Result<()>;
+
+    /// Send a control message, such as "HEARTBEAT", to all connected executors
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent
+    fn send_control(&mut self, message: &str) -> Result<()>;
+
+    /// Set the timeout to use when blocked receiving a message from executors, or `None` to
+    /// block forever
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the timeout cannot be set
+    fn set_results_timeout(&mut self, timeout: Option<Duration>) -> Result<()>;
+
+    /// Receive the next message from executors, waiting for one if `block` is true
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no message before the results timeout, or there is none
+    /// when not blocking
+    fn receive_result(&mut self, block: bool) -> Result<String>;
+
+    /// Wait up to `timeout` for a message from executors, returning true if there is one that
+    /// can be received without blocking
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it was not possible to wait for a message
+    fn wait_for_result(&mut self, timeout: Duration) -> Result<bool>;
+}
+
+/// An `ExecutorTransport` carries messages between one executor thread and the coordinator it
+/// executes jobs for
+pub trait ExecutorTransport: Send {
+    /// Wait up to `timeout` for messages from the coordinator, returning those received.
+    /// Control messages are returned ahead of jobs, so that jobs of a cancelled submission
+    /// are not executed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it was not possible to receive messages
+    fn receive(&mut self, timeout: Duration) -> Result<Vec<CoordinatorMessage>>;
+
+    /// Send a message, such as the result of a job, to the coordinator
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent
+    fn send(&self, message: &str) -> Result<()>;
+}
+
+/// An `ExecutorConnector` connects each thread of an `Executor` to the coordinator
+pub trait ExecutorConnector: Send + Sync {
+    /// Connect an executor thread to the coordinator, also getting jobs from the `Native` queue
+    /// if `native_jobs` is true
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection to the coordinator could not be made
+    fn connect(&self, native_jobs: bool) -> Result<Box<dyn ExecutorTransport>>;
+
+    /// Connect to the coordinator to receive control messages and send messages to it, without
+    /// getting any jobs. e.g. for an executor thread that responds to heartbeats
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection to the coordinator could not be made
+    fn connect_control(&self) -> Result<Box<dyn ExecutorTransport>>;
+}
+
+/// A `JobTransport` using `zmq` sockets, so that executors can be in other processes or on other
+/// machines, bound to the addresses of the lib job queue, the general job queue, the results
+/// queue, the control socket and the native lib job queue
+pub struct ZmqJobTransport {
+    lib_job_socket: zmq::Socket,
+    general_job_socket: zmq::Socket,
+    results_socket: zmq::Socket,
+    control_socket: zmq::Socket,
+    native_job_socket: zmq::Socket,
+}
+
+impl ZmqJobTransport {
+    /// Bind the sockets of the job queues to `job_queues` addresses. If `keys` are provided
+    /// then jobs and results are encrypted, and only executors using the same keys can connect.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sockets cannot be created or bound
+    pub fn bind(job_queues: &(String, String, String, String, String),
+                keys: Option<&CurveKeys>) -> Result<Self> {
+        let context = zmq::Context::new();
+        if let Some(curve_keys) = keys {
+            curve_keys.authenticate(&context)?;
+        }
+
+        let lib_job_socket = context.socket(zmq::PUSH)
+            .map_err(|_| "Could not create job socket")?;
+        secure_server(&lib_job_socket, keys)?;
+        enable_ipv6(&lib_job_socket)?;
+        lib_job_socket.bind(&job_queues.0)
+            .map_err(|_| "Could not bind to job socket")?;
+
+        let general_job_socket = context.socket(zmq::PUSH)
+            .map_err(|_| "Could not create context job socket")?;
+        secure_server(&general_job_socket, keys)?;
+        enable_ipv6(&general_job_socket)?;
+        general_job_socket.bind(&job_queues.1)
+            .map_err(|_| "Could not bind to context job socket")?;
+
+        let results_socket = context.socket(zmq::PULL)
+            .map_err(|_| "Could not create results socket")?;
+        secure_server(&results_socket, keys)?;
+        enable_ipv6(&results_socket)?;
+        results_socket.bind(&job_queues.2)
+            .map_err(|_| "Could not bind to results socket")?;
+
+        let control_socket = context.socket(zmq::PUB)
+            .map_err(|_| "Could not create control socket")?;
+        secure_server(&control_socket, keys)?;
+        enable_ipv6(&control_socket)?;
+        control_socket.bind(&job_queues.3)
+            .map_err(|_| "Could not bind to control socket")?;
+
+        let native_job_socket = context.socket(zmq::PUSH)
+            .map_err(|_| "Could not create native job socket")?;
+        secure_server(&native_job_socket, keys)?;
+        enable_ipv6(&native_job_socket)?;
+        native_job_socket.bind(&job_queues.4)
+            .map_err(|_| "Could not bind to native job socket")?;
+
+        Ok(ZmqJobTransport {
+            lib_job_socket,
+            general_job_socket,
+            results_socket,
+            control_socket,
+            native_job_socket,
+        })
+    }
+}
+
+impl JobTransport for ZmqJobTransport {
+    fn send_job(&mut self, queue: JobQueue, message: &str) -> Result<()> {
+        let (socket, flags) = match queue {
+            JobQueue::Lib => (&self.lib_job_socket, 0),
+            JobQueue::General => (&self.general_job_socket, 0),
+            JobQueue::Native => (&self.native_job_socket, DONTWAIT),
+        };
+        socket.send(message.as_bytes(), flags)
+            .map_err(|e| format!("Could not send Job on {queue:?} queue: {e}").into())
+    }
+
+    fn send_control(&mut self, message: &str) -> Result<()> {
+        self.control_socket.send(message.as_bytes(), DONTWAIT)
+            .map_err(|e| format!("Could not send '{message}' message: {e}").into())
+    }
+
+    fn set_results_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        let millis = match timeout {
+            Some(time) => i32::try_from(time.as_millis())?,
+            None => -1,
+        };
+        self.results_socket.set_rcvtimeo(millis)
+            .map_err(|e| format!("Error setting results timeout: {e}").into())
+    }
+
+    fn receive_result(&mut self, block: bool) -> Result<String> {
+        let flags = if block { 0 } else { DONTWAIT };
+        let msg = self.results_socket.recv_msg(flags)
+            .map_err(|_| "Error receiving result")?;
+        Ok(msg.as_str().ok_or("Could not get message as str")?.to_string())
+    }
+
+    fn wait_for_result(&mut self, timeout: Duration) -> Result<bool> {
+        let millis = i64::try_from(timeout.as_millis()).unwrap_or(i64::MAX);
+        self.results_socket.poll(zmq::POLLIN, millis)
+            .map(|events| events > 0)
+            .map_err(|e| format!("Error waiting for result: {e}").into())
+    }
+}
+
+/// An `ExecutorConnector` that connects executor threads to a `ZmqJobTransport` using the
+/// addresses of its job queue, native job queue, results queue and control socket
+pub struct ZmqConnector {
+    job_service: String,
+    native_job_service: String,
+    results_service: String,
+    control_service: String,
+    keys: Option<CurveKeys>,
+}
+
+impl ZmqConnector {
+    /// Create a `ZmqConnector` that connects to a job queue, the native job queue, the results
+    /// queue and the control socket at these addresses, using `keys` to encrypt the connections
+    /// if provided
+    #[must_use]
+    pub fn new(job_service: &str, native_job_service: &str, results_service: &str,
+               control_service: &str, keys: Option<CurveKeys>) -> Self {
+        ZmqConnector {
+            job_service: job_service.into(),
+            native_job_service: native_job_service.into(),
+            results_service: results_service.into(),
+            control_service: control_service.into(),
+            keys,
+        }
+    }
+}
+
+impl ZmqConnector {
+    // Connect the sockets of an executor thread, with those to get jobs from if `jobs` is true,
+    // including from the native job queue if `native_jobs` is also true
+    fn connect_sockets(&self, jobs: bool, native_jobs: bool) -> Result<Box<dyn ExecutorTransport>> {
+        let context = zmq::Context::new();
+        let keys = self.keys.as_ref();
+
+        let job_source = if jobs {
+            let socket = context.socket(zmq::PULL)
+                .map_err(|e| format!("Could not create PULL end of job socket: {e}"))?;
+            secure_client(&socket, keys)?;
+            enable_ipv6(&socket)?;
+            socket.connect(&self.job_service).map_err(|e| {
+                format!("Could not connect to PULL end of job socket: '{}' {e}", self.job_service)
+            })?;
+            Some(socket)
+        } else {
+            None
+        };
+
+        let results_sink = context.socket(zmq::PUSH)
+            .map_err(|e| format!("Could not create PUSH end of results socket: {e}"))?;
+        secure_client(&results_sink, keys)?;
+        enable_ipv6(&results_sink)?;
+        results_sink.connect(&self.results_service)
+            .map_err(|e| format!("Could not connect to PUSH end of results socket: {e}"))?;
+
+        let native_job_source = if jobs && native_jobs {
+            let socket = context.socket(zmq::PULL)
+                .map_err(|e| format!("Could not create PULL end of native job socket: {e}"))?;
+            secure_client(&socket, keys)?;
+            enable_ipv6(&socket)?;
+            socket.connect(&self.native_job_service).map_err(|e| {
+                format!("Could not connect to PULL end of native job socket: '{}' {e}",
+                        self.native_job_service)
+            })?;
+            Some(socket)
+        } else {
+            None
+        };
+
+        let control_socket = context.socket(zmq::SocketType::SUB)
+            .map_err(|e| format!("Could not create SUB end of control socket: {e}"))?;
+        secure_client(&control_socket, keys)?;
+        enable_ipv6(&control_socket)?;
+        control_socket.connect(&self.control_service)
+            .map_err(|e| format!("Could not connect to SUB end of control socket: {e}"))?;
+        control_socket.set_subscribe(&[])
+            .map_err(|e| format!("Could not subscribe to SUB end of control socket: {e}"))?;
+
+        Ok(Box::new(ZmqExecutorTransport {
+            job_source,
+            native_job_source,
+            results_sink,
+            control_socket,
+        }))
+    }
+}
+
+impl ExecutorConnector for ZmqConnector {
+    fn connect(&self, native_jobs: bool) -> Result<Box<dyn ExecutorTransport>> {
+        self.connect_sockets(true, native_jobs)
+    }
+
+    fn connect_control(&self) -> Result<Box<dyn ExecutorTransport>> {
+        self.connect_sockets(false, false)
+    }
+}
+
+// The sockets used by an executor thread connected to a `ZmqJobTransport`
+struct ZmqExecutorTransport {
+    job_source: Option<zmq::Socket>,
+    native_job_source: Option<zmq::Socket>,
+    results_sink: zmq::Socket,
+    control_socket: zmq::Socket,
+}
+
+impl ExecutorTransport for ZmqExecutorTransport {
+    fn receive(&mut self, timeout: Duration) -> Result<Vec<CoordinatorMessage>> {
+        // the control socket is first, so control messages are returned ahead of jobs
+        let sources: [MessageSource; 3] = [
+            (CoordinatorMessage::Control, Some(&self.control_socket)),
+            (CoordinatorMessage::Job, self.job_source.as_ref()),
+            (CoordinatorMessage::Job, self.native_job_source.as_ref()),
+        ];
+        let sources: Vec<_> = sources.into_iter()
+            .filter_map(|(kind, source)| source.map(|socket| (kind, socket)))
+            .collect();
+        let mut items: Vec<zmq::PollItem> = sources.iter()
+            .map(|(_, socket)| socket.as_poll_item(zmq::POLLIN))
+            .collect();
+
+        let millis = i64::try_from(timeout.as_millis()).unwrap_or(i64::MAX);
+        zmq::poll(&mut items, millis).map_err(|_| "Error while polling for Jobs to execute")?;
+
+        let mut messages = vec![];
+        for (item, (kind, socket)) in items.iter().zip(&sources) {
+            if item.is_readable() {
+                let msg = socket.recv_msg(0).map_err(|_| "Error receiving message")?;
+                messages.push(kind(msg.as_str().ok_or("Could not get message as str")?.into()));
+            }
+        }
+
+        Ok(messages)
+    }
+
+    fn send(&self, message: &str) -> Result<()> {
+        self.results_sink.send(message.as_bytes(), 0)
+            .map_err(|e| format!("Could not send message to coordinator: {e}").into())
+    }
+}
+
+// Allow a socket to bind or connect to IPv6 addresses as well as IPv4 ones
+fn enable_ipv6(socket: &zmq::Socket) -> Result<()> {
+    socket.set_ipv6(true)
+        .map_err(|e| format!("Could not enable IPv6 on socket: {e}").into())
+}
+
+// Enable CURVE encryption on a server socket, if `keys` were provided
+fn secure_server(socket: &zmq::Socket, keys: Option<&CurveKeys>) -> Result<()> {
+    match keys {
+        Some(curve_keys) => curve_keys.secure_server(socket),
+        None => Ok(()),
+    }
+}
+
+// Enable CURVE encryption on a client socket, if `keys` were provided
+fn secure_client(socket: &zmq::Socket, keys: Option<&CurveKeys>) -> Result<()> {
+    match keys {
+        Some(curve_keys) => curve_keys.secure_client(socket),
+        None => Ok(()),
+    }
+}
+
+// The ends of the in-process channels that executor threads connect to
+#[derive(Clone)]
+struct ChannelEnds {
+    lib_jobs: Receiver<String>,
+    general_jobs: Receiver<String>,
+    native_jobs: Receiver<String>,
+    results: Sender<String>,
+    // A sender of control messages to each connected executor thread
+    control: Arc<Mutex<Vec<Sender<String>>>>,
+    // The number of connected executor threads taking jobs from the native job queue
+    native_executors: Arc<AtomicUsize>,
+}
+
+/// A `JobTransport` using in-process channels, for running executors in the same process as the
+/// coordinator without any sockets, e.g. when embedding them in an IDE or a web page.
+/// Executors are connected to it using the `ChannelConnector`s returned by `connector()`
+pub struct ChannelJobTransport {
+    lib_jobs: Sender<String>,
+    general_jobs: Sender<String>,
+    native_jobs: Sender<String>,
+    results: Receiver<String>,
+    results_timeout: Option<Duration>,
+    // A message received while waiting for one, not yet returned by `receive_result()`
+    next_result: Option<String>,
+    ends: ChannelEnds,
+}
+
+impl Default for ChannelJobTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChannelJobTransport {
+    /// Create a new `ChannelJobTransport` with no executors connected to it
+    #[must_use]
+    pub fn new() -> Self {
+        let (lib_jobs, lib_job_source) = unbounded();
+        let (general_jobs, general_job_source) = unbounded();
+        let (native_jobs, native_job_source) = unbounded();
+        let (results_sink, results) = unbounded();
+
+        ChannelJobTransport {
+            lib_jobs,
+            general_jobs,
+            native_jobs,
+            results,
+            results_timeout: None,
+            next_result: None,
+            ends: ChannelEnds {
+                lib_jobs: lib_job_source,
+                general_jobs: general_job_source,
+                native_jobs: native_job_source,
+                results: results_sink,
+                control: Arc::new(Mutex::new(vec![])),
+                native_executors: Arc::new(AtomicUsize::new(0)),
+            },
+        }
+    }
+
+    /// Return a `ChannelConnector` to connect an `Executor` to, that gets jobs from `queue`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `queue` is the `Native` queue, as executors get jobs from it as well
+    /// as the queue they connect to, if they have native implementations of libraries
+    pub fn connector(&self, queue: JobQueue) -> Result<ChannelConnector> {
+        if queue == JobQueue::Native {
+            bail!("Executors cannot connect to the {:?} job queue only", queue);
+        }
+
+        Ok(ChannelConnector {
+            queue,
+            ends: self.ends.clone(),
+        })
+    }
+}
+
+impl JobTransport for ChannelJobTransport {
+    fn send_job(&mut self, queue: JobQueue, message: &str) -> Result<()> {
+        let sender = match queue {
+            JobQueue::Lib => &self.lib_jobs,
+            JobQueue::General => &self.general_jobs,
+            JobQueue::Native => {
+                if self.ends.native_executors.load(Ordering::SeqCst) == 0 {
+                    bail!("No executor connected to take Jobs on the {:?} queue", queue);
+                }
+                &self.native_jobs
+            }
+        };
+        sender.send(message.into())
+            .map_err(|e| format!("Could not send Job on {queue:?} queue: {e}").into())
+    }
+
+    fn send_control(&mut self, message: &str) -> Result<()> {
+        // executor threads that have ended are forgotten
+        self.ends.control.lock()
+            .map_err(|_| "Could not lock control channels")?
+            .retain(|sender| sender.send(message.into()).is_ok());
+        Ok(())
+    }
+
+    fn set_results_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.results_timeout = timeout;
+        Ok(())
+    }
+
+    fn receive_result(&mut self, block: bool) -> Result<String> {
+        if let Some(message) = self.next_result.take() {
+            return Ok(message);
+        }
+
+        let received = match (block, self.results_timeout) {
+            (true, Some(timeout)) => self.results.recv_timeout(timeout)
+                .map_err(|e| e.to_string()),
+            (true, None) => self.results.recv().map_err(|e| e.to_string()),
+            (false, _) => self.results.try_recv().map_err(|e| e.to_string()),
+        };
+        received.map_err(|e| format!("Error receiving result: {e}").into())
+    }
+
+    fn wait_for_result(&mut self, timeout: Duration) -> Result<bool> {
+        if self.next_result.is_some() {
+            return Ok(true);
+        }
+
+        match self.results.recv_timeout(timeout) {
+            Ok(message) => {
+                self.next_result = Some(message);
+                Ok(true)
+            }
+            Err(RecvTimeoutError::Timeout) => Ok(false),
+            Err(e) => bail!("Error waiting for result: {}", e),
+        }
+    }
+}
+
+/// An `ExecutorConnector` that connects executor threads to a `ChannelJobTransport`
+pub struct ChannelConnector {
+    queue: JobQueue,
+    ends: ChannelEnds,
+}
+
+impl ExecutorConnector for ChannelConnector {
+    fn connect(&self, native_jobs: bool) -> Result<Box<dyn ExecutorTransport>> {
+        let (control_sink, control) = unbounded();
+        self.ends.control.lock()
+            .map_err(|_| "Could not lock control channels")?
+            .push(control_sink);
+
+        let native_job_source = if native_jobs {
+            self.ends.native_executors.fetch_add(1, Ordering::SeqCst);
+            Some(self.ends.native_jobs.clone())
+        } else {
+            None
+        };
+        let job_source = match self.queue {
+            JobQueue::General => self.ends.general_jobs.clone(),
+            _ => self.ends.lib_jobs.clone(),
+        };
+
+        debug!("Executor thread connected to {:?} job channel", self.queue);
+        Ok(Box::new(ChannelExecutorTransport {
+            job_source,
+            native_job_source,
+            results_sink: self.ends.results.clone(),
+            control,
+            native_executors: self.ends.native_executors.clone(),
+        }))
+    }
+
+    fn connect_control(&self) -> Result<Box<dyn ExecutorTransport>> {
+        let (control_sink, control) = unbounded();
+        self.ends.control.lock()
+            .map_err(|_| "Could not lock control channels")?
+            .push(control_sink);
+
+        Ok(Box::new(ChannelExecutorTransport {
+            job_source: never(),
+            native_job_source: None,
+            results_sink: self.ends.results.clone(),
+            control,
+            native_executors: self.ends.native_executors.clone(),
+        }))
+    }
+}
+
+// The channels used by an executor thread connected to a `ChannelJobTransport`
+struct ChannelExecutorTransport {
+    job_source: Receiver<String>,
+    native_job_source: Option<Receiver<String>>,
+    results_sink: Sender<String>,
+    control: Receiver<String>,
+    native_executors: Arc<AtomicUsize>,
+}
+
+impl ExecutorTransport for ChannelExecutorTransport {
+    fn receive(&mut self, timeout: Duration) -> Result<Vec<CoordinatorMessage>> {
+        let mut messages: Vec<CoordinatorMessage> = self.control.try_iter()
+            .map(CoordinatorMessage::Control)
+            .collect();
+        for source in std::iter::once(&self.job_source).chain(&self.native_job_source) {
+            if let Ok(job) = source.try_recv() {
+                messages.push(CoordinatorMessage::Job(job));
+            }
+        }
+        if !messages.is_empty() {
+            return Ok(messages);
+        }
+
+        let no_native_jobs = never();
+        let native_job_source = self.native_job_source.as_ref().unwrap_or(&no_native_jobs);
+        let received = select! {
+            recv(self.control) -> message => message.map(CoordinatorMessage::Control),
+            recv(self.job_source) -> job => job.map(CoordinatorMessage::Job),
+            recv(native_job_source) -> job => job.map(CoordinatorMessage::Job),
+            default(timeout) => return Ok(vec![]),
+        };
+
+        // the channels are disconnected when the coordinator's transport is dropped, after which
+        // there will be no more jobs, so it is treated as if "DONE" was sent
+        Ok(vec![received.unwrap_or_else(|_| CoordinatorMessage::Control("DONE".into()))])
+    }
+
+    fn send(&self, message: &str) -> Result<()> {
+        self.results_sink.send(message.into())
+            .map_err(|e| format!("Could not send message to coordinator: {e}").into())
+    }
+}
+
+impl Drop for ChannelExecutorTransport {
+    fn drop(&mut self) {
+        if self.native_job_source.is_some() {
+            self.native_executors.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{ChannelJobTransport, CoordinatorMessage, ExecutorConnector, JobQueue,
+                JobTransport};
+
+    #[test]
+    fn jobs_and_results_sent_over_channels() {
+        let mut transport = ChannelJobTransport::new();
+        let mut executor = transport.connector(JobQueue::Lib).expect("Could not get connector")
+            .connect(false).expect("Could not connect executor");
+
+        transport.send_job(JobQueue::Lib, "job").expect("Could not send job");
+        assert_eq!(executor.receive(Duration::from_millis(10)).expect("Could not receive"),
+                   vec![CoordinatorMessage::Job("job".into())]);
+
+        executor.send("result").expect("Could not send result");
+        assert!(transport.wait_for_result(Duration::from_millis(10))
+            .expect("Could not wait for result"));
+        assert_eq!(transport.receive_result(false).expect("Could not receive result"), "result");
+        assert!(transport.receive_result(false).is_err());
+    }
+
+    #[test]
+    fn control_messages_received_ahead_of_jobs() {
+        let mut transport = ChannelJobTransport::new();
+        let connector = transport.connector(JobQueue::General).expect("Could not get connector");
+        let mut first = connector.connect(false).expect("Could not connect executor");
+        let mut second = connector.connect(false).expect("Could not connect executor");
+
+        transport.send_job(JobQueue::General, "job").expect("Could not send job");
+        transport.send_control("CLEAR").expect("Could not send control message");
+        assert_eq!(first.receive(Duration::ZERO).expect("Could not receive"),
+                   vec![CoordinatorMessage::Control("CLEAR".into()),
+                        CoordinatorMessage::Job("job".into())]);
+        assert_eq!(second.receive(Duration::ZERO).expect("Could not receive"),
+                   vec![CoordinatorMessage::Control("CLEAR".into())]);
+    }
+
+    #[test]
+    fn native_jobs_need_a_native_executor() {
+        let mut transport = ChannelJobTransport::new();
+        let connector = transport.connector(JobQueue::Lib).expect("Could not get connector");
+        assert!(transport.connector(JobQueue::Native).is_err());
+        assert!(transport.send_job(JobQueue::Native, "job").is_err());
+
+        let mut executor = connector.connect(true).expect("Could not connect executor");
+        transport.send_job(JobQueue::Native, "job").expect("Could not send native job");
+        assert_eq!(executor.receive(Duration::ZERO).expect("Could not receive"),
+                   vec![CoordinatorMessage::Job("job".into())]);
+
+        drop(executor);
+        assert!(transport.send_job(JobQueue::Native, "job").is_err());
+    }
+
+    #[test]
+    fn control_connection_gets_no_jobs() {
+        let mut transport = ChannelJobTransport::new();
+        let connector = transport.connector(JobQueue::Lib).expect("Could not get connector");
+        let mut control = connector.connect_control().expect("Could not connect");
+
+        transport.send_job(JobQueue::Lib, "job").expect("Could not send job");
+        transport.send_control("HEARTBEAT").expect("Could not send control message");
+        assert_eq!(control.receive(Duration::ZERO).expect("Could not receive"),
+                   vec![CoordinatorMessage::Control("HEARTBEAT".into())]);
+
+        control.send("heartbeat").expect("Could not send heartbeat");
+        assert_eq!(transport.receive_result(false).expect("Could not receive"), "heartbeat");
+    }
+
+    #[test]
+    fn executor_done_when_transport_dropped() {
+        let transport = ChannelJobTransport::new();
+        let mut executor = transport.connector(JobQueue::Lib).expect("Could not get connector")
+            .connect(false).expect("Could not connect executor");
+        drop(transport);
+        assert_eq!(executor.receive(Duration::from_secs(1)).expect("Could not receive"),
+                   vec![CoordinatorMessage::Control("DONE".into())]);
+    }
+}
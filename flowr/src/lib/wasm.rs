@@ -1,19 +1,33 @@
 use std::cmp::max;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use log::info;
-use log::trace;
+use log::{debug, info, trace, warn};
+use once_cell::sync::OnceCell;
 use serde_json::Value;
 use url::Url;
-use wasmtime::{Func, Instance, Memory, Module, Store, Val};
+use wasmtime::{Config, Engine, Func, Instance, Memory, Module, Store, Trap, Val};
 
-use flowcore::{Implementation, RunAgain};
+use flowcore::{Implementation, RunAgain, WASM_MIN_BUFFER_SIZE};
+use flowcore::content_hash;
 use flowcore::errors::{Result, ResultExt, bail};
+use flowcore::model::bytes::{from_wasm_data, to_wasm_data};
 use flowcore::provider::Provider;
 
+use crate::job::OUT_OF_FUEL;
+
 const DEFAULT_WASM_FILENAME: &str = "module";
 
-const MAX_RESULT_SIZE: i32 = 1024;
+// Environment variable to set the directory compiled WASM modules are cached in, instead of
+// `~/.flow/cache`. If set to an empty value then they are not cached
+const CACHE_DIR_ENV_VAR: &str = "FLOW_WASM_CACHE";
+// The file in the cache directory with the configuration of wasmtime's cache
+const CACHE_CONFIG_FILENAME: &str = "wasmtime-cache.toml";
+
+// The `Engine` used to compile and run all WASM modules, shared so that it is only created once
+static ENGINE: OnceCell<Engine> = OnceCell::new();
 
 #[derive(Debug)]
 pub struct Executor {
@@ -25,17 +39,19 @@ pub struct Executor {
 }
 
 impl Executor {
-    // Serialize the inputs into JSON and then write them into the linear memory for WASM to read
-    // Return the offset of the data in linear memory and the data size in bytes
-    fn send_inputs(&self, store: &mut Store<()>, inputs: &[Value]) -> Result<(i32, i32)> {
-        let input_data = serde_json::to_vec(&inputs)?;
-        let alloc_size = max(i32::try_from(input_data.len())?, MAX_RESULT_SIZE);
+    // Serialize the inputs into JSON, with values of the `bytes` type carried as raw bytes after
+    // it, and then write them into the linear memory for WASM to read
+    // Return the offset of the data in linear memory, the data size in bytes and the size of
+    // the buffer allocated, which is the maximum size of result the implementation can return
+    fn send_inputs(&self, store: &mut Store<()>, inputs: &[Value]) -> Result<(i32, i32, i32)> {
+        let input_data = to_wasm_data(&inputs)?;
+        let alloc_size = i32::try_from(max(input_data.len(), WASM_MIN_BUFFER_SIZE))?;
         let offset = self.alloc(alloc_size, store)?;
         self.memory
             .write(store, usize::try_from(offset)?, &input_data)
             .map_err(|_| "Could not write to WASM Linear Memory")?;
         let data_size = i32::try_from(input_data.len())?;
-        Ok((offset, data_size))
+        Ok((offset, data_size, alloc_size))
     }
 
     // Call the "alloc" wasm function
@@ -44,6 +60,8 @@ impl Executor {
     fn alloc(&self, length: i32, store: &mut Store<()>) -> Result<i32> {
         let mut results: [Val;1] = [Val::I32(0)];
         let params = [Val::I32(length)];
+        store.set_fuel(u64::MAX)
+            .map_err(|e| format!("Could not set WASM fuel: {e}"))?;
         self.alloc.call(store, &params, &mut results)
             .map_err(|_| "WASM alloc() call failed")?;
 
@@ -56,22 +74,32 @@ impl Executor {
     // Call the "implementation" wasm function
     // - `offset` is the offset to the input values (json), and the length of the json
     // - `length` is the length of the input json
+    // - `capacity` is the size of the buffer allocated at `offset`
+    // - `fuel` is the limit (if any) on the fuel it may consume
     // - returns the length of the resulting json, at the same offset
-    fn call(&self, offset: i32, length: i32, store: &mut Store<()>) -> Result<i32> {
+    fn call(&self, offset: i32, length: i32, capacity: i32, fuel: Option<u64>,
+            store: &mut Store<()>) -> Result<i32> {
         let mut results: [Val;1] = [Val::I32(0)];
         let params = [Val::I32(offset), Val::I32(length)];
+        store.set_fuel(fuel.unwrap_or(u64::MAX))
+            .map_err(|e| format!("Could not set WASM fuel: {e}"))?;
         self.implementation
             .call(store, &params, &mut results)
-            .map_err(|e| format!("Error returned by WASM implementation.call() for {:?} => '{}'",
-            self.source_url, e))?;
+            .map_err(|e| match (e.downcast_ref::<Trap>(), fuel) {
+                (Some(Trap::OutOfFuel), Some(fuel)) => format!(
+                    "{OUT_OF_FUEL}: WASM implementation '{}' exceeded its limit of {fuel} units of fuel",
+                    self.source_url),
+                _ => format!("Error returned by WASM implementation.call() for {:?} => '{}'",
+                             self.source_url, e),
+            })?;
 
         match results[0] {
             Val::I32(result_length) => {
                 trace!("Return length from wasm function of {}", result_length);
-                if result_length > MAX_RESULT_SIZE {
+                if result_length < 0 || result_length > capacity {
                     bail!(
-                    "Return length from wasm function of {} exceed maximum allowed",
-                    result_length
+                    "Return length from wasm function of {} is outside the buffer of {} bytes",
+                    result_length, capacity
                     );
                 }
                 Ok(result_length)
@@ -94,7 +122,7 @@ impl Executor {
             .read(store, offset, &mut buffer)
             .map_err(|_| "could not read return value from WASM linear memory")?;
 
-        let result_returned = serde_json::from_slice(buffer.as_slice())
+        let result_returned = from_wasm_data(buffer.as_slice())
             .chain_err(|| "Could not convert returned data from wasm to json")?;
         trace!("WASM run() function invocation Result = {:?}", result_returned);
         result_returned
@@ -104,17 +132,26 @@ impl Executor {
 
 impl Implementation for Executor {
     fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        self.run_metered(inputs, None)
+    }
+
+    // Only the execution of the implementation itself is metered, not the allocation of memory
+    // for the inputs to it
+    fn run_metered(&self, inputs: &[Value], fuel: Option<u64>) -> Result<(Option<Value>, RunAgain)> {
         let mut store = self.store.lock().map_err(|_| "Could not lock WASM store")?;
-        let (offset, length) = self.send_inputs(&mut store, inputs)?;
-        let result_length = self.call(offset, length, &mut store)?;
+        let (offset, length, capacity) = self.send_inputs(&mut store, inputs)?;
+        let result_length = self.call(offset, length, capacity, fuel, &mut store)?;
         assert!(offset >= 0, "offset was negative");
         #[allow(clippy::cast_sign_loss)]
         self.get_result(result_length, offset as usize, &mut store)
     }
 }
 
-/// load a Wasm module from the specified Url and return it wrapped in a `WasmExecutor` `Implementation`
-pub fn load(provider: &Arc<dyn Provider>, source_url: &Url) -> Result<Executor> {
+/// load a Wasm module from the specified Url and return it wrapped in a `WasmExecutor` `Implementation`.
+/// If an `expected_hash` was recorded for it when it was compiled, the SHA-256 hash of the module
+/// loaded must match it, so that a stale or corrupted module is not executed.
+pub fn load(provider: &Arc<dyn Provider>, source_url: &Url, expected_hash: Option<&str>)
+    -> Result<Executor> {
     trace!("Attempting to load WASM module from '{}'", source_url);
     let (resolved_url, _) = provider
         .resolve_url(source_url, DEFAULT_WASM_FILENAME, &["wasm"])
@@ -123,7 +160,12 @@ pub fn load(provider: &Arc<dyn Provider>, source_url: &Url) -> Result<Executor>
         format!("Could not fetch content from url '{resolved_url}' for loading wasm")
     })?;
 
-    let mut store: Store<()> = Store::default();
+    if let Some(expected_hash) = expected_hash {
+        content_hash::verify(&content, expected_hash, resolved_url.as_str())
+            .chain_err(|| format!("Could not load WASM module from '{resolved_url}'"))?;
+    }
+
+    let mut store: Store<()> = Store::new(engine()?, ());
     let module = Module::from_binary(store.engine(), &content)
         .map_err(|e| format!("Could not create WASM Module: {e}"))?;
     let instance = Instance::new(&mut store, &module, &[])
@@ -151,6 +193,51 @@ pub fn load(provider: &Arc<dyn Provider>, source_url: &Url) -> Result<Executor>
     })
 }
 
+// Get the `Engine` used to compile and run WASM modules, creating it the first time.
+// Fuel is consumed by all WASM execution, so that jobs can be metered if required, and compiled
+// modules are cached so they are not compiled again by later runs or executors
+fn engine() -> Result<&'static Engine> {
+    ENGINE.get_or_try_init(|| {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        if let Some(cache_dir) = cache_dir() {
+            match enable_cache(&mut config, &cache_dir) {
+                Ok(()) => debug!("Caching compiled WASM modules in '{}'", cache_dir.display()),
+                Err(e) => warn!("Compiled WASM modules will not be cached: {e}"),
+            }
+        }
+        Engine::new(&config).map_err(|e| format!("Could not create WASM Engine: {e}").into())
+    })
+}
+
+// The directory to cache compiled WASM modules in: set by `FLOW_WASM_CACHE`, or by default
+// `~/.flow/cache` if the user's home directory is known
+fn cache_dir() -> Option<PathBuf> {
+    match env::var_os(CACHE_DIR_ENV_VAR) {
+        Some(dir) if dir.is_empty() => None,
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => env::var_os("HOME")
+            .map(|home_dir| Path::new(&home_dir).join(".flow").join("cache")),
+    }
+}
+
+// Configure wasmtime to cache the modules it compiles in `cache_dir`. Modules are cached by a
+// hash of their contents and of the compiler's settings, so a module is only compiled again if
+// it, or the version of wasmtime, changes
+fn enable_cache(config: &mut Config, cache_dir: &Path) -> Result<()> {
+    fs::create_dir_all(cache_dir)
+        .chain_err(|| format!("Could not create directory '{}'", cache_dir.display()))?;
+    let config_path = cache_dir.join(CACHE_CONFIG_FILENAME);
+    if !config_path.exists() {
+        let directory = toml::Value::from(cache_dir.display().to_string());
+        fs::write(&config_path, format!("[cache]\nenabled = true\ndirectory = {directory}\n"))
+            .chain_err(|| format!("Could not write '{}'", config_path.display()))?;
+    }
+    config.cache_config_load(&config_path)
+        .map_err(|e| format!("Could not load '{}': {e}", config_path.display()))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use std::path::Path;
@@ -159,16 +246,109 @@ mod test {
     use serde_json::json;
     use url::Url;
 
+    use flowcore::content_hash;
     use flowcore::content::file_provider::FileProvider;
     use flowcore::Implementation;
     use flowcore::provider::Provider;
 
+    // Requires the WASM version of flowstdlib to have been built and installed in a directory
+    // in the library search path (`FLOW_LIB_PATH` or `~/.flow/lib`), so only run with the
+    // "wasm_flowstdlib" feature
+    #[cfg(feature = "wasm_flowstdlib")]
+    mod agreement {
+        use std::path::PathBuf;
+        use std::sync::Arc;
+
+        use proptest::prelude::*;
+        use proptest::test_runner::TestRunner;
+        use serde_json::Value;
+        use simpath::Simpath;
+
+        use flowcore::errors::Result;
+        use flowcore::Implementation;
+        use flowcore::meta_provider::MetaProvider;
+        use flowcore::model::lib_manifest::ImplementationLocator::Native;
+        use flowcore::provider::Provider;
+        use flowcore::RunAgain;
+
+        // Check the results of running the native and WASM implementations of a function agree.
+        // An output of `null` cannot be distinguished from no output once serialized to JSON to be
+        // returned from WASM, so those are considered equal.
+        fn assert_agree(native: &Result<(Option<Value>, RunAgain)>,
+                        wasm: &Result<(Option<Value>, RunAgain)>, inputs: &[Value])
+            -> std::result::Result<(), TestCaseError> {
+            match (native, wasm) {
+                (Ok((native_value, native_run_again)), Ok((wasm_value, wasm_run_again))) => {
+                    prop_assert_eq!(native_value.as_ref().filter(|value| !value.is_null()),
+                        wasm_value.as_ref(), "inputs: {:?}", inputs);
+                    prop_assert_eq!(native_run_again, wasm_run_again, "inputs: {:?}", inputs);
+                },
+                (Err(_), Err(_)) => {},
+                _ => prop_assert!(false, "native result {:?} and wasm result {:?} differ for inputs {:?}",
+                    native, wasm, inputs),
+            }
+            Ok(())
+        }
+
+        // Small arbitrary json values of all types, including nested arrays and objects
+        fn json_value() -> impl Strategy<Value = Value> {
+            let leaf = prop_oneof![
+                Just(Value::Null),
+                any::<bool>().prop_map(Value::from),
+                (-100i64..100).prop_map(Value::from),
+                (-100.0f64..100.0).prop_map(Value::from),
+                "[a-z ]{0,8}".prop_map(Value::from),
+            ];
+            leaf.prop_recursive(3, 16, 4, |inner| prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+                prop::collection::btree_map("[a-z]{1,4}", inner, 0..4)
+                    .prop_map(|map| Value::Object(map.into_iter().collect())),
+            ])
+        }
+
+        #[test]
+        fn wasm_flowstdlib_agrees_with_native() {
+            let mut lib_search_path = Simpath::new_with_separator("FLOW_LIB_PATH", ',');
+            if let Ok(home_dir) = std::env::var("HOME") {
+                lib_search_path.add(&format!("{home_dir}/.flow/lib"));
+            }
+            let provider = Arc::new(MetaProvider::new(lib_search_path, PathBuf::from("/")))
+                as Arc<dyn Provider>;
+            let manifest = flowstdlib::manifest::get().expect("Could not get flowstdlib manifest");
+
+            for (url, locator) in &manifest.locators {
+                let Native(native) = locator else { continue };
+                let wasm = super::super::load(&provider, url, None)
+                    .unwrap_or_else(|e| panic!("Could not load WASM for '{url}': {e}"));
+
+                TestRunner::default().run(&prop::collection::vec(json_value(), 0..4), |inputs| {
+                    assert_agree(&native.run(&inputs), &wasm.run(&inputs), &inputs)
+                }).unwrap_or_else(|e| panic!("Native and WASM '{url}' do not agree: {e}"));
+            }
+        }
+    }
+
+    #[test]
+    fn cache_config_written() {
+        let cache_dir = tempfile::tempdir().expect("Could not create temp dir");
+        let mut config = wasmtime::Config::new();
+        super::enable_cache(&mut config, cache_dir.path()).expect("Could not enable cache");
+
+        let cache_config = std::fs::read_to_string(
+            cache_dir.path().join(super::CACHE_CONFIG_FILENAME))
+            .expect("Could not read cache config");
+        assert!(cache_config.contains(&cache_dir.path().display().to_string()));
+
+        // an existing config is used as it is
+        super::enable_cache(&mut config, cache_dir.path()).expect("Could not enable cache again");
+    }
+
     #[test]
     fn load_test_wasm() {
         let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/add.wasm");
         let url = Url::from_file_path(path).expect("Could not convert path to Url");
         let provider = Arc::new(FileProvider{}) as Arc<dyn Provider>;
-        let adder = &super::load(&provider, &url)
+        let adder = &super::load(&provider, &url, None)
             .expect("Could not load test_wasm.wasm") as &dyn Implementation;
 
         let inputs = vec![json!(1), json!(2)];
@@ -177,4 +357,41 @@ mod test {
         assert_eq!(value, Some(json!(3)));
         assert!(run_again);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn out_of_fuel() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/add.wasm");
+        let url = Url::from_file_path(path).expect("Could not convert path to Url");
+        let provider = Arc::new(FileProvider{}) as Arc<dyn Provider>;
+        let adder = &super::load(&provider, &url, None)
+            .expect("Could not load test_wasm.wasm") as &dyn Implementation;
+
+        let inputs = vec![json!(1), json!(2)];
+        let error = adder.run_metered(&inputs, Some(1)).expect_err("Should have run out of fuel");
+        assert!(crate::job::is_out_of_fuel(&error));
+
+        // the same instance can still be run with enough fuel
+        let (value, _) = adder.run_metered(&inputs, Some(1_000_000))
+            .expect("Could not call run_metered");
+        assert_eq!(value, Some(json!(3)));
+    }
+
+    #[test]
+    fn load_verified_wasm() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/add.wasm");
+        let hash = content_hash::sha256(&std::fs::read(&path).expect("Could not read add.wasm"));
+        let url = Url::from_file_path(path).expect("Could not convert path to Url");
+        let provider = Arc::new(FileProvider{}) as Arc<dyn Provider>;
+        assert!(super::load(&provider, &url, Some(&hash)).is_ok());
+    }
+
+    #[test]
+    fn load_stale_wasm() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/add.wasm");
+        let url = Url::from_file_path(path).expect("Could not convert path to Url");
+        let provider = Arc::new(FileProvider{}) as Arc<dyn Provider>;
+        let error = super::load(&provider, &url, Some(&content_hash::sha256(b"other wasm")))
+            .expect_err("Stale wasm module should not load");
+        assert!(error.iter().any(|e| e.to_string().contains("does not match the hash")));
+    }
+}
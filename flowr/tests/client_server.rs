@@ -5,6 +5,7 @@ use std::path::PathBuf;
 #[test]
 fn hello_world_client_server() {
     let example_dir = PathBuf::from("examples/hello-world");
-    utilities::compile_example(&example_dir, "flowrcli");
-    utilities::execute_flow_client_server("hello-world", example_dir.join("manifest.json"));
+    let output_dir = utilities::create_output_dir(&example_dir, "client-server");
+    utilities::compile_example(&example_dir, "flowrcli", &output_dir);
+    utilities::execute_flow_client_server("hello-world", output_dir.join("manifest.json"));
 }
\ No newline at end of file
@@ -0,0 +1,38 @@
+//! Criterion benchmarks of the end-to-end execution of some example flows in-process, so that
+//! changes in the performance of flow execution in `flowrlib` can be measured
+
+#![allow(missing_docs)]
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use utilities::create_output_dir;
+use utilities::in_process::CompiledFlow;
+
+fn example_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples").join(name)
+}
+
+fn bench_example(c: &mut Criterion, name: &str, args: &[&str]) {
+    let sample_dir = example_dir(name);
+    let output_dir = create_output_dir(&sample_dir, "bench");
+    let flow = CompiledFlow::compile(&sample_dir, &output_dir)
+        .expect("Could not compile example");
+    let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+
+    c.bench_function(name, |b| b.iter(|| {
+        flow.run(args.clone(), vec![], true, Duration::from_secs(60))
+            .expect("Could not run example")
+    }));
+}
+
+fn examples(c: &mut Criterion) {
+    bench_example(c, "fibonacci", &[]);
+    bench_example(c, "prime", &["9"]);
+    bench_example(c, "sequence", &[]);
+}
+
+criterion_group!(benches, examples);
+criterion_main!(benches);
@@ -0,0 +1,215 @@
+//! Benchmark the end-to-end execution of a flow, by running a [CompiledFlow] in-process a number
+//! of times after some warmup runs, and reporting statistics on the wall time taken and jobs
+//! executed per second.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use flowcore::errors::{bail, Result};
+
+use crate::in_process::CompiledFlow;
+
+/// Options that control how a flow is benchmarked
+#[derive(Debug, Clone)]
+pub struct BenchmarkOptions {
+    /// Number of runs of the flow before measurements are taken
+    pub warmup: usize,
+    /// Number of runs of the flow that are measured
+    pub iterations: usize,
+    /// Use the native version of `flowstdlib` linked in, instead of the WASM version
+    pub native: bool,
+    /// Arguments to pass to the flow on each run
+    pub flow_args: Vec<String>,
+    /// Contents supplied to the flow as standard input on each run
+    pub stdin: Vec<u8>,
+    /// Maximum time allowed for each run of the flow
+    pub timeout: Duration,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        BenchmarkOptions {
+            warmup: 2,
+            iterations: 10,
+            native: true,
+            flow_args: vec![],
+            stdin: vec![],
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The measurements taken while benchmarking a flow
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    /// The wall time taken by each measured run of the flow
+    pub run_times: Vec<Duration>,
+    /// The number of jobs executed in each run of the flow
+    pub jobs_per_run: usize,
+}
+
+impl BenchmarkReport {
+    /// The mean time taken to run the flow
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        self.run_times.iter().sum::<Duration>()
+            .checked_div(u32::try_from(self.run_times.len()).unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+
+    /// The median time taken to run the flow
+    #[must_use]
+    pub fn median(&self) -> Duration {
+        let mut sorted = self.run_times.clone();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        match (sorted.get(middle.wrapping_sub(1)), sorted.get(middle)) {
+            (Some(lower), Some(upper)) if sorted.len().is_multiple_of(2) => (*lower + *upper) / 2,
+            (_, Some(median)) => *median,
+            _ => Duration::default(),
+        }
+    }
+
+    /// The sample standard deviation of the time taken to run the flow
+    #[must_use]
+    pub fn std_dev(&self) -> Duration {
+        if self.run_times.len() < 2 {
+            return Duration::default();
+        }
+        let mean = self.mean().as_secs_f64();
+        let sum_of_squares: f64 = self.run_times.iter()
+            .map(|time| (time.as_secs_f64() - mean).powi(2))
+            .sum();
+        #[allow(clippy::cast_precision_loss)]
+        let variance = sum_of_squares / (self.run_times.len() - 1) as f64;
+        Duration::from_secs_f64(variance.sqrt())
+    }
+
+    /// The shortest time taken to run the flow
+    #[must_use]
+    pub fn min(&self) -> Duration {
+        self.run_times.iter().min().copied().unwrap_or_default()
+    }
+
+    /// The longest time taken to run the flow
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        self.run_times.iter().max().copied().unwrap_or_default()
+    }
+
+    /// The number of jobs executed per second, based on the mean time taken to run the flow
+    #[must_use]
+    pub fn jobs_per_second(&self) -> f64 {
+        let mean = self.mean().as_secs_f64();
+        if mean == 0.0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let jobs = self.jobs_per_run as f64;
+        jobs / mean
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Runs: {}", self.run_times.len())?;
+        writeln!(f, "Jobs per run: {}", self.jobs_per_run)?;
+        writeln!(f, "Mean: {:?} (± {:?})", self.mean(), self.std_dev())?;
+        writeln!(f, "Median: {:?}", self.median())?;
+        writeln!(f, "Min: {:?}", self.min())?;
+        writeln!(f, "Max: {:?}", self.max())?;
+        write!(f, "Jobs/sec: {:.1}", self.jobs_per_second())
+    }
+}
+
+/// Run `flow` `options.warmup` times without measuring, then `options.iterations` times
+/// measuring the wall time taken by each run
+///
+/// # Errors
+///
+/// Returns an error if any run of the flow fails, produces output on `stderr`, or does not
+/// execute the same number of jobs as the other runs
+pub fn benchmark(flow: &CompiledFlow, options: &BenchmarkOptions) -> Result<BenchmarkReport> {
+    if options.iterations == 0 {
+        bail!("At least one iteration is needed to benchmark a flow");
+    }
+
+    for _ in 0..options.warmup {
+        run(flow, options)?;
+    }
+
+    let mut report = BenchmarkReport {
+        run_times: Vec::with_capacity(options.iterations),
+        jobs_per_run: 0,
+    };
+
+    for iteration in 0..options.iterations {
+        let start = Instant::now();
+        let jobs = run(flow, options)?;
+        report.run_times.push(start.elapsed());
+
+        if iteration == 0 {
+            report.jobs_per_run = jobs;
+        } else if jobs != report.jobs_per_run {
+            bail!("Run {} of the flow executed {jobs} jobs, but the first executed {}",
+                iteration + 1, report.jobs_per_run);
+        }
+    }
+
+    Ok(report)
+}
+
+// Run the flow once, returning the number of jobs executed
+fn run(flow: &CompiledFlow, options: &BenchmarkOptions) -> Result<usize> {
+    let output = flow.run(options.flow_args.clone(), options.stdin.clone(), options.native,
+                          options.timeout)?;
+    if !output.stderr.is_empty() {
+        bail!("Flow produced output on stderr:\n{}", output.stderr);
+    }
+    Ok(output.jobs_executed)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::BenchmarkReport;
+
+    fn report(millis: &[u64], jobs_per_run: usize) -> BenchmarkReport {
+        BenchmarkReport {
+            run_times: millis.iter().map(|ms| Duration::from_millis(*ms)).collect(),
+            jobs_per_run,
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        let report = report(&[40, 10, 30, 20], 100);
+        assert_eq!(report.mean(), Duration::from_millis(25));
+        assert_eq!(report.median(), Duration::from_millis(25));
+        assert_eq!(report.min(), Duration::from_millis(10));
+        assert_eq!(report.max(), Duration::from_millis(40));
+        assert_eq!(report.std_dev().as_micros(), 12_909);
+        assert!((report.jobs_per_second() - 4000.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn median_of_odd_number_of_runs() {
+        assert_eq!(report(&[30, 10, 20], 1).median(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn single_run() {
+        let report = report(&[10], 1);
+        assert_eq!(report.std_dev(), Duration::default());
+        assert_eq!(report.median(), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn no_runs() {
+        let report = report(&[], 0);
+        assert_eq!(report.mean(), Duration::default());
+        assert_eq!(report.median(), Duration::default());
+        assert!(report.jobs_per_second().abs() < f64::EPSILON);
+    }
+}
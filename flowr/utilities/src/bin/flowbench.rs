@@ -0,0 +1,109 @@
+//! `flowbench` measures the end-to-end execution time of a flow.
+//!
+//! It compiles the flow once and then runs it a number of times in-process (after some warmup
+//! runs), using a `flowrlib` [`Coordinator`][flowrlib::coordinator::Coordinator], and reports
+//! statistics on the wall time taken and the number of jobs executed per second.
+//!
+//! Use `flowbench --help` at the command line to see the command line options
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::Duration;
+
+use clap::{Arg, ArgMatches, Command};
+
+use flowcore::errors::{Result, ResultExt};
+use utilities::bench::{benchmark, BenchmarkOptions};
+use utilities::create_output_dir;
+use utilities::in_process::CompiledFlow;
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{e}");
+        for cause in e.iter().skip(1) {
+            eprintln!("caused by: {cause}");
+        }
+        exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let matches = get_matches();
+    let defaults = BenchmarkOptions::default();
+
+    let flow_dir = PathBuf::from(matches.get_one::<String>("flow-dir")
+        .ok_or("No flow directory specified")?);
+    let stdin = match matches.get_one::<String>("stdin") {
+        Some(stdin_file) => fs::read(stdin_file)
+            .chain_err(|| format!("Could not read stdin file '{stdin_file}'"))?,
+        None => vec![],
+    };
+    let options = BenchmarkOptions {
+        warmup: matches.get_one::<usize>("warmup").copied().unwrap_or(defaults.warmup),
+        iterations: matches.get_one::<usize>("iterations").copied()
+            .unwrap_or(defaults.iterations),
+        native: !matches.get_flag("wasm"),
+        flow_args: matches.get_many::<String>("flow_args")
+            .map(|args| args.cloned().collect()).unwrap_or_default(),
+        stdin,
+        timeout: matches.get_one::<u64>("timeout").map_or(defaults.timeout,
+                                                           |secs| Duration::from_secs(*secs)),
+    };
+
+    let output_dir = create_output_dir(&flow_dir, "flowbench");
+    println!("Compiling flow in '{}' into '{}'", flow_dir.display(), output_dir.display());
+    let flow = CompiledFlow::compile(&flow_dir, &output_dir)
+        .chain_err(|| format!("Could not compile flow in '{}'", flow_dir.display()))?;
+
+    println!("Running flow {} times after {} warmup runs", options.iterations, options.warmup);
+    let report = benchmark(&flow, &options)?;
+    println!("{report}");
+
+    Ok(())
+}
+
+/// Parse the command line arguments using clap
+fn get_matches() -> ArgMatches {
+    Command::new("flowbench").version(env!("CARGO_PKG_VERSION"))
+        .arg(Arg::new("iterations")
+            .short('i')
+            .long("iterations")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("ITERATIONS")
+            .help("Set number of measured runs of the flow (default: 10)"))
+        .arg(Arg::new("warmup")
+            .short('w')
+            .long("warmup")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(usize))
+            .value_name("WARMUP")
+            .help("Set number of runs of the flow before measuring (default: 2)"))
+        .arg(Arg::new("timeout")
+            .short('t')
+            .long("timeout")
+            .number_of_values(1)
+            .value_parser(clap::value_parser!(u64))
+            .value_name("SECONDS")
+            .help("Set the maximum time allowed for each run of the flow (default: 60)"))
+        .arg(Arg::new("stdin")
+            .short('s')
+            .long("stdin")
+            .number_of_values(1)
+            .value_name("STDIN_FILE")
+            .help("A file whose contents are supplied to the flow as standard input on each run"))
+        .arg(Arg::new("wasm")
+            .long("wasm")
+            .action(clap::ArgAction::SetTrue)
+            .help("Use the WASM (not native) version of flowstdlib"))
+        .arg(Arg::new("flow-dir")
+            .num_args(1)
+            .required(true)
+            .help("the directory containing the flow to benchmark"))
+        .arg(Arg::new("flow_args")
+            .num_args(0..)
+            .trailing_var_arg(true)
+            .help("A list of arguments to pass to the flow on each run."))
+        .get_matches()
+}
@@ -0,0 +1,169 @@
+//! Run an example flow distributed across a coordinator and a number of `flowrex` worker
+//! processes on this machine.
+//!
+//! The coordinator is `flowrcli` started with no executor threads of its own (other than those
+//! for context functions), so every library job is executed by one of the workers. Workers find
+//! the coordinator's job queues via discovery, execute jobs until the coordinator announces the
+//! flow is done on the control socket, and then log how many jobs they executed, which is read
+//! from their logging output on `stderr`.
+//!
+//! Discovery uses a fixed port, so only one distributed run can take place on a machine at a time.
+
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use flowrlib::services::JOBS_EXECUTED_MESSAGE;
+
+use crate::{check_test_output, compile_example, create_output_dir, read_file,
+            spawn_runner, timeout, workspace_path, TEST_STDERR_FILENAME, TEST_STDOUT_FILENAME};
+
+/// Maximum time to wait for workers to report the jobs they executed after the flow is done
+const REPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The result of running an example distributed across a number of workers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistributedRun {
+    /// The directory the flow was compiled into, and its output saved in
+    pub output_dir: PathBuf,
+    /// The number of jobs executed by each worker, or `None` if it did not report it
+    pub worker_jobs: Vec<Option<usize>>,
+}
+
+impl DistributedRun {
+    /// The total number of jobs reported as executed by the workers
+    #[must_use]
+    pub fn total_jobs(&self) -> usize {
+        self.worker_jobs.iter().flatten().sum()
+    }
+}
+
+impl fmt::Display for DistributedRun {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (number, jobs) in self.worker_jobs.iter().enumerate() {
+            match jobs {
+                Some(jobs) => writeln!(f, "Worker #{number}: {jobs} jobs executed")?,
+                None => writeln!(f, "Worker #{number}: did not report jobs executed")?,
+            }
+        }
+        write!(f, "Total: {} jobs executed", self.total_jobs())
+    }
+}
+
+/// A `flowrex` worker process, and the channel its logged report of jobs executed will arrive on
+struct Worker {
+    child: Child,
+    report: Receiver<usize>,
+}
+
+impl Worker {
+    fn spawn() -> Self {
+        let mut child = Command::new("flowrex")
+            .args(["--verbosity", "info"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn().expect("Could not spawn flowrex");
+
+        let (sender, report) = mpsc::channel();
+        let stderr = child.stderr.take().expect("Could not take flowrex STDERR");
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if let Some(Ok(jobs)) = line.split_once(JOBS_EXECUTED_MESSAGE)
+                    .map(|(_, jobs)| jobs.trim().parse::<usize>()) {
+                    let _ = sender.send(jobs);
+                }
+            }
+        });
+
+        Worker { child, report }
+    }
+
+    // Wait until `deadline` for the worker to report the jobs it executed, then kill it
+    fn finish(mut self, deadline: Instant) -> Option<usize> {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let jobs = self.report.recv_timeout(remaining).ok();
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        jobs
+    }
+}
+
+/// Run the example whose source file is `source_file` using `flowrcli` as the coordinator and
+/// `workers` `flowrex` processes to execute its jobs, failing with the output produced so far if
+/// it does not complete within the example's timeout.
+///
+/// The flow is compiled into, and run in, a new output directory, which is returned along with
+/// the number of jobs each worker executed.
+pub fn run_example_distributed(source_file: &str, workers: usize) -> DistributedRun {
+    let mut sample_dir = PathBuf::from(source_file);
+    sample_dir.pop();
+
+    let output_dir = create_output_dir(&sample_dir, "distributed");
+    compile_example(&sample_dir, "flowrcli", &output_dir);
+
+    println!("\n\tRunning example distributed: {}", sample_dir.display());
+    println!("\t\tWorkers: {workers}");
+    println!("\t\tOutput is saved in {}", output_dir.display());
+    let timeout = timeout(&sample_dir);
+    println!("\t\tTimeout is {}s", timeout.as_secs());
+
+    // start the workers first, so they are waiting to discover the coordinator when it starts
+    let running: Vec<Worker> = (0..workers).map(|_| Worker::spawn()).collect();
+
+    // set 0 executor threads in the coordinator, so that all job execution is done by workers
+    let runner_args = vec!["--native".into(), "--threads".into(), "0".into()];
+    let completed = spawn_runner(&sample_dir, &output_dir, "flowrcli", runner_args, timeout);
+
+    let deadline = Instant::now() + REPORT_TIMEOUT;
+    let worker_jobs = running.into_iter().map(|worker| worker.finish(deadline)).collect();
+
+    if !completed {
+        panic!("Example '{}' did not complete within {}s\nSTDOUT:\n{}\nSTDERR:\n{}",
+               sample_dir.display(), timeout.as_secs(),
+               read_file(&output_dir, TEST_STDOUT_FILENAME),
+               read_file(&output_dir, TEST_STDERR_FILENAME));
+    }
+
+    DistributedRun { output_dir, worker_jobs }
+}
+
+/// Run an example distributed across `workers` `flowrex` processes, check the output matches
+/// the expected and that the workers executed its jobs, and report the jobs each one executed
+pub fn test_example_distributed(source_file: &str, workers: usize) -> DistributedRun {
+    let source_file = &workspace_path(source_file);
+    let run = run_example_distributed(source_file, workers);
+    println!("{run}");
+    check_test_output(source_file, &run.output_dir);
+    check_workers(&run);
+    run
+}
+
+// Check that the workers executed some jobs. A worker that had not discovered the coordinator
+// by the time the flow was done will not have been told it is done, and so will not report
+fn check_workers(run: &DistributedRun) {
+    assert!(run.total_jobs() > 0, "No jobs were executed by the workers\n{run}");
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::DistributedRun;
+
+    #[test]
+    fn total_ignores_unreported_workers() {
+        let run = DistributedRun {
+            output_dir: PathBuf::new(),
+            worker_jobs: vec![Some(10), None, Some(5)],
+        };
+        assert_eq!(run.total_jobs(), 15);
+        assert_eq!(run.to_string(), "Worker #0: 10 jobs executed\n\
+            Worker #1: did not report jobs executed\nWorker #2: 5 jobs executed\n\
+            Total: 15 jobs executed");
+    }
+}
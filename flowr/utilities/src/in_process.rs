@@ -0,0 +1,578 @@
+//! Compile and run an example flow within the calling process, using `flowrclib` to compile it
+//! and a `flowrlib` [Coordinator] to execute it, with a set of context functions that capture
+//! all the output of the flow into buffers instead of interacting with the terminal or file system.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufRead, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use image::{ImageFormat, Rgb, RgbImage};
+use portpicker::pick_unused_port;
+use serde_json::{json, Value};
+use simpath::Simpath;
+use url::Url;
+
+use flowcore::{DONT_RUN_AGAIN, Implementation, RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result, ResultExt};
+use flowcore::meta_provider::MetaProvider;
+use flowcore::model::flow_manifest::FlowManifest;
+use flowcore::model::input::Input;
+use flowcore::model::lib_manifest::ImplementationLocator::Native;
+use flowcore::model::lib_manifest::LibraryManifest;
+use flowcore::model::manifest_format::ManifestFormat;
+use flowcore::model::metadata::MetaData;
+use flowcore::model::metrics::Metrics;
+use flowcore::model::output_connection::OutputConnection;
+use flowcore::model::process::Process::FlowProcess;
+use flowcore::model::runtime_function::RuntimeFunction;
+use flowcore::model::source_location::SourceLocation;
+use flowcore::model::submission::Submission;
+use flowcore::provider::Provider;
+use flowrclib::compiler::{compile, parser};
+use flowrclib::generator::generate;
+use flowrlib::block::Block;
+use flowrlib::coordinator::Coordinator;
+use flowrlib::debug_command::DebugCommand;
+use flowrlib::debugger_handler::DebuggerHandler;
+use flowrlib::dispatcher::Dispatcher;
+use flowrlib::executor::Executor;
+use flowrlib::job::Job;
+use flowrlib::run_state::{RunState, State};
+use flowrlib::submission_handler::SubmissionHandler;
+
+/// The output captured from running a flow in-process
+#[derive(Debug, Default)]
+pub struct CapturedOutput {
+    /// Everything the flow wrote to `stdout`
+    pub stdout: String,
+    /// Everything the flow wrote to `stderr`
+    pub stderr: String,
+    /// The contents of files written by the flow, keyed by the file name used by the flow
+    pub files: HashMap<String, Vec<u8>>,
+    /// The number of jobs executed while running the flow
+    pub jobs_executed: usize,
+}
+
+/// The state of the programmatic client the context functions interact with
+struct TestClient {
+    args: Vec<String>,
+    stdin: Cursor<Vec<u8>>,
+    output: CapturedOutput,
+    image_buffers: HashMap<String, RgbImage>,
+}
+
+impl TestClient {
+    // Encode any image buffers written to as PNG files in the captured output
+    fn flush_image_buffers(&mut self) -> Result<()> {
+        for (filename, image_buffer) in self.image_buffers.drain() {
+            let mut bytes = Cursor::new(Vec::new());
+            image_buffer.write_to(&mut bytes, ImageFormat::Png)
+                .map_err(|e| format!("Could not encode ImageBuffer '{filename}': {e}"))?;
+            self.output.files.insert(filename, bytes.into_inner());
+        }
+        Ok(())
+    }
+}
+
+type Client = Arc<Mutex<TestClient>>;
+
+// How long to wait for the execution of a flow that did not complete in time to stop, after it
+// has been cancelled
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Compile the flow in `sample_dir` in-process, writing the manifest into `output_dir`
+/// and returning the path to it.
+///
+/// # Errors
+///
+/// Returns an error if the flow cannot be parsed or compiled, or the manifest cannot be written
+pub fn compile_example(sample_dir: &Path, output_dir: &Path, provider: &dyn Provider)
+    -> Result<PathBuf> {
+    let sample_dir = sample_dir.canonicalize()?;
+    let source_url = Url::from_directory_path(&sample_dir)
+        .map_err(|()| "Could not create Url for example directory")?;
+
+    match parser::parse(&source_url, provider)
+        .map_err(|e| format!("Could not parse the flow '{source_url}': {e}"))? {
+        FlowProcess(flow) => {
+            let mut source_urls = BTreeMap::<String, Url>::new();
+            let tables = compile::compile(&flow, output_dir, false, true, &mut source_urls)
+                .map_err(|e| format!("Could not compile the flow '{source_url}': {e}"))?;
+            generate::write_flow_manifest(&flow, true, output_dir, &tables, source_urls,
+                                          ManifestFormat::default())
+                .map_err(|e| format!("Could not write manifest: {e}").into())
+        }
+        _ => bail!("Process parsed from '{}' was not a flow", sample_dir.display()),
+    }
+}
+
+/// Compile then run the example flow in `sample_dir` in-process, passing it `flow_args` and
+/// supplying `stdin` as its standard input, capturing all its output.
+///
+/// The manifest is written into `output_dir` and the sample directory is not modified, so
+/// that examples can be run concurrently. Relative paths of files read by the flow are
+/// relative to `sample_dir`.
+///
+/// If `native` is true then the native version of `flowstdlib` linked into this crate is used,
+/// otherwise the WASM version is loaded from the library search path.
+///
+/// # Errors
+///
+/// Returns an error if the flow cannot be compiled, an error occurs executing it, or it does
+/// not complete within `timeout`, in which case the error includes the output captured so far
+pub fn run_example(sample_dir: &Path, output_dir: &Path, flow_args: Vec<String>, stdin: Vec<u8>,
+                   native: bool, timeout: Duration) -> Result<CapturedOutput> {
+    CompiledFlow::compile(sample_dir, output_dir)?.run(flow_args, stdin, native, timeout)
+}
+
+/// A flow that has been compiled in-process and can be run in-process, as many times as required
+pub struct CompiledFlow {
+    provider: Arc<dyn Provider>,
+    manifest: FlowManifest,
+    manifest_url: Url,
+    working_dir: PathBuf,
+}
+
+impl CompiledFlow {
+    /// Compile the flow in `sample_dir`, writing the manifest into `output_dir`. Relative paths
+    /// of files read by the flow when run are relative to `sample_dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flow cannot be compiled or the manifest generated loaded
+    pub fn compile(sample_dir: &Path, output_dir: &Path) -> Result<Self> {
+        let provider = Arc::new(MetaProvider::new(lib_search_path()?, context_root()))
+            as Arc<dyn Provider>;
+
+        let working_dir = sample_dir.canonicalize()?;
+        let manifest_path = compile_example(&working_dir, output_dir, &*provider)?;
+        let manifest_url = Url::from_file_path(&manifest_path)
+            .map_err(|()| "Could not create Url for manifest")?;
+        let (manifest, _) = FlowManifest::load(&*provider, &manifest_url)?;
+
+        Ok(CompiledFlow {
+            provider,
+            manifest,
+            manifest_url,
+            working_dir,
+        })
+    }
+
+    /// Run the flow, passing it `flow_args` and supplying `stdin` as its standard input,
+    /// capturing all its output.
+    ///
+    /// If `native` is true then the native version of `flowstdlib` linked into this crate is
+    /// used, otherwise the WASM version is loaded from the library search path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an error occurs executing it, or it does not complete within
+    /// `timeout`, in which case the error includes the output captured so far
+    pub fn run(&self, flow_args: Vec<String>, stdin: Vec<u8>, native: bool, timeout: Duration)
+        -> Result<CapturedOutput> {
+        let mut args = vec![self.manifest_url.to_string()];
+        args.extend(flow_args);
+        let client = Arc::new(Mutex::new(TestClient {
+            args,
+            stdin: Cursor::new(stdin),
+            output: CapturedOutput::default(),
+            image_buffers: HashMap::new(),
+        }));
+
+        // execute on another thread so that a flow that does not complete in time can be stopped
+        let (result_sender, result_receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let execution_stop = stop.clone();
+        let execution_client = client.clone();
+        let manifest = self.manifest.clone();
+        let provider = self.provider.clone();
+        let working_dir = self.working_dir.clone();
+        let execution = thread::spawn(move || {
+            let _ = result_sender.send(execute(manifest, &provider, &execution_client,
+                                               &working_dir, native, &execution_stop));
+        });
+
+        let result = result_receiver.recv_timeout(timeout);
+        if result.is_err() {
+            // cancel the flow and stop the executors, waiting for them unless a job never ends
+            stop.store(true, Ordering::SeqCst);
+            if result_receiver.recv_timeout(STOP_TIMEOUT).is_ok() {
+                let _ = execution.join();
+            }
+        } else {
+            let _ = execution.join();
+        }
+
+        let mut test_client = client.lock().map_err(|_| "Could not lock client")?;
+        match result {
+            Ok(execution_result) => test_client.output.jobs_executed = execution_result?,
+            Err(_) => bail!("Flow did not complete within {}s\nSTDOUT:\n{}\nSTDERR:\n{}",
+                timeout.as_secs(), test_client.output.stdout, test_client.output.stderr),
+        }
+        test_client.flush_image_buffers()?;
+        Ok(std::mem::take(&mut test_client.output))
+    }
+}
+
+// Execute the flow described by `manifest` using a `Coordinator` and `Executors` in this process,
+// returning the number of jobs executed. Execution is cancelled if `stop` is set, and the
+// executors are stopped and waited for when it ends
+fn execute(manifest: FlowManifest, provider: &Arc<dyn Provider>, client: &Client,
+           working_dir: &Path, native: bool, stop: &Arc<AtomicBool>) -> Result<usize> {
+    let (dispatcher, ports) = bind_dispatcher()?;
+    let job_source = format!("tcp://127.0.0.1:{}", ports.0);
+    let context_job_source = format!("tcp://127.0.0.1:{}", ports.1);
+    let results_sink = format!("tcp://127.0.0.1:{}", ports.2);
+    let control_socket = format!("tcp://127.0.0.1:{}", ports.3);
+    let native_job_source = format!("tcp://127.0.0.1:{}", ports.4);
+
+    let mut executor = Executor::new();
+    executor.set_shutdown_flag(stop.clone());
+    if native {
+        executor.add_lib(
+            flowstdlib::manifest::get().chain_err(|| "Could not get 'native' flowstdlib manifest")?,
+            Url::parse("memory://")?,
+        )?;
+    }
+    executor.start(provider, 2, &job_source, &native_job_source, &results_sink,
+                   &control_socket);
+
+    let mut context_executor = Executor::new();
+    context_executor.set_shutdown_flag(stop.clone());
+    context_executor.add_lib(get_context_manifest(client, working_dir)?,
+                             Url::parse("memory://")?)?;
+    context_executor.start(provider, 1, &context_job_source, &native_job_source, &results_sink,
+                           &control_socket);
+
+    let mut submission_handler = TestSubmissionHandler {
+        jobs_created: 0,
+        cancel: stop.clone(),
+    };
+    let mut debug_handler = NoDebugHandler;
+    let result = Coordinator::new(dispatcher, &mut submission_handler, &mut debug_handler)
+        .execute_flow(Submission::new(manifest, None, None, None, None, false));
+
+    stop.store(true, Ordering::SeqCst);
+    executor.wait();
+    context_executor.wait();
+
+    result.map(|()| submission_handler.jobs_created)
+}
+
+// Number of sets of free ports to try binding a `Dispatcher` to
+const BIND_ATTEMPTS: usize = 10;
+
+// The ports of the lib job, context job, results, control and native job queues
+type JobQueuePorts = (u16, u16, u16, u16, u16);
+
+// Create a `Dispatcher` bound to a set of free ports, returning it and the ports. Another
+// example running concurrently may bind to a port between it being picked and bound, so
+// a new set of ports is picked if binding fails
+fn bind_dispatcher() -> Result<(Dispatcher, JobQueuePorts)> {
+    let mut attempts = 0;
+    loop {
+        let ports = (free_port()?, free_port()?, free_port()?, free_port()?, free_port()?);
+        match Dispatcher::new(&(
+            format!("tcp://*:{}", ports.0),
+            format!("tcp://*:{}", ports.1),
+            format!("tcp://*:{}", ports.2),
+            format!("tcp://*:{}", ports.3),
+            format!("tcp://*:{}", ports.4),
+        ), None) {
+            Ok(dispatcher) => return Ok((dispatcher, ports)),
+            Err(e) => {
+                attempts += 1;
+                if attempts == BIND_ATTEMPTS {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+fn free_port() -> Result<u16> {
+    pick_unused_port().ok_or_else(|| "No ports free".into())
+}
+
+// The context functions definitions are those of the `flowrcli` runner in the source tree, so that
+// the runner does not need to be installed
+fn context_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../src/bin/flowrcli/context")
+}
+
+// Build a library search path from `FLOW_LIB_PATH` plus a directory where `flowstdlib` resolves
+// to its definitions in the source tree, so that it does not need to be installed
+fn lib_search_path() -> Result<Simpath> {
+    let mut search_path = Simpath::new_with_separator("FLOW_LIB_PATH", ',');
+
+    let lib_dir = std::env::temp_dir().join("flow-utilities-libs");
+    let flowstdlib_link = lib_dir.join("flowstdlib");
+    if !flowstdlib_link.exists() {
+        fs::create_dir_all(&lib_dir)?;
+        let flowstdlib_src = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../flowstdlib/src").canonicalize()?;
+        #[cfg(unix)]
+        let _ = std::os::unix::fs::symlink(flowstdlib_src, &flowstdlib_link);
+    }
+    search_path.add(&lib_dir.to_string_lossy());
+
+    Ok(search_path)
+}
+
+/// A [SubmissionHandler] for a single flow execution started directly, that does not need to
+/// communicate with a client, that records the number of jobs created in the execution and
+/// cancels it when `cancel` is set
+struct TestSubmissionHandler {
+    jobs_created: usize,
+    cancel: Arc<AtomicBool>,
+}
+
+impl SubmissionHandler for TestSubmissionHandler {
+    fn flow_execution_starting(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn should_enter_debugger(&mut self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn should_cancel(&mut self) -> Result<bool> {
+        Ok(self.cancel.load(Ordering::SeqCst))
+    }
+
+    fn flow_execution_ended(&mut self, state: &RunState, _metrics: Metrics) -> Result<()> {
+        self.jobs_created = state.get_number_of_jobs_created();
+        Ok(())
+    }
+
+    fn wait_for_submission(&mut self) -> Result<Option<Submission>> {
+        Ok(None)
+    }
+
+    fn coordinator_is_exiting(&mut self, result: Result<()>) -> Result<()> {
+        result
+    }
+}
+
+/// A [DebuggerHandler] that ignores all debugger events, as flows are not debugged in-process
+struct NoDebugHandler;
+
+impl DebuggerHandler for NoDebugHandler {
+    fn start(&mut self) {}
+    fn job_breakpoint(&mut self, _job: &Job, _function: &RuntimeFunction, _states: Vec<State>) {}
+    fn block_breakpoint(&mut self, _block: &Block) {}
+    fn flow_unblock_breakpoint(&mut self, _flow_id: usize) {}
+    fn send_breakpoint(&mut self, _source_function_name: &str, _source_function_id: usize,
+                       _output_route: &str, _value: &Value, _destination_id: usize,
+                       _destination_name: &str, _io_name: &str, _input_number: usize,
+                       _source_location: Option<&SourceLocation>) {}
+    fn job_error(&mut self, _job: &Job, _source_location: Option<&SourceLocation>) {}
+    fn job_completed(&mut self, _job: &Job) {}
+    fn watch_value(&mut self, _job: &Job, _function_name: &str, _output_route: &str, _value: &Value) {}
+    fn blocks(&mut self, _blocks: Vec<Block>) {}
+    fn outputs(&mut self, _output: Vec<OutputConnection>) {}
+    fn input(&mut self, _input: Input) {}
+    fn function_list(&mut self, _functions: &[RuntimeFunction]) {}
+    fn function_states(&mut self, _function: RuntimeFunction, _function_states: Vec<State>) {}
+    fn run_state(&mut self, _run_state: &RunState) {}
+    fn message(&mut self, _message: String) {}
+    fn panic(&mut self, _state: &RunState, _error_message: String) {}
+    fn debugger_exiting(&mut self) {}
+    fn debugger_resetting(&mut self) {}
+    fn debugger_error(&mut self, _error: String) {}
+    fn execution_starting(&mut self) {}
+    fn execution_ended(&mut self) {}
+    fn get_command(&mut self, _state: &RunState) -> Result<DebugCommand> {
+        bail!("Debugging is not supported when running a flow in-process")
+    }
+}
+
+// Return a `LibraryManifest` for the context functions that interact with the `TestClient`
+fn get_context_manifest(client: &Client, working_dir: &Path) -> Result<LibraryManifest> {
+    let metadata = MetaData {
+        name: "context".into(),
+        version: "0.1.0".into(),
+        description: "context functions for running flows in-process".into(),
+        authors: vec!["Andrew Mackenzie".to_string()],
+    };
+    let mut manifest = LibraryManifest::new(Url::parse("context://")?, metadata);
+
+    let functions: Vec<(&str, Arc<dyn Implementation>)> = vec![
+        ("args/get", Arc::new(ArgsGet(client.clone()))),
+        ("file/file_write", Arc::new(FileWrite(client.clone()))),
+        ("file/file_read", Arc::new(FileRead(working_dir.to_path_buf()))),
+        ("image/image_buffer", Arc::new(ImageBuffer(client.clone()))),
+        ("stdio/readline", Arc::new(Readline(client.clone()))),
+        ("stdio/stdin", Arc::new(Stdin(client.clone()))),
+        ("stdio/stdout", Arc::new(Stdout(client.clone()))),
+        ("stdio/stderr", Arc::new(Stderr(client.clone()))),
+    ];
+
+    for (path, implementation) in functions {
+        manifest.locators.insert(Url::parse(&format!("context://{path}"))?, Native(implementation));
+    }
+
+    Ok(manifest)
+}
+
+fn lock(client: &Client) -> Result<std::sync::MutexGuard<'_, TestClient>> {
+    client.lock().map_err(|_| "Could not lock client".into())
+}
+
+// Convert a value to the text output for it, as done by the `flowrcli` client
+fn output_text(input: &Value) -> Option<String> {
+    match input {
+        Value::Null => None,
+        Value::String(string) => Some(string.to_string()),
+        _ => Some(input.to_string()),
+    }
+}
+
+// Form the output of the stdin and readline functions from a line of text read, or EOF
+fn text_output(text: Option<String>) -> (Option<Value>, RunAgain) {
+    let mut output_map = serde_json::Map::new();
+    if let Some(contents) = text {
+        if let Ok(value) = serde_json::from_str(&contents) {
+            output_map.insert("json".into(), value);
+        }
+        output_map.insert("string".into(), Value::String(contents));
+        (Some(Value::Object(output_map)), RUN_AGAIN)
+    } else {
+        output_map.insert("string".into(), Value::Null);
+        output_map.insert("json".into(), Value::Null);
+        (Some(Value::Object(output_map)), DONT_RUN_AGAIN)
+    }
+}
+
+struct ArgsGet(Client);
+
+impl Implementation for ArgsGet {
+    fn run(&self, _inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let args = lock(&self.0)?.args.clone();
+        let json_args: Vec<Value> = args.iter()
+            .map(|arg| serde_json::from_str(arg).unwrap_or_else(|_| Value::String(arg.into())))
+            .collect();
+        Ok((Some(json!({"json": json_args, "string": args})), DONT_RUN_AGAIN))
+    }
+}
+
+struct Stdout(Client);
+
+impl Implementation for Stdout {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let input = inputs.first().ok_or("Could not get input")?;
+        if let Some(text) = output_text(input) {
+            let mut client = lock(&self.0)?;
+            client.output.stdout.push_str(&text);
+            client.output.stdout.push('\n');
+        }
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+struct Stderr(Client);
+
+impl Implementation for Stderr {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let input = inputs.first().ok_or("Could not get input")?;
+        if let Some(text) = output_text(input) {
+            let mut client = lock(&self.0)?;
+            client.output.stderr.push_str(&text);
+            client.output.stderr.push('\n');
+        }
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+struct Stdin(Client);
+
+impl Implementation for Stdin {
+    fn run(&self, _inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let mut buffer = String::new();
+        let size = lock(&self.0)?.stdin.read_to_string(&mut buffer)?;
+        Ok(text_output((size > 0).then(|| buffer.trim().to_string())))
+    }
+}
+
+struct Readline(Client);
+
+impl Implementation for Readline {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let mut client = lock(&self.0)?;
+        if let Some(Value::String(prompt)) = inputs.first() {
+            client.output.stdout.push_str(prompt);
+        }
+        let mut line = String::new();
+        let size = client.stdin.read_line(&mut line)?;
+        Ok(text_output((size > 0).then(|| line.trim().to_string())))
+    }
+}
+
+struct FileWrite(Client);
+
+impl Implementation for FileWrite {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let filename = inputs.first().ok_or("Could not get filename")?;
+        let bytes = inputs.get(1).ok_or("Could not get bytes")?
+            .as_array().ok_or("Could not get bytes")?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let bytes = bytes.iter()
+            .map(|byte_value| byte_value.as_u64().unwrap_or(0) as u8)
+            .collect();
+        lock(&self.0)?.output.files.insert(filename.as_str().unwrap_or("").to_string(), bytes);
+
+        Ok((None, RUN_AGAIN))
+    }
+}
+
+// Reading files is done from the file system, relative paths being relative to the directory
+struct FileRead(PathBuf);
+
+impl Implementation for FileRead {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let path = inputs.first().ok_or("Could not get path")?;
+        match fs::read(self.0.join(path.as_str().unwrap_or(""))) {
+            Ok(bytes) => {
+                let string = String::from_utf8(bytes.clone())
+                    .map_err(|_| "Could not create Utf8 String")?;
+                Ok((Some(json!({"bytes": bytes, "string": string})), RUN_AGAIN))
+            }
+            Err(_) => Ok((None, DONT_RUN_AGAIN)),
+        }
+    }
+}
+
+struct ImageBuffer(Client);
+
+impl Implementation for ImageBuffer {
+    fn run(&self, inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+        let number = |index: usize, element: usize, name: &str| -> Result<u64> {
+            inputs.get(index).and_then(Value::as_array)
+                .and_then(|array| array.get(element)).and_then(Value::as_u64)
+                .ok_or_else(|| format!("Could not get {name}").into())
+        };
+        let filename = inputs.get(3).and_then(Value::as_str).ok_or("Could not get filename")?;
+
+        let (x, y) = (u32::try_from(number(0, 0, "x")?)?, u32::try_from(number(0, 1, "y")?)?);
+        let pixel = Rgb([u8::try_from(number(1, 0, "r")?)?, u8::try_from(number(1, 1, "g")?)?,
+            u8::try_from(number(1, 2, "b")?)?]);
+        let (width, height) = (u32::try_from(number(2, 0, "w")?)?,
+                               u32::try_from(number(2, 1, "h")?)?);
+
+        let mut client = lock(&self.0)?;
+        let image = client.image_buffers.entry(filename.to_string())
+            .or_insert_with(|| RgbImage::new(width, height));
+        let (image_width, image_height) = image.dimensions();
+        *image.get_pixel_mut_checked(x, y).ok_or_else(|| format!(
+            "Pixel ({x}, {y}) is outside the {image_width}x{image_height} image '{filename}'"))?
+            = pixel;
+
+        Ok((None, RUN_AGAIN))
+    }
+}
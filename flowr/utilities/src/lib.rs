@@ -1,10 +1,24 @@
 use std::{env, fs, io};
 use std::fmt::Write;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write as IoWrite};
 use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{self, Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+/// Compile and run example flows within the calling process, capturing their output
+pub mod in_process;
+
+/// Benchmark the execution of flows run in-process
+pub mod bench;
+
+/// Run example flows distributed across a coordinator and a number of `flowrex` workers
+pub mod distributed;
 
 /// Name of file where any Stdout will be written while executing an example
 const TEST_STDOUT_FILENAME: &str = "test.stdout";
@@ -12,6 +26,13 @@ const TEST_STDOUT_FILENAME: &str = "test.stdout";
 /// Name of file where the Stdout is defined
 const EXPECTED_STDOUT_FILENAME: &str = "expected.stdout";
 
+/// Name of file where the Stdout is defined as a set of regular expressions, one per line
+const EXPECTED_STDOUT_REGEX_FILENAME: &str = "expected.stdout.regex";
+
+/// A first line in an [`EXPECTED_STDOUT_REGEX_FILENAME`] file that allows lines of output to
+/// match the patterns in any order
+const UNORDERED_DIRECTIVE: &str = "# unordered";
+
 /// Name of file where any Stdin will be read from while executing am example
 const TEST_STDIN_FILENAME: &str = "test.stdin";
 
@@ -27,25 +48,55 @@ const EXPECTED_FILE_FILENAME: &str = "expected.file";
 /// Name of file where flow arguments for a flow example test are read from
 const TEST_ARGS_FILENAME: &str = "test.args";
 
-/// Run one specific flow example
-pub fn run_example(source_file: &str, runner: &str, flowrex: bool, native: bool) {
+/// Name of file where the timeout (in seconds) for running an example can be specified
+const TEST_TIMEOUT_FILENAME: &str = "test.timeout";
+
+/// Maximum time an example is allowed to run for if no timeout is specified for it
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Interval between checks if a process running an example has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Name of the environment variable that when set to "1" causes the expected output files of an
+/// example to be overwritten with its actual output, instead of the actual output being checked
+const UPDATE_EXPECTED_ENV: &str = "UPDATE_EXPECTED";
+
+/// Count of output directories created by this process, used to make each one unique
+static OUTPUT_DIR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Create a new, empty, directory for the output of one run of the example in `sample_dir`
+/// using `runner`. Each run gets its own directory so that examples (including the same example
+/// with different runners) can be run concurrently without overwriting each other's output.
+pub fn create_output_dir(sample_dir: &Path, runner: &str) -> PathBuf {
+    let example_name = sample_dir.file_name().unwrap_or_default().to_string_lossy();
+    let output_dir = env::temp_dir().join("flow-examples").join(format!("{example_name}-{runner}-{}-{}",
+        process::id(), OUTPUT_DIR_COUNT.fetch_add(1, Ordering::SeqCst)));
+    let _ = fs::remove_dir_all(&output_dir);
+    fs::create_dir_all(&output_dir).expect("Could not create output directory");
+    output_dir
+}
+
+/// Run one specific flow example, killing the runner (and `flowrex` if used) and failing
+/// with the output produced so far if it does not complete within the example's timeout.
+///
+/// The flow is compiled into, and run in, a new output directory which is returned
+pub fn run_example(source_file: &str, runner: &str, flowrex: bool, native: bool) -> PathBuf {
     let mut sample_dir = PathBuf::from(source_file);
     sample_dir.pop();
 
-    compile_example(&sample_dir, runner);
+    let output_dir = create_output_dir(&sample_dir, runner);
+    compile_example(&sample_dir, runner, &output_dir);
 
     println!("\n\tRunning example: {}", sample_dir.display());
     println!("\t\tRunner: {}", runner);
     println!("\t\tSTDIN is read from {TEST_STDIN_FILENAME}");
     println!("\t\tArguments are read from {TEST_ARGS_FILENAME}");
+    println!("\t\tOutput is saved in {}", output_dir.display());
     println!("\t\tSTDOUT is saved in {TEST_STDOUT_FILENAME}");
     println!("\t\tSTDERR is saved in {TEST_STDERR_FILENAME}");
     println!("\t\tFile output is saved in {TEST_FILE_FILENAME}");
-
-    // Remove any previous output
-    let _ = fs::remove_file(sample_dir.join(TEST_STDERR_FILENAME));
-    let _ = fs::remove_file(sample_dir.join(TEST_FILE_FILENAME));
-    let _ = fs::remove_file(sample_dir.join(TEST_STDOUT_FILENAME));
+    let timeout = timeout(&sample_dir);
+    println!("\t\tTimeout is {}s", timeout.as_secs());
 
     let mut runner_args: Vec<String> = if native {
         vec!["--native".into()]
@@ -66,49 +117,139 @@ pub fn run_example(source_file: &str, runner: &str, flowrex: bool, native: bool)
         None
     };
 
+    let completed = spawn_runner(&sample_dir, &output_dir, runner, runner_args, timeout);
+
+    // If flowrex was started - then kill it
+    if let Some(mut child) = flowrex_child {
+        println!("Killing 'flowrex'");
+        child.kill().expect("Failed to kill server child process");
+        child.wait().expect("Failed to wait for child to exit");
+    }
+
+    if !completed {
+        panic!("Example '{}' did not complete within {}s\nSTDOUT:\n{}\nSTDERR:\n{}",
+               sample_dir.display(), timeout.as_secs(),
+               read_file(&output_dir, TEST_STDOUT_FILENAME),
+               read_file(&output_dir, TEST_STDERR_FILENAME));
+    }
+
+    output_dir
+}
+
+/// Wait for `child` to exit, returning true if it did. If it has not exited within `timeout`
+/// then kill it and return false.
+fn wait_or_kill(child: &mut Child, timeout: Duration) -> bool {
+    let start = Instant::now();
+    loop {
+        if child.try_wait().expect("Could not get status of child process").is_some() {
+            return true;
+        }
+
+        if start.elapsed() > timeout {
+            println!("Killing process that did not complete within {}s", timeout.as_secs());
+            let _ = child.kill();
+            let _ = child.wait();
+            return false;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Spawn `runner` with `runner_args`, to run the flow compiled into `output_dir` with the args
+/// and stdin of the example in `sample_dir`, saving its output in `output_dir`. Return true if
+/// it completed within `timeout`, otherwise kill it and return false.
+fn spawn_runner(sample_dir: &Path, output_dir: &Path, runner: &str, mut runner_args: Vec<String>,
+                timeout: Duration) -> bool {
     runner_args.push("manifest.json".into());
-    runner_args.append(&mut args(&sample_dir).expect("Could not get flow args"));
+    runner_args.append(&mut args(sample_dir).expect("Could not get flow args"));
 
-    let output = File::create(sample_dir.join(TEST_STDOUT_FILENAME))
+    let output = File::create(output_dir.join(TEST_STDOUT_FILENAME))
         .expect("Could not create Test StdOutput File");
-    let error = File::create(sample_dir.join(TEST_STDERR_FILENAME))
+    let error = File::create(output_dir.join(TEST_STDERR_FILENAME))
         .expect("Could not create Test StdError File ");
 
     println!("\tCommand line: '{} {}'", runner, runner_args.join(" "));
     let mut runner_child = Command::new(runner)
         .args(runner_args)
-        .current_dir(sample_dir.canonicalize().expect("Could not canonicalize path"))
+        .current_dir(output_dir)
         .stdin(Stdio::piped())
         .stdout(Stdio::from(output))
         .stderr(Stdio::from(error))
         .spawn().expect("Could not spawn runner");
 
+    // Write the contents of the stdin file (if any) to the runner's STDIN from another thread
     let stdin_file = sample_dir.join(TEST_STDIN_FILENAME);
-    if stdin_file.exists() {
-        let _ = Command::new("cat")
-            .args(vec![stdin_file])
-            .stdout(runner_child.stdin.take().expect("Could not take STDIN"))
-            .spawn();
+    let mut runner_stdin = runner_child.stdin.take().expect("Could not take STDIN");
+    let stdin_contents = fs::read(stdin_file).unwrap_or_default();
+    thread::spawn(move || {
+        let _ = runner_stdin.write_all(&stdin_contents);
+    });
+
+    wait_or_kill(&mut runner_child, timeout)
+}
+
+/// Read the timeout for running an example from its timeout file if it exists,
+/// otherwise use [DEFAULT_TIMEOUT]
+fn timeout(sample_dir: &Path) -> Duration {
+    match fs::read_to_string(sample_dir.join(TEST_TIMEOUT_FILENAME)) {
+        Ok(contents) => Duration::from_secs(contents.trim().parse()
+            .expect("Could not parse timeout in seconds from timeout file")),
+        Err(_) => DEFAULT_TIMEOUT,
     }
+}
 
-    runner_child.wait_with_output().expect("Could not get sub process output");
+/// Run one specific flow example in-process, using the [in_process] harness, saving the output
+/// captured in a new output directory (which is returned) in the same files that `run_example`
+/// would produce
+pub fn run_example_in_process(source_file: &str, native: bool) -> PathBuf {
+    let mut sample_dir = PathBuf::from(source_file);
+    sample_dir.pop();
 
-    // If flowrex was started - then kill it
-    if let Some(mut child) = flowrex_child {
-        println!("Killing 'flowrex'");
-        child.kill().expect("Failed to kill server child process");
-        child.wait().expect("Failed to wait for child to exit");
+    let output_dir = create_output_dir(&sample_dir, "in-process");
+    println!("\n\tRunning example in-process: {}", sample_dir.display());
+    println!("\t\tOutput is saved in {}", output_dir.display());
+
+    let flow_args = args(&sample_dir).expect("Could not get flow args");
+    let stdin = fs::read(sample_dir.join(TEST_STDIN_FILENAME)).unwrap_or_default();
+    let output = in_process::run_example(&sample_dir, &output_dir, flow_args, stdin, native,
+                                         timeout(&sample_dir))
+        .unwrap_or_else(|e| panic!("Error running example '{}': {e}", sample_dir.display()));
+
+    fs::write(output_dir.join(TEST_STDOUT_FILENAME), output.stdout)
+        .expect("Could not write Test StdOutput File");
+    fs::write(output_dir.join(TEST_STDERR_FILENAME), output.stderr)
+        .expect("Could not write Test StdError File");
+    if let Some(contents) = output.files.get(TEST_FILE_FILENAME) {
+        fs::write(output_dir.join(TEST_FILE_FILENAME), contents)
+            .expect("Could not write Test File output");
     }
+
+    output_dir
 }
 
 /// Run an example and check the output matches the expected
+///
+/// Examples run with `flowrcli` and without `flowrex` are run in-process, without needing
+/// `flowc` or `flowrcli` binaries to be installed. Others are run by spawning the runner.
 pub fn test_example(source_file: &str, runner: &str, flowrex: bool, native: bool) {
-    let _ = env::set_current_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .parent().expect("Could not cd into flowr directory")
-        .parent().expect("Could not cd into flow directory"));
+    let source_file = &workspace_path(source_file);
+    let output_dir = if runner == "flowrcli" && !flowrex {
+        run_example_in_process(source_file, native)
+    } else {
+        run_example(source_file, runner, flowrex, native)
+    };
+    check_test_output(source_file, &output_dir);
+}
 
-    run_example(source_file, runner, flowrex, native);
-    check_test_output(source_file);
+// Get the full path of an example source file, given by `file!()` relative to the root of the
+// workspace. The current directory is not changed to the root instead, as that would affect other
+// tests running concurrently in the same process
+fn workspace_path(source_file: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent().expect("Could not get flowr directory")
+        .parent().expect("Could not get flow directory")
+        .join(source_file).to_string_lossy().into_owned()
 }
 
 /// Read the flow args from a file and return them as a Vector of Strings that will be passed in
@@ -129,9 +270,11 @@ fn args(sample_dir: &Path) -> io::Result<Vec<String>> {
     Ok(args)
 }
 
-/// Compile a flow example in-place in the `sample_dir` directory using flowc
-pub fn compile_example(sample_path: &Path, runner: &str) {
+/// Compile a flow example in the `sample_dir` directory using flowc, writing the manifest
+/// into `output_dir`
+pub fn compile_example(sample_path: &Path, runner: &str, output_dir: &Path) {
     let sample_dir = sample_path.to_string_lossy();
+    let output_dir = output_dir.to_string_lossy();
 
     let mut command = Command::new("flowc");
     // -d for debug symbols
@@ -139,8 +282,9 @@ pub fn compile_example(sample_path: &Path, runner: &str) {
     // -c to skip running and only compile the flow
     // -O to optimize the WASM files generated
     // -r <runner> to specify the runner to use
+    // -o <output_dir> to specify where to write the manifest
     // <sample_dir> is the path to the directory of the sample flow to compile
-    let command_args = vec!["-d", "-g", "-c", "-O", "-r", runner, &sample_dir];
+    let command_args = vec!["-d", "-g", "-c", "-O", "-r", runner, "-o", &output_dir, &sample_dir];
 
     let stat = command
         .args(&command_args)
@@ -151,11 +295,16 @@ pub fn compile_example(sample_path: &Path, runner: &str) {
     }
 }
 
-pub fn check_test_output(source_file: &str) {
+/// Check the output of running an example, saved in `output_dir`, matches that expected.
+///
+/// If the `UPDATE_EXPECTED` environment variable is set to "1" then the expected output files
+/// are first updated with the actual output. Output to STDERR always causes a failure, and the
+/// `expected.stdout` file is not updated if the expected output is defined by regex patterns.
+pub fn check_test_output(source_file: &str, output_dir: &Path) {
     let mut sample_dir = PathBuf::from(source_file);
     sample_dir.pop();
 
-    let error_output = sample_dir.join(TEST_STDERR_FILENAME);
+    let error_output = output_dir.join(TEST_STDERR_FILENAME);
     if error_output.exists() {
         let contents = fs::read_to_string(&error_output).expect("Could not read from {STDERR_FILENAME} file");
 
@@ -167,29 +316,130 @@ pub fn check_test_output(source_file: &str) {
         }
     }
 
-    compare_and_fail(sample_dir.join(EXPECTED_STDOUT_FILENAME), sample_dir.join(TEST_STDOUT_FILENAME));
-    compare_and_fail(sample_dir.join(EXPECTED_FILE_FILENAME), sample_dir.join(TEST_FILE_FILENAME));
+    if update_expected() {
+        if !sample_dir.join(EXPECTED_STDOUT_REGEX_FILENAME).exists() {
+            update_expected_file(sample_dir.join(EXPECTED_STDOUT_FILENAME),
+                                 output_dir.join(TEST_STDOUT_FILENAME));
+        }
+        update_expected_file(sample_dir.join(EXPECTED_FILE_FILENAME),
+                             output_dir.join(TEST_FILE_FILENAME));
+    }
+
+    compare_and_fail(sample_dir.join(EXPECTED_STDOUT_FILENAME), output_dir.join(TEST_STDOUT_FILENAME));
+    match_and_fail(sample_dir.join(EXPECTED_STDOUT_REGEX_FILENAME), output_dir.join(TEST_STDOUT_FILENAME));
+    compare_and_fail(sample_dir.join(EXPECTED_FILE_FILENAME), output_dir.join(TEST_FILE_FILENAME));
+}
+
+/// Return true if expected output files should be updated with the actual output
+fn update_expected() -> bool {
+    env::var(UPDATE_EXPECTED_ENV).is_ok_and(|value| value == "1")
+}
+
+/// Overwrite the expected output file with the actual output. If there is no actual output then
+/// remove the expected file, and don't create an expected file for empty output.
+fn update_expected_file(expected_path: PathBuf, actual_path: PathBuf) {
+    match fs::read(&actual_path) {
+        Ok(actual) if !actual.is_empty() || expected_path.exists() => {
+            fs::write(&expected_path, actual).expect("Could not update expected file");
+            println!("Updated '{}'", expected_path.display());
+        }
+        Ok(_) => {}
+        Err(_) => if expected_path.exists() {
+            fs::remove_file(&expected_path).expect("Could not remove expected file");
+            println!("Removed '{}'", expected_path.display());
+        }
+    }
+}
+
+/// Check each line of the actual output matches the corresponding regular expression (one per
+/// line) in the expected file. Each pattern must match the whole line.
+///
+/// If the first line of the expected file is [`UNORDERED_DIRECTIVE`] then the lines of output
+/// may appear in any order, but each pattern must match a different line of output.
+fn match_and_fail(expected_path: PathBuf, actual_path: PathBuf) {
+    if expected_path.exists() {
+        let expected = fs::read_to_string(&expected_path).expect("Could not read expected file");
+        let actual = fs::read_to_string(&actual_path).unwrap_or_default();
+        if let Err(message) = match_lines(&expected, &actual) {
+            println!("Expected patterns:\n{expected}");
+            println!("Actual:\n{actual}");
+            panic!("Contents of '{}' don't match the patterns in '{}': {message}",
+                   actual_path.display(), expected_path.display());
+        }
+    }
+}
+
+fn match_lines(expected: &str, actual: &str) -> Result<(), String> {
+    let mut pattern_lines = expected.lines().peekable();
+    let unordered = pattern_lines.next_if(|line| line.trim() == UNORDERED_DIRECTIVE).is_some();
+    let patterns = pattern_lines
+        .map(|line| Regex::new(&format!("^(?:{line})$"))
+            .map_err(|e| format!("Invalid pattern '{line}': {e}")))
+        .collect::<Result<Vec<Regex>, String>>()?;
+    let lines: Vec<&str> = actual.lines().collect();
+
+    if patterns.len() != lines.len() {
+        return Err(format!("Expected {} lines of output but found {}", patterns.len(), lines.len()));
+    }
+
+    if unordered {
+        // assign each pattern a distinct line, re-assigning earlier choices where needed
+        let mut line_owner: Vec<Option<usize>> = vec![None; lines.len()];
+        for pattern in 0..patterns.len() {
+            let mut visited = vec![false; lines.len()];
+            if !assign(pattern, &patterns, &lines, &mut line_owner, &mut visited) {
+                return Err(format!("No unmatched line of output matches pattern '{}'",
+                                   patterns[pattern].as_str()));
+            }
+        }
+    } else {
+        for (number, (pattern, line)) in patterns.iter().zip(&lines).enumerate() {
+            if !pattern.is_match(line) {
+                return Err(format!("Line {} '{line}' doesn't match pattern '{}'",
+                                   number + 1, pattern.as_str()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Try to find a line for `pattern`, taking a line from another pattern if that pattern can
+// be assigned a different line
+fn assign(pattern: usize, patterns: &[Regex], lines: &[&str], line_owner: &mut [Option<usize>],
+          visited: &mut [bool]) -> bool {
+    for (index, line) in lines.iter().enumerate() {
+        if !visited[index] && patterns[pattern].is_match(line) {
+            visited[index] = true;
+            let available = match line_owner[index] {
+                None => true,
+                Some(other) => assign(other, patterns, lines, line_owner, visited),
+            };
+            if available {
+                line_owner[index] = Some(pattern);
+                return true;
+            }
+        }
+    }
+    false
 }
 
 fn compare_and_fail(expected_path: PathBuf, actual_path: PathBuf) {
     if expected_path.exists() {
-        let diff = Command::new("diff")
-            .args(vec![&expected_path, &actual_path])
-            .stdin(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .spawn()
-            .expect("Could not get child process");
-        let output = diff.wait_with_output().expect("Could not get diff output");
-        if output.status.success() {
+        let expected = fs::read(&expected_path).expect("Could not read expected file");
+        let actual = fs::read(&actual_path).unwrap_or_default();
+        if expected == actual {
             return;
         }
+        println!("Expected:\n{}", String::from_utf8_lossy(&expected));
+        println!("Actual:\n{}", String::from_utf8_lossy(&actual));
         panic!("Contents of '{}' doesn't match the expected contents in '{}'",
                actual_path.display(), expected_path.display());
     }
 }
 
-/// Execute a flow using separate server (coordinator) and client
+/// Execute a flow using separate server (coordinator) and client, failing with the output
+/// produced so far if it does not complete within the example's timeout
 pub fn execute_flow_client_server(example_name: &str, manifest: PathBuf) {
     let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     let root_dir = crate_dir.parent().expect("Could not go to parent flowr dir");
@@ -231,32 +481,35 @@ pub fn execute_flow_client_server(example_name: &str, manifest: PathBuf) {
         .spawn()
         .expect("Could not spawn flowrcli process");
 
-    // read it's stderr - don't fail, to ensure we kill the server
-    let mut actual_stderr = String::new();
-    if let Some(ref mut stderr) = runner.stderr {
-        for line in BufReader::new(stderr).lines() {
-            let _ = writeln!(actual_stderr, "{}", &line.expect("Could not read line"));
-        }
-    }
+    // read it's stderr and stdout from other threads - don't fail, to ensure we kill the server
+    let stderr_reader = read_lines(runner.stderr.take());
+    let stdout_reader = read_lines(runner.stdout.take());
 
-    // read it's stdout - don't fail, to ensure we kill the server
-    let mut actual_stdout = String::new();
-    if let Some(ref mut stdout) = runner.stdout {
-        for line in BufReader::new(stdout).lines() {
-            let _ = writeln!(actual_stdout, "{}", &line.expect("Could not read line"));
-        }
-    }
+    let timeout = timeout(&samples_dir);
+    let completed = wait_or_kill(&mut runner, timeout);
+    let actual_stderr = stderr_reader.join().unwrap_or_default();
+    let actual_stdout = stdout_reader.join().unwrap_or_default();
 
     println!("Killing 'flowr' server");
     server.kill().expect("Failed to kill server child process");
     server.wait().expect("Failed to wait for child to exit");
 
+    if !completed {
+        panic!("Example '{example_name}' did not complete within {}s\nSTDOUT:\n{actual_stdout}\n\
+                STDERR:\n{actual_stderr}", timeout.as_secs());
+    }
+
     if !actual_stderr.is_empty() {
         eprintln!("STDERR: {actual_stderr}");
         panic!("Failed due to STDERR output")
     }
 
-    let expected_stdout = read_file(&samples_dir, "expected.stdout");
+    if update_expected() {
+        fs::write(samples_dir.join(EXPECTED_STDOUT_FILENAME), &actual_stdout)
+            .expect("Could not update expected file");
+    }
+
+    let expected_stdout = read_file(&samples_dir, EXPECTED_STDOUT_FILENAME);
     if expected_stdout != actual_stdout {
         println!("Expected STDOUT:\n{expected_stdout}");
         println!("Actual STDOUT:\n{actual_stdout}");
@@ -264,6 +517,19 @@ pub fn execute_flow_client_server(example_name: &str, manifest: PathBuf) {
     }
 }
 
+// Read all the lines from `source` on another thread, returning them when it is joined
+fn read_lines<R: Read + Send + 'static>(source: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut lines = String::new();
+        if let Some(source) = source {
+            for line in BufReader::new(source).lines().map_while(io::Result::ok) {
+                let _ = writeln!(lines, "{line}");
+            }
+        }
+        lines
+    })
+}
+
 fn read_file(test_dir: &Path, file_name: &str) -> String {
     let expected_file = test_dir.join(file_name);
     if !expected_file.exists() {
@@ -274,4 +540,50 @@ fn read_file(test_dir: &Path, file_name: &str) -> String {
     let mut buffer = Vec::new();
     f.read_to_end(&mut buffer).expect("Could not read from file");
     String::from_utf8(buffer).expect("Could not convert to String")
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::match_lines;
+
+    #[test]
+    fn ordered_lines_match() {
+        assert!(match_lines("hello\n[0-9]+\n", "hello\n42\n").is_ok());
+    }
+
+    #[test]
+    fn ordered_lines_out_of_order_fail() {
+        assert!(match_lines("hello\n[0-9]+\n", "42\nhello\n").is_err());
+    }
+
+    #[test]
+    fn pattern_must_match_whole_line() {
+        assert!(match_lines("[0-9]+\n", "42 and more\n").is_err());
+    }
+
+    #[test]
+    fn line_count_mismatch_fails() {
+        assert!(match_lines("a\nb\n", "a\n").is_err());
+    }
+
+    #[test]
+    fn unordered_lines_match() {
+        assert!(match_lines("# unordered\nfirst\nsecond\n", "second\nfirst\n").is_ok());
+    }
+
+    #[test]
+    fn unordered_lines_reassigned() {
+        // a greedy assignment of '.*' to 'b' would leave no line for 'b'
+        assert!(match_lines("# unordered\n.*\nb\n", "b\na\n").is_ok());
+    }
+
+    #[test]
+    fn unordered_each_line_used_once() {
+        assert!(match_lines("# unordered\na\na\n", "a\nb\n").is_err());
+    }
+
+    #[test]
+    fn invalid_pattern_fails() {
+        assert!(match_lines("(\n", "(\n").is_err());
+    }
+}
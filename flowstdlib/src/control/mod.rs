@@ -19,10 +19,18 @@ pub mod join;
 #[path = "tap/tap.rs"]
 pub mod tap;
 
+/// A function to limit the rate at which data flows
+#[path = "throttle/throttle.rs"]
+pub mod throttle;
+
 /// A function to route data based on a control value
 #[path = "route/route.rs"]
 pub mod route;
 
+/// A function to route data to one of a number of outputs, selected by a control value
+#[path = "switch/switch.rs"]
+pub mod switch;
+
 /// A function to select data on output on a control value
 #[path = "select/select.rs"]
 pub mod select;
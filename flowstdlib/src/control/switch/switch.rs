@@ -0,0 +1,72 @@
+use serde_json::Value;
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+// The number of `case` outputs that `data` can be routed to
+const NUMBER_OF_CASES: usize = 4;
+
+#[flow_function]
+fn inner_switch(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let data = inputs.first().ok_or("Could not get data")?;
+    let control = inputs.get(1).ok_or("Could not get control")?;
+    let cases = inputs.get(2).ok_or("Could not get cases")?.as_array()
+        .ok_or("Could not get array of cases")?;
+
+    if cases.len() > NUMBER_OF_CASES {
+        bail!("Cannot switch between {} cases, the maximum is {NUMBER_OF_CASES}", cases.len());
+    }
+
+    let output_route = match cases.iter().position(|case| case == control) {
+        Some(index) => format!("case{}", index + 1),
+        None => "default".into(),
+    };
+
+    let mut output_map = serde_json::Map::new();
+    output_map.insert(output_route, data.clone());
+
+    Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use flowcore::RUN_AGAIN;
+
+    use super::inner_switch;
+
+    #[test]
+    fn test_switch_case() {
+        let inputs = vec![json!(42), json!("error"), json!(["ok", "error"])];
+        let (output, run_again) = inner_switch(&inputs).expect("_switch() failed");
+        assert_eq!(run_again, RUN_AGAIN);
+
+        let value = output.expect("Could not get the Value from the output");
+        let map = value.as_object().expect("Could not get the object from the output");
+        assert_eq!(map.get("case2").expect("No 'case2' value in map"), &json!(42));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_switch_number() {
+        let inputs = vec![json!(42), json!(3), json!([1, 2, 3])];
+        let (output, _) = inner_switch(&inputs).expect("_switch() failed");
+        let value = output.expect("Could not get the Value from the output");
+        assert_eq!(value.pointer("/case3"), Some(&json!(42)));
+    }
+
+    #[test]
+    fn test_switch_default() {
+        let inputs = vec![json!(42), json!("unknown"), json!(["ok", "error"])];
+        let (output, _) = inner_switch(&inputs).expect("_switch() failed");
+        let value = output.expect("Could not get the Value from the output");
+        assert_eq!(value.pointer("/default"), Some(&json!(42)));
+    }
+
+    #[test]
+    fn test_switch_too_many_cases() {
+        assert!(inner_switch(&[json!(42), json!(1), json!([1, 2, 3, 4, 5])]).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_throttle(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let value = inputs.first().ok_or("Could not get value")?;
+    let now = inputs.get(1).ok_or("Could not get clock")?
+        .as_u64().ok_or("Could not get clock")?;
+    let limit = inputs.get(2).ok_or("Could not get limit")?
+        .as_u64().ok_or("Could not get limit")?;
+    let window = inputs.get(3).ok_or("Could not get window")?
+        .as_u64().ok_or("Could not get window")?;
+    let mode = inputs.get(4).ok_or("Could not get mode")?
+        .as_str().ok_or("Could not get mode")?;
+    let state = inputs.get(5).ok_or("Could not get state")?;
+
+    let mut window_start = state.get("window_start").and_then(Value::as_u64).unwrap_or(now);
+    let mut count = state.get("count").and_then(Value::as_u64).unwrap_or(0);
+    let mut buffer = state.get("buffer").and_then(Value::as_array).cloned().unwrap_or_default();
+    if now >= window_start.saturating_add(window) {
+        window_start = now;
+        count = 0;
+    }
+
+    let mut output_map = serde_json::Map::new();
+    match mode {
+        "buffer" => {
+            // a null value is only a tick of the clock, to release a buffered value
+            if !value.is_null() {
+                buffer.push(value.clone());
+            }
+            if count < limit && !buffer.is_empty() {
+                count += 1;
+                output_map.insert("value".into(), buffer.remove(0));
+            }
+        },
+        "drop" => {
+            if !value.is_null() {
+                if count < limit {
+                    count += 1;
+                    output_map.insert("value".into(), value.clone());
+                } else {
+                    output_map.insert("dropped".into(), value.clone());
+                }
+            }
+        },
+        _ => bail!("Unknown throttle mode '{}', expected \"buffer\" or \"drop\"", mode),
+    }
+    output_map.insert("state".into(),
+                      json!({"window_start": window_start, "count": count, "buffer": buffer}));
+
+    Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_throttle;
+
+    // Run throttle with `value` at time `clock` and `state`, returning the outputs
+    fn throttle(value: Value, clock: u64, mode: &str, state: &Value) -> Value {
+        let (result, _) = inner_throttle(&[value, json!(clock), json!(2), json!(100), json!(mode),
+            state.clone()]).expect("_throttle() failed");
+        result.expect("Could not get output value")
+    }
+
+    #[test]
+    fn passes_values_within_limit() {
+        let first = throttle(json!(1), 1000, "drop", &json!({}));
+        assert_eq!(first.get("value"), Some(&json!(1)));
+        let second = throttle(json!(2), 1010, "drop", first.get("state").expect("No state"));
+        assert_eq!(second.get("value"), Some(&json!(2)));
+        assert_eq!(second.pointer("/state/count"), Some(&json!(2)));
+    }
+
+    #[test]
+    fn drops_values_over_limit() {
+        let state = json!({"window_start": 1000, "count": 2, "buffer": []});
+        let output = throttle(json!(3), 1050, "drop", &state);
+        assert_eq!(output.get("value"), None);
+        assert_eq!(output.get("dropped"), Some(&json!(3)));
+        assert_eq!(output.get("state"), Some(&state));
+    }
+
+    #[test]
+    fn value_passed_in_next_window() {
+        let output = throttle(json!(3), 1100, "drop",
+                              &json!({"window_start": 1000, "count": 2, "buffer": []}));
+        assert_eq!(output.get("value"), Some(&json!(3)));
+        assert_eq!(output.get("dropped"), None);
+        assert_eq!(output.get("state"),
+                   Some(&json!({"window_start": 1100, "count": 1, "buffer": []})));
+    }
+
+    #[test]
+    fn buffers_values_over_limit() {
+        let output = throttle(json!(3), 1050, "buffer",
+                              &json!({"window_start": 1000, "count": 2, "buffer": []}));
+        assert_eq!(output.get("value"), None);
+        assert_eq!(output.get("dropped"), None);
+        assert_eq!(output.pointer("/state/buffer"), Some(&json!([3])));
+    }
+
+    #[test]
+    fn buffered_values_released_in_order_on_later_ticks() {
+        let mut state = json!({"window_start": 1000, "count": 2, "buffer": []});
+        for value in 3..=5 {
+            let output = throttle(json!(value), 1050, "buffer", &state);
+            assert_eq!(output.get("value"), None);
+            state = output.get("state").expect("No state").clone();
+        }
+        assert_eq!(state.get("buffer"), Some(&json!([3, 4, 5])));
+
+        // ticks within the same window release nothing
+        let output = throttle(Value::Null, 1090, "buffer", &state);
+        assert_eq!(output.get("value"), None);
+        state = output.get("state").expect("No state").clone();
+
+        // ticks in the next window release at most `limit` values, in the order they arrived
+        let mut released = vec![];
+        for clock in [1100, 1110, 1120] {
+            let output = throttle(Value::Null, clock, "buffer", &state);
+            if let Some(value) = output.get("value") {
+                released.push(value.clone());
+            }
+            state = output.get("state").expect("No state").clone();
+        }
+        assert_eq!(released, vec![json!(3), json!(4)]);
+        assert_eq!(state.get("buffer"), Some(&json!([5])));
+
+        // and the rest in the window after that
+        let output = throttle(Value::Null, 1200, "buffer", &state);
+        assert_eq!(output.get("value"), Some(&json!(5)));
+        assert_eq!(output.pointer("/state/buffer"), Some(&json!([])));
+    }
+
+    #[test]
+    fn new_value_queued_behind_buffered_values() {
+        let output = throttle(json!(4), 1100, "buffer",
+                              &json!({"window_start": 1000, "count": 2, "buffer": [3]}));
+        assert_eq!(output.get("value"), Some(&json!(3)));
+        assert_eq!(output.pointer("/state/buffer"), Some(&json!([4])));
+    }
+
+    #[test]
+    fn unknown_mode() {
+        let state = json!({"window_start": 1000, "count": 2});
+        assert!(inner_throttle(&[json!(3), json!(1050), json!(2), json!(100), json!("queue"), state]).is_err());
+    }
+}
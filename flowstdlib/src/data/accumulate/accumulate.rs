@@ -7,9 +7,11 @@ use flowmacro::flow_function;
 #[flow_function]
 fn inner_accumulate(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
     let value = inputs.first().ok_or("Could not get value")?.clone(); // input value to accumulate in array
+    // output the accumulated array, even if smaller than chunk_size
+    let flush = inputs.get(3).ok_or("Could not get flush")?.as_bool().ok_or("Could not get flush")?;
     let mut output_map = serde_json::Map::new();
 
-    if value.is_null() {
+    if value.is_null() && !flush {
         output_map.insert("chunk".into(), Value::Null);
     } else {
         let mut partial_input = inputs.get(1).ok_or("Could not get partial_input")?.clone(); // A partial array to append the values to
@@ -17,9 +19,12 @@ fn inner_accumulate(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
         let chunk_size = inputs.get(2).ok_or("Could not get chunk_size")?.as_u64().ok_or("Could not get chunk_size")?;
 
         let partial = partial_input.as_array_mut().ok_or("Could not get partial")?;
-        partial.push(value);
+        if !value.is_null() {
+            partial.push(value);
+        }
 
-        if partial.len() >= usize::try_from(chunk_size)? {
+        let full = chunk_size > 0 && partial.len() >= usize::try_from(chunk_size)?;
+        if (full || flush) && !partial.is_empty() {
             // TODO could pass on any extra elements beyond chunk size in 'partial'
             // and also force chunk size to be exact....
             output_map.insert("chunk".into(), Value::Array(partial.clone()));
@@ -46,7 +51,7 @@ mod test {
         let partial = json!([]);
         let chunk_size = json!(1);
 
-        let (result, _) = inner_accumulate(&[value, partial, chunk_size]).expect("_accumulate() failed");
+        let (result, _) = inner_accumulate(&[value, partial, chunk_size, json!(false)]).expect("_accumulate() failed");
         let output = result.expect("Could not get the Value from the output");
         assert_eq!(output.pointer("/chunk").expect("Could not get the /chunk from the output"), &json!([1]));
     }
@@ -57,7 +62,7 @@ mod test {
         let partial = json!([]);
         let chunk_size = json!(2);
 
-        let (result, _) = inner_accumulate(&[value, partial, chunk_size]).expect("_accumulate() failed");
+        let (result, _) = inner_accumulate(&[value, partial, chunk_size, json!(false)]).expect("_accumulate() failed");
         let output = result.expect("Could not get the Value from the output");
         assert_eq!(output.pointer("/chunk"), None);
         assert_eq!(output.pointer("/partial").expect("Could not get the /partial from the output"), &json!([1]));
@@ -69,8 +74,44 @@ mod test {
         let partial = json!([1]);
         let chunk_size = json!(2);
 
-        let (result, _) = inner_accumulate(&[value, partial, chunk_size]).expect("_accumulate() failed");
+        let (result, _) = inner_accumulate(&[value, partial, chunk_size, json!(false)]).expect("_accumulate() failed");
+        let output = result.expect("Could not get the Value from the output");
+        assert_eq!(output.pointer("/chunk").expect("Could not get the /chunk from the output"), &json!([1, 2]));
+    }
+
+    #[test]
+    fn accumulate_flush_with_value() {
+        let (result, _) = inner_accumulate(&[json!(2), json!([1]), json!(5), json!(true)])
+            .expect("_accumulate() failed");
+        let output = result.expect("Could not get the Value from the output");
+        assert_eq!(output.pointer("/chunk").expect("Could not get the /chunk from the output"), &json!([1, 2]));
+        assert_eq!(output.pointer("/partial").expect("Could not get the /partial from the output"), &json!([]));
+    }
+
+    #[test]
+    fn accumulate_flush_with_null() {
+        let (result, _) = inner_accumulate(&[json!(null), json!([1, 2]), json!(5), json!(true)])
+            .expect("_accumulate() failed");
         let output = result.expect("Could not get the Value from the output");
         assert_eq!(output.pointer("/chunk").expect("Could not get the /chunk from the output"), &json!([1, 2]));
+        assert_eq!(output.pointer("/chunk_size").expect("Could not get the /chunk_size from the output"), &json!(5));
+    }
+
+    #[test]
+    fn accumulate_flush_empty() {
+        let (result, _) = inner_accumulate(&[json!(null), json!([]), json!(5), json!(true)])
+            .expect("_accumulate() failed");
+        let output = result.expect("Could not get the Value from the output");
+        assert_eq!(output.pointer("/chunk"), None);
+        assert_eq!(output.pointer("/partial").expect("Could not get the /partial from the output"), &json!([]));
+    }
+
+    #[test]
+    fn accumulate_without_chunk_size() {
+        let (result, _) = inner_accumulate(&[json!(3), json!([1, 2]), json!(0), json!(false)])
+            .expect("_accumulate() failed");
+        let output = result.expect("Could not get the Value from the output");
+        assert_eq!(output.pointer("/chunk"), None);
+        assert_eq!(output.pointer("/partial").expect("Could not get the /partial from the output"), &json!([1, 2, 3]));
     }
 }
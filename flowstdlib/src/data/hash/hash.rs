@@ -0,0 +1,72 @@
+use std::fmt::Write;
+
+use md5::Md5;
+use serde_json::{json, Value};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_hash(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let data = match inputs.first().ok_or("Could not get data")? {
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    };
+    let algorithm = inputs.get(1).ok_or("Could not get algorithm")?
+        .as_str().ok_or("Could not get algorithm string")?;
+
+    let hash = match algorithm {
+        "sha256" => Sha256::digest(&data).to_vec(),
+        "sha1" => Sha1::digest(&data).to_vec(),
+        "md5" => Md5::digest(&data).to_vec(),
+        _ => bail!("Unknown hash algorithm '{}', expected \"sha256\", \"sha1\" or \"md5\"", algorithm),
+    };
+
+    let mut hex = String::with_capacity(hash.len() * 2);
+    for byte in hash {
+        write!(hex, "{byte:02x}").map_err(|e| e.to_string())?;
+    }
+
+    Ok((Some(json!(hex)), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_hash;
+
+    fn hash(data: Value, algorithm: &str) -> Value {
+        let (result, _) = inner_hash(&[data, json!(algorithm)]).expect("_hash() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn sha256() {
+        assert_eq!(hash(json!("abc"), "sha256"),
+                   json!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+    }
+
+    #[test]
+    fn sha1() {
+        assert_eq!(hash(json!("abc"), "sha1"), json!("a9993e364706816aba3e25717850c26c9cd0d89d"));
+    }
+
+    #[test]
+    fn md5() {
+        assert_eq!(hash(json!("abc"), "md5"), json!("900150983cd24fb0d6963f7d28e17f72"));
+    }
+
+    #[test]
+    fn non_string_hashed_as_json() {
+        assert_eq!(hash(json!({"a": 1}), "md5"), hash(json!("{\"a\":1}"), "md5"));
+    }
+
+    #[test]
+    fn unknown_algorithm() {
+        assert!(inner_hash(&[json!("abc"), json!("crc32")]).is_err());
+    }
+}
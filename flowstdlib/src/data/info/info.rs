@@ -2,7 +2,8 @@ use serde_json::{json, Value};
 
 use flowcore::{RUN_AGAIN, RunAgain};
 use flowcore::errors::Result;
-use flowcore::model::datatype::{ARRAY_TYPE, BOOLEAN_TYPE, NULL_TYPE, NUMBER_TYPE, OBJECT_TYPE, STRING_TYPE};
+use flowcore::model::datatype::{ARRAY_TYPE, BOOLEAN_TYPE, NULL_TYPE, NUMBER_TYPE, OBJECT_TYPE,
+                                STRING_TYPE};
 use flowmacro::flow_function;
 
 fn type_string(value: &Value) -> Result<String> {
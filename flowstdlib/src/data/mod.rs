@@ -16,6 +16,9 @@ pub mod duplicate;
 /// A module that enumerates entries of an array
 #[path = "enumerate/enumerate.rs"]
 pub mod enumerate;
+/// A module with a function to calculate the hash of a Value
+#[path = "hash/hash.rs"]
+pub mod hash;
 /// A module with a function to get info about a Value
 #[path = "info/info.rs"]
 pub mod info;
@@ -25,12 +28,21 @@ pub mod ordered_split;
 /// A module that removes elements from an array
 #[path = "remove/remove.rs"]
 pub mod remove;
-/// A module with a function to sort values into an ordered array of numbers
+/// A module with a function to select a sub-value from a value using a path
+#[path = "select/select.rs"]
+pub mod select;
+/// A module with a function to sort an array of values, by their value or by a key within them
 #[path = "sort/sort.rs"]
 pub mod sort;
 /// A module that splits a String into an array of strings
 #[path = "split/split.rs"]
 pub mod split;
+/// A module with a function to remove repeated values from an array
+#[path = "unique/unique.rs"]
+pub mod unique;
+/// A module with a function to generate a random UUID
+#[path = "uuid/uuid.rs"]
+pub mod uuid;
 /// A module that zips two sets of data into a set of tuples of data
 #[path = "zip/zip.rs"]
 pub mod zip;
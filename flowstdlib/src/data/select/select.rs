@@ -0,0 +1,78 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_select(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let data = inputs.first().ok_or("Could not get data")?;
+    let path = inputs.get(1).ok_or("Could not get path")?
+        .as_str().ok_or("Could not get path")?;
+
+    let selected = data.pointer(&pointer(path)).cloned().unwrap_or(Value::Null);
+
+    Ok((Some(json!(selected)), RUN_AGAIN))
+}
+
+// Convert a dotted path to a JSON Pointer, escaping '~' and '/' within its keys
+fn pointer(path: &str) -> String {
+    if path.is_empty() || path.starts_with('/') {
+        return path.to_string();
+    }
+
+    path.split('.')
+        .map(|key| format!("/{}", key.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_select;
+
+    fn select(data: &Value, path: &str) -> Value {
+        let (result, _) = inner_select(&[data.clone(), json!(path)]).expect("_select() failed");
+        result.expect("Could not get output")
+    }
+
+    fn customer() -> Value {
+        json!({
+            "name": "Ada",
+            "address": {"city": "London", "post/code": "N1"},
+            "orders": [{"total": 10}, {"total": 25}]
+        })
+    }
+
+    #[test]
+    fn select_whole_value() {
+        assert_eq!(select(&customer(), ""), customer());
+    }
+
+    #[test]
+    fn select_with_pointer() {
+        assert_eq!(select(&customer(), "/address/city"), json!("London"));
+        assert_eq!(select(&customer(), "/orders/1/total"), json!(25));
+        assert_eq!(select(&customer(), "/address/post~1code"), json!("N1"));
+    }
+
+    #[test]
+    fn select_with_dotted_path() {
+        assert_eq!(select(&customer(), "name"), json!("Ada"));
+        assert_eq!(select(&customer(), "orders.0.total"), json!(10));
+        assert_eq!(select(&customer(), "address.post/code"), json!("N1"));
+    }
+
+    #[test]
+    fn select_missing_is_null() {
+        assert_eq!(select(&customer(), "address.country"), Value::Null);
+        assert_eq!(select(&customer(), "/orders/2"), Value::Null);
+        assert_eq!(select(&json!(42), "value"), Value::Null);
+    }
+
+    #[test]
+    fn select_invalid_path() {
+        assert!(inner_select(&[customer(), json!(1)]).is_err());
+    }
+}
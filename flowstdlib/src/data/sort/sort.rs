@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use serde_json::{json, Value};
 
 use flowcore::{RUN_AGAIN, RunAgain};
@@ -6,15 +8,61 @@ use flowmacro::flow_function;
 
 #[flow_function]
 fn inner_sort(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
-    if inputs.first().ok_or("Could not get first")?.is_null() {
+    if inputs.first().ok_or("Could not get array")?.is_null() {
         return Ok((Some(Value::Null), RUN_AGAIN));
     }
 
-    let array_num = inputs.first().ok_or("Could not get array_num")?.as_array().ok_or("Could not get array")?;
-    let mut array_of_numbers: Vec<Value> = array_num.clone();
-    array_of_numbers.sort_by_key(|a| a.as_i64().unwrap_or(0));
+    let array = inputs.first().ok_or("Could not get array")?.as_array().ok_or("Could not get array")?;
+    let descending = inputs.get(1).ok_or("Could not get descending")?
+        .as_bool().ok_or("Could not get descending")?;
+    let key = inputs.get(2).ok_or("Could not get key")?
+        .as_str().ok_or("Could not get key")?;
+
+    let mut sorted: Vec<&Value> = array.iter().collect();
+    // sort_by() is stable, so elements with equal keys keep their order in either direction
+    sorted.sort_by(|a, b| {
+        let ordering = compare(sort_key(a, key), sort_key(b, key));
+        if descending { ordering.reverse() } else { ordering }
+    });
+
+    Ok((Some(json!(sorted)), RUN_AGAIN))
+}
+
+// Get the value in `element` at the `key` path to sort it by, or the element itself if `key` is
+// empty. `None` if there is no value at that path
+fn sort_key<'a>(element: &'a Value, key: &str) -> Option<&'a Value> {
+    if key.is_empty() {
+        Some(element)
+    } else {
+        element.pointer(&format!("/{}", key.trim_start_matches('/')))
+    }
+}
 
-    Ok((Some(json!(array_of_numbers)), RUN_AGAIN))
+// The rank of the type of a value, used to order values of different types
+fn type_rank(value: Option<&Value>) -> u8 {
+    match value {
+        None | Some(Value::Null) => 0,
+        Some(Value::Bool(_)) => 1,
+        Some(Value::Number(_)) => 2,
+        Some(Value::String(_)) => 3,
+        Some(Value::Array(_)) => 4,
+        Some(Value::Object(_)) => 5,
+    }
+}
+
+// Compare two values, by value if they are of the same type and by the rank of their type if not
+fn compare(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(Value::Bool(a)), Some(Value::Bool(b))) => a.cmp(b),
+        (Some(Value::Number(a)), Some(Value::Number(b))) =>
+            a.as_f64().unwrap_or_default().total_cmp(&b.as_f64().unwrap_or_default()),
+        (Some(Value::String(a)), Some(Value::String(b))) => a.cmp(b),
+        (Some(Value::Array(a)), Some(Value::Array(b))) => a.iter().zip(b.iter())
+            .map(|(a, b)| compare(Some(a), Some(b)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        _ => type_rank(a).cmp(&type_rank(b)),
+    }
 }
 
 #[cfg(test)]
@@ -23,40 +71,73 @@ mod test {
 
     use super::inner_sort;
 
+    fn sort(array: Value, descending: bool, key: &str) -> Value {
+        let (result, _) = inner_sort(&[array, json!(descending), json!(key)])
+            .expect("_sort() failed");
+        result.expect("Could not get output value")
+    }
+
     #[test]
     fn sort_null() {
-        let (result, _) = inner_sort(&[Value::Null]).expect("_sort() failed");
-
-        let output = result.expect("Could not get output value");
-        assert_eq!(output, Value::Null);
+        assert_eq!(sort(Value::Null, false, ""), Value::Null);
     }
 
     #[test]
     fn sort_invalid() {
-        assert!(inner_sort(&[json!("Hello World")]).is_err());
+        assert!(inner_sort(&[json!("Hello World"), json!(false), json!("")]).is_err());
     }
 
     #[test]
     fn sort_one() {
-        let (result, _) = inner_sort(&[json!([1])]).expect("_sort() failed");
-
-        let output = result.expect("Could not get output value");
-        assert_eq!(output, json!([1]));
+        assert_eq!(sort(json!([1]), false, ""), json!([1]));
     }
 
     #[test]
     fn sort_array() {
-        let (result, _) = inner_sort(&[json!([7, 1, 4, 8, 3, 9])]).expect("_sort() failed");
-
-        let output = result.expect("Could not get output value");
-        assert_eq!(output, json!([1, 3, 4, 7, 8, 9]));
+        assert_eq!(sort(json!([7, 1, 4, 8, 3, 9]), false, ""), json!([1, 3, 4, 7, 8, 9]));
     }
 
     #[test]
     fn sort_array_repeats() {
-        let (result, _) = inner_sort(&[json!([7, 1, 8, 4, 8, 3, 1, 9])]).expect("_sort() failed");
+        assert_eq!(sort(json!([7, 1, 8, 4, 8, 3, 1, 9]), false, ""),
+                   json!([1, 1, 3, 4, 7, 8, 8, 9]));
+    }
+
+    #[test]
+    fn sort_descending() {
+        assert_eq!(sort(json!([7, 1.5, 4, -8]), true, ""), json!([7, 4, 1.5, -8]));
+    }
+
+    #[test]
+    fn sort_strings() {
+        assert_eq!(sort(json!(["pear", "apple", "fig"]), false, ""),
+                   json!(["apple", "fig", "pear"]));
+    }
 
-        let output = result.expect("Could not get output value");
-        assert_eq!(output, json!([1, 1, 3, 4, 7, 8, 8, 9]));
+    #[test]
+    fn sort_mixed_types() {
+        assert_eq!(sort(json!(["a", 2, null, [1], true, {"b": 1}]), false, ""),
+                   json!([null, true, 2, "a", [1], {"b": 1}]));
+    }
+
+    #[test]
+    fn sort_by_key() {
+        let people = json!([{"name": "Zoe", "age": 30}, {"name": "Al", "age": 25},
+            {"name": "Bo", "age": 30}]);
+        assert_eq!(sort(people.clone(), false, "age"),
+                   json!([{"name": "Al", "age": 25}, {"name": "Zoe", "age": 30},
+                       {"name": "Bo", "age": 30}]));
+        assert_eq!(sort(people, true, "name"),
+                   json!([{"name": "Zoe", "age": 30}, {"name": "Bo", "age": 30},
+                       {"name": "Al", "age": 25}]));
+    }
+
+    #[test]
+    fn sort_by_key_path() {
+        let places = json!([{"address": {"city": "Paris"}}, {"address": {}},
+            {"address": {"city": "Berlin"}}]);
+        assert_eq!(sort(places, false, "address/city"),
+                   json!([{"address": {}}, {"address": {"city": "Berlin"}},
+                       {"address": {"city": "Paris"}}]));
     }
 }
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_unique(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let array = inputs.first().ok_or("Could not get array")?.as_array().ok_or("Could not get array")?;
+
+    // values are compared using their serialized form, as `Value` cannot be hashed
+    let mut seen = HashSet::new();
+    let unique: Vec<&Value> = array.iter()
+        .filter(|value| seen.insert(value.to_string()))
+        .collect();
+
+    Ok((Some(json!(unique)), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_unique;
+
+    #[test]
+    fn unique_numbers() {
+        let (result, _) = inner_unique(&[json!([3, 1, 3, 2, 1])]).expect("_unique() failed");
+        assert_eq!(result, Some(json!([3, 1, 2])));
+    }
+
+    #[test]
+    fn unique_values() {
+        let (result, _) = inner_unique(&[json!(["a", {"b": 1}, "a", {"b": 1}, [1], null, null])])
+            .expect("_unique() failed");
+        assert_eq!(result, Some(json!(["a", {"b": 1}, [1], null])));
+    }
+
+    #[test]
+    fn unique_empty_array() {
+        let (result, _) = inner_unique(&[json!([])]).expect("_unique() failed");
+        assert_eq!(result, Some(json!([])));
+    }
+
+    #[test]
+    fn unique_invalid() {
+        assert!(inner_unique(&[json!("Hello")]).is_err());
+    }
+}
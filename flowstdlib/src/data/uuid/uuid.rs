@@ -0,0 +1,82 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use serde_json::{json, Value};
+use uuid::Builder;
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_uuid(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let seed = match inputs.first().ok_or("Could not get seed")? {
+        Value::Null => random_seed()?,
+        seed => seed.as_u64().ok_or("The seed must be null or a positive integer")?,
+    };
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let uuid = Builder::from_random_bytes(rng.gen()).into_uuid();
+
+    let mut output_map = serde_json::Map::new();
+    output_map.insert("uuid".into(), json!(uuid.hyphenated().to_string()));
+    output_map.insert("seed".into(), json!(rng.gen::<u64>()));
+
+    Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+}
+
+// Get a random seed from the randomly keyed hasher of the standard library
+#[cfg(not(target_arch = "wasm32"))]
+fn random_seed() -> Result<u64> {
+    use std::collections::hash_map::RandomState;
+    use std::hash::BuildHasher;
+
+    Ok(RandomState::new().hash_one(std::time::SystemTime::now()))
+}
+
+// There is no source of randomness available to WASM, so the native implementation must be used
+#[cfg(target_arch = "wasm32")]
+fn random_seed() -> Result<u64> {
+    flowcore::errors::bail!("A random seed is not available to WASM. Supply a seed or use the native flowstdlib")
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_uuid;
+
+    fn uuid(seed: Value) -> Value {
+        let (result, _) = inner_uuid(&[seed]).expect("_uuid() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn same_seed_same_uuid() {
+        assert_eq!(uuid(json!(42)), uuid(json!(42)));
+    }
+
+    #[test]
+    fn uuid_is_version_4() {
+        let output = uuid(json!(42));
+        let uuid = output.get("uuid").and_then(Value::as_str).expect("No uuid");
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.chars().nth(14), Some('4'));
+    }
+
+    #[test]
+    fn next_seed_different_uuid() {
+        let first = uuid(json!(42));
+        let second = uuid(first.get("seed").expect("No seed").clone());
+        assert_ne!(first.get("uuid"), second.get("uuid"));
+    }
+
+    #[test]
+    fn random_seed() {
+        assert!(uuid(json!(null)).get("uuid").is_some());
+    }
+
+    #[test]
+    fn invalid_seed() {
+        assert!(inner_uuid(&[json!("seed")]).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, FixedOffset, SecondsFormat, TimeDelta};
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_add_duration(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let datetime = datetime(inputs.first().ok_or("Could not get datetime")?)?;
+    let duration = inputs.get(1).ok_or("Could not get duration")?
+        .as_i64().ok_or("Could not get duration")?;
+
+    let sum = TimeDelta::try_milliseconds(duration)
+        .and_then(|duration| datetime.checked_add_signed(duration))
+        .ok_or("The resulting date and time is out of range")?;
+
+    Ok((Some(outputs(&sum)), RUN_AGAIN))
+}
+
+// Get a date and time from an ISO-8601 string, or a number of milliseconds since the UNIX epoch
+fn datetime(value: &Value) -> Result<DateTime<FixedOffset>> {
+    match value {
+        Value::String(string) => DateTime::parse_from_rfc3339(string)
+            .map_err(|e| format!("Could not parse '{string}' as an ISO-8601 date and time: {e}").into()),
+        Value::Number(number) => number.as_i64().and_then(DateTime::from_timestamp_millis)
+            .map(|datetime| datetime.fixed_offset())
+            .ok_or_else(|| format!("'{number}' is not a valid number of milliseconds since the UNIX epoch").into()),
+        _ => bail!("'{}' is not a date and time", value),
+    }
+}
+
+// Output a date and time as both an ISO-8601 string and a number of milliseconds since the UNIX epoch
+fn outputs(datetime: &DateTime<FixedOffset>) -> Value {
+    json!({
+        "iso": datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "epoch": datetime.timestamp_millis(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_add_duration;
+
+    fn add_duration(datetime: Value, duration: i64) -> Value {
+        let (result, _) = inner_add_duration(&[datetime, json!(duration)])
+            .expect("_add_duration() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn add_to_iso_keeps_offset() {
+        assert_eq!(add_duration(json!("2024-02-28T23:30:00+01:00"), 2 * 3_600_000),
+                   json!({"iso": "2024-02-29T01:30:00+01:00", "epoch": 1_709_166_600_000_i64}));
+    }
+
+    #[test]
+    fn subtract_from_epoch() {
+        assert_eq!(add_duration(json!(1_709_296_200_000_i64), -1_500),
+                   json!({"iso": "2024-03-01T12:29:58.500Z", "epoch": 1_709_296_198_500_i64}));
+    }
+
+    #[test]
+    fn add_out_of_range() {
+        assert!(inner_add_duration(&[json!(0), json!(i64::MAX)]).is_err());
+    }
+}
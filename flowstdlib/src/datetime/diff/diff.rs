@@ -0,0 +1,51 @@
+use chrono::{DateTime, FixedOffset};
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_diff(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let from = datetime(inputs.first().ok_or("Could not get from")?)?;
+    let to = datetime(inputs.get(1).ok_or("Could not get to")?)?;
+
+    Ok((Some(json!(to.signed_duration_since(from).num_milliseconds())), RUN_AGAIN))
+}
+
+// Get a date and time from an ISO-8601 string, or a number of milliseconds since the UNIX epoch
+fn datetime(value: &Value) -> Result<DateTime<FixedOffset>> {
+    match value {
+        Value::String(string) => DateTime::parse_from_rfc3339(string)
+            .map_err(|e| format!("Could not parse '{string}' as an ISO-8601 date and time: {e}").into()),
+        Value::Number(number) => number.as_i64().and_then(DateTime::from_timestamp_millis)
+            .map(|datetime| datetime.fixed_offset())
+            .ok_or_else(|| format!("'{number}' is not a valid number of milliseconds since the UNIX epoch").into()),
+        _ => bail!("'{}' is not a date and time", value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_diff;
+
+    #[test]
+    fn diff_across_time_zones() {
+        let (result, _) = inner_diff(&[json!("2024-03-01T12:00:00Z"), json!("2024-03-01T14:30:00+01:00")])
+            .expect("_diff() failed");
+        assert_eq!(result, Some(json!(5_400_000)));
+    }
+
+    #[test]
+    fn diff_negative() {
+        let (result, _) = inner_diff(&[json!(1_000), json!(250)]).expect("_diff() failed");
+        assert_eq!(result, Some(json!(-750)));
+    }
+
+    #[test]
+    fn diff_invalid() {
+        assert!(inner_diff(&[json!("today"), json!(0)]).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, FixedOffset};
+use chrono::format::{Item, StrftimeItems};
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_format(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let datetime = datetime(inputs.first().ok_or("Could not get datetime")?)?;
+    let format = inputs.get(1).ok_or("Could not get format")?
+        .as_str().ok_or("Could not get format")?;
+
+    let items = StrftimeItems::new(format).collect::<Vec<Item>>();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        bail!("Invalid date and time format '{}'", format);
+    }
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", datetime.format_with_items(items.iter()))
+        .map_err(|_| format!("Could not format date and time using '{format}'"))?;
+
+    Ok((Some(json!(formatted)), RUN_AGAIN))
+}
+
+// Get a date and time from an ISO-8601 string, or a number of milliseconds since the UNIX epoch
+fn datetime(value: &Value) -> Result<DateTime<FixedOffset>> {
+    match value {
+        Value::String(string) => DateTime::parse_from_rfc3339(string)
+            .map_err(|e| format!("Could not parse '{string}' as an ISO-8601 date and time: {e}").into()),
+        Value::Number(number) => number.as_i64().and_then(DateTime::from_timestamp_millis)
+            .map(|datetime| datetime.fixed_offset())
+            .ok_or_else(|| format!("'{number}' is not a valid number of milliseconds since the UNIX epoch").into()),
+        _ => bail!("'{}' is not a date and time", value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_format;
+
+    fn format(datetime: Value, format: &str) -> Value {
+        let (result, _) = inner_format(&[datetime, json!(format)]).expect("_format() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn format_iso_string() {
+        assert_eq!(format(json!("2024-03-01T13:30:00+01:00"), "%Y-%m-%d %H:%M:%S %z"),
+                   json!("2024-03-01 13:30:00 +0100"));
+    }
+
+    #[test]
+    fn format_epoch() {
+        assert_eq!(format(json!(1_709_296_200_000_i64), "%a %d %b %Y %H:%M"), json!("Fri 01 Mar 2024 12:30"));
+    }
+
+    #[test]
+    fn format_invalid() {
+        assert!(inner_format(&[json!(0), json!("%Q")]).is_err());
+        assert!(inner_format(&[json!("yesterday"), json!("%Y")]).is_err());
+        assert!(inner_format(&[json!(true), json!("%Y")]).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+//! functions for manipulating dates and times
+//! ## Datetime (//flowstdlib/datetime)
+//! Functions for manipulating dates and times, as ISO-8601 strings or numbers of milliseconds since the UNIX epoch.
+
+/// A module with a function to add a duration to a date and time
+#[path = "add_duration/add_duration.rs"]
+pub mod add_duration;
+/// A module with a function to calculate the duration between two dates and times
+#[path = "diff/diff.rs"]
+pub mod diff;
+/// A module with a function to format a date and time as a `String`
+#[path = "format/format.rs"]
+pub mod format;
+/// A module with a function to get the current date and time from the time read from a clock
+#[path = "now/now.rs"]
+pub mod now;
+/// A module with a function to parse a date and time from a `String`
+#[path = "parse/parse.rs"]
+pub mod parse;
@@ -0,0 +1,43 @@
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_now(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let clock = inputs.first().ok_or("Could not get clock")?
+        .as_i64().ok_or("Could not get clock")?;
+    let now = DateTime::from_timestamp_millis(clock)
+        .ok_or_else(|| format!("'{clock}' is not a valid number of milliseconds since the UNIX epoch"))?;
+
+    Ok((Some(outputs(&now.fixed_offset())), RUN_AGAIN))
+}
+
+// Output a date and time as both an ISO-8601 string and a number of milliseconds since the UNIX epoch
+fn outputs(datetime: &DateTime<FixedOffset>) -> Value {
+    json!({
+        "iso": datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "epoch": datetime.timestamp_millis(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_now;
+
+    #[test]
+    fn now_from_clock() {
+        let (result, _) = inner_now(&[json!(1_709_296_200_123_i64)]).expect("_now() failed");
+        assert_eq!(result, Some(json!({"iso": "2024-03-01T12:30:00.123Z", "epoch": 1_709_296_200_123_i64})));
+    }
+
+    #[test]
+    fn now_from_invalid_clock() {
+        assert!(inner_now(&[json!("now")]).is_err());
+        assert!(inner_now(&[json!(i64::MAX)]).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat};
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_parse(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let string = inputs.first().ok_or("Could not get string")?
+        .as_str().ok_or("Could not get string")?;
+    let format = inputs.get(1).ok_or("Could not get format")?
+        .as_str().ok_or("Could not get format")?;
+
+    let datetime = if format.is_empty() {
+        DateTime::parse_from_rfc3339(string)
+            .map_err(|e| format!("Could not parse '{string}' as an ISO-8601 date and time: {e}"))?
+    } else {
+        parse_with_format(string, format)?
+    };
+
+    Ok((Some(outputs(&datetime)), RUN_AGAIN))
+}
+
+// Parse a date and time with a time zone offset, a date and time in UTC or a date at midnight UTC
+fn parse_with_format(string: &str, format: &str) -> Result<DateTime<FixedOffset>> {
+    if let Ok(datetime) = DateTime::parse_from_str(string, format) {
+        return Ok(datetime);
+    }
+
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(string, format) {
+        return Ok(datetime.and_utc().fixed_offset());
+    }
+
+    let date = NaiveDate::parse_from_str(string, format)
+        .map_err(|e| format!("Could not parse '{string}' using format '{format}': {e}"))?;
+    Ok(date.and_time(NaiveTime::MIN).and_utc().fixed_offset())
+}
+
+// Output a date and time as both an ISO-8601 string and a number of milliseconds since the UNIX epoch
+fn outputs(datetime: &DateTime<FixedOffset>) -> Value {
+    json!({
+        "iso": datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        "epoch": datetime.timestamp_millis(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_parse;
+
+    fn parse(string: &str, format: &str) -> Value {
+        let (result, _) = inner_parse(&[json!(string), json!(format)]).expect("_parse() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn parse_iso() {
+        assert_eq!(parse("2024-03-01T13:30:00+01:00", ""),
+                   json!({"iso": "2024-03-01T13:30:00+01:00", "epoch": 1_709_296_200_000_i64}));
+    }
+
+    #[test]
+    fn parse_log_timestamp() {
+        assert_eq!(parse("01/Mar/2024:13:30:00 +0100", "%d/%b/%Y:%H:%M:%S %z"),
+                   json!({"iso": "2024-03-01T13:30:00+01:00", "epoch": 1_709_296_200_000_i64}));
+    }
+
+    #[test]
+    fn parse_without_offset_is_utc() {
+        assert_eq!(parse("2024-03-01 12:30:00", "%Y-%m-%d %H:%M:%S"),
+                   json!({"iso": "2024-03-01T12:30:00Z", "epoch": 1_709_296_200_000_i64}));
+    }
+
+    #[test]
+    fn parse_date_is_midnight_utc() {
+        assert_eq!(parse("2024-03-01", "%Y-%m-%d"),
+                   json!({"iso": "2024-03-01T00:00:00Z", "epoch": 1_709_251_200_000_i64}));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(inner_parse(&[json!("yesterday"), json!("")]).is_err());
+        assert!(inner_parse(&[json!("2024-03-01"), json!("%H:%M")]).is_err());
+    }
+}
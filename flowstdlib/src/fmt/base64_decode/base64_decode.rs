@@ -0,0 +1,53 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_base64_decode(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let encoded = inputs.first().ok_or("Could not get encoded")?
+        .as_str().ok_or("Could not get encoded string")?;
+    let bytes = STANDARD.decode(encoded)
+        .map_err(|e| format!("Could not base64 decode '{encoded}': {e}"))?;
+
+    let mut output_map = serde_json::Map::new();
+    if let Ok(string) = std::str::from_utf8(&bytes) {
+        output_map.insert("string".into(), json!(string));
+    }
+    output_map.insert("bytes".into(), json!(bytes));
+
+    Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_base64_decode;
+
+    #[test]
+    fn decode_string() {
+        let (result, _) = inner_base64_decode(&[json!("SGVsbG8gV29ybGQ=")])
+            .expect("_base64_decode() failed");
+        let output = result.expect("Could not get output");
+        assert_eq!(output.pointer("/string"), Some(&json!("Hello World")));
+        assert_eq!(output.pointer("/bytes/0"), Some(&json!(72)));
+    }
+
+    #[test]
+    fn decode_bytes() {
+        let (result, _) = inner_base64_decode(&[json!("AID/")])
+            .expect("_base64_decode() failed");
+        let output = result.expect("Could not get output");
+        assert_eq!(output.pointer("/string"), None);
+        assert_eq!(output.pointer("/bytes"), Some(&json!([0, 128, 255])));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert!(inner_base64_decode(&[json!("not base64!")]).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_base64_encode(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let bytes = match inputs.first().ok_or("Could not get data")? {
+        Value::String(string) => string.as_bytes().to_vec(),
+        Value::Array(array) => array.iter()
+            .map(|byte| byte.as_u64().and_then(|number| u8::try_from(number).ok())
+                .ok_or_else(|| format!("'{byte}' is not a byte").into()))
+            .collect::<Result<Vec<u8>>>()?,
+        data => bail!("Cannot base64 encode '{}', it must be a string or an array of bytes", data),
+    };
+
+    Ok((Some(json!(STANDARD.encode(bytes))), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_base64_encode;
+
+    #[test]
+    fn encode_string() {
+        let (result, _) = inner_base64_encode(&[json!("Hello World")])
+            .expect("_base64_encode() failed");
+        assert_eq!(result, Some(json!("SGVsbG8gV29ybGQ=")));
+    }
+
+    #[test]
+    fn encode_bytes() {
+        let (result, _) = inner_base64_encode(&[json!([0, 128, 255])])
+            .expect("_base64_encode() failed");
+        assert_eq!(result, Some(json!("AID/")));
+    }
+
+    #[test]
+    fn encode_invalid_bytes() {
+        assert!(inner_base64_encode(&[json!([1, 256])]).is_err());
+        assert!(inner_base64_encode(&[json!(["a"])]).is_err());
+    }
+
+    #[test]
+    fn encode_invalid_data() {
+        assert!(inner_base64_encode(&[json!(42)]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use csv::{ReaderBuilder, StringRecord};
+use serde_json::{json, Map, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_csv_parse(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let text = inputs.first().ok_or("Could not get text")?
+        .as_str().ok_or("Could not get text")?;
+    let headers = inputs.get(1).ok_or("Could not get headers")?
+        .as_bool().ok_or("Could not get headers")?;
+
+    let mut reader = ReaderBuilder::new()
+        .has_headers(headers)
+        .from_reader(text.as_bytes());
+
+    let names = if headers {
+        Some(reader.headers().map_err(|e| format!("Could not parse CSV headers: {e}"))?.clone())
+    } else {
+        None
+    };
+
+    let mut records = vec![];
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Could not parse CSV: {e}"))?;
+        records.push(match &names {
+            Some(names) => object(names, &record),
+            None => json!(record.iter().collect::<Vec<&str>>()),
+        });
+    }
+
+    Ok((Some(Value::Array(records)), RUN_AGAIN))
+}
+
+// Create an object from the fields of a record, using the names of the columns as keys
+fn object(names: &StringRecord, record: &StringRecord) -> Value {
+    Value::Object(names.iter().zip(record.iter())
+        .map(|(name, field)| (name.to_string(), json!(field)))
+        .collect::<Map<String, Value>>())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_csv_parse;
+
+    fn parse(text: &str, headers: bool) -> Value {
+        let (result, _) = inner_csv_parse(&[json!(text), json!(headers)]).expect("_csv_parse() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn parse_with_headers() {
+        assert_eq!(parse("name,age\nAda,36\nAlan,41\n", true),
+                   json!([{"name": "Ada", "age": "36"}, {"name": "Alan", "age": "41"}]));
+    }
+
+    #[test]
+    fn parse_without_headers() {
+        assert_eq!(parse("name,age\nAda,36", false), json!([["name", "age"], ["Ada", "36"]]));
+    }
+
+    #[test]
+    fn parse_quoted() {
+        assert_eq!(parse("\"Lovelace, Ada\",\"say \"\"hi\"\"\"\n", false), json!([["Lovelace, Ada", "say \"hi\""]]));
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert_eq!(parse("", false), json!([]));
+        assert_eq!(parse("name,age\n", true), json!([]));
+    }
+
+    #[test]
+    fn parse_uneven_rows() {
+        assert!(inner_csv_parse(&[json!("a,b\n1\n"), json!(false)]).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use csv::Writer;
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_csv_serialize(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let records = inputs.first().ok_or("Could not get records")?
+        .as_array().ok_or("Could not get records")?;
+    let headers = inputs.get(1).ok_or("Could not get headers")?
+        .as_bool().ok_or("Could not get headers")?;
+
+    // the columns of records that are objects, in the order they are first found
+    let mut names: Vec<&String> = vec![];
+    for record in records {
+        if let Value::Object(map) = record {
+            for name in map.keys() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+
+    let mut writer = Writer::from_writer(vec![]);
+    if headers && !names.is_empty() {
+        writer.write_record(&names).map_err(|e| format!("Could not write CSV headers: {e}"))?;
+    }
+
+    for record in records {
+        let fields = match record {
+            Value::Array(fields) => fields.iter().map(field).collect::<Vec<String>>(),
+            Value::Object(map) => names.iter()
+                .map(|name| map.get(*name).map(field).unwrap_or_default())
+                .collect(),
+            _ => bail!("Record '{}' is not an array or an object", record),
+        };
+        writer.write_record(&fields).map_err(|e| format!("Could not write CSV: {e}"))?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| format!("Could not write CSV: {e}"))?;
+    let text = String::from_utf8(bytes).map_err(|e| format!("Could not write CSV: {e}"))?;
+
+    Ok((Some(json!(text)), RUN_AGAIN))
+}
+
+// The text of a field in the CSV output
+fn field(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => String::new(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_csv_serialize;
+
+    fn serialize(records: Value, headers: bool) -> Value {
+        let (result, _) = inner_csv_serialize(&[records, json!(headers)]).expect("_csv_serialize() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn serialize_arrays() {
+        assert_eq!(serialize(json!([["name", "age"], ["Ada", 36]]), true), json!("name,age\nAda,36\n"));
+    }
+
+    #[test]
+    fn serialize_objects_with_headers() {
+        assert_eq!(serialize(json!([{"name": "Ada", "age": 36}, {"name": "Alan", "city": "London"}]), true),
+                   json!("age,name,city\n36,Ada,\n,Alan,London\n"));
+    }
+
+    #[test]
+    fn serialize_objects_without_headers() {
+        assert_eq!(serialize(json!([{"name": "Ada"}, {"name": null}]), false), json!("Ada\n\"\"\n"));
+    }
+
+    #[test]
+    fn serialize_quoted() {
+        assert_eq!(serialize(json!([["Lovelace, Ada", "say \"hi\""]]), false),
+                   json!("\"Lovelace, Ada\",\"say \"\"hi\"\"\"\n"));
+    }
+
+    #[test]
+    fn serialize_empty() {
+        assert_eq!(serialize(json!([]), true), json!(""));
+    }
+
+    #[test]
+    fn serialize_invalid_record() {
+        assert!(inner_csv_serialize(&[json!([1, 2]), json!(false)]).is_err());
+    }
+}
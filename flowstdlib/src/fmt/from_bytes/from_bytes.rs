@@ -0,0 +1,53 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowcore::model::bytes::Bytes;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_from_bytes(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let bytes = Bytes::from_value(inputs.first().ok_or("Could not get bytes")?)?;
+
+    let mut output_map = serde_json::Map::new();
+    if let Ok(string) = std::str::from_utf8(&bytes) {
+        output_map.insert("string".into(), json!(string));
+    }
+    output_map.insert("length".into(), json!(bytes.len()));
+    output_map.insert("array".into(), json!(bytes.into_vec()));
+
+    Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use flowcore::model::bytes::Bytes;
+
+    use super::inner_from_bytes;
+
+    #[test]
+    fn utf8_bytes() {
+        let (result, _) = inner_from_bytes(&[Bytes::from(&b"Hi"[..]).to_value()])
+            .expect("_from_bytes() failed");
+        let output = result.expect("Could not get output");
+        assert_eq!(output.pointer("/string"), Some(&json!("Hi")));
+        assert_eq!(output.pointer("/array"), Some(&json!([72, 105])));
+        assert_eq!(output.pointer("/length"), Some(&json!(2)));
+    }
+
+    #[test]
+    fn binary_bytes() {
+        let (result, _) = inner_from_bytes(&[Bytes::from(vec![0, 128, 255]).to_value()])
+            .expect("_from_bytes() failed");
+        let output = result.expect("Could not get output");
+        assert_eq!(output.pointer("/string"), None);
+        assert_eq!(output.pointer("/array"), Some(&json!([0, 128, 255])));
+    }
+
+    #[test]
+    fn not_bytes() {
+        assert!(inner_from_bytes(&[json!("Hi")]).is_err());
+    }
+}
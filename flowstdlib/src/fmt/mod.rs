@@ -2,9 +2,30 @@
 //! ## Fmt (//flowstdlib/fmt)
 //! Functions for the formatting of values and conversion from one type to another.
 
+/// A module to decode a base64 `String`
+#[path = "base64_decode/base64_decode.rs"]
+pub mod base64_decode;
+/// A module to encode a `String` or bytes as base64
+#[path = "base64_encode/base64_encode.rs"]
+pub mod base64_encode;
+/// A module to parse CSV text into an array of records
+#[path = "csv_parse/csv_parse.rs"]
+pub mod csv_parse;
+/// A module to serialize an array of records as CSV text
+#[path = "csv_serialize/csv_serialize.rs"]
+pub mod csv_serialize;
+/// A module to convert `Bytes` to other types
+#[path = "from_bytes/from_bytes.rs"]
+pub mod from_bytes;
 /// A module to reverse a `String`
 #[path = "reverse/reverse.rs"]
 pub mod reverse;
+/// A module to replace the placeholders in a template `String` with values
+#[path = "template/template.rs"]
+pub mod template;
+/// A module to convert a `String` or an array of numbers to `Bytes`
+#[path = "to_bytes/to_bytes.rs"]
+pub mod to_bytes;
 /// A module to convert a `String` to its `Json` representation
 #[path = "to_json/to_json.rs"]
 pub mod to_json;
@@ -0,0 +1,98 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_template(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let template = inputs.first().ok_or("Could not get template")?
+        .as_str().ok_or("Could not get template")?;
+    let values = inputs.get(1).ok_or("Could not get values")?;
+
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => output.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => output.push('}'),
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => bail!("Unclosed placeholder '{{{}' in template", name),
+                    }
+                }
+
+                match lookup(values, &name) {
+                    Some(Value::String(string)) => output.push_str(string),
+                    Some(value) => output.push_str(&value.to_string()),
+                    None => bail!("No value for placeholder '{{{}}}' in template", name),
+                }
+            }
+            '}' => bail!("Unmatched '}}' in template, use '}}}}' for a literal '}}'"),
+            _ => output.push(c),
+        }
+    }
+
+    Ok((Some(json!(output)), RUN_AGAIN))
+}
+
+// Find the value at a dotted path (e.g. "address.city") within `values`
+fn lookup<'a>(values: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(values, |value, key| match value {
+        Value::Object(map) => map.get(key),
+        Value::Array(array) => key.parse::<usize>().ok().and_then(|index| array.get(index)),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_template;
+
+    fn template(template: &str, values: &Value) -> Value {
+        let (result, _) = inner_template(&[json!(template), values.clone()]).expect("_template() failed");
+        result.expect("Could not get output")
+    }
+
+    #[test]
+    fn template_without_placeholders() {
+        assert_eq!(template("Hello World", &json!({})), json!("Hello World"));
+    }
+
+    #[test]
+    fn template_with_placeholders() {
+        assert_eq!(template("{greeting} {name}!", &json!({"greeting": "Hello", "name": "Ada"})),
+                   json!("Hello Ada!"));
+    }
+
+    #[test]
+    fn template_with_non_string_values() {
+        assert_eq!(template("{count} {valid} {list}", &json!({"count": 3, "valid": true, "list": [1, 2]})),
+                   json!("3 true [1,2]"));
+    }
+
+    #[test]
+    fn template_with_path() {
+        assert_eq!(template("{name} has {orders.0.total} items in {{basket}}",
+                            &json!({"name": "Ada", "orders": [{"total": 3}]})),
+                   json!("Ada has 3 items in {basket}"));
+    }
+
+    #[test]
+    fn template_missing_value() {
+        assert!(inner_template(&[json!("Hello {name}"), json!({})]).is_err());
+        assert!(inner_template(&[json!("{orders.1}"), json!({"orders": [1]})]).is_err());
+    }
+
+    #[test]
+    fn template_invalid() {
+        assert!(inner_template(&[json!("Hello {name"), json!({"name": "Ada"})]).is_err());
+        assert!(inner_template(&[json!("Hello name}"), json!({"name": "Ada"})]).is_err());
+    }
+}
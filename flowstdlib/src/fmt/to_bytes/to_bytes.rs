@@ -0,0 +1,44 @@
+use serde_json::Value;
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowcore::model::bytes::Bytes;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_to_bytes(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let bytes = match inputs.first().ok_or("Could not get data")? {
+        Value::String(string) => Bytes::from(string.as_bytes()),
+        data @ Value::Array(_) => Bytes::from_value(data)?,
+        data => bail!("Cannot convert '{}' to bytes, it must be a string or an array of bytes", data),
+    };
+
+    Ok((Some(bytes.to_value()), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use flowcore::model::bytes::Bytes;
+
+    use super::inner_to_bytes;
+
+    #[test]
+    fn string_to_bytes() {
+        let (result, _) = inner_to_bytes(&[json!("Hi")]).expect("_to_bytes() failed");
+        assert_eq!(result, Some(Bytes::from(&b"Hi"[..]).to_value()));
+    }
+
+    #[test]
+    fn array_to_bytes() {
+        let (result, _) = inner_to_bytes(&[json!([0, 128, 255])]).expect("_to_bytes() failed");
+        assert_eq!(result, Some(Bytes::from(vec![0, 128, 255]).to_value()));
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert!(inner_to_bytes(&[json!([1, 256])]).is_err());
+        assert!(inner_to_bytes(&[json!(42)]).is_err());
+    }
+}
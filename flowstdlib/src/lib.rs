@@ -23,6 +23,9 @@ pub mod control;
 /// Some generic processes that act on data.
 pub mod data;
 
+/// Functions for manipulating dates and times, as ISO-8601 strings or milliseconds since the UNIX epoch
+pub mod datetime;
+
 /// Math Functions and flows
 pub mod math;
 
@@ -32,6 +35,9 @@ pub mod matrix;
 /// Functions for the formatting of values and conversion from one type to another.
 pub mod fmt;
 
+/// Functions for the manipulation of `String`s.
+pub mod string;
+
 /// Use `manifest::get` to get the natively/statically linked
 /// `LibraryManifest``flowcore::model::lib_manifest::LibraryManifest` for this library
 /// to get access to everything `error_chain` creates.
@@ -6,7 +6,7 @@ use flowcore::model::lib_manifest::ImplementationLocator::Native;
 use flowcore::model::lib_manifest::LibraryManifest;
 use flowcore::model::metadata::MetaData;
 
-use crate::{control, data, fmt, math, matrix};
+use crate::{control, data, datetime, fmt, math, matrix, string};
 use crate::errors::Result;
 
 /// Return the `LibraryManifest` for this library
@@ -53,11 +53,21 @@ pub fn get() -> Result<LibraryManifest> {
             Native(Arc::new(control::select::Select)),
         );
 
+    manifest.locators.insert(
+            Url::parse("lib://flowstdlib/control/switch")?,
+            Native(Arc::new(control::switch::Switch)),
+        );
+
     manifest.locators.insert(
             Url::parse("lib://flowstdlib/control/tap")?,
             Native(Arc::new(control::tap::Tap)),
         );
 
+    manifest.locators.insert(
+            Url::parse("lib://flowstdlib/control/throttle")?,
+            Native(Arc::new(control::throttle::Throttle)),
+        );
+
     // Data module functions
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/data/accumulate")?,
@@ -84,6 +94,11 @@ pub fn get() -> Result<LibraryManifest> {
         Native(Arc::new(data::enumerate::Enumerate)),
     );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/data/hash")?,
+        Native(Arc::new(data::hash::Hash)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/data/info")?,
         Native(Arc::new(data::info::Info)),
@@ -99,6 +114,11 @@ pub fn get() -> Result<LibraryManifest> {
         Native(Arc::new(data::remove::Remove)),
     );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/data/select")?,
+        Native(Arc::new(data::select::Select)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/data/sort")?,
         Native(Arc::new(data::sort::Sort)),
@@ -109,17 +129,87 @@ pub fn get() -> Result<LibraryManifest> {
         Native(Arc::new(data::split::Split)),
     );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/data/unique")?,
+        Native(Arc::new(data::unique::Unique)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/data/uuid")?,
+        Native(Arc::new(data::uuid::Uuid)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/data/zip")?,
         Native(Arc::new(data::zip::Zip)),
     );
 
     // Format module functions
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/datetime/add_duration")?,
+        Native(Arc::new(datetime::add_duration::AddDuration)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/datetime/diff")?,
+        Native(Arc::new(datetime::diff::Diff)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/datetime/format")?,
+        Native(Arc::new(datetime::format::Format)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/datetime/now")?,
+        Native(Arc::new(datetime::now::Now)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/datetime/parse")?,
+        Native(Arc::new(datetime::parse::Parse)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/fmt/base64_decode")?,
+        Native(Arc::new(fmt::base64_decode::Base64Decode)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/fmt/base64_encode")?,
+        Native(Arc::new(fmt::base64_encode::Base64Encode)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/fmt/csv_parse")?,
+        Native(Arc::new(fmt::csv_parse::CsvParse)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/fmt/csv_serialize")?,
+        Native(Arc::new(fmt::csv_serialize::CsvSerialize)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/fmt/from_bytes")?,
+        Native(Arc::new(fmt::from_bytes::FromBytes)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/fmt/reverse")?,
         Native(Arc::new(fmt::reverse::Reverse)),
     );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/fmt/template")?,
+        Native(Arc::new(fmt::template::Template)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/fmt/to_bytes")?,
+        Native(Arc::new(fmt::to_bytes::ToBytes)),
+    );
+
     manifest.locators.insert(
             Url::parse("lib://flowstdlib/fmt/to_json")?,
             Native(Arc::new(fmt::to_json::ToJson)),
@@ -146,11 +236,31 @@ pub fn get() -> Result<LibraryManifest> {
             Native(Arc::new(math::divide::Divide)),
         );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/math/mean")?,
+        Native(Arc::new(math::mean::Mean)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/math/median")?,
+        Native(Arc::new(math::median::Median)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/math/min_max")?,
+        Native(Arc::new(math::min_max::MinMax)),
+    );
+
     manifest.locators.insert(
             Url::parse("lib://flowstdlib/math/multiply")?,
             Native(Arc::new(math::multiply::Multiply)),
         );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/math/random")?,
+        Native(Arc::new(math::random::Random)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/math/range_split")?,
         Native(Arc::new(math::range_split::RangeSplit)),
@@ -161,22 +271,52 @@ pub fn get() -> Result<LibraryManifest> {
             Native(Arc::new(math::sqrt::Sqrt)),
         );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/math/stddev")?,
+        Native(Arc::new(math::stddev::Stddev)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/math/subtract")?,
         Native(Arc::new(math::subtract::Subtract)),
     );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/math/variance")?,
+        Native(Arc::new(math::variance::Variance)),
+    );
+
     // Matrix module functions
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/matrix/determinant")?,
+        Native(Arc::new(matrix::determinant::Determinant)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/matrix/duplicate_rows")?,
         Native(Arc::new(matrix::duplicate_rows::DuplicateRows)),
     );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/matrix/identity")?,
+        Native(Arc::new(matrix::identity::Identity)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/matrix/invert")?,
+        Native(Arc::new(matrix::invert::Invert)),
+    );
+
     manifest.locators.insert(
         Url::parse("lib://flowstdlib/matrix/multiply_row")?,
         Native(Arc::new(matrix::multiply_row::MultiplyRow)),
     );
 
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/matrix/scalar_multiply")?,
+        Native(Arc::new(matrix::scalar_multiply::ScalarMultiply)),
+    );
+
     manifest.locators.insert(
             Url::parse("lib://flowstdlib/matrix/transpose")?,
             Native(Arc::new(matrix::transpose::Transpose)),
@@ -187,5 +327,56 @@ pub fn get() -> Result<LibraryManifest> {
         Native(Arc::new(matrix::compose_matrix::ComposeMatrix)),
     );
 
+    // String module functions
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/contains")?,
+        Native(Arc::new(string::contains::Contains)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/join")?,
+        Native(Arc::new(string::join::Join)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/regex_capture")?,
+        Native(Arc::new(string::regex_capture::RegexCapture)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/regex_match")?,
+        Native(Arc::new(string::regex_match::RegexMatch)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/regex_replace")?,
+        Native(Arc::new(string::regex_replace::RegexReplace)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/replace")?,
+        Native(Arc::new(string::replace::Replace)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/split")?,
+        Native(Arc::new(string::split::Split)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/to_lower")?,
+        Native(Arc::new(string::to_lower::ToLower)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/to_upper")?,
+        Native(Arc::new(string::to_upper::ToUpper)),
+    );
+
+    manifest.locators.insert(
+        Url::parse("lib://flowstdlib/string/trim")?,
+        Native(Arc::new(string::trim::Trim)),
+    );
+
     Ok(manifest)
 }
\ No newline at end of file
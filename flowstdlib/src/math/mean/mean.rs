@@ -0,0 +1,51 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_mean(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    if let Some(array) = inputs.first().ok_or("Could not get numbers")?.as_array() {
+        let numbers = array.iter()
+            .map(|number| number.as_f64().ok_or("Could not get number"))
+            .collect::<std::result::Result<Vec<f64>, _>>()?;
+        if !numbers.is_empty() {
+            value = Some(json!(numbers.iter().sum::<f64>() / numbers.len() as f64));
+        }
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_mean;
+
+    #[test]
+    fn mean_of_numbers() {
+        let (result, _) = inner_mean(&[json!([1, 2, 3, 4])]).expect("_mean() failed");
+        assert_eq!(result, Some(json!(2.5)));
+    }
+
+    #[test]
+    fn mean_of_one() {
+        let (result, _) = inner_mean(&[json!([-7.5])]).expect("_mean() failed");
+        assert_eq!(result, Some(json!(-7.5)));
+    }
+
+    #[test]
+    fn mean_of_empty_array_has_no_output() {
+        let (result, _) = inner_mean(&[json!([])]).expect("_mean() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn mean_of_non_numbers() {
+        assert!(inner_mean(&[json!([1, "two"])]).is_err());
+    }
+}
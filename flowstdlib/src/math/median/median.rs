@@ -0,0 +1,56 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_median(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    if let Some(array) = inputs.first().ok_or("Could not get numbers")?.as_array() {
+        let mut numbers = array.iter()
+            .map(|number| number.as_f64().ok_or("Could not get number"))
+            .collect::<std::result::Result<Vec<f64>, _>>()?;
+        numbers.sort_by(f64::total_cmp);
+
+        let middle = numbers.len() / 2;
+        value = match (numbers.len() % 2, numbers.get(middle.wrapping_sub(1)), numbers.get(middle)) {
+            (1, _, Some(median)) => Some(json!(median)),
+            (0, Some(lower), Some(upper)) => Some(json!((lower + upper) / 2.0)),
+            _ => None,
+        };
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_median;
+
+    #[test]
+    fn median_of_odd_number() {
+        let (result, _) = inner_median(&[json!([5, 1, 3])]).expect("_median() failed");
+        assert_eq!(result, Some(json!(3.0)));
+    }
+
+    #[test]
+    fn median_of_even_number() {
+        let (result, _) = inner_median(&[json!([4, 1, 3, 2])]).expect("_median() failed");
+        assert_eq!(result, Some(json!(2.5)));
+    }
+
+    #[test]
+    fn median_of_empty_array_has_no_output() {
+        let (result, _) = inner_median(&[json!([])]).expect("_median() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn median_of_non_numbers() {
+        assert!(inner_median(&[json!([1, null])]).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use serde_json::Value;
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_min_max(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    if let Some(array) = inputs.first().ok_or("Could not get numbers")?.as_array() {
+        // pair each number as f64, for comparison, with its original value to be output
+        let numbers = array.iter()
+            .map(|number| number.as_f64().map(|number_f64| (number_f64, number))
+                .ok_or("Could not get number"))
+            .collect::<std::result::Result<Vec<(f64, &Value)>, _>>()?;
+        let min = numbers.iter().min_by(|a, b| a.0.total_cmp(&b.0));
+        let max = numbers.iter().max_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let (Some((_, min)), Some((_, max))) = (min, max) {
+            let mut output_map = serde_json::Map::new();
+            output_map.insert("min".into(), (*min).clone());
+            output_map.insert("max".into(), (*max).clone());
+            value = Some(Value::Object(output_map));
+        }
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_min_max;
+
+    #[test]
+    fn min_max_of_numbers() {
+        let (result, _) = inner_min_max(&[json!([3, -1.5, 10, 2])]).expect("_min_max() failed");
+        assert_eq!(result, Some(json!({"min": -1.5, "max": 10})));
+    }
+
+    #[test]
+    fn min_max_of_one() {
+        let (result, _) = inner_min_max(&[json!([7])]).expect("_min_max() failed");
+        assert_eq!(result, Some(json!({"min": 7, "max": 7})));
+    }
+
+    #[test]
+    fn min_max_of_empty_array_has_no_output() {
+        let (result, _) = inner_min_max(&[json!([])]).expect("_min_max() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn min_max_of_non_numbers() {
+        assert!(inner_min_max(&[json!([1, "2"])]).is_err());
+    }
+}
@@ -17,9 +17,21 @@ pub mod add;
 /// A module with a function to divide two `Numbers`
 #[path = "divide/divide.rs"]
 pub mod divide;
+/// A module with a function to calculate the mean of an array of `Numbers`
+#[path = "mean/mean.rs"]
+pub mod mean;
+/// A module with a function to calculate the median of an array of `Numbers`
+#[path = "median/median.rs"]
+pub mod median;
+/// A module with a function to find the minimum and maximum of an array of `Numbers`
+#[path = "min_max/min_max.rs"]
+pub mod min_max;
 /// A module with a function to multiply two `Numbers`
 #[path = "multiply/multiply.rs"]
 pub mod multiply;
+/// A module with a function to generate a random `Number`
+#[path = "random/random.rs"]
+pub mod random;
 /// A module with a function to split a range of `Numbers`, into two sub-ranges
 #[path = "range_split/range_split.rs"]
 pub mod range_split;
@@ -28,4 +40,10 @@ pub mod range_split;
 pub mod subtract;
 /// A module with a function to calculate the square root of a `Number`
 #[path = "sqrt/sqrt.rs"]
-pub mod sqrt;
\ No newline at end of file
+pub mod sqrt;
+/// A module with a function to calculate the standard deviation of an array of `Numbers`
+#[path = "stddev/stddev.rs"]
+pub mod stddev;
+/// A module with a function to calculate the variance of an array of `Numbers`
+#[path = "variance/variance.rs"]
+pub mod variance;
\ No newline at end of file
@@ -0,0 +1,133 @@
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_random(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let distribution = inputs.first().ok_or("Could not get distribution")?
+        .as_str().ok_or("Could not get distribution string")?;
+    let parameters = inputs.get(1).ok_or("Could not get parameters")?
+        .as_array().ok_or("Could not get parameters array")?;
+    let (a, b) = match parameters.as_slice() {
+        [a, b] => (a.as_f64().ok_or("Could not get parameter")?,
+                   b.as_f64().ok_or("Could not get parameter")?),
+        _ => bail!("Expected two parameters, but got {}", parameters.len()),
+    };
+    let seed = match inputs.get(2).ok_or("Could not get seed")? {
+        Value::Null => random_seed()?,
+        seed => seed.as_u64().ok_or("The seed must be null or a positive integer")?,
+    };
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let value = match distribution {
+        "uniform" => match a.partial_cmp(&b) {
+            Some(Ordering::Less) if (b - a).is_finite() => rng.gen_range(a..b),
+            _ => bail!("Invalid uniform range [{}, {}], min must be less than max", a, b),
+        },
+        "normal" => {
+            if !(b >= 0.0 && a.is_finite() && b.is_finite()) {
+                bail!("Invalid normal mean {} and standard deviation {}", a, b);
+            }
+            // Box-Muller transform of two uniform samples, avoiding ln(0)
+            let u1: f64 = 1.0 - rng.gen::<f64>();
+            let u2: f64 = rng.gen();
+            a + b * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+        },
+        _ => bail!("Unknown distribution '{}', expected \"uniform\" or \"normal\"", distribution),
+    };
+
+    let mut output_map = serde_json::Map::new();
+    output_map.insert("value".into(), json!(value));
+    output_map.insert("seed".into(), json!(rng.gen::<u64>()));
+
+    Ok((Some(Value::Object(output_map)), RUN_AGAIN))
+}
+
+// Get a random seed from the randomly keyed hasher of the standard library
+#[cfg(not(target_arch = "wasm32"))]
+fn random_seed() -> Result<u64> {
+    use std::collections::hash_map::RandomState;
+    use std::hash::BuildHasher;
+
+    Ok(RandomState::new().hash_one(std::time::SystemTime::now()))
+}
+
+// There is no source of randomness available to WASM, so the native implementation must be used
+#[cfg(target_arch = "wasm32")]
+fn random_seed() -> Result<u64> {
+    bail!("A random seed is not available to WASM. Supply a seed or use the native flowstdlib")
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_random;
+
+    fn random(distribution: &str, parameters: Value, seed: Value) -> Value {
+        let (result, _) = inner_random(&[json!(distribution), parameters, seed])
+            .expect("_random() failed");
+        result.expect("Could not get output")
+    }
+
+    fn value(output: &Value) -> f64 {
+        output.get("value").and_then(Value::as_f64).expect("Could not get value")
+    }
+
+    #[test]
+    fn same_seed_same_value() {
+        assert_eq!(random("uniform", json!([0, 10]), json!(42)),
+                   random("uniform", json!([0, 10]), json!(42)));
+    }
+
+    #[test]
+    fn next_seed_different_value() {
+        let first = random("uniform", json!([0, 10]), json!(42));
+        let second = random("uniform", json!([0, 10]), first.get("seed").expect("No seed").clone());
+        assert_ne!(value(&first), value(&second));
+    }
+
+    #[test]
+    fn uniform_within_range() {
+        let mut seed = json!(1);
+        for _ in 0..100 {
+            let output = random("uniform", json!([-5, 5]), seed);
+            assert!((-5.0..5.0).contains(&value(&output)));
+            seed = output.get("seed").expect("No seed").clone();
+        }
+    }
+
+    #[test]
+    fn normal_has_mean() {
+        let mut seed = json!(1);
+        let mut sum = 0.0;
+        for _ in 0..1000 {
+            let output = random("normal", json!([10, 2]), seed);
+            sum += value(&output);
+            seed = output.get("seed").expect("No seed").clone();
+        }
+        assert!((sum / 1000.0 - 10.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn random_seed() {
+        let output = random("uniform", json!([0, 1]), json!(null));
+        assert!((0.0..1.0).contains(&value(&output)));
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        assert!(inner_random(&[json!("uniform"), json!([1, 1]), json!(1)]).is_err());
+        assert!(inner_random(&[json!("uniform"), json!([1]), json!(1)]).is_err());
+        assert!(inner_random(&[json!("normal"), json!([0, -1]), json!(1)]).is_err());
+        assert!(inner_random(&[json!("poisson"), json!([0, 1]), json!(1)]).is_err());
+        assert!(inner_random(&[json!("uniform"), json!([0, 1]), json!(-1)]).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_stddev(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    if let Some(array) = inputs.first().ok_or("Could not get numbers")?.as_array() {
+        let numbers = array.iter()
+            .map(|number| number.as_f64().ok_or("Could not get number"))
+            .collect::<std::result::Result<Vec<f64>, _>>()?;
+        if !numbers.is_empty() {
+            let count = numbers.len() as f64;
+            let mean = numbers.iter().sum::<f64>() / count;
+            let variance = numbers.iter().map(|number| (number - mean).powi(2)).sum::<f64>() / count;
+            value = Some(json!(variance.sqrt()));
+        }
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_stddev;
+
+    #[test]
+    fn stddev_of_numbers() {
+        let (result, _) = inner_stddev(&[json!([2, 4, 4, 4, 5, 5, 7, 9])])
+            .expect("_stddev() failed");
+        assert_eq!(result, Some(json!(2.0)));
+    }
+
+    #[test]
+    fn stddev_of_one() {
+        let (result, _) = inner_stddev(&[json!([42])]).expect("_stddev() failed");
+        assert_eq!(result, Some(json!(0.0)));
+    }
+
+    #[test]
+    fn stddev_of_empty_array_has_no_output() {
+        let (result, _) = inner_stddev(&[json!([])]).expect("_stddev() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn stddev_of_non_numbers() {
+        assert!(inner_stddev(&[json!([true])]).is_err());
+    }
+}
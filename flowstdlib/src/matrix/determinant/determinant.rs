@@ -0,0 +1,108 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_determinant(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let matrix = inputs.first().ok_or("Could not get matrix")?;
+    let determinant = bareiss_determinant(square_matrix(matrix)?);
+
+    // the determinant of a matrix of integers is an integer, calculated exactly
+    let integers = matrix.as_array().into_iter().flatten()
+        .filter_map(Value::as_array).flatten().all(Value::is_i64);
+    if integers {
+        #[allow(clippy::cast_possible_truncation)]
+        return Ok((Some(json!(determinant.round() as i64)), RUN_AGAIN));
+    }
+
+    Ok((Some(json!(determinant)), RUN_AGAIN))
+}
+
+// Get the rows of numbers of a square matrix
+fn square_matrix(matrix: &Value) -> Result<Vec<Vec<f64>>> {
+    let rows = matrix.as_array().ok_or("Could not get matrix")?;
+    rows.iter().map(|row| {
+        let row = row.as_array().ok_or("Could not get row")?;
+        if row.len() != rows.len() {
+            bail!("Matrix is not square");
+        }
+        row.iter().map(|element| element.as_f64().ok_or_else(|| "Could not get number".into()))
+            .collect()
+    }).collect()
+}
+
+// The fraction-free Bareiss algorithm, where all divisions are exact for a matrix of integers
+fn bareiss_determinant(mut rows: Vec<Vec<f64>>) -> f64 {
+    let mut sign = 1.0;
+    let mut previous_pivot = 1.0;
+
+    for k in 0..rows.len() {
+        // swap a row with a non-zero element in column k into the pivot row
+        match rows.iter().skip(k).position(|row| row.get(k).is_some_and(|element| *element != 0.0)) {
+            None => return 0.0,
+            Some(0) => {},
+            Some(offset) => {
+                rows.swap(k, k + offset);
+                sign = -sign;
+            }
+        }
+
+        let (top, bottom) = rows.split_at_mut(k + 1);
+        let Some(pivot_row) = top.last() else { return 0.0 };
+        let pivot = pivot_row.get(k).copied().unwrap_or(0.0);
+        for row in bottom {
+            let factor = row.get(k).copied().unwrap_or(0.0);
+            for (element, pivot_element) in row.iter_mut().zip(pivot_row).skip(k + 1) {
+                *element = (*element * pivot - factor * pivot_element) / previous_pivot;
+            }
+        }
+        previous_pivot = pivot;
+    }
+
+    sign * previous_pivot
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_determinant;
+
+    fn determinant(matrix: Value) -> Value {
+        let (result, _) = inner_determinant(&[matrix]).expect("_determinant() failed");
+        result.expect("Could not get the Value from the output")
+    }
+
+    #[test]
+    fn determinant_1x1() {
+        assert_eq!(determinant(json!([[7]])), json!(7));
+    }
+
+    #[test]
+    fn determinant_2x2() {
+        assert_eq!(determinant(json!([[1, 2], [3, 4]])), json!(-2));
+    }
+
+    #[test]
+    fn determinant_3x3_with_zero_pivot() {
+        assert_eq!(determinant(json!([[0, 2, 1], [3, 0, 4], [5, 6, 0]])), json!(58));
+    }
+
+    #[test]
+    fn determinant_singular() {
+        assert_eq!(determinant(json!([[1, 2, 3], [4, 5, 6], [7, 8, 9]])), json!(0));
+    }
+
+    #[test]
+    fn determinant_of_decimals() {
+        let value = determinant(json!([[0.5, 1.5], [2.0, 1.0]])).as_f64().expect("Not a number");
+        assert!((value + 2.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn determinant_not_square() {
+        assert!(inner_determinant(&[json!([[1, 2, 3], [4, 5, 6]])]).is_err());
+    }
+}
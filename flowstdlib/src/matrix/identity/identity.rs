@@ -0,0 +1,40 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_identity(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let size = inputs.first().ok_or("Could not get size")?.as_u64().ok_or("Could not get size")?;
+
+    let matrix: Vec<Vec<u64>> = (0..size)
+        .map(|row| (0..size).map(|column| u64::from(row == column)).collect())
+        .collect();
+
+    Ok((Some(json!(matrix)), RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_identity;
+
+    #[test]
+    fn identity_3x3() {
+        let (result, _) = inner_identity(&[json!(3)]).expect("_identity() failed");
+        assert_eq!(result, Some(json!([[1, 0, 0], [0, 1, 0], [0, 0, 1]])));
+    }
+
+    #[test]
+    fn identity_empty() {
+        let (result, _) = inner_identity(&[json!(0)]).expect("_identity() failed");
+        assert_eq!(result, Some(json!([])));
+    }
+
+    #[test]
+    fn identity_invalid_size() {
+        assert!(inner_identity(&[json!(-1)]).is_err());
+    }
+}
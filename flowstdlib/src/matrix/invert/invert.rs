@@ -0,0 +1,123 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::{bail, Result};
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_invert(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let matrix = square_matrix(inputs.first().ok_or("Could not get matrix")?)?;
+    Ok((Some(json!(gauss_jordan_inverse(matrix)?)), RUN_AGAIN))
+}
+
+// Get the rows of numbers of a square matrix
+fn square_matrix(matrix: &Value) -> Result<Vec<Vec<f64>>> {
+    let rows = matrix.as_array().ok_or("Could not get matrix")?;
+    rows.iter().map(|row| {
+        let row = row.as_array().ok_or("Could not get row")?;
+        if row.len() != rows.len() {
+            bail!("Matrix is not square");
+        }
+        row.iter().map(|element| element.as_f64().ok_or_else(|| "Could not get number".into()))
+            .collect()
+    }).collect()
+}
+
+// Reduce the matrix to the identity matrix, applying the same row operations to an identity matrix
+// alongside it, which then becomes the inverse
+fn gauss_jordan_inverse(mut rows: Vec<Vec<f64>>) -> Result<Vec<Vec<f64>>> {
+    let size = rows.len();
+    let largest = rows.iter().flatten().fold(0.0_f64, |largest, element| largest.max(element.abs()));
+    let singular_threshold = largest * size as f64 * f64::EPSILON;
+
+    for (index, row) in rows.iter_mut().enumerate() {
+        row.extend((0..size).map(|column| if column == index { 1.0 } else { 0.0 }));
+    }
+
+    for k in 0..size {
+        // use the row with the largest element in column k as the pivot row, for numerical stability
+        let column_k = |row: &Vec<f64>| row.get(k).copied().unwrap_or(0.0).abs();
+        let offset = rows.iter().skip(k).enumerate()
+            .max_by(|(_, a), (_, b)| column_k(a).total_cmp(&column_k(b)))
+            .map(|(offset, _)| offset).ok_or("Could not get pivot row")?;
+        rows.swap(k, k + offset);
+
+        let pivot_row = rows.get(k).ok_or("Could not get pivot row")?.clone();
+        let pivot = pivot_row.get(k).copied().unwrap_or(0.0);
+        if pivot.abs() <= singular_threshold {
+            bail!("Matrix is singular, so it cannot be inverted");
+        }
+
+        for (index, row) in rows.iter_mut().enumerate() {
+            if index == k {
+                row.iter_mut().for_each(|element| *element /= pivot);
+            } else {
+                let factor = row.get(k).copied().unwrap_or(0.0) / pivot;
+                for (element, pivot_element) in row.iter_mut().zip(&pivot_row) {
+                    *element -= factor * pivot_element;
+                }
+            }
+        }
+    }
+
+    Ok(rows.into_iter().map(|row| row.into_iter().skip(size).collect()).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::inner_invert;
+
+    fn assert_matrix_eq(actual: &Value, expected: &[Vec<f64>]) {
+        let rows = actual.as_array().expect("Could not get rows");
+        assert_eq!(rows.len(), expected.len());
+        for (row, expected_row) in rows.iter().zip(expected) {
+            let row = row.as_array().expect("Could not get row");
+            assert_eq!(row.len(), expected_row.len());
+            for (element, expected_element) in row.iter().zip(expected_row) {
+                let element = element.as_f64().expect("Could not get number");
+                assert!((element - expected_element).abs() < 1e-12, "{element} != {expected_element}");
+            }
+        }
+    }
+
+    fn invert(matrix: Value) -> Value {
+        let (result, _) = inner_invert(&[matrix]).expect("_invert() failed");
+        result.expect("Could not get the Value from the output")
+    }
+
+    #[test]
+    fn invert_identity() {
+        assert_matrix_eq(&invert(json!([[1, 0], [0, 1]])), &[vec![1.0, 0.0], vec![0.0, 1.0]]);
+    }
+
+    #[test]
+    fn invert_2x2() {
+        assert_matrix_eq(&invert(json!([[4, 7], [2, 6]])), &[vec![0.6, -0.7], vec![-0.2, 0.4]]);
+    }
+
+    #[test]
+    fn invert_3x3_with_zero_pivot() {
+        assert_matrix_eq(&invert(json!([[0, 1, 0], [0, 0, 1], [1, 0, 0]])),
+                         &[vec![0.0, 0.0, 1.0], vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn invert_twice_is_identity() {
+        let matrix = json!([[2, -1, 0], [-1, 2, -1], [0, -1, 2]]);
+        assert_matrix_eq(&invert(invert(matrix)),
+                         &[vec![2.0, -1.0, 0.0], vec![-1.0, 2.0, -1.0], vec![0.0, -1.0, 2.0]]);
+    }
+
+    #[test]
+    fn invert_singular() {
+        assert!(inner_invert(&[json!([[1, 2], [2, 4]])]).is_err());
+        assert!(inner_invert(&[json!([[1, 2, 3], [4, 5, 6], [7, 8, 9]])]).is_err());
+    }
+
+    #[test]
+    fn invert_not_square() {
+        assert!(inner_invert(&[json!([[1, 2, 3], [4, 5, 6]])]).is_err());
+    }
+}
@@ -4,6 +4,28 @@
 /// A flow to multiply two matrices
 pub mod multiply;
 
+/// A flow to generate a square matrix with a scalar on the diagonal
+pub mod scalar_matrix;
+
+/// A flow to divide a matrix by a scalar
+pub mod scalar_divide;
+
+/// A module with a function to calculate the determinant of a square Matrix
+#[path = "determinant/determinant.rs"]
+pub mod determinant;
+
+/// A module with a function to generate an identity Matrix
+#[path = "identity/identity.rs"]
+pub mod identity;
+
+/// A module with a function to invert a square Matrix
+#[path = "invert/invert.rs"]
+pub mod invert;
+
+/// A module with a function to multiply a Matrix by a scalar
+#[path = "scalar_multiply/scalar_multiply.rs"]
+pub mod scalar_multiply;
+
 /// A Module that duplicates rows in a matrix
 #[path = "duplicate_rows/duplicate_rows.rs"]
 pub mod duplicate_rows;
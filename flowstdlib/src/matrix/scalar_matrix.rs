@@ -0,0 +1,40 @@
+#[doc = include_str!("scalar_matrix.md")]
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::Write;
+
+    use tempfile::tempdir;
+
+    use super::super::super::test::execute_flow;
+
+    #[test]
+    fn test_scalar_matrix_flow() {
+        let flow = "\
+flow = \"scalar_matrix_test\"
+
+[[process]]
+source = \"lib://flowstdlib/matrix/scalar_matrix\"
+input.size = { once = 2 }
+input.scalar = { once = 3 }
+
+[[process]]
+source = \"context://stdio/stdout\"
+
+[[connection]]
+from = \"scalar_matrix/matrix\"
+to = \"stdout\"
+";
+
+        let temp_dir = tempdir().expect("Could not create temporary directory")
+            .into_path();
+        let flow_filename = temp_dir.join("scalar_matrix_test.toml");
+        let mut flow_file = File::create(&flow_filename)
+            .expect("Could not create lib manifest file");
+        flow_file.write_all(flow.as_bytes())
+            .expect("Could not write data bytes to created flow file");
+
+        let stdout = execute_flow(&flow_filename);
+        assert_eq!(stdout, "[[3,0],[0,3]]\n".to_string());
+    }
+}
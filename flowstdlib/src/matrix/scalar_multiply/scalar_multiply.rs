@@ -0,0 +1,56 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_scalar_multiply(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let matrix = inputs.first().ok_or("Could not get matrix")?.as_array().ok_or("Could not get matrix")?;
+    let scalar = inputs.get(1).ok_or("Could not get scalar")?;
+
+    let product = matrix.iter().map(|row| {
+        row.as_array().ok_or("Could not get row")?.iter()
+            .map(|element| multiply(element, scalar))
+            .collect::<Result<Vec<Value>>>()
+    }).collect::<Result<Vec<Vec<Value>>>>()?;
+
+    Ok((Some(json!(product)), RUN_AGAIN))
+}
+
+// Multiply two numbers, keeping the product an integer if both numbers are and it does not overflow
+fn multiply(a: &Value, b: &Value) -> Result<Value> {
+    if let Some(product) = a.as_i64().zip(b.as_i64()).and_then(|(a, b)| a.checked_mul(b)) {
+        return Ok(json!(product));
+    }
+    let a = a.as_f64().ok_or("Could not get number")?;
+    let b = b.as_f64().ok_or("Could not get number")?;
+    Ok(json!(a * b))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_scalar_multiply;
+
+    #[test]
+    fn scalar_multiply_integers() {
+        let (result, _) = inner_scalar_multiply(&[json!([[1, 2], [3, 4]]), json!(3)])
+            .expect("_scalar_multiply() failed");
+        assert_eq!(result, Some(json!([[3, 6], [9, 12]])));
+    }
+
+    #[test]
+    fn scalar_multiply_decimal() {
+        let (result, _) = inner_scalar_multiply(&[json!([[1, 2], [3, 4]]), json!(0.5)])
+            .expect("_scalar_multiply() failed");
+        assert_eq!(result, Some(json!([[0.5, 1.0], [1.5, 2.0]])));
+    }
+
+    #[test]
+    fn scalar_multiply_not_numbers() {
+        assert!(inner_scalar_multiply(&[json!([[1, "a"]]), json!(2)]).is_err());
+        assert!(inner_scalar_multiply(&[json!([[1, 2]]), json!("a")]).is_err());
+    }
+}
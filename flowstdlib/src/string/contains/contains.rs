@@ -0,0 +1,54 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_contains(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    let substring = inputs.get(1).ok_or("Could not get substring")?
+        .as_str().ok_or("Could not get substring")?;
+
+    if let Some(string) = input.as_str() {
+        value = Some(json!(string.contains(substring)));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_contains;
+
+    #[test]
+    fn contains_substring() {
+        let (result, _) = inner_contains(&[json!("hello world"), json!("o w")])
+            .expect("_contains() failed");
+        assert_eq!(result, Some(json!(true)));
+    }
+
+    #[test]
+    fn does_not_contain_substring() {
+        let (result, _) = inner_contains(&[json!("hello world"), json!("World")])
+            .expect("_contains() failed");
+        assert_eq!(result, Some(json!(false)));
+    }
+
+    #[test]
+    fn contains_empty_string() {
+        let (result, _) = inner_contains(&[json!(""), json!("")]).expect("_contains() failed");
+        assert_eq!(result, Some(json!(true)));
+    }
+
+    #[test]
+    fn contains_non_string_has_no_output() {
+        let (result, _) = inner_contains(&[json!(null), json!("a")])
+            .expect("_contains() failed");
+        assert_eq!(result, None);
+    }
+}
@@ -0,0 +1,61 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_join(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get strings")?;
+    let separator = inputs.get(1).ok_or("Could not get separator")?
+        .as_str().ok_or("Could not get separator")?;
+
+    if let Some(array) = input.as_array() {
+        let strings = array.iter()
+            .map(|string| string.as_str().ok_or("Could not get string"))
+            .collect::<std::result::Result<Vec<&str>, _>>()?;
+        value = Some(json!(strings.join(separator)));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_join;
+
+    #[test]
+    fn join_with_separator() {
+        let (result, _) = inner_join(&[json!(["the", "quick", "brown", "fox"]), json!(" ")])
+            .expect("_join() failed");
+        assert_eq!(result, Some(json!("the quick brown fox")));
+    }
+
+    #[test]
+    fn join_with_empty_separator() {
+        let (result, _) = inner_join(&[json!(["a", "b", "c"]), json!("")])
+            .expect("_join() failed");
+        assert_eq!(result, Some(json!("abc")));
+    }
+
+    #[test]
+    fn join_empty_array() {
+        let (result, _) = inner_join(&[json!([]), json!(",")]).expect("_join() failed");
+        assert_eq!(result, Some(json!("")));
+    }
+
+    #[test]
+    fn join_non_array_has_no_output() {
+        let (result, _) = inner_join(&[json!(null), json!(",")]).expect("_join() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn join_non_strings() {
+        assert!(inner_join(&[json!(["a", 1]), json!(",")]).is_err());
+    }
+}
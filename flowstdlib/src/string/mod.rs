@@ -0,0 +1,34 @@
+//! functions for manipulating strings
+//! ## String (//flowstdlib/string)
+//! Functions for the manipulation of `String`s.
+
+/// A module to determine if a `String` contains another `String`
+#[path = "contains/contains.rs"]
+pub mod contains;
+/// A module to join an array of `String`s with a separator
+#[path = "join/join.rs"]
+pub mod join;
+/// A module to find all matches of a regular expression within a `String`
+#[path = "regex_match/regex_match.rs"]
+pub mod regex_match;
+/// A module to find the capture groups of the first match of a regular expression in a `String`
+#[path = "regex_capture/regex_capture.rs"]
+pub mod regex_capture;
+/// A module to replace matches of a regular expression within a `String`
+#[path = "regex_replace/regex_replace.rs"]
+pub mod regex_replace;
+/// A module to replace occurrences of a `String` within a `String`
+#[path = "replace/replace.rs"]
+pub mod replace;
+/// A module to split a `String` into an array of `String`s using a separator
+#[path = "split/split.rs"]
+pub mod split;
+/// A module to convert a `String` to lower case
+#[path = "to_lower/to_lower.rs"]
+pub mod to_lower;
+/// A module to convert a `String` to upper case
+#[path = "to_upper/to_upper.rs"]
+pub mod to_upper;
+/// A module to remove leading and trailing whitespace from a `String`
+#[path = "trim/trim.rs"]
+pub mod trim;
@@ -0,0 +1,75 @@
+use regex::Regex;
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_regex_capture(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    let pattern = inputs.get(1).ok_or("Could not get pattern")?
+        .as_str().ok_or("Could not get pattern")?;
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {e}"))?;
+
+    if let Some(string) = input.as_str() {
+        let captures: Vec<Option<&str>> = match regex.captures(string) {
+            Some(captures) => captures.iter()
+                .map(|group| group.map(|found| found.as_str())).collect(),
+            None => vec![],
+        };
+        value = Some(json!(captures));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_regex_capture;
+
+    #[test]
+    fn capture_groups() {
+        let (result, _) = inner_regex_capture(&[json!("Date: 2023-07-14"),
+                                                json!("([0-9]{4})-([0-9]{2})-([0-9]{2})")])
+            .expect("_regex_capture() failed");
+        assert_eq!(result, Some(json!(["2023-07-14", "2023", "07", "14"])));
+    }
+
+    #[test]
+    fn capture_first_match_only() {
+        let (result, _) = inner_regex_capture(&[json!("a1 b2"), json!("([a-z])([0-9])")])
+            .expect("_regex_capture() failed");
+        assert_eq!(result, Some(json!(["a1", "a", "1"])));
+    }
+
+    #[test]
+    fn unmatched_group_is_null() {
+        let (result, _) = inner_regex_capture(&[json!("ab"), json!("a(x)?b")])
+            .expect("_regex_capture() failed");
+        assert_eq!(result, Some(json!(["ab", null])));
+    }
+
+    #[test]
+    fn no_match() {
+        let (result, _) = inner_regex_capture(&[json!("abc"), json!("([0-9])")])
+            .expect("_regex_capture() failed");
+        assert_eq!(result, Some(json!([])));
+    }
+
+    #[test]
+    fn capture_non_string_has_no_output() {
+        let (result, _) = inner_regex_capture(&[json!(1), json!("(a)")])
+            .expect("_regex_capture() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(inner_regex_capture(&[json!("abc"), json!("[a")]).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use regex::Regex;
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_regex_match(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    let pattern = inputs.get(1).ok_or("Could not get pattern")?
+        .as_str().ok_or("Could not get pattern")?;
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {e}"))?;
+
+    if let Some(string) = input.as_str() {
+        let matches: Vec<&str> = regex.find_iter(string).map(|found| found.as_str()).collect();
+        value = Some(json!(matches));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_regex_match;
+
+    #[test]
+    fn match_all_occurrences() {
+        let (result, _) = inner_regex_match(&[json!("a1 b22 c333"), json!("[0-9]+")])
+            .expect("_regex_match() failed");
+        assert_eq!(result, Some(json!(["1", "22", "333"])));
+    }
+
+    #[test]
+    fn no_matches() {
+        let (result, _) = inner_regex_match(&[json!("abc"), json!("[0-9]+")])
+            .expect("_regex_match() failed");
+        assert_eq!(result, Some(json!([])));
+    }
+
+    #[test]
+    fn match_non_string_has_no_output() {
+        let (result, _) = inner_regex_match(&[json!(null), json!("a")])
+            .expect("_regex_match() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(inner_regex_match(&[json!("abc"), json!("(a")]).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use regex::Regex;
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_regex_replace(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    let pattern = inputs.get(1).ok_or("Could not get pattern")?
+        .as_str().ok_or("Could not get pattern")?;
+    let replacement = inputs.get(2).ok_or("Could not get replacement")?
+        .as_str().ok_or("Could not get replacement")?;
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {e}"))?;
+
+    if let Some(string) = input.as_str() {
+        value = Some(json!(regex.replace_all(string, replacement)));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_regex_replace;
+
+    #[test]
+    fn replace_all_matches() {
+        let (result, _) = inner_regex_replace(&[json!("a1 b22 c333"), json!("[0-9]+"),
+                                                json!("#")])
+            .expect("_regex_replace() failed");
+        assert_eq!(result, Some(json!("a# b# c#")));
+    }
+
+    #[test]
+    fn replace_with_capture_groups() {
+        let (result, _) = inner_regex_replace(&[json!("2023-07-14"),
+                                                json!("(?<y>[0-9]{4})-([0-9]{2})-([0-9]{2})"),
+                                                json!("$3/$2/${y}")])
+            .expect("_regex_replace() failed");
+        assert_eq!(result, Some(json!("14/07/2023")));
+    }
+
+    #[test]
+    fn replace_non_string_has_no_output() {
+        let (result, _) = inner_regex_replace(&[json!(null), json!("a"), json!("b")])
+            .expect("_regex_replace() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn replace_missing_input() {
+        assert!(inner_regex_replace(&[json!("abc"), json!("b")]).is_err());
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(inner_regex_replace(&[json!("abc"), json!("(a"), json!("b")]).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_replace(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    let from = inputs.get(1).ok_or("Could not get from")?
+        .as_str().ok_or("Could not get from")?;
+    let to = inputs.get(2).ok_or("Could not get to")?
+        .as_str().ok_or("Could not get to")?;
+
+    if let Some(string) = input.as_str() {
+        if from.is_empty() {
+            value = Some(json!(string));
+        } else {
+            value = Some(json!(string.replace(from, to)));
+        }
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_replace;
+
+    #[test]
+    fn replace_all_occurrences() {
+        let (result, _) = inner_replace(&[json!("one fish two fish"), json!("fish"),
+                                          json!("cat")]).expect("_replace() failed");
+        assert_eq!(result, Some(json!("one cat two cat")));
+    }
+
+    #[test]
+    fn replace_with_empty_string() {
+        let (result, _) = inner_replace(&[json!("a-b-c"), json!("-"), json!("")])
+            .expect("_replace() failed");
+        assert_eq!(result, Some(json!("abc")));
+    }
+
+    #[test]
+    fn replace_empty_string() {
+        let (result, _) = inner_replace(&[json!("abc"), json!(""), json!("-")])
+            .expect("_replace() failed");
+        assert_eq!(result, Some(json!("abc")));
+    }
+
+    #[test]
+    fn replace_non_string_has_no_output() {
+        let (result, _) = inner_replace(&[json!(null), json!("b"), json!("c")])
+            .expect("_replace() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn replace_missing_input() {
+        assert!(inner_replace(&[json!("abc"), json!("b")]).is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_split(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    let separator = inputs.get(1).ok_or("Could not get separator")?
+        .as_str().ok_or("Could not get separator")?;
+
+    if let Some(string) = input.as_str() {
+        let parts: Vec<String> = if separator.is_empty() {
+            string.chars().map(String::from).collect()
+        } else {
+            string.split(separator).map(String::from).collect()
+        };
+        value = Some(json!(parts));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_split;
+
+    #[test]
+    fn split_on_separator() {
+        let (result, _) = inner_split(&[json!("the quick brown fox"), json!(" ")])
+            .expect("_split() failed");
+        assert_eq!(result, Some(json!(["the", "quick", "brown", "fox"])));
+    }
+
+    #[test]
+    fn split_keeps_empty_parts() {
+        let (result, _) = inner_split(&[json!(",a,,b"), json!(",")]).expect("_split() failed");
+        assert_eq!(result, Some(json!(["", "a", "", "b"])));
+    }
+
+    #[test]
+    fn split_without_separator_present() {
+        let (result, _) = inner_split(&[json!("hello"), json!(",")]).expect("_split() failed");
+        assert_eq!(result, Some(json!(["hello"])));
+    }
+
+    #[test]
+    fn split_into_characters() {
+        let (result, _) = inner_split(&[json!("héllo"), json!("")]).expect("_split() failed");
+        assert_eq!(result, Some(json!(["h", "é", "l", "l", "o"])));
+    }
+
+    #[test]
+    fn split_non_string_has_no_output() {
+        let (result, _) = inner_split(&[json!(42), json!(",")]).expect("_split() failed");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn split_non_string_separator() {
+        assert!(inner_split(&[json!("a,b"), json!(1)]).is_err());
+    }
+}
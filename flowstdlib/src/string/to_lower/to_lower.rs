@@ -0,0 +1,36 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_to_lower(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    if let Some(string) = input.as_str() {
+        value = Some(json!(string.to_lowercase()));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_to_lower;
+
+    #[test]
+    fn to_lower_case() {
+        let (result, _) = inner_to_lower(&[json!("Hello WORLD")]).expect("_to_lower() failed");
+        assert_eq!(result, Some(json!("hello world")));
+    }
+
+    #[test]
+    fn to_lower_non_string_has_no_output() {
+        let (result, _) = inner_to_lower(&[json!(true)]).expect("_to_lower() failed");
+        assert_eq!(result, None);
+    }
+}
@@ -0,0 +1,36 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_to_upper(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    if let Some(string) = input.as_str() {
+        value = Some(json!(string.to_uppercase()));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_to_upper;
+
+    #[test]
+    fn to_upper_case() {
+        let (result, _) = inner_to_upper(&[json!("hello World")]).expect("_to_upper() failed");
+        assert_eq!(result, Some(json!("HELLO WORLD")));
+    }
+
+    #[test]
+    fn to_upper_non_string_has_no_output() {
+        let (result, _) = inner_to_upper(&[json!(true)]).expect("_to_upper() failed");
+        assert_eq!(result, None);
+    }
+}
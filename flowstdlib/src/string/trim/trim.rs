@@ -0,0 +1,42 @@
+use serde_json::{json, Value};
+
+use flowcore::{RUN_AGAIN, RunAgain};
+use flowcore::errors::Result;
+use flowmacro::flow_function;
+
+#[flow_function]
+fn inner_trim(inputs: &[Value]) -> Result<(Option<Value>, RunAgain)> {
+    let mut value = None;
+
+    let input = inputs.first().ok_or("Could not get string")?;
+    if let Some(string) = input.as_str() {
+        value = Some(json!(string.trim()));
+    }
+
+    Ok((value, RUN_AGAIN))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::inner_trim;
+
+    #[test]
+    fn trim_whitespace() {
+        let (result, _) = inner_trim(&[json!(" \t hello world\n ")]).expect("_trim() failed");
+        assert_eq!(result, Some(json!("hello world")));
+    }
+
+    #[test]
+    fn trim_only_whitespace() {
+        let (result, _) = inner_trim(&[json!("   ")]).expect("_trim() failed");
+        assert_eq!(result, Some(json!("")));
+    }
+
+    #[test]
+    fn trim_non_string_has_no_output() {
+        let (result, _) = inner_trim(&[json!(42)]).expect("_trim() failed");
+        assert_eq!(result, None);
+    }
+}
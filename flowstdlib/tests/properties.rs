@@ -0,0 +1,228 @@
+#![allow(missing_docs)]
+
+//! Property based tests of the `flowstdlib` function implementations, feeding them randomly
+//! generated inputs and checking invariants of their results
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use url::Url;
+
+use flowcore::Implementation;
+use flowcore::model::lib_manifest::ImplementationLocator::Native;
+
+// Get the native implementation of the function in flowstdlib at `path`
+fn implementation(path: &str) -> Arc<dyn Implementation> {
+    let manifest = flowstdlib::manifest::get().expect("Could not get flowstdlib manifest");
+    let url = Url::parse(&format!("lib://flowstdlib/{path}")).expect("Could not parse Url");
+    match manifest.locators.get(&url) {
+        Some(Native(implementation)) => implementation.clone(),
+        _ => panic!("No native implementation of '{url}' found"),
+    }
+}
+
+// Run the function at `path` with `inputs`, returning its output value
+fn run(path: &str, inputs: &[Value]) -> Option<Value> {
+    implementation(path).run(inputs)
+        .unwrap_or_else(|e| panic!("Running '{path}' failed: {e}")).0
+}
+
+// Small arbitrary json values of all types, including nested arrays and objects
+fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        (-100i64..100).prop_map(Value::from),
+        // decimals short enough to be parsed back exactly from their string representation
+        (-10_000i64..10_000).prop_map(|hundredths| Value::from(hundredths as f64 / 100.0)),
+        "[a-z ]{0,8}".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| prop_oneof![
+        prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+        prop::collection::btree_map("[a-z]{1,4}", inner, 0..4)
+            .prop_map(|map| Value::Object(map.into_iter().collect())),
+    ])
+}
+
+// Rectangular matrices of numbers, with at least one row and one column
+fn matrix() -> impl Strategy<Value = Vec<Vec<i64>>> {
+    (1usize..5, 1usize..5).prop_flat_map(|(rows, cols)|
+        prop::collection::vec(prop::collection::vec(any::<i64>(), cols), rows))
+}
+
+proptest! {
+    #[test]
+    fn no_function_panics(inputs in prop::collection::vec(json_value(), 0..4)) {
+        let manifest = flowstdlib::manifest::get().expect("Could not get flowstdlib manifest");
+        for (url, locator) in &manifest.locators {
+            if let Native(implementation) = locator {
+                let result = panic::catch_unwind(AssertUnwindSafe(||
+                    implementation.run(&inputs)));
+                prop_assert!(result.is_ok(), "'{}' panicked with inputs {:?}", url, inputs);
+            }
+        }
+    }
+
+    #[test]
+    fn add_is_commutative(a in any::<i64>(), b in any::<i64>()) {
+        let sum = run("math/add", &[json!(a), json!(b)]);
+        prop_assert_eq!(&sum, &run("math/add", &[json!(b), json!(a)]));
+        prop_assert_eq!(sum, a.checked_add(b).map(|sum| json!(sum)));
+    }
+
+    #[test]
+    fn multiply_is_commutative(a in 0..u64::from(u32::MAX), b in 0..u64::from(u32::MAX)) {
+        let product = run("math/multiply", &[json!(a), json!(b)]);
+        prop_assert_eq!(&product, &run("math/multiply", &[json!(b), json!(a)]));
+        prop_assert_eq!(product, Some(json!(a * b)));
+    }
+
+    #[test]
+    fn subtract_reverses_add(a in -1_000_000i64..1_000_000, b in -1_000_000i64..1_000_000) {
+        let sum = run("math/add", &[json!(a), json!(b)]).expect("No sum");
+        prop_assert_eq!(run("math/subtract", &[sum, json!(b)]), Some(json!(a)));
+    }
+
+    #[test]
+    fn compare_is_consistent(a in any::<i64>(), b in any::<i64>()) {
+        let comparison = run("math/compare", &[json!(a), json!(b)]).expect("No comparison");
+        prop_assert_eq!(comparison, json!({"equal": a == b, "ne": a != b, "lt": a < b,
+            "gt": a > b, "lte": a <= b, "gte": a >= b}));
+    }
+
+    #[test]
+    fn sort_output_is_ordered(numbers in prop::collection::vec(any::<i64>(), 0..20)) {
+        let sorted = run("data/sort", &[json!(numbers), json!(false), json!("")])
+            .expect("No sorted output");
+        let sorted: Vec<i64> = serde_json::from_value(sorted).expect("Not an array of numbers");
+        prop_assert!(sorted.windows(2).all(|pair| pair.first() <= pair.get(1)));
+        let mut expected = numbers.clone();
+        expected.sort_unstable();
+        prop_assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn statistics_are_within_min_max(numbers in prop::collection::vec(-1_000i64..1_000, 1..20)) {
+        let min_max = run("math/min_max", &[json!(numbers)]).expect("No min_max output");
+        let min = min_max.get("min").and_then(Value::as_f64).expect("No min");
+        let max = min_max.get("max").and_then(Value::as_f64).expect("No max");
+        for statistic in ["math/mean", "math/median"] {
+            let value = run(statistic, &[json!(numbers)]).and_then(|value| value.as_f64())
+                .expect("No output");
+            prop_assert!(min <= value && value <= max, "{} {} not within [{}, {}]",
+                statistic, value, min, max);
+        }
+        let variance = run("math/variance", &[json!(numbers)]).and_then(|value| value.as_f64())
+            .expect("No variance");
+        let stddev = run("math/stddev", &[json!(numbers)]).and_then(|value| value.as_f64())
+            .expect("No stddev");
+        prop_assert!(variance >= 0.0);
+        prop_assert!((stddev * stddev - variance).abs() <= 1e-6 * variance.max(1.0));
+    }
+
+    #[test]
+    fn sort_descending_reverses_ascending(numbers in prop::collection::vec(-100i64..100, 0..20)) {
+        // only distinct numbers, as equal ones keep their order in either direction
+        let unique = run("data/unique", &[json!(numbers)]).expect("No unique output");
+        let ascending = run("data/sort", &[unique.clone(), json!(false), json!("")])
+            .expect("No sorted output");
+        let descending = run("data/sort", &[unique, json!(true), json!("")])
+            .expect("No sorted output");
+        let mut reversed: Vec<Value> = serde_json::from_value(descending)
+            .expect("Not an array");
+        reversed.reverse();
+        prop_assert_eq!(json!(reversed), ascending);
+    }
+
+    #[test]
+    fn unique_has_no_repeats(values in prop::collection::vec(json_value(), 0..10)) {
+        let unique = run("data/unique", &[json!(values)]).expect("No unique output");
+        let unique: Vec<Value> = serde_json::from_value(unique).expect("Not an array");
+        prop_assert!(unique.iter().enumerate()
+            .all(|(i, value)| !unique.iter().skip(i + 1).any(|other| other == value)));
+        prop_assert!(values.iter().all(|value| unique.contains(value)));
+    }
+
+    #[test]
+    fn random_uniform_is_within_range(seed in any::<u64>(), min in -1_000i64..1_000, size in 1i64..1_000) {
+        let output = run("math/random", &[json!("uniform"), json!([min, min + size]), json!(seed)])
+            .expect("No random output");
+        let value = output.get("value").and_then(Value::as_f64).expect("No random value");
+        prop_assert!(value >= min as f64 && value < (min + size) as f64);
+        prop_assert_eq!(run("math/random", &[json!("uniform"), json!([min, min + size]), json!(seed)]),
+                        Some(output));
+    }
+
+    #[test]
+    fn reverse_twice_is_identity(string in ".{0,20}") {
+        let reversed = run("fmt/reverse", &[json!(string)]).expect("No reversed output");
+        let reversed = reversed.get("reversed").expect("No reversed string").clone();
+        let twice = run("fmt/reverse", &[reversed]).expect("No reversed output");
+        prop_assert_eq!(twice.get("reversed"), Some(&json!(string)));
+    }
+
+    #[test]
+    fn append_concatenates(a in ".{0,10}", b in ".{0,10}") {
+        prop_assert_eq!(run("data/append", &[json!(a), json!(b)]), Some(json!(format!("{a}{b}"))));
+    }
+
+    #[test]
+    fn join_of_split_is_identity(string in "[a-c,]{0,20}") {
+        let parts = run("string/split", &[json!(string), json!(",")]).expect("No split output");
+        prop_assert_eq!(run("string/join", &[parts, json!(",")]), Some(json!(string)));
+    }
+
+    #[test]
+    fn to_lower_of_to_upper_is_lower(string in "[a-zA-Z ]{0,20}") {
+        let upper = run("string/to_upper", &[json!(string)]).expect("No upper case output");
+        prop_assert_eq!(run("string/to_lower", &[upper]), Some(json!(string.to_lowercase())));
+    }
+
+    #[test]
+    fn regex_replace_of_literal_is_replace(string in "[a-c]{0,20}", from in "[a-c]{1,2}",
+                                           to in "[a-c]{0,2}") {
+        prop_assert_eq!(run("string/regex_replace", &[json!(string), json!(from), json!(to)]),
+                        run("string/replace", &[json!(string), json!(from), json!(to)]));
+    }
+
+    #[test]
+    fn duplicate_repeats_value(value in json_value(), factor in 0i64..20) {
+        let duplicates = run("data/duplicate", &[value.clone(), json!(factor)])
+            .expect("No duplicated output");
+        let duplicates = duplicates.as_array().expect("Not an array");
+        prop_assert_eq!(duplicates.len() as i64, factor);
+        prop_assert!(duplicates.iter().all(|duplicate| duplicate == &value));
+    }
+
+    #[test]
+    fn transpose_twice_is_identity(matrix in matrix()) {
+        let transposed = run("matrix/transpose", &[json!(matrix)]).expect("No transposed output");
+        let transposed = transposed.get("matrix").expect("No transposed matrix").clone();
+        let twice = run("matrix/transpose", &[transposed]).expect("No transposed output");
+        prop_assert_eq!(twice.get("matrix"), Some(&json!(matrix)));
+    }
+
+    #[test]
+    fn to_json_of_to_string_is_identity(value in json_value()) {
+        let string = run("fmt/to_string", std::slice::from_ref(&value)).expect("No string output");
+        prop_assert_eq!(run("fmt/to_json", &[string]), Some(value));
+    }
+
+    #[test]
+    fn diff_of_add_duration_is_duration(epoch in -10_000_000_000_000i64..10_000_000_000_000,
+                                        duration in -10_000_000_000_000i64..10_000_000_000_000) {
+        let sum = run("datetime/add_duration", &[json!(epoch), json!(duration)]).expect("No sum output");
+        let iso = sum.get("iso").expect("No iso output").clone();
+        prop_assert_eq!(run("datetime/diff", &[json!(epoch), iso]), Some(json!(duration)));
+    }
+
+    // a leading byte order mark is skipped when parsing, so none are generated
+    #[test]
+    fn csv_parse_of_csv_serialize_is_identity(records in (1usize..4).prop_flat_map(|width|
+        prop::collection::vec(prop::collection::vec("[^\u{feff}]{0,10}", width), 0..5))) {
+        let text = run("fmt/csv_serialize", &[json!(records), json!(false)]).expect("No CSV output");
+        prop_assert_eq!(run("fmt/csv_parse", &[text, json!(false)]), Some(json!(records)));
+    }
+}